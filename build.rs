@@ -0,0 +1,5 @@
+fn main() {
+    // The gRPC services are generated from proto/ only when they're built
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/asanadw.proto").expect("compile proto/asanadw.proto");
+}
@@ -0,0 +1,297 @@
+//! Python bindings for the asanadw warehouse.
+//!
+//! Read-only access to a synced database: SQL, task queries, search, and
+//! metrics. Results come back as plain dicts and lists, so they drop
+//! straight into `pandas.DataFrame(...)`. Syncing stays in the CLI.
+
+use std::future::Future;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+
+use asanadw_core::{Database, Period, QueryBuilder, SearchHitType, SearchOptions};
+
+create_exception!(asanadw, AsanaDWError, PyException);
+
+fn to_py_err(e: asanadw_core::Error) -> PyErr {
+    AsanaDWError::new_err(e.to_string())
+}
+
+/// Convert any serializable result into native Python objects.
+fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_value(value).map_err(|e| AsanaDWError::new_err(e.to_string()))?;
+    json_to_py(py, &json)
+}
+
+fn json_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<Py<PyAny>> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.into_pyobject(py)?.into_any().unbind(),
+            (None, Some(f)) => f.into_pyobject(py)?.into_any().unbind(),
+            (None, None) => py.None(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any().unbind(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_any().unbind()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(k, json_to_py(py, v)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    })
+}
+
+/// A handle on an asanadw SQLite warehouse.
+#[pyclass(module = "asanadw")]
+struct Warehouse {
+    runtime: tokio::runtime::Runtime,
+    db: Database,
+}
+
+impl Warehouse {
+    /// Run a library future to completion with the GIL released.
+    fn block_on<F, T>(&self, py: Python<'_>, fut: F) -> PyResult<T>
+    where
+        F: Future<Output = asanadw_core::Result<T>> + Send,
+        T: Send,
+    {
+        py.detach(|| self.runtime.block_on(fut)).map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl Warehouse {
+    /// Open the warehouse at `path`, or `~/.asanadw/asanadw.db` by default,
+    /// read-only. The database must already exist.
+    #[new]
+    #[pyo3(signature = (path=None))]
+    fn new(py: Python<'_>, path: Option<String>) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| AsanaDWError::new_err(e.to_string()))?;
+        let db = py
+            .detach(|| {
+                runtime.block_on(async {
+                    let path = match path {
+                        Some(p) => std::path::PathBuf::from(p),
+                        None => Database::default_path()?,
+                    };
+                    Database::open_read_only(path).await
+                })
+            })
+            .map_err(to_py_err)?;
+        Ok(Self { runtime, db })
+    }
+
+    /// Run a read-only SQL statement; returns a list of row dicts.
+    fn query(&self, py: Python<'_>, sql: &str) -> PyResult<Py<PyAny>> {
+        let rows = self.block_on(py, self.db.query_arbitrary(sql))?;
+        to_py(py, &rows)
+    }
+
+    /// Query tasks with the same filters as `asanadw query`; returns a list of dicts.
+    #[pyo3(signature = (
+        project=None, portfolio=None, team=None, assignee=None, completed=None,
+        overdue=None, created_after=None, created_before=None, due_after=None,
+        due_before=None, project_label=None, subtype=None, limit=1000
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn tasks(
+        &self,
+        py: Python<'_>,
+        project: Option<&str>,
+        portfolio: Option<&str>,
+        team: Option<&str>,
+        assignee: Option<&str>,
+        completed: Option<bool>,
+        overdue: Option<bool>,
+        created_after: Option<&str>,
+        created_before: Option<&str>,
+        due_after: Option<&str>,
+        due_before: Option<&str>,
+        project_label: Option<&str>,
+        subtype: Option<&str>,
+        limit: u32,
+    ) -> PyResult<Py<PyAny>> {
+        let mut builder = QueryBuilder::new()
+            .limit(limit)
+            .order_by("t.modified_at")
+            .descending();
+        if let Some(p) = project {
+            builder = builder.project(p);
+        }
+        if let Some(p) = portfolio {
+            builder = builder.portfolio(p);
+        }
+        if let Some(t) = team {
+            builder = builder.team(t);
+        }
+        if let Some(a) = assignee {
+            builder = builder.assignee(a);
+        }
+        if let Some(c) = completed {
+            builder = builder.completed(c);
+        }
+        if let Some(o) = overdue {
+            builder = builder.overdue(o);
+        }
+        if let Some(d) = created_after {
+            builder = builder.created_after(d);
+        }
+        if let Some(d) = created_before {
+            builder = builder.created_before(d);
+        }
+        if let Some(d) = due_after {
+            builder = builder.due_after(d);
+        }
+        if let Some(d) = due_before {
+            builder = builder.due_before(d);
+        }
+        if let Some(l) = project_label {
+            builder = builder.project_label(l);
+        }
+        if let Some(s) = subtype {
+            builder = builder.subtype(s);
+        }
+        let rows = self.block_on(py, builder.tasks(&self.db))?;
+        to_py(py, &rows)
+    }
+
+    /// Full-text search; returns a dict with `query`, `hits`, and `total`.
+    /// `completed`, `subtype`, and the `YYYY-MM-DD` date bounds filter task
+    /// and comment hits.
+    #[pyo3(signature = (
+        query, limit=None, hit_type=None, assignee=None, project=None, offset=None,
+        completed=None, created_after=None, created_before=None,
+        modified_after=None, modified_before=None, subtype=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        limit: Option<u32>,
+        hit_type: Option<&str>,
+        assignee: Option<String>,
+        project: Option<String>,
+        offset: Option<u32>,
+        completed: Option<bool>,
+        created_after: Option<String>,
+        created_before: Option<String>,
+        modified_after: Option<String>,
+        modified_before: Option<String>,
+        subtype: Option<String>,
+    ) -> PyResult<Py<PyAny>> {
+        let hit_type = match hit_type {
+            None => None,
+            Some("task") => Some(SearchHitType::Task),
+            Some("comment") => Some(SearchHitType::Comment),
+            Some("project") => Some(SearchHitType::Project),
+            Some("portfolio") => Some(SearchHitType::Portfolio),
+            Some("brief") => Some(SearchHitType::ProjectBrief),
+            Some("status") => Some(SearchHitType::StatusUpdate),
+            Some("custom_field") => Some(SearchHitType::CustomField),
+            Some(other) => {
+                return Err(AsanaDWError::new_err(format!(
+                    "unknown hit_type '{other}'. Use: task, comment, project, portfolio, brief, status, custom_field"
+                )))
+            }
+        };
+        let options = SearchOptions {
+            limit,
+            offset,
+            hit_type,
+            assignee_gid: assignee,
+            project_gid: project,
+            completed,
+            created_after,
+            created_before,
+            modified_after,
+            modified_before,
+            subtype,
+        };
+        let results = self.block_on(py, asanadw_core::search::search(&self.db, query, &options))?;
+        to_py(py, &results)
+    }
+
+    /// Metrics for a `user`, `project`, `portfolio`, `team`, or project `label`
+    /// over a period such as `qtd`, `2024-Q1`, or `30d`.
+    #[pyo3(signature = (entity_type, identifier, period="qtd"))]
+    fn metrics(
+        &self,
+        py: Python<'_>,
+        entity_type: &str,
+        identifier: &str,
+        period: &str,
+    ) -> PyResult<Py<PyAny>> {
+        let period = Period::parse(period).map_err(to_py_err)?;
+        let gid = if entity_type == "label" {
+            identifier.to_string()
+        } else {
+            asanadw_core::url::resolve_gid(identifier).map_err(to_py_err)?
+        };
+        let db = &self.db;
+        match entity_type {
+            "user" => {
+                let m = self.block_on(
+                    py,
+                    asanadw_core::metrics::compute_user_metrics(db, &gid, &period),
+                )?;
+                to_py(py, &m)
+            }
+            "project" => {
+                let m = self.block_on(
+                    py,
+                    asanadw_core::metrics::compute_project_metrics(db, &gid, &period),
+                )?;
+                to_py(py, &m)
+            }
+            "portfolio" => {
+                let m = self.block_on(
+                    py,
+                    asanadw_core::metrics::compute_portfolio_metrics(db, &gid, &period),
+                )?;
+                to_py(py, &m)
+            }
+            "team" => {
+                let m = self.block_on(
+                    py,
+                    asanadw_core::metrics::compute_team_metrics(db, &gid, &period),
+                )?;
+                to_py(py, &m)
+            }
+            "label" => {
+                let m = self.block_on(
+                    py,
+                    asanadw_core::metrics::compute_label_metrics(db, &gid, &period),
+                )?;
+                to_py(py, &m)
+            }
+            other => Err(AsanaDWError::new_err(format!(
+                "unknown entity type '{other}'. Use: user, project, portfolio, team, label"
+            ))),
+        }
+    }
+}
+
+#[pymodule]
+fn asanadw(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Warehouse>()?;
+    m.add("AsanaDWError", m.py().get_type::<AsanaDWError>())?;
+    Ok(())
+}
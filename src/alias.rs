@@ -0,0 +1,117 @@
+//! Short names for entities, e.g. `roadmap` for `project:1209759542987106`.
+//!
+//! Aliases are stored in `entity_aliases` and resolved before
+//! [`resolve_gid`], so they work anywhere a GID or Asana URL does.
+
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::Database;
+use crate::url::{is_gid, resolve_gid};
+
+/// Entity types an alias can point at.
+pub const ALIAS_ENTITY_TYPES: &[&str] = &["project", "portfolio", "team", "user"];
+
+/// Check an alias and the entity key it names. Aliases can't look like a
+/// GID, URL, or entity key, since those never reach alias lookup.
+pub fn validate_alias(alias: &str, entity_key: &str) -> Result<()> {
+    if alias.is_empty() || is_gid(alias) || alias.contains(':') || alias.contains('/') {
+        return Err(Error::InvalidIdentifier(format!(
+            "alias '{alias}' must be a name, not a GID, URL, or entity key"
+        )));
+    }
+    let Some((entity_type, gid)) = entity_key.split_once(':') else {
+        return Err(Error::InvalidIdentifier(format!(
+            "'{entity_key}' is not an entity key like project:1234567890"
+        )));
+    };
+    if !ALIAS_ENTITY_TYPES.contains(&entity_type) || !is_gid(gid) {
+        return Err(Error::InvalidIdentifier(format!(
+            "'{entity_key}' is not an entity key. Use one of {} with a GID, e.g. project:1234567890",
+            ALIAS_ENTITY_TYPES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+pub async fn set_alias(db: &Database, alias: &str, entity_key: &str) -> Result<()> {
+    validate_alias(alias, entity_key)?;
+    let alias = alias.to_string();
+    let entity_key = entity_key.to_string();
+    db.writer()
+        .call(move |conn| repository::set_alias(conn, &alias, &entity_key))
+        .await?;
+    Ok(())
+}
+
+/// Returns false if no such alias exists.
+pub async fn remove_alias(db: &Database, alias: &str) -> Result<bool> {
+    let alias = alias.to_string();
+    Ok(db
+        .writer()
+        .call(move |conn| repository::remove_alias(conn, &alias))
+        .await?)
+}
+
+pub async fn list_aliases(db: &Database) -> Result<Vec<(String, String)>> {
+    Ok(db
+        .reader()
+        .call(|conn| repository::list_aliases(conn))
+        .await?)
+}
+
+/// Resolve an identifier to a GID: an alias becomes the GID of the entity
+/// it names, and anything else goes through [`resolve_gid`].
+pub async fn resolve(db: &Database, identifier: &str) -> Result<String> {
+    if is_gid(identifier) || identifier.contains("asana.com") {
+        return resolve_gid(identifier);
+    }
+    let alias = identifier.to_string();
+    let entity_key = db
+        .reader()
+        .call(move |conn| repository::get_alias(conn, &alias))
+        .await?;
+    match entity_key.as_deref().and_then(|k| k.split_once(':')) {
+        Some((_, gid)) => Ok(gid.to_string()),
+        None => resolve_gid(identifier),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_alias() {
+        assert!(validate_alias("roadmap", "project:1209759542987106").is_ok());
+        assert!(validate_alias("1234", "project:1209759542987106").is_err());
+        assert!(validate_alias("project:1", "project:1209759542987106").is_err());
+        assert!(validate_alias("roadmap", "1209759542987106").is_err());
+        assert!(validate_alias("roadmap", "task:1209759542987106").is_err());
+        assert!(validate_alias("roadmap", "project:abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_alias() {
+        let db = Database::open_memory().await.unwrap();
+        set_alias(&db, "Roadmap", "project:1209759542987106")
+            .await
+            .unwrap();
+
+        assert_eq!(resolve(&db, "roadmap").await.unwrap(), "1209759542987106");
+        assert_eq!(resolve(&db, "42").await.unwrap(), "42");
+        assert_eq!(
+            resolve(&db, "https://app.asana.com/0/1234567890/list")
+                .await
+                .unwrap(),
+            "1234567890"
+        );
+        // Not an alias: passed through for name or email resolution
+        assert_eq!(
+            resolve(&db, "ada@example.com").await.unwrap(),
+            "ada@example.com"
+        );
+
+        assert!(remove_alias(&db, "ROADMAP").await.unwrap());
+        assert!(list_aliases(&db).await.unwrap().is_empty());
+    }
+}
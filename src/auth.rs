@@ -0,0 +1,380 @@
+//! OAuth 2.0 login (the `oauth` feature): the authorization code flow with
+//! PKCE, as an alternative to personal access tokens.
+//!
+//! `login` opens Asana's consent page in the browser and catches the
+//! redirect on a local port. The session, including the refresh token, is
+//! kept in the OS keychain, one per profile, and `access_token` refreshes
+//! the short-lived access token when it is about to expire.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::keychain;
+
+/// Environment variable holding the OAuth app's client ID.
+pub const CLIENT_ID_ENV: &str = "ASANA_CLIENT_ID";
+
+/// Environment variable holding the OAuth app's client secret.
+pub const CLIENT_SECRET_ENV: &str = "ASANA_CLIENT_SECRET";
+
+/// Local port the browser is redirected to; the app's redirect URL must be
+/// `http://localhost:<port>/callback`.
+pub const DEFAULT_REDIRECT_PORT: u16 = 8976;
+
+/// Keychain session used outside any profile.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+const AUTHORIZE_URL: &str = "https://app.asana.com/-/oauth_authorize";
+const TOKEN_URL: &str = "https://app.asana.com/-/oauth_token";
+
+/// Access tokens this close to expiry are refreshed before use.
+pub const REFRESH_MARGIN_SECS: i64 = 300;
+
+/// A logged-in OAuth session, stored as JSON in the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+    /// The Asana user who granted access.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_email: Option<String>,
+}
+
+impl Session {
+    fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at - Duration::seconds(REFRESH_MARGIN_SECS) <= now
+    }
+}
+
+/// The registered OAuth app to log in with.
+#[derive(Debug, Clone)]
+pub struct LoginOptions {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub port: u16,
+    /// Try to open the consent page in a browser; the URL is always
+    /// passed to `login`'s `show_url` too.
+    pub open_browser: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    expires_in: i64,
+    #[serde(default)]
+    data: Option<TokenUser>,
+}
+
+#[derive(Deserialize)]
+struct TokenUser {
+    #[serde(default)]
+    email: Option<String>,
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// `len` random bytes, base64url-encoded.
+fn random_string(len: usize) -> Result<String> {
+    let mut buf = vec![0u8; len];
+    getrandom::fill(&mut buf).map_err(|e| Error::Auth(format!("random bytes: {e}")))?;
+    Ok(base64url(&buf))
+}
+
+/// The S256 code challenge for a PKCE verifier.
+pub fn pkce_challenge(verifier: &str) -> String {
+    base64url(&Sha256::digest(verifier.as_bytes()))
+}
+
+/// Asana's consent page for this login attempt.
+pub fn authorize_url(client_id: &str, redirect_uri: &str, state: &str, challenge: &str) -> String {
+    url::Url::parse_with_params(
+        AUTHORIZE_URL,
+        &[
+            ("client_id", client_id),
+            ("redirect_uri", redirect_uri),
+            ("response_type", "code"),
+            ("state", state),
+            ("code_challenge_method", "S256"),
+            ("code_challenge", challenge),
+        ],
+    )
+    .map(String::from)
+    .unwrap_or_default()
+}
+
+/// The authorization code from the redirect's request line
+/// (`GET /callback?code=...&state=... HTTP/1.1`), checking `state`.
+fn parse_callback(request_line: &str, expected_state: &str) -> Result<String> {
+    let target = request_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| Error::Auth(format!("unexpected request: {request_line}")))?;
+    let url = url::Url::parse(&format!("http://localhost{target}"))
+        .map_err(|e| Error::Auth(format!("unexpected redirect {target}: {e}")))?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+    if let Some(error) = param("error") {
+        let detail = param("error_description").unwrap_or_default();
+        return Err(Error::Auth(format!(
+            "Asana denied access: {error} {detail}"
+        )));
+    }
+    if param("state").as_deref() != Some(expected_state) {
+        return Err(Error::Auth(
+            "redirect state did not match; try logging in again".into(),
+        ));
+    }
+    param("code").ok_or_else(|| Error::Auth("redirect had no authorization code".into()))
+}
+
+/// Serve one redirect on `listener` and return its authorization code.
+fn wait_for_callback(listener: &TcpListener, expected_state: &str) -> Result<String> {
+    let io_err = |e: std::io::Error| Error::Auth(format!("local redirect listener: {e}"));
+    loop {
+        let (mut stream, _) = listener.accept().map_err(io_err)?;
+        let mut request_line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .map_err(io_err)?;
+        // Browsers also ask for /favicon.ico; only the callback counts
+        if !request_line.contains("/callback") {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+            continue;
+        }
+        let result = parse_callback(&request_line, expected_state);
+        let body = match &result {
+            Ok(_) => "Logged in to asanadw. You can close this tab.".to_string(),
+            Err(e) => format!("asanadw login failed: {e}"),
+        };
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        return result;
+    }
+}
+
+fn request_token(form: &[(&str, &str)]) -> Result<TokenResponse> {
+    match ureq::post(TOKEN_URL).send_form(form) {
+        Ok(response) => response
+            .into_json()
+            .map_err(|e| Error::Auth(format!("invalid token response: {e}"))),
+        Err(ureq::Error::Status(code, response)) => {
+            let detail = response.into_string().unwrap_or_default();
+            Err(Error::Auth(format!(
+                "token request returned HTTP {code}: {detail}"
+            )))
+        }
+        Err(e) => Err(Error::Auth(format!("token request failed: {e}"))),
+    }
+}
+
+/// Form fields identifying the app, plus `fields`.
+fn token_form<'a>(
+    client_id: &'a str,
+    client_secret: Option<&'a str>,
+    redirect_uri: &'a str,
+    fields: &[(&'a str, &'a str)],
+) -> Vec<(&'a str, &'a str)> {
+    let mut form = vec![("client_id", client_id), ("redirect_uri", redirect_uri)];
+    if let Some(secret) = client_secret {
+        form.push(("client_secret", secret));
+    }
+    form.extend_from_slice(fields);
+    form
+}
+
+/// Keychain name `account`'s session is saved under.
+fn keychain_name(account: &str) -> String {
+    format!("oauth:{account}")
+}
+
+/// The stored session for `account` (a profile name, or
+/// [`DEFAULT_ACCOUNT`]), if it has logged in.
+pub fn load_session(account: &str) -> Result<Option<Session>> {
+    let json = keychain::get(&keychain_name(account))
+        .map_err(|e| Error::Auth(format!("keychain: {e}")))?;
+    json.map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| Error::Auth(format!("stored session: {e}")))
+}
+
+fn save_session(account: &str, session: &Session) -> Result<()> {
+    let json = serde_json::to_string(session).map_err(|e| Error::Other(e.to_string()))?;
+    keychain::set(&keychain_name(account), &json).map_err(|e| Error::Auth(format!("keychain: {e}")))
+}
+
+/// Forget `account`'s session. Returns whether there was one.
+pub fn logout(account: &str) -> Result<bool> {
+    keychain::delete(&keychain_name(account)).map_err(|e| Error::Auth(format!("keychain: {e}")))
+}
+
+fn login_blocking(
+    account: &str,
+    options: &LoginOptions,
+    show_url: impl FnOnce(&str),
+) -> Result<Session> {
+    let listener = TcpListener::bind(("127.0.0.1", options.port)).map_err(|e| {
+        Error::Auth(format!(
+            "cannot listen on port {} for the redirect: {e}",
+            options.port
+        ))
+    })?;
+    let redirect_uri = format!("http://localhost:{}/callback", options.port);
+    let verifier = random_string(32)?;
+    let state = random_string(16)?;
+    let url = authorize_url(
+        &options.client_id,
+        &redirect_uri,
+        &state,
+        &pkce_challenge(&verifier),
+    );
+
+    show_url(&url);
+    if options.open_browser {
+        open_browser(&url);
+    }
+    let code = wait_for_callback(&listener, &state)?;
+
+    let form = token_form(
+        &options.client_id,
+        options.client_secret.as_deref(),
+        &redirect_uri,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("code_verifier", verifier.as_str()),
+        ],
+    );
+    let token = request_token(&form)?;
+    let refresh_token = token
+        .refresh_token
+        .ok_or_else(|| Error::Auth("Asana returned no refresh token".into()))?;
+    let session = Session {
+        client_id: options.client_id.clone(),
+        client_secret: options.client_secret.clone(),
+        redirect_uri,
+        access_token: token.access_token,
+        refresh_token,
+        expires_at: Utc::now() + Duration::seconds(token.expires_in),
+        user_email: token.data.and_then(|u| u.email),
+    };
+    save_session(account, &session)?;
+    Ok(session)
+}
+
+/// Best effort; `show_url` has the URL for when no browser opens.
+fn open_browser(url: &str) {
+    let result = if cfg!(target_os = "macos") {
+        std::process::Command::new("open").arg(url).spawn()
+    } else if cfg!(windows) {
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", url])
+            .spawn()
+    } else {
+        std::process::Command::new("xdg-open").arg(url).spawn()
+    };
+    if let Err(e) = result {
+        log::debug!("could not open a browser: {e}");
+    }
+}
+
+/// Run the browser login for `account` and store the session in the
+/// keychain. `show_url` gets the consent page URL to show the user before
+/// the login waits for Asana's redirect.
+pub async fn login(
+    account: &str,
+    options: LoginOptions,
+    show_url: impl FnOnce(&str) + Send + 'static,
+) -> Result<Session> {
+    let account = account.to_string();
+    tokio::task::spawn_blocking(move || login_blocking(&account, &options, show_url))
+        .await
+        .map_err(|e| Error::Auth(e.to_string()))?
+}
+
+fn access_token_blocking(account: &str) -> Result<Option<Session>> {
+    let Some(mut session) = load_session(account)? else {
+        return Ok(None);
+    };
+    if !session.needs_refresh(Utc::now()) {
+        return Ok(Some(session));
+    }
+    let form = token_form(
+        &session.client_id,
+        session.client_secret.as_deref(),
+        &session.redirect_uri,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", session.refresh_token.as_str()),
+        ],
+    );
+    let token = request_token(&form)?;
+    session.access_token = token.access_token;
+    session.expires_at = Utc::now() + Duration::seconds(token.expires_in);
+    if let Some(refresh_token) = token.refresh_token {
+        session.refresh_token = refresh_token;
+    }
+    save_session(account, &session)?;
+    Ok(Some(session))
+}
+
+/// `account`'s session with a current access token, refreshing it first
+/// when it is about to expire. `None` when the account hasn't logged in.
+pub async fn access_token(account: &str) -> Result<Option<Session>> {
+    let account = account.to_string();
+    tokio::task::spawn_blocking(move || access_token_blocking(&account))
+        .await
+        .map_err(|e| Error::Auth(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636, appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        let url = authorize_url("123", "http://localhost:8976/callback", "s", "c");
+        assert!(url.contains("redirect_uri=http%3A%2F%2Flocalhost%3A8976%2Fcallback"));
+        assert!(url.contains("code_challenge_method=S256"));
+    }
+
+    #[test]
+    fn test_parse_callback() {
+        assert_eq!(
+            parse_callback("GET /callback?code=abc%2F1&state=xyz HTTP/1.1", "xyz").unwrap(),
+            "abc/1"
+        );
+        assert!(parse_callback("GET /callback?code=abc&state=other HTTP/1.1", "xyz").is_err());
+        assert!(parse_callback(
+            "GET /callback?error=access_denied&state=xyz HTTP/1.1",
+            "xyz"
+        )
+        .is_err());
+    }
+}
@@ -1,4 +1,11 @@
-use clap::{Parser, Subcommand};
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+
+#[cfg(not(feature = "llm"))]
+const LLM_DISABLED: &str =
+    "LLM features are not enabled in this build. Reinstall with: cargo install asanadw --features llm";
 
 #[derive(Parser)]
 #[command(name = "asanadw", about = "Asana data warehouse CLI")]
@@ -15,10 +22,147 @@ struct Cli {
     #[arg(long)]
     page_size: Option<u32>,
 
+    /// Comment requests in flight per project sync (default: 4)
+    #[arg(long)]
+    comment_concurrency: Option<usize>,
+
+    /// Take the sync lock even if another process appears to hold it
+    #[arg(long, global = true)]
+    force_unlock: bool,
+
+    /// Open the database read-only, refusing syncs and other changes
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Asana workspace (GID or name) to use instead of the configured one
+    #[arg(long, global = true)]
+    workspace: Option<String>,
+
+    /// Named profile (token, workspace, database) to run under; see `asanadw profile`
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Suppress progress and status messages on stderr
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Scripting mode: implies --quiet, and wraps JSON on stdout (each line
+    /// of --jsonl) as {"schema_version", "data"} so its shape can be checked
+    #[arg(long, global = true)]
+    machine: bool,
+
+    /// OAuth account whose token is in use, and when to refresh it
+    #[arg(skip)]
+    oauth: Option<(String, chrono::DateTime<chrono::Utc>)>,
+
+    /// Asana token from the profile or an OAuth login, used instead of ASANA_TOKEN
+    #[arg(skip)]
+    token: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// `--period` help, listing the formats `Period::parse` accepts.
+fn period_help() -> String {
+    format!("Period (e.g. {})", asanadw::query::period::PERIOD_EXAMPLES)
+}
+
+/// Set from `--quiet` or `--machine` before any command runs.
+static QUIET: AtomicBool = AtomicBool::new(false);
+/// Set from `--machine`.
+static MACHINE: AtomicBool = AtomicBool::new(false);
+
+/// `eprintln!` for progress and status messages, which `--quiet` silences.
+/// Errors and warnings still go through `eprintln!` or `log`.
+macro_rules! note {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// JSON wrapper `--machine` puts around every `--json` output.
+#[derive(serde::Serialize)]
+struct Envelope<'a, T: ?Sized> {
+    schema_version: u32,
+    data: &'a T,
+}
+
+/// Print `value` as pretty JSON, in an [`Envelope`] under `--machine`.
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    if MACHINE.load(Ordering::Relaxed) {
+        let envelope = Envelope {
+            schema_version: asanadw::JSON_SCHEMA_VERSION,
+            data: value,
+        };
+        println!("{}", serde_json::to_string_pretty(&envelope)?);
+    } else {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    }
+    Ok(())
+}
+
+/// Print JSON text a library call has already rendered.
+fn print_json_text(text: &str) -> anyhow::Result<()> {
+    if MACHINE.load(Ordering::Relaxed) {
+        print_json(&serde_json::from_str::<serde_json::Value>(text)?)
+    } else {
+        println!("{text}");
+        Ok(())
+    }
+}
+
+/// Writer for JSON lines that wraps each line in an [`Envelope`], as
+/// `--machine` does for `--json`.
+struct EnvelopeLines<W: Write> {
+    inner: W,
+    line: Vec<u8>,
+}
+
+impl<W: Write> EnvelopeLines<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for EnvelopeLines<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            if byte == b'\n' {
+                write!(
+                    self.inner,
+                    "{{\"schema_version\":{},\"data\":",
+                    asanadw::JSON_SCHEMA_VERSION
+                )?;
+                self.inner.write_all(&self.line)?;
+                self.inner.write_all(b"}\n")?;
+                self.line.clear();
+            } else {
+                self.line.push(byte);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Sync progress for stderr, or none under `--quiet`.
+fn stderr_progress() -> &'static dyn asanadw::SyncProgress {
+    if QUIET.load(Ordering::Relaxed) {
+        &asanadw::NoopProgress
+    } else {
+        &StderrProgress
+    }
+}
+
 /// Progress reporter that writes to stderr.
 struct StderrProgress;
 
@@ -49,11 +193,18 @@ impl asanadw::SyncProgress for StderrProgress {
         }
     }
 
+    fn on_tasks_deleted(&self, entity_key: &str, count: usize) {
+        eprintln!("  [{entity_key}] Marked {count} tasks deleted");
+    }
+
     fn on_incremental_sync(&self, entity_key: &str, summary: &asanadw::IncrementalSyncSummary) {
         let mut parts: Vec<String> = Vec::new();
         if summary.tasks_changed > 0 {
             parts.push(format!("{} tasks", summary.tasks_changed));
         }
+        if summary.tasks_deleted > 0 {
+            parts.push(format!("{} deleted tasks", summary.tasks_deleted));
+        }
         if summary.project_changed {
             parts.push("project metadata".to_string());
         }
@@ -79,13 +230,90 @@ impl asanadw::SyncProgress for StderrProgress {
     }
 }
 
+/// Prints each task change `asanadw watch` picks up, one line per change.
+struct WatchProgress {
+    json: bool,
+}
+
+impl asanadw::SyncProgress for WatchProgress {
+    fn on_task_changes(&self, _entity_key: &str, changes: &[asanadw::sync::changes::TaskChange]) {
+        let now = chrono::Local::now();
+        let mut out = std::io::stdout().lock();
+        for change in changes {
+            if self.json {
+                let mut line = serde_json::to_value(change).unwrap_or_default();
+                line["at"] = serde_json::Value::String(now.to_rfc3339());
+                line["schema_version"] = asanadw::JSON_SCHEMA_VERSION.into();
+                let _ = writeln!(out, "{line}");
+            } else {
+                let _ = writeln!(out, "{}  {change}", now.format("%H:%M:%S"));
+            }
+        }
+        let _ = out.flush();
+    }
+}
+
+/// Progress for concurrent syncs on a terminal: one live line per in-flight
+/// entity, redrawn in place, with completed entities printed above them.
+fn in_flight_progress() -> impl asanadw::SyncProgress {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // Number of live lines currently on screen, erased before each redraw
+    let drawn = Arc::new(AtomicUsize::new(0));
+    fn erase(out: &mut std::io::StderrLock<'_>, lines: usize) {
+        for _ in 0..lines {
+            let _ = write!(out, "\x1b[1A\x1b[2K");
+        }
+    }
+
+    let redraw = {
+        let drawn = drawn.clone();
+        move |entities: &[asanadw::sync::progress::InFlightEntity]| {
+            let mut out = std::io::stderr().lock();
+            erase(&mut out, drawn.swap(entities.len(), Ordering::SeqCst));
+            for e in entities {
+                let _ = writeln!(out, "  [{}] {}", e.entity_key, e.status);
+            }
+            let _ = out.flush();
+        }
+    };
+    let complete = move |report: &asanadw::SyncReport| {
+        let mut out = std::io::stderr().lock();
+        erase(&mut out, drawn.swap(0, Ordering::SeqCst));
+        let _ = writeln!(
+            out,
+            "[{}] Done: {} items synced",
+            report.entity_key, report.items_synced
+        );
+    };
+    asanadw::sync::progress::InFlightProgress::new(redraw, complete)
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Sync Asana data to the local warehouse
     Sync {
+        /// Write every progress event as a JSON line on stdout instead of
+        /// text on stderr, with each entity's report in its entity_complete event
+        #[arg(long, global = true)]
+        progress_json: bool,
         #[command(subcommand)]
         target: SyncTarget,
     },
+    /// Run continuously, syncing monitored entities on their schedule
+    Daemon,
+    /// Follow a project live: poll its events and print each change as it's synced
+    Watch {
+        /// Project GID or Asana URL
+        project: String,
+        /// Seconds between polls
+        #[arg(long, default_value_t = asanadw::sync::watch::DEFAULT_WATCH_INTERVAL_SECS)]
+        interval: u64,
+        /// Print changes as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
     /// Manage monitored entities
     Monitor {
         #[command(subcommand)]
@@ -96,12 +324,50 @@ enum Commands {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// List Asana workspaces and choose which one to work in
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+    /// Manage named profiles, each with its own token, workspace, and database
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Log in to Asana in the browser (OAuth) instead of using a personal access token
+    Login {
+        /// OAuth app client ID (default: ASANA_CLIENT_ID)
+        #[arg(long)]
+        client_id: Option<String>,
+        /// OAuth app client secret (default: ASANA_CLIENT_SECRET)
+        #[arg(long)]
+        client_secret: Option<String>,
+        /// Local port for the redirect; the app's redirect URL must be http://localhost:<PORT>/callback
+        #[arg(long, default_value_t = 8976)]
+        port: u16,
+        /// Print the login URL without opening a browser
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// Forget the OAuth login for the current profile
+    Logout,
+    /// Name entities so the name works anywhere a GID or URL does
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+    /// Revert the last `monitor remove` or `config set`
+    Undo {
+        /// List recent monitor removals and config changes instead
+        #[arg(long)]
+        list: bool,
+    },
     /// Search across all synced data
     Search {
         /// Search query
         #[arg(num_args = 1..)]
         query: Vec<String>,
-        /// Filter by type: task, comment, project, portfolio, custom_field
+        /// Filter by type: task, comment, project, portfolio, brief, status, custom_field
         #[arg(long, value_name = "TYPE")]
         r#type: Option<String>,
         /// Filter by assignee GID or email
@@ -113,189 +379,738 @@ enum Commands {
         /// Filter by project GID
         #[arg(long)]
         project: Option<String>,
-        /// Maximum results
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        /// Output as JSON
-        #[arg(long)]
-        json: bool,
-    },
-    /// Query tasks with filters
-    Query {
-        /// Filter by project GID
-        #[arg(long)]
-        project: Option<String>,
-        /// Filter by portfolio GID
-        #[arg(long)]
-        portfolio: Option<String>,
-        /// Filter by team GID
-        #[arg(long)]
-        team: Option<String>,
-        /// Filter by assignee GID or email
-        #[arg(long)]
-        assignee: Option<String>,
-        /// Filter to tasks assigned to the current user
-        #[arg(long)]
-        mine: bool,
-        /// Filter completed tasks only
-        #[arg(long)]
+        /// Only completed tasks (and their comments)
+        #[arg(long, conflicts_with = "incomplete")]
         completed: bool,
-        /// Filter incomplete tasks only
+        /// Only incomplete tasks (and their comments)
         #[arg(long)]
         incomplete: bool,
-        /// Filter overdue tasks only
-        #[arg(long)]
-        overdue: bool,
-        /// Created after date (YYYY-MM-DD)
+        /// Only tasks of this subtype (and their comments): default_task, milestone, approval
+        #[arg(long, value_name = "SUBTYPE")]
+        subtype: Option<String>,
+        /// Created on or after date (YYYY-MM-DD)
         #[arg(long)]
         created_after: Option<String>,
-        /// Created before date (YYYY-MM-DD)
+        /// Created on or before date (YYYY-MM-DD)
         #[arg(long)]
         created_before: Option<String>,
-        /// Due after date (YYYY-MM-DD)
+        /// Modified on or after date (YYYY-MM-DD)
         #[arg(long)]
-        due_after: Option<String>,
-        /// Due before date (YYYY-MM-DD)
+        modified_after: Option<String>,
+        /// Modified on or before date (YYYY-MM-DD)
         #[arg(long)]
-        due_before: Option<String>,
+        modified_before: Option<String>,
         /// Maximum results
-        #[arg(long, default_value = "100")]
+        #[arg(long, default_value = "20")]
         limit: u32,
+        /// Skip this many results (for paging)
+        #[arg(long, default_value = "0")]
+        offset: u32,
+        /// Check the search indexes against the synced data and rebuild any that have drifted
+        #[arg(long, conflicts_with = "query")]
+        verify_index: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
-        /// Output as CSV
-        #[arg(long)]
-        csv: bool,
-        /// Count only (no output rows)
+    },
+    /// Query tasks with filters, or comments and status updates
+    #[command(args_conflicts_with_subcommands = true)]
+    Query {
+        #[command(subcommand)]
+        source: Option<QuerySource>,
+        #[command(flatten)]
+        filters: Box<TaskFilterArgs>,
+        /// Only tasks listed in this file (task URLs or GIDs, one per line; `-` for stdin)
+        #[arg(long, value_name = "FILE")]
+        urls: Option<String>,
+        /// Maximum results
+        #[arg(long, default_value = "100")]
+        limit: u32,
+        #[command(flatten)]
+        format: QueryOutputArgs,
+        /// Sync a filtered project, portfolio, team, or assignee first if it has no local data
         #[arg(long)]
-        count: bool,
+        fetch: bool,
     },
     /// Generate LLM-powered summaries
     Summarize {
         #[command(subcommand)]
         target: SummarizeTarget,
+        /// Sync the entity first if it has no local data
+        #[arg(long, global = true)]
+        fetch: bool,
     },
-    /// Compute metrics for an entity over a period
-    Metrics {
-        #[command(subcommand)]
-        target: MetricsTarget,
+    /// Ask a question about the warehouse, answered by the LLM with sources
+    Ask {
+        /// The question, e.g. "which projects slipped the most this quarter?"
+        #[arg(num_args = 1..)]
+        question: Vec<String>,
+        /// Maximum tool calls before the LLM must answer
+        #[arg(long, default_value = "8")]
+        max_steps: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
-    /// Show warehouse status
-    Status,
-}
-
-#[derive(Subcommand)]
-enum MetricsTarget {
-    /// Metrics for the current user
-    Me {
-        /// Period (e.g. 2024-Q1, 2024-M03, ytd, rolling-30d)
-        #[arg(long, default_value = "qtd")]
+    /// Interactive session: /query, /metrics and /search run those commands,
+    /// anything else is asked of the LLM
+    Repl {
+        /// Maximum tool calls before the LLM must answer each question
+        #[arg(long, default_value = "8")]
+        max_steps: usize,
+    },
+    /// Digest of your work and your monitored projects and portfolios
+    Digest {
+        #[arg(long, default_value = "wtd", help = period_help())]
         period: String,
+        /// Output an HTML page ready to send as an email body
+        #[arg(long, conflicts_with = "json")]
+        email_format: bool,
+        /// Bypass the digest and summary caches and regenerate
+        #[arg(long)]
+        force: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Metrics for a user
-    User {
-        /// User GID or email address
-        #[arg(value_name = "USER_GID_OR_EMAIL")]
-        user_gid: String,
-        /// Period (e.g. 2024-Q1, 2024-M03, ytd, rolling-30d)
-        #[arg(long, default_value = "qtd")]
+    /// Flag risks and blockers the LLM finds in status updates and comments
+    Risks {
+        #[arg(long, default_value = "30d", help = period_help())]
         period: String,
+        /// Only show flags at or above this severity: low, medium, or high
+        #[arg(long)]
+        min_severity: Option<String>,
+        /// Rescan sources that were already scanned
+        #[arg(long)]
+        force: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Metrics for a project
-    Project {
-        /// Project GID or Asana URL
-        #[arg(value_name = "PROJECT_GID_OR_URL")]
-        project_gid: String,
-        #[arg(long, default_value = "qtd")]
-        period: String,
+    /// Compute metrics for an entity over a period
+    Metrics {
+        #[command(subcommand)]
+        target: MetricsTarget,
+        /// Sync the entity first if it has no local data
+        #[arg(long, global = true)]
+        fetch: bool,
+    },
+    /// List open tasks assigned to nobody or to deactivated users
+    Orphans {
+        /// Entity type: project or team
+        entity_type: String,
+        /// Entity GID or Asana URL
+        identifier: String,
+        /// Refresh deactivated users from the Asana workspace first
+        #[arg(long)]
+        refresh: bool,
+        /// Ask the configured LLM to pick a new owner for each task
+        #[arg(long)]
+        llm: bool,
+        /// Number of candidate owners to show per task
+        #[arg(long, default_value = "3")]
+        candidates: usize,
+        /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Metrics for a portfolio
-    Portfolio {
-        /// Portfolio GID or Asana URL
-        #[arg(value_name = "PORTFOLIO_GID_OR_URL")]
-        portfolio_gid: String,
-        #[arg(long, default_value = "qtd")]
-        period: String,
+    /// Rank projects by recent activity to see what needs attention
+    Hot {
+        /// Number of projects to show
+        #[arg(long, default_value = "10")]
+        limit: u32,
+        /// Look-back window in days
+        #[arg(long, default_value = "7")]
+        days: u32,
+        /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Metrics for a team
-    Team {
-        /// Team GID or Asana URL
-        #[arg(value_name = "TEAM_GID_OR_URL")]
-        team_gid: String,
-        #[arg(long, default_value = "qtd")]
+    /// Count likes given and received per user, as informal kudos
+    Recognition {
+        #[arg(long, default_value = "mtd", help = period_help())]
         period: String,
+        /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-}
-
-#[derive(Subcommand)]
-enum SummarizeTarget {
-    /// Summarize the current user's period
-    Me {
-        #[arg(long, default_value = "qtd")]
+    /// Group a project's completed tasks into labeled topics
+    Cluster {
+        /// Project GID, URL, or alias
+        project: String,
+        #[arg(long, default_value = "qtd", help = period_help())]
         period: String,
+        /// Number of topics (default: picked from the task count)
         #[arg(long)]
-        force: bool,
+        clusters: Option<usize>,
+        /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Summarize a single task
+    /// Personal stats: completion streaks, busiest day, and response times
+    Stats {
+        #[command(subcommand)]
+        target: StatsTarget,
+    },
+    /// Reports for managers
+    Report {
+        #[command(subcommand)]
+        report: ReportKind,
+    },
+    /// Run a read-only SQL query against the warehouse
+    Sql {
+        /// SQL statement (SELECT, WITH, EXPLAIN, PRAGMA, or VALUES)
+        #[arg(num_args = 1..)]
+        query: Vec<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Output as CSV
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Database maintenance: stats, integrity check, VACUUM, ANALYZE
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// Export warehouse tables for external analysis
+    Export {
+        #[command(subcommand)]
+        format: ExportFormat,
+    },
+    /// Import data Asana doesn't provide
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Show a task's details and its comment threads
     Task {
         /// Task GID or Asana URL
-        #[arg(value_name = "TASK_GID_OR_URL")]
-        task_gid: String,
-        /// Force regeneration (ignore cache)
+        identifier: String,
+        /// Output as JSON
         #[arg(long)]
-        force: bool,
+        json: bool,
+    },
+    /// Show a project's status update history, or without a project the
+    /// latest status of every project
+    StatusUpdates {
+        /// Project GID or Asana URL
+        project: Option<String>,
+        /// Maximum number of updates to show for a project
+        #[arg(long, default_value = "10")]
+        limit: usize,
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Summarize a user's period
-    User {
-        /// User GID or email address
-        #[arg(value_name = "USER_GID_OR_EMAIL")]
-        user_gid: String,
-        #[arg(long, default_value = "qtd")]
+    /// Serve the warehouse to LLM assistants as an MCP server over stdio
+    Mcp,
+    /// Serve typed Metrics, Search, and Summarize gRPC services
+    Grpc {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        addr: std::net::SocketAddr,
+    },
+    /// Show warehouse status
+    Status {
+        /// Show the daemon's per-entity sync schedule
+        #[arg(long)]
+        schedule: bool,
+        /// Show LLM calls, cache hits, and token usage per agent and model
+        #[arg(long, conflicts_with = "schedule")]
+        llm: bool,
+        /// Period for --llm (e.g., 30d, mtd, 2025-Q1)
+        #[arg(long, requires = "llm", default_value = "30d")]
         period: String,
+        /// Output --llm usage as JSON
+        #[arg(long, requires = "llm")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum QuerySource {
+    /// Query comments on tasks
+    Comments {
+        #[command(flatten)]
+        filters: TextQueryArgs,
+    },
+    /// Query project and portfolio status updates
+    StatusUpdates {
+        #[command(flatten)]
+        filters: TextQueryArgs,
+    },
+    /// Save task filters under a name, to run later with `query run`
+    Save {
+        /// Letters, digits, '-', '_', and '.', e.g. my-overdue-p1s
+        name: String,
+        /// What the query is for, shown by `query saved`
         #[arg(long)]
-        force: bool,
+        description: Option<String>,
+        #[command(flatten)]
+        filters: Box<TaskFilterArgs>,
+        /// Maximum results when run (default: 100)
         #[arg(long)]
-        json: bool,
+        limit: Option<u32>,
     },
-    /// Summarize a project's period
-    Project {
-        /// Project GID or Asana URL
-        #[arg(value_name = "PROJECT_GID_OR_URL")]
-        project_gid: String,
-        #[arg(long, default_value = "qtd")]
-        period: String,
+    /// Run a saved query
+    Run {
+        name: String,
+        /// Maximum results, instead of the saved limit
         #[arg(long)]
-        force: bool,
+        limit: Option<u32>,
+        #[command(flatten)]
+        format: QueryOutputArgs,
+        /// Sync a filtered project, portfolio, team, or assignee first if it has no local data
+        #[arg(long)]
+        fetch: bool,
+    },
+    /// List saved queries
+    Saved {
+        /// Output as JSON
         #[arg(long)]
         json: bool,
     },
-    /// Summarize a portfolio's period
-    Portfolio {
-        /// Portfolio GID or Asana URL
-        #[arg(value_name = "PORTFOLIO_GID_OR_URL")]
+    /// Remove a saved query
+    Unsave { name: String },
+}
+
+/// Task filters shared by `query` and `query save`.
+#[derive(clap::Args)]
+struct TaskFilterArgs {
+    /// Filter by project GID
+    #[arg(long)]
+    project: Option<String>,
+    /// Filter by portfolio GID
+    #[arg(long)]
+    portfolio: Option<String>,
+    /// Filter by team GID
+    #[arg(long)]
+    team: Option<String>,
+    /// Filter by project color (e.g. dark-green)
+    #[arg(long)]
+    project_color: Option<String>,
+    /// Filter by a label from the project_labels config
+    #[arg(long)]
+    project_label: Option<String>,
+    /// Tasks whose custom field holds or once held a value, e.g. Status=Blocked
+    #[arg(long, value_name = "FIELD=VALUE")]
+    field_was: Option<String>,
+    /// Custom field filter by name or GID, repeatable: Priority=High, Notes~text, Points>=3
+    #[arg(long = "field", value_name = "FIELD=VALUE")]
+    fields: Vec<String>,
+    /// Tasks whose name or notes match, in search syntax; sorts by relevance
+    #[arg(long)]
+    text: Option<String>,
+    /// Aggregate by assignee, project, section, tag, or month (of completion)
+    #[arg(long, value_name = "DIMENSION")]
+    group_by: Option<String>,
+    /// With --group-by, also sum this number custom field per group
+    #[arg(long, value_name = "FIELD", requires = "group_by")]
+    sum: Option<String>,
+    /// Filter by assignee GID or email
+    #[arg(long)]
+    assignee: Option<String>,
+    /// Filter to tasks assigned to the current user
+    #[arg(long)]
+    mine: bool,
+    /// Filter completed tasks only
+    #[arg(long)]
+    completed: bool,
+    /// Filter incomplete tasks only
+    #[arg(long)]
+    incomplete: bool,
+    /// Filter overdue tasks only
+    #[arg(long)]
+    overdue: bool,
+    /// Filter by task subtype: default_task, milestone, approval
+    #[arg(long, value_name = "SUBTYPE")]
+    r#type: Option<String>,
+    /// Created after date (YYYY-MM-DD)
+    #[arg(long)]
+    created_after: Option<String>,
+    /// Created before date (YYYY-MM-DD)
+    #[arg(long)]
+    created_before: Option<String>,
+    /// Due after date (YYYY-MM-DD)
+    #[arg(long)]
+    due_after: Option<String>,
+    /// Due before date (YYYY-MM-DD)
+    #[arg(long)]
+    due_before: Option<String>,
+    /// Include tasks from template projects
+    #[arg(long)]
+    include_templates: bool,
+    /// Include tasks deleted in Asana
+    #[arg(long)]
+    include_deleted: bool,
+}
+
+impl TaskFilterArgs {
+    fn into_filters(self, limit: Option<u32>) -> asanadw::QueryFilters {
+        asanadw::QueryFilters {
+            project: self.project,
+            portfolio: self.portfolio,
+            team: self.team,
+            project_color: self.project_color,
+            project_label: self.project_label,
+            field_was: self.field_was,
+            fields: self.fields,
+            text: self.text,
+            group_by: self.group_by,
+            sum: self.sum,
+            assignee: self.assignee,
+            mine: self.mine,
+            // --incomplete wins when both are given
+            completed: if self.incomplete {
+                Some(false)
+            } else if self.completed {
+                Some(true)
+            } else {
+                None
+            },
+            overdue: self.overdue,
+            subtype: self.r#type,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            due_after: self.due_after,
+            due_before: self.due_before,
+            include_templates: self.include_templates,
+            include_deleted: self.include_deleted,
+            limit,
+        }
+    }
+}
+
+/// How `query` and `query run` print tasks.
+#[derive(clap::Args)]
+struct QueryOutputArgs {
+    /// Output as JSON
+    #[arg(long)]
+    json: bool,
+    /// Output as CSV
+    #[arg(long)]
+    csv: bool,
+    /// Output as JSON lines, one task (or group) per line
+    #[arg(long)]
+    jsonl: bool,
+    /// Write --csv, --jsonl, or --json output to FILE instead of stdout
+    #[arg(long, value_name = "FILE")]
+    output: Option<String>,
+    /// Count only (no output rows)
+    #[arg(long)]
+    count: bool,
+}
+
+#[derive(clap::Args)]
+struct TextQueryArgs {
+    /// Comments on this project's tasks, or this project's status updates
+    #[arg(long)]
+    project: Option<String>,
+    /// Filter by author GID or email
+    #[arg(long)]
+    author: Option<String>,
+    /// Created after date (YYYY-MM-DD)
+    #[arg(long)]
+    created_after: Option<String>,
+    /// Created before date (YYYY-MM-DD)
+    #[arg(long)]
+    created_before: Option<String>,
+    /// Only text at least this many characters long
+    #[arg(long)]
+    min_length: Option<u32>,
+    /// Only text at most this many characters long
+    #[arg(long)]
+    max_length: Option<u32>,
+    /// Maximum results
+    #[arg(long, default_value = "100")]
+    limit: u32,
+    /// Output as JSON
+    #[arg(long)]
+    json: bool,
+    /// Output as CSV
+    #[arg(long)]
+    csv: bool,
+    /// Count only (no output rows)
+    #[arg(long)]
+    count: bool,
+}
+
+#[derive(Subcommand)]
+enum StatsTarget {
+    /// Stats for the current user
+    Me {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportKind {
+    /// A new hire's first weeks: projects joined, tasks completed, and who
+    /// they worked with
+    Onboarding {
+        /// User GID, email, or alias
+        user: String,
+        /// Number of weeks to cover
+        #[arg(long, default_value = "8")]
+        weeks: u32,
+        /// First day of the window (YYYY-MM-DD; defaults to their first
+        /// assigned task or comment)
+        #[arg(long)]
+        since: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// A project's period in review: metrics, burndown, lead time, top
+    /// contributors, and the cached summary, as Markdown or HTML
+    Project {
+        /// Project GID, Asana URL, or alias
+        #[arg(value_name = "PROJECT_GID_OR_URL")]
+        project_gid: String,
+        #[arg(long, default_value = "qtd", help = period_help())]
+        period: String,
+        /// Output format: markdown, html, or json
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Output file (default: stdout)
+        #[arg(long)]
+        out: Option<String>,
+        /// Sync the project first if it has no local data
+        #[arg(long)]
+        fetch: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportSource {
+    /// Import manager mappings from a CSV with user and manager columns
+    OrgChart {
+        /// CSV file path, or - for stdin
+        file: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Seed the warehouse from an Asana project export (CSV or JSON), no API access needed
+    AsanaExport {
+        /// Export file path, or - for stdin
+        file: String,
+        /// GID, Asana URL, or alias of the exported project
+        #[arg(long, value_name = "PROJECT_GID_OR_URL")]
+        project: String,
+        /// Project name, if the project hasn't been synced (default: file name)
+        #[arg(long)]
+        name: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetricsTarget {
+    /// Metrics for the current user
+    Me {
+        #[arg(long, default_value = "qtd", help = period_help())]
+        period: String,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long)]
+        histogram: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Metrics for a user
+    User {
+        /// User GID or email address
+        #[arg(value_name = "USER_GID_OR_EMAIL")]
+        user_gid: String,
+        #[arg(long, default_value = "qtd", help = period_help())]
+        period: String,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long)]
+        histogram: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Metrics for a project
+    Project {
+        /// Project GID or Asana URL
+        #[arg(value_name = "PROJECT_GID_OR_URL")]
+        project_gid: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        /// Break metrics down by an enum custom field (GID or name, e.g. Priority)
+        #[arg(long, value_name = "FIELD")]
+        group_by_field: Option<String>,
+        /// Show open counts, oldest open task, and throughput per section
+        #[arg(long, conflicts_with = "group_by_field")]
+        by_section: bool,
+        /// Show how long tasks held each value of a tracked custom field (e.g. Status)
+        #[arg(long, value_name = "FIELD", conflicts_with_all = ["group_by_field", "by_section"])]
+        field_history: Option<String>,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long)]
+        histogram: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Metrics for a portfolio
+    Portfolio {
+        /// Portfolio GID or Asana URL
+        #[arg(value_name = "PORTFOLIO_GID_OR_URL")]
         portfolio_gid: String,
         #[arg(long, default_value = "qtd")]
         period: String,
+        /// Levels of nested portfolios whose projects are included
+        #[arg(long, default_value_t = asanadw::sync::DEFAULT_PORTFOLIO_DEPTH)]
+        max_depth: u32,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long)]
+        histogram: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Metrics for a team
+    Team {
+        /// Team GID or Asana URL
+        #[arg(value_name = "TEAM_GID_OR_URL")]
+        team_gid: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        /// Show open, overdue, and due-soon tasks and estimated load per member
+        #[arg(long)]
+        workload: bool,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long, conflicts_with = "workload")]
+        histogram: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Metrics across a manager's reporting line (see `import org-chart`)
+    Org {
+        /// Manager GID or email address
+        #[arg(value_name = "MANAGER_GID_OR_EMAIL")]
+        manager: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long)]
+        histogram: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Most notable tasks completed in a period, by heuristic notability score
+    Notable {
+        /// Only tasks assigned to this user (GID or email address)
+        #[arg(long, value_name = "USER_GID_OR_EMAIL", conflicts_with = "project")]
+        user: Option<String>,
+        /// Only tasks in this project
+        #[arg(long, value_name = "PROJECT_GID")]
+        project: Option<String>,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        #[arg(long, default_value = "10")]
+        limit: u32,
+        /// Recompute every task's score first (e.g. after changing notability_weights)
+        #[arg(long)]
+        rescore: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Metrics across projects matching a configured project label
+    Label {
+        /// Label name from the project_labels config
+        label: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        /// Show the lead-time distribution as a bar chart
+        #[arg(long)]
+        histogram: bool,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SummarizeTarget {
+    /// Summarize the current user's period
+    Me {
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        #[arg(long)]
+        force: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize a single task
+    Task {
+        /// Task GID or Asana URL
+        #[arg(value_name = "TASK_GID_OR_URL")]
+        task_gid: String,
+        /// Force regeneration (ignore cache)
+        #[arg(long)]
+        force: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize a user's period
+    User {
+        /// User GID or email address
+        #[arg(value_name = "USER_GID_OR_EMAIL")]
+        user_gid: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        #[arg(long, conflicts_with_all = ["history", "diff"])]
+        force: bool,
+        /// List every stored generation of the summary instead of generating one
+        #[arg(long, conflicts_with = "diff")]
+        history: bool,
+        /// Show what changed between the last two generations
+        #[arg(long)]
+        diff: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize a project's period
+    Project {
+        /// Project GID or Asana URL
+        #[arg(value_name = "PROJECT_GID_OR_URL")]
+        project_gid: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        #[arg(long, conflicts_with_all = ["history", "diff"])]
+        force: bool,
+        /// List every stored generation of the summary instead of generating one
+        #[arg(long, conflicts_with = "diff")]
+        history: bool,
+        /// Show what changed between the last two generations
         #[arg(long)]
+        diff: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize a portfolio's period
+    Portfolio {
+        /// Portfolio GID or Asana URL
+        #[arg(value_name = "PORTFOLIO_GID_OR_URL")]
+        portfolio_gid: String,
+        #[arg(long, default_value = "qtd")]
+        period: String,
+        #[arg(long, conflicts_with_all = ["history", "diff"])]
         force: bool,
+        /// List every stored generation of the summary instead of generating one
+        #[arg(long, conflicts_with = "diff")]
+        history: bool,
+        /// Show what changed between the last two generations
+        #[arg(long)]
+        diff: bool,
         #[arg(long)]
         json: bool,
     },
@@ -306,8 +1121,14 @@ enum SummarizeTarget {
         team_gid: String,
         #[arg(long, default_value = "qtd")]
         period: String,
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["history", "diff"])]
         force: bool,
+        /// List every stored generation of the summary instead of generating one
+        #[arg(long, conflicts_with = "diff")]
+        history: bool,
+        /// Show what changed between the last two generations
+        #[arg(long)]
+        diff: bool,
         #[arg(long)]
         json: bool,
     },
@@ -350,6 +1171,8 @@ enum SyncTarget {
         #[arg(long)]
         full: bool,
     },
+    /// Sync every team in the workspace and its members, without their projects
+    Teams,
     /// Sync a portfolio's projects
     Portfolio {
         /// Portfolio GID or Asana URL
@@ -361,6 +1184,9 @@ enum SyncTarget {
         /// Force a full sync (skip incremental)
         #[arg(long)]
         full: bool,
+        /// Levels of nested portfolios to sync
+        #[arg(long, default_value_t = asanadw::sync::DEFAULT_PORTFOLIO_DEPTH)]
+        max_depth: u32,
     },
     /// Sync all monitored entities
     All {
@@ -371,6 +1197,33 @@ enum SyncTarget {
         /// Force a full sync (skip incremental)
         #[arg(long)]
         full: bool,
+        /// Number of entities to sync at once
+        #[arg(long, default_value = "1")]
+        concurrency: usize,
+        /// Levels of nested portfolios to sync
+        #[arg(long, default_value_t = asanadw::sync::DEFAULT_PORTFOLIO_DEPTH)]
+        max_depth: u32,
+        /// Show what would be synced, with estimated task counts, without
+        /// fetching or writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Output the dry-run plan as JSON
+        #[arg(long, requires = "dry_run")]
+        json: bool,
+    },
+    /// Resume full syncs interrupted in the last 24 hours
+    Resume,
+    /// Show which days a project's syncs have covered, and the holes a
+    /// `sync project --since` would backfill
+    Gaps {
+        /// Project GID, Asana URL, or entity key (project:GID)
+        entity: String,
+        /// Start of the range to check (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -380,7 +1233,7 @@ enum MonitorAction {
     Add {
         /// Entity type: project, user, team, portfolio
         entity_type: String,
-        /// Entity GID or Asana URL
+        /// Entity GID or Asana URL, or a team name after `sync teams`
         identifier: String,
     },
     /// Add all favorited projects and portfolios to monitoring
@@ -392,34 +1245,252 @@ enum MonitorAction {
     },
     /// List monitored entities
     List,
+    /// Review projects that synced tasks belong to but that are not monitored
+    Review {
+        /// List discovered projects without prompting
+        #[arg(long)]
+        list: bool,
+    },
+    /// Set how often the daemon syncs an entity
+    Interval {
+        /// Entity key (e.g. project:123456)
+        entity_key: String,
+        /// Interval such as 15m, 2h, or 1d; "default" uses the sync_interval config
+        interval: String,
+    },
 }
 
 #[derive(Subcommand)]
-enum ConfigAction {
-    /// Get a config value
-    Get { key: String },
-    /// Set a config value
-    Set { key: String, value: String },
-    /// List all config values
-    List,
-}
-
-fn parse_since(since: Option<&str>) -> Option<chrono::NaiveDate> {
-    since.and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
-}
-
-/// Resolve a user identifier (GID, email, or name) to a numeric GID via the database.
-/// Falls back to returning the original identifier if no match is found in the DB.
-async fn resolve_user(db: &asanadw::Database, identifier: &str) -> anyhow::Result<String> {
-    let id = identifier.to_string();
-    let resolved = db
-        .reader()
-        .call(move |conn| asanadw::storage::repository::resolve_user_identifier(conn, &id))
-        .await?;
-    match resolved {
-        Some(gid) => Ok(gid),
-        None => {
-            log::warn!("Could not resolve user '{identifier}' in local database — using as-is");
+enum DbAction {
+    /// Show the database size and each table's row count and size
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run SQLite's integrity check
+    Check,
+    /// Rebuild the database file to reclaim space left by deleted rows
+    Vacuum {
+        /// Merge full-text search index segments first
+        #[arg(long)]
+        fts: bool,
+    },
+    /// Refresh the query planner's statistics
+    Analyze,
+    /// Delete tasks completed or deleted, and status updates posted, before a cutoff
+    Prune {
+        /// Age such as 90d, 6w, 18mo, or 2y
+        #[arg(long)]
+        older_than: String,
+        /// Append the pruned rows to this JSON-lines file first
+        #[arg(long)]
+        archive: Option<std::path::PathBuf>,
+        /// Count what would be pruned without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Fill in permalink URLs missing from rows synced by older versions
+    BackfillPermalinks {
+        /// Fetch permalinks from Asana (batched and rate-limited) instead of
+        /// only building them from each row's workspace
+        #[arg(long)]
+        api: bool,
+        /// Count what would be filled without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write a SQLCipher-encrypted copy of the database, keyed by ASANADW_DB_KEY
+    Encrypt {
+        /// Where to write the copy (default: the database path plus .encrypted)
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+        /// Also store the key in the OS keychain
+        #[arg(long)]
+        save_key: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportFormat {
+    /// Write each fact, dimension, and bridge table to a Parquet file
+    Parquet {
+        /// Output directory
+        #[arg(long)]
+        out: String,
+        /// Export only this table (repeatable; default: all tables)
+        #[arg(long)]
+        table: Vec<String>,
+        /// Pseudonymize users, strip emails, and truncate notes and comments
+        #[arg(long)]
+        anonymize: bool,
+        /// Salt for user pseudonyms; reuse it to keep them stable across exports
+        #[arg(long, requires = "anonymize")]
+        salt: Option<String>,
+    },
+    /// Write a user-to-user collaboration graph for a project or team
+    Graph {
+        /// Project GID, Asana URL, or alias
+        #[arg(long, required_unless_present = "team", conflicts_with = "team")]
+        project: Option<String>,
+        /// Team GID, Asana URL, or alias (edges between members only)
+        #[arg(long)]
+        team: Option<String>,
+        /// Only count activity in this period (e.g. 2025-Q1, ytd; default: all time)
+        #[arg(long)]
+        period: Option<String>,
+        /// Output format: graphml, dot, or json (adjacency list)
+        #[arg(long, default_value = "graphml")]
+        format: String,
+        /// Output file (default: stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Manage recurring exports run by the daemon
+    Schedule {
+        #[command(subcommand)]
+        action: ExportScheduleAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportScheduleAction {
+    /// Add a recurring export
+    Add {
+        /// Export format (parquet)
+        #[arg(long, default_value = "parquet")]
+        format: String,
+        /// Export only this table (repeatable; default: all tables)
+        #[arg(long)]
+        table: Vec<String>,
+        /// Five-field cron expression in UTC, e.g. "0 6 * * *"
+        #[arg(long)]
+        cron: String,
+        /// Output directory or s3://bucket/prefix (uploaded with the AWS CLI)
+        #[arg(long)]
+        out: String,
+    },
+    /// List export schedules
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove an export schedule
+    Remove {
+        /// Schedule ID (from `export schedule list`)
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    /// Point an alias at an entity, e.g. `alias set roadmap project:1209759542987106`
+    Set {
+        alias: String,
+        /// Entity key: project, portfolio, team, or user, a colon, and the GID
+        entity_key: String,
+    },
+    /// Remove an alias
+    Remove { alias: String },
+    /// List aliases
+    List,
+}
+
+#[derive(Subcommand)]
+enum WorkspaceAction {
+    /// List the workspaces the token can see; * marks the current one
+    List {
+        /// Show the workspaces already recorded, without calling Asana
+        #[arg(long)]
+        cached: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Make a workspace (GID or name) the default for syncs and monitors
+    Use { workspace: String },
+}
+
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// Add a profile, or replace one with the same name
+    Add {
+        name: String,
+        /// Asana personal access token (default: ASANA_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+        /// Workspace GID or name (default: the database's configured workspace)
+        #[arg(long)]
+        workspace: Option<String>,
+        /// Database path (default: ~/.asanadw/asanadw.db)
+        #[arg(long)]
+        db: Option<std::path::PathBuf>,
+        /// Also make it the current profile
+        #[arg(long)]
+        switch: bool,
+    },
+    /// List profiles; * marks the current one
+    List {
+        /// Output as JSON (tokens are omitted)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Make a profile the one commands use without --profile
+    Switch { name: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Get a config value
+    Get { key: String },
+    /// Set a config value
+    Set { key: String, value: String },
+    /// List all config values
+    List,
+    /// Check config values, export schedules, and secrets for mistakes
+    Validate {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List summary prompts and where each comes from, or print one
+    Prompts {
+        /// Prompt to print (e.g. project_period)
+        name: Option<String>,
+    },
+}
+
+fn parse_since(since: Option<&str>) -> Option<chrono::NaiveDate> {
+    since.and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+}
+
+/// Resolve a user identifier (GID, email, name, or user alias) to a numeric GID via the database.
+/// Falls back to returning the original identifier if no match is found in the DB.
+async fn resolve_user(db: &asanadw::Database, identifier: &str) -> anyhow::Result<String> {
+    use asanadw::storage::repository;
+
+    let id = identifier.to_string();
+    let resolved = db
+        .reader()
+        .call(move |conn| {
+            let alias = repository::get_alias(conn, &id)?;
+            match alias.as_deref().and_then(|k| k.strip_prefix("user:")) {
+                Some(gid) => Ok(Some(gid.to_string())),
+                None => repository::resolve_user_identifier(conn, &id),
+            }
+        })
+        .await?;
+    match resolved {
+        Some(gid) => Ok(gid),
+        None => {
+            log::warn!("Could not resolve user '{identifier}' in local database — using as-is");
             Ok(identifier.to_string())
         }
     }
@@ -428,8 +1499,11 @@ async fn resolve_user(db: &asanadw::Database, identifier: &str) -> anyhow::Resul
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    QUIET.store(cli.quiet || cli.machine, Ordering::Relaxed);
+    MACHINE.store(cli.machine, Ordering::Relaxed);
 
     let level = match cli.verbose {
+        0 if cli.quiet || cli.machine => "error",
         0 => "warn",
         1 => "info",
         2 => "debug",
@@ -437,17 +1511,439 @@ async fn main() -> anyhow::Result<()> {
     };
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level)).init();
 
-    let db = match &cli.db {
-        Some(path) => asanadw::Database::open_at(path).await?,
-        None => asanadw::Database::open().await?,
+    let profiles_path = asanadw::profile::Profiles::default_path()?;
+    let profiles = asanadw::profile::Profiles::load(&profiles_path)?;
+    if let Commands::Profile { action } = cli.command {
+        return handle_profile(profiles, &profiles_path, action);
+    }
+    let active = profiles.active(cli.profile.as_deref())?;
+    let account = active
+        .map_or(DEFAULT_OAUTH_ACCOUNT, |(name, _)| name)
+        .to_string();
+    let profile = active.map(|(_, profile)| profile.clone());
+    if matches!(cli.command, Commands::Login { .. } | Commands::Logout) {
+        return handle_login(&account, cli.command).await;
+    }
+    // ASANA_TOKEN is the fallback when neither the profile nor an OAuth
+    // login provides a token
+    let (token, oauth) = match profile.as_ref().and_then(|p| p.token.clone()) {
+        Some(token) => (Some(token), None),
+        None => match oauth_token(&account).await {
+            Some((token, renew_at)) => (Some(token), Some((account.clone(), renew_at))),
+            None => (None, None),
+        },
+    };
+    let mut cli = cli;
+    apply_profile(&mut cli, profile.as_ref());
+
+    let path = match &cli.db {
+        Some(path) => std::path::PathBuf::from(path),
+        None => asanadw::Database::default_path()?,
+    };
+    // `db encrypt` reads the plaintext database even when a key is set
+    let plaintext = matches!(
+        cli.command,
+        Commands::Db {
+            action: DbAction::Encrypt { .. }
+        }
+    );
+    if cli.read_only {
+        if let Some(what) = mutation(&cli.command) {
+            anyhow::bail!("{what} is not allowed with --read-only");
+        }
+    }
+    let db = open_database(&path, plaintext, cli.read_only).await?;
+
+    // `default_period` and `default_output` replace built-in flag defaults,
+    // so re-parse once they're known.
+    let defaults = asanadw::config::cli_defaults(&db).await?;
+    let cli = if defaults == asanadw::config::CliDefaults::default() {
+        cli
+    } else {
+        let matches = with_cli_defaults(Cli::command(), &defaults).get_matches();
+        let mut cli = Cli::from_arg_matches(&matches)?;
+        apply_profile(&mut cli, profile.as_ref());
+        cli
+    };
+    let cli = Cli {
+        oauth,
+        token,
+        ..cli
+    };
+
+    run(cli, db).await
+}
+
+/// Fill `--db` and `--workspace` from the active profile when not given.
+fn apply_profile(cli: &mut Cli, profile: Option<&asanadw::profile::Profile>) {
+    let Some(profile) = profile else {
+        return;
+    };
+    if cli.db.is_none() {
+        cli.db = profile.db.as_ref().map(|p| p.display().to_string());
+    }
+    if cli.workspace.is_none() {
+        cli.workspace = profile.workspace.clone();
+    }
+}
+
+fn handle_profile(
+    mut profiles: asanadw::profile::Profiles,
+    path: &std::path::Path,
+    action: ProfileAction,
+) -> anyhow::Result<()> {
+    match action {
+        ProfileAction::Add {
+            name,
+            token,
+            workspace,
+            db,
+            switch,
+        } => {
+            profiles.add(
+                &name,
+                asanadw::profile::Profile {
+                    token,
+                    workspace,
+                    db,
+                },
+            )?;
+            if switch {
+                profiles.switch(&name)?;
+            }
+            profiles.save(path)?;
+            println!("Saved profile {name} to {}", path.display());
+        }
+        ProfileAction::List { json } => {
+            if json {
+                let list: Vec<serde_json::Value> = profiles
+                    .profiles
+                    .iter()
+                    .map(|(name, p)| {
+                        serde_json::json!({
+                            "name": name,
+                            "current": profiles.current.as_deref() == Some(name.as_str()),
+                            "has_token": p.token.is_some(),
+                            "workspace": p.workspace,
+                            "db": p.db,
+                        })
+                    })
+                    .collect();
+                print_json(&list)?;
+                return Ok(());
+            }
+            if profiles.profiles.is_empty() {
+                println!("No profiles. Add one with: asanadw profile add <name> --token <token>");
+            }
+            for (name, p) in &profiles.profiles {
+                let marker = if profiles.current.as_deref() == Some(name.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                let mut details = Vec::new();
+                if let Some(w) = &p.workspace {
+                    details.push(format!("workspace {w}"));
+                }
+                if let Some(db) = &p.db {
+                    details.push(format!("db {}", db.display()));
+                }
+                if p.token.is_none() {
+                    details.push("token from ASANA_TOKEN".to_string());
+                }
+                if details.is_empty() {
+                    println!("{marker} {name}");
+                } else {
+                    println!("{marker} {name} ({})", details.join(", "));
+                }
+            }
+        }
+        ProfileAction::Switch { name } => {
+            profiles.switch(&name)?;
+            profiles.save(path)?;
+            println!("Now using profile {name}.");
+        }
+    }
+    Ok(())
+}
+
+/// OAuth session name used outside any profile.
+const DEFAULT_OAUTH_ACCOUNT: &str = "default";
+
+/// `account`'s OAuth access token, refreshed first if it is about to
+/// expire, and when it should next be refreshed. `None` when the account
+/// hasn't logged in.
+#[cfg(feature = "oauth")]
+async fn oauth_token(account: &str) -> Option<(String, chrono::DateTime<chrono::Utc>)> {
+    use asanadw::auth;
+
+    match auth::access_token(account).await {
+        Ok(Some(session)) => {
+            let renew_at =
+                session.expires_at - chrono::Duration::seconds(auth::REFRESH_MARGIN_SECS);
+            Some((session.access_token, renew_at))
+        }
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!("OAuth login unusable, falling back to ASANA_TOKEN: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "oauth"))]
+async fn oauth_token(_account: &str) -> Option<(String, chrono::DateTime<chrono::Utc>)> {
+    None
+}
+
+/// An Asana client using `token`, or `ASANA_TOKEN` without one.
+fn asana_client(
+    token: Option<&str>,
+    page_size: Option<u32>,
+) -> anyhow::Result<asanaclient::Client> {
+    let client = match token {
+        Some(token) => asanaclient::Client::new(token),
+        None => asanaclient::Client::from_env()?,
+    };
+    Ok(match page_size {
+        Some(ps) => client.with_page_size(ps),
+        None => client,
+    })
+}
+
+/// Wait until `renew_at`, or forever without one.
+async fn oauth_renewal(renew_at: Option<chrono::DateTime<chrono::Utc>>) {
+    match renew_at {
+        Some(at) => {
+            let wait = (at - chrono::Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(feature = "oauth")]
+async fn handle_login(account: &str, command: Commands) -> anyhow::Result<()> {
+    use asanadw::auth;
+
+    match command {
+        Commands::Login {
+            client_id,
+            client_secret,
+            port,
+            no_browser,
+        } => {
+            let client_id = client_id
+                .or_else(|| std::env::var(auth::CLIENT_ID_ENV).ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No OAuth client ID. Register an app at https://app.asana.com/0/my-apps \
+                         with redirect URL http://localhost:{port}/callback, then pass \
+                         --client-id or set {}",
+                        auth::CLIENT_ID_ENV
+                    )
+                })?;
+            let client_secret =
+                client_secret.or_else(|| std::env::var(auth::CLIENT_SECRET_ENV).ok());
+            let session = auth::login(
+                account,
+                auth::LoginOptions {
+                    client_id,
+                    client_secret,
+                    port,
+                    open_browser: !no_browser,
+                },
+                // Shown even under --quiet: without a browser, logging in
+                // needs it
+                |url| eprintln!("Open this URL to log in to Asana:\n\n  {url}\n"),
+            )
+            .await?;
+            match &session.user_email {
+                Some(email) => println!("Logged in as {email}."),
+                None => println!("Logged in."),
+            }
+        }
+        Commands::Logout => {
+            if auth::logout(account)? {
+                println!("Logged out.");
+            } else {
+                println!("Not logged in.");
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "oauth"))]
+async fn handle_login(_account: &str, _command: Commands) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "OAuth login is not enabled in this build. Reinstall with: cargo install asanadw --features oauth"
+    )
+}
+
+/// Open the database encrypted when a key is configured.
+#[cfg(feature = "sqlcipher")]
+async fn open_database(
+    path: &std::path::Path,
+    plaintext: bool,
+    read_only: bool,
+) -> anyhow::Result<asanadw::Database> {
+    use asanadw::Database;
+
+    let key = if plaintext {
+        None
+    } else {
+        asanadw::storage::encryption::database_key()?
     };
+    Ok(match (key, read_only) {
+        (Some(key), true) => Database::open_encrypted_read_only(path, &key).await?,
+        (Some(key), false) => Database::open_encrypted(path, &key).await?,
+        (None, true) => Database::open_read_only(path).await?,
+        (None, false) => Database::open_at(path).await?,
+    })
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+async fn open_database(
+    path: &std::path::Path,
+    _plaintext: bool,
+    read_only: bool,
+) -> anyhow::Result<asanadw::Database> {
+    Ok(if read_only {
+        asanadw::Database::open_read_only(path).await?
+    } else {
+        asanadw::Database::open_at(path).await?
+    })
+}
 
+/// What `command` would change in the database, if anything, for
+/// rejecting it under `--read-only` before it starts.
+fn mutation(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Sync {
+            target: SyncTarget::All { dry_run: true, .. } | SyncTarget::Gaps { .. },
+            ..
+        } => None,
+        Commands::Sync { .. } => Some("sync"),
+        Commands::Daemon => Some("daemon"),
+        Commands::Watch { .. } => Some("watch"),
+        Commands::Monitor {
+            action: MonitorAction::List | MonitorAction::Review { list: true },
+        } => None,
+        Commands::Monitor { .. } => Some("changing monitored entities"),
+        Commands::Config {
+            action: ConfigAction::Set { .. },
+        } => Some("config set"),
+        Commands::Workspace {
+            action: WorkspaceAction::Use { .. },
+        } => Some("workspace use"),
+        Commands::Alias {
+            action: AliasAction::Set { .. } | AliasAction::Remove { .. },
+        } => Some("changing aliases"),
+        Commands::Query {
+            source: Some(QuerySource::Save { .. } | QuerySource::Unsave { .. }),
+            ..
+        } => Some("changing saved queries"),
+        Commands::Undo { list: false } => Some("undo"),
+        Commands::Orphans { refresh: true, .. } => Some("orphans --refresh"),
+        Commands::Search {
+            verify_index: true, ..
+        } => Some("search --verify-index"),
+        Commands::Risks { .. } => Some("risk scans"),
+        Commands::Db {
+            action:
+                DbAction::Vacuum { .. }
+                | DbAction::Analyze
+                | DbAction::Prune { .. }
+                | DbAction::BackfillPermalinks { dry_run: false, .. }
+                | DbAction::Encrypt { .. },
+        } => Some("database maintenance"),
+        Commands::Export {
+            format:
+                ExportFormat::Schedule {
+                    action: ExportScheduleAction::Add { .. } | ExportScheduleAction::Remove { .. },
+                },
+        } => Some("changing export schedules"),
+        Commands::Import { .. } => Some("import"),
+        _ => None,
+    }
+}
+
+/// Swap the `qtd` default of every `--period` for the configured period, and
+/// default every `--json` to on when `default_output` is `json`.
+fn with_cli_defaults(cmd: clap::Command, defaults: &asanadw::config::CliDefaults) -> clap::Command {
+    cmd.mut_args(|arg| match arg.get_id().as_str() {
+        "period"
+            if arg
+                .get_default_values()
+                .iter()
+                .any(|v| v.to_str() == Some("qtd")) =>
+        {
+            match &defaults.period {
+                Some(period) => arg.default_value(period.clone()),
+                None => arg,
+            }
+        }
+        "json" if defaults.json => arg.default_value("true"),
+        _ => arg,
+    })
+    .mut_subcommands(|sub| with_cli_defaults(sub, defaults))
+}
+
+async fn run(cli: Cli, db: asanadw::Database) -> anyhow::Result<()> {
     match cli.command {
-        Commands::Status => {
-            print_status(&db).await?;
+        Commands::Status {
+            schedule,
+            llm,
+            period,
+            json,
+        } => {
+            if schedule {
+                print_schedule(&db).await?;
+            } else if llm {
+                print_llm_usage(&db, &period, json).await?;
+            } else {
+                print_status(&db).await?;
+            }
         }
         Commands::Config { action } => {
-            handle_config(&db, action).await?;
+            handle_config(&db, action, cli.token.as_deref()).await?;
+        }
+        Commands::Workspace { action } => {
+            let client = asana_client(cli.token.as_deref(), None)?;
+            let dw = asana_dw(db, client, cli.workspace.as_deref()).await?;
+            handle_workspace(&dw, action).await?;
+        }
+        // Handled in main, before any database is opened
+        Commands::Profile { .. } | Commands::Login { .. } | Commands::Logout => unreachable!(),
+        Commands::Alias { action } => {
+            handle_alias(&db, action).await?;
+        }
+        Commands::Undo { list } => {
+            handle_undo(&db, list).await?;
+        }
+        Commands::Search {
+            verify_index: true,
+            json,
+            ..
+        } => {
+            let checks = asanadw::search::index::verify_index(&db, true).await?;
+            if json {
+                print_json(&checks)?;
+            } else {
+                for c in &checks {
+                    let state = match (c.in_sync, c.repaired) {
+                        (true, _) => "ok",
+                        (false, true) => "drifted, rebuilt",
+                        (false, false) => "drifted",
+                    };
+                    println!(
+                        "  {:<20} {:>8} indexed / {:>8} in {:<24} {state}",
+                        c.index, c.indexed_rows, c.source_rows, c.source_table
+                    );
+                }
+            }
         }
         Commands::Search {
             query,
@@ -455,8 +1951,17 @@ async fn main() -> anyhow::Result<()> {
             assignee,
             mine,
             project,
+            completed,
+            incomplete,
+            subtype,
+            created_after,
+            created_before,
+            modified_after,
+            modified_before,
             limit,
+            offset,
             json,
+            ..
         } => {
             let query = query.join(" ");
             let effective_assignee = if mine {
@@ -471,85 +1976,430 @@ async fn main() -> anyhow::Result<()> {
             } else {
                 assignee
             };
+            let options = asanadw::SearchOptions {
+                limit: Some(limit),
+                offset: Some(offset),
+                project_gid: project,
+                completed: (completed || incomplete).then_some(completed),
+                created_after,
+                created_before,
+                modified_after,
+                modified_before,
+                subtype,
+                ..Default::default()
+            };
             handle_search(
                 &db,
                 &query,
                 r#type.as_deref(),
                 effective_assignee.as_deref(),
-                project.as_deref(),
-                limit,
+                options,
                 json,
             )
             .await?;
         }
         Commands::Query {
-            project,
-            portfolio,
-            team,
-            assignee,
-            mine,
-            completed,
-            incomplete,
-            overdue,
-            created_after,
-            created_before,
-            due_after,
-            due_before,
-            limit,
-            json,
-            csv,
-            count,
+            source: Some(QuerySource::Comments { filters }),
+            ..
         } => {
-            let effective_assignee = if mine {
-                let gid = db
-                    .reader()
-                    .call(|c| asanadw::storage::repository::get_config(c, "user_gid"))
-                    .await?
-                    .ok_or_else(|| {
-                        anyhow::anyhow!("User identity not set. Run 'asanadw sync all' first.")
-                    })?;
-                Some(gid)
-            } else {
-                assignee
-            };
-            handle_query(
+            handle_query_text(
                 &db,
-                project.as_deref(),
-                portfolio.as_deref(),
-                team.as_deref(),
-                effective_assignee.as_deref(),
-                completed,
-                incomplete,
-                overdue,
-                created_after.as_deref(),
-                created_before.as_deref(),
-                due_after.as_deref(),
-                due_before.as_deref(),
-                limit,
-                json,
-                csv,
-                count,
+                asanadw::CommentQueryBuilder::comments(),
+                filters,
+                "comments",
             )
             .await?;
         }
-        Commands::Summarize { target } => {
-            handle_summarize(&db, target).await?;
+        Commands::Query {
+            source: Some(QuerySource::StatusUpdates { filters }),
+            ..
+        } => {
+            handle_query_text(
+                &db,
+                asanadw::CommentQueryBuilder::status_updates(),
+                filters,
+                "status updates",
+            )
+            .await?;
         }
-        Commands::Metrics { target } => {
-            handle_metrics(&db, target).await?;
+        Commands::Query {
+            source:
+                Some(QuerySource::Save {
+                    name,
+                    description,
+                    filters,
+                    limit,
+                }),
+            ..
+        } => {
+            let filters = filters.into_filters(limit);
+            asanadw::query::saved::save_query(&db, &name, description.as_deref(), &filters).await?;
+            println!("Saved: {name}");
         }
-        Commands::Monitor { action } => {
-            let client = asanaclient::Client::from_env()?;
-            let dw = asanadw::AsanaDW::new(db, client);
+        Commands::Query {
+            source:
+                Some(QuerySource::Run {
+                    name,
+                    limit,
+                    format,
+                    fetch,
+                }),
+            ..
+        } => {
+            let mut filters = asanadw::query::saved::get_query(&db, &name)
+                .await?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("No saved query named '{name}'. See `asanadw query saved`.")
+                })?
+                .filters;
+            if limit.is_some() {
+                filters.limit = limit;
+            }
+            ensure_filters_synced(
+                &db,
+                cli.workspace.as_deref(),
+                &filters,
+                fetch,
+                cli.force_unlock,
+                cli.token.as_deref(),
+            )
+            .await?;
+            handle_query(&db, &filters, None, &format).await?;
+        }
+        Commands::Query {
+            source: Some(QuerySource::Saved { json }),
+            ..
+        } => {
+            let queries = asanadw::query::saved::list_queries(&db).await?;
+            if json {
+                print_json(&queries)?;
+            } else if queries.is_empty() {
+                println!("No saved queries.");
+            } else {
+                for q in &queries {
+                    println!("  {:<20} {}", q.name, serde_json::to_string(&q.filters)?);
+                    if let Some(description) = &q.description {
+                        println!("  {:<20} {description}", "");
+                    }
+                }
+            }
+        }
+        Commands::Query {
+            source: Some(QuerySource::Unsave { name }),
+            ..
+        } => {
+            if asanadw::query::saved::remove_query(&db, &name).await? {
+                println!("Removed: {name}");
+            } else {
+                println!("Not found: {name}");
+            }
+        }
+        Commands::Query {
+            source: None,
+            filters,
+            urls,
+            limit,
+            format,
+            fetch,
+        } => {
+            let filters = filters.into_filters(Some(limit));
+            ensure_filters_synced(
+                &db,
+                cli.workspace.as_deref(),
+                &filters,
+                fetch,
+                cli.force_unlock,
+                cli.token.as_deref(),
+            )
+            .await?;
+            handle_query(&db, &filters, urls.as_deref(), &format).await?;
+        }
+        Commands::Summarize { target, fetch } => {
+            if let Some((entity_type, identifier)) = summarize_entity(&target) {
+                ensure_synced(
+                    &db,
+                    cli.workspace.as_deref(),
+                    entity_type,
+                    identifier,
+                    fetch,
+                    cli.force_unlock,
+                    cli.token.as_deref(),
+                )
+                .await?;
+            }
+            handle_summarize(&db, target).await?;
+        }
+        Commands::Ask {
+            question,
+            max_steps,
+            json,
+        } => {
+            handle_ask(&db, &question.join(" "), max_steps, json).await?;
+        }
+        Commands::Repl { max_steps } => {
+            run_repl(&db, max_steps).await?;
+        }
+        Commands::Digest {
+            period,
+            email_format,
+            force,
+            json,
+        } => {
+            handle_digest(&db, &period, email_format, force, json).await?;
+        }
+        Commands::Risks {
+            period,
+            min_severity,
+            force,
+            json,
+        } => {
+            handle_risks(&db, &period, min_severity.as_deref(), force, json).await?;
+        }
+        Commands::Metrics { target, fetch } => {
+            if let Some((entity_type, identifier)) = metrics_entity(&target) {
+                ensure_synced(
+                    &db,
+                    cli.workspace.as_deref(),
+                    entity_type,
+                    identifier,
+                    fetch,
+                    cli.force_unlock,
+                    cli.token.as_deref(),
+                )
+                .await?;
+            }
+            handle_metrics(&db, target).await?;
+        }
+        Commands::Orphans {
+            entity_type,
+            identifier,
+            refresh,
+            llm,
+            candidates,
+            json,
+        } => {
+            if refresh {
+                let client = asana_client(cli.token.as_deref(), None)?;
+                let dw = asana_dw(db.clone(), client, cli.workspace.as_deref()).await?;
+                let inactive = dw.refresh_user_activity().await?;
+                note!("Refreshed user activity: {inactive} deactivated users");
+            }
+            handle_orphans(&db, &entity_type, &identifier, llm, candidates, json).await?;
+        }
+        Commands::Hot { limit, days, json } => {
+            handle_hot(&db, limit, days, json).await?;
+        }
+        Commands::Recognition { period, json } => {
+            handle_recognition(&db, &period, json).await?;
+        }
+        Commands::Cluster {
+            project,
+            period,
+            clusters,
+            json,
+        } => {
+            handle_cluster(&db, &project, &period, clusters, json).await?;
+        }
+        Commands::Stats { target } => {
+            handle_stats(&db, target).await?;
+        }
+        Commands::Report {
+            report:
+                ReportKind::Onboarding {
+                    user,
+                    weeks,
+                    since,
+                    json,
+                },
+        } => {
+            handle_onboarding_report(&db, &user, weeks, since.as_deref(), json).await?;
+        }
+        Commands::Report {
+            report:
+                ReportKind::Project {
+                    project_gid,
+                    period,
+                    format,
+                    out,
+                    fetch,
+                },
+        } => {
+            ensure_synced(
+                &db,
+                cli.workspace.as_deref(),
+                "project",
+                &project_gid,
+                fetch,
+                cli.force_unlock,
+                cli.token.as_deref(),
+            )
+            .await?;
+            handle_project_report(&db, &project_gid, &period, &format, out.as_deref()).await?;
+        }
+        Commands::Sql { query, json, csv } => {
+            handle_sql(&db, &query.join(" "), json, csv).await?;
+        }
+        Commands::Db {
+            action: DbAction::BackfillPermalinks { api, dry_run, json },
+        } => {
+            let report = if api {
+                let client = asana_client(cli.token.as_deref(), None)?;
+                let dw = asana_dw(db.clone(), client, cli.workspace.as_deref()).await?;
+                dw.backfill_permalinks(dry_run).await?
+            } else {
+                asanadw::sync::permalinks::backfill_permalinks(&db, None, dry_run).await?
+            };
+            if json {
+                print_json(&report)?;
+            } else {
+                let verb = if dry_run { "Would fill" } else { "Filled" };
+                for c in &report.counts {
+                    println!(
+                        "{:<10} {} missing: {verb} {} from Asana, {} generated; {} skipped",
+                        format!("{}s", c.entity_type),
+                        c.missing,
+                        c.from_api,
+                        c.generated,
+                        c.skipped
+                    );
+                }
+                if report.counts.iter().any(|c| c.skipped > 0) {
+                    println!("Set workspace_gid (or use --api) to fill skipped rows.");
+                }
+            }
+        }
+        Commands::Db { action } => {
+            handle_db(&db, cli.db.as_deref(), action).await?;
+        }
+        Commands::Export { format } => {
+            handle_export(&db, format).await?;
+        }
+        Commands::Import { source } => {
+            handle_import(&db, source).await?;
+        }
+        Commands::Task { identifier, json } => {
+            handle_task(&db, &identifier, json).await?;
+        }
+        Commands::StatusUpdates {
+            project,
+            limit,
+            json,
+        } => {
+            handle_status_updates(&db, project.as_deref(), limit, json).await?;
+        }
+        Commands::Mcp => {
+            asanadw::mcp::serve_stdio(&db).await?;
+        }
+        Commands::Grpc { addr } => {
+            serve_grpc(db, addr).await?;
+        }
+        Commands::Monitor { action } => {
+            let client = asana_client(cli.token.as_deref(), None)?;
+            let dw = asana_dw(db, client, cli.workspace.as_deref()).await?;
             handle_monitor(&dw, action).await?;
         }
-        Commands::Sync { target } => {
-            let mut client = asanaclient::Client::from_env()?;
-            if let Some(ps) = cli.page_size {
-                client = client.with_page_size(ps);
+        Commands::Sync {
+            target,
+            progress_json,
+        } => {
+            let client = asana_client(cli.token.as_deref(), cli.page_size)?;
+            let dw = asana_dw(db, client, cli.workspace.as_deref()).await?;
+            // A dry run or coverage check writes nothing, so it doesn't need the lock
+            let lock = if matches!(
+                target,
+                SyncTarget::All { dry_run: true, .. } | SyncTarget::Gaps { .. }
+            ) {
+                None
+            } else {
+                Some(asanadw::sync::lock::SyncLock::acquire(dw.db(), cli.force_unlock).await?)
+            };
+            let result = handle_sync(&dw, target, cli.comment_concurrency, progress_json).await;
+            if let Some(lock) = lock {
+                lock.release().await?;
+            }
+            result?;
+        }
+        Commands::Daemon => {
+            let lock = asanadw::sync::lock::SyncLock::acquire(&db, cli.force_unlock).await?;
+            let interval = asanadw::sync::scheduler::default_interval(&db).await?;
+            note!("asanadw daemon started (default interval: {interval} minutes). Ctrl-C to stop.");
+            let options = make_sync_options(None, None, false, cli.comment_concurrency);
+            let stop = std::sync::Arc::new(tokio::sync::Notify::new());
+            tokio::spawn({
+                let stop = stop.clone();
+                async move {
+                    let _ = tokio::signal::ctrl_c().await;
+                    stop.notify_one();
+                }
+            });
+            let result = async {
+                let mut oauth = cli.oauth.clone();
+                let mut token = cli.token.clone();
+                loop {
+                    let client = asana_client(token.as_deref(), cli.page_size)?;
+                    let dw = asana_dw(db.clone(), client, cli.workspace.as_deref()).await?;
+                    // An OAuth access token expires within hours, so stop
+                    // between rounds to refresh it and carry on
+                    let renewing = std::sync::atomic::AtomicBool::new(false);
+                    let shutdown = async {
+                        tokio::select! {
+                            _ = stop.notified() => {}
+                            _ = oauth_renewal(oauth.as_ref().map(|(_, at)| *at)) => {
+                                renewing.store(true, std::sync::atomic::Ordering::Relaxed);
+                            }
+                        }
+                    };
+                    asanadw::sync::scheduler::run_daemon(
+                        &dw,
+                        &options,
+                        stderr_progress(),
+                        shutdown,
+                    )
+                    .await?;
+                    if !renewing.load(std::sync::atomic::Ordering::Relaxed) {
+                        return anyhow::Ok(());
+                    }
+                    let account = oauth.take().map(|(account, _)| account).unwrap_or_default();
+                    match oauth_token(&account).await {
+                        Some((renewed, renew_at)) => {
+                            token = Some(renewed);
+                            oauth = Some((account, renew_at));
+                        }
+                        None => {
+                            log::warn!("Could not refresh the OAuth token; later syncs may fail")
+                        }
+                    }
+                }
             }
-            let dw = asanadw::AsanaDW::new(db, client);
-            handle_sync(&dw, target).await?;
+            .await;
+            lock.release().await?;
+            result?;
+            note!("asanadw daemon stopped.");
+        }
+        Commands::Watch {
+            project,
+            interval,
+            json,
+        } => {
+            let client = asana_client(cli.token.as_deref(), cli.page_size)?;
+            let dw = asana_dw(db, client, cli.workspace.as_deref()).await?;
+            let lock = asanadw::sync::lock::SyncLock::acquire(dw.db(), cli.force_unlock).await?;
+            note!("Watching {project} every {interval}s. Ctrl-C to stop.");
+            let options = make_sync_options(None, None, false, cli.comment_concurrency);
+            let shutdown = async {
+                let _ = tokio::signal::ctrl_c().await;
+            };
+            let result = asanadw::sync::watch::watch_project(
+                &dw,
+                &project,
+                &options,
+                std::time::Duration::from_secs(interval.max(1)),
+                &WatchProgress { json },
+                shutdown,
+            )
+            .await;
+            lock.release().await?;
+            result?;
         }
     }
 
@@ -582,11 +2432,15 @@ async fn print_status(db: &asanadw::Database) -> anyhow::Result<()> {
                 )
                 .ok();
 
-            Ok::<_, rusqlite::Error>((tasks, projects, users, comments, monitored, last_sync))
+            let api_usage = asanadw::storage::repository::api_usage_since(conn, 24)?;
+
+            Ok::<_, rusqlite::Error>((
+                tasks, projects, users, comments, monitored, last_sync, api_usage,
+            ))
         })
         .await?;
 
-    let (tasks, projects, users, comments, monitored, last_sync) = stats;
+    let (tasks, projects, users, comments, monitored, last_sync, api_usage) = stats;
     println!("Warehouse Status");
     println!("  Tasks:     {tasks}");
     println!("  Projects:  {projects}");
@@ -597,10 +2451,182 @@ async fn print_status(db: &asanadw::Database) -> anyhow::Result<()> {
         "  Last sync: {}",
         last_sync.unwrap_or_else(|| "never".to_string())
     );
+    println!("  API (24h): {}", format_api_usage(&api_usage));
+    if let Some(lock) = asanadw::sync::lock::current_holder(db).await? {
+        println!(
+            "  Syncing:   PID {} since {} UTC (heartbeat {} UTC)",
+            lock.pid, lock.acquired_at, lock.heartbeat_at
+        );
+    }
+    Ok(())
+}
+
+async fn print_llm_usage(db: &asanadw::Database, period: &str, json: bool) -> anyhow::Result<()> {
+    let p = asanadw::Period::parse(period)?;
+    let usage = asanadw::storage::llm_usage::usage_summary(db, &p).await?;
+    if json {
+        print_json(&usage)?;
+        return Ok(());
+    }
+    if usage.is_empty() {
+        println!("No LLM calls in {}.", p.to_key());
+        return Ok(());
+    }
+
+    println!("LLM Usage ({})", p.to_key());
+    for u in &usage {
+        println!("  {} ({} {})", u.agent, u.provider, u.model);
+        let latency = u
+            .avg_latency_ms
+            .map(|ms| format!("{:.1}s avg", ms / 1000.0))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "    {} calls | {} cache hits | {} failed | {latency}",
+            u.calls, u.cache_hits, u.failures
+        );
+        let estimated = if u.estimated_calls > 0 {
+            format!(" ({} calls estimated)", u.estimated_calls)
+        } else {
+            String::new()
+        };
+        println!(
+            "    tokens: {} in, {} out{estimated}",
+            u.input_tokens, u.output_tokens
+        );
+        if u.parse_failures > 0 {
+            println!(
+                "    {} unparseable replies (see llm_parse_failures)",
+                u.parse_failures
+            );
+        }
+    }
+    let (input, output): (u64, u64) = usage.iter().fold((0, 0), |(i, o), u| {
+        (i + u.input_tokens, o + u.output_tokens)
+    });
+    println!("  Total tokens: {input} in, {output} out");
+    Ok(())
+}
+
+async fn print_schedule(db: &asanadw::Database) -> anyhow::Result<()> {
+    let default_interval = asanadw::sync::scheduler::default_interval(db).await?;
+    let schedule = asanadw::sync::scheduler::load_schedule(db).await?;
+    if schedule.is_empty() {
+        println!("No monitored entities.");
+        return Ok(());
+    }
+
+    println!("Sync Schedule (default interval: {default_interval} minutes, times UTC)");
+    for row in &schedule {
+        let interval = match row.interval_minutes {
+            Some(m) => format!("{m}m"),
+            None => format!("{default_interval}m (default)"),
+        };
+        println!(
+            "  {} {}",
+            row.entity_key,
+            row.display_name.as_deref().unwrap_or("")
+        );
+        println!(
+            "    every {interval} | next: {} | last: {} ({})",
+            row.next_run_at.as_deref().unwrap_or("due now"),
+            row.last_run_at.as_deref().unwrap_or("never"),
+            row.last_status.as_deref().unwrap_or("-"),
+        );
+        if row.consecutive_failures > 0 {
+            println!(
+                "    {} consecutive failures: {}",
+                row.consecutive_failures,
+                row.last_error.as_deref().unwrap_or("unknown error")
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn handle_alias(db: &asanadw::Database, action: AliasAction) -> anyhow::Result<()> {
+    match action {
+        AliasAction::Set { alias, entity_key } => {
+            asanadw::alias::set_alias(db, &alias, &entity_key).await?;
+            println!("{alias} -> {entity_key}");
+        }
+        AliasAction::Remove { alias } => {
+            if asanadw::alias::remove_alias(db, &alias).await? {
+                println!("Removed: {alias}");
+            } else {
+                println!("Not found: {alias}");
+            }
+        }
+        AliasAction::List => {
+            let aliases = asanadw::alias::list_aliases(db).await?;
+            if aliases.is_empty() {
+                println!("No aliases set.");
+            }
+            for (alias, entity_key) in aliases {
+                println!("  {alias:<20} {entity_key}");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_undo(db: &asanadw::Database, list: bool) -> anyhow::Result<()> {
+    use asanadw::storage::repository::{LocalChange, LOCAL_CHANGE_MONITOR_REMOVE};
+
+    let describe = |c: &LocalChange| {
+        if c.change_type == LOCAL_CHANGE_MONITOR_REMOVE {
+            format!("monitor remove {}", c.target_key)
+        } else {
+            let shown = |v: &Option<String>| match v {
+                _ if c.target_key == "llm_api_key" => "(hidden)".to_string(),
+                Some(v) => v.clone(),
+                None => "(unset)".to_string(),
+            };
+            format!(
+                "config set {}: {} -> {}",
+                c.target_key,
+                shown(&c.old_value),
+                shown(&c.new_value)
+            )
+        }
+    };
+
+    if list {
+        let changes = db
+            .reader()
+            .call(|conn| asanadw::storage::repository::recent_local_changes(conn, 20))
+            .await?;
+        if changes.is_empty() {
+            println!("No local changes recorded.");
+        }
+        for c in &changes {
+            let state = if c.undone_at.is_some() {
+                "  (undone)"
+            } else {
+                ""
+            };
+            println!("  {}  {}{state}", c.changed_at, describe(c));
+        }
+        return Ok(());
+    }
+
+    let undone = db
+        .writer()
+        .call(|conn| asanadw::storage::repository::undo_last_local_change(conn))
+        .await?;
+    match undone {
+        Some(c) => println!("Undid: {}", describe(&c)),
+        None => println!("Nothing to undo."),
+    }
     Ok(())
 }
 
-async fn handle_config(db: &asanadw::Database, action: ConfigAction) -> anyhow::Result<()> {
+/// `token` is the Asana token from a profile or OAuth login, which stands in
+/// for `ASANA_TOKEN` when validating.
+async fn handle_config(
+    db: &asanadw::Database,
+    action: ConfigAction,
+    token: Option<&str>,
+) -> anyhow::Result<()> {
     match action {
         ConfigAction::Get { key } => {
             let val: Option<String> = db
@@ -616,13 +2642,28 @@ async fn handle_config(db: &asanadw::Database, action: ConfigAction) -> anyhow::
             }
         }
         ConfigAction::Set { key, value } => {
+            let is_llm = key.starts_with("llm_");
+            if is_llm || key.starts_with("prompt.") {
+                if let Some(issue) = asanadw::config::validate_entry(&key, &value)
+                    .filter(|i| i.severity == asanadw::config::Severity::Error)
+                {
+                    anyhow::bail!("Invalid {}: {}", issue.key, issue.message);
+                }
+            }
             db.writer()
                 .call(move |conn| {
-                    asanadw::storage::repository::set_config(conn, &key, &value)?;
+                    asanadw::storage::repository::overwrite_config(conn, &key, &value)?;
                     Ok::<(), rusqlite::Error>(())
                 })
                 .await?;
             println!("Config updated.");
+            if is_llm {
+                // Settings are checked together, so a half-configured
+                // provider is reported rather than rejected
+                for issue in asanadw::config::validate_llm(db).await? {
+                    println!("  note: {}: {}", issue.key, issue.message);
+                }
+            }
         }
         ConfigAction::List => {
             let items: Vec<(String, String)> = db
@@ -633,10 +2674,121 @@ async fn handle_config(db: &asanadw::Database, action: ConfigAction) -> anyhow::
                 println!("No configuration set.");
             } else {
                 for (k, v) in items {
-                    println!("{k} = {v}");
+                    if k == "llm_api_key" {
+                        println!("{k} = (hidden)");
+                    } else {
+                        println!("{k} = {v}");
+                    }
                 }
             }
         }
+        ConfigAction::Validate { json } => {
+            let mut issues = asanadw::config::validate(db).await?;
+            if token.is_some() {
+                issues.retain(|i| i.key != "ASANA_TOKEN");
+            }
+            let errors = issues
+                .iter()
+                .filter(|i| i.severity == asanadw::config::Severity::Error)
+                .count();
+            if json {
+                print_json(&issues)?;
+            } else if issues.is_empty() {
+                println!("Configuration OK.");
+            } else {
+                for issue in &issues {
+                    let label = match issue.severity {
+                        asanadw::config::Severity::Error => "error",
+                        asanadw::config::Severity::Warning => "warning",
+                    };
+                    println!("  {label}: {}: {}", issue.key, issue.message);
+                }
+                println!("{errors} errors, {} warnings", issues.len() - errors);
+            }
+            if errors > 0 {
+                anyhow::bail!("configuration has {errors} errors");
+            }
+        }
+        ConfigAction::Prompts { name } => handle_prompts(db, name).await?,
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn handle_prompts(_db: &asanadw::Database, _name: Option<String>) -> anyhow::Result<()> {
+    anyhow::bail!("{LLM_DISABLED}")
+}
+
+#[cfg(feature = "llm")]
+async fn handle_prompts(db: &asanadw::Database, name: Option<String>) -> anyhow::Result<()> {
+    use asanadw::llm::prompts::{self, PromptSource};
+
+    if let Some(name) = name {
+        let prompt = prompts::load_prompt(db, &name).await?;
+        println!("{}", prompt.template);
+        return Ok(());
+    }
+    for prompt in prompts::list_prompts(db).await? {
+        let source = match &prompt.source {
+            PromptSource::BuiltIn => "built-in".to_string(),
+            PromptSource::Config => format!("config {}{}", prompts::CONFIG_PREFIX, prompt.name),
+            PromptSource::File(path) => path.display().to_string(),
+        };
+        println!("{:<18} {:<28} {source}", prompt.name, prompt.version);
+    }
+    Ok(())
+}
+
+/// An `AsanaDW` working in `workspace` (GID or name) when given.
+async fn asana_dw(
+    db: asanadw::Database,
+    client: asanaclient::Client,
+    workspace: Option<&str>,
+) -> anyhow::Result<asanadw::AsanaDW> {
+    let dw = asanadw::AsanaDW::new(db, client);
+    Ok(match workspace {
+        Some(workspace) => {
+            let gid = dw.resolve_workspace(workspace).await?.workspace_gid;
+            dw.with_workspace(gid)
+        }
+        None => dw,
+    })
+}
+
+async fn handle_workspace(dw: &asanadw::AsanaDW, action: WorkspaceAction) -> anyhow::Result<()> {
+    match action {
+        WorkspaceAction::List { cached, json } => {
+            let workspaces = if cached || dw.is_read_only() {
+                dw.workspaces().await?
+            } else {
+                dw.refresh_workspaces().await?
+            };
+            // Without one configured, a single workspace is the current one
+            let current = match dw.config_get("workspace_gid").await? {
+                Some(gid) => Some(gid),
+                None if workspaces.len() == 1 => Some(workspaces[0].workspace_gid.clone()),
+                None => None,
+            };
+            if json {
+                print_json(&workspaces)?;
+                return Ok(());
+            }
+            if workspaces.is_empty() {
+                println!("No workspaces recorded. Run: asanadw workspace list");
+            }
+            for w in &workspaces {
+                let marker = if current.as_deref() == Some(w.workspace_gid.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{marker} {} {}", w.workspace_gid, w.name);
+            }
+        }
+        WorkspaceAction::Use { workspace } => {
+            let w = dw.use_workspace(&workspace).await?;
+            println!("Now using workspace {} ({}).", w.name, w.workspace_gid);
+        }
     }
     Ok(())
 }
@@ -681,12 +2833,98 @@ async fn handle_monitor(dw: &asanadw::AsanaDW, action: MonitorAction) -> anyhow:
                 }
             }
         }
+        MonitorAction::Review { list } => {
+            let discovered = dw.monitor_discovered().await?;
+            if discovered.is_empty() {
+                println!("No unmonitored projects found in synced tasks.");
+                return Ok(());
+            }
+            if list {
+                for p in &discovered {
+                    println!(
+                        "project:{} {} ({} tasks, first seen {})",
+                        p.project_gid,
+                        p.project_name.as_deref().unwrap_or(""),
+                        p.task_count,
+                        p.first_seen_at
+                    );
+                }
+                return Ok(());
+            }
+            println!(
+                "{} unmonitored projects found in synced tasks. \
+                 [y] monitor  [n] dismiss  [s] skip  [q] quit",
+                discovered.len()
+            );
+            let stdin = std::io::stdin();
+            for p in &discovered {
+                print!(
+                    "project:{} {} ({} tasks)? ",
+                    p.project_gid,
+                    p.project_name.as_deref().unwrap_or(""),
+                    p.task_count
+                );
+                std::io::Write::flush(&mut std::io::stdout())?;
+                let mut answer = String::new();
+                if stdin.read_line(&mut answer)? == 0 {
+                    break;
+                }
+                match answer.trim().to_ascii_lowercase().as_str() {
+                    "y" | "yes" => {
+                        let key = dw.monitor_promote_discovered(p).await?;
+                        println!("  Added: {key}");
+                    }
+                    "n" | "no" => {
+                        dw.monitor_dismiss_discovered(&p.project_gid).await?;
+                        println!("  Dismissed");
+                    }
+                    "q" | "quit" => break,
+                    _ => println!("  Skipped"),
+                }
+            }
+        }
+        MonitorAction::Interval {
+            entity_key,
+            interval,
+        } => {
+            let minutes = if interval.eq_ignore_ascii_case("default") {
+                None
+            } else {
+                Some(asanadw::sync::scheduler::parse_interval(&interval)?)
+            };
+            if dw.monitor_set_interval(&entity_key, minutes).await? {
+                match minutes {
+                    Some(m) => println!("{entity_key} syncs every {m} minutes."),
+                    None => println!("{entity_key} uses the default sync interval."),
+                }
+            } else {
+                println!("Not found: {entity_key}");
+            }
+        }
     }
     Ok(())
 }
 
-async fn handle_sync(dw: &asanadw::AsanaDW, target: SyncTarget) -> anyhow::Result<()> {
-    let progress = StderrProgress;
+async fn handle_sync(
+    dw: &asanadw::AsanaDW,
+    target: SyncTarget,
+    comment_concurrency: Option<usize>,
+    progress_json: bool,
+) -> anyhow::Result<()> {
+    let json_progress = asanadw::sync::progress::JsonLinesProgress::new(std::io::stdout());
+    let progress: &dyn asanadw::SyncProgress = if progress_json {
+        &json_progress
+    } else {
+        stderr_progress()
+    };
+    // With --progress-json, reports go out as entity_complete events
+    let print_report = |report: &asanadw::SyncReport| {
+        if progress_json {
+            asanadw::SyncProgress::on_entity_complete(&json_progress, report);
+        } else {
+            print_sync_report(report);
+        }
+    };
     match target {
         SyncTarget::Project {
             identifier,
@@ -694,18 +2932,18 @@ async fn handle_sync(dw: &asanadw::AsanaDW, target: SyncTarget) -> anyhow::Resul
             since,
             full,
         } => {
-            let options = make_sync_options(days, since.as_deref(), full);
-            let report = dw.sync_project(&identifier, &options, &progress).await?;
-            print_sync_report(&report);
+            let options = make_sync_options(days, since.as_deref(), full, comment_concurrency);
+            let report = dw.sync_project(&identifier, &options, progress).await?;
+            print_report(&report);
         }
         SyncTarget::User {
             identifier,
             days,
             since,
         } => {
-            let options = make_sync_options(days, since.as_deref(), false);
-            let report = dw.sync_user(&identifier, &options, &progress).await?;
-            print_sync_report(&report);
+            let options = make_sync_options(days, since.as_deref(), false, comment_concurrency);
+            let report = dw.sync_user(&identifier, &options, progress).await?;
+            print_report(&report);
         }
         SyncTarget::Team {
             identifier,
@@ -713,50 +2951,127 @@ async fn handle_sync(dw: &asanadw::AsanaDW, target: SyncTarget) -> anyhow::Resul
             since,
             full,
         } => {
-            let options = make_sync_options(days, since.as_deref(), full);
-            let report = dw.sync_team(&identifier, &options, &progress).await?;
-            print_sync_report(&report);
+            let options = make_sync_options(days, since.as_deref(), full, comment_concurrency);
+            let report = dw.sync_team(&identifier, &options, progress).await?;
+            print_report(&report);
+        }
+        SyncTarget::Teams => {
+            let report = dw.sync_teams().await?;
+            print_report(&report);
         }
         SyncTarget::Portfolio {
             identifier,
             days,
             since,
             full,
+            max_depth,
         } => {
-            let options = make_sync_options(days, since.as_deref(), full);
-            let report = dw.sync_portfolio(&identifier, &options, &progress).await?;
-            print_sync_report(&report);
+            let mut options = make_sync_options(days, since.as_deref(), full, comment_concurrency);
+            options.max_portfolio_depth = max_depth;
+            let report = dw.sync_portfolio(&identifier, &options, progress).await?;
+            print_report(&report);
         }
-        SyncTarget::All { days, since, full } => {
-            let options = make_sync_options(days, since.as_deref(), full);
-            let reports = dw.sync_all(&options, &progress).await?;
+        SyncTarget::All {
+            days,
+            since,
+            full,
+            concurrency,
+            max_depth,
+            dry_run,
+            json,
+        } => {
+            let mut options = make_sync_options(days, since.as_deref(), full, comment_concurrency);
+            options.concurrency = concurrency;
+            options.max_portfolio_depth = max_depth;
+            if dry_run {
+                let plan = dw.sync_plan(&options).await?;
+                if json {
+                    print_json(&plan)?;
+                } else {
+                    print_sync_plan(&plan);
+                }
+                return Ok(());
+            }
+            if progress_json {
+                // sync_all reports each entity's completion itself
+                dw.sync_all(&options, progress).await?;
+                return Ok(());
+            }
+            let live = !QUIET.load(Ordering::Relaxed) && std::io::stderr().is_terminal();
+            let reports = if concurrency > 1 && live {
+                dw.sync_all(&options, &in_flight_progress()).await?
+            } else {
+                dw.sync_all(&options, progress).await?
+            };
+            let diffs = dw.sync_diffs(&reports).await?;
             for report in &reports {
                 print_sync_report(report);
+                if let Some(diff) = diffs.iter().find(|d| d.entity_key == report.entity_key) {
+                    print_sync_diff(diff);
+                }
                 println!();
             }
             if reports.is_empty() {
                 println!("No monitored entities to sync. Use 'monitor add' first.");
             }
         }
-    }
-    Ok(())
-}
-
-fn make_sync_options(days: Option<u32>, since: Option<&str>, full: bool) -> asanadw::SyncOptions {
-    asanadw::SyncOptions {
-        since: parse_since(since),
-        days,
-        full,
-    }
-}
-
+        SyncTarget::Resume => {
+            let options = make_sync_options(None, None, true, comment_concurrency);
+            let reports = dw.sync_resume(&options, progress).await?;
+            if progress_json {
+                reports.iter().for_each(print_report);
+                return Ok(());
+            }
+            for report in &reports {
+                print_sync_report(report);
+                println!();
+            }
+            if reports.is_empty() {
+                println!("No interrupted syncs to resume.");
+            }
+        }
+        SyncTarget::Gaps {
+            entity,
+            since,
+            json,
+        } => {
+            let coverage = dw
+                .sync_coverage(&entity, parse_since(since.as_deref()))
+                .await?;
+            if json {
+                print_json(&coverage)?;
+            } else {
+                print_coverage(&coverage);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn make_sync_options(
+    days: Option<u32>,
+    since: Option<&str>,
+    full: bool,
+    comment_concurrency: Option<usize>,
+) -> asanadw::SyncOptions {
+    asanadw::SyncOptions {
+        since: parse_since(since),
+        days,
+        full,
+        dry_run: false,
+        concurrency: 1,
+        comment_concurrency: comment_concurrency
+            .unwrap_or(asanadw::sync::DEFAULT_COMMENT_CONCURRENCY),
+        max_portfolio_depth: asanadw::sync::DEFAULT_PORTFOLIO_DEPTH,
+    }
+}
+
 async fn handle_search(
     db: &asanadw::Database,
     query: &str,
     hit_type: Option<&str>,
     assignee: Option<&str>,
-    project: Option<&str>,
-    limit: u32,
+    mut options: asanadw::SearchOptions,
     json: bool,
 ) -> anyhow::Result<()> {
     let type_filter = match hit_type {
@@ -764,39 +3079,51 @@ async fn handle_search(
         Some("comment") => Some(asanadw::SearchHitType::Comment),
         Some("project") => Some(asanadw::SearchHitType::Project),
         Some("portfolio") => Some(asanadw::SearchHitType::Portfolio),
+        Some("brief") => Some(asanadw::SearchHitType::ProjectBrief),
+        Some("status") => Some(asanadw::SearchHitType::StatusUpdate),
         Some("custom_field") => Some(asanadw::SearchHitType::CustomField),
         Some(other) => {
-            anyhow::bail!("Unknown search type: {other}. Use: task, comment, project, portfolio, custom_field")
+            anyhow::bail!("Unknown search type: {other}. Use: task, comment, project, portfolio, brief, status, custom_field")
         }
         None => None,
     };
 
-    let resolved_assignee = match assignee {
+    options.hit_type = type_filter;
+    options.assignee_gid = match assignee {
         Some(a) => Some(resolve_user(db, a).await?),
         None => None,
     };
-    let options = asanadw::SearchOptions {
-        limit: Some(limit),
-        hit_type: type_filter,
-        assignee_gid: resolved_assignee,
-        project_gid: project.map(|s| s.to_string()),
-    };
 
     let results = asanadw::search::search(db, query, &options).await?;
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&results)?);
+        print_json(&results)?;
     } else {
-        println!("Search: \"{}\" ({} results)", results.query, results.total);
+        if results.total > 0 && results.total < results.total_unlimited {
+            println!(
+                "Search: \"{}\" (results {}-{} of {})",
+                results.query,
+                results.offset as usize + 1,
+                results.offset as usize + results.total,
+                results.total_unlimited
+            );
+        } else {
+            println!("Search: \"{}\" ({} results)", results.query, results.total);
+        }
         for hit in &results.hits {
             let type_label = match hit.hit_type {
                 asanadw::SearchHitType::Task => "task",
                 asanadw::SearchHitType::Comment => "comment",
                 asanadw::SearchHitType::Project => "project",
                 asanadw::SearchHitType::Portfolio => "portfolio",
+                asanadw::SearchHitType::ProjectBrief => "brief",
+                asanadw::SearchHitType::StatusUpdate => "status",
                 asanadw::SearchHitType::CustomField => "field",
             };
             println!("  [{type_label}] {} ({})", hit.title, hit.gid);
+            if let (Some(author), Some(created_at)) = (&hit.author, &hit.created_at) {
+                println!("    {author}, {created_at}");
+            }
             println!("    {}", hit.snippet);
             if let Some(ref url) = hit.asana_url {
                 println!("    {url}");
@@ -807,74 +3134,147 @@ async fn handle_search(
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Sync what `filters` are scoped to first if it has no local data (with
+/// `fetch`), or fail with how to sync it.
+async fn ensure_filters_synced(
+    db: &asanadw::Database,
+    workspace: Option<&str>,
+    filters: &asanadw::QueryFilters,
+    fetch: bool,
+    force_unlock: bool,
+    token: Option<&str>,
+) -> anyhow::Result<()> {
+    let scoped = [
+        ("project", filters.project.as_deref()),
+        ("portfolio", filters.portfolio.as_deref()),
+        ("team", filters.team.as_deref()),
+        (
+            "user",
+            if filters.mine {
+                None
+            } else {
+                filters.assignee.as_deref()
+            },
+        ),
+    ];
+    for (entity_type, identifier) in scoped {
+        if let Some(identifier) = identifier {
+            ensure_synced(
+                db,
+                workspace,
+                entity_type,
+                identifier,
+                fetch,
+                force_unlock,
+                token,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
 async fn handle_query(
     db: &asanadw::Database,
-    project: Option<&str>,
-    portfolio: Option<&str>,
-    team: Option<&str>,
-    assignee: Option<&str>,
-    completed: bool,
-    incomplete: bool,
-    overdue: bool,
-    created_after: Option<&str>,
-    created_before: Option<&str>,
-    due_after: Option<&str>,
-    due_before: Option<&str>,
-    limit: u32,
-    json: bool,
-    csv: bool,
-    count: bool,
+    filters: &asanadw::QueryFilters,
+    urls: Option<&str>,
+    format: &QueryOutputArgs,
 ) -> anyhow::Result<()> {
-    let mut builder = asanadw::QueryBuilder::new()
-        .limit(limit)
-        .order_by("t.modified_at")
-        .descending();
+    let mut builder = filters.to_builder(db).await?;
 
-    if let Some(p) = project {
-        builder = builder.project(p);
-    }
-    if let Some(p) = portfolio {
-        builder = builder.portfolio(p);
-    }
-    if let Some(t) = team {
-        builder = builder.team(t);
-    }
-    if let Some(a) = assignee {
-        let resolved = resolve_user(db, a).await?;
-        builder = builder.assignee(&resolved);
-    }
-    if completed {
-        builder = builder.completed(true);
-    }
-    if incomplete {
-        builder = builder.completed(false);
-    }
-    if overdue {
-        builder = builder.overdue(true);
-    }
-    if let Some(d) = created_after {
-        builder = builder.created_after(d);
-    }
-    if let Some(d) = created_before {
-        builder = builder.created_before(d);
-    }
-    if let Some(d) = due_after {
-        builder = builder.due_after(d);
+    if let Some(path) = urls {
+        let text = if path == "-" {
+            std::io::read_to_string(std::io::stdin())?
+        } else {
+            std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read {path}: {e}"))?
+        };
+        let gids = asanadw::url::task_gids_from_list(&text)?;
+        // Every listed task should come back, whatever the default limit
+        let limit = filters
+            .limit
+            .unwrap_or(asanadw::query::saved::DEFAULT_LIMIT);
+        builder = builder.limit(limit.max(gids.len() as u32)).gids(gids);
     }
-    if let Some(d) = due_before {
-        builder = builder.due_before(d);
+
+    let QueryOutputArgs {
+        json,
+        csv,
+        jsonl,
+        ref output,
+        count,
+    } = *format;
+    let output = output.as_deref();
+    let sum = filters.sum.as_deref();
+
+    if output.is_some() && !(csv || json || jsonl) {
+        anyhow::bail!("--output needs --csv, --jsonl, or --json");
     }
+    // CSV and JSON lines stream row by row, so large exports stay out of memory
+    let sink = || -> anyhow::Result<Box<dyn Write + Send>> {
+        Ok(match output {
+            Some(path) => Box::new(std::io::BufWriter::new(
+                std::fs::File::create(path)
+                    .map_err(|e| anyhow::anyhow!("Failed to create {path}: {e}"))?,
+            )),
+            None if jsonl && MACHINE.load(Ordering::Relaxed) => Box::new(EnvelopeLines::new(
+                std::io::BufWriter::new(std::io::stdout()),
+            )),
+            None => Box::new(std::io::BufWriter::new(std::io::stdout())),
+        })
+    };
 
     if count {
         let n = builder.count(db).await?;
         println!("{n}");
+    } else if jsonl || csv {
+        let written = if jsonl {
+            builder.write_jsonl(db, sink()?).await?
+        } else {
+            builder.write_csv(db, sink()?).await?
+        };
+        if let Some(path) = output {
+            note!("Wrote {written} rows to {path}");
+        }
     } else if json {
-        let output = builder.to_json(db).await?;
-        println!("{output}");
-    } else if csv {
-        let output = builder.to_csv(db).await?;
-        print!("{output}");
+        let text = builder.to_json(db).await?;
+        match output {
+            Some(_) => {
+                let mut out = sink()?;
+                writeln!(out, "{text}")?;
+                out.flush()?;
+            }
+            None => print_json_text(&text)?,
+        }
+    } else if filters.group_by.is_some() {
+        let groups = builder.groups(db).await?;
+        if groups.is_empty() {
+            println!("No tasks found.");
+        } else {
+            println!(
+                "{:<40} {:>6} {:>6} {:>8} {:>9}{}",
+                "Group",
+                "Tasks",
+                "Done",
+                "Overdue",
+                "Avg days",
+                if sum.is_some() { "       Sum" } else { "" }
+            );
+            for g in &groups {
+                let avg = g
+                    .avg_days_to_complete
+                    .map_or("-".to_string(), |d| format!("{d:.1}"));
+                let total = match (sum, g.field_sum) {
+                    (None, _) => String::new(),
+                    (Some(_), Some(s)) => format!(" {s:>9}"),
+                    (Some(_), None) => format!(" {:>9}", "-"),
+                };
+                println!(
+                    "{:<40} {:>6} {:>6} {:>8} {:>9}{total}",
+                    g.label, g.tasks, g.completed, g.overdue, avg
+                );
+            }
+        }
     } else {
         let rows = builder.tasks(db).await?;
         if rows.is_empty() {
@@ -897,7 +3297,435 @@ async fn handle_query(
     Ok(())
 }
 
-async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> anyhow::Result<()> {
+async fn handle_query_text(
+    db: &asanadw::Database,
+    builder: asanadw::CommentQueryBuilder,
+    filters: TextQueryArgs,
+    noun: &str,
+) -> anyhow::Result<()> {
+    let mut builder = builder.limit(filters.limit);
+    if let Some(p) = &filters.project {
+        builder = builder.project(&asanadw::alias::resolve(db, p).await?);
+    }
+    if let Some(a) = &filters.author {
+        builder = builder.author(&resolve_user(db, a).await?);
+    }
+    if let Some(d) = &filters.created_after {
+        builder = builder.created_after(d);
+    }
+    if let Some(d) = &filters.created_before {
+        builder = builder.created_before(d);
+    }
+    if let Some(n) = filters.min_length {
+        builder = builder.min_length(n);
+    }
+    if let Some(n) = filters.max_length {
+        builder = builder.max_length(n);
+    }
+
+    if filters.count {
+        println!("{}", builder.count(db).await?);
+    } else if filters.json {
+        print_json_text(&builder.to_json(db).await?)?;
+    } else if filters.csv {
+        print!("{}", builder.to_csv(db).await?);
+    } else {
+        let rows = builder.rows(db).await?;
+        if rows.is_empty() {
+            println!("No {noun} found.");
+        } else {
+            const MAX_WIDTH: usize = 100;
+            for row in &rows {
+                let author = row.author_name.as_deref().unwrap_or("unknown");
+                let parent = row.parent_name.as_deref().unwrap_or(&row.parent_gid);
+                let date = row.created_at.get(..10).unwrap_or(&row.created_at);
+                let mut text = row.text.as_deref().unwrap_or("").replace('\n', " ");
+                if text.chars().count() > MAX_WIDTH {
+                    let truncated: String = text.chars().take(MAX_WIDTH - 1).collect();
+                    text = format!("{truncated}…");
+                }
+                match &row.title {
+                    Some(title) => println!(
+                        "{date} {author} on {parent} [{}] {title}: {text}",
+                        row.status_type.as_deref().unwrap_or("")
+                    ),
+                    None => println!("{date} {author} on {parent}: {text}"),
+                }
+            }
+            println!("\n{} {noun}", rows.len());
+        }
+    }
+    Ok(())
+}
+
+const REPL_HELP: &str = "\
+  /query ...      Run `asanadw query` with these arguments
+  /metrics ...    Run `asanadw metrics`
+  /search ...     Run `asanadw search`
+  /history [n]    Show the last n lines entered (default 20)
+  /help           Show this help
+  /quit           Leave (or Ctrl-D)
+Anything else is asked of the LLM.";
+
+async fn run_repl(db: &asanadw::Database, max_steps: usize) -> anyhow::Result<()> {
+    use asanadw::repl::ReplLine;
+    use tokio::io::AsyncBufReadExt;
+
+    note!("asanadw repl. /help for commands, /quit to leave.");
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("asanadw> ");
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            break;
+        };
+
+        let parsed = match asanadw::repl::parse_line(&line) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        if matches!(parsed, ReplLine::Command { .. } | ReplLine::Question(_)) && !db.is_read_only()
+        {
+            let input = line.trim().to_string();
+            db.writer()
+                .call(move |conn| asanadw::storage::repository::add_repl_history(conn, &input))
+                .await?;
+        }
+
+        let result = match parsed {
+            ReplLine::Empty => Ok(()),
+            ReplLine::Quit => break,
+            ReplLine::Help => {
+                println!("{REPL_HELP}");
+                Ok(())
+            }
+            ReplLine::History(n) => {
+                let limit = n.unwrap_or(20);
+                let history = db
+                    .reader()
+                    .call(move |conn| {
+                        asanadw::storage::repository::recent_repl_history(conn, limit)
+                    })
+                    .await?;
+                for entry in history {
+                    println!("  {}  {}", entry.created_at, entry.input);
+                }
+                Ok(())
+            }
+            ReplLine::Command { name, args } => {
+                let argv = ["asanadw".to_string(), name].into_iter().chain(args);
+                match Cli::try_parse_from(argv) {
+                    Ok(cli) => Box::pin(run(cli, db.clone())).await,
+                    Err(e) => {
+                        let _ = e.print();
+                        Ok(())
+                    }
+                }
+            }
+            ReplLine::Question(question) => handle_ask(db, &question, max_steps, false).await,
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {e:#}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn handle_ask(
+    _db: &asanadw::Database,
+    _question: &str,
+    _max_steps: usize,
+    _json: bool,
+) -> anyhow::Result<()> {
+    anyhow::bail!("{LLM_DISABLED}")
+}
+
+#[cfg(feature = "llm")]
+async fn handle_ask(
+    db: &asanadw::Database,
+    question: &str,
+    max_steps: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    let agent = asanadw::llm::create_agent(db).await?;
+    let answer = asanadw::llm::agents::ask::ask(db, &agent, question, max_steps).await?;
+
+    if json {
+        print_json(&answer)?;
+        return Ok(());
+    }
+    println!("{}", answer.answer);
+    if !answer.citations.is_empty() {
+        println!("\nSources:");
+        for c in &answer.citations {
+            println!("  - {} ({})", c.name, c.url);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn handle_digest(
+    _db: &asanadw::Database,
+    _period: &str,
+    _email_format: bool,
+    _force: bool,
+    _json: bool,
+) -> anyhow::Result<()> {
+    anyhow::bail!("{LLM_DISABLED}")
+}
+
+#[cfg(feature = "llm")]
+async fn handle_digest(
+    db: &asanadw::Database,
+    period: &str,
+    email_format: bool,
+    force: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let user_gid = db
+        .reader()
+        .call(|c| asanadw::storage::repository::get_config(c, "user_gid"))
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("User identity not set. Run 'asanadw sync all' first."))?;
+    let p = asanadw::Period::parse(period)?;
+    let agent = asanadw::llm::create_agent(db).await?;
+    let digest =
+        asanadw::llm::agents::digest::compose_digest(db, &agent, &user_gid, &p, force).await?;
+
+    if json {
+        print_json(&digest)?;
+    } else if email_format {
+        print!("{}", digest.to_html());
+    } else {
+        print!("{}", digest.to_markdown());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn handle_risks(
+    _db: &asanadw::Database,
+    _period: &str,
+    _min_severity: Option<&str>,
+    _force: bool,
+    _json: bool,
+) -> anyhow::Result<()> {
+    anyhow::bail!("{LLM_DISABLED}")
+}
+
+#[cfg(feature = "llm")]
+async fn handle_risks(
+    db: &asanadw::Database,
+    period: &str,
+    min_severity: Option<&str>,
+    force: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    use asanadw::llm::agents::risks;
+
+    let min_severity = min_severity
+        .map(|s| {
+            risks::RiskSeverity::parse(s)
+                .ok_or_else(|| anyhow::anyhow!("Unknown severity: {s}. Use: low, medium, high"))
+        })
+        .transpose()?;
+    let p = asanadw::Period::parse(period)?;
+    let agent = asanadw::llm::create_agent(db).await?;
+    let report = risks::scan_risks(db, &agent, &p, force).await?;
+    if report.sources_scanned > 0 {
+        note!(
+            "Scanned {} status updates and comment threads: {} risks flagged",
+            report.sources_scanned,
+            report.flags_found
+        );
+    }
+    let flags = risks::list_risks(db, &p, min_severity).await?;
+
+    if json {
+        print_json(&flags)?;
+        return Ok(());
+    }
+    println!("Risks ({}): {}", p.to_key(), flags.len());
+    for f in &flags {
+        println!(
+            "  [{}] {} — {}",
+            f.severity.as_str().to_uppercase(),
+            f.entity_name.as_deref().unwrap_or(&f.entity_gid),
+            f.summary
+        );
+        if !f.evidence.is_empty() {
+            println!("      \"{}\"", f.evidence);
+        }
+        println!("      {} ({})", f.entity_url, f.source_date_key);
+        for url in f.task_urls.iter().filter(|u| **u != f.entity_url) {
+            println!("      {url}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn handle_summarize(_db: &asanadw::Database, _target: SummarizeTarget) -> anyhow::Result<()> {
+    anyhow::bail!("{LLM_DISABLED}")
+}
+
+#[cfg(feature = "llm")]
+use asanadw::llm::agents::history::SummaryKind;
+
+/// The summary to list or diff, when `--history` or `--diff` was given.
+#[cfg(feature = "llm")]
+fn summary_history_target(
+    target: &SummarizeTarget,
+) -> Option<(SummaryKind, String, String, bool, bool)> {
+    let (kind, gid, period, history, diff, json) = match target {
+        SummarizeTarget::User {
+            user_gid,
+            period,
+            history,
+            diff,
+            json,
+            ..
+        } => (SummaryKind::User, user_gid, period, history, diff, json),
+        SummarizeTarget::Project {
+            project_gid,
+            period,
+            history,
+            diff,
+            json,
+            ..
+        } => (
+            SummaryKind::Project,
+            project_gid,
+            period,
+            history,
+            diff,
+            json,
+        ),
+        SummarizeTarget::Portfolio {
+            portfolio_gid,
+            period,
+            history,
+            diff,
+            json,
+            ..
+        } => (
+            SummaryKind::Portfolio,
+            portfolio_gid,
+            period,
+            history,
+            diff,
+            json,
+        ),
+        SummarizeTarget::Team {
+            team_gid,
+            period,
+            history,
+            diff,
+            json,
+            ..
+        } => (SummaryKind::Team, team_gid, period, history, diff, json),
+        SummarizeTarget::Me { .. } | SummarizeTarget::Task { .. } => return None,
+    };
+    (*history || *diff).then(|| (kind, gid.clone(), period.clone(), *diff, *json))
+}
+
+#[cfg(feature = "llm")]
+async fn print_summary_history(
+    db: &asanadw::Database,
+    kind: SummaryKind,
+    gid: &str,
+    period_key: &str,
+    diff: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    use asanadw::llm::agents::history::{diff_generations, summary_history, DiffSegment};
+
+    let generations = summary_history(db, kind, gid, period_key).await?;
+    if !diff {
+        if json {
+            print_json(&generations)?;
+        } else if generations.is_empty() {
+            println!("No summaries stored for {period_key}.");
+        } else {
+            for g in &generations {
+                println!(
+                    "#{} {} ({})\n  {}",
+                    g.generation, g.generated_at, g.prompt_version, g.headline
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let [new, old, ..] = generations.as_slice() else {
+        anyhow::bail!("Need at least two stored summaries for {period_key} to diff");
+    };
+    let diffs = diff_generations(old, new);
+    if json {
+        print_json(&diffs)?;
+        return Ok(());
+    }
+    println!(
+        "Generation {} ({}) -> {} ({})",
+        old.generation, old.generated_at, new.generation, new.generated_at
+    );
+    if diffs.is_empty() {
+        println!("No changes.");
+    }
+    for d in &diffs {
+        let text: String = d
+            .segments
+            .iter()
+            .map(|s| match s {
+                DiffSegment::Same(t) => t.clone(),
+                DiffSegment::Removed(t) => format!("[-{}-]", t.trim_end()),
+                DiffSegment::Added(t) => format!("{{+{}+}}", t.trim_end()),
+            })
+            .collect();
+        println!(
+            "\n{}:\n  {}",
+            d.field,
+            text.trim_end().replace('\n', "\n  ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "llm")]
+async fn handle_summarize(
+    db: &asanadw::Database,
+    mut target: SummarizeTarget,
+) -> anyhow::Result<()> {
+    match &mut target {
+        SummarizeTarget::Project {
+            project_gid: gid, ..
+        }
+        | SummarizeTarget::Portfolio {
+            portfolio_gid: gid, ..
+        }
+        | SummarizeTarget::Team { team_gid: gid, .. } => {
+            *gid = asanadw::alias::resolve(db, gid).await?;
+        }
+        _ => {}
+    }
+
+    // History and diffs read stored summaries and need no model
+    if let Some((kind, gid, period, diff, json)) = summary_history_target(&target) {
+        let gid = if kind == SummaryKind::User {
+            resolve_user(db, &gid).await?
+        } else {
+            gid
+        };
+        let period_key = asanadw::Period::parse(&period)?.to_key();
+        return print_summary_history(db, kind, &gid, &period_key, diff, json).await;
+    }
+
     let agent = asanadw::llm::create_agent(db).await?;
 
     match target {
@@ -919,11 +3747,14 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             )
             .await?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&summary)?);
+                print_json(&summary)?;
             } else {
                 println!("{}", summary.headline);
                 println!("\n{}", summary.what_changed);
                 println!("\n{}", summary.why_it_matters);
+                if let Some(ref trend) = summary.trend {
+                    println!("\nTrend: {trend}");
+                }
                 println!("\nKey accomplishments:");
                 for a in &summary.key_accomplishments {
                     println!("  - {a}");
@@ -931,6 +3762,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
                 if let Some(ref notes) = summary.collaboration_notes {
                     println!("\nCollaboration: {notes}");
                 }
+                print_extra(&summary.extra);
             }
         }
         SummarizeTarget::Task {
@@ -941,7 +3773,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             let summary =
                 asanadw::llm::agents::task::summarize_task(db, &agent, &task_gid, force).await?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&summary)?);
+                print_json(&summary)?;
             } else {
                 println!("Task Summary: {}", summary.headline);
                 println!("\nWhat happened: {}", summary.what_happened);
@@ -949,6 +3781,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
                 println!("Complexity: {}", summary.complexity_signal);
                 println!("Notability: {}/10", summary.notability_score);
                 println!("Types: {}", summary.change_types.join(", "));
+                print_extra(&summary.extra);
             }
         }
         SummarizeTarget::User {
@@ -956,6 +3789,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             period,
             force,
             json,
+            ..
         } => {
             let user_gid = resolve_user(db, &user_gid).await?;
             let p = asanadw::Period::parse(&period)?;
@@ -964,11 +3798,14 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             )
             .await?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&summary)?);
+                print_json(&summary)?;
             } else {
                 println!("{}", summary.headline);
                 println!("\n{}", summary.what_changed);
                 println!("\n{}", summary.why_it_matters);
+                if let Some(ref trend) = summary.trend {
+                    println!("\nTrend: {trend}");
+                }
                 println!("\nKey accomplishments:");
                 for a in &summary.key_accomplishments {
                     println!("  - {a}");
@@ -976,6 +3813,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
                 if let Some(ref notes) = summary.collaboration_notes {
                     println!("\nCollaboration: {notes}");
                 }
+                print_extra(&summary.extra);
             }
         }
         SummarizeTarget::Project {
@@ -983,6 +3821,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             period,
             force,
             json,
+            ..
         } => {
             let p = asanadw::Period::parse(&period)?;
             let summary = asanadw::llm::agents::period::summarize_project_period(
@@ -994,11 +3833,14 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             )
             .await?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&summary)?);
+                print_json(&summary)?;
             } else {
                 println!("{}", summary.headline);
                 println!("\n{}", summary.what_changed);
                 println!("\n{}", summary.why_it_matters);
+                if let Some(ref trend) = summary.trend {
+                    println!("\nTrend: {trend}");
+                }
                 println!("\nKey milestones:");
                 for m in &summary.key_milestones {
                     println!("  - {m}");
@@ -1006,6 +3848,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
                 if let Some(ref health) = summary.health_assessment {
                     println!("\nHealth: {health}");
                 }
+                print_extra(&summary.extra);
             }
         }
         SummarizeTarget::Portfolio {
@@ -1013,6 +3856,7 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             period,
             force,
             json,
+            ..
         } => {
             let p = asanadw::Period::parse(&period)?;
             let summary = asanadw::llm::agents::period::summarize_portfolio_period(
@@ -1024,161 +3868,1483 @@ async fn handle_summarize(db: &asanadw::Database, target: SummarizeTarget) -> an
             )
             .await?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&summary)?);
+                print_json(&summary)?;
+            } else {
+                println!("{}", summary.headline);
+                println!("\n{}", summary.what_changed);
+                println!("\n{}", summary.why_it_matters);
+                if let Some(ref trend) = summary.trend {
+                    println!("\nTrend: {trend}");
+                }
+                println!("\nKey milestones:");
+                for m in &summary.key_milestones {
+                    println!("  - {m}");
+                }
+                if let Some(ref health) = summary.health_assessment {
+                    println!("\nHealth: {health}");
+                }
+                print_extra(&summary.extra);
+            }
+        }
+        SummarizeTarget::Team {
+            team_gid,
+            period,
+            force,
+            json,
+            ..
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let summary = asanadw::llm::agents::period::summarize_team_period(
+                db, &agent, &team_gid, &p, force,
+            )
+            .await?;
+            if json {
+                print_json(&summary)?;
+            } else {
+                println!("{}", summary.headline);
+                println!("\n{}", summary.what_changed);
+                println!("\n{}", summary.why_it_matters);
+                if let Some(ref trend) = summary.trend {
+                    println!("\nTrend: {trend}");
+                }
+                println!("\nKey accomplishments:");
+                for a in &summary.key_accomplishments {
+                    println!("  - {a}");
+                }
+                if let Some(ref health) = summary.health_assessment {
+                    println!("\nHealth: {health}");
+                }
+                print_extra(&summary.extra);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print fields a custom prompt asked for beyond the built-in ones.
+#[cfg(feature = "llm")]
+fn print_extra(extra: &serde_json::Map<String, serde_json::Value>) {
+    if extra.is_empty() {
+        return;
+    }
+    println!();
+    for (key, value) in extra {
+        match value {
+            serde_json::Value::String(s) => println!("{key}: {s}"),
+            other => println!("{key}: {other}"),
+        }
+    }
+}
+
+/// The project, user, portfolio, or team a `metrics` command reports on.
+fn metrics_entity(target: &MetricsTarget) -> Option<(&'static str, &str)> {
+    match target {
+        MetricsTarget::User { user_gid, .. } => Some(("user", user_gid)),
+        MetricsTarget::Project { project_gid, .. } => Some(("project", project_gid)),
+        MetricsTarget::Portfolio { portfolio_gid, .. } => Some(("portfolio", portfolio_gid)),
+        MetricsTarget::Team { team_gid, .. } => Some(("team", team_gid)),
+        MetricsTarget::Notable {
+            user: Some(user), ..
+        } => Some(("user", user)),
+        MetricsTarget::Notable {
+            project: Some(project),
+            ..
+        } => Some(("project", project)),
+        _ => None,
+    }
+}
+
+/// The project, user, portfolio, or team a `summarize` command covers.
+fn summarize_entity(target: &SummarizeTarget) -> Option<(&'static str, &str)> {
+    match target {
+        SummarizeTarget::User { user_gid, .. } => Some(("user", user_gid)),
+        SummarizeTarget::Project { project_gid, .. } => Some(("project", project_gid)),
+        SummarizeTarget::Portfolio { portfolio_gid, .. } => Some(("portfolio", portfolio_gid)),
+        SummarizeTarget::Team { team_gid, .. } => Some(("team", team_gid)),
+        _ => None,
+    }
+}
+
+/// Fail when an entity has no local data, or with `fetch` sync it first
+/// under the sync lock, taken from a live holder with `force_unlock`.
+async fn ensure_synced(
+    db: &asanadw::Database,
+    workspace: Option<&str>,
+    entity_type: &str,
+    identifier: &str,
+    fetch: bool,
+    force_unlock: bool,
+    token: Option<&str>,
+) -> anyhow::Result<()> {
+    let gid = if entity_type == "user" {
+        resolve_user(db, identifier).await?
+    } else {
+        asanadw::alias::resolve(db, identifier).await?
+    };
+    match db.require_synced(entity_type, &gid).await {
+        Err(asanadw::Error::NotSynced(key)) if fetch => {
+            note!("{key} has no local data; syncing it first");
+            let client = asana_client(token, None)?;
+            let dw = asana_dw(db.clone(), client, workspace).await?;
+            let options = make_sync_options(None, None, false, None);
+            let lock = asanadw::sync::lock::SyncLock::acquire(dw.db(), force_unlock).await?;
+            let report = match entity_type {
+                "project" => dw.sync_project(&gid, &options, stderr_progress()).await,
+                "user" => dw.sync_user(&gid, &options, stderr_progress()).await,
+                "portfolio" => dw.sync_portfolio(&gid, &options, stderr_progress()).await,
+                _ => dw.sync_team(&gid, &options, stderr_progress()).await,
+            };
+            lock.release().await?;
+            let report = report?;
+            note!(
+                "Synced {}: {} items ({} failed)",
+                report.entity_key,
+                report.items_synced,
+                report.items_failed
+            );
+            Ok(())
+        }
+        Err(e @ asanadw::Error::NotSynced(_)) => {
+            anyhow::bail!("{e}. Sync it with `asanadw sync {entity_type} {gid}`, or pass --fetch")
+        }
+        result => Ok(result?),
+    }
+}
+
+async fn handle_metrics(db: &asanadw::Database, mut target: MetricsTarget) -> anyhow::Result<()> {
+    match &mut target {
+        MetricsTarget::Project {
+            project_gid: gid, ..
+        }
+        | MetricsTarget::Portfolio {
+            portfolio_gid: gid, ..
+        }
+        | MetricsTarget::Team { team_gid: gid, .. } => {
+            *gid = asanadw::alias::resolve(db, gid).await?;
+        }
+        _ => {}
+    }
+
+    match target {
+        MetricsTarget::Me {
+            period,
+            histogram,
+            json,
+        } => {
+            let user_gid = db
+                .reader()
+                .call(|c| asanadw::storage::repository::get_config(c, "user_gid"))
+                .await?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("User identity not set. Run 'asanadw sync all' first.")
+                })?;
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_user_metrics(db, &user_gid, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "User Metrics: {} ({})",
+                    m.user_name.as_deref().unwrap_or(&m.user_gid),
+                    m.period_key
+                );
+                print_throughput(&m.throughput);
+                print_lead_time(&m.lead_time, histogram);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::User {
+            user_gid,
+            period,
+            histogram,
+            json,
+        } => {
+            let user_gid = resolve_user(db, &user_gid).await?;
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_user_metrics(db, &user_gid, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "User Metrics: {} ({})",
+                    m.user_name.as_deref().unwrap_or(&m.user_gid),
+                    m.period_key
+                );
+                print_throughput(&m.throughput);
+                print_lead_time(&m.lead_time, histogram);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::Project {
+            project_gid,
+            period,
+            field_history: Some(field),
+            json,
+            ..
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_field_history(db, &project_gid, &field, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "{} History: {} ({})",
+                    m.field_name,
+                    m.project_name.as_deref().unwrap_or(&m.project_gid),
+                    m.period_key
+                );
+                if m.values.is_empty() {
+                    println!("  No recorded changes to {} in this period.", m.field_name);
+                }
+                for v in &m.values {
+                    println!(
+                        "  {}: {} tasks | {:.1} days total | avg {:.1} | max {:.1} | {} open now",
+                        v.value, v.tasks, v.total_days, v.avg_days, v.max_days, v.open_now
+                    );
+                }
+            }
+        }
+        MetricsTarget::Project {
+            project_gid,
+            period,
+            by_section: true,
+            json,
+            ..
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_section_metrics(db, &project_gid, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "Section Metrics: {} ({})",
+                    m.project_name.as_deref().unwrap_or(&m.project_gid),
+                    m.period_key
+                );
+                if m.sections.is_empty() {
+                    println!("  No sections synced for this project.");
+                }
+                for s in &m.sections {
+                    println!(
+                        "  {}: {} open ({} overdue) | created {} | completed {}",
+                        s.section_name,
+                        s.open_count,
+                        s.overdue_count,
+                        s.tasks_created,
+                        s.tasks_completed
+                    );
+                    if let (Some(days), Some(name)) = (s.oldest_open_days, &s.oldest_open_task_name)
+                    {
+                        println!("    Oldest open: {name} ({days} days)");
+                    }
+                }
+            }
+        }
+        MetricsTarget::Project {
+            project_gid,
+            period,
+            group_by_field: Some(field),
+            json,
+            ..
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m =
+                asanadw::metrics::compute_project_metrics_by_field(db, &project_gid, &field, &p)
+                    .await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "Project Metrics: {} by {} ({})",
+                    m.project_name.as_deref().unwrap_or(&m.project_gid),
+                    m.field_name,
+                    m.period_key
+                );
+                if m.groups.is_empty() {
+                    println!("  No tasks in this project.");
+                }
+                for g in &m.groups {
+                    println!("  {}:", g.value);
+                    println!(
+                        "    Created {} | Completed {} | Open {} ({} overdue, {} unassigned)",
+                        g.throughput.tasks_created,
+                        g.throughput.tasks_completed,
+                        g.health.total_open,
+                        g.health.overdue_count,
+                        g.health.unassigned_count
+                    );
+                    if let Some(median) = g.lead_time.median_days_to_complete {
+                        println!(
+                            "    Lead time: median {median:.1} days, p90 {:.1} days",
+                            g.lead_time.p90_days_to_complete.unwrap_or(0.0)
+                        );
+                    }
+                }
+            }
+        }
+        MetricsTarget::Project {
+            project_gid,
+            period,
+            group_by_field: None,
+            histogram,
+            json,
+            ..
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_project_metrics(db, &project_gid, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "Project Metrics: {} ({})",
+                    m.project_name.as_deref().unwrap_or(&m.project_gid),
+                    m.period_key
+                );
+                print_throughput(&m.throughput);
+                print_health(&m.health);
+                print_lead_time(&m.lead_time, histogram);
+                print_cycle_time(&m.cycle_time);
+                print_phases(&m.phases);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::Portfolio {
+            portfolio_gid,
+            period,
+            max_depth,
+            histogram,
+            json,
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_portfolio_metrics_to_depth(
+                db,
+                &portfolio_gid,
+                &p,
+                max_depth,
+            )
+            .await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "Portfolio Metrics: {} ({})",
+                    m.portfolio_name.as_deref().unwrap_or(&m.portfolio_gid),
+                    m.period_key
+                );
+                if m.nested_portfolio_count > 0 {
+                    println!(
+                        "  Projects: {} (including {} nested portfolios)",
+                        m.project_count, m.nested_portfolio_count
+                    );
+                } else {
+                    println!("  Projects: {}", m.project_count);
+                }
+                print_throughput(&m.throughput);
+                print_health(&m.health);
+                print_lead_time(&m.lead_time, histogram);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::Team {
+            team_gid,
+            workload: true,
+            json,
+            ..
+        } => {
+            let w = asanadw::metrics::workload::compute_team_workload(db, &team_gid).await?;
+            if json {
+                print_json(&w)?;
+            } else {
+                println!(
+                    "Team Workload: {}",
+                    w.team_name.as_deref().unwrap_or(&w.team_gid)
+                );
+                if let Some(field) = &w.estimate_field {
+                    println!("  Load: sum of {field} (unestimated tasks count as 1)");
+                }
+                if w.members.is_empty() {
+                    println!("  No members synced for this team.");
+                }
+                for m in &w.members {
+                    let vs_avg = m
+                        .load_vs_average
+                        .map(|r| format!(" ({r:.1}x avg)"))
+                        .unwrap_or_default();
+                    println!(
+                        "  {}: load {:.1}{} | {} open | {} overdue | {} due in 7 days",
+                        m.user_name.as_deref().unwrap_or(&m.user_gid),
+                        m.estimated_load,
+                        vs_avg,
+                        m.open_tasks,
+                        m.overdue_tasks,
+                        m.due_next_7_days
+                    );
+                }
+            }
+        }
+        MetricsTarget::Team {
+            team_gid,
+            period,
+            histogram,
+            json,
+            ..
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_team_metrics(db, &team_gid, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "Team Metrics: {} ({})",
+                    m.team_name.as_deref().unwrap_or(&m.team_gid),
+                    m.period_key
+                );
+                println!("  Members: {}", m.member_count);
+                print_throughput(&m.throughput);
+                print_health(&m.health);
+                print_lead_time(&m.lead_time, histogram);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::Org {
+            manager,
+            period,
+            histogram,
+            json,
+        } => {
+            let manager_gid = resolve_user(db, &manager).await?;
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_org_metrics(db, &manager_gid, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!(
+                    "Org Metrics: {} ({})",
+                    m.manager_name.as_deref().unwrap_or(&m.manager_gid),
+                    m.period_key
+                );
+                println!(
+                    "  People: {} ({} direct reports)",
+                    m.member_count, m.direct_reports
+                );
+                print_throughput(&m.throughput);
+                print_health(&m.health);
+                print_lead_time(&m.lead_time, histogram);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::Label {
+            label,
+            period,
+            histogram,
+            json,
+        } => {
+            let p = asanadw::Period::parse(&period)?;
+            let m = asanadw::metrics::compute_label_metrics(db, &label, &p).await?;
+            if json {
+                print_json(&m)?;
+            } else {
+                println!("Label Metrics: {} ({})", m.label, m.period_key);
+                println!("  Projects: {}", m.project_count);
+                print_throughput(&m.throughput);
+                print_health(&m.health);
+                print_lead_time(&m.lead_time, histogram);
+                print_collaboration(&m.collaboration);
+            }
+        }
+        MetricsTarget::Notable {
+            user,
+            project,
+            period,
+            limit,
+            rescore,
+            json,
+        } => {
+            use asanadw::metrics::notability::{self, NotableScope};
+            if rescore {
+                let scored = notability::rescore_all(db).await?;
+                note!("Rescored {scored} tasks");
+            }
+            let scope = match (user, project) {
+                (Some(user), _) => NotableScope::User(resolve_user(db, &user).await?),
+                (None, Some(project)) => NotableScope::Project(project),
+                (None, None) => NotableScope::All,
+            };
+            let p = asanadw::Period::parse(&period)?;
+            let tasks = notability::top_notable_tasks(db, &scope, &p, limit).await?;
+            if json {
+                print_json(&tasks)?;
+            } else {
+                println!("Notable tasks ({})", p.to_key());
+                if tasks.is_empty() {
+                    println!("  No scored tasks completed in this period.");
+                }
+                for t in &tasks {
+                    let milestone = if t.resource_subtype == "milestone" {
+                        " [milestone]"
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "  {:>6.2}  {}{milestone} ({})",
+                        t.notability_score,
+                        t.name,
+                        t.assignee_name.as_deref().unwrap_or("unassigned")
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "llm")]
+async fn suggest_orphan_owners(
+    db: &asanadw::Database,
+    report: &asanadw::metrics::orphans::OrphanReport,
+) -> anyhow::Result<Vec<asanadw::metrics::orphans::OwnerSuggestion>> {
+    let agent = asanadw::llm::create_agent(db).await?;
+    Ok(asanadw::llm::agents::orphans::suggest_owners(&agent, report).await?)
+}
+
+#[cfg(not(feature = "llm"))]
+async fn suggest_orphan_owners(
+    _db: &asanadw::Database,
+    _report: &asanadw::metrics::orphans::OrphanReport,
+) -> anyhow::Result<Vec<asanadw::metrics::orphans::OwnerSuggestion>> {
+    anyhow::bail!("{LLM_DISABLED}")
+}
+
+async fn handle_orphans(
+    db: &asanadw::Database,
+    entity_type: &str,
+    identifier: &str,
+    llm: bool,
+    candidates: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    let gid = asanadw::alias::resolve(db, identifier).await?;
+    let report = match entity_type {
+        "project" => asanadw::metrics::orphans::find_project_orphans(db, &gid, candidates).await?,
+        "team" => asanadw::metrics::orphans::find_team_orphans(db, &gid, candidates).await?,
+        other => anyhow::bail!("Unknown entity type: {other}. Use: project, team"),
+    };
+
+    let suggestions = if llm && !report.tasks.is_empty() {
+        suggest_orphan_owners(db, &report).await?
+    } else {
+        Vec::new()
+    };
+
+    if json {
+        if llm {
+            let output = serde_json::json!({
+                "orphans": report,
+                "suggestions": suggestions,
+            });
+            print_json(&output)?;
+        } else {
+            print_json(&report)?;
+        }
+        return Ok(());
+    }
+
+    println!(
+        "Orphaned tasks: {} ({} open tasks without an active owner)",
+        report.entity_name.as_deref().unwrap_or(&report.entity_key),
+        report.tasks.len()
+    );
+    for task in &report.tasks {
+        let reason = match task.reason {
+            asanadw::metrics::orphans::OrphanReason::Unassigned => "unassigned".to_string(),
+            asanadw::metrics::orphans::OrphanReason::DeactivatedAssignee => format!(
+                "deactivated: {}",
+                task.assignee_name
+                    .as_deref()
+                    .or(task.assignee_gid.as_deref())
+                    .unwrap_or("unknown")
+            ),
+        };
+        let due = task.due_on.as_deref().unwrap_or("no due date");
+        println!(
+            "  {} ({}) - {reason} | due: {due}",
+            task.name, task.task_gid
+        );
+        if let Some(s) = suggestions.iter().find(|s| s.task_gid == task.task_gid) {
+            let name = task
+                .candidates
+                .iter()
+                .find(|c| c.user_gid == s.user_gid)
+                .and_then(|c| c.user_name.as_deref())
+                .unwrap_or(&s.user_gid);
+            println!("    Suggested owner: {name} — {}", s.rationale);
+        } else if !task.candidates.is_empty() {
+            let names: Vec<String> = task
+                .candidates
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{} ({})",
+                        c.user_name.as_deref().unwrap_or(&c.user_gid),
+                        c.score
+                    )
+                })
+                .collect();
+            println!("    Candidates: {}", names.join(", "));
+        }
+    }
+    Ok(())
+}
+
+async fn handle_db(
+    db: &asanadw::Database,
+    db_path: Option<&str>,
+    action: DbAction,
+) -> anyhow::Result<()> {
+    match action {
+        DbAction::Stats { json } => {
+            let stats = db.stats().await?;
+            if json {
+                print_json(&stats)?;
+                return Ok(());
+            }
+            println!(
+                "Size: {} ({} free)",
+                format_bytes(stats.size_bytes),
+                format_bytes(stats.free_bytes)
+            );
+            println!();
+            println!("  {:<36} {:>10} {:>10}", "TABLE", "ROWS", "SIZE");
+            for t in &stats.tables {
+                let size = t.bytes.map(format_bytes).unwrap_or_else(|| "-".into());
+                println!("  {:<36} {:>10} {:>10}", t.name, t.rows, size);
+            }
+        }
+        DbAction::Check => {
+            let problems = db.integrity_check().await?;
+            if problems.is_empty() {
+                println!("Integrity check passed.");
+            } else {
+                for p in &problems {
+                    println!("  {p}");
+                }
+                anyhow::bail!("integrity check found {} problem(s)", problems.len());
+            }
+        }
+        DbAction::Vacuum { fts } => {
+            let report = db.vacuum(fts).await?;
+            if !report.fts_optimized.is_empty() {
+                println!("Optimized {} search indexes.", report.fts_optimized.len());
+            }
+            println!(
+                "Vacuumed: {} -> {}",
+                format_bytes(report.size_before),
+                format_bytes(report.size_after)
+            );
+        }
+        DbAction::Analyze => {
+            db.analyze().await?;
+            println!("Query planner statistics updated.");
+        }
+        DbAction::Prune {
+            older_than,
+            archive,
+            dry_run,
+            json,
+        } => {
+            let report =
+                asanadw::storage::retention::prune_older_than(db, &older_than, archive, dry_run)
+                    .await?;
+            if json {
+                print_json(&report)?;
+                return Ok(());
+            }
+            let verb = if dry_run { "Would prune" } else { "Pruned" };
+            println!(
+                "{verb} {} tasks ({} comments) and {} status updates from before {}.",
+                report.tasks, report.comments, report.status_updates, report.cutoff
+            );
+            if let Some(path) = &report.archived_to {
+                println!("Archived to {path}");
+            }
+        }
+        // Handled in run, which has the workspace override
+        DbAction::BackfillPermalinks { .. } => unreachable!(),
+        DbAction::Encrypt { out, save_key } => {
+            let source = match db_path {
+                Some(path) => std::path::PathBuf::from(path),
+                None => asanadw::Database::default_path()?,
+            };
+            encrypt_database(db, source, out, save_key).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sqlcipher")]
+async fn encrypt_database(
+    db: &asanadw::Database,
+    source: std::path::PathBuf,
+    out: Option<std::path::PathBuf>,
+    save_key: bool,
+) -> anyhow::Result<()> {
+    use asanadw::storage::encryption;
+
+    let key = encryption::database_key()?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No database key. Set {} to the key to encrypt with.",
+            encryption::KEY_ENV
+        )
+    })?;
+    let out = out.unwrap_or_else(|| {
+        let mut name = source.clone().into_os_string();
+        name.push(".encrypted");
+        name.into()
+    });
+    db.export_encrypted(out.clone(), key.clone()).await?;
+    // Make sure the copy opens and migrates with the key before anyone
+    // swaps it in
+    asanadw::Database::open_encrypted(&out, &key).await?;
+    if save_key {
+        encryption::save_key(&key)?;
+        println!("Saved the key to the OS keychain.");
+    }
+    println!("Wrote an encrypted copy to {}", out.display());
+    println!(
+        "Stop any running asanadw processes, then replace {} (and its -wal and -shm files) with it.",
+        source.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+async fn encrypt_database(
+    _db: &asanadw::Database,
+    _source: std::path::PathBuf,
+    _out: Option<std::path::PathBuf>,
+    _save_key: bool,
+) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "Database encryption is not enabled in this build. Reinstall with: cargo install asanadw --features sqlcipher"
+    )
+}
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+async fn handle_sql(
+    db: &asanadw::Database,
+    sql: &str,
+    json: bool,
+    csv: bool,
+) -> anyhow::Result<()> {
+    let result = db.query_arbitrary_table(sql).await?;
+    if json {
+        print_json(&result.to_objects())?;
+        return Ok(());
+    }
+    if csv {
+        print!("{}", result.to_csv());
+        return Ok(());
+    }
+
+    const MAX_WIDTH: usize = 60;
+    let cells: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|v| {
+                    let text = asanadw::storage::sql::value_to_text(v).replace('\n', " ");
+                    if text.chars().count() > MAX_WIDTH {
+                        let truncated: String = text.chars().take(MAX_WIDTH - 1).collect();
+                        format!("{truncated}…")
+                    } else {
+                        text
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = result
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            cells
+                .iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(c.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let format_row = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(v, w)| format!("{v:<w$}"))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", format_row(&result.columns));
+    println!(
+        "{}",
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("-+-")
+    );
+    for row in &cells {
+        println!("{}", format_row(row));
+    }
+    println!("\n{} rows", result.rows.len());
+    Ok(())
+}
+
+async fn handle_export(db: &asanadw::Database, format: ExportFormat) -> anyhow::Result<()> {
+    match format {
+        ExportFormat::Parquet {
+            out,
+            table,
+            anonymize,
+            salt,
+        } => {
+            let anonymizer =
+                anonymize.then(|| asanadw::export::anonymize::Anonymizer::new(salt.as_deref()));
+            export_parquet_now(db, &out, &table, anonymizer.as_ref()).await
+        }
+        ExportFormat::Schedule { action } => handle_export_schedule(db, action).await,
+        ExportFormat::Graph {
+            project,
+            team,
+            period,
+            format,
+            out,
+        } => {
+            export_graph(
+                db,
+                project,
+                team,
+                period.as_deref(),
+                &format,
+                out.as_deref(),
+            )
+            .await
+        }
+    }
+}
+
+async fn handle_project_report(
+    db: &asanadw::Database,
+    project_gid: &str,
+    period: &str,
+    format: &str,
+    out: Option<&str>,
+) -> anyhow::Result<()> {
+    let gid = asanadw::alias::resolve(db, project_gid).await?;
+    let period = asanadw::Period::parse(period)?;
+    let report = asanadw::report::project_report(db, &gid, &period).await?;
+    if format == "json" && out.is_none() {
+        return print_json(&report);
+    }
+    let text = match format {
+        "markdown" | "md" => report.to_markdown(),
+        "html" => report.to_html(),
+        "json" => format!("{}\n", serde_json::to_string_pretty(&report)?),
+        other => {
+            anyhow::bail!("Unknown report format '{other}' (expected markdown, html, or json)")
+        }
+    };
+    match out {
+        Some(path) => {
+            std::fs::write(path, text)?;
+            note!("Wrote the {} report to {path}", report.period_key);
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+async fn export_graph(
+    db: &asanadw::Database,
+    project: Option<String>,
+    team: Option<String>,
+    period: Option<&str>,
+    format: &str,
+    out: Option<&str>,
+) -> anyhow::Result<()> {
+    use asanadw::export::graph::{collaboration_graph, GraphScope};
+
+    let scope = match (project, team) {
+        (Some(p), _) => GraphScope::Project(asanadw::alias::resolve(db, &p).await?),
+        (None, Some(t)) => GraphScope::Team(asanadw::alias::resolve(db, &t).await?),
+        (None, None) => anyhow::bail!("Pass --project or --team"),
+    };
+    let period = period.map(asanadw::Period::parse).transpose()?;
+    let graph = collaboration_graph(db, scope, period.as_ref()).await?;
+    if format == "json" && out.is_none() {
+        return print_json(&graph.adjacency());
+    }
+    let text = match format {
+        "graphml" => graph.to_graphml(),
+        "dot" => graph.to_dot(),
+        "json" => format!("{}\n", serde_json::to_string_pretty(&graph.adjacency())?),
+        other => anyhow::bail!("Unknown graph format '{other}' (expected graphml, dot, or json)"),
+    };
+    match out {
+        Some(path) => {
+            std::fs::write(path, text)?;
+            note!(
+                "Wrote {} users and {} edges to {path}",
+                graph.nodes.len(),
+                graph.edges.len()
+            );
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "grpc")]
+async fn serve_grpc(db: asanadw::Database, addr: std::net::SocketAddr) -> anyhow::Result<()> {
+    note!("Serving gRPC on {addr} (Ctrl-C to stop)");
+    asanadw::grpc::serve(db, addr).await?;
+    Ok(())
+}
+
+#[cfg(not(feature = "grpc"))]
+async fn serve_grpc(_db: asanadw::Database, _addr: std::net::SocketAddr) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "The gRPC server is not enabled in this build. Reinstall with: cargo install asanadw --features grpc"
+    )
+}
+
+#[cfg(feature = "parquet")]
+async fn export_parquet_now(
+    db: &asanadw::Database,
+    out: &str,
+    tables: &[String],
+    anonymizer: Option<&asanadw::export::anonymize::Anonymizer>,
+) -> anyhow::Result<()> {
+    let out = std::path::Path::new(out);
+    let exported = asanadw::export::parquet::export_parquet(db, out, tables, anonymizer).await?;
+    for t in &exported {
+        println!("  {} ({} rows) -> {}", t.table, t.rows, t.path);
+    }
+    println!("Exported {} tables to {}", exported.len(), out.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+async fn export_parquet_now(
+    _db: &asanadw::Database,
+    _out: &str,
+    _tables: &[String],
+    _anonymizer: Option<&asanadw::export::anonymize::Anonymizer>,
+) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "Parquet export is not enabled in this build. Reinstall with: cargo install asanadw --features parquet"
+    )
+}
+
+async fn handle_export_schedule(
+    db: &asanadw::Database,
+    action: ExportScheduleAction,
+) -> anyhow::Result<()> {
+    use asanadw::export::schedule;
+
+    match action {
+        ExportScheduleAction::Add {
+            format,
+            table,
+            cron,
+            out,
+        } => {
+            let id = schedule::add_schedule(db, &format, &table, &cron, &out).await?;
+            println!("Added export schedule #{id}: {format} to {out} on \"{cron}\" (UTC)");
+            println!("Scheduled exports run while `asanadw daemon` is running.");
+        }
+        ExportScheduleAction::List { json } => {
+            let schedules = schedule::list_schedules(db).await?;
+            if json {
+                print_json(&schedules)?;
+                return Ok(());
+            }
+            if schedules.is_empty() {
+                println!("No export schedules.");
+                return Ok(());
+            }
+            println!("Export Schedules (times UTC)");
+            for s in &schedules {
+                println!(
+                    "  #{} {} [{}] -> {}",
+                    s.schedule_id,
+                    s.format,
+                    s.tables.as_deref().unwrap_or("all tables"),
+                    s.out
+                );
+                println!(
+                    "    cron \"{}\" | next: {} | last: {} ({})",
+                    s.cron,
+                    s.next_run_at,
+                    s.last_run_at.as_deref().unwrap_or("never"),
+                    s.last_status.as_deref().unwrap_or("-"),
+                );
+                if let Some(err) = &s.last_error {
+                    println!("    error: {err}");
+                }
+            }
+        }
+        ExportScheduleAction::Remove { id } => {
+            if schedule::remove_schedule(db, id).await? {
+                println!("Removed export schedule #{id}");
+            } else {
+                println!("No export schedule #{id}");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_hot(
+    db: &asanadw::Database,
+    limit: u32,
+    days: u32,
+    json: bool,
+) -> anyhow::Result<()> {
+    let projects = asanadw::metrics::hot::compute_hot_projects(db, days, limit).await?;
+    if json {
+        print_json(&projects)?;
+        return Ok(());
+    }
+
+    println!("Hot projects (last {days} days)");
+    if projects.is_empty() {
+        println!("  No task changes or comments in synced projects.");
+    }
+    for (i, p) in projects.iter().enumerate() {
+        println!(
+            "  {:>2}. {} (heat {:.1}): {} tasks modified, {} comments",
+            i + 1,
+            p.project_name.as_deref().unwrap_or(&p.project_gid),
+            p.heat,
+            p.tasks_modified,
+            p.comments
+        );
+    }
+    Ok(())
+}
+
+async fn handle_recognition(
+    db: &asanadw::Database,
+    period: &str,
+    json: bool,
+) -> anyhow::Result<()> {
+    let p = asanadw::Period::parse(period)?;
+    let report = asanadw::metrics::recognition::compute_recognition(db, &p).await?;
+    if json {
+        print_json(&report)?;
+        return Ok(());
+    }
+
+    println!(
+        "Recognition ({}): {} likes on completed tasks",
+        report.period_key, report.total_likes
+    );
+    if report.users.is_empty() {
+        println!("  No likes found. Likes are captured by project syncs.");
+    }
+    for u in &report.users {
+        println!(
+            "  {}: received {} | given {}",
+            u.user_name.as_deref().unwrap_or(&u.user_gid),
+            u.received,
+            u.given
+        );
+    }
+    Ok(())
+}
+
+async fn handle_cluster(
+    db: &asanadw::Database,
+    project: &str,
+    period: &str,
+    clusters: Option<usize>,
+    json: bool,
+) -> anyhow::Result<()> {
+    if clusters == Some(0) {
+        anyhow::bail!("--clusters must be at least 1");
+    }
+    let gid = asanadw::alias::resolve(db, project).await?;
+    let p = asanadw::Period::parse(period)?;
+    let b = asanadw::metrics::topics::compute_project_topics(db, &gid, &p, clusters).await?;
+    if json {
+        print_json(&b)?;
+        return Ok(());
+    }
+
+    println!(
+        "Topics: {} ({}): {} completed tasks",
+        b.project_name.as_deref().unwrap_or(&b.project_gid),
+        b.period_key,
+        b.total_tasks
+    );
+    if b.topics.is_empty() {
+        println!("  No completed tasks in this period.");
+    }
+    for t in &b.topics {
+        let share = t.tasks as f64 / b.total_tasks as f64 * 100.0;
+        println!("\n  {} — {} tasks ({share:.0}%)", t.label, t.tasks);
+        for name in &t.sample_tasks {
+            println!("    - {name}");
+        }
+    }
+    Ok(())
+}
+
+async fn handle_import(db: &asanadw::Database, source: ImportSource) -> anyhow::Result<()> {
+    match source {
+        ImportSource::OrgChart { file, json } => {
+            let text = if file == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(&file)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {file}: {e}"))?
+            };
+            let report = asanadw::import::org_chart::import_org_chart(db, &text).await?;
+            if json {
+                print_json(&report)?;
             } else {
-                println!("{}", summary.headline);
-                println!("\n{}", summary.what_changed);
-                println!("\n{}", summary.why_it_matters);
-                println!("\nKey milestones:");
-                for m in &summary.key_milestones {
-                    println!("  - {m}");
-                }
-                if let Some(ref health) = summary.health_assessment {
-                    println!("\nHealth: {health}");
+                println!(
+                    "Imported org chart: {} managers set, {} at the top",
+                    report.assigned, report.cleared
+                );
+                if !report.unresolved.is_empty() {
+                    println!("  Skipped (user not synced):");
+                    for u in &report.unresolved {
+                        println!("    {u}");
+                    }
                 }
             }
         }
-        SummarizeTarget::Team {
-            team_gid,
-            period,
-            force,
+        ImportSource::AsanaExport {
+            file,
+            project,
+            name,
             json,
         } => {
-            let p = asanadw::Period::parse(&period)?;
-            let summary = asanadw::llm::agents::period::summarize_team_period(
-                db, &agent, &team_gid, &p, force,
-            )
-            .await?;
+            let text = if file == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(&file)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {file}: {e}"))?
+            };
+            let project_gid = asanadw::alias::resolve(db, &project).await?;
+            let name = name.unwrap_or_else(|| {
+                std::path::Path::new(&file)
+                    .file_stem()
+                    .filter(|_| file != "-")
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| project_gid.clone())
+            });
+            let report =
+                asanadw::import::asana_export::import_asana_export(db, &project_gid, &name, &text)
+                    .await?;
             if json {
-                println!("{}", serde_json::to_string_pretty(&summary)?);
+                print_json(&report)?;
             } else {
-                println!("{}", summary.headline);
-                println!("\n{}", summary.what_changed);
-                println!("\n{}", summary.why_it_matters);
-                println!("\nKey accomplishments:");
-                for a in &summary.key_accomplishments {
-                    println!("  - {a}");
+                println!(
+                    "Imported {} tasks into project:{project_gid}",
+                    report.imported
+                );
+                if report.skipped_synced > 0 {
+                    println!(
+                        "  Skipped {} tasks already synced from the API",
+                        report.skipped_synced
+                    );
                 }
-                if let Some(ref health) = summary.health_assessment {
-                    println!("\nHealth: {health}");
+                for row in &report.skipped_rows {
+                    println!("  Skipped {row}");
                 }
             }
         }
     }
+    Ok(())
+}
+
+async fn handle_stats(db: &asanadw::Database, target: StatsTarget) -> anyhow::Result<()> {
+    let StatsTarget::Me { json } = target;
+    let user_gid = db
+        .reader()
+        .call(|c| asanadw::storage::repository::get_config(c, "user_gid"))
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("User identity not set. Run 'asanadw sync all' first."))?;
+    let s = asanadw::metrics::stats::compute_personal_stats(db, &user_gid).await?;
+    if json {
+        print_json(&s)?;
+        return Ok(());
+    }
 
+    println!("Stats: {}", s.user_name.as_deref().unwrap_or(&s.user_gid));
+    println!("  Completed:        {} tasks", s.tasks_completed);
+    println!("  Current streak:   {} days", s.current_streak_days);
+    match &s.longest_streak_end {
+        Some(end) => println!(
+            "  Longest streak:   {} days (ended {end})",
+            s.longest_streak_days
+        ),
+        None => println!("  Longest streak:   0 days"),
+    }
+    if let Some(ref b) = s.busiest_day {
+        println!("  Busiest day:      {} ({} completed)", b.date, b.completed);
+    }
+    if let Some(ref t) = s.longest_open_closed {
+        println!(
+            "  Longest-open task closed: {} ({}) after {} days",
+            t.name, t.task_gid, t.days_open
+        );
+    }
+    match s.avg_comment_response_hours {
+        Some(h) => println!(
+            "  Comment response: {h:.1} hours on average ({} replies)",
+            s.comment_responses
+        ),
+        None => println!("  Comment response: no replies to others' comments yet"),
+    }
     Ok(())
 }
 
-async fn handle_metrics(db: &asanadw::Database, target: MetricsTarget) -> anyhow::Result<()> {
-    match target {
-        MetricsTarget::Me { period, json } => {
-            let user_gid = db
-                .reader()
-                .call(|c| asanadw::storage::repository::get_config(c, "user_gid"))
-                .await?
-                .ok_or_else(|| {
-                    anyhow::anyhow!("User identity not set. Run 'asanadw sync all' first.")
-                })?;
-            let p = asanadw::Period::parse(&period)?;
-            let m = asanadw::metrics::compute_user_metrics(db, &user_gid, &p).await?;
-            if json {
-                println!("{}", serde_json::to_string_pretty(&m)?);
-            } else {
-                println!(
-                    "User Metrics: {} ({})",
-                    m.user_name.as_deref().unwrap_or(&m.user_gid),
-                    m.period_key
-                );
-                print_throughput(&m.throughput);
-                print_lead_time(&m.lead_time);
-                print_collaboration(&m.collaboration);
-            }
+async fn handle_onboarding_report(
+    db: &asanadw::Database,
+    user: &str,
+    weeks: u32,
+    since: Option<&str>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let user_gid = resolve_user(db, user).await?;
+    let r = asanadw::metrics::onboarding::compute_onboarding_report(
+        db,
+        &user_gid,
+        weeks,
+        parse_since(since),
+    )
+    .await?;
+    if json {
+        print_json(&r)?;
+        return Ok(());
+    }
+
+    println!(
+        "Onboarding: {} ({} to {})",
+        r.user_name.as_deref().unwrap_or(&r.user_gid),
+        r.start_date,
+        r.end_date
+    );
+    println!(
+        "  {} tasks completed, {} comments, {} projects, {} collaborators",
+        r.tasks_completed,
+        r.comments,
+        r.projects.len(),
+        r.collaborators.len()
+    );
+
+    println!("\nWeek of       Completed  Comments");
+    for w in &r.weeks {
+        println!(
+            "{:<12}  {:>9}  {:>8}",
+            w.week_start, w.tasks_completed, w.comments
+        );
+    }
+
+    if !r.projects.is_empty() {
+        println!("\nProjects joined:");
+        for p in &r.projects {
+            println!(
+                "  {}  {} ({} completed)",
+                p.first_activity,
+                p.name.as_deref().unwrap_or(&p.project_gid),
+                p.tasks_completed
+            );
         }
-        MetricsTarget::User {
-            user_gid,
-            period,
-            json,
-        } => {
-            let user_gid = resolve_user(db, &user_gid).await?;
-            let p = asanadw::Period::parse(&period)?;
-            let m = asanadw::metrics::compute_user_metrics(db, &user_gid, &p).await?;
-            if json {
-                println!("{}", serde_json::to_string_pretty(&m)?);
-            } else {
-                println!(
-                    "User Metrics: {} ({})",
-                    m.user_name.as_deref().unwrap_or(&m.user_gid),
-                    m.period_key
-                );
-                print_throughput(&m.throughput);
-                print_lead_time(&m.lead_time);
-                print_collaboration(&m.collaboration);
+    }
+
+    if !r.collaborators.is_empty() {
+        println!("\nWorked with:");
+        for c in &r.collaborators {
+            println!(
+                "  {:<24}  {} shared tasks, {} replies, {} likes",
+                c.user_name.as_deref().unwrap_or(&c.user_gid),
+                c.shared_tasks,
+                c.replies,
+                c.likes
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn handle_status_updates(
+    db: &asanadw::Database,
+    project: Option<&str>,
+    limit: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    use asanadw::storage::repository;
+
+    let gid = match project {
+        Some(p) => Some(asanadw::alias::resolve(db, p).await?),
+        None => None,
+    };
+    let updates = db
+        .reader()
+        .call({
+            let gid = gid.clone();
+            move |conn| match gid {
+                Some(gid) => repository::get_status_updates(conn, &gid, limit),
+                None => repository::latest_project_status_updates(conn),
+            }
+        })
+        .await?;
+    if json {
+        print_json(&updates)?;
+        return Ok(());
+    }
+
+    match &gid {
+        Some(gid) => {
+            let name = updates
+                .first()
+                .and_then(|u| u.parent_name.clone())
+                .unwrap_or_else(|| gid.clone());
+            println!("Status updates: {name}");
+            if updates.is_empty() {
+                println!("  No status updates stored. Sync the project first.");
             }
         }
-        MetricsTarget::Project {
-            project_gid,
-            period,
-            json,
-        } => {
-            let p = asanadw::Period::parse(&period)?;
-            let m = asanadw::metrics::compute_project_metrics(db, &project_gid, &p).await?;
-            if json {
-                println!("{}", serde_json::to_string_pretty(&m)?);
-            } else {
-                println!(
-                    "Project Metrics: {} ({})",
-                    m.project_name.as_deref().unwrap_or(&m.project_gid),
-                    m.period_key
-                );
-                print_throughput(&m.throughput);
-                print_health(&m.health);
-                print_lead_time(&m.lead_time);
-                print_collaboration(&m.collaboration);
+        None => {
+            println!("Latest project status updates");
+            if updates.is_empty() {
+                println!("  No status updates stored.");
             }
         }
-        MetricsTarget::Portfolio {
-            portfolio_gid,
-            period,
-            json,
-        } => {
-            let p = asanadw::Period::parse(&period)?;
-            let m = asanadw::metrics::compute_portfolio_metrics(db, &portfolio_gid, &p).await?;
-            if json {
-                println!("{}", serde_json::to_string_pretty(&m)?);
-            } else {
-                println!(
-                    "Portfolio Metrics: {} ({})",
-                    m.portfolio_name.as_deref().unwrap_or(&m.portfolio_gid),
-                    m.period_key
-                );
-                println!("  Projects: {}", m.project_count);
-                print_throughput(&m.throughput);
-                print_health(&m.health);
-                print_lead_time(&m.lead_time);
-                print_collaboration(&m.collaboration);
+    }
+    for update in &updates {
+        let date = update.created_at.get(..10).unwrap_or(&update.created_at);
+        let status = update.status_type.replace('_', " ").to_uppercase();
+        let author = update
+            .author_name
+            .as_deref()
+            .map(|a| format!(" ({a})"))
+            .unwrap_or_default();
+        if gid.is_some() {
+            println!("  {date}  {status:<10} {}{author}", update.title);
+        } else {
+            let project = update.parent_name.as_deref().unwrap_or(&update.parent_gid);
+            println!("  {date}  {status:<10} {project}: {}{author}", update.title);
+        }
+        const MAX_WIDTH: usize = 100;
+        if let Some(text) = update.text.as_deref().and_then(|t| t.lines().next()) {
+            let text = text.trim();
+            if text.chars().count() > MAX_WIDTH {
+                let truncated: String = text.chars().take(MAX_WIDTH - 1).collect();
+                println!("      {truncated}…");
+            } else if !text.is_empty() {
+                println!("      {text}");
             }
         }
-        MetricsTarget::Team {
-            team_gid,
-            period,
-            json,
-        } => {
-            let p = asanadw::Period::parse(&period)?;
-            let m = asanadw::metrics::compute_team_metrics(db, &team_gid, &p).await?;
-            if json {
-                println!("{}", serde_json::to_string_pretty(&m)?);
-            } else {
-                println!(
-                    "Team Metrics: {} ({})",
-                    m.team_name.as_deref().unwrap_or(&m.team_gid),
-                    m.period_key
-                );
-                println!("  Members: {}", m.member_count);
-                print_throughput(&m.throughput);
-                print_health(&m.health);
-                print_lead_time(&m.lead_time);
-                print_collaboration(&m.collaboration);
+    }
+    Ok(())
+}
+
+async fn handle_task(db: &asanadw::Database, identifier: &str, json: bool) -> anyhow::Result<()> {
+    let gid = asanadw::url::resolve_gid(identifier)?;
+    let (task, threads) = db
+        .reader()
+        .call({
+            let gid = gid.clone();
+            move |conn| {
+                #[allow(clippy::type_complexity)]
+                let task: Option<(
+                    String,
+                    bool,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                )> = conn
+                    .query_row(
+                        "SELECT t.name, t.is_completed, u.name, t.due_on, t.permalink_url
+                         FROM fact_tasks t
+                         LEFT JOIN dim_users u ON u.user_gid = t.assignee_gid
+                         WHERE t.task_gid = ?1",
+                        [&gid],
+                        |row| {
+                            Ok((
+                                row.get(0)?,
+                                row.get(1)?,
+                                row.get(2)?,
+                                row.get(3)?,
+                                row.get(4)?,
+                            ))
+                        },
+                    )
+                    .ok();
+                let threads = asanadw::storage::threads::load_comment_threads(conn, &gid)?;
+                Ok::<_, rusqlite::Error>((task, threads))
             }
+        })
+        .await?;
+
+    let Some((name, is_completed, assignee, due_on, permalink_url)) = task else {
+        anyhow::bail!("Task {gid} not found in the local warehouse. Sync its project first.");
+    };
+
+    if json {
+        let output = serde_json::json!({
+            "task_gid": gid,
+            "name": name,
+            "is_completed": is_completed,
+            "assignee_name": assignee,
+            "due_on": due_on,
+            "permalink_url": permalink_url,
+            "comments": threads,
+        });
+        print_json(&output)?;
+        return Ok(());
+    }
+
+    let status = if is_completed { "done" } else { "open" };
+    println!("[{status}] {name} ({gid})");
+    println!(
+        "  Assignee: {}",
+        assignee.as_deref().unwrap_or("unassigned")
+    );
+    println!("  Due: {}", due_on.as_deref().unwrap_or("no due date"));
+    if let Some(url) = permalink_url {
+        println!("  URL: {url}");
+    }
+    if threads.is_empty() {
+        println!("\nNo comments.");
+    } else {
+        println!("\nComments:");
+        for line in asanadw::storage::threads::render_threads(&threads, 500) {
+            println!("{line}");
         }
     }
     Ok(())
@@ -1187,7 +5353,14 @@ async fn handle_metrics(db: &asanadw::Database, target: MetricsTarget) -> anyhow
 fn print_throughput(t: &asanadw::metrics::ThroughputMetrics) {
     println!("  Throughput:");
     println!("    Created:   {}", t.tasks_created);
-    println!("    Completed: {}", t.tasks_completed);
+    if t.milestones_completed > 0 {
+        println!(
+            "    Completed: {} ({} milestones)",
+            t.tasks_completed, t.milestones_completed
+        );
+    } else {
+        println!("    Completed: {}", t.tasks_completed);
+    }
     println!("    Net new:   {}", t.net_new);
 }
 
@@ -1205,7 +5378,7 @@ fn print_health(h: &asanadw::metrics::HealthMetrics) {
     println!("    Stale (14d): {}", h.stale_count);
 }
 
-fn print_lead_time(lt: &asanadw::metrics::LeadTimeMetrics) {
+fn print_lead_time(lt: &asanadw::metrics::LeadTimeMetrics, histogram: bool) {
     println!("  Lead Time:");
     match lt.avg_days_to_complete {
         Some(avg) => {
@@ -1223,16 +5396,179 @@ fn print_lead_time(lt: &asanadw::metrics::LeadTimeMetrics) {
                 lt.min_days_to_complete.unwrap_or(0),
                 lt.max_days_to_complete.unwrap_or(0)
             );
+            if histogram {
+                print_lead_time_histogram(&lt.histogram);
+            }
         }
         None => println!("    No completed tasks in period"),
     }
 }
 
+fn print_lead_time_histogram(buckets: &[asanadw::metrics::LeadTimeBucket]) {
+    const BAR_WIDTH: u64 = 30;
+    let max = buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    println!("    Distribution:");
+    for b in buckets {
+        // Round up so any non-empty bucket shows at least one block
+        let width = (b.count * BAR_WIDTH).div_ceil(max) as usize;
+        println!("      {:>6} {:<30} {}", b.label, "█".repeat(width), b.count);
+    }
+}
+
+fn print_cycle_time(ct: &asanadw::metrics::CycleTimeMetrics) {
+    println!("  Cycle Time:");
+    match ct.avg_days {
+        Some(avg) => {
+            println!("    Average: {avg:.1} days ({} tasks)", ct.tasks_measured);
+            println!("    Median:  {:.1} days", ct.median_days.unwrap_or(0.0));
+            println!("    P90:     {:.1} days", ct.p90_days.unwrap_or(0.0));
+        }
+        None => println!("    No completed tasks passed through an in-progress section"),
+    }
+    if !ct.section_dwell.is_empty() {
+        println!("  Section Dwell:");
+        for d in &ct.section_dwell {
+            println!(
+                "    {}: avg {:.1} days, median {:.1} days ({} visits)",
+                d.section_name, d.avg_days, d.median_days, d.visits
+            );
+        }
+    }
+}
+
+fn print_phases(p: &asanadw::metrics::PhaseMetrics) {
+    println!("  Phases (open):");
+    println!("    Backlog:     {}", p.backlog_open);
+    println!("    In progress: {}", p.in_progress_open);
+    println!("    Review:      {}", p.review_open);
+    println!("    Done:        {}", p.done_open);
+    if p.unmapped_open > 0 {
+        let sections = if p.unmapped_sections.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", p.unmapped_sections.join(", "))
+        };
+        println!("    Unmapped:    {}{sections}", p.unmapped_open);
+    }
+    if let Some(pct) = p.flow_efficiency_pct {
+        println!("    Flow efficiency: {pct:.1}%");
+    }
+}
+
 fn print_collaboration(c: &asanadw::metrics::CollaborationMetrics) {
     println!("  Collaboration:");
     println!("    Comments:    {}", c.total_comments);
     println!("    Commenters:  {}", c.unique_commenters);
     println!("    Likes:       {}", c.total_likes);
+    if let Some(pct) = c.silent_completion_pct {
+        println!(
+            "    Silent completions: {} ({pct:.1}% of completed)",
+            c.silent_completions
+        );
+    }
+}
+
+fn print_sync_plan(plan: &[asanadw::sync::plan::PlannedSync]) {
+    use asanadw::sync::plan::{EstimateSource, PlanMode, PlannedSync};
+
+    fn print_entry(entry: &PlannedSync, indent: &str) {
+        let name = entry
+            .display_name
+            .as_deref()
+            .map(|n| format!(" ({n})"))
+            .unwrap_or_default();
+        let mode = match entry.mode {
+            PlanMode::Incremental => "incremental".to_string(),
+            PlanMode::Full => format!(
+                "full since {}",
+                entry.since.as_deref().unwrap_or("the default range")
+            ),
+            PlanMode::Projects => format!("{} projects", entry.projects.len()),
+        };
+        let estimate = match (entry.estimated_tasks, entry.estimate_source) {
+            (Some(n), EstimateSource::Stored) => format!(", ~{n} tasks (stored)"),
+            (Some(n), EstimateSource::LastJob) => format!(", ~{n} tasks (last sync)"),
+            _ if entry.mode == PlanMode::Projects => String::new(),
+            _ => ", task count unknown".to_string(),
+        };
+        println!("{indent}{}{name}: {mode}{estimate}", entry.entity_key);
+        for project in &entry.projects {
+            print_entry(project, &format!("{indent}  "));
+        }
+    }
+
+    if plan.is_empty() {
+        println!("No monitored entities to sync. Use 'monitor add' first.");
+        return;
+    }
+    println!("Sync plan (dry run, nothing fetched):");
+    for entry in plan {
+        print_entry(entry, "  ");
+    }
+    let estimated: u64 = plan
+        .iter()
+        .flat_map(|e| std::iter::once(e).chain(&e.projects))
+        .filter_map(|e| e.estimated_tasks)
+        .sum();
+    println!("Estimated tasks: ~{estimated}");
+}
+
+fn print_coverage(coverage: &asanadw::sync::gap::Coverage) {
+    println!(
+        "Coverage: {} from {} to {}",
+        coverage.entity_key, coverage.start, coverage.end
+    );
+    if coverage.covered.is_empty() {
+        println!("  Nothing synced in this range yet.");
+    } else {
+        println!("  Synced:");
+        for range in &coverage.covered {
+            println!("    {range}");
+        }
+    }
+    if coverage.holes.is_empty() {
+        println!("  No gaps.");
+    } else {
+        println!("  Gaps:");
+        for hole in &coverage.holes {
+            let days = (hole.end - hole.start).num_days() + 1;
+            println!("    {hole} ({days} days)");
+        }
+    }
+}
+
+fn format_api_usage(usage: &asanadw::sync::rate_limit::RateLimitUsage) -> String {
+    format!(
+        "{} requests, {} rate-limited retries, {:.0}s waiting",
+        usage.requests, usage.retries, usage.wait_secs
+    )
+}
+
+fn format_secs(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// One line comparing a sync with the entity's previous `sync all` run.
+fn print_sync_diff(diff: &asanadw::sync::history::SyncReportDiff) {
+    println!(
+        "  vs last: {} items (was {}), {} (was {}), {} requests (was {})",
+        diff.items_synced,
+        diff.previous_items_synced,
+        format_secs(diff.duration_secs),
+        format_secs(diff.previous_duration_secs),
+        diff.requests,
+        diff.previous_requests
+    );
+    if diff.new_failure {
+        println!("  New failure: the previous sync succeeded");
+    } else if diff.recovered {
+        println!("  Recovered: the previous sync failed");
+    }
 }
 
 fn print_sync_report(report: &asanadw::SyncReport) {
@@ -1240,10 +5576,22 @@ fn print_sync_report(report: &asanadw::SyncReport) {
     println!("  Status:  {:?}", report.status);
     println!("  Synced:  {} items", report.items_synced);
     println!("  Failed:  {} items", report.items_failed);
+    if report.skipped_duplicates > 0 {
+        println!(
+            "  Shared:  {} projects synced earlier this run, counted above",
+            report.skipped_duplicates
+        );
+    }
+    for range in &report.skipped_ranges {
+        println!("  Skipped: {range}, already synced");
+    }
     println!(
         "  Batches: {}/{}",
         report.batches_completed, report.batches_total
     );
+    if report.rate_limit.requests > 0 {
+        println!("  API:     {}", format_api_usage(&report.rate_limit));
+    }
     if let Some(ref err) = report.error {
         println!("  Error:   {err}");
     }
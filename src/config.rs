@@ -0,0 +1,505 @@
+//! Validation of `app_config` values and the secrets they depend on.
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::export::schedule::CronSchedule;
+use crate::metrics::cycle_time::CYCLE_START_SECTIONS_KEY;
+use crate::metrics::notability::{parse_notability_weights, NOTABILITY_WEIGHTS_KEY};
+use crate::metrics::phases::{parse_section_phases, SECTION_PHASES_KEY};
+use crate::metrics::workload::WORKLOAD_ESTIMATE_FIELD_KEY;
+use crate::query::labels::{parse_project_labels, PROJECT_LABELS_KEY};
+use crate::query::period::Period;
+use crate::storage::bots::{BOT_AUTHORS_KEY, BOT_STORY_TYPES_KEY};
+use crate::storage::field_changes::TRACKED_FIELDS_KEY;
+use crate::storage::repository;
+use crate::storage::retention::{cutoff_date, RETENTION_ARCHIVE_DIR_KEY, RETENTION_KEY};
+use crate::storage::Database;
+use crate::sync::scheduler::{parse_interval, SYNC_INTERVAL_KEY};
+
+/// Providers accepted by `llm_provider`.
+pub const LLM_PROVIDERS: &[&str] = &["bedrock", "anthropic", "openai"];
+
+/// Models the `bedrock` and `anthropic` providers map to; anything else
+/// falls back to Sonnet. The `openai` provider takes any model identifier.
+pub const LLM_MODELS: &[&str] = &["claude-sonnet-4-5", "sonnet", "claude-haiku-4-5", "haiku"];
+
+/// Config key for the period `--period` flags default to instead of `qtd`.
+pub const DEFAULT_PERIOD_KEY: &str = "default_period";
+
+/// Config key for the output format of commands that take `--json`.
+pub const DEFAULT_OUTPUT_KEY: &str = "default_output";
+
+/// Values accepted by `default_output`.
+pub const OUTPUT_FORMATS: &[&str] = &["text", "json"];
+
+/// Every config key asanadw reads, with a one-line description.
+pub const KNOWN_KEYS: &[(&str, &str)] = &[
+    ("workspace_gid", "Asana workspace GID"),
+    (
+        "llm_provider",
+        "LLM provider: bedrock, anthropic, or openai",
+    ),
+    ("llm_model", "LLM model name"),
+    (
+        "llm_base_url",
+        "OpenAI-compatible API root for the openai provider",
+    ),
+    ("llm_api_key", "API key for the openai provider"),
+    (
+        "llm_embedding_model",
+        "embedding model for topic clustering with the openai provider",
+    ),
+    (
+        CYCLE_START_SECTIONS_KEY,
+        "sections that start the cycle-time clock",
+    ),
+    (SECTION_PHASES_KEY, "section names for each lifecycle phase"),
+    (SYNC_INTERVAL_KEY, "default daemon sync interval"),
+    (PROJECT_LABELS_KEY, "project label mapping"),
+    (
+        WORKLOAD_ESTIMATE_FIELD_KEY,
+        "number custom field holding task estimates",
+    ),
+    (
+        NOTABILITY_WEIGHTS_KEY,
+        "weights for heuristic task notability scores",
+    ),
+    (DEFAULT_PERIOD_KEY, "period used when --period is omitted"),
+    (
+        DEFAULT_OUTPUT_KEY,
+        "output format when --json is omitted: text or json",
+    ),
+    (
+        RETENTION_KEY,
+        "age past which sync all prunes old data, e.g. 2y",
+    ),
+    (
+        RETENTION_ARCHIVE_DIR_KEY,
+        "directory automatic pruning archives rows into",
+    ),
+    (
+        TRACKED_FIELDS_KEY,
+        "custom fields whose value changes are recorded",
+    ),
+    (
+        BOT_AUTHORS_KEY,
+        "authors whose stories are bot noise: GIDs or name/email patterns",
+    ),
+    (BOT_STORY_TYPES_KEY, "story types that are always bot noise"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Will cause a runtime failure.
+    Error,
+    /// Probably a mistake, but asanadw will still run.
+    Warning,
+}
+
+/// One problem found by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    /// Config key, environment variable, or `export_schedule:<id>`.
+    pub key: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// Check one config value. Unknown keys are reported as likely typos.
+pub fn validate_entry(key: &str, value: &str) -> Option<ConfigIssue> {
+    let value = value.trim();
+    match key {
+        "workspace_gid" => (value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()))
+            .then(|| ConfigIssue::error(key, format!("'{value}' is not a numeric GID"))),
+        "llm_provider" => (!LLM_PROVIDERS.contains(&value)).then(|| {
+            ConfigIssue::error(
+                key,
+                format!(
+                    "unknown provider '{value}'. Use: {}",
+                    LLM_PROVIDERS.join(", ")
+                ),
+            )
+        }),
+        "llm_model" | "llm_embedding_model" => value
+            .is_empty()
+            .then(|| ConfigIssue::error(key, "no model given")),
+        "llm_base_url" => match url::Url::parse(value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => None,
+            Ok(_) => Some(ConfigIssue::error(key, "must be an http or https URL")),
+            Err(e) => Some(ConfigIssue::error(
+                key,
+                format!("'{value}' is not a URL: {e}"),
+            )),
+        },
+        "llm_api_key" => value
+            .is_empty()
+            .then(|| ConfigIssue::error(key, "no key given")),
+        CYCLE_START_SECTIONS_KEY => value
+            .split(',')
+            .all(|s| s.trim().is_empty())
+            .then(|| ConfigIssue::error(key, "no section names given")),
+        SECTION_PHASES_KEY => parse_section_phases(value)
+            .err()
+            .map(|e| ConfigIssue::error(key, e.to_string())),
+        SYNC_INTERVAL_KEY => parse_interval(value)
+            .err()
+            .map(|e| ConfigIssue::error(key, e.to_string())),
+        PROJECT_LABELS_KEY => parse_project_labels(value)
+            .err()
+            .map(|e| ConfigIssue::error(key, e.to_string())),
+        WORKLOAD_ESTIMATE_FIELD_KEY => value
+            .is_empty()
+            .then(|| ConfigIssue::error(key, "no custom field given")),
+        NOTABILITY_WEIGHTS_KEY => parse_notability_weights(value)
+            .err()
+            .map(|e| ConfigIssue::error(key, e.to_string())),
+        DEFAULT_PERIOD_KEY => Period::parse(value)
+            .err()
+            .map(|e| ConfigIssue::error(key, e.to_string())),
+        DEFAULT_OUTPUT_KEY => (!OUTPUT_FORMATS.contains(&value)).then(|| {
+            ConfigIssue::error(
+                key,
+                format!(
+                    "unknown format '{value}'. Use: {}",
+                    OUTPUT_FORMATS.join(", ")
+                ),
+            )
+        }),
+        RETENTION_KEY => cutoff_date(value, chrono::Local::now().date_naive())
+            .err()
+            .map(|e| ConfigIssue::error(key, e.to_string())),
+        RETENTION_ARCHIVE_DIR_KEY => value
+            .is_empty()
+            .then(|| ConfigIssue::error(key, "no directory given")),
+        TRACKED_FIELDS_KEY => value
+            .split(',')
+            .all(|s| s.trim().is_empty())
+            .then(|| ConfigIssue::error(key, "no custom field names given")),
+        BOT_AUTHORS_KEY | BOT_STORY_TYPES_KEY => value
+            .split(',')
+            .all(|s| s.trim().is_empty())
+            .then(|| ConfigIssue::error(key, "no entries given")),
+        #[cfg(feature = "llm")]
+        _ if key.starts_with(crate::llm::prompts::CONFIG_PREFIX) => validate_prompt(key, value),
+        _ => {
+            let message = match closest_known_key(key) {
+                Some(known) => format!("unknown key (did you mean '{known}'?)"),
+                None => "unknown key; asanadw never reads it".to_string(),
+            };
+            Some(ConfigIssue::warning(key, message))
+        }
+    }
+}
+
+/// Check a `prompt.<name>` override against the built-in prompt it replaces.
+#[cfg(feature = "llm")]
+fn validate_prompt(key: &str, value: &str) -> Option<ConfigIssue> {
+    use crate::llm::prompts;
+
+    let name = &key[prompts::CONFIG_PREFIX.len()..];
+    match prompts::spec(name) {
+        Some(spec) => prompts::validate_template(spec, value)
+            .err()
+            .map(|e| ConfigIssue::error(key, e)),
+        None => {
+            let names: Vec<_> = prompts::PROMPTS.iter().map(|p| p.name).collect();
+            Some(ConfigIssue::error(
+                key,
+                format!("unknown prompt '{name}'. Use: {}", names.join(", ")),
+            ))
+        }
+    }
+}
+
+/// The known key within edit distance 2 of `key`, if any.
+fn closest_known_key(key: &str) -> Option<&'static str> {
+    KNOWN_KEYS
+        .iter()
+        .map(|(known, _)| (*known, edit_distance(key, known)))
+        .filter(|(_, d)| *d <= 2)
+        .min_by_key(|(_, d)| *d)
+        .map(|(known, _)| known)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Check that the LLM settings work together: `bedrock` and `anthropic`
+/// only know the Claude models in [`LLM_MODELS`], while `openai` needs a
+/// base URL and an explicit model.
+pub fn validate_llm_settings(
+    provider: Option<&str>,
+    model: Option<&str>,
+    base_url: Option<&str>,
+) -> Vec<ConfigIssue> {
+    let provider = provider.unwrap_or("bedrock");
+    let mut issues = Vec::new();
+    if provider == "openai" {
+        if base_url.is_none() {
+            issues.push(ConfigIssue::error(
+                "llm_base_url",
+                "not set, but llm_provider is openai",
+            ));
+        }
+        if model.is_none() {
+            issues.push(ConfigIssue::error(
+                "llm_model",
+                "not set, but llm_provider is openai (there is no default model)",
+            ));
+        }
+        return issues;
+    }
+    if let Some(model) = model.filter(|m| !LLM_MODELS.contains(m)) {
+        issues.push(ConfigIssue::warning(
+            "llm_model",
+            format!(
+                "unrecognized model '{model}' for {provider} will fall back to claude-sonnet-4-5. \
+                 Known: {}. Use llm_provider openai for other models",
+                LLM_MODELS.join(", ")
+            ),
+        ));
+    }
+    if base_url.is_some() {
+        issues.push(ConfigIssue::warning(
+            "llm_base_url",
+            format!("ignored by the {provider} provider"),
+        ));
+    }
+    issues
+}
+
+/// Defaults for CLI flags, from `default_period` and `default_output`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CliDefaults {
+    /// Replaces the built-in `qtd` default of `--period`.
+    pub period: Option<String>,
+    /// Whether commands that take `--json` output JSON without it.
+    pub json: bool,
+}
+
+/// Read the CLI defaults. Invalid values are a [`Error::Config`].
+pub async fn cli_defaults(db: &Database) -> Result<CliDefaults> {
+    let (period, output) = db
+        .reader()
+        .call(|conn| {
+            Ok::<_, rusqlite::Error>((
+                repository::get_config(conn, DEFAULT_PERIOD_KEY)?,
+                repository::get_config(conn, DEFAULT_OUTPUT_KEY)?,
+            ))
+        })
+        .await?;
+    for (key, value) in [(DEFAULT_PERIOD_KEY, &period), (DEFAULT_OUTPUT_KEY, &output)] {
+        if let Some(issue) = value.as_deref().and_then(|v| validate_entry(key, v)) {
+            return Err(Error::Config(format!("{key}: {}", issue.message)));
+        }
+    }
+    Ok(CliDefaults {
+        period: period.map(|p| p.trim().to_string()),
+        json: output.is_some_and(|o| o.trim() == "json"),
+    })
+}
+
+/// The configured `default_period`, or `fallback` when it's unset.
+pub async fn default_period(db: &Database, fallback: &str) -> Result<Period> {
+    let period = cli_defaults(db).await?.period;
+    Period::parse(period.as_deref().unwrap_or(fallback))
+}
+
+/// Validate the stored LLM settings, each on its own and together.
+pub async fn validate_llm(db: &Database) -> Result<Vec<ConfigIssue>> {
+    let entries = db
+        .reader()
+        .call(|conn| repository::list_config(conn))
+        .await?;
+    Ok(llm_issues(&entries))
+}
+
+fn llm_issues(entries: &[(String, String)]) -> Vec<ConfigIssue> {
+    let mut issues: Vec<ConfigIssue> = entries
+        .iter()
+        .filter(|(k, _)| k.starts_with("llm_"))
+        .filter_map(|(key, value)| validate_entry(key, value))
+        .collect();
+    issues.extend(cross_check_llm(entries));
+    issues
+}
+
+fn cross_check_llm(entries: &[(String, String)]) -> Vec<ConfigIssue> {
+    let get = |key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.trim())
+    };
+    validate_llm_settings(get("llm_provider"), get("llm_model"), get("llm_base_url"))
+}
+
+/// Check secrets the configured features need, using `env` to read
+/// environment variables. Only presence is checked; values are never shown.
+pub fn validate_secrets(
+    llm_provider: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Vec<ConfigIssue> {
+    let set = |name: &str| env(name).is_some_and(|v| !v.trim().is_empty());
+    let mut issues = Vec::new();
+    if !set("ASANA_TOKEN") {
+        issues.push(ConfigIssue::warning(
+            "ASANA_TOKEN",
+            "not set; sync and the daemon will fail",
+        ));
+    }
+    if llm_provider == Some("anthropic") && !set("ANTHROPIC_API_KEY") {
+        issues.push(ConfigIssue::warning(
+            "ANTHROPIC_API_KEY",
+            "not set, but llm_provider is anthropic",
+        ));
+    }
+    issues
+}
+
+/// Validate every stored config value, export schedule, and required secret.
+pub async fn validate(db: &Database) -> Result<Vec<ConfigIssue>> {
+    let (entries, schedules) = db
+        .reader()
+        .call(|conn| {
+            let entries = repository::list_config(conn)?;
+            let schedules = repository::list_export_schedules(conn)?;
+            Ok::<_, rusqlite::Error>((entries, schedules))
+        })
+        .await?;
+
+    let mut issues: Vec<ConfigIssue> = entries
+        .iter()
+        .filter_map(|(key, value)| validate_entry(key, value))
+        .collect();
+    for s in &schedules {
+        if let Err(e) = CronSchedule::parse(&s.cron) {
+            issues.push(ConfigIssue::error(
+                &format!("export_schedule:{}", s.schedule_id),
+                e.to_string(),
+            ));
+        }
+    }
+    issues.extend(cross_check_llm(&entries));
+    let provider = entries
+        .iter()
+        .find(|(k, _)| k == "llm_provider")
+        .map(|(_, v)| v.trim());
+    issues.extend(validate_secrets(provider, |name| std::env::var(name).ok()));
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_entry() {
+        assert_eq!(validate_entry("workspace_gid", "12345"), None);
+        assert!(validate_entry("workspace_gid", "acme").is_some());
+        assert_eq!(validate_entry("llm_provider", "anthropic"), None);
+        assert_eq!(validate_entry("llm_provider", "openai"), None);
+        assert_eq!(
+            validate_entry("llm_provider", "vertex").unwrap().severity,
+            Severity::Error
+        );
+        assert_eq!(validate_entry("llm_model", "gpt-4o"), None);
+        assert!(validate_entry("llm_model", " ").is_some());
+        assert_eq!(
+            validate_entry("llm_base_url", "http://localhost:8000/v1"),
+            None
+        );
+        assert!(validate_entry("llm_base_url", "localhost:8000").is_some());
+        assert!(validate_entry("llm_base_url", "ftp://models.internal").is_some());
+        assert_eq!(validate_entry("sync_interval", "2h"), None);
+        assert!(validate_entry("sync_interval", "2 weeks").is_some());
+        assert!(validate_entry("cycle_start_sections", " , ").is_some());
+        assert!(validate_entry("project_labels", "no-equals").is_some());
+        assert_eq!(validate_entry("default_period", "mtd"), None);
+        assert!(validate_entry("default_period", "monthly").is_some());
+        assert_eq!(validate_entry("default_output", "json"), None);
+        assert!(validate_entry("default_output", "yaml").is_some());
+        assert_eq!(validate_entry("retention", "2y"), None);
+        assert!(validate_entry("retention", "forever").is_some());
+
+        let typo = validate_entry("sync_intervall", "30m").unwrap();
+        assert_eq!(typo.severity, Severity::Warning);
+        assert!(typo.message.contains("'sync_interval'"));
+        assert!(!validate_entry("color_scheme", "dark")
+            .unwrap()
+            .message
+            .contains("did you mean"));
+
+        #[cfg(feature = "llm")]
+        {
+            assert_eq!(validate_entry("prompt.task", "Summarize: {context}"), None);
+            assert!(validate_entry("prompt.task", "Summarize this").is_some());
+            assert_eq!(
+                validate_entry("prompt.tsak", "{context}").unwrap().severity,
+                Severity::Error
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_llm_settings() {
+        assert!(validate_llm_settings(None, None, None).is_empty());
+        let issues = validate_llm_settings(Some("bedrock"), Some("gpt-4o"), None);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+
+        assert!(validate_llm_settings(
+            Some("openai"),
+            Some("gpt-4o"),
+            Some("https://api.openai.com/v1")
+        )
+        .is_empty());
+        let issues = validate_llm_settings(Some("openai"), None, None);
+        assert_eq!(
+            issues.iter().map(|i| i.key.as_str()).collect::<Vec<_>>(),
+            ["llm_base_url", "llm_model"]
+        );
+        assert!(issues.iter().all(|i| i.severity == Severity::Error));
+    }
+
+    #[test]
+    fn test_validate_secrets() {
+        let env = |name: &str| (name == "ASANA_TOKEN").then(|| "token".to_string());
+        assert!(validate_secrets(Some("bedrock"), env).is_empty());
+        let issues = validate_secrets(Some("anthropic"), env);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, "ANTHROPIC_API_KEY");
+        assert_eq!(validate_secrets(None, |_| None).len(), 1);
+    }
+}
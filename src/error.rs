@@ -15,6 +15,12 @@ pub enum Error {
     #[error("Sync error for {entity_key}: {message}")]
     Sync { entity_key: String, message: String },
 
+    #[error("Sync lock held: {0}")]
+    SyncLocked(String),
+
+    #[error("Read-only mode: {0} is not allowed")]
+    ReadOnly(String),
+
     #[error("Invalid URL: {0}")]
     UrlParse(String),
 
@@ -30,9 +36,15 @@ pub enum Error {
     #[error("LLM error: {0}")]
     Llm(String),
 
+    #[error("Auth error: {0}")]
+    Auth(String),
+
     #[error("Entity not found: {0}")]
     NotFound(String),
 
+    #[error("Not synced: {0} has no local data")]
+    NotSynced(String),
+
     #[error("{0}")]
     Other(String),
 }
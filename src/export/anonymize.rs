@@ -0,0 +1,185 @@
+//! Redaction applied to exported rows so a warehouse can be shared for
+//! process analysis without revealing who did what or what they wrote.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use rusqlite::types::Value;
+use sha2::{Digest, Sha256};
+
+/// Characters of free text (notes, comments, status updates) kept per value.
+pub const DEFAULT_TEXT_CHARS: usize = 80;
+
+/// Columns holding a user GID, in any table.
+const USER_GID_COLUMNS: &[&str] = &["user_gid", "assignee_gid", "author_gid", "owner_gid"];
+
+/// Free-text columns that are truncated.
+const TEXT_COLUMNS: &[&str] = &["notes", "text", "description", "text_value"];
+
+/// Rich-text duplicates of free text, dropped entirely.
+const HTML_COLUMNS: &[&str] = &["notes_html", "html_text"];
+
+static RE_EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap());
+
+/// Rewrites exported rows: user GIDs become salted pseudonyms, user names
+/// are replaced by the same pseudonym, emails are removed, and free text is
+/// truncated.
+///
+/// Pseudonyms are stable for a given salt, so tables still join on user
+/// columns. Reuse a salt to keep pseudonyms stable across exports; without
+/// one, a random salt is used and the export cannot be linked to others.
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    salt: String,
+    text_chars: usize,
+}
+
+impl Anonymizer {
+    pub fn new(salt: Option<&str>) -> Self {
+        let salt = match salt {
+            Some(s) => s.to_string(),
+            None => random_salt(),
+        };
+        Self {
+            salt,
+            text_chars: DEFAULT_TEXT_CHARS,
+        }
+    }
+
+    /// Keep at most `chars` characters of free text (0 drops it).
+    pub fn with_text_chars(mut self, chars: usize) -> Self {
+        self.text_chars = chars;
+        self
+    }
+
+    /// Salted pseudonym for a user GID.
+    pub fn pseudonym(&self, user_gid: &str) -> String {
+        let digest = Sha256::new()
+            .chain_update(self.salt.as_bytes())
+            .chain_update(b":")
+            .chain_update(user_gid.as_bytes())
+            .finalize();
+        let hex: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+        format!("anon-{hex}")
+    }
+
+    /// Redact one row of `table` in place. `columns` names each value.
+    pub fn anonymize_row(&self, table: &str, columns: &[String], values: &mut [Value]) {
+        let user_gid = (table == "dim_users")
+            .then(|| columns.iter().position(|c| c == "user_gid"))
+            .flatten()
+            .and_then(|i| match &values[i] {
+                Value::Text(gid) => Some(gid.clone()),
+                _ => None,
+            });
+
+        for (column, value) in columns.iter().zip(values.iter_mut()) {
+            let column = column.as_str();
+            if column == "email" || HTML_COLUMNS.contains(&column) {
+                *value = Value::Null;
+            } else if table == "dim_users" && column == "name" {
+                *value = match &user_gid {
+                    Some(gid) => Value::Text(self.pseudonym(gid)),
+                    None => Value::Null,
+                };
+            } else if USER_GID_COLUMNS.contains(&column) {
+                if let Value::Text(gid) = value {
+                    *value = Value::Text(self.pseudonym(gid));
+                }
+            } else if TEXT_COLUMNS.contains(&column) {
+                if let Value::Text(text) = value {
+                    *value = Value::Text(self.redact_text(text));
+                }
+            }
+        }
+    }
+
+    /// Emails are replaced before truncating so none is left half-cut.
+    fn redact_text(&self, text: &str) -> String {
+        RE_EMAIL
+            .replace_all(text, "[email]")
+            .chars()
+            .take(self.text_chars)
+            .collect()
+    }
+}
+
+fn random_salt() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let state =
+        std::hash::BuildHasher::hash_one(&std::collections::hash_map::RandomState::new(), nanos);
+    let digest = Sha256::new()
+        .chain_update(nanos.to_le_bytes())
+        .chain_update(std::process::id().to_le_bytes())
+        .chain_update(state.to_le_bytes())
+        .finalize();
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_anonymize_rows() {
+        let anon = Anonymizer::new(Some("salt")).with_text_chars(20);
+        let pseudonym = anon.pseudonym("u1");
+        assert!(pseudonym.starts_with("anon-"));
+        assert_eq!(pseudonym, Anonymizer::new(Some("salt")).pseudonym("u1"));
+        assert_ne!(pseudonym, Anonymizer::new(Some("other")).pseudonym("u1"));
+
+        let columns: Vec<String> = ["user_gid", "email", "name", "cached_at"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let mut user = vec![
+            text("u1"),
+            text("a@example.com"),
+            text("Alice"),
+            text("now"),
+        ];
+        anon.anonymize_row("dim_users", &columns, &mut user);
+        assert_eq!(
+            user,
+            vec![text(&pseudonym), Value::Null, text(&pseudonym), text("now")]
+        );
+
+        let columns: Vec<String> = ["comment_gid", "author_gid", "text", "html_text"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let mut comment = vec![
+            text("c1"),
+            text("u1"),
+            text("ping bob@example.com about the long thing"),
+            text("<body>ping</body>"),
+        ];
+        anon.anonymize_row("fact_comments", &columns, &mut comment);
+        assert_eq!(
+            comment,
+            vec![
+                text("c1"),
+                text(&pseudonym),
+                text("ping [email] about t"),
+                Value::Null
+            ]
+        );
+
+        // Names outside dim_users (projects, tasks) are kept
+        let mut project = vec![text("p1"), text("Roadmap")];
+        anon.anonymize_row(
+            "dim_projects",
+            &["project_gid".to_string(), "name".to_string()],
+            &mut project,
+        );
+        assert_eq!(project, vec![text("p1"), text("Roadmap")]);
+    }
+}
@@ -0,0 +1,479 @@
+//! User-to-user collaboration graphs for org-network analysis, exported as
+//! GraphML, Graphviz DOT, or a JSON adjacency list.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::query::period::Period;
+use crate::storage::Database;
+
+/// A user mention anywhere in a comment's HTML body.
+static RE_MENTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<a [^>]*data-asana-gid="([^"]+)"[^>]*data-asana-type="user""#).unwrap()
+});
+
+/// What the graph covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphScope {
+    /// Everyone who worked on the project's tasks.
+    Project(String),
+    /// The team's members, and only edges between them.
+    Team(String),
+}
+
+impl GraphScope {
+    /// `project:<gid>` or `team:<gid>`.
+    pub fn key(&self) -> String {
+        match self {
+            GraphScope::Project(gid) => format!("project:{gid}"),
+            GraphScope::Team(gid) => format!("team:{gid}"),
+        }
+    }
+}
+
+/// A user in the graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub user_gid: String,
+    pub name: Option<String>,
+}
+
+/// An undirected edge between two users; `source` sorts before `target`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// Tasks both were assigned to or commented on.
+    pub shared_tasks: u64,
+    /// Comment replies between the two, in either direction.
+    pub replies: u64,
+    /// Comments in which one @mentioned the other.
+    pub mentions: u64,
+    /// `shared_tasks + replies + mentions`.
+    pub weight: u64,
+}
+
+/// A collaboration graph, ready to export.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollaborationGraph {
+    pub scope: String,
+    pub period_key: Option<String>,
+    pub nodes: Vec<GraphNode>,
+    /// Heaviest first.
+    pub edges: Vec<GraphEdge>,
+}
+
+/// One neighbor in the JSON adjacency list.
+#[derive(Debug, Clone, Serialize)]
+pub struct Neighbor {
+    pub user_gid: String,
+    pub weight: u64,
+    pub shared_tasks: u64,
+    pub replies: u64,
+    pub mentions: u64,
+}
+
+/// One user's entry in the JSON adjacency list.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdjacencyEntry {
+    pub name: Option<String>,
+    /// Heaviest first.
+    pub neighbors: Vec<Neighbor>,
+}
+
+/// The graph as `{ user_gid: { name, neighbors } }`, each edge listed under
+/// both of its users.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdjacencyList {
+    pub scope: String,
+    pub period_key: Option<String>,
+    pub users: BTreeMap<String, AdjacencyEntry>,
+}
+
+#[derive(Default)]
+struct EdgeCounts {
+    shared_tasks: u64,
+    replies: u64,
+    mentions: u64,
+}
+
+fn edge_key(a: &str, b: &str) -> Option<(String, String)> {
+    match a.cmp(b) {
+        std::cmp::Ordering::Less => Some((a.to_string(), b.to_string())),
+        std::cmp::Ordering::Greater => Some((b.to_string(), a.to_string())),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// Build the graph for `scope` from tasks open at some point in
+/// `[start, end]` and the comments made on them in that window.
+pub(crate) fn collaboration_graph_sql(
+    conn: &Connection,
+    scope: &GraphScope,
+    start: &str,
+    end: &str,
+) -> std::result::Result<CollaborationGraph, rusqlite::Error> {
+    let (scope_gid, scope_tasks) = match scope {
+        GraphScope::Project(gid) => (
+            gid,
+            "SELECT task_gid FROM bridge_task_projects WHERE project_gid = ?1",
+        ),
+        GraphScope::Team(gid) => (
+            gid,
+            "SELECT task_gid FROM fact_tasks WHERE assignee_gid IN
+                 (SELECT user_gid FROM bridge_team_members WHERE team_gid = ?1)
+             UNION
+             SELECT task_gid FROM fact_comments WHERE story_type = 'comment' AND is_bot = 0 AND author_gid IN
+                 (SELECT user_gid FROM bridge_team_members WHERE team_gid = ?1)",
+        ),
+    };
+    let scoped = format!(
+        "scoped AS (
+             SELECT t.task_gid, t.assignee_gid FROM fact_tasks t
+             WHERE t.task_gid IN ({scope_tasks}) AND t.is_deleted = 0
+               AND t.created_date_key <= ?3
+               AND (t.completed_date_key IS NULL OR t.completed_date_key >= ?2)
+         )"
+    );
+
+    // Participants per task: the assignee and everyone who commented
+    let mut participants: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut stmt = conn.prepare(&format!(
+        "WITH {scoped}
+         SELECT task_gid, assignee_gid FROM scoped WHERE assignee_gid IS NOT NULL
+         UNION
+         SELECT c.task_gid, c.author_gid FROM fact_comments c
+         JOIN scoped s ON s.task_gid = c.task_gid
+         WHERE c.story_type = 'comment' AND c.is_bot = 0 AND c.author_gid IS NOT NULL
+           AND c.created_date_key >= ?2 AND c.created_date_key <= ?3"
+    ))?;
+    let rows = stmt.query_map(params![scope_gid, start, end], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (task_gid, user_gid) = row?;
+        participants.entry(task_gid).or_default().insert(user_gid);
+    }
+
+    let mut edges: HashMap<(String, String), EdgeCounts> = HashMap::new();
+    for users in participants.values() {
+        let users: Vec<&String> = users.iter().collect();
+        for (i, a) in users.iter().enumerate() {
+            for b in &users[i + 1..] {
+                if let Some(key) = edge_key(a, b) {
+                    edges.entry(key).or_default().shared_tasks += 1;
+                }
+            }
+        }
+    }
+
+    // Replies and mentions, from the comments themselves
+    let mut stmt = conn.prepare(&format!(
+        "WITH {scoped}
+         SELECT c.author_gid, p.author_gid, c.html_text FROM fact_comments c
+         JOIN scoped s ON s.task_gid = c.task_gid
+         LEFT JOIN fact_comments p ON p.comment_gid = c.parent_comment_gid AND p.is_bot = 0
+         WHERE c.story_type = 'comment' AND c.is_bot = 0 AND c.author_gid IS NOT NULL
+           AND c.created_date_key >= ?2 AND c.created_date_key <= ?3"
+    ))?;
+    let rows = stmt.query_map(params![scope_gid, start, end], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (author, parent_author, html) = row?;
+        if let Some(key) = parent_author.and_then(|p| edge_key(&author, &p)) {
+            edges.entry(key).or_default().replies += 1;
+        }
+        let mentioned: BTreeSet<&str> = RE_MENTION
+            .captures_iter(html.as_deref().unwrap_or(""))
+            .filter_map(|c| c.get(1).map(|m| m.as_str()))
+            .collect();
+        for user_gid in mentioned {
+            if let Some(key) = edge_key(&author, user_gid) {
+                edges.entry(key).or_default().mentions += 1;
+            }
+        }
+    }
+
+    // A team graph keeps only its members, isolated ones included
+    let mut node_gids: BTreeSet<String> = BTreeSet::new();
+    if let GraphScope::Team(team_gid) = scope {
+        let mut stmt =
+            conn.prepare("SELECT user_gid FROM bridge_team_members WHERE team_gid = ?1")?;
+        let members = stmt.query_map([team_gid], |row| row.get::<_, String>(0))?;
+        for member in members {
+            node_gids.insert(member?);
+        }
+        edges.retain(|(a, b), _| node_gids.contains(a) && node_gids.contains(b));
+    } else {
+        node_gids.extend(participants.into_values().flatten());
+        for (a, b) in edges.keys() {
+            node_gids.insert(a.clone());
+            node_gids.insert(b.clone());
+        }
+    }
+
+    let mut stmt = conn.prepare("SELECT name FROM dim_users WHERE user_gid = ?1")?;
+    let mut nodes = Vec::with_capacity(node_gids.len());
+    for user_gid in node_gids {
+        let name = stmt
+            .query_row([&user_gid], |row| row.get::<_, Option<String>>(0))
+            .ok()
+            .flatten();
+        nodes.push(GraphNode { user_gid, name });
+    }
+
+    let mut edges: Vec<GraphEdge> = edges
+        .into_iter()
+        .map(|((source, target), c)| GraphEdge {
+            source,
+            target,
+            weight: c.shared_tasks + c.replies + c.mentions,
+            shared_tasks: c.shared_tasks,
+            replies: c.replies,
+            mentions: c.mentions,
+        })
+        .collect();
+    edges.sort_by(|a, b| {
+        b.weight
+            .cmp(&a.weight)
+            .then_with(|| (&a.source, &a.target).cmp(&(&b.source, &b.target)))
+    });
+
+    Ok(CollaborationGraph {
+        scope: scope.key(),
+        period_key: None,
+        nodes,
+        edges,
+    })
+}
+
+/// Build the collaboration graph for `scope`, over `period` or all time.
+pub async fn collaboration_graph(
+    db: &Database,
+    scope: GraphScope,
+    period: Option<&Period>,
+) -> Result<CollaborationGraph> {
+    let (start, end, period_key) = match period {
+        Some(p) => {
+            let (start, end) = p.date_range();
+            (
+                start.format("%Y-%m-%d").to_string(),
+                end.format("%Y-%m-%d").to_string(),
+                Some(p.to_key()),
+            )
+        }
+        None => ("0000-01-01".to_string(), "9999-12-31".to_string(), None),
+    };
+    let mut graph = db
+        .reader()
+        .call(move |conn| collaboration_graph_sql(conn, &scope, &start, &end))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+    graph.period_key = period_key;
+    Ok(graph)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl CollaborationGraph {
+    /// GraphML, readable by Gephi, yEd, NetworkX, and igraph.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+             <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n  \
+             <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"int\"/>\n  \
+             <key id=\"shared_tasks\" for=\"edge\" attr.name=\"shared_tasks\" attr.type=\"int\"/>\n  \
+             <key id=\"replies\" for=\"edge\" attr.name=\"replies\" attr.type=\"int\"/>\n  \
+             <key id=\"mentions\" for=\"edge\" attr.name=\"mentions\" attr.type=\"int\"/>\n",
+        );
+        out.push_str(&format!(
+            "  <graph id=\"{}\" edgedefault=\"undirected\">\n",
+            xml_escape(&self.scope)
+        ));
+        for n in &self.nodes {
+            out.push_str(&format!("    <node id=\"{}\">", xml_escape(&n.user_gid)));
+            if let Some(name) = &n.name {
+                out.push_str(&format!("<data key=\"name\">{}</data>", xml_escape(name)));
+            }
+            out.push_str("</node>\n");
+        }
+        for e in &self.edges {
+            out.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\
+                 <data key=\"weight\">{}</data>\
+                 <data key=\"shared_tasks\">{}</data>\
+                 <data key=\"replies\">{}</data>\
+                 <data key=\"mentions\">{}</data></edge>\n",
+                xml_escape(&e.source),
+                xml_escape(&e.target),
+                e.weight,
+                e.shared_tasks,
+                e.replies,
+                e.mentions
+            ));
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Graphviz DOT, with edge weight as `weight` and `penwidth`.
+    pub fn to_dot(&self) -> String {
+        let mut out = format!("graph \"{}\" {{\n", dot_escape(&self.scope));
+        for n in &self.nodes {
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\"];\n",
+                dot_escape(&n.user_gid),
+                dot_escape(n.name.as_deref().unwrap_or(&n.user_gid))
+            ));
+        }
+        for e in &self.edges {
+            out.push_str(&format!(
+                "  \"{}\" -- \"{}\" [weight={}, penwidth={}, shared_tasks={}, replies={}, mentions={}];\n",
+                dot_escape(&e.source),
+                dot_escape(&e.target),
+                e.weight,
+                1 + (e.weight as f64).log2().floor() as u64,
+                e.shared_tasks,
+                e.replies,
+                e.mentions
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Each user with their neighbors, for the JSON export.
+    pub fn adjacency(&self) -> AdjacencyList {
+        let mut users: BTreeMap<String, AdjacencyEntry> = self
+            .nodes
+            .iter()
+            .map(|n| {
+                (
+                    n.user_gid.clone(),
+                    AdjacencyEntry {
+                        name: n.name.clone(),
+                        neighbors: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+        // Edges are heaviest first, so each neighbor list is too
+        for e in &self.edges {
+            for (from, to) in [(&e.source, &e.target), (&e.target, &e.source)] {
+                if let Some(entry) = users.get_mut(from) {
+                    entry.neighbors.push(Neighbor {
+                        user_gid: to.clone(),
+                        weight: e.weight,
+                        shared_tasks: e.shared_tasks,
+                        replies: e.replies,
+                        mentions: e.mentions,
+                    });
+                }
+            }
+        }
+        AdjacencyList {
+            scope: self.scope.clone(),
+            period_key: self.period_key.clone(),
+            users,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_db() -> Database {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('a', 'Ann', datetime('now')), ('b', 'Ben \"B\"', datetime('now')), ('c', 'Cy', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'w1', datetime('now'));
+                     INSERT INTO dim_teams (team_gid, name, workspace_gid, cached_at) VALUES ('tm', 'Core', 'w1', datetime('now'));
+                     INSERT INTO bridge_team_members (team_gid, user_gid) VALUES ('tm', 'a'), ('tm', 'b');
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Plan', 'a', 0, '2025-01-02', '2025-01-02', datetime('now')),
+                            ('t2', 'Build', 'b', 0, '2025-01-02', '2025-01-02', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t1', 'p1'), ('t2', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, html_text, story_type, created_at, created_date_key, parent_comment_gid, cached_at)
+                     VALUES ('c1', 't1', 'b', 'Looks good', NULL, 'comment', '2025-01-03T09:00:00Z', '2025-01-03', NULL, datetime('now')),
+                            ('c2', 't1', 'a', '@Ben thanks', '<body><a data-asana-gid=\"b\" data-asana-type=\"user\">@Ben</a> thanks</body>', 'comment', '2025-01-03T10:00:00Z', '2025-01-03', 'c1', datetime('now')),
+                            ('c3', 't2', 'c', 'FYI @Ann', '<body>FYI <a data-asana-gid=\"a\" data-asana-type=\"user\">@Ann</a></body>', 'comment', '2025-01-04T10:00:00Z', '2025-01-04', NULL, datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_project_graph() {
+        let db = seeded_db().await;
+        let g = collaboration_graph(&db, GraphScope::Project("p1".into()), None)
+            .await
+            .unwrap();
+        assert_eq!(g.nodes.len(), 3);
+        let ab = &g.edges[0];
+        assert_eq!((ab.source.as_str(), ab.target.as_str()), ("a", "b"));
+        assert_eq!((ab.shared_tasks, ab.replies, ab.mentions), (1, 1, 1));
+        assert_eq!(ab.weight, 3);
+        // c commented on b's task and mentioned a there
+        let edges: Vec<(&str, &str, u64)> = g.edges[1..]
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.weight))
+            .collect();
+        assert_eq!(edges, vec![("a", "c", 1), ("b", "c", 1)]);
+
+        let adjacency = g.adjacency();
+        assert_eq!(adjacency.users["a"].neighbors.len(), 2);
+        assert_eq!(adjacency.users["a"].neighbors[0].user_gid, "b");
+
+        assert!(g
+            .to_graphml()
+            .contains("<data key=\"name\">Ben &quot;B&quot;</data>"));
+        assert!(g.to_dot().contains("\"a\" -- \"b\" [weight=3"));
+
+        // Nothing happened in 2024
+        let period = Period::parse("2024").unwrap();
+        let g = collaboration_graph(&db, GraphScope::Project("p1".into()), Some(&period))
+            .await
+            .unwrap();
+        assert!(g.edges.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_team_graph_keeps_members_only() {
+        let db = seeded_db().await;
+        let g = collaboration_graph(&db, GraphScope::Team("tm".into()), None)
+            .await
+            .unwrap();
+        let nodes: Vec<&str> = g.nodes.iter().map(|n| n.user_gid.as_str()).collect();
+        assert_eq!(nodes, vec!["a", "b"]);
+        assert_eq!(g.edges.len(), 1);
+        assert_eq!(g.edges[0].weight, 3);
+    }
+}
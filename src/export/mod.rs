@@ -0,0 +1,154 @@
+//! Bulk export of warehouse tables for analysis in external tools.
+
+pub mod anonymize;
+pub mod graph;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod schedule;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::error::Error;
+
+/// Storage class used for an exported column, from the column's declared type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColumnKind {
+    /// Map a declared SQLite column type using SQLite's affinity rules.
+    pub fn from_decl_type(decl: &str) -> Self {
+        let decl = decl.to_uppercase();
+        if decl.contains("INT") {
+            ColumnKind::Integer
+        } else if decl.contains("REAL") || decl.contains("FLOA") || decl.contains("DOUB") {
+            ColumnKind::Real
+        } else {
+            ColumnKind::Text
+        }
+    }
+}
+
+/// One exported table.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedTable {
+    pub table: String,
+    pub rows: u64,
+    pub path: String,
+}
+
+/// Fact, dimension, and bridge tables, in name order. Sync bookkeeping,
+/// config, cached LLM output, and FTS indexes are not part of the export.
+pub fn warehouse_tables(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table'
+           AND (name LIKE 'fact\\_%' ESCAPE '\\' OR name LIKE 'dim\\_%' ESCAPE '\\'
+                OR name LIKE 'bridge\\_%' ESCAPE '\\')
+           AND name NOT LIKE '%\\_summaries' ESCAPE '\\'
+         ORDER BY name",
+    )?;
+    let tables = stmt
+        .query_map([], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tables)
+}
+
+/// The requested tables, checked against [`warehouse_tables`]; an empty
+/// request selects every warehouse table.
+pub fn select_tables(conn: &Connection, requested: &[String]) -> crate::error::Result<Vec<String>> {
+    let available = warehouse_tables(conn)?;
+    if requested.is_empty() {
+        return Ok(available);
+    }
+    for table in requested {
+        if !available.contains(table) {
+            return Err(Error::Config(format!(
+                "unknown table '{table}'. Exportable tables: {}",
+                available.join(", ")
+            )));
+        }
+    }
+    Ok(requested.to_vec())
+}
+
+/// Column names and kinds for a table, in declaration order.
+pub fn table_columns(
+    conn: &Connection,
+    table: &str,
+) -> Result<Vec<(String, ColumnKind)>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT name, type FROM pragma_table_info(?1) ORDER BY cid")?;
+    let columns = stmt
+        .query_map([table], |row| {
+            let name: String = row.get(0)?;
+            let decl: String = row.get(1)?;
+            Ok((name, ColumnKind::from_decl_type(&decl)))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_warehouse_tables_and_columns() {
+        let db = Database::open_memory().await.unwrap();
+        let (tables, columns) = db
+            .reader()
+            .call(|conn| {
+                let tables = warehouse_tables(conn)?;
+                let columns = table_columns(conn, "fact_tasks")?;
+                Ok::<_, rusqlite::Error>((tables, columns))
+            })
+            .await
+            .unwrap();
+
+        assert!(tables.contains(&"fact_tasks".to_string()));
+        assert!(tables.contains(&"dim_users".to_string()));
+        assert!(tables.contains(&"bridge_task_projects".to_string()));
+        assert!(!tables.iter().any(|t| t.contains("fts")));
+        assert!(!tables.contains(&"fact_task_summaries".to_string()));
+        assert!(!tables.contains(&"app_config".to_string()));
+
+        let kind = |name: &str| columns.iter().find(|(n, _)| n == name).map(|(_, k)| *k);
+        assert_eq!(kind("task_gid"), Some(ColumnKind::Text));
+        assert_eq!(kind("is_completed"), Some(ColumnKind::Integer));
+    }
+
+    #[tokio::test]
+    async fn test_select_tables() {
+        let db = Database::open_memory().await.unwrap();
+        let (all, some, bad) = db
+            .reader()
+            .call(|conn| {
+                Ok::<_, rusqlite::Error>((
+                    select_tables(conn, &[]).unwrap(),
+                    select_tables(conn, &["fact_tasks".to_string()]).unwrap(),
+                    select_tables(conn, &["app_config".to_string()]).is_err(),
+                ))
+            })
+            .await
+            .unwrap();
+        assert!(all.len() > 1);
+        assert_eq!(some, vec!["fact_tasks"]);
+        assert!(bad);
+    }
+
+    #[test]
+    fn test_column_kind_affinity() {
+        assert_eq!(ColumnKind::from_decl_type("INTEGER"), ColumnKind::Integer);
+        assert_eq!(ColumnKind::from_decl_type("real"), ColumnKind::Real);
+        assert_eq!(ColumnKind::from_decl_type("DOUBLE"), ColumnKind::Real);
+        assert_eq!(ColumnKind::from_decl_type("TEXT"), ColumnKind::Text);
+        assert_eq!(ColumnKind::from_decl_type(""), ColumnKind::Text);
+    }
+}
@@ -0,0 +1,238 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use ::arrow::array::{ArrayRef, Float64Builder, Int64Builder, StringBuilder};
+use ::arrow::datatypes::{DataType, Field, Schema};
+use ::arrow::record_batch::RecordBatch;
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::Compression;
+use ::parquet::file::properties::WriterProperties;
+use rusqlite::types::{Value, ValueRef};
+
+use super::anonymize::Anonymizer;
+use super::{select_tables, table_columns, ColumnKind, ExportedTable};
+use crate::error::{Error, Result};
+use crate::storage::Database;
+
+/// Rows buffered per Arrow record batch.
+const BATCH_SIZE: usize = 8192;
+
+/// Write each table in `tables` (every warehouse table if empty) to
+/// `<out_dir>/<table>.parquet`, redacting rows with `anonymizer` if given.
+pub async fn export_parquet(
+    db: &Database,
+    out_dir: &Path,
+    tables: &[String],
+    anonymizer: Option<&Anonymizer>,
+) -> Result<Vec<ExportedTable>> {
+    std::fs::create_dir_all(out_dir).map_err(|e| Error::Other(e.to_string()))?;
+    let out_dir = out_dir.to_path_buf();
+    let tables = tables.to_vec();
+    let anonymizer = anonymizer.cloned();
+
+    let exported = db
+        .reader()
+        .call(move |conn| {
+            let mut exported = Vec::new();
+            for table in select_tables(conn, &tables)? {
+                let path = out_dir.join(format!("{table}.parquet"));
+                let rows = export_table(conn, &table, &path, anonymizer.as_ref())?;
+                exported.push(ExportedTable {
+                    table,
+                    rows,
+                    path: path.display().to_string(),
+                });
+            }
+            Ok::<Vec<ExportedTable>, Error>(exported)
+        })
+        .await?;
+    Ok(exported)
+}
+
+fn export_table(
+    conn: &rusqlite::Connection,
+    table: &str,
+    path: &Path,
+    anonymizer: Option<&Anonymizer>,
+) -> Result<u64> {
+    let columns = table_columns(conn, table)?;
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|(name, kind)| {
+                let data_type = match kind {
+                    ColumnKind::Integer => DataType::Int64,
+                    ColumnKind::Real => DataType::Float64,
+                    ColumnKind::Text => DataType::Utf8,
+                };
+                Field::new(name, data_type, true)
+            })
+            .collect::<Vec<_>>(),
+    ));
+
+    let file = File::create(path).map_err(|e| Error::Other(e.to_string()))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))
+        .map_err(|e| Error::Other(e.to_string()))?;
+
+    let column_list = columns
+        .iter()
+        .map(|(name, _)| format!("\"{name}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn.prepare(&format!("SELECT {column_list} FROM \"{table}\""))?;
+    let mut rows = stmt.query([])?;
+
+    let mut builders: Vec<ColumnBuilder> = columns
+        .iter()
+        .map(|(_, kind)| ColumnBuilder::new(*kind))
+        .collect();
+    let names: Vec<String> = columns.iter().map(|(name, _)| name.clone()).collect();
+    let mut values: Vec<Value> = Vec::with_capacity(columns.len());
+    let mut buffered = 0;
+    let mut total: u64 = 0;
+    while let Some(row) = rows.next()? {
+        match anonymizer {
+            Some(anonymizer) => {
+                values.clear();
+                for i in 0..columns.len() {
+                    values.push(row.get(i)?);
+                }
+                anonymizer.anonymize_row(table, &names, &mut values);
+                for (builder, value) in builders.iter_mut().zip(&values) {
+                    builder.append(ValueRef::from(value));
+                }
+            }
+            None => {
+                for (i, builder) in builders.iter_mut().enumerate() {
+                    builder.append(row.get_ref(i)?);
+                }
+            }
+        }
+        buffered += 1;
+        total += 1;
+        if buffered == BATCH_SIZE {
+            write_batch(&mut writer, &schema, &mut builders)?;
+            buffered = 0;
+        }
+    }
+    if buffered > 0 || total == 0 {
+        write_batch(&mut writer, &schema, &mut builders)?;
+    }
+
+    writer.close().map_err(|e| Error::Other(e.to_string()))?;
+    Ok(total)
+}
+
+fn write_batch(
+    writer: &mut ArrowWriter<File>,
+    schema: &Arc<Schema>,
+    builders: &mut [ColumnBuilder],
+) -> Result<()> {
+    let arrays: Vec<ArrayRef> = builders.iter_mut().map(|b| b.finish()).collect();
+    let batch =
+        RecordBatch::try_new(schema.clone(), arrays).map_err(|e| Error::Other(e.to_string()))?;
+    writer
+        .write(&batch)
+        .map_err(|e| Error::Other(e.to_string()))
+}
+
+/// Arrow builder for one column. SQLite values that don't fit the
+/// column's declared type are coerced where possible, otherwise null.
+enum ColumnBuilder {
+    Integer(Int64Builder),
+    Real(Float64Builder),
+    Text(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(kind: ColumnKind) -> Self {
+        match kind {
+            ColumnKind::Integer => ColumnBuilder::Integer(Int64Builder::with_capacity(BATCH_SIZE)),
+            ColumnKind::Real => ColumnBuilder::Real(Float64Builder::with_capacity(BATCH_SIZE)),
+            ColumnKind::Text => ColumnBuilder::Text(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: ValueRef<'_>) {
+        match self {
+            ColumnBuilder::Integer(b) => match value {
+                ValueRef::Integer(i) => b.append_value(i),
+                ValueRef::Real(f) => b.append_value(f as i64),
+                ValueRef::Text(t) => b.append_option(
+                    std::str::from_utf8(t)
+                        .ok()
+                        .and_then(|s| s.trim().parse().ok()),
+                ),
+                ValueRef::Null | ValueRef::Blob(_) => b.append_null(),
+            },
+            ColumnBuilder::Real(b) => match value {
+                ValueRef::Integer(i) => b.append_value(i as f64),
+                ValueRef::Real(f) => b.append_value(f),
+                ValueRef::Text(t) => b.append_option(
+                    std::str::from_utf8(t)
+                        .ok()
+                        .and_then(|s| s.trim().parse().ok()),
+                ),
+                ValueRef::Null | ValueRef::Blob(_) => b.append_null(),
+            },
+            ColumnBuilder::Text(b) => match value {
+                ValueRef::Integer(i) => b.append_value(i.to_string()),
+                ValueRef::Real(f) => b.append_value(f.to_string()),
+                ValueRef::Text(t) => b.append_value(String::from_utf8_lossy(t)),
+                ValueRef::Null | ValueRef::Blob(_) => b.append_null(),
+            },
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Integer(b) => Arc::new(b.finish()),
+            ColumnBuilder::Real(b) => Arc::new(b.finish()),
+            ColumnBuilder::Text(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export_parquet_writes_each_table() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'))",
+                    [],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let exported = export_parquet(&db, dir.path(), &[], None).await.unwrap();
+        let users = exported.iter().find(|t| t.table == "dim_users").unwrap();
+        assert_eq!(users.rows, 1);
+        assert!(dir.path().join("dim_users.parquet").exists());
+        assert!(dir.path().join("fact_tasks.parquet").exists());
+
+        let only = tempfile::tempdir().unwrap();
+        let anonymizer = Anonymizer::new(None);
+        let exported = export_parquet(
+            &db,
+            only.path(),
+            &["dim_users".to_string()],
+            Some(&anonymizer),
+        )
+        .await
+        .unwrap();
+        assert_eq!(exported.len(), 1);
+        assert!(!only.path().join("fact_tasks.parquet").exists());
+    }
+}
@@ -0,0 +1,436 @@
+//! Recurring exports run by the daemon on a cron schedule.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use serde::Serialize;
+
+use super::select_tables;
+use crate::error::{Error, Result};
+use crate::storage::repository::{self, ExportScheduleRow};
+use crate::storage::Database;
+use crate::sync::scheduler::TIMESTAMP_FORMAT;
+
+/// Export formats a schedule can use.
+pub const EXPORT_FORMATS: &[&str] = &["parquet"];
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`),
+/// evaluated in UTC.
+///
+/// Each field accepts `*`, numbers, ranges (`1-5`), lists (`1,15`), and steps
+/// (`*/15`, `0-30/10`). Day-of-week is 0-6 from Sunday (7 is also Sunday).
+/// As in standard cron, when both day fields are restricted a time matches
+/// if either one does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(Error::Config(format!(
+                "invalid cron expression '{expr}': expected 5 fields (minute hour day month weekday)"
+            )));
+        }
+        let field = |i: usize, min: u32, max: u32| {
+            parse_field(fields[i], min, max).ok_or_else(|| {
+                Error::Config(format!(
+                    "invalid cron expression '{expr}': bad field '{}' (allowed {min}-{max})",
+                    fields[i]
+                ))
+            })
+        };
+        let mut days_of_week = field(4, 0, 7)?;
+        // 7 is an alias for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)? as u32,
+            days_of_month: field(2, 1, 31)? as u32,
+            months: field(3, 1, 12)? as u16,
+            days_of_week: (days_of_week & 0x7f) as u8,
+            any_day_of_month: fields[2] == "*",
+            any_day_of_week: fields[4] == "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => dom || dow,
+            (true, false) => dow,
+            _ => dom,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, or `None` if the
+    /// expression never matches (e.g. `0 0 31 2 *`).
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        // Every valid expression matches within a leap-year cycle
+        let limit = t + Duration::days(366 * 4 + 1);
+        while t <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    m => (t.year(), m + 1),
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a bitmask of allowed values.
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (a.parse().ok()?, b.parse().ok()?)
+        } else {
+            let n: u32 = range.parse().ok()?;
+            // `5/15` means "from 5 to the end, every 15"
+            (n, if part.contains('/') { max } else { n })
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Some(mask)
+}
+
+/// Outcome of one scheduled export run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportRun {
+    pub schedule_id: i64,
+    pub out: String,
+    pub tables_exported: usize,
+    pub error: Option<String>,
+}
+
+fn format_timestamp(t: NaiveDateTime) -> String {
+    t.format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn split_tables(tables: Option<&str>) -> Vec<String> {
+    tables
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn check_format(format: &str) -> Result<()> {
+    if !EXPORT_FORMATS.contains(&format) {
+        return Err(Error::Config(format!(
+            "unsupported export format '{format}'. Use: {}",
+            EXPORT_FORMATS.join(", ")
+        )));
+    }
+    if format == "parquet" && !cfg!(feature = "parquet") {
+        return Err(Error::Config(
+            "Parquet export is not enabled in this build. Reinstall with: cargo install asanadw --features parquet".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate and store a new export schedule; returns its ID. `tables` may be
+/// empty to export every warehouse table.
+pub async fn add_schedule(
+    db: &Database,
+    format: &str,
+    tables: &[String],
+    cron: &str,
+    out: &str,
+) -> Result<i64> {
+    check_format(format)?;
+    if out.trim().is_empty() {
+        return Err(Error::Config("export destination must not be empty".into()));
+    }
+    let schedule = CronSchedule::parse(cron)?;
+    let next = schedule
+        .next_after(Utc::now().naive_utc())
+        .ok_or_else(|| Error::Config(format!("cron expression '{cron}' never matches")))?;
+
+    let requested = tables.to_vec();
+    db.reader()
+        .call(move |conn| select_tables(conn, &requested))
+        .await?;
+
+    let tables = (!tables.is_empty()).then(|| tables.join(","));
+    let (format, cron, out) = (format.to_string(), cron.to_string(), out.to_string());
+    let next = format_timestamp(next);
+    let id = db
+        .writer()
+        .call(move |conn| {
+            repository::add_export_schedule(conn, &format, tables.as_deref(), &cron, &out, &next)
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(id)
+}
+
+pub async fn list_schedules(db: &Database) -> Result<Vec<ExportScheduleRow>> {
+    db.reader()
+        .call(|conn| repository::list_export_schedules(conn))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Returns false if no schedule has this ID.
+pub async fn remove_schedule(db: &Database, schedule_id: i64) -> Result<bool> {
+    db.writer()
+        .call(move |conn| repository::remove_export_schedule(conn, schedule_id))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// When the soonest scheduled export is due, if any exist.
+pub async fn next_due(db: &Database) -> Result<Option<NaiveDateTime>> {
+    let schedules = list_schedules(db).await?;
+    Ok(schedules
+        .iter()
+        .filter_map(|s| NaiveDateTime::parse_from_str(&s.next_run_at, TIMESTAMP_FORMAT).ok())
+        .min())
+}
+
+/// Run every export whose next run is at or before now and record the
+/// outcomes. A failed export is retried at its next scheduled time.
+pub async fn run_due(db: &Database) -> Result<Vec<ExportRun>> {
+    let now = Utc::now().naive_utc();
+    let now_str = format_timestamp(now);
+    let due: Vec<ExportScheduleRow> = list_schedules(db)
+        .await?
+        .into_iter()
+        .filter(|s| s.next_run_at <= now_str)
+        .collect();
+
+    let mut runs = Vec::new();
+    for schedule in due {
+        let cron = CronSchedule::parse(&schedule.cron);
+        let result = match &cron {
+            Ok(_) => run_export(db, &schedule).await,
+            Err(e) => Err(Error::Config(e.to_string())),
+        };
+        let (tables_exported, error) = match result {
+            Ok(n) => (n, None),
+            Err(e) => {
+                log::error!("Scheduled export #{} failed: {e}", schedule.schedule_id);
+                (0, Some(e.to_string()))
+            }
+        };
+
+        // An unparseable or never-matching expression parks the schedule a
+        // day out rather than retrying every poll.
+        let next = cron
+            .ok()
+            .and_then(|c| c.next_after(Utc::now().naive_utc()))
+            .unwrap_or_else(|| now + Duration::days(1));
+        let next = format_timestamp(next);
+        let status = if error.is_some() { "failed" } else { "success" };
+        let schedule_id = schedule.schedule_id;
+        let err = error.clone();
+        db.writer()
+            .call(move |conn| {
+                repository::record_export_run(conn, schedule_id, status, err.as_deref(), &next)
+            })
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        runs.push(ExportRun {
+            schedule_id,
+            out: schedule.out,
+            tables_exported,
+            error,
+        });
+    }
+    Ok(runs)
+}
+
+/// Run one export now; returns the number of tables written.
+pub async fn run_export(db: &Database, schedule: &ExportScheduleRow) -> Result<usize> {
+    check_format(&schedule.format)?;
+    let tables = split_tables(schedule.tables.as_deref());
+    write_parquet(db, &schedule.out, &tables).await
+}
+
+#[cfg(feature = "parquet")]
+async fn write_parquet(db: &Database, out: &str, tables: &[String]) -> Result<usize> {
+    use super::parquet::export_parquet;
+
+    if !out.starts_with("s3://") {
+        let exported = export_parquet(db, std::path::Path::new(out), tables, None).await?;
+        return Ok(exported.len());
+    }
+
+    // S3 destinations are staged locally and uploaded with the AWS CLI, which
+    // picks up credentials the usual way (env, profile, instance role).
+    let staging = std::env::temp_dir().join(format!(
+        "asanadw-export-{}-{}",
+        std::process::id(),
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    let result = match export_parquet(db, &staging, tables, None).await {
+        Ok(exported) => upload_to_s3(&staging, out).await.map(|_| exported.len()),
+        Err(e) => Err(e),
+    };
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+#[cfg(not(feature = "parquet"))]
+async fn write_parquet(_db: &Database, _out: &str, _tables: &[String]) -> Result<usize> {
+    Err(Error::Config(
+        "Parquet export is not enabled in this build. Reinstall with: cargo install asanadw --features parquet".into(),
+    ))
+}
+
+#[cfg(feature = "parquet")]
+async fn upload_to_s3(dir: &std::path::Path, url: &str) -> Result<()> {
+    let output = tokio::process::Command::new("aws")
+        .args(["s3", "cp", "--recursive", "--only-show-errors"])
+        .arg(dir)
+        .arg(url)
+        .output()
+        .await
+        .map_err(|e| Error::Other(format!("could not run the AWS CLI for {url}: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "upload to {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, TIMESTAMP_FORMAT).unwrap()
+    }
+
+    fn next(expr: &str, after: &str) -> Option<NaiveDateTime> {
+        CronSchedule::parse(expr).unwrap().next_after(ts(after))
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        // Daily at 06:00
+        assert_eq!(
+            next("0 6 * * *", "2025-01-01 05:59:30"),
+            Some(ts("2025-01-01 06:00:00"))
+        );
+        assert_eq!(
+            next("0 6 * * *", "2025-01-01 06:00:00"),
+            Some(ts("2025-01-02 06:00:00"))
+        );
+        // Every 15 minutes
+        assert_eq!(
+            next("*/15 * * * *", "2025-01-01 10:16:00"),
+            Some(ts("2025-01-01 10:30:00"))
+        );
+        // Weekdays at 09:30; 2025-01-04 is a Saturday
+        assert_eq!(
+            next("30 9 * * 1-5", "2025-01-04 00:00:00"),
+            Some(ts("2025-01-06 09:30:00"))
+        );
+        // Sunday as 7, across a year boundary
+        assert_eq!(
+            next("0 0 * 12 7", "2025-12-29 00:00:00"),
+            Some(ts("2026-12-06 00:00:00"))
+        );
+        // Both day fields restricted: either matches
+        assert_eq!(
+            next("0 0 15 * 1", "2025-01-07 00:00:00"),
+            Some(ts("2025-01-13 00:00:00"))
+        );
+        // Leap day
+        assert_eq!(
+            next("0 0 29 2 *", "2025-03-01 00:00:00"),
+            Some(ts("2028-02-29 00:00:00"))
+        );
+        assert_eq!(next("0 0 31 2 *", "2025-01-01 00:00:00"), None);
+    }
+
+    #[test]
+    fn test_cron_parse_errors() {
+        assert!(CronSchedule::parse("0 6 * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* 24 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("@daily").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_roundtrip() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                repository::add_export_schedule(
+                    conn,
+                    "parquet",
+                    Some("fact_tasks,dim_users"),
+                    "0 6 * * *",
+                    "s3://lake/asana",
+                    "2025-01-01 06:00:00",
+                )
+            })
+            .await
+            .unwrap();
+
+        let schedules = list_schedules(&db).await.unwrap();
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(
+            split_tables(schedules[0].tables.as_deref()),
+            vec!["fact_tasks", "dim_users"]
+        );
+        assert_eq!(
+            next_due(&db).await.unwrap(),
+            Some(ts("2025-01-01 06:00:00"))
+        );
+
+        assert!(add_schedule(&db, "csv", &[], "0 6 * * *", "/tmp/x")
+            .await
+            .is_err());
+        assert!(remove_schedule(&db, schedules[0].schedule_id)
+            .await
+            .unwrap());
+        assert!(!remove_schedule(&db, schedules[0].schedule_id)
+            .await
+            .unwrap());
+        assert_eq!(next_due(&db).await.unwrap(), None);
+    }
+}
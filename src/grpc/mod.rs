@@ -0,0 +1,461 @@
+//! gRPC server exposing the warehouse as typed Metrics, Search, and
+//! Summarize services (see `proto/asanadw.proto`).
+//!
+//! `asanadw grpc` serves them for platform teams wiring asanadw into a
+//! service mesh. Like the MCP server, it's read-only: responses reflect the
+//! last `asanadw sync`. Built with the `grpc` cargo feature.
+
+use std::net::SocketAddr;
+
+use serde::Serialize;
+use tonic::{Request, Response, Status};
+
+use crate::error::{Error, Result};
+use crate::metrics::{CollaborationMetrics, HealthMetrics, LeadTimeMetrics, ThroughputMetrics};
+use crate::query::period::Period;
+use crate::search::{SearchHitType, SearchOptions};
+use crate::storage::Database;
+
+pub mod proto {
+    tonic::include_proto!("asanadw.v1");
+}
+
+use proto::metrics_server::{Metrics, MetricsServer};
+use proto::search_server::{Search, SearchServer};
+use proto::summarize_server::{Summarize, SummarizeServer};
+use proto::{
+    EntityType, HitType, MetricsRequest, MetricsResponse, SearchRequest, SearchResponse,
+    SummarizeRequest, SummarizeResponse,
+};
+
+/// Serve the gRPC services on `addr` until Ctrl-C.
+pub async fn serve(db: Database, addr: SocketAddr) -> Result<()> {
+    let warehouse = Warehouse::new(db);
+    tonic::transport::Server::builder()
+        .add_service(MetricsServer::new(warehouse.clone()))
+        .add_service(SearchServer::new(warehouse.clone()))
+        .add_service(SummarizeServer::new(warehouse))
+        .serve_with_shutdown(addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .map_err(|e| Error::Other(format!("gRPC server: {e}")))
+}
+
+/// Implements every service over one database.
+#[derive(Clone)]
+pub struct Warehouse {
+    db: Database,
+}
+
+impl Warehouse {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// The requested period, else the configured `default_period`, else qtd.
+    async fn period(&self, period: &str) -> Result<Period> {
+        if period.is_empty() {
+            crate::config::default_period(&self.db, "qtd").await
+        } else {
+            Period::parse(period)
+        }
+    }
+}
+
+fn status(e: Error) -> Status {
+    match e {
+        Error::NotFound(_) => Status::not_found(e.to_string()),
+        Error::InvalidIdentifier(_) | Error::UrlParse(_) | Error::PeriodParse(_) => {
+            Status::invalid_argument(e.to_string())
+        }
+        Error::Config(_) | Error::NotSynced(_) => Status::failed_precondition(e.to_string()),
+        _ => Status::internal(e.to_string()),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| Error::Other(e.to_string()))
+}
+
+impl From<&ThroughputMetrics> for proto::Throughput {
+    fn from(m: &ThroughputMetrics) -> Self {
+        Self {
+            tasks_created: m.tasks_created,
+            tasks_completed: m.tasks_completed,
+            net_new: m.net_new,
+            milestones_completed: m.milestones_completed,
+        }
+    }
+}
+
+impl From<&HealthMetrics> for proto::Health {
+    fn from(m: &HealthMetrics) -> Self {
+        Self {
+            overdue_count: m.overdue_count,
+            unassigned_count: m.unassigned_count,
+            stale_count: m.stale_count,
+            total_open: m.total_open,
+            overdue_pct: m.overdue_pct,
+            unassigned_pct: m.unassigned_pct,
+        }
+    }
+}
+
+impl From<&LeadTimeMetrics> for proto::LeadTime {
+    fn from(m: &LeadTimeMetrics) -> Self {
+        Self {
+            avg_days_to_complete: m.avg_days_to_complete,
+            median_days_to_complete: m.median_days_to_complete,
+            p90_days_to_complete: m.p90_days_to_complete,
+            min_days_to_complete: m.min_days_to_complete,
+            max_days_to_complete: m.max_days_to_complete,
+        }
+    }
+}
+
+impl From<&CollaborationMetrics> for proto::Collaboration {
+    fn from(m: &CollaborationMetrics) -> Self {
+        Self {
+            total_comments: m.total_comments,
+            unique_commenters: m.unique_commenters,
+            total_likes: m.total_likes,
+            silent_completions: m.silent_completions,
+            silent_completion_pct: m.silent_completion_pct,
+        }
+    }
+}
+
+/// Response fields every entity type has; the typed metrics are filled in
+/// by the caller.
+fn metrics_response(
+    id: &str,
+    name: Option<&str>,
+    period_key: &str,
+    json: String,
+) -> MetricsResponse {
+    MetricsResponse {
+        id: id.to_string(),
+        name: name.map(String::from),
+        period_key: period_key.to_string(),
+        json,
+        ..Default::default()
+    }
+}
+
+impl Warehouse {
+    async fn run_metrics(&self, request: MetricsRequest) -> Result<MetricsResponse> {
+        let db = &self.db;
+        let period = self.period(&request.period).await?;
+        let identifier = request.identifier.as_str();
+        let response = match request.entity_type() {
+            EntityType::User => {
+                let gid = crate::mcp::resolve_user(db, identifier).await?;
+                let m = crate::metrics::compute_user_metrics(db, &gid, &period).await?;
+                MetricsResponse {
+                    throughput: Some((&m.throughput).into()),
+                    lead_time: Some((&m.lead_time).into()),
+                    collaboration: Some((&m.collaboration).into()),
+                    ..metrics_response(
+                        &m.user_gid,
+                        m.user_name.as_deref(),
+                        &m.period_key,
+                        to_json(&m)?,
+                    )
+                }
+            }
+            EntityType::Project => {
+                let gid = crate::alias::resolve(db, identifier).await?;
+                let m = crate::metrics::compute_project_metrics(db, &gid, &period).await?;
+                MetricsResponse {
+                    throughput: Some((&m.throughput).into()),
+                    health: Some((&m.health).into()),
+                    lead_time: Some((&m.lead_time).into()),
+                    collaboration: Some((&m.collaboration).into()),
+                    ..metrics_response(
+                        &m.project_gid,
+                        m.project_name.as_deref(),
+                        &m.period_key,
+                        to_json(&m)?,
+                    )
+                }
+            }
+            EntityType::Portfolio => {
+                let gid = crate::alias::resolve(db, identifier).await?;
+                let m = crate::metrics::compute_portfolio_metrics(db, &gid, &period).await?;
+                MetricsResponse {
+                    throughput: Some((&m.throughput).into()),
+                    health: Some((&m.health).into()),
+                    lead_time: Some((&m.lead_time).into()),
+                    collaboration: Some((&m.collaboration).into()),
+                    ..metrics_response(
+                        &m.portfolio_gid,
+                        m.portfolio_name.as_deref(),
+                        &m.period_key,
+                        to_json(&m)?,
+                    )
+                }
+            }
+            EntityType::Team => {
+                let gid = crate::alias::resolve(db, identifier).await?;
+                let m = crate::metrics::compute_team_metrics(db, &gid, &period).await?;
+                MetricsResponse {
+                    throughput: Some((&m.throughput).into()),
+                    health: Some((&m.health).into()),
+                    lead_time: Some((&m.lead_time).into()),
+                    collaboration: Some((&m.collaboration).into()),
+                    ..metrics_response(
+                        &m.team_gid,
+                        m.team_name.as_deref(),
+                        &m.period_key,
+                        to_json(&m)?,
+                    )
+                }
+            }
+            EntityType::Label => {
+                let m = crate::metrics::compute_label_metrics(db, identifier, &period).await?;
+                MetricsResponse {
+                    throughput: Some((&m.throughput).into()),
+                    health: Some((&m.health).into()),
+                    lead_time: Some((&m.lead_time).into()),
+                    collaboration: Some((&m.collaboration).into()),
+                    ..metrics_response(&m.label, None, &m.period_key, to_json(&m)?)
+                }
+            }
+            EntityType::Task | EntityType::Unspecified => {
+                return Err(Error::InvalidIdentifier(
+                    "metrics need an entity_type of user, project, portfolio, team, or label"
+                        .into(),
+                ))
+            }
+        };
+        Ok(response)
+    }
+
+    async fn run_search(&self, request: SearchRequest) -> Result<SearchResponse> {
+        let hit_type = match request.r#type() {
+            HitType::Unspecified => None,
+            HitType::Task => Some(SearchHitType::Task),
+            HitType::Comment => Some(SearchHitType::Comment),
+            HitType::Project => Some(SearchHitType::Project),
+            HitType::Portfolio => Some(SearchHitType::Portfolio),
+            HitType::ProjectBrief => Some(SearchHitType::ProjectBrief),
+            HitType::StatusUpdate => Some(SearchHitType::StatusUpdate),
+            HitType::CustomField => Some(SearchHitType::CustomField),
+        };
+        let assignee_gid = match request.assignee.as_deref() {
+            Some(a) => Some(crate::mcp::resolve_user(&self.db, a).await?),
+            None => None,
+        };
+        let options = SearchOptions {
+            limit: Some(request.limit.unwrap_or(20)),
+            offset: Some(request.offset),
+            hit_type,
+            assignee_gid,
+            project_gid: request.project,
+            completed: request.completed,
+            subtype: request.subtype,
+            created_after: request.created_after,
+            created_before: request.created_before,
+            ..Default::default()
+        };
+        let results = crate::search::search(&self.db, &request.query, &options).await?;
+        let hits = results
+            .hits
+            .into_iter()
+            .map(|hit| {
+                let hit_type = match hit.hit_type {
+                    SearchHitType::Task => HitType::Task,
+                    SearchHitType::Comment => HitType::Comment,
+                    SearchHitType::Project => HitType::Project,
+                    SearchHitType::Portfolio => HitType::Portfolio,
+                    SearchHitType::ProjectBrief => HitType::ProjectBrief,
+                    SearchHitType::StatusUpdate => HitType::StatusUpdate,
+                    SearchHitType::CustomField => HitType::CustomField,
+                };
+                proto::SearchHit {
+                    hit_type: hit_type.into(),
+                    gid: hit.gid,
+                    task_gid: hit.task_gid,
+                    author: hit.author,
+                    created_at: hit.created_at,
+                    title: hit.title,
+                    snippet: hit.snippet,
+                    rank: hit.rank,
+                    asana_url: hit.asana_url,
+                }
+            })
+            .collect();
+        Ok(SearchResponse {
+            hits,
+            total_unlimited: results.total_unlimited as u64,
+            offset: results.offset,
+        })
+    }
+
+    #[cfg(feature = "llm")]
+    async fn run_summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse> {
+        use crate::llm::agents::{period, task};
+
+        let db = &self.db;
+        let identifier = request.identifier.as_str();
+        let agent = crate::llm::create_agent(db).await?;
+        let summary = match request.entity_type() {
+            EntityType::Task => {
+                let gid = crate::alias::resolve(db, identifier).await?;
+                to_value(&task::summarize_task(db, &agent, &gid, false).await?)?
+            }
+            EntityType::User => {
+                let p = self.period(&request.period).await?;
+                let gid = crate::mcp::resolve_user(db, identifier).await?;
+                to_value(&period::summarize_user_period(db, &agent, &gid, &p, false).await?)?
+            }
+            EntityType::Project => {
+                let p = self.period(&request.period).await?;
+                let gid = crate::alias::resolve(db, identifier).await?;
+                to_value(&period::summarize_project_period(db, &agent, &gid, &p, false).await?)?
+            }
+            EntityType::Portfolio => {
+                let p = self.period(&request.period).await?;
+                let gid = crate::alias::resolve(db, identifier).await?;
+                to_value(&period::summarize_portfolio_period(db, &agent, &gid, &p, false).await?)?
+            }
+            EntityType::Team => {
+                let p = self.period(&request.period).await?;
+                let gid = crate::alias::resolve(db, identifier).await?;
+                to_value(&period::summarize_team_period(db, &agent, &gid, &p, false).await?)?
+            }
+            EntityType::Label | EntityType::Unspecified => {
+                return Err(Error::InvalidIdentifier(
+                    "summaries need an entity_type of task, user, project, portfolio, or team"
+                        .into(),
+                ))
+            }
+        };
+        let field = |key: &str| {
+            summary
+                .get(key)
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        Ok(SummarizeResponse {
+            headline: field("headline"),
+            // Task summaries say what happened rather than what changed
+            what_changed: if summary.get("what_happened").is_some() {
+                field("what_happened")
+            } else {
+                field("what_changed")
+            },
+            why_it_matters: field("why_it_matters"),
+            json: summary.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "llm")]
+fn to_value<T: Serialize>(value: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| Error::Other(e.to_string()))
+}
+
+#[tonic::async_trait]
+impl Metrics for Warehouse {
+    async fn get_metrics(
+        &self,
+        request: Request<MetricsRequest>,
+    ) -> std::result::Result<Response<MetricsResponse>, Status> {
+        self.run_metrics(request.into_inner())
+            .await
+            .map(Response::new)
+            .map_err(status)
+    }
+}
+
+#[tonic::async_trait]
+impl Search for Warehouse {
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> std::result::Result<Response<SearchResponse>, Status> {
+        self.run_search(request.into_inner())
+            .await
+            .map(Response::new)
+            .map_err(status)
+    }
+}
+
+#[tonic::async_trait]
+impl Summarize for Warehouse {
+    async fn summarize(
+        &self,
+        request: Request<SummarizeRequest>,
+    ) -> std::result::Result<Response<SummarizeResponse>, Status> {
+        #[cfg(feature = "llm")]
+        {
+            self.run_summarize(request.into_inner())
+                .await
+                .map(Response::new)
+                .map_err(status)
+        }
+        #[cfg(not(feature = "llm"))]
+        {
+            let _ = request;
+            Err(Status::unimplemented(
+                "summaries need asanadw built with the llm feature",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metrics_and_search() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', '2026-01-01T00:00:00Z');",
+                )
+            })
+            .await
+            .unwrap();
+        let warehouse = Warehouse::new(db);
+
+        let response = warehouse
+            .get_metrics(Request::new(MetricsRequest {
+                entity_type: EntityType::Project.into(),
+                identifier: "p1".into(),
+                period: "ytd".into(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.id, "p1");
+        assert_eq!(response.name.as_deref(), Some("Roadmap"));
+        assert_eq!(response.throughput.unwrap().tasks_completed, 0);
+        assert!(response.health.is_some());
+        let full: serde_json::Value = serde_json::from_str(&response.json).unwrap();
+        assert_eq!(full["project_gid"], "p1");
+
+        let err = warehouse
+            .get_metrics(Request::new(MetricsRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let results = warehouse
+            .search(Request::new(SearchRequest {
+                query: "roadmap".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].hit_type(), HitType::Project);
+    }
+}
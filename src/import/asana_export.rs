@@ -0,0 +1,437 @@
+//! Cold-start import of an Asana project export, for seeding the warehouse
+//! before API access is set up.
+//!
+//! Both of Asana's project export formats are read: CSV (Export/Print >
+//! CSV), which needs a `Task ID` and `Name` column, and JSON, a `data` array
+//! of tasks as the API returns them. Imported tasks are stored with
+//! `fact_tasks.source = 'export'`; a later sync overwrites them, and an
+//! import never overwrites a task that was synced.
+//!
+//! Exports don't carry user GIDs for CSV assignees, so an assignee email
+//! that matches a synced user is linked to them, and any other assignee is
+//! stored as a placeholder user `export:<email or name>`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::metrics::notability;
+use crate::storage::repository::{self, ExportedTask};
+use crate::storage::Database;
+
+/// Prefix of the GIDs given to assignees who aren't synced users.
+pub const PLACEHOLDER_USER_PREFIX: &str = "export:";
+
+/// Outcome of an export import.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExportImport {
+    /// Tasks written to `fact_tasks`.
+    pub imported: u64,
+    /// Tasks skipped because they were already synced from the API.
+    pub skipped_synced: u64,
+    /// Rows that couldn't be read, with their line numbers.
+    pub skipped_rows: Vec<String>,
+}
+
+/// An exported task with its assignee not yet resolved to a user GID.
+#[derive(Debug, Clone)]
+struct ParsedTask {
+    task: ExportedTask,
+    assignee_gid: Option<String>,
+    assignee_name: Option<String>,
+    assignee_email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonExport {
+    Wrapped { data: Vec<JsonTask> },
+    Bare(Vec<JsonTask>),
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonTask {
+    gid: String,
+    name: String,
+    notes: Option<String>,
+    #[serde(default)]
+    completed: bool,
+    completed_at: Option<String>,
+    due_on: Option<String>,
+    start_on: Option<String>,
+    created_at: Option<String>,
+    modified_at: Option<String>,
+    assignee: Option<JsonUser>,
+    parent: Option<JsonRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonUser {
+    gid: String,
+    name: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRef {
+    gid: String,
+}
+
+/// Parse export text, telling JSON from CSV by its first character.
+fn parse_export(text: &str) -> Result<(Vec<ParsedTask>, Vec<String>)> {
+    match text.trim_start().chars().next() {
+        Some('{') | Some('[') => Ok((parse_json_export(text)?, Vec::new())),
+        _ => parse_csv_export(text),
+    }
+}
+
+fn parse_json_export(text: &str) -> Result<Vec<ParsedTask>> {
+    let export: JsonExport = serde_json::from_str(text)
+        .map_err(|e| Error::Other(format!("not an Asana JSON export: {e}")))?;
+    let tasks = match export {
+        JsonExport::Wrapped { data } | JsonExport::Bare(data) => data,
+    };
+    Ok(tasks
+        .into_iter()
+        .map(|t| ParsedTask {
+            task: ExportedTask {
+                gid: t.gid,
+                name: t.name,
+                notes: t.notes.filter(|n| !n.is_empty()),
+                assignee_gid: None,
+                completed: t.completed,
+                completed_at: t.completed_at,
+                due_on: t.due_on,
+                start_on: t.start_on,
+                created_at: t.created_at.unwrap_or_default(),
+                modified_at: t.modified_at,
+                parent_gid: t.parent.map(|p| p.gid),
+            },
+            assignee_gid: t.assignee.as_ref().map(|a| a.gid.clone()),
+            assignee_name: t.assignee.as_ref().and_then(|a| a.name.clone()),
+            assignee_email: t.assignee.and_then(|a| a.email),
+        })
+        .collect())
+}
+
+fn parse_csv_export(text: &str) -> Result<(Vec<ParsedTask>, Vec<String>)> {
+    let mut records = csv_records(text).into_iter();
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| Error::Other("export CSV is empty".to_string()))?;
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (Some(gid_col), Some(name_col)) = (column("task id"), column("name")) else {
+        return Err(Error::Other(format!(
+            "export CSV needs 'Task ID' and 'Name' columns, found: {}",
+            header.join(", ")
+        )));
+    };
+    let created_col = column("created at");
+    let completed_col = column("completed at");
+    let modified_col = column("last modified");
+    let assignee_col = column("assignee");
+    let email_col = column("assignee email");
+    let start_col = column("start date");
+    let due_col = column("due date");
+    let notes_col = column("notes");
+    let parent_col = column("parent task");
+
+    let mut tasks = Vec::new();
+    let mut skipped = Vec::new();
+    let mut parent_names = Vec::new();
+    for (line, fields) in records {
+        let field = |col: Option<usize>| {
+            col.and_then(|c| fields.get(c))
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty())
+        };
+        let Some(gid) = field(Some(gid_col)).filter(|g| crate::url::is_gid(g)) else {
+            skipped.push(format!("line {line}: no task ID"));
+            continue;
+        };
+        let completed_at = field(completed_col);
+        parent_names.push(field(parent_col));
+        tasks.push(ParsedTask {
+            task: ExportedTask {
+                gid,
+                name: field(Some(name_col)).unwrap_or_default(),
+                notes: field(notes_col),
+                assignee_gid: None,
+                completed: completed_at.is_some(),
+                completed_at,
+                due_on: field(due_col),
+                start_on: field(start_col),
+                created_at: field(created_col).unwrap_or_default(),
+                modified_at: field(modified_col),
+                parent_gid: None,
+            },
+            assignee_gid: None,
+            assignee_name: field(assignee_col),
+            assignee_email: field(email_col),
+        });
+    }
+
+    // CSV exports name the parent task rather than giving its ID, so link
+    // subtasks whose parent name is unique within the file
+    let mut by_name: HashMap<String, Option<String>> = HashMap::new();
+    for t in &tasks {
+        by_name
+            .entry(t.task.name.clone())
+            .and_modify(|gid| *gid = None)
+            .or_insert_with(|| Some(t.task.gid.clone()));
+    }
+    for (t, parent) in tasks.iter_mut().zip(parent_names) {
+        t.task.parent_gid = parent.and_then(|p| by_name.get(&p).cloned().flatten());
+    }
+    Ok((tasks, skipped))
+}
+
+/// Split CSV text into records with their starting line numbers, honouring
+/// double-quoted fields with `""` escapes and embedded newlines. Blank
+/// lines are skipped.
+fn csv_records(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                let record = std::mem::take(&mut fields);
+                if record.iter().any(|f| !f.trim().is_empty()) {
+                    records.push((start, record));
+                }
+                line += 1;
+                start = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    fields.push(field);
+    if fields.iter().any(|f| !f.trim().is_empty()) {
+        records.push((start, fields));
+    }
+    records
+}
+
+/// Import an Asana export of the project `project_gid` (named
+/// `project_name` unless it's already synced).
+pub async fn import_asana_export(
+    db: &Database,
+    project_gid: &str,
+    project_name: &str,
+    text: &str,
+) -> Result<ExportImport> {
+    let (tasks, skipped_rows) = parse_export(text)?;
+    let project_gid = project_gid.to_string();
+    let project_name = project_name.to_string();
+    let mut report = db
+        .writer()
+        .call(move |conn| {
+            // Temporarily disable FK checks — parents may be outside the export
+            conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+            let report = write_tasks(conn, &project_gid, &project_name, tasks);
+            conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+            report
+        })
+        .await?;
+    report.skipped_rows = skipped_rows;
+    Ok(report)
+}
+
+fn write_tasks(
+    conn: &mut rusqlite::Connection,
+    project_gid: &str,
+    project_name: &str,
+    tasks: Vec<ParsedTask>,
+) -> std::result::Result<ExportImport, rusqlite::Error> {
+    let tx = conn.transaction()?;
+    repository::ensure_project_minimal(&tx, project_gid, project_name)?;
+    let mut report = ExportImport::default();
+    let mut written = Vec::new();
+    for parsed in tasks {
+        let mut task = parsed.task;
+        task.assignee_gid = resolve_assignee(
+            &tx,
+            parsed.assignee_gid,
+            parsed.assignee_name.as_deref(),
+            parsed.assignee_email.as_deref(),
+        )?;
+        if repository::upsert_exported_task(&tx, project_gid, &task)? {
+            report.imported += 1;
+            written.push(task.gid);
+        } else {
+            report.skipped_synced += 1;
+        }
+    }
+    let weights = notability::load_weights(&tx)?;
+    notability::score_tasks(&tx, &weights, &written)?;
+    tx.commit()?;
+    Ok(report)
+}
+
+/// The user GID to store for an exported assignee, adding the user if
+/// needed.
+fn resolve_assignee(
+    conn: &rusqlite::Connection,
+    gid: Option<String>,
+    name: Option<&str>,
+    email: Option<&str>,
+) -> std::result::Result<Option<String>, rusqlite::Error> {
+    if let Some(gid) = gid {
+        repository::upsert_user_minimal_with_email(conn, &gid, name, email)?;
+        return Ok(Some(gid));
+    }
+    if let Some(email) = email {
+        if let Some(gid) = repository::resolve_user_identifier(conn, email)? {
+            return Ok(Some(gid));
+        }
+    }
+    let Some(key) = email.or(name) else {
+        return Ok(None);
+    };
+    let gid = format!("{PLACEHOLDER_USER_PREFIX}{key}");
+    repository::upsert_user_minimal_with_email(conn, &gid, name, email)?;
+    Ok(Some(gid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_records() {
+        let text = "Task ID,Name,Notes\r\n1,A,\"two\nlines\"\n\n2,\"B, \"\"quoted\"\"\",\n";
+        let records = csv_records(text);
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[1],
+            (2, vec!["1".into(), "A".into(), "two\nlines".into()])
+        );
+        assert_eq!(
+            records[2],
+            (5, vec!["2".into(), r#"B, "quoted""#.into(), String::new()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_export() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, email, cached_at) VALUES
+                        ('u1', 'Ada', 'ada@example.com', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at) VALUES
+                        ('103', 'Synced', '2025-01-02', '2025-01-02', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let csv = "Task ID,Created At,Completed At,Last Modified,Name,Assignee,Assignee Email,Start Date,Due Date,Notes,Parent task\n\
+                   101,2025-01-02,2025-01-10,2025-01-10,Launch,Ada,ada@example.com,,2025-01-09,Ship it,\n\
+                   102,2025-01-03,,2025-01-04,Write docs,Bob,bob@example.com,,,,Launch\n\
+                   103,2025-01-02,,,Synced elsewhere,,,,,,\n\
+                   ,2025-01-02,,,No ID,,,,,,\n";
+        let report = import_asana_export(&db, "p1", "Launch plan", csv)
+            .await
+            .unwrap();
+        assert_eq!((report.imported, report.skipped_synced), (2, 1));
+        assert_eq!(report.skipped_rows, ["line 5: no task ID"]);
+
+        let rows = db
+            .reader()
+            .call(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT t.task_gid, t.assignee_gid, t.is_completed, t.days_to_complete,
+                            t.parent_gid, t.source, btp.project_gid
+                     FROM fact_tasks t LEFT JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+                     ORDER BY t.task_gid",
+                )?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, Option<String>>(1)?,
+                            row.get::<_, bool>(2)?,
+                            row.get::<_, Option<i32>>(3)?,
+                            row.get::<_, Option<String>>(4)?,
+                            row.get::<_, String>(5)?,
+                            row.get::<_, Option<String>>(6)?,
+                        ))
+                    })?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok::<_, rusqlite::Error>(rows)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            rows[0],
+            (
+                "101".into(),
+                Some("u1".into()),
+                true,
+                Some(8),
+                None,
+                "export".into(),
+                Some("p1".into())
+            )
+        );
+        assert_eq!(rows[1].1.as_deref(), Some("export:bob@example.com"));
+        assert_eq!(rows[1].4.as_deref(), Some("101"));
+        assert_eq!((rows[2].5.as_str(), rows[2].6.as_deref()), ("api", None));
+    }
+
+    #[tokio::test]
+    async fn test_import_json_export() {
+        let db = Database::open_memory().await.unwrap();
+        let json = r#"{"data": [
+            {"gid": "201", "name": "Plan", "completed": false, "created_at": "2025-02-01T09:00:00.000Z",
+             "assignee": {"gid": "u9", "name": "Grace"}},
+            {"gid": "202", "name": "Subtask", "completed": true, "completed_at": "2025-02-03T09:00:00.000Z",
+             "created_at": "2025-02-01T10:00:00.000Z", "parent": {"gid": "201"}}
+        ]}"#;
+        let report = import_asana_export(&db, "p2", "Roadmap", json)
+            .await
+            .unwrap();
+        assert_eq!(report.imported, 2);
+
+        let (assignee, parent): (String, String) = db
+            .reader()
+            .call(|conn| {
+                conn.query_row(
+                    "SELECT (SELECT assignee_gid FROM fact_tasks WHERE task_gid = '201'),
+                            (SELECT parent_gid FROM fact_tasks WHERE task_gid = '202')",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!((assignee.as_str(), parent.as_str()), ("u9", "201"));
+
+        assert!(import_asana_export(&db, "p2", "Roadmap", "{\"nope\": 1}")
+            .await
+            .is_err());
+    }
+}
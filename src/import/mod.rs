@@ -0,0 +1,4 @@
+//! Import of data that Asana itself doesn't provide.
+
+pub mod asana_export;
+pub mod org_chart;
@@ -0,0 +1,192 @@
+//! Manager mappings from an org-chart CSV, stored in `dim_users.manager_gid`
+//! for reporting-line rollups (`metrics org`).
+//!
+//! The CSV needs a header row with a `user` column and a `manager` column
+//! (`email`/`user_email`/`user_gid` and `manager_email`/`manager_gid` are
+//! accepted too). Each value is a user GID or email address. A blank
+//! manager marks the top of the chart and clears any previous manager.
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::Database;
+
+const USER_COLUMNS: &[&str] = &["user", "user_email", "user_gid", "email"];
+const MANAGER_COLUMNS: &[&str] = &["manager", "manager_email", "manager_gid"];
+
+/// Outcome of an org-chart import.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrgChartImport {
+    /// Users whose manager was set.
+    pub assigned: u64,
+    /// Users marked as having no manager.
+    pub cleared: u64,
+    /// Identifiers that matched no synced user, with their line numbers.
+    /// Rows naming them are skipped.
+    pub unresolved: Vec<String>,
+}
+
+/// Parse org-chart CSV text into (user, manager) identifier pairs.
+fn parse_org_chart(text: &str) -> Result<Vec<(usize, String, Option<String>)>> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| Error::Other("org chart CSV is empty".to_string()))?;
+    let header: Vec<String> = split_csv_line(header)
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.contains(&h.as_str()))
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "org chart CSV needs a '{}' column, found: {}",
+                    names[0],
+                    header.join(", ")
+                ))
+            })
+    };
+    let user_col = column(USER_COLUMNS)?;
+    let manager_col = column(MANAGER_COLUMNS)?;
+
+    let mut rows = Vec::new();
+    for (i, line) in lines {
+        let fields = split_csv_line(line);
+        let field = |col: usize| {
+            fields
+                .get(col)
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty())
+        };
+        let Some(user) = field(user_col) else {
+            return Err(Error::Other(format!("line {}: no user", i + 1)));
+        };
+        rows.push((i + 1, user, field(manager_col)));
+    }
+    Ok(rows)
+}
+
+/// Split one CSV line into fields, honouring double-quoted fields with
+/// `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Import an org-chart CSV, setting `manager_gid` for every listed user
+/// that has been synced. Users not in the file keep their current manager.
+pub async fn import_org_chart(db: &Database, csv: &str) -> Result<OrgChartImport> {
+    let rows = parse_org_chart(csv)?;
+    let report = db
+        .writer()
+        .call(move |conn| {
+            let tx = conn.transaction()?;
+            let mut report = OrgChartImport::default();
+            for (line, user, manager) in rows {
+                let Some(user_gid) = repository::resolve_user_identifier(&tx, &user)? else {
+                    report.unresolved.push(format!("line {line}: {user}"));
+                    continue;
+                };
+                let manager_gid = match manager {
+                    Some(m) => match repository::resolve_user_identifier(&tx, &m)? {
+                        Some(gid) => Some(gid),
+                        None => {
+                            report.unresolved.push(format!("line {line}: {m}"));
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                // Someone listed as their own manager is at the top
+                let manager_gid = manager_gid.filter(|m| *m != user_gid);
+                if !repository::set_user_manager(&tx, &user_gid, manager_gid.as_deref())? {
+                    report.unresolved.push(format!("line {line}: {user}"));
+                } else if manager_gid.is_some() {
+                    report.assigned += 1;
+                } else {
+                    report.cleared += 1;
+                }
+            }
+            tx.commit()?;
+            Ok::<OrgChartImport, rusqlite::Error>(report)
+        })
+        .await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(split_csv_line("a,b,,c"), ["a", "b", "", "c"]);
+        assert_eq!(
+            split_csv_line(r#""Doe, Jane",jane@example.com,"say ""hi""""#),
+            ["Doe, Jane", "jane@example.com", r#"say "hi""#]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_org_chart_and_rollup() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, email, cached_at) VALUES
+                        ('1', 'Ceo', 'ceo@example.com', datetime('now')),
+                        ('2', 'Vp', 'vp@example.com', datetime('now')),
+                        ('3', 'Eng', 'eng@example.com', datetime('now')),
+                        ('4', 'Other', 'other@example.com', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at) VALUES
+                        ('t1', 'A', '2', 1, '2025-01-10', '2025-01-10', '2025-01-02', '2025-01-02', datetime('now')),
+                        ('t2', 'B', '3', 1, '2025-01-11', '2025-01-11', '2025-01-02', '2025-01-02', datetime('now')),
+                        ('t3', 'C', '4', 1, '2025-01-12', '2025-01-12', '2025-01-02', '2025-01-02', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let csv = "Name,Email,Manager\n\
+                   Ceo,ceo@example.com,ceo@example.com\n\
+                   Vp,vp@example.com,1\n\
+                   \"Eng, Senior\",eng@example.com,vp@example.com\n\
+                   Ghost,ghost@example.com,vp@example.com\n";
+        let report = import_org_chart(&db, csv).await.unwrap();
+        assert_eq!((report.assigned, report.cleared), (2, 1));
+        assert_eq!(report.unresolved, ["line 5: ghost@example.com"]);
+
+        let period = crate::Period::parse("2025-Q1").unwrap();
+        let m = crate::metrics::compute_org_metrics(&db, "1", &period)
+            .await
+            .unwrap();
+        assert_eq!(m.manager_name.as_deref(), Some("Ceo"));
+        assert_eq!((m.member_count, m.direct_reports), (3, 1));
+        assert_eq!(m.throughput.tasks_completed, 2);
+
+        let err = import_org_chart(&db, "email,boss\nceo@example.com,\n").await;
+        assert!(err.unwrap_err().to_string().contains("'manager' column"));
+    }
+}
@@ -0,0 +1,37 @@
+//! Secrets in the OS keychain (macOS Keychain, Windows Credential Manager,
+//! or the Secret Service on Linux), all under the `asanadw` service: the
+//! SQLCipher database key and OAuth sessions.
+
+const SERVICE: &str = "asanadw";
+
+fn entry(name: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, name)
+}
+
+/// The secret stored as `name`, or `None` when there is none.
+pub(crate) fn get(name: &str) -> keyring::Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        // No keychain at all (headless Linux, containers) means no secret
+        Err(keyring::Error::PlatformFailure(e)) | Err(keyring::Error::NoStorageAccess(e)) => {
+            log::debug!("keychain unavailable: {e}");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub(crate) fn set(name: &str, secret: &str) -> keyring::Result<()> {
+    entry(name)?.set_password(secret)
+}
+
+/// Remove the secret stored as `name`. Returns whether there was one.
+#[cfg(feature = "oauth")]
+pub(crate) fn delete(name: &str) -> keyring::Result<bool> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
@@ -1,8 +1,24 @@
+pub mod alias;
+#[cfg(feature = "oauth")]
+pub mod auth;
+pub mod config;
 pub mod date_util;
 pub mod error;
+pub mod export;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod import;
+#[cfg(any(feature = "oauth", feature = "sqlcipher"))]
+mod keychain;
+#[cfg(feature = "llm")]
 pub mod llm;
+pub mod mcp;
 pub mod metrics;
+pub mod overview;
+pub mod profile;
 pub mod query;
+pub mod repl;
+pub mod report;
 pub mod search;
 pub mod storage;
 pub mod sync;
@@ -10,30 +26,84 @@ pub mod url;
 
 pub use error::{Error, Result};
 pub use metrics::{PortfolioMetrics, ProjectMetrics, TeamMetrics, UserMetrics};
-pub use query::builder::QueryBuilder;
+pub use query::builder::{FieldOp, GroupBy, GroupRow, QueryBuilder, TaskPage};
+pub use query::comments::CommentQueryBuilder;
 pub use query::period::Period;
+pub use query::saved::{QueryFilters, SavedQuery};
 pub use search::{SearchHit, SearchHitType, SearchOptions, SearchResults};
 pub use storage::Database;
 pub use sync::{
     IncrementalSyncSummary, NoopProgress, SyncOptions, SyncProgress, SyncReport, SyncStatus,
+    SyncedProjects,
 };
-pub use url::{generate_asana_url, parse_asana_url, AsanaUrlInfo};
+pub use url::{generate_asana_url, generate_comment_url, parse_asana_url, AsanaUrlInfo};
+
+/// Version of the JSON the CLI prints under `--machine` and in JSON-lines
+/// progress. Bumped when a field is renamed, removed, or changes type;
+/// new fields don't bump it.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
 
 // Re-export repository types needed by the binary crate, but not the module itself
 pub use storage::repository::MonitoredEntity;
 
-use storage::repository;
+use storage::{bots, field_changes, repository};
 use sync::syncer;
 
 /// Main entry point for the Asana Data Warehouse.
 pub struct AsanaDW {
     db: Database,
     client: asanaclient::Client,
+    read_only: bool,
+    workspace_override: Option<String>,
 }
 
 impl AsanaDW {
+    /// Read-only when `db` was opened with [`Database::open_read_only`].
     pub fn new(db: Database, client: asanaclient::Client) -> Self {
-        Self { db, client }
+        let read_only = db.is_read_only();
+        Self {
+            db,
+            client,
+            read_only,
+            workspace_override: None,
+        }
+    }
+
+    /// Work in `workspace_gid` instead of the configured workspace, for
+    /// this instance only. `sync_all` then syncs just that workspace's
+    /// monitored entities.
+    pub fn with_workspace(mut self, workspace_gid: impl Into<String>) -> Self {
+        self.workspace_override = Some(workspace_gid.into());
+        self
+    }
+
+    /// Reject syncs and monitor, config, and undo changes with
+    /// [`Error::ReadOnly`], so a shared warehouse can be queried without
+    /// being modified.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn ensure_writable(&self, action: &str) -> Result<()> {
+        if self.read_only {
+            return Err(Error::ReadOnly(action.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Run `sync` holding the [`SyncLock`](sync::lock::SyncLock), which
+    /// nests in one the caller already holds.
+    async fn locked<T>(&self, sync: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        self.ensure_writable("sync")?;
+        let lock = sync::lock::SyncLock::acquire(&self.db, false).await?;
+        let result = sync.await;
+        lock.release().await?;
+        result
     }
 
     /// Access the database (for direct queries in the CLI).
@@ -41,9 +111,14 @@ impl AsanaDW {
         &self.db
     }
 
-    /// Auto-detect or retrieve the workspace GID.
+    /// Auto-detect or retrieve the workspace GID: the one passed to
+    /// [`AsanaDW::with_workspace`], else the configured one.
     /// On first use, calls the API. If one workspace, stores it. If multiple, returns error.
     pub async fn workspace_gid(&self) -> Result<String> {
+        if let Some(gid) = &self.workspace_override {
+            return Ok(gid.clone());
+        }
+
         // Check config first
         let cached: Option<String> = self
             .db
@@ -56,13 +131,13 @@ impl AsanaDW {
         }
 
         // Auto-detect from API
-        let workspaces = self.client.workspaces().list().await?;
+        let workspaces = self.refresh_workspaces().await?;
         match workspaces.len() {
             0 => Err(Error::Config(
                 "no workspaces found for this Asana token".into(),
             )),
             1 => {
-                let gid = workspaces[0].gid.clone();
+                let gid = workspaces[0].workspace_gid.clone();
                 self.db
                     .writer()
                     .call({
@@ -78,16 +153,117 @@ impl AsanaDW {
             _ => {
                 let names: Vec<String> = workspaces
                     .iter()
-                    .map(|w| format!("  {} ({})", w.name, w.gid))
+                    .map(|w| format!("  {} ({})", w.name, w.workspace_gid))
                     .collect();
                 Err(Error::Config(format!(
-                    "multiple workspaces found. Run: asanadw config set workspace_gid <GID>\n{}",
+                    "multiple workspaces found. Run: asanadw workspace use <GID or name>\n{}",
                     names.join("\n")
                 )))
             }
         }
     }
 
+    // ── Workspaces ───────────────────────────────────────────────
+
+    /// Workspaces seen so far, from the warehouse.
+    pub async fn workspaces(&self) -> Result<Vec<repository::Workspace>> {
+        Ok(self
+            .db
+            .reader()
+            .call(|conn| repository::list_workspaces(conn))
+            .await?)
+    }
+
+    /// Fetch the workspaces the token can see and record them.
+    pub async fn refresh_workspaces(&self) -> Result<Vec<repository::Workspace>> {
+        self.ensure_writable("refreshing workspaces")?;
+        let workspaces: Vec<repository::Workspace> = self
+            .client
+            .workspaces()
+            .list()
+            .await?
+            .into_iter()
+            .map(|w| repository::Workspace {
+                workspace_gid: w.gid,
+                name: w.name,
+            })
+            .collect();
+        self.db
+            .writer()
+            .call({
+                let workspaces = workspaces.clone();
+                move |conn| {
+                    for w in &workspaces {
+                        repository::upsert_workspace(conn, &w.workspace_gid, &w.name)?;
+                    }
+                    Ok::<(), rusqlite::Error>(())
+                }
+            })
+            .await?;
+        Ok(workspaces)
+    }
+
+    /// Find a workspace by GID or name, fetching the list from Asana if
+    /// the warehouse doesn't know it yet.
+    pub async fn resolve_workspace(&self, identifier: &str) -> Result<repository::Workspace> {
+        let find = |identifier: String| async move {
+            self.db
+                .reader()
+                .call(move |conn| repository::find_workspace(conn, &identifier))
+                .await
+        };
+        let mut found = find(identifier.to_string()).await?;
+        if found.is_empty() && !self.read_only {
+            self.refresh_workspaces().await?;
+            found = find(identifier.to_string()).await?;
+        }
+        match found.len() {
+            1 => Ok(found.remove(0)),
+            0 if url::is_gid(identifier) => Ok(repository::Workspace {
+                workspace_gid: identifier.to_string(),
+                name: identifier.to_string(),
+            }),
+            0 => Err(Error::NotFound(format!(
+                "workspace '{identifier}'. Run: asanadw workspace list"
+            ))),
+            _ => Err(Error::InvalidIdentifier(format!(
+                "several workspaces are named '{identifier}'; use its GID"
+            ))),
+        }
+    }
+
+    /// Make `identifier` (a GID or name) the configured workspace.
+    /// Undoable with [`AsanaDW::undo`].
+    pub async fn use_workspace(&self, identifier: &str) -> Result<repository::Workspace> {
+        self.ensure_writable("changing config")?;
+        let workspace = self.resolve_workspace(identifier).await?;
+        self.db
+            .writer()
+            .call({
+                let gid = workspace.workspace_gid.clone();
+                move |conn| repository::overwrite_config(conn, "workspace_gid", &gid)
+            })
+            .await?;
+        Ok(workspace)
+    }
+
+    /// The workspace a monitored entity belongs to, falling back to the
+    /// current one for entities added before workspaces were recorded.
+    async fn entity_workspace_gid(&self, entity_key: &str) -> Result<String> {
+        let recorded = self
+            .db
+            .reader()
+            .call({
+                let entity_key = entity_key.to_string();
+                move |conn| repository::get_entity_workspace(conn, &entity_key)
+            })
+            .await?;
+        match recorded {
+            Some(gid) => Ok(gid),
+            None => self.workspace_gid().await,
+        }
+    }
+
     // ── User identity ────────────────────────────────────────────
 
     /// Auto-detect and cache the current user's identity.
@@ -150,6 +326,33 @@ impl AsanaDW {
             .map_err(|e| Error::Database(e.to_string()))
     }
 
+    /// Refresh which known users are deactivated in the workspace.
+    /// Returns the number of deactivated users found.
+    pub async fn refresh_user_activity(&self) -> Result<usize> {
+        self.ensure_writable("refreshing user activity")?;
+        let workspace_gid = self.workspace_gid().await?;
+        syncer::refresh_user_activity(&self.db, &self.client, &workspace_gid).await
+    }
+
+    /// Fill in permalinks missing from rows synced before they were stored,
+    /// fetching them from Asana and building the rest from their workspace.
+    pub async fn backfill_permalinks(
+        &self,
+        dry_run: bool,
+    ) -> Result<sync::permalinks::PermalinkReport> {
+        if !dry_run {
+            self.ensure_writable("backfilling permalinks")?;
+        }
+        sync::permalinks::backfill_permalinks(&self.db, Some(&self.client), dry_run).await
+    }
+
+    /// Metadata, sync state, headline metrics, and the latest cached
+    /// summary of `entity_key` (e.g. `project:123`) in one call, from local
+    /// data only.
+    pub async fn entity_overview(&self, entity_key: &str) -> Result<overview::EntityOverview> {
+        overview::entity_overview(&self.db, entity_key).await
+    }
+
     // ── Sync commands ──────────────────────────────────────────────
 
     pub async fn sync_project(
@@ -158,8 +361,11 @@ impl AsanaDW {
         options: &SyncOptions,
         progress: &dyn SyncProgress,
     ) -> Result<SyncReport> {
-        let gid = url::resolve_gid(identifier)?;
-        syncer::sync_project(&self.db, &self.client, &gid, options, progress).await
+        self.locked(async {
+            let gid = alias::resolve(&self.db, identifier).await?;
+            syncer::sync_project(&self.db, &self.client, &gid, options, progress).await
+        })
+        .await
     }
 
     pub async fn sync_user(
@@ -168,16 +374,19 @@ impl AsanaDW {
         options: &SyncOptions,
         progress: &dyn SyncProgress,
     ) -> Result<SyncReport> {
-        let workspace_gid = self.workspace_gid().await?;
-        let gid = url::resolve_gid(identifier)?;
-        syncer::sync_user(
-            &self.db,
-            &self.client,
-            &workspace_gid,
-            &gid,
-            options,
-            progress,
-        )
+        self.locked(async {
+            let workspace_gid = self.workspace_gid().await?;
+            let gid = alias::resolve(&self.db, identifier).await?;
+            syncer::sync_user(
+                &self.db,
+                &self.client,
+                &workspace_gid,
+                &gid,
+                options,
+                progress,
+            )
+            .await
+        })
         .await
     }
 
@@ -187,16 +396,30 @@ impl AsanaDW {
         options: &SyncOptions,
         progress: &dyn SyncProgress,
     ) -> Result<SyncReport> {
-        let workspace_gid = self.workspace_gid().await?;
-        let gid = url::resolve_gid(identifier)?;
-        syncer::sync_team(
-            &self.db,
-            &self.client,
-            &workspace_gid,
-            &gid,
-            options,
-            progress,
-        )
+        self.locked(async {
+            let workspace_gid = self.workspace_gid().await?;
+            let gid = alias::resolve(&self.db, identifier).await?;
+            syncer::sync_team(
+                &self.db,
+                &self.client,
+                &workspace_gid,
+                &gid,
+                options,
+                progress,
+                &SyncedProjects::default(),
+            )
+            .await
+        })
+        .await
+    }
+
+    /// Sync every team in the workspace and its members, without syncing
+    /// the teams' projects.
+    pub async fn sync_teams(&self) -> Result<SyncReport> {
+        self.locked(async {
+            let workspace_gid = self.workspace_gid().await?;
+            syncer::sync_team_directory(&self.db, &self.client, &workspace_gid).await
+        })
         .await
     }
 
@@ -206,14 +429,40 @@ impl AsanaDW {
         options: &SyncOptions,
         progress: &dyn SyncProgress,
     ) -> Result<SyncReport> {
-        let gid = url::resolve_gid(identifier)?;
-        syncer::sync_portfolio(&self.db, &self.client, &gid, options, progress).await
+        self.locked(async {
+            let gid = alias::resolve(&self.db, identifier).await?;
+            syncer::sync_portfolio(
+                &self.db,
+                &self.client,
+                &gid,
+                options,
+                progress,
+                &SyncedProjects::default(),
+            )
+            .await
+        })
+        .await
     }
 
+    /// Sync every monitored entity. A project reachable from several
+    /// entities is synced once; the repeats are counted in each report's
+    /// `skipped_duplicates` and report that sync's counts and failures.
     pub async fn sync_all(
         &self,
         options: &SyncOptions,
         progress: &dyn SyncProgress,
+    ) -> Result<Vec<SyncReport>> {
+        if options.dry_run {
+            progress.on_plan(&self.sync_plan(options).await?);
+            return Ok(Vec::new());
+        }
+        self.locked(self.sync_monitored(options, progress)).await
+    }
+
+    async fn sync_monitored(
+        &self,
+        options: &SyncOptions,
+        progress: &dyn SyncProgress,
     ) -> Result<Vec<SyncReport>> {
         // Auto-detect user identity on first sync
         if let Err(e) = self.ensure_user_identity().await {
@@ -226,43 +475,210 @@ impl AsanaDW {
             .call(|conn| repository::list_monitored_entities(conn))
             .await?;
 
-        let total = entities.len();
-        let mut reports = Vec::new();
-        for (i, entity) in entities.iter().enumerate() {
-            progress.on_entity_start(&entity.entity_key, i, total);
+        let syncable: Vec<&repository::MonitoredEntity> = entities
+            .iter()
+            .filter(|entity| {
+                let known = matches!(
+                    entity.entity_type.as_str(),
+                    "project" | "user" | "team" | "portfolio"
+                );
+                if !known {
+                    log::warn!("Unknown entity type: {}", entity.entity_type);
+                }
+                known
+            })
+            // With an explicit workspace, only that workspace's entities
+            .filter(|entity| match &self.workspace_override {
+                Some(ws) => entity.workspace_gid.as_deref() == Some(ws.as_str()),
+                None => true,
+            })
+            .collect();
+
+        // Entities are independent, so up to `concurrency` of them sync at
+        // once. They share one client, so 429 backoff still applies to all.
+        let total = syncable.len();
+        let synced = SyncedProjects::default();
+        let semaphore = tokio::sync::Semaphore::new(options.concurrency.max(1));
+        let syncs = syncable.into_iter().enumerate().map(|(i, entity)| {
+            let semaphore = &semaphore;
+            let synced = &synced;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("semaphore is never closed");
+                progress.on_entity_start(&entity.entity_key, i, total);
 
-            let result = match entity.entity_type.as_str() {
-                "project" => {
-                    syncer::sync_project(
-                        &self.db,
-                        &self.client,
+                let started = std::time::Instant::now();
+                let result = self
+                    .sync_entity_with(
+                        &entity.entity_type,
                         &entity.entity_gid,
                         options,
                         progress,
+                        synced,
                     )
-                    .await
+                    .await;
+                let report = match result {
+                    Ok(report) => report,
+                    Err(e) => {
+                        log::error!("Failed to sync {}: {e}", entity.entity_key);
+                        SyncReport::failed(entity.entity_key.clone(), e)
+                    }
+                };
+                progress.on_entity_complete(&report);
+                let duration = started.elapsed().as_secs_f64();
+                if let Err(e) = sync::history::record(&self.db, &report, duration).await {
+                    log::warn!(
+                        "Could not record sync report for {}: {e}",
+                        report.entity_key
+                    );
+                }
+                report
+            }
+        });
+        let reports = futures::future::join_all(syncs).await;
+
+        // Prune past the configured retention; a failure here shouldn't
+        // fail the sync
+        match storage::retention::auto_prune(&self.db).await {
+            Ok(Some(pruned)) if pruned.tasks > 0 || pruned.status_updates > 0 => log::info!(
+                "Pruned {} tasks, {} comments, and {} status updates before {}",
+                pruned.tasks,
+                pruned.comments,
+                pruned.status_updates,
+                pruned.cutoff
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!("Automatic pruning failed: {e}"),
+        }
+        Ok(reports)
+    }
+
+    /// How each report's sync compares with the entity's previous
+    /// [`sync_all`](Self::sync_all) run, for entities synced at least twice.
+    pub async fn sync_diffs(
+        &self,
+        reports: &[SyncReport],
+    ) -> Result<Vec<sync::history::SyncReportDiff>> {
+        let keys = reports.iter().map(|r| r.entity_key.clone()).collect();
+        sync::history::diffs(&self.db, keys).await
+    }
+
+    /// What [`sync_all`](Self::sync_all) would fetch with `options`, from
+    /// local state only: nothing is fetched or written.
+    pub async fn sync_plan(&self, options: &SyncOptions) -> Result<Vec<sync::plan::PlannedSync>> {
+        sync::plan::plan_sync_all(&self.db, options).await
+    }
+
+    /// Which days a project's syncs have covered from `since` through
+    /// yesterday, and the holes `sync_project` would backfill. Without
+    /// `since`, the range starts at the earlier of the first covered day and
+    /// the default 90-day sync window.
+    pub async fn sync_coverage(
+        &self,
+        identifier: &str,
+        since: Option<chrono::NaiveDate>,
+    ) -> Result<sync::gap::Coverage> {
+        let identifier = identifier.strip_prefix("project:").unwrap_or(identifier);
+        let gid = alias::resolve(&self.db, identifier).await?;
+        let entity_key = format!("project:{gid}");
+        let yesterday = chrono::Local::now().date_naive() - chrono::Duration::days(1);
+        let since = match since {
+            Some(since) => since,
+            None => {
+                let first = self
+                    .db
+                    .reader()
+                    .call({
+                        let entity_key = entity_key.clone();
+                        move |conn| sync::gap::entity_coverage(conn, &entity_key)
+                    })
+                    .await?
+                    .first()
+                    .map(|r| r.start);
+                let default = yesterday - chrono::Duration::days(89);
+                first.map_or(default, |first| first.min(default))
+            }
+        };
+        sync::gap::coverage(&self.db, &entity_key, since, yesterday).await
+    }
+
+    /// Resume full project syncs interrupted within the last day, each from
+    /// where it stopped.
+    pub async fn sync_resume(
+        &self,
+        options: &SyncOptions,
+        progress: &dyn SyncProgress,
+    ) -> Result<Vec<SyncReport>> {
+        self.locked(syncer::resume_interrupted(
+            &self.db,
+            &self.client,
+            options,
+            progress,
+        ))
+        .await
+    }
+
+    /// Sync one monitored entity by type (`project`, `user`, `team`, or `portfolio`) and GID.
+    pub async fn sync_entity(
+        &self,
+        entity_type: &str,
+        entity_gid: &str,
+        options: &SyncOptions,
+        progress: &dyn SyncProgress,
+    ) -> Result<SyncReport> {
+        let synced = SyncedProjects::default();
+        self.sync_entity_with(entity_type, entity_gid, options, progress, &synced)
+            .await
+    }
+
+    /// Like [`sync_entity`](Self::sync_entity), as part of a run syncing
+    /// several entities. Projects already in `synced` aren't fetched again,
+    /// and report the earlier sync's result.
+    pub async fn sync_entity_with(
+        &self,
+        entity_type: &str,
+        entity_gid: &str,
+        options: &SyncOptions,
+        progress: &dyn SyncProgress,
+        synced: &SyncedProjects,
+    ) -> Result<SyncReport> {
+        self.locked(async {
+            match entity_type {
+                "project" => {
+                    let sync =
+                        syncer::sync_project(&self.db, &self.client, entity_gid, options, progress);
+                    let outcome = synced.sync_once(entity_gid, sync).await;
+                    match outcome.result {
+                        // Its requests count toward the entity that synced it
+                        Ok(report) if outcome.shared => Ok(SyncReport {
+                            skipped_duplicates: 1,
+                            rate_limit: Default::default(),
+                            ..report
+                        }),
+                        result => result,
+                    }
                 }
                 "user" => {
-                    let ws = self.workspace_gid().await?;
-                    syncer::sync_user(
-                        &self.db,
-                        &self.client,
-                        &ws,
-                        &entity.entity_gid,
-                        options,
-                        progress,
-                    )
-                    .await
+                    let ws = self
+                        .entity_workspace_gid(&format!("user:{entity_gid}"))
+                        .await?;
+                    syncer::sync_user(&self.db, &self.client, &ws, entity_gid, options, progress)
+                        .await
                 }
                 "team" => {
-                    let ws = self.workspace_gid().await?;
+                    let ws = self
+                        .entity_workspace_gid(&format!("team:{entity_gid}"))
+                        .await?;
                     syncer::sync_team(
                         &self.db,
                         &self.client,
                         &ws,
-                        &entity.entity_gid,
+                        entity_gid,
                         options,
                         progress,
+                        synced,
                     )
                     .await
                 }
@@ -270,49 +686,37 @@ impl AsanaDW {
                     syncer::sync_portfolio(
                         &self.db,
                         &self.client,
-                        &entity.entity_gid,
+                        entity_gid,
                         options,
                         progress,
+                        synced,
                     )
                     .await
                 }
-                other => {
-                    log::warn!("Unknown entity type: {other}");
-                    continue;
-                }
-            };
-            match result {
-                Ok(report) => {
-                    progress.on_entity_complete(&report);
-                    reports.push(report);
-                }
-                Err(e) => {
-                    log::error!("Failed to sync {}: {e}", entity.entity_key);
-                    let report = SyncReport {
-                        entity_key: entity.entity_key.clone(),
-                        status: SyncStatus::Failed,
-                        items_synced: 0,
-                        items_failed: 1,
-                        batches_completed: 0,
-                        batches_total: 0,
-                        error: Some(e.to_string()),
-                    };
-                    progress.on_entity_complete(&report);
-                    reports.push(report);
-                }
+                other => Err(Error::InvalidIdentifier(format!(
+                    "unknown entity type: {other}"
+                ))),
             }
-        }
-        Ok(reports)
+        })
+        .await
     }
 
     // ── Monitor commands ───────────────────────────────────────────
 
     pub async fn monitor_add(&self, entity_type: &str, identifier: &str) -> Result<String> {
-        let gid = url::resolve_gid(identifier)?;
+        self.ensure_writable("changing monitored entities")?;
+        let mut gid = alias::resolve(&self.db, identifier).await?;
+        let mut team_name = None;
+        if entity_type == "team" && !url::is_gid(&gid) {
+            // A team name, resolved against the directory `sync teams` fills
+            team_name = Some(gid.clone());
+            gid = self.team_gid_by_name(&gid).await?;
+        }
         let entity_key = format!("{entity_type}:{gid}");
 
         // Try to get a display name
         let display_name = match entity_type {
+            "team" => team_name,
             "project" => self.client.projects().get(&gid).await.map(|p| p.name).ok(),
             "portfolio" => self
                 .client
@@ -323,6 +727,8 @@ impl AsanaDW {
                 .ok(),
             _ => None,
         };
+        // Unknown when several workspaces exist and none is chosen yet
+        let workspace_gid = self.workspace_gid().await.ok();
 
         self.db
             .writer()
@@ -338,6 +744,9 @@ impl AsanaDW {
                         &gid,
                         display_name.as_deref(),
                     )?;
+                    if let Some(ws) = &workspace_gid {
+                        repository::set_entity_workspace(conn, &entity_key, ws)?;
+                    }
                     Ok::<(), rusqlite::Error>(())
                 }
             })
@@ -346,7 +755,30 @@ impl AsanaDW {
         Ok(entity_key)
     }
 
+    /// The GID of the synced team called `name`.
+    async fn team_gid_by_name(&self, name: &str) -> Result<String> {
+        let gids = self
+            .db
+            .reader()
+            .call({
+                let name = name.to_string();
+                move |conn| repository::find_teams_by_name(conn, &name)
+            })
+            .await?;
+        match gids.as_slice() {
+            [gid] => Ok(gid.clone()),
+            [] => Err(Error::NotFound(format!(
+                "team '{name}'. Run: asanadw sync teams"
+            ))),
+            _ => Err(Error::InvalidIdentifier(format!(
+                "several teams are named '{name}': {}",
+                gids.join(", ")
+            ))),
+        }
+    }
+
     pub async fn monitor_remove(&self, entity_key: &str) -> Result<bool> {
+        self.ensure_writable("changing monitored entities")?;
         self.db
             .writer()
             .call({
@@ -357,6 +789,24 @@ impl AsanaDW {
             .map_err(|e| Error::Database(e.to_string()))
     }
 
+    /// Set an entity's daemon sync interval in minutes (`None` restores the default).
+    /// Returns false if the entity is not monitored.
+    pub async fn monitor_set_interval(
+        &self,
+        entity_key: &str,
+        interval_minutes: Option<u32>,
+    ) -> Result<bool> {
+        self.ensure_writable("changing monitored entities")?;
+        self.db
+            .writer()
+            .call({
+                let entity_key = entity_key.to_string();
+                move |conn| repository::set_sync_interval(conn, &entity_key, interval_minutes)
+            })
+            .await
+            .map_err(|e| Error::Database(e.to_string()))
+    }
+
     pub async fn monitor_list(&self) -> Result<Vec<repository::MonitoredEntity>> {
         self.db
             .reader()
@@ -365,9 +815,61 @@ impl AsanaDW {
             .map_err(|e| Error::Database(e.to_string()))
     }
 
+    /// Unmonitored projects that synced tasks belong to, awaiting review.
+    pub async fn monitor_discovered(&self) -> Result<Vec<repository::DiscoveredProject>> {
+        self.db
+            .reader()
+            .call(|conn| repository::list_discovered_projects(conn))
+            .await
+            .map_err(|e| Error::Database(e.to_string()))
+    }
+
+    /// Monitor a discovered project. Returns its entity key.
+    pub async fn monitor_promote_discovered(
+        &self,
+        project: &repository::DiscoveredProject,
+    ) -> Result<String> {
+        self.ensure_writable("changing monitored entities")?;
+        let entity_key = format!("project:{}", project.project_gid);
+        self.db
+            .writer()
+            .call({
+                let entity_key = entity_key.clone();
+                let gid = project.project_gid.clone();
+                let name = project.project_name.clone();
+                move |conn| {
+                    repository::add_monitored_entity(
+                        conn,
+                        &entity_key,
+                        "project",
+                        &gid,
+                        name.as_deref(),
+                    )?;
+                    repository::set_discovered_project_status(conn, &gid, "promoted")?;
+                    Ok::<(), rusqlite::Error>(())
+                }
+            })
+            .await?;
+        Ok(entity_key)
+    }
+
+    /// Stop suggesting a discovered project. Returns false if it was never discovered.
+    pub async fn monitor_dismiss_discovered(&self, project_gid: &str) -> Result<bool> {
+        self.ensure_writable("changing monitored entities")?;
+        self.db
+            .writer()
+            .call({
+                let gid = project_gid.to_string();
+                move |conn| repository::set_discovered_project_status(conn, &gid, "dismissed")
+            })
+            .await
+            .map_err(|e| Error::Database(e.to_string()))
+    }
+
     /// Discover the user's favorited projects and portfolios and add them
     /// as monitored entities. Returns the list of entity keys added.
     pub async fn monitor_add_favorites(&self) -> Result<Vec<String>> {
+        self.ensure_writable("changing monitored entities")?;
         let workspace_gid = self.workspace_gid().await?;
         let _user_gid = self.ensure_user_identity().await?;
 
@@ -420,12 +922,23 @@ impl AsanaDW {
     }
 
     pub async fn config_set(&self, key: &str, value: &str) -> Result<()> {
+        self.ensure_writable("changing config")?;
         self.db
             .writer()
             .call({
                 let key = key.to_string();
                 let value = value.to_string();
-                move |conn| repository::set_config(conn, &key, &value)
+                move |conn| {
+                    let tx = conn.transaction()?;
+                    repository::overwrite_config(&tx, &key, &value)?;
+                    if key == field_changes::TRACKED_FIELDS_KEY {
+                        field_changes::rebuild_all_field_changes(&tx)?;
+                    }
+                    if key == bots::BOT_AUTHORS_KEY || key == bots::BOT_STORY_TYPES_KEY {
+                        bots::reflag_all_bot_comments(&tx)?;
+                    }
+                    tx.commit()
+                }
             })
             .await
             .map_err(|e| Error::Database(e.to_string()))
@@ -438,4 +951,26 @@ impl AsanaDW {
             .await
             .map_err(|e| Error::Database(e.to_string()))
     }
+
+    /// Revert the last monitor removal or config change not yet undone.
+    pub async fn undo(&self) -> Result<Option<repository::LocalChange>> {
+        self.ensure_writable("undo")?;
+        self.db
+            .writer()
+            .call(|conn| {
+                let tx = conn.transaction()?;
+                let change = repository::undo_last_local_change(&tx)?;
+                let key = change.as_ref().map(|c| c.target_key.as_str());
+                if key == Some(field_changes::TRACKED_FIELDS_KEY) {
+                    field_changes::rebuild_all_field_changes(&tx)?;
+                }
+                if key == Some(bots::BOT_AUTHORS_KEY) || key == Some(bots::BOT_STORY_TYPES_KEY) {
+                    bots::reflag_all_bot_comments(&tx)?;
+                }
+                tx.commit()?;
+                Ok::<_, rusqlite::Error>(change)
+            })
+            .await
+            .map_err(|e| Error::Database(e.to_string()))
+    }
 }
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::date_util::strip_code_fences;
+use crate::error::Result;
+use crate::llm::{Agent, LlmCall};
+use crate::mcp::{run_warehouse_tool, warehouse_tool_definitions};
+use crate::storage::Database;
+use crate::url::generate_asana_url;
+
+const PROMPT_VERSION: &str = "ask-v1";
+
+const CALL: LlmCall = LlmCall {
+    agent: "ask",
+    prompt_version: PROMPT_VERSION,
+};
+
+/// Longest tool output passed back to the model, in characters.
+const MAX_RESULT_CHARS: usize = 8000;
+
+/// One tool call the model made while answering.
+#[derive(Debug, Clone, Serialize)]
+pub struct AskStep {
+    pub tool: String,
+    pub arguments: Value,
+    pub is_error: bool,
+}
+
+/// A task the answer relies on.
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+    pub task_gid: String,
+    pub name: String,
+    pub url: String,
+}
+
+/// The model's answer to a question, with its sources.
+#[derive(Debug, Clone, Serialize)]
+pub struct AskAnswer {
+    pub question: String,
+    pub answer: String,
+    /// Cited tasks found in the warehouse; GIDs the model made up are dropped.
+    pub citations: Vec<Citation>,
+    pub steps: Vec<AskStep>,
+}
+
+/// What the model wants to do next.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum Reply {
+    Tool {
+        tool: String,
+        #[serde(default)]
+        arguments: Value,
+    },
+    Answer {
+        answer: String,
+        #[serde(default)]
+        task_gids: Vec<String>,
+    },
+}
+
+/// Answer a free-form question about the warehouse. The model calls the
+/// read-only warehouse tools (search, task query, metrics, SQL) up to
+/// `max_steps` times, then answers citing the tasks it relied on. Answers
+/// are not cached since the data changes with every sync.
+pub async fn ask(
+    db: &Database,
+    agent: &Agent,
+    question: &str,
+    max_steps: usize,
+) -> Result<AskAnswer> {
+    let mut steps: Vec<AskStep> = Vec::new();
+    let mut transcript = String::new();
+
+    loop {
+        let must_answer = steps.len() >= max_steps;
+        let prompt = build_prompt(question, &transcript, must_answer);
+        let response = agent.run(CALL, &prompt).await?;
+        let text = response.trim();
+
+        let (answer, task_gids) = match parse_reply(text) {
+            Some(Reply::Tool { tool, arguments }) if !must_answer => {
+                log::info!("ask: {tool} {arguments}");
+                let (output, is_error) = match run_warehouse_tool(db, &tool, &arguments).await {
+                    Some(Ok(output)) => (output, false),
+                    Some(Err(e)) => (format!("Error: {e}"), true),
+                    None => (format!("Error: unknown tool {tool}"), true),
+                };
+                transcript.push_str(&format!(
+                    "\nStep {}: {tool} {arguments}\nResult:\n{}\n",
+                    steps.len() + 1,
+                    truncate(&output, MAX_RESULT_CHARS)
+                ));
+                steps.push(AskStep {
+                    tool,
+                    arguments,
+                    is_error,
+                });
+                continue;
+            }
+            Some(Reply::Answer { answer, task_gids }) => (answer, task_gids),
+            // A model that ignores the format has still answered in prose
+            _ => (text.to_string(), Vec::new()),
+        };
+
+        let citations = cite(db, task_gids).await?;
+        return Ok(AskAnswer {
+            question: question.to_string(),
+            answer,
+            citations,
+            steps,
+        });
+    }
+}
+
+fn build_prompt(question: &str, transcript: &str, must_answer: bool) -> String {
+    let today = chrono::Local::now().date_naive();
+    let tools: Vec<String> = warehouse_tool_definitions()
+        .iter()
+        .map(|t| {
+            format!(
+                "- {}: {}\n  Arguments: {}",
+                t["name"].as_str().unwrap_or_default(),
+                t["description"].as_str().unwrap_or_default(),
+                t["inputSchema"]["properties"]
+            )
+        })
+        .collect();
+    let tools = tools.join("\n");
+    let transcript = if transcript.is_empty() {
+        "(none yet)"
+    } else {
+        transcript
+    };
+    let next = if must_answer {
+        "You have used all your tool calls. Answer now with what you have."
+    } else {
+        "Call one tool, or answer once the results support an answer."
+    };
+
+    format!(
+        r#"You answer questions about a team's Asana work using a local warehouse of synced Asana data. Today is {today}.
+
+Tools:
+{tools}
+
+Question: {question}
+
+Tool calls so far:
+{transcript}
+
+{next}
+
+Respond with ONLY a JSON object (no markdown, no code fences), either a tool call:
+{{"tool": "search", "arguments": {{"query": "..."}}}}
+or your answer:
+{{"answer": "Your answer, citing tasks by GID", "task_gids": ["GIDs of the tasks the answer relies on"]}}
+
+Base the answer only on tool results, and say so if they don't answer the question."#
+    )
+}
+
+fn parse_reply(text: &str) -> Option<Reply> {
+    serde_json::from_str(strip_code_fences(text)).ok()
+}
+
+/// Cut `s` to at most `max` characters, noting how much was dropped.
+fn truncate(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => format!(
+            "{}\n... ({} more characters)",
+            &s[..i],
+            s[i..].chars().count()
+        ),
+        None => s.to_string(),
+    }
+}
+
+/// Look up cited tasks, keeping only ones in the warehouse.
+async fn cite(db: &Database, task_gids: Vec<String>) -> Result<Vec<Citation>> {
+    let citations = db
+        .reader()
+        .call(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT name, permalink_url FROM fact_tasks WHERE task_gid = ?1")?;
+            let mut citations: Vec<Citation> = Vec::new();
+            for gid in task_gids {
+                if citations.iter().any(|c| c.task_gid == gid) {
+                    continue;
+                }
+                let mut rows = stmt.query([&gid])?;
+                if let Some(row) = rows.next()? {
+                    let url: Option<String> = row.get(1)?;
+                    citations.push(Citation {
+                        name: row.get(0)?,
+                        url: url.unwrap_or_else(|| generate_asana_url("task", &gid)),
+                        task_gid: gid,
+                    });
+                }
+            }
+            Ok::<Vec<Citation>, rusqlite::Error>(citations)
+        })
+        .await?;
+    Ok(citations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(
+            parse_reply(r#"{"tool": "search", "arguments": {"query": "launch"}}"#),
+            Some(Reply::Tool {
+                tool: "search".to_string(),
+                arguments: serde_json::json!({"query": "launch"}),
+            })
+        );
+        assert_eq!(
+            parse_reply("```json\n{\"answer\": \"Two slipped.\", \"task_gids\": [\"1\"]}\n```"),
+            Some(Reply::Answer {
+                answer: "Two slipped.".to_string(),
+                task_gids: vec!["1".to_string()],
+            })
+        );
+        assert_eq!(parse_reply("Two projects slipped."), None);
+
+        let prompt = build_prompt("Which projects slipped?", "", true);
+        assert!(prompt.contains("Which projects slipped?"));
+        assert!(prompt.contains("- metrics:"));
+        assert!(prompt.contains("Answer now"));
+
+        assert_eq!(truncate("abcdef", 10), "abcdef");
+        assert_eq!(truncate("abcdef", 4), "abcd\n... (2 more characters)");
+    }
+
+    #[tokio::test]
+    async fn test_cite_drops_unknown_tasks() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('111', 'Ship it', '2025-01-01', '2025-01-01', datetime('now'))",
+                    [],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let gids = ["111", "999", "111"].map(String::from).to_vec();
+        let citations = cite(&db, gids).await.unwrap();
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].name, "Ship it");
+        assert_eq!(citations[0].url, generate_asana_url("task", "111"));
+    }
+}
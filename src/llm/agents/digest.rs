@@ -0,0 +1,438 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::llm::agents::{period, run_json};
+use crate::llm::{Agent, LlmCall};
+use crate::metrics::{CollaborationMetrics, HealthMetrics, ThroughputMetrics};
+use crate::query::period::Period;
+use crate::report::escape_html;
+use crate::storage::repository;
+use crate::storage::Database;
+use crate::url::generate_asana_url;
+
+const PROMPT_VERSION: &str = "digest-v1";
+
+const CALL: LlmCall = LlmCall {
+    agent: "digest",
+    prompt_version: PROMPT_VERSION,
+};
+
+/// One entity's part of a digest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSection {
+    /// `user`, `project`, or `portfolio`.
+    pub entity_type: String,
+    pub entity_gid: String,
+    pub title: String,
+    /// Asana link for projects and portfolios.
+    pub url: Option<String>,
+    pub headline: String,
+    pub what_changed: String,
+    /// Accomplishments or milestones from the entity's period summary.
+    pub highlights: Vec<String>,
+    pub health_assessment: Option<String>,
+    /// One-line metrics, e.g. "5 completed (2 without comments), 3 created,
+    /// 12 open (2 overdue)".
+    pub stats: String,
+}
+
+/// A digest of one user's period: their own work, then each monitored
+/// project and portfolio.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub user_gid: String,
+    pub period_key: String,
+    pub headline: String,
+    /// The few items across all sections most worth reading.
+    pub top_items: Vec<String>,
+    pub sections: Vec<DigestSection>,
+}
+
+#[derive(Deserialize)]
+struct DigestIntro {
+    headline: String,
+    top_items: Vec<String>,
+}
+
+/// Build a digest for `user_gid` over `period` from period summaries of the
+/// user and every monitored project and portfolio, plus their metrics.
+/// Summaries are reused from their own caches; `force` regenerates them and
+/// the digest. Entities that fail to summarize are left out with a warning.
+/// Nothing is cached on a read-only database.
+pub async fn compose_digest(
+    db: &Database,
+    agent: &Agent,
+    user_gid: &str,
+    period: &Period,
+    force: bool,
+) -> Result<Digest> {
+    let period_key = period.to_key();
+
+    if !force {
+        if let Some(cached) = get_cached_digest(db, user_gid, &period_key).await? {
+            agent.record_cache_hit(CALL).await;
+            return Ok(cached);
+        }
+    }
+
+    let mut sections = vec![user_section(db, agent, user_gid, period, force).await?];
+
+    let monitored = db
+        .reader()
+        .call(|conn| repository::list_monitored_entities(conn))
+        .await?;
+    for entity in monitored.iter().filter(|e| e.entity_type == "project") {
+        match project_section(db, agent, &entity.entity_gid, period, force).await {
+            Ok(section) => sections.push(section),
+            Err(e) => log::warn!("Skipping {} in digest: {e}", entity.entity_key),
+        }
+    }
+    for entity in monitored.iter().filter(|e| e.entity_type == "portfolio") {
+        match portfolio_section(db, agent, &entity.entity_gid, period, force).await {
+            Ok(section) => sections.push(section),
+            Err(e) => log::warn!("Skipping {} in digest: {e}", entity.entity_key),
+        }
+    }
+
+    let intro = write_intro(agent, &period_key, &sections).await?;
+    let digest = Digest {
+        user_gid: user_gid.to_string(),
+        period_key,
+        headline: intro.headline,
+        top_items: intro.top_items,
+        sections,
+    };
+    store_digest(db, &digest).await?;
+    Ok(digest)
+}
+
+async fn write_intro(
+    agent: &Agent,
+    period_key: &str,
+    sections: &[DigestSection],
+) -> Result<DigestIntro> {
+    let sections_json = serde_json::to_string_pretty(sections).unwrap_or_default();
+    let prompt = format!(
+        r#"These are summaries of one person's work and the projects and portfolios they follow during the period {period_key}. Write the opening of a digest that tells them what to read first.
+
+Summaries:
+{sections_json}
+
+Respond with ONLY a JSON object (no markdown, no code fences):
+{{
+  "headline": "One sentence on the period overall",
+  "top_items": ["2-5 of the most important wins, risks, or changes across all summaries, each naming its project or portfolio"]
+}}"#
+    );
+
+    run_json(agent, CALL, &prompt).await
+}
+
+async fn user_section(
+    db: &Database,
+    agent: &Agent,
+    user_gid: &str,
+    period: &Period,
+    force: bool,
+) -> Result<DigestSection> {
+    let summary = period::summarize_user_period(db, agent, user_gid, period, force).await?;
+    let metrics = crate::metrics::compute_user_metrics(db, user_gid, period).await?;
+    Ok(DigestSection {
+        entity_type: "user".to_string(),
+        entity_gid: user_gid.to_string(),
+        title: "My work".to_string(),
+        url: None,
+        headline: summary.headline,
+        what_changed: summary.what_changed,
+        highlights: summary.key_accomplishments,
+        health_assessment: None,
+        stats: stats_line(&metrics.throughput, &metrics.collaboration, None),
+    })
+}
+
+async fn project_section(
+    db: &Database,
+    agent: &Agent,
+    project_gid: &str,
+    period: &Period,
+    force: bool,
+) -> Result<DigestSection> {
+    let summary = period::summarize_project_period(db, agent, project_gid, period, force).await?;
+    let metrics = crate::metrics::compute_project_metrics(db, project_gid, period).await?;
+    Ok(DigestSection {
+        entity_type: "project".to_string(),
+        entity_gid: project_gid.to_string(),
+        title: metrics
+            .project_name
+            .unwrap_or_else(|| project_gid.to_string()),
+        url: Some(generate_asana_url("project", project_gid)),
+        headline: summary.headline,
+        what_changed: summary.what_changed,
+        highlights: summary.key_milestones,
+        health_assessment: summary.health_assessment,
+        stats: stats_line(
+            &metrics.throughput,
+            &metrics.collaboration,
+            Some(&metrics.health),
+        ),
+    })
+}
+
+async fn portfolio_section(
+    db: &Database,
+    agent: &Agent,
+    portfolio_gid: &str,
+    period: &Period,
+    force: bool,
+) -> Result<DigestSection> {
+    let summary =
+        period::summarize_portfolio_period(db, agent, portfolio_gid, period, force).await?;
+    let metrics = crate::metrics::compute_portfolio_metrics(db, portfolio_gid, period).await?;
+    Ok(DigestSection {
+        entity_type: "portfolio".to_string(),
+        entity_gid: portfolio_gid.to_string(),
+        title: metrics
+            .portfolio_name
+            .unwrap_or_else(|| portfolio_gid.to_string()),
+        url: Some(generate_asana_url("portfolio", portfolio_gid)),
+        headline: summary.headline,
+        what_changed: summary.what_changed,
+        highlights: summary.key_milestones,
+        health_assessment: summary.health_assessment,
+        stats: format!(
+            "{} projects, {}",
+            metrics.project_count,
+            stats_line(
+                &metrics.throughput,
+                &metrics.collaboration,
+                Some(&metrics.health),
+            )
+        ),
+    })
+}
+
+fn stats_line(
+    throughput: &ThroughputMetrics,
+    collaboration: &CollaborationMetrics,
+    health: Option<&HealthMetrics>,
+) -> String {
+    let mut line = format!("{} completed", throughput.tasks_completed);
+    if collaboration.silent_completions > 0 {
+        line.push_str(&format!(
+            " ({} without comments)",
+            collaboration.silent_completions
+        ));
+    }
+    line.push_str(&format!(", {} created", throughput.tasks_created));
+    if let Some(h) = health {
+        line.push_str(&format!(
+            ", {} open ({} overdue)",
+            h.total_open, h.overdue_count
+        ));
+    }
+    line
+}
+
+impl Digest {
+    /// Render as Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Digest: {}\n\n{}\n", self.period_key, self.headline);
+        if !self.top_items.is_empty() {
+            md.push_str("\n## Top items\n\n");
+            for item in &self.top_items {
+                md.push_str(&format!("- {item}\n"));
+            }
+        }
+        for s in &self.sections {
+            let title = match &s.url {
+                Some(url) => format!("[{}]({url})", s.title),
+                None => s.title.clone(),
+            };
+            md.push_str(&format!(
+                "\n## {title}\n\n**{}**\n\n{}\n\n_{}_\n",
+                s.headline, s.what_changed, s.stats
+            ));
+            if !s.highlights.is_empty() {
+                md.push('\n');
+                for h in &s.highlights {
+                    md.push_str(&format!("- {h}\n"));
+                }
+            }
+            if let Some(health) = &s.health_assessment {
+                md.push_str(&format!("\nHealth: {health}\n"));
+            }
+        }
+        md
+    }
+
+    /// Render as a standalone HTML page with inline styles, suitable as an
+    /// email body.
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<body style=\"font-family: Helvetica, Arial, sans-serif; max-width: 640px; color: #1e1f21;\">\n\
+             <h1 style=\"font-size: 22px;\">Digest: {}</h1>\n<p>{}</p>\n",
+            escape_html(&self.period_key),
+            escape_html(&self.headline)
+        );
+        if !self.top_items.is_empty() {
+            html.push_str("<h2 style=\"font-size: 18px;\">Top items</h2>\n");
+            html.push_str(&html_list(&self.top_items));
+        }
+        for s in &self.sections {
+            let title = match &s.url {
+                Some(url) => format!(
+                    "<a href=\"{}\" style=\"color: #1e1f21;\">{}</a>",
+                    escape_html(url),
+                    escape_html(&s.title)
+                ),
+                None => escape_html(&s.title),
+            };
+            html.push_str(&format!(
+                "<h2 style=\"font-size: 18px; border-top: 1px solid #e0e0e0; padding-top: 12px;\">{title}</h2>\n\
+                 <p><strong>{}</strong></p>\n<p>{}</p>\n\
+                 <p style=\"color: #6d6e6f; font-size: 13px;\">{}</p>\n",
+                escape_html(&s.headline),
+                escape_html(&s.what_changed),
+                escape_html(&s.stats)
+            ));
+            if !s.highlights.is_empty() {
+                html.push_str(&html_list(&s.highlights));
+            }
+            if let Some(health) = &s.health_assessment {
+                html.push_str(&format!("<p>Health: {}</p>\n", escape_html(health)));
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn html_list(items: &[String]) -> String {
+    let mut html = String::from("<ul>\n");
+    for item in items {
+        html.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+// ── Cache helpers ──────────────────────────────────────────────
+
+async fn get_cached_digest(
+    db: &Database,
+    user_gid: &str,
+    period_key: &str,
+) -> Result<Option<Digest>> {
+    let user_gid = user_gid.to_string();
+    let period_key = period_key.to_string();
+    let json: Option<String> = db
+        .reader()
+        .call(move |conn| {
+            let result = conn
+                .query_row(
+                    "SELECT digest_json FROM fact_digests
+                     WHERE user_gid = ?1 AND period_key = ?2 AND prompt_version = ?3",
+                    rusqlite::params![user_gid, period_key, PROMPT_VERSION],
+                    |row| row.get(0),
+                )
+                .ok();
+            Ok::<Option<String>, rusqlite::Error>(result)
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+}
+
+async fn store_digest(db: &Database, digest: &Digest) -> Result<()> {
+    if db.is_read_only() {
+        return Ok(());
+    }
+    let user_gid = digest.user_gid.clone();
+    let period_key = digest.period_key.clone();
+    let digest_json = serde_json::to_string(digest).unwrap_or_default();
+    let markdown = digest.to_markdown();
+    let html = digest.to_html();
+
+    db.writer()
+        .call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO fact_digests
+                 (user_gid, period_key, digest_json, markdown, html, prompt_version, generated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+                rusqlite::params![
+                    user_gid,
+                    period_key,
+                    digest_json,
+                    markdown,
+                    html,
+                    PROMPT_VERSION
+                ],
+            )?;
+            Ok::<(), rusqlite::Error>(())
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_digest() -> Digest {
+        Digest {
+            user_gid: "u1".to_string(),
+            period_key: "2025-W10".to_string(),
+            headline: "A steady week".to_string(),
+            top_items: vec!["Launch shipped".to_string()],
+            sections: vec![DigestSection {
+                entity_type: "project".to_string(),
+                entity_gid: "p1".to_string(),
+                title: "R&D <Launch>".to_string(),
+                url: Some(generate_asana_url("project", "p1")),
+                headline: "Launch went out".to_string(),
+                what_changed: "The team shipped.".to_string(),
+                highlights: vec!["Beta opened".to_string()],
+                health_assessment: Some("On track".to_string()),
+                stats: "5 completed, 2 created, 4 open (1 overdue)".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_digest_rendering() {
+        let digest = sample_digest();
+        let md = digest.to_markdown();
+        assert!(md.starts_with("# Digest: 2025-W10\n\nA steady week\n"));
+        assert!(md.contains("- Launch shipped\n"));
+        assert!(md.contains("## [R&D <Launch>](https://app.asana.com/"));
+        assert!(md.contains("Health: On track"));
+
+        let html = digest.to_html();
+        assert!(html.contains("R&amp;D &lt;Launch&gt;"));
+        assert!(html.contains("<li>Beta opened</li>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[tokio::test]
+    async fn test_digest_cache_roundtrip() {
+        let db = Database::open_memory().await.unwrap();
+        assert!(get_cached_digest(&db, "u1", "2025-W10")
+            .await
+            .unwrap()
+            .is_none());
+
+        store_digest(&db, &sample_digest()).await.unwrap();
+        let cached = get_cached_digest(&db, "u1", "2025-W10")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached.sections[0].title, "R&D <Launch>");
+
+        let html: String = db
+            .reader()
+            .call(|conn| conn.query_row("SELECT html FROM fact_digests", [], |row| row.get(0)))
+            .await
+            .unwrap();
+        assert!(html.contains("A steady week"));
+    }
+}
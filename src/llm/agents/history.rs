@@ -0,0 +1,275 @@
+//! Every generation of a period summary, and what changed between two.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::{Error, Result};
+use crate::llm::agents::parse_extra;
+use crate::storage::Database;
+
+/// The entity a period summary is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryKind {
+    User,
+    Project,
+    Portfolio,
+    Team,
+}
+
+impl SummaryKind {
+    fn table(self) -> &'static str {
+        match self {
+            SummaryKind::User => "fact_user_period_summaries",
+            SummaryKind::Project => "fact_project_period_summaries",
+            SummaryKind::Portfolio => "fact_portfolio_period_summaries",
+            SummaryKind::Team => "fact_team_period_summaries",
+        }
+    }
+
+    fn gid_column(self) -> &'static str {
+        match self {
+            SummaryKind::User => "user_gid",
+            SummaryKind::Project => "project_gid",
+            SummaryKind::Portfolio => "portfolio_gid",
+            SummaryKind::Team => "team_gid",
+        }
+    }
+
+    /// Accomplishments for users and teams, milestones for projects and
+    /// portfolios.
+    fn items_column(self) -> &'static str {
+        match self {
+            SummaryKind::User | SummaryKind::Team => "key_accomplishments",
+            SummaryKind::Project | SummaryKind::Portfolio => "key_milestones",
+        }
+    }
+
+    /// Collaboration notes for users, the health assessment otherwise.
+    fn notes_column(self) -> &'static str {
+        match self {
+            SummaryKind::User => "collaboration_notes",
+            _ => "health_assessment",
+        }
+    }
+}
+
+/// One stored generation of a period summary.
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryGeneration {
+    /// 1 for the first summary of the period, counting up on each regeneration.
+    pub generation: u32,
+    pub prompt_version: String,
+    pub generated_at: String,
+    pub headline: String,
+    pub what_changed: String,
+    pub why_it_matters: String,
+    /// Key accomplishments or milestones.
+    pub items: Vec<String>,
+    /// Collaboration notes or health assessment.
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+}
+
+/// Every generation of the summary of `gid` for `period_key`, newest first.
+pub async fn summary_history(
+    db: &Database,
+    kind: SummaryKind,
+    gid: &str,
+    period_key: &str,
+) -> Result<Vec<SummaryGeneration>> {
+    let gid = gid.to_string();
+    let period_key = period_key.to_string();
+    let sql = format!(
+        "SELECT generation, prompt_version, generated_at, headline, what_changed, why_it_matters,
+                {items}, {notes}, extra_json
+         FROM {table} WHERE {gid_col} = ?1 AND period_key = ?2
+         ORDER BY generation DESC",
+        items = kind.items_column(),
+        notes = kind.notes_column(),
+        table = kind.table(),
+        gid_col = kind.gid_column(),
+    );
+    db.reader()
+        .call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params![gid, period_key], |row| {
+                let items: String = row.get(6)?;
+                Ok(SummaryGeneration {
+                    generation: row.get(0)?,
+                    prompt_version: row.get(1)?,
+                    generated_at: row.get(2)?,
+                    headline: row.get(3)?,
+                    what_changed: row.get(4)?,
+                    why_it_matters: row.get(5)?,
+                    items: serde_json::from_str(&items).unwrap_or_default(),
+                    notes: row.get(7)?,
+                    extra: parse_extra(row.get(8)?),
+                })
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// A run of text in a diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", content = "text", rename_all = "snake_case")]
+pub enum DiffSegment {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// How one field changed between two generations.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub segments: Vec<DiffSegment>,
+}
+
+/// The fields that differ from `old` to `new`. Text fields are compared word
+/// by word; list items are compared whole, one per line.
+pub fn diff_generations(old: &SummaryGeneration, new: &SummaryGeneration) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    let mut text_field = |field: &str, old: &str, new: &str| {
+        if old != new {
+            diffs.push(FieldDiff {
+                field: field.to_string(),
+                segments: diff_tokens(&words(old), &words(new)),
+            });
+        }
+    };
+    text_field("headline", &old.headline, &new.headline);
+    text_field("what_changed", &old.what_changed, &new.what_changed);
+    text_field("why_it_matters", &old.why_it_matters, &new.why_it_matters);
+    text_field(
+        "notes",
+        old.notes.as_deref().unwrap_or_default(),
+        new.notes.as_deref().unwrap_or_default(),
+    );
+    if old.items != new.items {
+        let lines =
+            |items: &[String]| -> Vec<String> { items.iter().map(|i| format!("{i}\n")).collect() };
+        diffs.push(FieldDiff {
+            field: "items".to_string(),
+            segments: diff_tokens(&lines(&old.items), &lines(&new.items)),
+        });
+    }
+    diffs
+}
+
+/// Split text into words, each keeping its trailing whitespace, so joining
+/// the tokens gives back the text.
+fn words(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if !c.is_whitespace() && current.ends_with(char::is_whitespace) {
+            tokens.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Longest-common-subsequence diff of two token lists, with adjacent tokens
+/// of the same kind merged.
+fn diff_tokens(old: &[String], new: &[String]) -> Vec<DiffSegment> {
+    let key = |t: &String| t.trim_end().to_string();
+    // lcs[i][j]: common tokens between old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if key(&old[i]) == key(&new[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && key(&old[i]) == key(&new[j]) {
+            push_segment(&mut segments, DiffSegment::Same(new[j].clone()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push_segment(&mut segments, DiffSegment::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            push_segment(&mut segments, DiffSegment::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    segments
+}
+
+/// Append `segment`, merging it into the last one when they're the same kind.
+fn push_segment(segments: &mut Vec<DiffSegment>, segment: DiffSegment) {
+    match (segments.last_mut(), &segment) {
+        (Some(DiffSegment::Same(a)), DiffSegment::Same(b))
+        | (Some(DiffSegment::Removed(a)), DiffSegment::Removed(b))
+        | (Some(DiffSegment::Added(a)), DiffSegment::Added(b)) => a.push_str(b),
+        _ => segments.push(segment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_history_and_diff() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO fact_project_period_summaries (project_gid, period_key, generation, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at)
+                     VALUES ('p1', '2025-Q1', 1, 'Beta shipped on time', 'The team shipped the beta.', 'Customers can try it.', '[\"Beta\"]', 'On track', 'period-v2', '2025-03-30 10:00:00'),
+                            ('p1', '2025-Q1', 2, 'Beta shipped late', 'The team shipped the beta.', 'Customers can try it.', '[\"Beta\",\"Pricing page\"]', 'On track', 'period-v2', '2025-04-02 10:00:00'),
+                            ('p1', '2025-Q2', 1, 'Other quarter', '', '', '[]', NULL, 'period-v2', '2025-06-30 10:00:00');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let history = summary_history(&db, SummaryKind::Project, "p1", "2025-Q1")
+            .await
+            .unwrap();
+        assert_eq!(
+            history.iter().map(|g| g.generation).collect::<Vec<_>>(),
+            [2, 1]
+        );
+
+        let diffs = diff_generations(&history[1], &history[0]);
+        assert_eq!(
+            diffs.iter().map(|d| d.field.as_str()).collect::<Vec<_>>(),
+            ["headline", "items"]
+        );
+        assert_eq!(
+            diffs[0].segments,
+            [
+                DiffSegment::Same("Beta shipped ".to_string()),
+                DiffSegment::Removed("on time".to_string()),
+                DiffSegment::Added("late".to_string()),
+            ]
+        );
+        assert_eq!(
+            diffs[1].segments,
+            [
+                DiffSegment::Same("Beta\n".to_string()),
+                DiffSegment::Added("Pricing page\n".to_string()),
+            ]
+        );
+    }
+}
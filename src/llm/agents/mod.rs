@@ -1,2 +1,112 @@
+pub mod ask;
+pub mod digest;
+pub mod history;
+pub mod orphans;
 pub mod period;
+pub mod risks;
 pub mod task;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::date_util::strip_code_fences;
+use crate::error::{Error, Result};
+use crate::llm::{Agent, LlmCall};
+
+/// Times a reply that doesn't parse is sent back to the model, with the
+/// parse error, before giving up.
+pub const MAX_REPAIRS: u32 = 2;
+
+/// Send `prompt` and parse the reply as `T`. A reply that doesn't
+/// deserialize is recorded in `llm_parse_failures` and sent back with the
+/// error for the model to correct, up to [`MAX_REPAIRS`] times.
+pub(crate) async fn run_json<T: DeserializeOwned>(
+    agent: &Agent,
+    call: LlmCall<'_>,
+    prompt: &str,
+) -> Result<T> {
+    let mut request = prompt.to_string();
+    let mut attempt = 0;
+    loop {
+        let response = agent.run(call, &request).await?;
+        let text = response.trim();
+        let error = match parse_reply(text) {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        agent
+            .record_parse_failure(call, attempt, &error, text)
+            .await;
+        if attempt == MAX_REPAIRS {
+            return Err(Error::Llm(format!(
+                "Failed to parse LLM response after {} attempts: {error}\nResponse: {text}",
+                attempt + 1
+            )));
+        }
+        log::debug!(
+            "{} reply didn't parse ({error}); asking for a repair",
+            call.agent
+        );
+        attempt += 1;
+        request = repair_prompt(prompt, text, &error);
+    }
+}
+
+/// Parse a reply, ignoring surrounding whitespace and markdown fences.
+fn parse_reply<T: DeserializeOwned>(text: &str) -> std::result::Result<T, String> {
+    serde_json::from_str(strip_code_fences(text.trim())).map_err(|e| e.to_string())
+}
+
+/// The original prompt followed by the bad reply and why it was rejected.
+/// The whole prompt is resent since agents keep no conversation history.
+fn repair_prompt(prompt: &str, reply: &str, error: &str) -> String {
+    format!(
+        "{prompt}\n\n---\nYour previous reply could not be used:\n\n{reply}\n\n\
+         It failed to parse as the requested JSON: {error}\n\n\
+         Respond again with ONLY the corrected JSON (no markdown, no code fences, no commentary)."
+    )
+}
+
+/// Serialize a summary's extra fields for the `extra_json` column.
+pub(crate) fn extra_json(extra: &Map<String, Value>) -> Option<String> {
+    (!extra.is_empty()).then(|| Value::Object(extra.clone()).to_string())
+}
+
+/// Read an `extra_json` column back, treating bad JSON as no extras.
+pub(crate) fn parse_extra(json: Option<String>) -> Map<String, Value> {
+    json.and_then(|j| serde_json::from_str(&j).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Reply {
+        headline: String,
+        score: i32,
+    }
+
+    #[test]
+    fn test_parse_and_repair_prompt() {
+        let ok: Reply =
+            parse_reply("```json\n{\"headline\": \"Done\", \"score\": 3}\n```").unwrap();
+        assert_eq!(
+            ok,
+            Reply {
+                headline: "Done".to_string(),
+                score: 3
+            }
+        );
+
+        // Valid JSON that doesn't match the schema is rejected too
+        let err = parse_reply::<Reply>(r#"{"headline": "Done", "score": "high"}"#).unwrap_err();
+        assert!(err.contains("invalid type"), "{err}");
+
+        let repair = repair_prompt("Summarize this.", "{\"headline\": 1}", &err);
+        assert!(repair.starts_with("Summarize this."));
+        assert!(repair.contains("{\"headline\": 1}"));
+        assert!(repair.contains(&err));
+    }
+}
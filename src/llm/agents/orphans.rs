@@ -0,0 +1,68 @@
+use crate::error::Result;
+use crate::llm::agents::run_json;
+use crate::llm::{Agent, LlmCall};
+use crate::metrics::orphans::OrphanReport;
+pub use crate::metrics::orphans::OwnerSuggestion;
+
+const PROMPT_VERSION: &str = "orphans-v1";
+
+const CALL: LlmCall = LlmCall {
+    agent: "orphans",
+    prompt_version: PROMPT_VERSION,
+};
+
+/// Ask the LLM to pick the best owner for each orphaned task from its
+/// heuristic candidate list. Tasks without candidates are skipped, and
+/// suggestions naming users outside the candidate list are discarded.
+/// Suggestions are not cached since they depend on current team state.
+pub async fn suggest_owners(agent: &Agent, report: &OrphanReport) -> Result<Vec<OwnerSuggestion>> {
+    let mut parts = Vec::new();
+    for task in report.tasks.iter().filter(|t| !t.candidates.is_empty()) {
+        parts.push(format!("Task {}: {}", task.task_gid, task.name));
+        if let Some(ref section) = task.section_name {
+            parts.push(format!("  Section: {section}"));
+        }
+        if let Some(ref due) = task.due_on {
+            parts.push(format!("  Due: {due}"));
+        }
+        parts.push("  Candidates:".to_string());
+        for c in &task.candidates {
+            parts.push(format!(
+                "    {} ({}): {} comments on this task, {} related completed tasks",
+                c.user_name.as_deref().unwrap_or("unknown"),
+                c.user_gid,
+                c.comments_on_task,
+                c.related_completions
+            ));
+        }
+    }
+
+    if parts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let context = parts.join("\n");
+
+    let prompt = format!(
+        r#"These open Asana tasks have no active owner. For each task, pick the best new owner from its candidate list, based on who has context (commented on it) or has completed similar work.
+
+{context}
+
+Respond with ONLY a JSON array (no markdown, no code fences) in this exact format:
+[
+  {{"task_gid": "...", "user_gid": "...", "rationale": "One sentence explaining the choice"}}
+]
+
+Only use user_gid values from the task's own candidate list."#
+    );
+
+    let suggestions: Vec<OwnerSuggestion> = run_json(agent, CALL, &prompt).await?;
+
+    Ok(suggestions
+        .into_iter()
+        .filter(|s| {
+            report.tasks.iter().any(|t| {
+                t.task_gid == s.task_gid && t.candidates.iter().any(|c| c.user_gid == s.user_gid)
+            })
+        })
+        .collect())
+}
@@ -1,11 +1,102 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::date_util::strip_code_fences;
 use crate::error::{Error, Result};
+use crate::llm::agents::{extra_json, parse_extra, run_json};
+use crate::llm::{prompts, Agent, LlmCall};
 use crate::query::period::Period;
-use crate::storage::Database;
+use crate::storage::{repository, Database};
+use crate::sync::DEFAULT_PORTFOLIO_DEPTH;
 
-const PROMPT_VERSION: &str = "period-v1";
+pub(crate) const PROMPT_VERSION: &str = "period-v3";
+
+/// Built-in user summary prompt.
+pub(crate) const USER_PROMPT: &str = r#"Analyze this user's work during the period {period_key} and provide a structured summary as JSON.
+
+Activity data:
+{context}
+
+Metrics:
+{metrics}
+
+Change from the prior period:
+{trends}
+
+Respond with ONLY a JSON object (no markdown, no code fences):
+{
+  "headline": "One-sentence executive summary",
+  "what_changed": "2-3 sentences describing what the user worked on and accomplished",
+  "why_it_matters": "1-2 sentences on impact and significance",
+  "key_accomplishments": ["list of 2-5 notable accomplishments"],
+  "trend": "1-2 sentences on how this period compares with the prior one, citing the changes above with their numbers (e.g. \"throughput up 30% vs last quarter\"), or null",
+  "collaboration_notes": "Optional note on collaboration patterns, or null"
+}"#;
+
+/// Built-in project summary prompt.
+pub(crate) const PROJECT_PROMPT: &str = r#"Analyze this project's progress during the period {period_key} and provide a structured summary as JSON.
+
+Activity data (tasks marked [MILESTONE] are Asana milestones; lead with them in key_milestones):
+{context}
+
+Metrics:
+{metrics}
+
+Change from the prior period:
+{trends}
+
+Respond with ONLY a JSON object (no markdown, no code fences):
+{
+  "headline": "One-sentence executive summary",
+  "what_changed": "2-3 sentences describing what happened in the project",
+  "why_it_matters": "1-2 sentences on impact and significance",
+  "key_milestones": ["list of 2-5 notable milestones or events"],
+  "trend": "1-2 sentences on how this period compares with the prior one, citing the changes above with their numbers (e.g. \"throughput up 30% vs last quarter\"), or null",
+  "health_assessment": "Brief assessment of project health, or null"
+}"#;
+
+/// Built-in portfolio summary prompt.
+pub(crate) const PORTFOLIO_PROMPT: &str = r#"Analyze this portfolio's progress during the period {period_key} and provide a structured summary as JSON.
+
+Activity data:
+{context}
+
+Metrics:
+{metrics}
+
+Change from the prior period:
+{trends}
+
+Respond with ONLY a JSON object (no markdown, no code fences):
+{
+  "headline": "One-sentence executive summary",
+  "what_changed": "2-3 sentences describing what happened across the portfolio",
+  "why_it_matters": "1-2 sentences on impact and significance",
+  "key_milestones": ["list of 2-5 notable milestones across projects"],
+  "trend": "1-2 sentences on how this period compares with the prior one, citing the changes above with their numbers (e.g. \"throughput up 30% vs last quarter\"), or null",
+  "health_assessment": "Brief assessment of portfolio health, or null"
+}"#;
+
+/// Built-in team summary prompt.
+pub(crate) const TEAM_PROMPT: &str = r#"Analyze this team's work during the period {period_key} and provide a structured summary as JSON.
+
+Activity data:
+{context}
+
+Metrics:
+{metrics}
+
+Change from the prior period:
+{trends}
+
+Respond with ONLY a JSON object (no markdown, no code fences):
+{
+  "headline": "One-sentence executive summary",
+  "what_changed": "2-3 sentences describing what the team accomplished",
+  "why_it_matters": "1-2 sentences on impact and significance",
+  "key_accomplishments": ["list of 2-5 notable team accomplishments"],
+  "trend": "1-2 sentences on how this period compares with the prior one, citing the changes above with their numbers (e.g. \"throughput up 30% vs last quarter\"), or null",
+  "health_assessment": "Brief assessment of team health and workload, or null"
+}"#;
 
 /// Structured period summary for a user.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +106,12 @@ pub struct UserPeriodSummary {
     pub why_it_matters: String,
     pub key_accomplishments: Vec<String>,
     pub collaboration_notes: Option<String>,
+    /// How the period compared with the prior one.
+    #[serde(default)]
+    pub trend: Option<String>,
+    /// Fields an overridden prompt asked for beyond the built-in ones.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Structured period summary for a project.
@@ -25,6 +122,12 @@ pub struct ProjectPeriodSummary {
     pub why_it_matters: String,
     pub key_milestones: Vec<String>,
     pub health_assessment: Option<String>,
+    /// How the period compared with the prior one.
+    #[serde(default)]
+    pub trend: Option<String>,
+    /// Fields an overridden prompt asked for beyond the built-in ones.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Structured period summary for a portfolio.
@@ -35,6 +138,12 @@ pub struct PortfolioPeriodSummary {
     pub why_it_matters: String,
     pub key_milestones: Vec<String>,
     pub health_assessment: Option<String>,
+    /// How the period compared with the prior one.
+    #[serde(default)]
+    pub trend: Option<String>,
+    /// Fields an overridden prompt asked for beyond the built-in ones.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Structured period summary for a team.
@@ -45,65 +154,56 @@ pub struct TeamPeriodSummary {
     pub why_it_matters: String,
     pub key_accomplishments: Vec<String>,
     pub health_assessment: Option<String>,
+    /// How the period compared with the prior one.
+    #[serde(default)]
+    pub trend: Option<String>,
+    /// Fields an overridden prompt asked for beyond the built-in ones.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 // ── User period summary ────────────────────────────────────────
 
 pub async fn summarize_user_period(
     db: &Database,
-    agent: &mixtape_core::Agent,
+    agent: &Agent,
     user_gid: &str,
     period: &Period,
     force: bool,
 ) -> Result<UserPeriodSummary> {
     let period_key = period.to_key();
-
-    if !force {
-        if let Some(cached) = get_cached_user_summary(db, user_gid, &period_key).await? {
+    let prompt = prompts::load_prompt(db, "user_period").await?;
+    let call = LlmCall {
+        agent: "user_period",
+        prompt_version: &prompt.version,
+    };
+
+    if use_cache(period, force) {
+        if let Some(cached) =
+            get_cached_user_summary(db, user_gid, &period_key, &prompt.version).await?
+        {
+            agent.record_cache_hit(call).await;
             return Ok(cached);
         }
     }
 
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
+    let (start_str, end_str) = period.timestamp_bounds();
 
     let context = gather_user_period_context(db, user_gid, &start_str, &end_str).await?;
-    let metrics = crate::metrics::compute_user_metrics(db, user_gid, period).await?;
-    let metrics_json = serde_json::to_string_pretty(&metrics).unwrap_or_default();
-
-    let prompt = format!(
-        r#"Analyze this user's work during the period {period_key} and provide a structured summary as JSON.
+    let comparison = crate::metrics::compare::compare_user_metrics(db, user_gid, period).await?;
+    let metrics_json = serde_json::to_string_pretty(&comparison.current).unwrap_or_default();
+    let trends = comparison.describe_deltas();
 
-Activity data:
-{context}
+    let rendered = prompt.render(&[
+        ("period_key", &period_key),
+        ("context", &context),
+        ("metrics", &metrics_json),
+        ("trends", &trends),
+    ]);
 
-Metrics:
-{metrics_json}
-
-Respond with ONLY a JSON object (no markdown, no code fences):
-{{
-  "headline": "One-sentence executive summary",
-  "what_changed": "2-3 sentences describing what the user worked on and accomplished",
-  "why_it_matters": "1-2 sentences on impact and significance",
-  "key_accomplishments": ["list of 2-5 notable accomplishments"],
-  "collaboration_notes": "Optional note on collaboration patterns, or null"
-}}"#
-    );
+    let summary: UserPeriodSummary = run_json(agent, call, &rendered).await?;
 
-    let response = agent
-        .run(&prompt)
-        .await
-        .map_err(|e| Error::Llm(e.to_string()))?;
-    let text = response.text().trim();
-    let json_str = strip_code_fences(text);
-    let summary: UserPeriodSummary = serde_json::from_str(json_str).map_err(|e| {
-        Error::Llm(format!(
-            "Failed to parse LLM response: {e}\nResponse: {text}"
-        ))
-    })?;
-
-    store_user_summary(db, user_gid, &period_key, &summary).await?;
+    store_user_summary(db, user_gid, &period_key, &prompt.version, &summary).await?;
     Ok(summary)
 }
 
@@ -111,59 +211,48 @@ Respond with ONLY a JSON object (no markdown, no code fences):
 
 pub async fn summarize_project_period(
     db: &Database,
-    agent: &mixtape_core::Agent,
+    agent: &Agent,
     project_gid: &str,
     period: &Period,
     force: bool,
 ) -> Result<ProjectPeriodSummary> {
     let period_key = period.to_key();
-
-    if !force {
-        if let Some(cached) = get_cached_project_summary(db, project_gid, &period_key).await? {
+    let prompt = prompts::load_prompt(db, "project_period").await?;
+    let call = LlmCall {
+        agent: "project_period",
+        prompt_version: &prompt.version,
+    };
+
+    if use_cache(period, force) {
+        if let Some(cached) =
+            get_cached_project_summary(db, project_gid, &period_key, &prompt.version).await?
+        {
+            agent.record_cache_hit(call).await;
             return Ok(cached);
         }
     }
 
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
-
-    let context = gather_project_period_context(db, project_gid, &start_str, &end_str).await?;
-    let metrics = crate::metrics::compute_project_metrics(db, project_gid, period).await?;
-    let metrics_json = serde_json::to_string_pretty(&metrics).unwrap_or_default();
+    let (start_str, end_str) = period.timestamp_bounds();
 
-    let prompt = format!(
-        r#"Analyze this project's progress during the period {period_key} and provide a structured summary as JSON.
+    let mut context = gather_project_period_context(db, project_gid, &start_str, &end_str).await?;
+    let topics =
+        crate::metrics::topics::compute_project_topics(db, project_gid, period, None).await?;
+    context.push_str(&topics.describe());
+    let comparison =
+        crate::metrics::compare::compare_project_metrics(db, project_gid, period).await?;
+    let metrics_json = serde_json::to_string_pretty(&comparison.current).unwrap_or_default();
+    let trends = comparison.describe_deltas();
 
-Activity data:
-{context}
+    let rendered = prompt.render(&[
+        ("period_key", &period_key),
+        ("context", &context),
+        ("metrics", &metrics_json),
+        ("trends", &trends),
+    ]);
 
-Metrics:
-{metrics_json}
+    let summary: ProjectPeriodSummary = run_json(agent, call, &rendered).await?;
 
-Respond with ONLY a JSON object (no markdown, no code fences):
-{{
-  "headline": "One-sentence executive summary",
-  "what_changed": "2-3 sentences describing what happened in the project",
-  "why_it_matters": "1-2 sentences on impact and significance",
-  "key_milestones": ["list of 2-5 notable milestones or events"],
-  "health_assessment": "Brief assessment of project health, or null"
-}}"#
-    );
-
-    let response = agent
-        .run(&prompt)
-        .await
-        .map_err(|e| Error::Llm(e.to_string()))?;
-    let text = response.text().trim();
-    let json_str = strip_code_fences(text);
-    let summary: ProjectPeriodSummary = serde_json::from_str(json_str).map_err(|e| {
-        Error::Llm(format!(
-            "Failed to parse LLM response: {e}\nResponse: {text}"
-        ))
-    })?;
-
-    store_project_summary(db, project_gid, &period_key, &summary).await?;
+    store_project_summary(db, project_gid, &period_key, &prompt.version, &summary).await?;
     Ok(summary)
 }
 
@@ -171,59 +260,45 @@ Respond with ONLY a JSON object (no markdown, no code fences):
 
 pub async fn summarize_portfolio_period(
     db: &Database,
-    agent: &mixtape_core::Agent,
+    agent: &Agent,
     portfolio_gid: &str,
     period: &Period,
     force: bool,
 ) -> Result<PortfolioPeriodSummary> {
     let period_key = period.to_key();
-
-    if !force {
-        if let Some(cached) = get_cached_portfolio_summary(db, portfolio_gid, &period_key).await? {
+    let prompt = prompts::load_prompt(db, "portfolio_period").await?;
+    let call = LlmCall {
+        agent: "portfolio_period",
+        prompt_version: &prompt.version,
+    };
+
+    if use_cache(period, force) {
+        if let Some(cached) =
+            get_cached_portfolio_summary(db, portfolio_gid, &period_key, &prompt.version).await?
+        {
+            agent.record_cache_hit(call).await;
             return Ok(cached);
         }
     }
 
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
+    let (start_str, end_str) = period.timestamp_bounds();
 
     let context = gather_portfolio_period_context(db, portfolio_gid, &start_str, &end_str).await?;
-    let metrics = crate::metrics::compute_portfolio_metrics(db, portfolio_gid, period).await?;
-    let metrics_json = serde_json::to_string_pretty(&metrics).unwrap_or_default();
+    let comparison =
+        crate::metrics::compare::compare_portfolio_metrics(db, portfolio_gid, period).await?;
+    let metrics_json = serde_json::to_string_pretty(&comparison.current).unwrap_or_default();
+    let trends = comparison.describe_deltas();
 
-    let prompt = format!(
-        r#"Analyze this portfolio's progress during the period {period_key} and provide a structured summary as JSON.
+    let rendered = prompt.render(&[
+        ("period_key", &period_key),
+        ("context", &context),
+        ("metrics", &metrics_json),
+        ("trends", &trends),
+    ]);
 
-Activity data:
-{context}
-
-Metrics:
-{metrics_json}
-
-Respond with ONLY a JSON object (no markdown, no code fences):
-{{
-  "headline": "One-sentence executive summary",
-  "what_changed": "2-3 sentences describing what happened across the portfolio",
-  "why_it_matters": "1-2 sentences on impact and significance",
-  "key_milestones": ["list of 2-5 notable milestones across projects"],
-  "health_assessment": "Brief assessment of portfolio health, or null"
-}}"#
-    );
+    let summary: PortfolioPeriodSummary = run_json(agent, call, &rendered).await?;
 
-    let response = agent
-        .run(&prompt)
-        .await
-        .map_err(|e| Error::Llm(e.to_string()))?;
-    let text = response.text().trim();
-    let json_str = strip_code_fences(text);
-    let summary: PortfolioPeriodSummary = serde_json::from_str(json_str).map_err(|e| {
-        Error::Llm(format!(
-            "Failed to parse LLM response: {e}\nResponse: {text}"
-        ))
-    })?;
-
-    store_portfolio_summary(db, portfolio_gid, &period_key, &summary).await?;
+    store_portfolio_summary(db, portfolio_gid, &period_key, &prompt.version, &summary).await?;
     Ok(summary)
 }
 
@@ -231,64 +306,56 @@ Respond with ONLY a JSON object (no markdown, no code fences):
 
 pub async fn summarize_team_period(
     db: &Database,
-    agent: &mixtape_core::Agent,
+    agent: &Agent,
     team_gid: &str,
     period: &Period,
     force: bool,
 ) -> Result<TeamPeriodSummary> {
     let period_key = period.to_key();
-
-    if !force {
-        if let Some(cached) = get_cached_team_summary(db, team_gid, &period_key).await? {
+    let prompt = prompts::load_prompt(db, "team_period").await?;
+    let call = LlmCall {
+        agent: "team_period",
+        prompt_version: &prompt.version,
+    };
+
+    if use_cache(period, force) {
+        if let Some(cached) =
+            get_cached_team_summary(db, team_gid, &period_key, &prompt.version).await?
+        {
+            agent.record_cache_hit(call).await;
             return Ok(cached);
         }
     }
 
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
+    let (start_str, end_str) = period.timestamp_bounds();
 
     let context = gather_team_period_context(db, team_gid, &start_str, &end_str).await?;
-    let metrics = crate::metrics::compute_team_metrics(db, team_gid, period).await?;
-    let metrics_json = serde_json::to_string_pretty(&metrics).unwrap_or_default();
-
-    let prompt = format!(
-        r#"Analyze this team's work during the period {period_key} and provide a structured summary as JSON.
-
-Activity data:
-{context}
+    let comparison = crate::metrics::compare::compare_team_metrics(db, team_gid, period).await?;
+    let metrics_json = serde_json::to_string_pretty(&comparison.current).unwrap_or_default();
+    let trends = comparison.describe_deltas();
 
-Metrics:
-{metrics_json}
+    let rendered = prompt.render(&[
+        ("period_key", &period_key),
+        ("context", &context),
+        ("metrics", &metrics_json),
+        ("trends", &trends),
+    ]);
 
-Respond with ONLY a JSON object (no markdown, no code fences):
-{{
-  "headline": "One-sentence executive summary",
-  "what_changed": "2-3 sentences describing what the team accomplished",
-  "why_it_matters": "1-2 sentences on impact and significance",
-  "key_accomplishments": ["list of 2-5 notable team accomplishments"],
-  "health_assessment": "Brief assessment of team health and workload, or null"
-}}"#
-    );
+    let summary: TeamPeriodSummary = run_json(agent, call, &rendered).await?;
 
-    let response = agent
-        .run(&prompt)
-        .await
-        .map_err(|e| Error::Llm(e.to_string()))?;
-    let text = response.text().trim();
-    let json_str = strip_code_fences(text);
-    let summary: TeamPeriodSummary = serde_json::from_str(json_str).map_err(|e| {
-        Error::Llm(format!(
-            "Failed to parse LLM response: {e}\nResponse: {text}"
-        ))
-    })?;
-
-    store_team_summary(db, team_gid, &period_key, &summary).await?;
+    store_team_summary(db, team_gid, &period_key, &prompt.version, &summary).await?;
     Ok(summary)
 }
 
 // ── Context gathering ──────────────────────────────────────────
 
+/// Whether a stored summary may answer this request. An hour window moves
+/// with the clock, so its summaries are never reused.
+fn use_cache(period: &Period, force: bool) -> bool {
+    !force && !matches!(period, Period::RollingHours(..))
+}
+
+/// `start` and `end` are the period's [`Period::timestamp_bounds`].
 async fn gather_user_period_context(
     db: &Database,
     user_gid: &str,
@@ -315,31 +382,35 @@ async fn gather_user_period_context(
                 name.unwrap_or_else(|| user_gid.clone())
             ));
 
-            // Tasks completed in period
+            // Tasks completed in period, most notable first
             let mut stmt = conn.prepare(
-                "SELECT name, completed_at, days_to_complete FROM fact_tasks
+                "SELECT name, completed_at, days_to_complete, resource_subtype FROM fact_tasks
                  WHERE assignee_gid = ?1 AND is_completed = 1
-                   AND completed_date_key >= ?2 AND completed_date_key <= ?3
-                 ORDER BY completed_at DESC LIMIT 50",
+                   AND completed_at >= ?2 AND completed_at < ?3
+                 ORDER BY COALESCE(notability_score, 0) DESC, completed_at DESC LIMIT 50",
             )?;
-            let completed: Vec<(String, Option<String>, Option<i32>)> = stmt
+            let completed: Vec<(String, Option<String>, Option<i32>, String)> = stmt
                 .query_map(rusqlite::params![user_gid, start, end], |row| {
-                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
                 })?
                 .filter_map(|r| r.ok())
                 .collect();
             if !completed.is_empty() {
                 parts.push(format!("\nCompleted tasks ({}):", completed.len()));
-                for (name, at, days) in &completed {
+                for (name, at, days, subtype) in &completed {
                     let d = days.map(|d| format!(" ({d}d)")).unwrap_or_default();
-                    parts.push(format!("  - {name} [{}]{d}", at.as_deref().unwrap_or("?")));
+                    parts.push(format!(
+                        "  - {name}{} [{}]{d}",
+                        subtype_tag(subtype),
+                        at.as_deref().unwrap_or("?")
+                    ));
                 }
             }
 
             // Tasks created in period
             let mut stmt = conn.prepare(
                 "SELECT name, due_on FROM fact_tasks
-                 WHERE assignee_gid = ?1 AND created_date_key >= ?2 AND created_date_key <= ?3
+                 WHERE assignee_gid = ?1 AND created_at >= ?2 AND created_at < ?3
                  ORDER BY created_at DESC LIMIT 30",
             )?;
             let created: Vec<(String, Option<String>)> = stmt
@@ -362,6 +433,15 @@ async fn gather_user_period_context(
         .map_err(|e| Error::Database(e.to_string()))
 }
 
+/// Marker that makes milestones and approvals stand out in summary context.
+fn subtype_tag(subtype: &str) -> &'static str {
+    match subtype {
+        "milestone" => " [MILESTONE]",
+        "approval" => " [APPROVAL]",
+        _ => "",
+    }
+}
+
 async fn gather_project_period_context(
     db: &Database,
     project_gid: &str,
@@ -387,27 +467,28 @@ async fn gather_project_period_context(
                 name.unwrap_or_else(|| project_gid.clone())
             ));
 
-            // Completed tasks
+            // Completed tasks, most notable first
             let mut stmt = conn.prepare(
-                "SELECT t.name, u.name, t.completed_at FROM fact_tasks t
+                "SELECT t.name, u.name, t.completed_at, t.resource_subtype FROM fact_tasks t
                  JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
                  LEFT JOIN dim_users u ON u.user_gid = t.assignee_gid
                  WHERE btp.project_gid = ?1 AND t.is_completed = 1
-                   AND t.completed_date_key >= ?2 AND t.completed_date_key <= ?3
-                 ORDER BY t.completed_at DESC LIMIT 50",
+                   AND t.completed_at >= ?2 AND t.completed_at < ?3
+                 ORDER BY COALESCE(t.notability_score, 0) DESC, t.completed_at DESC LIMIT 50",
             )?;
-            let completed: Vec<(String, Option<String>, Option<String>)> = stmt
+            let completed: Vec<(String, Option<String>, Option<String>, String)> = stmt
                 .query_map(rusqlite::params![project_gid, start, end], |row| {
-                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
                 })?
                 .filter_map(|r| r.ok())
                 .collect();
             if !completed.is_empty() {
                 parts.push(format!("\nCompleted tasks ({}):", completed.len()));
-                for (name, assignee, at) in &completed {
+                for (name, assignee, at, subtype) in &completed {
                     let a = assignee.as_deref().unwrap_or("unassigned");
                     parts.push(format!(
-                        "  - {name} ({a}) [{}]",
+                        "  - {name}{} ({a}) [{}]",
+                        subtype_tag(subtype),
                         at.as_deref().unwrap_or("?")
                     ));
                 }
@@ -415,25 +496,35 @@ async fn gather_project_period_context(
 
             // Open tasks
             let mut stmt = conn.prepare(
-                "SELECT t.name, u.name, t.due_on, t.is_overdue FROM fact_tasks t
+                "SELECT t.name, u.name, t.due_on, t.is_overdue, t.resource_subtype FROM fact_tasks t
                  JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
                  LEFT JOIN dim_users u ON u.user_gid = t.assignee_gid
                  WHERE btp.project_gid = ?1 AND t.is_completed = 0
                  ORDER BY t.due_on ASC LIMIT 30",
             )?;
-            let open: Vec<(String, Option<String>, Option<String>, i32)> = stmt
+            #[allow(clippy::type_complexity)]
+            let open: Vec<(String, Option<String>, Option<String>, i32, String)> = stmt
                 .query_map([&project_gid], |row| {
-                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
                 })?
                 .filter_map(|r| r.ok())
                 .collect();
             if !open.is_empty() {
                 parts.push(format!("\nOpen tasks ({}):", open.len()));
-                for (name, assignee, due, overdue) in &open {
+                for (name, assignee, due, overdue, subtype) in &open {
                     let a = assignee.as_deref().unwrap_or("unassigned");
                     let d = due.as_deref().unwrap_or("no due date");
                     let flag = if *overdue != 0 { " [OVERDUE]" } else { "" };
-                    parts.push(format!("  - {name} ({a}) due: {d}{flag}"));
+                    parts.push(format!(
+                        "  - {name}{} ({a}) due: {d}{flag}",
+                        subtype_tag(subtype)
+                    ));
                 }
             }
 
@@ -464,14 +555,16 @@ async fn gather_portfolio_period_context(
             // List projects with summary stats
             let mut stmt = conn.prepare(
                 "SELECT p.project_gid, p.name,
-                        (SELECT COUNT(*) FROM fact_tasks t JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid WHERE btp.project_gid = p.project_gid AND t.is_completed = 1 AND t.completed_date_key >= ?2 AND t.completed_date_key <= ?3),
+                        (SELECT COUNT(*) FROM fact_tasks t JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid WHERE btp.project_gid = p.project_gid AND t.is_completed = 1 AND t.completed_at >= ?2 AND t.completed_at < ?3),
                         (SELECT COUNT(*) FROM fact_tasks t JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid WHERE btp.project_gid = p.project_gid AND t.is_completed = 0)
-                 FROM bridge_portfolio_projects bpp
-                 JOIN dim_projects p ON p.project_gid = bpp.project_gid
-                 WHERE bpp.portfolio_gid = ?1"
+                 FROM dim_projects p
+                 WHERE p.project_gid IN (SELECT value FROM json_each(?1))"
             )?;
+            // Include projects of nested portfolios, as the metrics do
+            let project_gids = repository::portfolio_project_gids(conn, &portfolio_gid, DEFAULT_PORTFOLIO_DEPTH)?;
+            let project_gids = serde_json::to_string(&project_gids).unwrap_or_default();
             let projects: Vec<(String, String, i64, i64)> = stmt
-                .query_map(rusqlite::params![portfolio_gid, start, end], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+                .query_map(rusqlite::params![project_gids, start, end], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
                 .filter_map(|r| r.ok())
                 .collect();
             if !projects.is_empty() {
@@ -508,7 +601,7 @@ async fn gather_team_period_context(
             // Members with task counts
             let mut stmt = conn.prepare(
                 "SELECT u.name, u.user_gid,
-                        (SELECT COUNT(*) FROM fact_tasks t WHERE t.assignee_gid = u.user_gid AND t.is_completed = 1 AND t.completed_date_key >= ?2 AND t.completed_date_key <= ?3),
+                        (SELECT COUNT(*) FROM fact_tasks t WHERE t.assignee_gid = u.user_gid AND t.is_completed = 1 AND t.completed_at >= ?2 AND t.completed_at < ?3),
                         (SELECT COUNT(*) FROM fact_tasks t WHERE t.assignee_gid = u.user_gid AND t.is_completed = 0)
                  FROM bridge_team_members btm
                  JOIN dim_users u ON u.user_gid = btm.user_gid
@@ -538,16 +631,19 @@ async fn get_cached_user_summary(
     db: &Database,
     user_gid: &str,
     period_key: &str,
+    prompt_version: &str,
 ) -> Result<Option<UserPeriodSummary>> {
     let user_gid = user_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
     db.reader()
         .call(move |conn| {
             let result = conn
                 .query_row(
-                    "SELECT headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes
-                     FROM fact_user_period_summaries WHERE user_gid = ?1 AND period_key = ?2 AND prompt_version = ?3",
-                    rusqlite::params![user_gid, period_key, PROMPT_VERSION],
+                    "SELECT headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, extra_json, trend
+                     FROM fact_user_period_summaries WHERE user_gid = ?1 AND period_key = ?2 AND prompt_version = ?3
+                     ORDER BY generation DESC LIMIT 1",
+                    rusqlite::params![user_gid, period_key, prompt_version],
                     |row| {
                         let accomplishments_str: String = row.get(3)?;
                         Ok(UserPeriodSummary {
@@ -556,6 +652,8 @@ async fn get_cached_user_summary(
                             why_it_matters: row.get(2)?,
                             key_accomplishments: serde_json::from_str(&accomplishments_str).unwrap_or_default(),
                             collaboration_notes: row.get(4)?,
+                            trend: row.get(6)?,
+                            extra: parse_extra(row.get(5)?),
                         })
                     },
                 )
@@ -570,24 +668,33 @@ async fn store_user_summary(
     db: &Database,
     user_gid: &str,
     period_key: &str,
+    prompt_version: &str,
     summary: &UserPeriodSummary,
 ) -> Result<()> {
+    if db.is_read_only() {
+        return Ok(());
+    }
     let user_gid = user_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
+    let extra_json = extra_json(&summary.extra);
     let headline = summary.headline.clone();
     let what_changed = summary.what_changed.clone();
     let why_it_matters = summary.why_it_matters.clone();
     let key_accomplishments =
         serde_json::to_string(&summary.key_accomplishments).unwrap_or_default();
     let collaboration_notes = summary.collaboration_notes.clone();
+    let trend = summary.trend.clone();
 
     db.writer()
         .call(move |conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO fact_user_period_summaries
-                 (user_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, prompt_version, generated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
-                rusqlite::params![user_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, PROMPT_VERSION],
+                "INSERT INTO fact_user_period_summaries
+                 (user_gid, period_key, generation, headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, extra_json, trend, prompt_version, generated_at)
+                 VALUES (?1, ?2, (SELECT COALESCE(MAX(generation), 0) + 1 FROM fact_user_period_summaries
+                                 WHERE user_gid = ?1 AND period_key = ?2),
+                         ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+                rusqlite::params![user_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, extra_json, trend, prompt_version],
             )?;
             Ok::<(), rusqlite::Error>(())
         })
@@ -599,16 +706,19 @@ async fn get_cached_project_summary(
     db: &Database,
     project_gid: &str,
     period_key: &str,
+    prompt_version: &str,
 ) -> Result<Option<ProjectPeriodSummary>> {
     let project_gid = project_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
     db.reader()
         .call(move |conn| {
             let result = conn
                 .query_row(
-                    "SELECT headline, what_changed, why_it_matters, key_milestones, health_assessment
-                     FROM fact_project_period_summaries WHERE project_gid = ?1 AND period_key = ?2 AND prompt_version = ?3",
-                    rusqlite::params![project_gid, period_key, PROMPT_VERSION],
+                    "SELECT headline, what_changed, why_it_matters, key_milestones, health_assessment, extra_json, trend
+                     FROM fact_project_period_summaries WHERE project_gid = ?1 AND period_key = ?2 AND prompt_version = ?3
+                     ORDER BY generation DESC LIMIT 1",
+                    rusqlite::params![project_gid, period_key, prompt_version],
                     |row| {
                         let milestones_str: String = row.get(3)?;
                         Ok(ProjectPeriodSummary {
@@ -617,6 +727,8 @@ async fn get_cached_project_summary(
                             why_it_matters: row.get(2)?,
                             key_milestones: serde_json::from_str(&milestones_str).unwrap_or_default(),
                             health_assessment: row.get(4)?,
+                            trend: row.get(6)?,
+                            extra: parse_extra(row.get(5)?),
                         })
                     },
                 )
@@ -631,23 +743,32 @@ async fn store_project_summary(
     db: &Database,
     project_gid: &str,
     period_key: &str,
+    prompt_version: &str,
     summary: &ProjectPeriodSummary,
 ) -> Result<()> {
+    if db.is_read_only() {
+        return Ok(());
+    }
     let project_gid = project_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
+    let extra_json = extra_json(&summary.extra);
     let headline = summary.headline.clone();
     let what_changed = summary.what_changed.clone();
     let why_it_matters = summary.why_it_matters.clone();
     let key_milestones = serde_json::to_string(&summary.key_milestones).unwrap_or_default();
     let health_assessment = summary.health_assessment.clone();
+    let trend = summary.trend.clone();
 
     db.writer()
         .call(move |conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO fact_project_period_summaries
-                 (project_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
-                rusqlite::params![project_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, PROMPT_VERSION],
+                "INSERT INTO fact_project_period_summaries
+                 (project_gid, period_key, generation, headline, what_changed, why_it_matters, key_milestones, health_assessment, extra_json, trend, prompt_version, generated_at)
+                 VALUES (?1, ?2, (SELECT COALESCE(MAX(generation), 0) + 1 FROM fact_project_period_summaries
+                                 WHERE project_gid = ?1 AND period_key = ?2),
+                         ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+                rusqlite::params![project_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, extra_json, trend, prompt_version],
             )?;
             Ok::<(), rusqlite::Error>(())
         })
@@ -659,16 +780,19 @@ async fn get_cached_portfolio_summary(
     db: &Database,
     portfolio_gid: &str,
     period_key: &str,
+    prompt_version: &str,
 ) -> Result<Option<PortfolioPeriodSummary>> {
     let portfolio_gid = portfolio_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
     db.reader()
         .call(move |conn| {
             let result = conn
                 .query_row(
-                    "SELECT headline, what_changed, why_it_matters, key_milestones, health_assessment
-                     FROM fact_portfolio_period_summaries WHERE portfolio_gid = ?1 AND period_key = ?2 AND prompt_version = ?3",
-                    rusqlite::params![portfolio_gid, period_key, PROMPT_VERSION],
+                    "SELECT headline, what_changed, why_it_matters, key_milestones, health_assessment, extra_json, trend
+                     FROM fact_portfolio_period_summaries WHERE portfolio_gid = ?1 AND period_key = ?2 AND prompt_version = ?3
+                     ORDER BY generation DESC LIMIT 1",
+                    rusqlite::params![portfolio_gid, period_key, prompt_version],
                     |row| {
                         let milestones_str: String = row.get(3)?;
                         Ok(PortfolioPeriodSummary {
@@ -677,6 +801,8 @@ async fn get_cached_portfolio_summary(
                             why_it_matters: row.get(2)?,
                             key_milestones: serde_json::from_str(&milestones_str).unwrap_or_default(),
                             health_assessment: row.get(4)?,
+                            trend: row.get(6)?,
+                            extra: parse_extra(row.get(5)?),
                         })
                     },
                 )
@@ -691,23 +817,32 @@ async fn store_portfolio_summary(
     db: &Database,
     portfolio_gid: &str,
     period_key: &str,
+    prompt_version: &str,
     summary: &PortfolioPeriodSummary,
 ) -> Result<()> {
+    if db.is_read_only() {
+        return Ok(());
+    }
     let portfolio_gid = portfolio_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
+    let extra_json = extra_json(&summary.extra);
     let headline = summary.headline.clone();
     let what_changed = summary.what_changed.clone();
     let why_it_matters = summary.why_it_matters.clone();
     let key_milestones = serde_json::to_string(&summary.key_milestones).unwrap_or_default();
     let health_assessment = summary.health_assessment.clone();
+    let trend = summary.trend.clone();
 
     db.writer()
         .call(move |conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO fact_portfolio_period_summaries
-                 (portfolio_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
-                rusqlite::params![portfolio_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, PROMPT_VERSION],
+                "INSERT INTO fact_portfolio_period_summaries
+                 (portfolio_gid, period_key, generation, headline, what_changed, why_it_matters, key_milestones, health_assessment, extra_json, trend, prompt_version, generated_at)
+                 VALUES (?1, ?2, (SELECT COALESCE(MAX(generation), 0) + 1 FROM fact_portfolio_period_summaries
+                                 WHERE portfolio_gid = ?1 AND period_key = ?2),
+                         ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+                rusqlite::params![portfolio_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, extra_json, trend, prompt_version],
             )?;
             Ok::<(), rusqlite::Error>(())
         })
@@ -719,16 +854,19 @@ async fn get_cached_team_summary(
     db: &Database,
     team_gid: &str,
     period_key: &str,
+    prompt_version: &str,
 ) -> Result<Option<TeamPeriodSummary>> {
     let team_gid = team_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
     db.reader()
         .call(move |conn| {
             let result = conn
                 .query_row(
-                    "SELECT headline, what_changed, why_it_matters, key_accomplishments, health_assessment
-                     FROM fact_team_period_summaries WHERE team_gid = ?1 AND period_key = ?2 AND prompt_version = ?3",
-                    rusqlite::params![team_gid, period_key, PROMPT_VERSION],
+                    "SELECT headline, what_changed, why_it_matters, key_accomplishments, health_assessment, extra_json, trend
+                     FROM fact_team_period_summaries WHERE team_gid = ?1 AND period_key = ?2 AND prompt_version = ?3
+                     ORDER BY generation DESC LIMIT 1",
+                    rusqlite::params![team_gid, period_key, prompt_version],
                     |row| {
                         let accomplishments_str: String = row.get(3)?;
                         Ok(TeamPeriodSummary {
@@ -737,6 +875,8 @@ async fn get_cached_team_summary(
                             why_it_matters: row.get(2)?,
                             key_accomplishments: serde_json::from_str(&accomplishments_str).unwrap_or_default(),
                             health_assessment: row.get(4)?,
+                            trend: row.get(6)?,
+                            extra: parse_extra(row.get(5)?),
                         })
                     },
                 )
@@ -751,24 +891,33 @@ async fn store_team_summary(
     db: &Database,
     team_gid: &str,
     period_key: &str,
+    prompt_version: &str,
     summary: &TeamPeriodSummary,
 ) -> Result<()> {
+    if db.is_read_only() {
+        return Ok(());
+    }
     let team_gid = team_gid.to_string();
     let period_key = period_key.to_string();
+    let prompt_version = prompt_version.to_string();
+    let extra_json = extra_json(&summary.extra);
     let headline = summary.headline.clone();
     let what_changed = summary.what_changed.clone();
     let why_it_matters = summary.why_it_matters.clone();
     let key_accomplishments =
         serde_json::to_string(&summary.key_accomplishments).unwrap_or_default();
     let health_assessment = summary.health_assessment.clone();
+    let trend = summary.trend.clone();
 
     db.writer()
         .call(move |conn| {
             conn.execute(
-                "INSERT OR REPLACE INTO fact_team_period_summaries
-                 (team_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, health_assessment, prompt_version, generated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
-                rusqlite::params![team_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, health_assessment, PROMPT_VERSION],
+                "INSERT INTO fact_team_period_summaries
+                 (team_gid, period_key, generation, headline, what_changed, why_it_matters, key_accomplishments, health_assessment, extra_json, trend, prompt_version, generated_at)
+                 VALUES (?1, ?2, (SELECT COALESCE(MAX(generation), 0) + 1 FROM fact_team_period_summaries
+                                 WHERE team_gid = ?1 AND period_key = ?2),
+                         ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+                rusqlite::params![team_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, health_assessment, extra_json, trend, prompt_version],
             )?;
             Ok::<(), rusqlite::Error>(())
         })
@@ -0,0 +1,489 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::llm::agents::run_json;
+use crate::llm::{Agent, LlmCall};
+use crate::query::period::Period;
+use crate::storage::threads::{load_comment_threads, render_threads};
+use crate::storage::Database;
+use crate::url::generate_asana_url;
+
+const PROMPT_VERSION: &str = "risks-v1";
+
+const CALL: LlmCall = LlmCall {
+    agent: "risks",
+    prompt_version: PROMPT_VERSION,
+};
+
+/// Sources sent to the LLM per request.
+const BATCH_SIZE: usize = 20;
+
+/// Most recent comment lines of a thread included in the prompt.
+const MAX_THREAD_LINES: usize = 20;
+
+/// How serious a flagged risk is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskSeverity {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Some(RiskSeverity::Low),
+            "medium" | "med" => Some(RiskSeverity::Medium),
+            "high" => Some(RiskSeverity::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskSeverity::Low => "low",
+            RiskSeverity::Medium => "medium",
+            RiskSeverity::High => "high",
+        }
+    }
+}
+
+/// A risk or blocker the LLM found in a status update or comment thread.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskFlag {
+    /// `status_update` or `comment_thread`.
+    pub source_type: String,
+    /// Status update GID, or the task GID for a comment thread.
+    pub source_gid: String,
+    /// `project`, `portfolio`, or `task`.
+    pub entity_type: String,
+    pub entity_gid: String,
+    pub entity_name: Option<String>,
+    pub entity_url: String,
+    pub severity: RiskSeverity,
+    pub summary: String,
+    /// Short quote from the source backing the flag.
+    pub evidence: String,
+    pub task_gids: Vec<String>,
+    pub task_urls: Vec<String>,
+    /// Date of the status update or of the thread's latest comment.
+    pub source_date_key: String,
+    pub detected_at: String,
+}
+
+/// Outcome of a risk scan.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RiskScanReport {
+    pub sources_scanned: u64,
+    pub flags_found: u64,
+}
+
+/// A status update or comment thread to scan.
+#[derive(Debug, Clone)]
+struct RiskSource {
+    source_type: &'static str,
+    source_gid: String,
+    entity_type: String,
+    entity_gid: String,
+    entity_name: Option<String>,
+    /// Timestamp of the newest content, compared against earlier scans.
+    activity_at: String,
+    source_date_key: String,
+    text: String,
+}
+
+impl RiskSource {
+    /// Identifier the LLM uses to refer back to this source.
+    fn key(&self) -> String {
+        let prefix = if self.source_type == "status_update" {
+            "status"
+        } else {
+            "task"
+        };
+        format!("{prefix}:{}", self.source_gid)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFlag {
+    source: String,
+    severity: String,
+    summary: String,
+    #[serde(default)]
+    evidence: String,
+    #[serde(default)]
+    task_gids: Vec<String>,
+}
+
+/// Scan status updates and comment threads from `period` for risk and
+/// blocker language, storing flags in `fact_risk_flags`. Sources scanned
+/// before are skipped unless they have new content or `force` is set;
+/// rescanning a source replaces its flags. Fails with [`Error::ReadOnly`]
+/// on a read-only database.
+pub async fn scan_risks(
+    db: &Database,
+    agent: &Agent,
+    period: &Period,
+    force: bool,
+) -> Result<RiskScanReport> {
+    if db.is_read_only() {
+        return Err(Error::ReadOnly("risk scans".to_string()));
+    }
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+
+    let sources = db
+        .reader()
+        .call(move |conn| gather_sources(conn, &start_str, &end_str, force))
+        .await?;
+
+    let mut report = RiskScanReport::default();
+    for batch in sources.chunks(BATCH_SIZE) {
+        let flags = detect_risks(agent, batch).await?;
+        report.sources_scanned += batch.len() as u64;
+        report.flags_found += flags.len() as u64;
+        store_flags(db, batch.to_vec(), flags).await?;
+    }
+    Ok(report)
+}
+
+async fn detect_risks(agent: &Agent, batch: &[RiskSource]) -> Result<Vec<(usize, RawFlag)>> {
+    let context: Vec<String> = batch
+        .iter()
+        .map(|s| {
+            let what = match s.source_type {
+                "status_update" => format!("status update on {}", s.entity_type),
+                _ => "comments on task".to_string(),
+            };
+            format!(
+                "=== {} ({what} \"{}\", {}) ===\n{}",
+                s.key(),
+                s.entity_name.as_deref().unwrap_or("unnamed"),
+                s.source_date_key,
+                s.text
+            )
+        })
+        .collect();
+    let context = context.join("\n\n");
+
+    let prompt = format!(
+        r#"Scan these Asana status updates and comment threads for risks: blockers, slipping dates, missing owners or decisions, dependency problems, scope creep, or people raising concerns. Ignore routine progress and issues that were resolved.
+
+{context}
+
+Respond with ONLY a JSON array (no markdown, no code fences), one object per risk, or [] if there are none:
+[
+  {{"source": "status:123 or task:456, from the === header", "severity": "low, medium, or high", "summary": "One sentence describing the risk", "evidence": "Short exact quote from the source", "task_gids": ["GIDs of other tasks involved, if named"]}}
+]"#
+    );
+
+    let raw: Vec<RawFlag> = run_json(agent, CALL, &prompt).await?;
+    Ok(accept_flags(batch, raw))
+}
+
+/// Pair each flag with the index of its source in `batch`, dropping flags
+/// that name a source outside the batch.
+fn accept_flags(batch: &[RiskSource], raw: Vec<RawFlag>) -> Vec<(usize, RawFlag)> {
+    raw.into_iter()
+        .filter_map(|flag| {
+            let source = flag.source.trim();
+            let idx = batch.iter().position(|s| s.key() == source)?;
+            Some((idx, flag))
+        })
+        .collect()
+}
+
+fn gather_sources(
+    conn: &rusqlite::Connection,
+    start: &str,
+    end: &str,
+    force: bool,
+) -> std::result::Result<Vec<RiskSource>, rusqlite::Error> {
+    let mut sources = Vec::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT s.status_gid, s.parent_type, s.parent_gid, COALESCE(p.name, pf.name),
+                s.status_type, s.title, COALESCE(s.text, ''), s.created_at, s.created_date_key
+         FROM fact_status_updates s
+         LEFT JOIN dim_projects p ON s.parent_type = 'project' AND p.project_gid = s.parent_gid
+         LEFT JOIN dim_portfolios pf ON s.parent_type = 'portfolio' AND pf.portfolio_gid = s.parent_gid
+         WHERE s.created_date_key >= ?1 AND s.created_date_key <= ?2
+           AND (?4 OR NOT EXISTS (
+               SELECT 1 FROM risk_scans r
+               WHERE r.source_type = 'status_update' AND r.source_gid = s.status_gid
+                 AND r.prompt_version = ?3 AND r.activity_at >= s.created_at))
+         ORDER BY s.created_at",
+    )?;
+    let rows = stmt.query_map(
+        rusqlite::params![start, end, PROMPT_VERSION, force],
+        |row| {
+            let status_type: String = row.get(4)?;
+            let title: String = row.get(5)?;
+            let text: String = row.get(6)?;
+            Ok(RiskSource {
+                source_type: "status_update",
+                source_gid: row.get(0)?,
+                entity_type: row.get(1)?,
+                entity_gid: row.get(2)?,
+                entity_name: row.get(3)?,
+                activity_at: row.get(7)?,
+                source_date_key: row.get(8)?,
+                text: format!(
+                    "[{status_type}] {title}\n{}",
+                    text.chars().take(2000).collect::<String>()
+                ),
+            })
+        },
+    )?;
+    for row in rows {
+        sources.push(row?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT task_gid, name, last_at, last_date_key FROM (
+             SELECT t.task_gid, t.name, MAX(c.created_at) AS last_at,
+                    MAX(c.created_date_key) AS last_date_key
+             FROM fact_comments c
+             JOIN fact_tasks t ON t.task_gid = c.task_gid
+             WHERE c.story_type = 'comment'
+               AND c.created_date_key >= ?1 AND c.created_date_key <= ?2
+             GROUP BY t.task_gid
+         ) threads
+         WHERE ?4 OR NOT EXISTS (
+             SELECT 1 FROM risk_scans r
+             WHERE r.source_type = 'comment_thread' AND r.source_gid = threads.task_gid
+               AND r.prompt_version = ?3 AND r.activity_at >= threads.last_at)
+         ORDER BY last_at",
+    )?;
+    let threads: Vec<(String, String, String, String)> = stmt
+        .query_map(
+            rusqlite::params![start, end, PROMPT_VERSION, force],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?
+        .collect::<std::result::Result<_, _>>()?;
+    for (task_gid, name, last_at, last_date_key) in threads {
+        let lines = render_threads(&load_comment_threads(conn, &task_gid)?, 300);
+        let recent = &lines[lines.len().saturating_sub(MAX_THREAD_LINES)..];
+        sources.push(RiskSource {
+            source_type: "comment_thread",
+            source_gid: task_gid.clone(),
+            entity_type: "task".to_string(),
+            entity_gid: task_gid,
+            entity_name: Some(name),
+            activity_at: last_at,
+            source_date_key: last_date_key,
+            text: recent.join("\n"),
+        });
+    }
+    Ok(sources)
+}
+
+/// Replace the flags of every source in `batch` and mark them scanned.
+/// Task GIDs not in the warehouse are dropped; a thread's own task is
+/// always linked first.
+async fn store_flags(
+    db: &Database,
+    batch: Vec<RiskSource>,
+    flags: Vec<(usize, RawFlag)>,
+) -> Result<()> {
+    db.writer()
+        .call(move |conn| {
+            let tx = conn.transaction()?;
+            for s in &batch {
+                tx.execute(
+                    "DELETE FROM fact_risk_flags WHERE source_type = ?1 AND source_gid = ?2",
+                    rusqlite::params![s.source_type, s.source_gid],
+                )?;
+                tx.execute(
+                    "INSERT OR REPLACE INTO risk_scans
+                     (source_type, source_gid, activity_at, prompt_version, scanned_at)
+                     VALUES (?1, ?2, ?3, ?4, datetime('now'))",
+                    rusqlite::params![s.source_type, s.source_gid, s.activity_at, PROMPT_VERSION],
+                )?;
+            }
+            for (idx, flag) in flags {
+                let s = &batch[idx];
+                let mut task_gids: Vec<String> = Vec::new();
+                if s.entity_type == "task" {
+                    task_gids.push(s.entity_gid.clone());
+                }
+                for gid in flag.task_gids {
+                    let known: bool = tx.query_row(
+                        "SELECT EXISTS(SELECT 1 FROM fact_tasks WHERE task_gid = ?1)",
+                        [&gid],
+                        |row| row.get(0),
+                    )?;
+                    if known && !task_gids.contains(&gid) {
+                        task_gids.push(gid);
+                    }
+                }
+                let severity = RiskSeverity::parse(&flag.severity).unwrap_or(RiskSeverity::Medium);
+                let evidence: String = flag.evidence.chars().take(300).collect();
+                tx.execute(
+                    "INSERT INTO fact_risk_flags
+                     (source_type, source_gid, entity_type, entity_gid, entity_name, severity,
+                      summary, evidence, task_gids, source_date_key, prompt_version, detected_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, datetime('now'))",
+                    rusqlite::params![
+                        s.source_type,
+                        s.source_gid,
+                        s.entity_type,
+                        s.entity_gid,
+                        s.entity_name,
+                        severity.as_str(),
+                        flag.summary,
+                        evidence,
+                        serde_json::to_string(&task_gids).unwrap_or_default(),
+                        s.source_date_key,
+                        PROMPT_VERSION
+                    ],
+                )?;
+            }
+            tx.commit()?;
+            Ok::<(), rusqlite::Error>(())
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Stored risk flags from sources dated within `period`, most severe and
+/// most recent first.
+pub async fn list_risks(
+    db: &Database,
+    period: &Period,
+    min_severity: Option<RiskSeverity>,
+) -> Result<Vec<RiskFlag>> {
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+
+    let flags = db
+        .reader()
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT source_type, source_gid, entity_type, entity_gid, entity_name, severity,
+                        summary, evidence, task_gids, source_date_key, detected_at
+                 FROM fact_risk_flags
+                 WHERE source_date_key >= ?1 AND source_date_key <= ?2
+                 ORDER BY source_date_key DESC, id",
+            )?;
+            let flags = stmt
+                .query_map([&start_str, &end_str], |row| {
+                    let entity_type: String = row.get(2)?;
+                    let entity_gid: String = row.get(3)?;
+                    let severity: String = row.get(5)?;
+                    let task_gids: String = row.get(8)?;
+                    let task_gids: Vec<String> =
+                        serde_json::from_str(&task_gids).unwrap_or_default();
+                    Ok(RiskFlag {
+                        source_type: row.get(0)?,
+                        source_gid: row.get(1)?,
+                        entity_url: generate_asana_url(&entity_type, &entity_gid),
+                        entity_type,
+                        entity_gid,
+                        entity_name: row.get(4)?,
+                        severity: RiskSeverity::parse(&severity).unwrap_or(RiskSeverity::Medium),
+                        summary: row.get(6)?,
+                        evidence: row.get(7)?,
+                        task_urls: task_gids
+                            .iter()
+                            .map(|gid| generate_asana_url("task", gid))
+                            .collect(),
+                        task_gids,
+                        source_date_key: row.get(9)?,
+                        detected_at: row.get(10)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok::<Vec<RiskFlag>, rusqlite::Error>(flags)
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    let mut flags: Vec<RiskFlag> = flags
+        .into_iter()
+        .filter(|f| min_severity.is_none_or(|min| f.severity >= min))
+        .collect();
+    // Stable, so dates stay newest first within a severity
+    flags.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    Ok(flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(source: &str, severity: &str, task_gids: &[&str]) -> RawFlag {
+        RawFlag {
+            source: source.to_string(),
+            severity: severity.to_string(),
+            summary: format!("Risk in {source}"),
+            evidence: "we're blocked".to_string(),
+            task_gids: task_gids.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_sources_store_and_list() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'w1', datetime('now'));
+                     INSERT INTO fact_status_updates (status_gid, parent_gid, parent_type, title, text, status_type, created_at, created_date_key, cached_at)
+                     VALUES ('s1', 'p1', 'project', 'Week 2', 'Vendor is late', 'at_risk', '2025-01-06T10:00:00Z', '2025-01-06', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at) VALUES
+                        ('t1', 'Sign contract', '2025-01-01', '2025-01-01', datetime('now')),
+                        ('t2', 'Legal review', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'Still waiting on legal', 'comment', '2025-01-07T09:00:00Z', '2025-01-07', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let gather = |force| {
+            db.reader()
+                .call(move |conn| gather_sources(conn, "2025-01-01", "2025-01-31", force))
+        };
+        let sources = gather(false).await.unwrap();
+        let keys: Vec<String> = sources.iter().map(|s| s.key()).collect();
+        assert_eq!(keys, ["status:s1", "task:t1"]);
+        assert_eq!(sources[0].entity_name.as_deref(), Some("Launch"));
+        assert!(sources[1].text.contains("Still waiting on legal"));
+
+        let flags = accept_flags(
+            &sources,
+            vec![
+                raw("task:t1", "HIGH", &["t2", "t404"]),
+                raw("status:s1", "sort of", &[]),
+                raw("task:t999", "high", &[]),
+            ],
+        );
+        assert_eq!(flags.len(), 2);
+        store_flags(&db, sources, flags).await.unwrap();
+
+        // Scanned sources are skipped until they change
+        assert!(gather(false).await.unwrap().is_empty());
+        assert_eq!(gather(true).await.unwrap().len(), 2);
+
+        let period = Period::Month(2025, 1);
+        let risks = list_risks(&db, &period, None).await.unwrap();
+        assert_eq!(risks.len(), 2);
+        assert_eq!(risks[0].severity, RiskSeverity::High);
+        assert_eq!(risks[0].task_gids, ["t1", "t2"]);
+        assert_eq!(risks[0].task_urls[0], generate_asana_url("task", "t1"));
+        // Unknown severities default to medium
+        assert_eq!(risks[1].severity, RiskSeverity::Medium);
+        assert_eq!(risks[1].entity_url, generate_asana_url("project", "p1"));
+
+        let high = list_risks(&db, &period, Some(RiskSeverity::High))
+            .await
+            .unwrap();
+        assert_eq!(high.len(), 1);
+    }
+}
@@ -1,10 +1,32 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::date_util::strip_code_fences;
 use crate::error::{Error, Result};
-use crate::storage::Database;
+use crate::llm::agents::{extra_json, parse_extra, run_json};
+use crate::llm::{prompts, Agent, LlmCall};
+use crate::storage::{threads, Database};
 
-const PROMPT_VERSION: &str = "task-v1";
+pub(crate) const PROMPT_VERSION: &str = "task-v2";
+
+/// Built-in prompt; `{context}` is the task's details and comment threads.
+pub(crate) const TASK_PROMPT: &str = r#"Analyze this Asana task and provide a structured summary as JSON.
+
+Task data:
+{context}
+
+Respond with ONLY a JSON object (no markdown, no code fences) in this exact format:
+{
+  "headline": "One-sentence summary of the task",
+  "what_happened": "2-3 sentences describing what the task involves and its current state",
+  "why_it_matters": "1-2 sentences on the significance or impact",
+  "complexity_signal": "low|medium|high",
+  "notability_score": <1-10 integer>,
+  "change_types": ["list", "of", "relevant", "labels"]
+}
+
+Comments are shown as threads: replies are indented under the comment they answer.
+
+For change_types, use labels like: "feature", "bug", "design", "documentation", "infrastructure", "planning", "review", "discussion", "milestone", "blocked", "recurring"."#;
 
 /// Structured summary of a task from LLM analysis.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,19 +37,30 @@ pub struct TaskSummary {
     pub complexity_signal: String,
     pub notability_score: i32,
     pub change_types: Vec<String>,
+    /// Fields an overridden prompt asked for beyond the built-in ones.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-/// Summarize a task using the LLM. Caches results in fact_task_summaries.
+/// Summarize a task using the LLM. Caches results in fact_task_summaries,
+/// except on a read-only database.
 pub async fn summarize_task(
     db: &Database,
-    agent: &mixtape_core::Agent,
+    agent: &Agent,
     task_gid: &str,
     force: bool,
 ) -> Result<TaskSummary> {
+    let prompt = prompts::load_prompt(db, "task").await?;
+    let call = LlmCall {
+        agent: "task",
+        prompt_version: &prompt.version,
+    };
+
     // Check cache first
     if !force {
-        let cached = get_cached_summary(db, task_gid).await?;
+        let cached = get_cached_summary(db, task_gid, &prompt.version).await?;
         if let Some(summary) = cached {
+            agent.record_cache_hit(call).await;
             return Ok(summary);
         }
     }
@@ -35,56 +68,31 @@ pub async fn summarize_task(
     // Gather task data
     let context = gather_task_context(db, task_gid).await?;
 
-    let prompt = format!(
-        r#"Analyze this Asana task and provide a structured summary as JSON.
+    let rendered = prompt.render(&[("context", &context)]);
 
-Task data:
-{context}
-
-Respond with ONLY a JSON object (no markdown, no code fences) in this exact format:
-{{
-  "headline": "One-sentence summary of the task",
-  "what_happened": "2-3 sentences describing what the task involves and its current state",
-  "why_it_matters": "1-2 sentences on the significance or impact",
-  "complexity_signal": "low|medium|high",
-  "notability_score": <1-10 integer>,
-  "change_types": ["list", "of", "relevant", "labels"]
-}}
-
-For change_types, use labels like: "feature", "bug", "design", "documentation", "infrastructure", "planning", "review", "discussion", "milestone", "blocked", "recurring"."#
-    );
-
-    let response = agent
-        .run(&prompt)
-        .await
-        .map_err(|e| Error::Llm(e.to_string()))?;
-
-    let text = response.text().trim();
-
-    // Parse JSON from response (strip markdown fences if present)
-    let json_str = strip_code_fences(text);
-    let summary: TaskSummary = serde_json::from_str(json_str).map_err(|e| {
-        Error::Llm(format!(
-            "Failed to parse LLM response: {e}\nResponse: {text}"
-        ))
-    })?;
+    let summary: TaskSummary = run_json(agent, call, &rendered).await?;
 
     // Cache the result
-    store_summary(db, task_gid, &summary).await?;
+    store_summary(db, task_gid, &prompt.version, &summary).await?;
 
     Ok(summary)
 }
 
-async fn get_cached_summary(db: &Database, task_gid: &str) -> Result<Option<TaskSummary>> {
+async fn get_cached_summary(
+    db: &Database,
+    task_gid: &str,
+    prompt_version: &str,
+) -> Result<Option<TaskSummary>> {
     let task_gid = task_gid.to_string();
+    let prompt_version = prompt_version.to_string();
     db.reader()
         .call(move |conn| {
             let mut stmt = conn.prepare(
-                "SELECT headline, what_happened, why_it_matters, complexity_signal, notability_score, change_types
+                "SELECT headline, what_happened, why_it_matters, complexity_signal, notability_score, change_types, extra_json
                  FROM fact_task_summaries WHERE task_gid = ?1 AND prompt_version = ?2"
             )?;
             let result = stmt
-                .query_row(rusqlite::params![task_gid, PROMPT_VERSION], |row| {
+                .query_row(rusqlite::params![task_gid, prompt_version], |row| {
                     let change_types_str: String = row.get(5)?;
                     let change_types: Vec<String> =
                         serde_json::from_str(&change_types_str).unwrap_or_default();
@@ -95,6 +103,7 @@ async fn get_cached_summary(db: &Database, task_gid: &str) -> Result<Option<Task
                         complexity_signal: row.get(3)?,
                         notability_score: row.get(4)?,
                         change_types,
+                        extra: parse_extra(row.get(6)?),
                     })
                 })
                 .ok();
@@ -162,24 +171,12 @@ async fn gather_task_context(db: &Database, task_gid: &str) -> Result<String> {
                 }
             }
 
-            // Comments
-            let mut stmt = conn.prepare(
-                "SELECT u.name, c.text, c.created_at FROM fact_comments c
-                 LEFT JOIN dim_users u ON u.user_gid = c.author_gid
-                 WHERE c.task_gid = ?1 AND c.story_type = 'comment'
-                 ORDER BY c.created_at"
-            )?;
-            let comments: Vec<(Option<String>, Option<String>, String)> = stmt
-                .query_map([&task_gid], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
-                .filter_map(|r| r.ok())
-                .collect();
-            if !comments.is_empty() {
-                parts.push(format!("\nComments ({}):", comments.len()));
-                for (author, text, created_at) in &comments {
-                    let a = author.as_deref().unwrap_or("unknown");
-                    let t = text.as_deref().unwrap_or("");
-                    parts.push(format!("  [{created_at}] {a}: {}", truncate(t, 500)));
-                }
+            // Comments, with replies nested under the comment they answer
+            let threads = threads::load_comment_threads(conn, &task_gid)?;
+            if !threads.is_empty() {
+                let lines = threads::render_threads(&threads, 500);
+                parts.push(format!("\nComments ({}):", lines.len()));
+                parts.extend(lines);
             }
 
             // Project membership
@@ -209,8 +206,18 @@ async fn gather_task_context(db: &Database, task_gid: &str) -> Result<String> {
         .map_err(|e| Error::Database(e.to_string()))
 }
 
-async fn store_summary(db: &Database, task_gid: &str, summary: &TaskSummary) -> Result<()> {
+async fn store_summary(
+    db: &Database,
+    task_gid: &str,
+    prompt_version: &str,
+    summary: &TaskSummary,
+) -> Result<()> {
+    if db.is_read_only() {
+        return Ok(());
+    }
     let task_gid = task_gid.to_string();
+    let prompt_version = prompt_version.to_string();
+    let extra_json = extra_json(&summary.extra);
     let headline = summary.headline.clone();
     let what_happened = summary.what_happened.clone();
     let why_it_matters = summary.why_it_matters.clone();
@@ -222,8 +229,8 @@ async fn store_summary(db: &Database, task_gid: &str, summary: &TaskSummary) ->
         .call(move |conn| {
             conn.execute(
                 "INSERT OR REPLACE INTO fact_task_summaries
-                 (task_gid, headline, what_happened, why_it_matters, complexity_signal, notability_score, change_types, prompt_version, generated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
+                 (task_gid, headline, what_happened, why_it_matters, complexity_signal, notability_score, change_types, extra_json, prompt_version, generated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'))",
                 rusqlite::params![
                     task_gid,
                     headline,
@@ -232,7 +239,8 @@ async fn store_summary(db: &Database, task_gid: &str, summary: &TaskSummary) ->
                     complexity_signal,
                     notability_score,
                     change_types,
-                    PROMPT_VERSION
+                    extra_json,
+                    prompt_version
                 ],
             )?;
             Ok::<(), rusqlite::Error>(())
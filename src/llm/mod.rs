@@ -1,52 +1,313 @@
 pub mod agents;
+pub mod openai;
+pub mod prompts;
 
 use crate::error::{Error, Result};
+use crate::storage::llm_usage::{self, ParseFailure, UsageRecord};
 use crate::storage::repository;
 use crate::storage::Database;
 
-/// Create a mixtape Agent configured from the database's LLM settings.
-pub async fn create_agent(db: &Database) -> Result<mixtape_core::Agent> {
-    let (provider, model) = db
+use self::openai::OpenAiClient;
+
+/// Environment variable read for the `openai` provider's key when
+/// `llm_api_key` isn't set.
+pub const OPENAI_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Identifies an agent invocation in the `llm_usage` log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LlmCall<'a> {
+    pub agent: &'static str,
+    pub prompt_version: &'a str,
+}
+
+/// The model behind summaries and agents: a mixtape agent for Bedrock and
+/// Anthropic, or any OpenAI-compatible chat completions endpoint. Agents
+/// created with [`create_agent`] log every call to `llm_usage`, unless the
+/// database is read-only.
+pub struct Agent {
+    backend: Backend,
+    provider: String,
+    model: String,
+    usage_db: Option<Database>,
+}
+
+enum Backend {
+    Mixtape(mixtape_core::Agent),
+    OpenAi(OpenAiClient),
+}
+
+/// A model reply with the token counts the provider reported.
+#[derive(Debug)]
+pub(crate) struct Completion {
+    pub text: String,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+impl Agent {
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Send a prompt and return the model's reply.
+    pub async fn run(&self, call: LlmCall<'_>, prompt: &str) -> Result<String> {
+        let started = std::time::Instant::now();
+        let result = match &self.backend {
+            Backend::Mixtape(agent) => agent
+                .run(prompt)
+                .await
+                .map(|response| Completion {
+                    text: response.text().to_string(),
+                    input_tokens: None,
+                    output_tokens: None,
+                })
+                .map_err(|e| Error::Llm(e.to_string())),
+            Backend::OpenAi(client) => client.complete(prompt).await,
+        };
+
+        let mut record = self.usage_record(call);
+        record.latency_ms = Some(started.elapsed().as_millis() as u64);
+        match &result {
+            Ok(completion) => {
+                record.tokens_estimated =
+                    completion.input_tokens.is_none() || completion.output_tokens.is_none();
+                record.input_tokens = completion.input_tokens.or(Some(estimate_tokens(prompt)));
+                record.output_tokens = completion
+                    .output_tokens
+                    .or(Some(estimate_tokens(&completion.text)));
+            }
+            Err(e) => record.error = Some(e.to_string()),
+        }
+        self.record(record).await;
+        result.map(|completion| completion.text)
+    }
+
+    /// Log a call answered from a cached result.
+    pub async fn record_cache_hit(&self, call: LlmCall<'_>) {
+        let mut record = self.usage_record(call);
+        record.cache_hit = true;
+        self.record(record).await;
+    }
+
+    /// Log a reply that didn't parse as the JSON the agent expected.
+    pub(crate) async fn record_parse_failure(
+        &self,
+        call: LlmCall<'_>,
+        attempt: u32,
+        error: &str,
+        response: &str,
+    ) {
+        let Some(db) = &self.usage_db else {
+            return;
+        };
+        let failure = ParseFailure {
+            agent: call.agent.to_string(),
+            prompt_version: call.prompt_version.to_string(),
+            provider: self.provider.clone(),
+            model: self.model.clone(),
+            attempt,
+            error: error.to_string(),
+            response: response.to_string(),
+        };
+        let result = db
+            .writer()
+            .call(move |conn| llm_usage::record_parse_failure(conn, &failure))
+            .await;
+        if let Err(e) = result {
+            log::warn!("Failed to record LLM parse failure: {e}");
+        }
+    }
+
+    fn usage_record(&self, call: LlmCall<'_>) -> UsageRecord {
+        UsageRecord {
+            agent: call.agent.to_string(),
+            prompt_version: call.prompt_version.to_string(),
+            provider: self.provider.clone(),
+            model: self.model.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Usage logging never fails the call it describes.
+    async fn record(&self, record: UsageRecord) {
+        let Some(db) = &self.usage_db else {
+            return;
+        };
+        let result = db
+            .writer()
+            .call(move |conn| llm_usage::record_usage(conn, &record))
+            .await;
+        if let Err(e) = result {
+            log::warn!("Failed to record LLM usage: {e}");
+        }
+    }
+}
+
+/// Rough token count for providers that don't report usage: about four
+/// characters per token for English text.
+fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(4) as u64
+}
+
+/// LLM settings read from `app_config`.
+#[derive(Debug, Clone, Default)]
+struct LlmSettings {
+    provider: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+    api_key: Option<String>,
+}
+
+/// Create an Agent configured from the database's LLM settings.
+pub async fn create_agent(db: &Database) -> Result<Agent> {
+    let settings = db
         .reader()
         .call(|conn| {
-            let provider = repository::get_config(conn, "llm_provider")?;
-            let model = repository::get_config(conn, "llm_model")?;
-            Ok::<(Option<String>, Option<String>), rusqlite::Error>((provider, model))
+            Ok::<LlmSettings, rusqlite::Error>(LlmSettings {
+                provider: repository::get_config(conn, "llm_provider")?,
+                model: repository::get_config(conn, "llm_model")?,
+                base_url: repository::get_config(conn, "llm_base_url")?,
+                api_key: repository::get_config(conn, "llm_api_key")?,
+            })
         })
         .await?;
 
-    let provider = provider.as_deref().unwrap_or("bedrock");
-    let model_name = model.as_deref().unwrap_or("claude-sonnet-4-5");
+    let mut agent = build_agent(&settings).await?;
+    if !db.is_read_only() {
+        agent.usage_db = Some(db.clone());
+    }
+    Ok(agent)
+}
 
-    build_agent(provider, model_name).await
+/// Embed `texts` with `llm_embedding_model`. Returns `None` unless the
+/// `openai` provider is configured with an embedding model.
+pub async fn embed_texts(db: &Database, texts: &[String]) -> Result<Option<Vec<Vec<f64>>>> {
+    let (settings, embedding_model) = db
+        .reader()
+        .call(|conn| {
+            let settings = LlmSettings {
+                provider: repository::get_config(conn, "llm_provider")?,
+                model: None,
+                base_url: repository::get_config(conn, "llm_base_url")?,
+                api_key: repository::get_config(conn, "llm_api_key")?,
+            };
+            let model = repository::get_config(conn, "llm_embedding_model")?;
+            Ok::<_, rusqlite::Error>((settings, model))
+        })
+        .await?;
+    let (Some("openai"), Some(base_url), Some(model)) = (
+        settings.provider.as_deref(),
+        settings.base_url.as_deref(),
+        embedding_model.as_deref(),
+    ) else {
+        return Ok(None);
+    };
+    let api_key = settings
+        .api_key
+        .or_else(|| std::env::var(OPENAI_API_KEY_ENV).ok())
+        .filter(|key| !key.trim().is_empty());
+    let client = OpenAiClient::new(base_url, model, api_key);
+    client.embed(texts).await.map(Some)
 }
 
-async fn build_agent(provider: &str, model_name: &str) -> Result<mixtape_core::Agent> {
+async fn build_agent(settings: &LlmSettings) -> Result<Agent> {
+    let provider = settings.provider.as_deref().unwrap_or("bedrock");
+    if provider == "openai" {
+        let base_url = settings.base_url.as_deref().ok_or_else(|| {
+            Error::Config("llm_base_url must be set for the openai provider".to_string())
+        })?;
+        let model = settings.model.as_deref().ok_or_else(|| {
+            Error::Config("llm_model must be set for the openai provider".to_string())
+        })?;
+        let api_key = settings
+            .api_key
+            .clone()
+            .or_else(|| std::env::var(OPENAI_API_KEY_ENV).ok())
+            .filter(|key| !key.trim().is_empty());
+        return Ok(Agent {
+            backend: Backend::OpenAi(OpenAiClient::new(base_url, model, api_key)),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            usage_db: None,
+        });
+    }
+
+    let model_name = settings.model.as_deref().unwrap_or("claude-sonnet-4-5");
+    let (haiku, model) = match model_name {
+        "claude-haiku-4-5" | "haiku" => (true, "claude-haiku-4-5"),
+        "claude-sonnet-4-5" | "sonnet" => (false, "claude-sonnet-4-5"),
+        other => {
+            log::warn!("Unrecognized llm_model '{other}' for {provider}; using claude-sonnet-4-5");
+            (false, "claude-sonnet-4-5")
+        }
+    };
+
     // Each combination needs its own builder call since the model types are different.
-    match (provider, model_name) {
-        ("bedrock", "claude-haiku-4-5" | "haiku") => mixtape_core::Agent::builder()
-            .bedrock(mixtape_core::ClaudeHaiku4_5)
-            .build()
-            .await
-            .map_err(|e| Error::Llm(e.to_string())),
-        ("bedrock", _) => {
-            // Default bedrock model
+    let agent = match (provider, haiku) {
+        ("bedrock", true) => {
+            mixtape_core::Agent::builder()
+                .bedrock(mixtape_core::ClaudeHaiku4_5)
+                .build()
+                .await
+        }
+        ("bedrock", false) => {
             mixtape_core::Agent::builder()
                 .bedrock(mixtape_core::ClaudeSonnet4_5)
                 .build()
                 .await
-                .map_err(|e| Error::Llm(e.to_string()))
-        }
-        ("anthropic", "claude-haiku-4-5" | "haiku") => mixtape_core::Agent::builder()
-            .anthropic_from_env(mixtape_core::ClaudeHaiku4_5)
-            .build()
-            .await
-            .map_err(|e| Error::Llm(e.to_string())),
-        ("anthropic", _) => mixtape_core::Agent::builder()
-            .anthropic_from_env(mixtape_core::ClaudeSonnet4_5)
-            .build()
-            .await
-            .map_err(|e| Error::Llm(e.to_string())),
-        (other, _) => Err(Error::Config(format!("unknown llm_provider: {other}"))),
+        }
+        ("anthropic", true) => {
+            mixtape_core::Agent::builder()
+                .anthropic_from_env(mixtape_core::ClaudeHaiku4_5)
+                .build()
+                .await
+        }
+        ("anthropic", false) => {
+            mixtape_core::Agent::builder()
+                .anthropic_from_env(mixtape_core::ClaudeSonnet4_5)
+                .build()
+                .await
+        }
+        (other, _) => return Err(Error::Config(format!("unknown llm_provider: {other}"))),
+    };
+    let agent = agent.map_err(|e| Error::Llm(e.to_string()))?;
+    Ok(Agent {
+        backend: Backend::Mixtape(agent),
+        provider: provider.to_string(),
+        model: model.to_string(),
+        usage_db: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_openai_agent() {
+        let mut settings = LlmSettings {
+            provider: Some("openai".to_string()),
+            model: Some("llama3.1:70b".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_agent(&settings).await,
+            Err(Error::Config(msg)) if msg.contains("llm_base_url")
+        ));
+
+        settings.base_url = Some("http://localhost:11434/v1".to_string());
+        let agent = build_agent(&settings).await.unwrap();
+        assert!(matches!(agent.backend, Backend::OpenAi(_)));
+        assert_eq!(agent.model(), "llama3.1:70b");
+
+        settings.provider = Some("vertex".to_string());
+        assert!(matches!(
+            build_agent(&settings).await,
+            Err(Error::Config(_))
+        ));
     }
 }
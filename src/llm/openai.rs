@@ -0,0 +1,192 @@
+//! Client for OpenAI-compatible chat completions and embeddings endpoints
+//! (OpenAI, Azure OpenAI, vLLM, Ollama, LiteLLM, and similar gateways).
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::Completion;
+use crate::error::{Error, Result};
+
+/// How long to wait for a completion before giving up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Most inputs sent in one embeddings request.
+const EMBED_BATCH_SIZE: usize = 100;
+
+/// Sends prompts to `{base_url}/chat/completions` as a single user message.
+#[derive(Clone)]
+pub struct OpenAiClient {
+    http: ureq::Agent,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiClient {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`. The key
+    /// is sent as a bearer token when given; local servers often need none.
+    pub fn new(base_url: &str, model: &str, api_key: Option<String>) -> Self {
+        Self {
+            http: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key,
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Send `prompt` and return the first choice's message content.
+    pub(crate) async fn complete(&self, prompt: &str) -> Result<Completion> {
+        let client = self.clone();
+        let body = request_body(&self.model, prompt);
+        let response = tokio::task::spawn_blocking(move || client.post("chat/completions", body))
+            .await
+            .map_err(|e| Error::Llm(e.to_string()))??;
+        parse_response(&response)
+    }
+
+    /// Embed each input with `{base_url}/embeddings`, in input order. The
+    /// client's model must be an embedding model.
+    pub(crate) async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f64>>> {
+        let mut vectors = Vec::with_capacity(inputs.len());
+        for batch in inputs.chunks(EMBED_BATCH_SIZE) {
+            let client = self.clone();
+            let body = json!({"model": self.model, "input": batch});
+            let response = tokio::task::spawn_blocking(move || client.post("embeddings", body))
+                .await
+                .map_err(|e| Error::Llm(e.to_string()))??;
+            let batch_vectors = parse_embeddings(&response)?;
+            if batch_vectors.len() != batch.len() {
+                return Err(Error::Llm(format!(
+                    "Expected {} embeddings, got {}",
+                    batch.len(),
+                    batch_vectors.len()
+                )));
+            }
+            vectors.extend(batch_vectors);
+        }
+        Ok(vectors)
+    }
+
+    fn post(&self, path: &str, body: Value) -> Result<Value> {
+        let url = format!("{}/{path}", self.base_url);
+        let mut request = self.http.post(&url);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {key}"));
+        }
+        match request.send_json(body) {
+            Ok(response) => response
+                .into_json()
+                .map_err(|e| Error::Llm(format!("Invalid response from {url}: {e}"))),
+            Err(ureq::Error::Status(code, response)) => {
+                let detail = response.into_string().unwrap_or_default();
+                Err(Error::Llm(format!("{url} returned HTTP {code}: {detail}")))
+            }
+            Err(e) => Err(Error::Llm(format!("Request to {url} failed: {e}"))),
+        }
+    }
+}
+
+fn request_body(model: &str, prompt: &str) -> Value {
+    json!({
+        "model": model,
+        "messages": [{"role": "user", "content": prompt}],
+    })
+}
+
+fn parse_response(response: &Value) -> Result<Completion> {
+    let text = response["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| {
+            let detail = response["error"]["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| response.to_string());
+            Error::Llm(format!("No completion in response: {detail}"))
+        })?;
+    Ok(Completion {
+        text: text.to_string(),
+        input_tokens: response["usage"]["prompt_tokens"].as_u64(),
+        output_tokens: response["usage"]["completion_tokens"].as_u64(),
+    })
+}
+
+/// Embedding vectors ordered by each item's `index`.
+fn parse_embeddings(response: &Value) -> Result<Vec<Vec<f64>>> {
+    let data = response["data"].as_array().ok_or_else(|| {
+        let detail = response["error"]["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| response.to_string());
+        Error::Llm(format!("No embeddings in response: {detail}"))
+    })?;
+    let mut items: Vec<(u64, Vec<f64>)> = data
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let index = item["index"].as_u64().unwrap_or(i as u64);
+            let vector = item["embedding"]
+                .as_array()
+                .map(|v| v.iter().filter_map(Value::as_f64).collect())
+                .unwrap_or_default();
+            (index, vector)
+        })
+        .collect();
+    items.sort_by_key(|(index, _)| *index);
+    Ok(items.into_iter().map(|(_, v)| v).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_and_response() {
+        let client = OpenAiClient::new("http://localhost:8000/v1/", "qwen2.5", None);
+        assert_eq!(client.base_url, "http://localhost:8000/v1");
+        assert_eq!(
+            request_body(client.model(), "Hi")["messages"][0],
+            json!({"role": "user", "content": "Hi"})
+        );
+
+        let ok = json!({
+            "choices": [{"message": {"role": "assistant", "content": "{\"a\": 1}"}}],
+            "usage": {"prompt_tokens": 120, "completion_tokens": 8}
+        });
+        let completion = parse_response(&ok).unwrap();
+        assert_eq!(completion.text, "{\"a\": 1}");
+        assert_eq!(
+            (completion.input_tokens, completion.output_tokens),
+            (Some(120), Some(8))
+        );
+
+        let err = json!({"error": {"message": "model not found"}});
+        assert!(parse_response(&err)
+            .unwrap_err()
+            .to_string()
+            .contains("model not found"));
+    }
+
+    #[test]
+    fn test_parse_embeddings() {
+        let ok = json!({
+            "data": [
+                {"index": 1, "embedding": [0.5, 0.25]},
+                {"index": 0, "embedding": [1.0, 0.0]}
+            ]
+        });
+        assert_eq!(
+            parse_embeddings(&ok).unwrap(),
+            vec![vec![1.0, 0.0], vec![0.5, 0.25]]
+        );
+        let err = json!({"error": {"message": "not an embedding model"}});
+        assert!(parse_embeddings(&err)
+            .unwrap_err()
+            .to_string()
+            .contains("not an embedding model"));
+    }
+}
@@ -0,0 +1,247 @@
+//! User overrides for the built-in summary prompts.
+//!
+//! A prompt is overridden by an `app_config` entry `prompt.<name>` or a file
+//! `~/.asanadw/prompts/<name>.txt`, in that order. Templates fill in
+//! `{variable}` placeholders; any other braces are left alone, so JSON
+//! examples need no escaping. An override's prompt version includes a hash of
+//! its text, so editing it invalidates cached summaries.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::agents::{period, task};
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::Database;
+
+/// Prefix of `app_config` keys holding prompt overrides.
+pub const CONFIG_PREFIX: &str = "prompt.";
+
+/// A built-in prompt that can be overridden.
+#[derive(Debug)]
+pub struct PromptSpec {
+    pub name: &'static str,
+    /// Placeholders filled in when rendering; overrides must keep `{context}`.
+    pub variables: &'static [&'static str],
+    template: &'static str,
+    version: &'static str,
+}
+
+const PERIOD_VARIABLES: &[&str] = &["period_key", "context", "metrics", "trends"];
+
+/// Every overridable prompt.
+pub const PROMPTS: &[PromptSpec] = &[
+    PromptSpec {
+        name: "task",
+        variables: &["context"],
+        template: task::TASK_PROMPT,
+        version: task::PROMPT_VERSION,
+    },
+    PromptSpec {
+        name: "user_period",
+        variables: PERIOD_VARIABLES,
+        template: period::USER_PROMPT,
+        version: period::PROMPT_VERSION,
+    },
+    PromptSpec {
+        name: "project_period",
+        variables: PERIOD_VARIABLES,
+        template: period::PROJECT_PROMPT,
+        version: period::PROMPT_VERSION,
+    },
+    PromptSpec {
+        name: "portfolio_period",
+        variables: PERIOD_VARIABLES,
+        template: period::PORTFOLIO_PROMPT,
+        version: period::PROMPT_VERSION,
+    },
+    PromptSpec {
+        name: "team_period",
+        variables: PERIOD_VARIABLES,
+        template: period::TEAM_PROMPT,
+        version: period::PROMPT_VERSION,
+    },
+];
+
+/// Where a prompt's template came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptSource {
+    BuiltIn,
+    Config,
+    File(PathBuf),
+}
+
+/// A prompt template ready to render.
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompt {
+    pub name: &'static str,
+    pub template: String,
+    /// Stored with cached results; differs from the built-in version when
+    /// the prompt is overridden.
+    pub version: String,
+    pub source: PromptSource,
+    #[serde(skip)]
+    variables: &'static [&'static str],
+}
+
+impl Prompt {
+    /// Fill in `{variable}` placeholders. Substituted text is not scanned
+    /// again, so activity data containing braces is inserted as-is.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        let mut out = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let value = self.variables.iter().find_map(|var| {
+                after
+                    .strip_prefix(var)
+                    .filter(|tail| tail.starts_with('}'))
+                    .and_then(|_| values.iter().find(|(name, _)| name == var))
+                    .map(|(name, value)| (name.len(), *value))
+            });
+            match value {
+                Some((len, value)) => {
+                    out.push_str(value);
+                    rest = &after[len + 1..];
+                }
+                None => {
+                    out.push('{');
+                    rest = after;
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// The built-in prompt called `name`.
+pub fn spec(name: &str) -> Option<&'static PromptSpec> {
+    PROMPTS.iter().find(|p| p.name == name)
+}
+
+/// `~/.asanadw/prompts`, where override files live.
+pub fn prompts_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".asanadw").join("prompts"))
+}
+
+/// Check an override template for `spec`.
+pub fn validate_template(spec: &PromptSpec, template: &str) -> std::result::Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("template is empty".to_string());
+    }
+    if !template.contains("{context}") {
+        return Err(format!(
+            "template must include {{context}}. Available: {}",
+            spec.variables
+                .iter()
+                .map(|v| format!("{{{v}}}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Pick the template for `spec` from a config override, an override file in
+/// `dir`, or the built-in prompt.
+pub fn resolve(
+    spec: &'static PromptSpec,
+    config_value: Option<String>,
+    dir: Option<&Path>,
+) -> Result<Prompt> {
+    let file = dir.map(|d| d.join(format!("{}.txt", spec.name)));
+    let (template, source) = match (config_value, &file) {
+        (Some(template), _) => (template, PromptSource::Config),
+        (None, Some(path)) => match std::fs::read_to_string(path) {
+            Ok(template) => (template, PromptSource::File(path.clone())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                (spec.template.to_string(), PromptSource::BuiltIn)
+            }
+            Err(e) => {
+                return Err(Error::Config(format!(
+                    "cannot read {}: {e}",
+                    path.display()
+                )))
+            }
+        },
+        (None, None) => (spec.template.to_string(), PromptSource::BuiltIn),
+    };
+
+    let version = if source == PromptSource::BuiltIn {
+        spec.version.to_string()
+    } else {
+        validate_template(spec, &template)
+            .map_err(|e| Error::Config(format!("prompt '{}': {e}", spec.name)))?;
+        let digest = Sha256::digest(template.as_bytes());
+        let hex: String = digest[..6].iter().map(|b| format!("{b:02x}")).collect();
+        format!("{}-custom-{hex}", spec.version)
+    };
+    Ok(Prompt {
+        name: spec.name,
+        template,
+        version,
+        source,
+        variables: spec.variables,
+    })
+}
+
+/// Load the effective prompt called `name`.
+pub async fn load_prompt(db: &Database, name: &str) -> Result<Prompt> {
+    let spec = spec(name).ok_or_else(|| Error::Config(format!("unknown prompt '{name}'")))?;
+    let key = format!("{CONFIG_PREFIX}{name}");
+    let config_value = db
+        .reader()
+        .call(move |conn| repository::get_config(conn, &key))
+        .await?;
+    resolve(spec, config_value, prompts_dir().as_deref())
+}
+
+/// Every prompt as it would be used now.
+pub async fn list_prompts(db: &Database) -> Result<Vec<Prompt>> {
+    let mut prompts = Vec::with_capacity(PROMPTS.len());
+    for spec in PROMPTS {
+        prompts.push(load_prompt(db, spec.name).await?);
+    }
+    Ok(prompts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_render() {
+        let spec = spec("project_period").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let builtin = resolve(spec, None, Some(dir.path())).unwrap();
+        assert_eq!(builtin.source, PromptSource::BuiltIn);
+        assert_eq!(builtin.version, period::PROMPT_VERSION);
+
+        let path = dir.path().join("project_period.txt");
+        std::fs::write(
+            &path,
+            "Period {period_key}: {context}\nAdd {\"customer_impact\": \"...\"}",
+        )
+        .unwrap();
+        let file = resolve(spec, None, Some(dir.path())).unwrap();
+        assert_eq!(file.source, PromptSource::File(path.clone()));
+        assert!(file.version.starts_with("period-v3-custom-"));
+        assert_eq!(
+            file.render(&[("period_key", "2025-Q1"), ("context", "{metrics} stays")]),
+            "Period 2025-Q1: {metrics} stays\nAdd {\"customer_impact\": \"...\"}"
+        );
+
+        // Config wins over the file, and a different text gets a new version
+        let config = resolve(spec, Some("Just {context}".to_string()), Some(dir.path())).unwrap();
+        assert_eq!(config.source, PromptSource::Config);
+        assert_ne!(config.version, file.version);
+
+        assert!(resolve(spec, Some("No placeholders".to_string()), None).is_err());
+    }
+}
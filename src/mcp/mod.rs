@@ -0,0 +1,572 @@
+//! Model Context Protocol server over stdio.
+//!
+//! `asanadw mcp` exposes the warehouse to LLM assistants as MCP tools for
+//! search, task queries (ad hoc or saved), read-only SQL, metrics, and
+//! (with the `llm` feature) summaries. Messages are newline-delimited
+//! JSON-RPC 2.0, per the MCP stdio transport. Nothing here syncs; the
+//! assistant sees whatever the last `asanadw sync` stored.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::error::{Error, Result};
+use crate::query::builder::QueryBuilder;
+use crate::query::period::{Period, PERIOD_EXAMPLES};
+use crate::query::saved;
+use crate::search::{SearchHitType, SearchOptions};
+use crate::storage::repository;
+use crate::storage::Database;
+
+/// Protocol revisions this server speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve MCP requests from stdin until it closes. Logs go to stderr, so
+/// stdout carries only protocol messages.
+pub async fn serve_stdio(db: &Database) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    let io_err = |e: std::io::Error| Error::Other(format!("MCP stdio: {e}"));
+
+    while let Some(line) = lines.next_line().await.map_err(io_err)? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(db, &message).await,
+            Err(e) => Some(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+        };
+        if let Some(response) = response {
+            let mut out = response.to_string();
+            out.push('\n');
+            stdout.write_all(out.as_bytes()).await.map_err(io_err)?;
+            stdout.flush().await.map_err(io_err)?;
+        }
+    }
+    Ok(())
+}
+
+/// Handle one JSON-RPC message. Returns the response, or `None` for
+/// notifications, which get no reply.
+pub async fn handle_message(db: &Database, message: &Value) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        return Some(error_response(id, INVALID_REQUEST, "missing method"));
+    };
+    let id = message.get("id").cloned()?;
+    let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(db, &params).await,
+        other => Err((METHOD_NOT_FOUND, format!("unknown method: {other}"))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+fn initialize(params: &Value) -> Value {
+    // Answer with the client's revision when we speak it, else our newest
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "asanadw", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Read-only access to a local warehouse of synced Asana data. \
+            Use `search` to find tasks, comments, projects, and status updates by text, \
+            `query` to filter tasks, `sql` for anything else, and `metrics` for \
+            throughput, lead time, and health over a period.",
+    })
+}
+
+fn period_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": format!("Period such as {PERIOD_EXAMPLES} (default: qtd)"),
+    })
+}
+
+/// Definitions of the read-only warehouse tools, which the `ask` agent
+/// also uses.
+pub(crate) fn warehouse_tool_definitions() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "search",
+            "description": "Full-text search over tasks, comments, projects, portfolios, project briefs, status updates, and custom fields. Supports \"phrases\", word*, -exclusions, OR, assignee:<name>, project:<name>, and subtype:<milestone|approval|default_task>.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "type": {
+                        "type": "string",
+                        "enum": ["task", "comment", "project", "portfolio", "brief", "status", "custom_field"],
+                    },
+                    "assignee": { "type": "string", "description": "User GID or email" },
+                    "project": { "type": "string", "description": "Project GID" },
+                    "completed": { "type": "boolean" },
+                    "subtype": { "type": "string", "enum": ["default_task", "milestone", "approval"] },
+                    "created_after": { "type": "string", "description": "YYYY-MM-DD" },
+                    "created_before": { "type": "string", "description": "YYYY-MM-DD" },
+                    "limit": { "type": "integer", "minimum": 1 },
+                    "offset": { "type": "integer", "minimum": 0 },
+                },
+                "required": ["query"],
+            },
+        }),
+        json!({
+            "name": "query",
+            "description": "List tasks matching filters, most recently modified first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": { "type": "string", "description": "Project GID or URL" },
+                    "portfolio": { "type": "string", "description": "Portfolio GID or URL" },
+                    "team": { "type": "string", "description": "Team GID" },
+                    "assignee": { "type": "string", "description": "User GID or email" },
+                    "completed": { "type": "boolean" },
+                    "overdue": { "type": "boolean" },
+                    "subtype": { "type": "string", "enum": ["default_task", "milestone", "approval"] },
+                    "created_after": { "type": "string", "description": "YYYY-MM-DD" },
+                    "created_before": { "type": "string", "description": "YYYY-MM-DD" },
+                    "due_after": { "type": "string", "description": "YYYY-MM-DD" },
+                    "due_before": { "type": "string", "description": "YYYY-MM-DD" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Default: 100" },
+                },
+            },
+        }),
+        json!({
+            "name": "saved_query",
+            "description": "Run a task query saved with `asanadw query save`, by name. Without a name, lists the saved queries with their descriptions and filters.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Default: the saved limit" },
+                },
+            },
+        }),
+        json!({
+            "name": "sql",
+            "description": "Run a read-only SQL statement (SELECT, WITH, EXPLAIN, PRAGMA, or VALUES) against the warehouse. Main tables: fact_tasks, fact_comments, fact_status_updates, dim_projects, dim_portfolios, dim_users, dim_teams, bridge_task_projects.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "sql": { "type": "string" } },
+                "required": ["sql"],
+            },
+        }),
+        json!({
+            "name": "metrics",
+            "description": "Throughput, lead time, and health metrics for a user, project, portfolio, team, or project label over a period.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "entity_type": {
+                        "type": "string",
+                        "enum": ["user", "project", "portfolio", "team", "label"],
+                    },
+                    "identifier": { "type": "string", "description": "GID, URL, email (users), or label name" },
+                    "period": period_schema(),
+                },
+                "required": ["entity_type", "identifier"],
+            },
+        }),
+    ]
+}
+
+fn tool_definitions() -> Vec<Value> {
+    #[allow(unused_mut)]
+    let mut tools = warehouse_tool_definitions();
+    #[cfg(feature = "llm")]
+    tools.push(json!({
+        "name": "summarize",
+        "description": "LLM summary of a task, or of a user, project, portfolio, or team over a period. Cached summaries are returned without calling the model.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "entity_type": {
+                    "type": "string",
+                    "enum": ["task", "user", "project", "portfolio", "team"],
+                },
+                "identifier": { "type": "string", "description": "GID, URL, or email (users)" },
+                "period": period_schema(),
+            },
+            "required": ["entity_type", "identifier"],
+        },
+    }));
+    tools
+}
+
+/// Run a tool. Unknown tools are protocol errors; failures inside a tool are
+/// reported in the result so the assistant can see and correct them.
+async fn call_tool(db: &Database, params: &Value) -> std::result::Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "missing tool name".to_string()))?;
+    let args = params
+        .get("arguments")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+
+    let result = match name {
+        #[cfg(feature = "llm")]
+        "summarize" => summarize_tool(db, &args).await,
+        _ => match run_warehouse_tool(db, name, &args).await {
+            Some(result) => result,
+            None => return Err((INVALID_PARAMS, format!("unknown tool: {name}"))),
+        },
+    };
+    Ok(match result {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(e) => {
+            json!({ "content": [{ "type": "text", "text": e.to_string() }], "isError": true })
+        }
+    })
+}
+
+/// Run one of the [`warehouse_tool_definitions`] and return its JSON output,
+/// or `None` if `name` isn't one of them.
+pub(crate) async fn run_warehouse_tool(
+    db: &Database,
+    name: &str,
+    args: &Value,
+) -> Option<Result<String>> {
+    Some(match name {
+        "search" => search_tool(db, args).await,
+        "query" => query_tool(db, args).await,
+        "saved_query" => saved_query_tool(db, args).await,
+        "sql" => sql_tool(db, args).await,
+        "metrics" => metrics_tool(db, args).await,
+        _ => return None,
+    })
+}
+
+fn to_text<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value).map_err(|e| Error::Other(e.to_string()))
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(Value::as_str)
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+    str_arg(args, key).ok_or_else(|| Error::Other(format!("missing argument: {key}")))
+}
+
+fn bool_arg(args: &Value, key: &str) -> Option<bool> {
+    args.get(key).and_then(Value::as_bool)
+}
+
+fn u32_arg(args: &Value, key: &str) -> Option<u32> {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .map(|n| n.min(u32::MAX as u64) as u32)
+}
+
+fn period_arg(args: &Value) -> Result<Period> {
+    Period::parse(str_arg(args, "period").unwrap_or("qtd"))
+}
+
+/// Resolve a user GID or email; unknown emails are used as given.
+pub(crate) async fn resolve_user(db: &Database, identifier: &str) -> Result<String> {
+    let id = identifier.to_string();
+    let resolved = db
+        .reader()
+        .call(move |conn| repository::resolve_user_identifier(conn, &id))
+        .await?;
+    Ok(resolved.unwrap_or_else(|| identifier.to_string()))
+}
+
+async fn search_tool(db: &Database, args: &Value) -> Result<String> {
+    let query = required_str(args, "query")?;
+    let hit_type = match str_arg(args, "type") {
+        None => None,
+        Some("task") => Some(SearchHitType::Task),
+        Some("comment") => Some(SearchHitType::Comment),
+        Some("project") => Some(SearchHitType::Project),
+        Some("portfolio") => Some(SearchHitType::Portfolio),
+        Some("brief") => Some(SearchHitType::ProjectBrief),
+        Some("status") => Some(SearchHitType::StatusUpdate),
+        Some("custom_field") => Some(SearchHitType::CustomField),
+        Some(other) => return Err(Error::Other(format!("unknown search type: {other}"))),
+    };
+    let assignee_gid = match str_arg(args, "assignee") {
+        Some(a) => Some(resolve_user(db, a).await?),
+        None => None,
+    };
+    let options = SearchOptions {
+        limit: Some(u32_arg(args, "limit").unwrap_or(20)),
+        offset: u32_arg(args, "offset"),
+        hit_type,
+        assignee_gid,
+        project_gid: str_arg(args, "project").map(String::from),
+        completed: bool_arg(args, "completed"),
+        subtype: str_arg(args, "subtype").map(String::from),
+        created_after: str_arg(args, "created_after").map(String::from),
+        created_before: str_arg(args, "created_before").map(String::from),
+        ..Default::default()
+    };
+    to_text(&crate::search::search(db, query, &options).await?)
+}
+
+async fn query_tool(db: &Database, args: &Value) -> Result<String> {
+    let mut builder = QueryBuilder::new()
+        .limit(u32_arg(args, "limit").unwrap_or(100))
+        .order_by("t.modified_at")
+        .descending();
+    if let Some(p) = str_arg(args, "project") {
+        builder = builder.project(&crate::alias::resolve(db, p).await?);
+    }
+    if let Some(p) = str_arg(args, "portfolio") {
+        builder = builder.portfolio(&crate::alias::resolve(db, p).await?);
+    }
+    if let Some(t) = str_arg(args, "team") {
+        builder = builder.team(t);
+    }
+    if let Some(a) = str_arg(args, "assignee") {
+        builder = builder.assignee(&resolve_user(db, a).await?);
+    }
+    if let Some(c) = bool_arg(args, "completed") {
+        builder = builder.completed(c);
+    }
+    if let Some(o) = bool_arg(args, "overdue") {
+        builder = builder.overdue(o);
+    }
+    if let Some(s) = str_arg(args, "subtype") {
+        builder = builder.subtype(s);
+    }
+    if let Some(d) = str_arg(args, "created_after") {
+        builder = builder.created_after(d);
+    }
+    if let Some(d) = str_arg(args, "created_before") {
+        builder = builder.created_before(d);
+    }
+    if let Some(d) = str_arg(args, "due_after") {
+        builder = builder.due_after(d);
+    }
+    if let Some(d) = str_arg(args, "due_before") {
+        builder = builder.due_before(d);
+    }
+    to_text(&builder.tasks(db).await?)
+}
+
+async fn saved_query_tool(db: &Database, args: &Value) -> Result<String> {
+    let Some(name) = str_arg(args, "name") else {
+        return to_text(&saved::list_queries(db).await?);
+    };
+    let mut builder = saved::query_builder(db, name).await?;
+    if let Some(limit) = u32_arg(args, "limit") {
+        builder = builder.limit(limit);
+    }
+    builder.to_json(db).await
+}
+
+async fn sql_tool(db: &Database, args: &Value) -> Result<String> {
+    to_text(&db.query_arbitrary(required_str(args, "sql")?).await?)
+}
+
+async fn metrics_tool(db: &Database, args: &Value) -> Result<String> {
+    let entity_type = required_str(args, "entity_type")?;
+    let identifier = required_str(args, "identifier")?;
+    let period = period_arg(args)?;
+    match entity_type {
+        "user" => {
+            let gid = resolve_user(db, identifier).await?;
+            to_text(&crate::metrics::compute_user_metrics(db, &gid, &period).await?)
+        }
+        "project" => {
+            let gid = crate::alias::resolve(db, identifier).await?;
+            to_text(&crate::metrics::compute_project_metrics(db, &gid, &period).await?)
+        }
+        "portfolio" => {
+            let gid = crate::alias::resolve(db, identifier).await?;
+            to_text(&crate::metrics::compute_portfolio_metrics(db, &gid, &period).await?)
+        }
+        "team" => {
+            let gid = crate::alias::resolve(db, identifier).await?;
+            to_text(&crate::metrics::compute_team_metrics(db, &gid, &period).await?)
+        }
+        "label" => to_text(&crate::metrics::compute_label_metrics(db, identifier, &period).await?),
+        other => Err(Error::Other(format!(
+            "unknown entity type: {other}. Use: user, project, portfolio, team, label"
+        ))),
+    }
+}
+
+#[cfg(feature = "llm")]
+async fn summarize_tool(db: &Database, args: &Value) -> Result<String> {
+    use crate::llm::agents::{period, task};
+
+    let entity_type = required_str(args, "entity_type")?;
+    let identifier = required_str(args, "identifier")?;
+    let agent = crate::llm::create_agent(db).await?;
+    if entity_type == "task" {
+        let gid = crate::alias::resolve(db, identifier).await?;
+        return to_text(&task::summarize_task(db, &agent, &gid, false).await?);
+    }
+
+    let p = period_arg(args)?;
+    match entity_type {
+        "user" => {
+            let gid = resolve_user(db, identifier).await?;
+            to_text(&period::summarize_user_period(db, &agent, &gid, &p, false).await?)
+        }
+        "project" => {
+            let gid = crate::alias::resolve(db, identifier).await?;
+            to_text(&period::summarize_project_period(db, &agent, &gid, &p, false).await?)
+        }
+        "portfolio" => {
+            let gid = crate::alias::resolve(db, identifier).await?;
+            to_text(&period::summarize_portfolio_period(db, &agent, &gid, &p, false).await?)
+        }
+        "team" => {
+            let gid = crate::alias::resolve(db, identifier).await?;
+            to_text(&period::summarize_team_period(db, &agent, &gid, &p, false).await?)
+        }
+        other => Err(Error::Other(format!(
+            "unknown entity type: {other}. Use: task, user, project, portfolio, team"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn request(db: &Database, method: &str, params: Value) -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        handle_message(db, &message).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_tool_list() {
+        let db = Database::open_memory().await.unwrap();
+
+        let init = request(
+            &db,
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05" }),
+        )
+        .await;
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(init["result"]["serverInfo"]["name"], "asanadw");
+
+        let init = request(
+            &db,
+            "initialize",
+            json!({ "protocolVersion": "1999-01-01" }),
+        )
+        .await;
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+        // Notifications get no response
+        let note = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(handle_message(&db, &note).await.is_none());
+
+        let list = request(&db, "tools/list", json!({})).await;
+        let names: Vec<&str> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        for name in ["search", "query", "saved_query", "sql", "metrics"] {
+            assert!(names.contains(&name), "{names:?}");
+        }
+
+        let unknown = request(&db, "resources/list", json!({})).await;
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Migrate billing', '2025-01-01', '2025-01-01', datetime('now'))",
+                    [],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let call = |name: &str, arguments: Value| {
+            request(
+                &db,
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+            )
+        };
+        let text = |response: &Value| {
+            serde_json::from_str::<Value>(
+                response["result"]["content"][0]["text"].as_str().unwrap(),
+            )
+            .unwrap()
+        };
+
+        let search = call("search", json!({ "query": "billing" })).await;
+        assert_eq!(search["result"]["isError"], false);
+        assert_eq!(text(&search)["hits"][0]["gid"], "t1");
+
+        let query = call("query", json!({ "completed": false })).await;
+        assert_eq!(text(&query)[0]["task_gid"], "t1");
+
+        saved::save_query(
+            &db,
+            "open",
+            Some("Open tasks"),
+            &saved::QueryFilters {
+                completed: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let listed = call("saved_query", json!({})).await;
+        assert_eq!(text(&listed)[0]["description"], "Open tasks");
+        let run = call("saved_query", json!({ "name": "open" })).await;
+        assert_eq!(text(&run)[0]["task_gid"], "t1");
+        let missing = call("saved_query", json!({ "name": "closed" })).await;
+        assert_eq!(missing["result"]["isError"], true);
+
+        let sql = call(
+            "sql",
+            json!({ "sql": "SELECT COUNT(*) AS n FROM fact_tasks" }),
+        )
+        .await;
+        assert_eq!(text(&sql)[0]["n"], 1);
+
+        // Tool failures are results the assistant can read, not protocol errors
+        let bad = call("sql", json!({ "sql": "DELETE FROM fact_tasks" })).await;
+        assert_eq!(bad["result"]["isError"], true);
+        let missing = call("metrics", json!({ "entity_type": "project" })).await;
+        assert_eq!(missing["result"]["isError"], true);
+
+        let unknown = call("nope", json!({})).await;
+        assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+    }
+}
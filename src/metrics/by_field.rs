@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use rusqlite::params;
+
+use super::percentiles_from_days;
+use super::types::{FieldValueMetrics, HealthMetrics, ThroughputMetrics};
+
+/// Label for tasks that have no value for the field.
+pub const NO_VALUE: &str = "(no value)";
+
+/// Find a custom field by GID, or by name among fields used on the
+/// project's tasks. Returns `(field_gid, name, field_type)`.
+pub(crate) fn resolve_field(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    field: &str,
+) -> std::result::Result<Option<(String, String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT f.field_gid, f.name, f.field_type
+         FROM dim_custom_fields f
+         WHERE f.field_gid = ?1
+            OR (LOWER(f.name) = LOWER(?1) AND EXISTS (
+                SELECT 1 FROM fact_task_custom_fields tcf
+                JOIN bridge_task_projects btp ON btp.task_gid = tcf.task_gid
+                WHERE tcf.field_gid = f.field_gid AND btp.project_gid = ?2))
+         ORDER BY f.field_gid = ?1 DESC
+         LIMIT 1",
+    )?;
+    let mut rows = stmt.query(params![field, project_gid])?;
+    match rows.next()? {
+        Some(row) => Ok(Some((row.get(0)?, row.get(1)?, row.get(2)?))),
+        None => Ok(None),
+    }
+}
+
+#[derive(Default)]
+struct Bucket {
+    throughput: ThroughputMetrics,
+    health: HealthMetrics,
+    lead_time_days: Vec<i32>,
+}
+
+/// Throughput, health, and lead time for a project's tasks, grouped by
+/// their value of the enum field `field_gid`.
+pub(crate) fn compute_field_groups_sql(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    field_gid: &str,
+    start: &str,
+    end: &str,
+) -> std::result::Result<Vec<FieldValueMetrics>, rusqlite::Error> {
+    const FROM: &str = "FROM fact_tasks t
+         JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+         LEFT JOIN fact_task_custom_fields tcf
+           ON tcf.task_gid = t.task_gid AND tcf.field_gid = ?2
+         WHERE btp.project_gid = ?1 AND t.is_deleted = 0";
+
+    let mut buckets: HashMap<Option<String>, Bucket> = HashMap::new();
+
+    let sql = format!(
+        "SELECT tcf.enum_value_gid, COUNT(*) {FROM}
+           AND t.created_date_key >= ?3 AND t.created_date_key <= ?4
+         GROUP BY tcf.enum_value_gid"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![project_gid, field_gid, start, end])?;
+    while let Some(row) = rows.next()? {
+        let bucket = buckets.entry(row.get(0)?).or_default();
+        bucket.throughput.tasks_created = row.get::<_, i64>(1)? as u64;
+    }
+
+    let sql = format!(
+        "SELECT tcf.enum_value_gid, COUNT(*), SUM(t.resource_subtype = 'milestone') {FROM}
+           AND t.is_completed = 1
+           AND t.completed_date_key >= ?3 AND t.completed_date_key <= ?4
+         GROUP BY tcf.enum_value_gid"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![project_gid, field_gid, start, end])?;
+    while let Some(row) = rows.next()? {
+        let bucket = buckets.entry(row.get(0)?).or_default();
+        bucket.throughput.tasks_completed = row.get::<_, i64>(1)? as u64;
+        bucket.throughput.milestones_completed = row.get::<_, i64>(2)? as u64;
+    }
+
+    let sql = format!(
+        "SELECT tcf.enum_value_gid,
+            SUM(CASE WHEN t.due_on < date('now') AND t.due_on IS NOT NULL THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.assignee_gid IS NULL THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.modified_at < date('now', '-14 days') THEN 1 ELSE 0 END),
+            COUNT(*)
+         {FROM} AND t.is_completed = 0
+         GROUP BY tcf.enum_value_gid"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![project_gid, field_gid])?;
+    while let Some(row) = rows.next()? {
+        let bucket = buckets.entry(row.get(0)?).or_default();
+        let h = &mut bucket.health;
+        h.overdue_count = row.get::<_, i64>(1)? as u64;
+        h.unassigned_count = row.get::<_, i64>(2)? as u64;
+        h.stale_count = row.get::<_, i64>(3)? as u64;
+        h.total_open = row.get::<_, i64>(4)? as u64;
+        if h.total_open > 0 {
+            h.overdue_pct = h.overdue_count as f64 / h.total_open as f64 * 100.0;
+            h.unassigned_pct = h.unassigned_count as f64 / h.total_open as f64 * 100.0;
+        }
+    }
+
+    let sql = format!(
+        "SELECT tcf.enum_value_gid, t.days_to_complete {FROM}
+           AND t.is_completed = 1 AND t.days_to_complete IS NOT NULL
+           AND t.completed_date_key >= ?3 AND t.completed_date_key <= ?4
+         ORDER BY t.days_to_complete"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![project_gid, field_gid, start, end])?;
+    while let Some(row) = rows.next()? {
+        let bucket = buckets.entry(row.get(0)?).or_default();
+        bucket.lead_time_days.push(row.get(1)?);
+    }
+
+    let mut stmt =
+        conn.prepare("SELECT option_gid, name FROM dim_enum_options WHERE field_gid = ?1")?;
+    let names: HashMap<String, Option<String>> = stmt
+        .query_map([field_gid], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<_, _>>()?;
+
+    let mut groups: Vec<FieldValueMetrics> = buckets
+        .into_iter()
+        .map(|(option_gid, mut bucket)| {
+            let value = match &option_gid {
+                Some(gid) => names.get(gid).cloned().flatten().unwrap_or(gid.clone()),
+                None => NO_VALUE.to_string(),
+            };
+            bucket.throughput.net_new =
+                bucket.throughput.tasks_created as i64 - bucket.throughput.tasks_completed as i64;
+            FieldValueMetrics {
+                option_gid,
+                value,
+                throughput: bucket.throughput,
+                health: bucket.health,
+                lead_time: percentiles_from_days(&bucket.lead_time_days),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        a.option_gid
+            .is_none()
+            .cmp(&b.option_gid.is_none())
+            .then(
+                b.throughput
+                    .tasks_completed
+                    .cmp(&a.throughput.tasks_completed),
+            )
+            .then(b.health.total_open.cmp(&a.health.total_open))
+            .then(a.value.cmp(&b.value))
+    });
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::compute_project_metrics_by_field;
+    use crate::query::period::Period;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_metrics_grouped_by_enum_field() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('f1', 'Priority', 'enum', datetime('now'));
+                     INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('f2', 'Estimate', 'number', datetime('now'));
+                     INSERT INTO dim_enum_options (field_gid, option_gid, name, cached_at)
+                     VALUES ('f1', 'high', 'High', datetime('now'));
+                     INSERT INTO dim_enum_options (field_gid, option_gid, name, cached_at)
+                     VALUES ('f1', 'low', 'Low', datetime('now'));
+
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_date_key,
+                         created_at, created_date_key, days_to_complete, is_subtask, cached_at)
+                     VALUES ('t1', 'a', 1, '2025-01-10', '2025-01-02', '2025-01-02', 8, 0, datetime('now')),
+                            ('t2', 'b', 1, '2025-01-20', '2025-01-05', '2025-01-05', 15, 0, datetime('now')),
+                            ('t3', 'c', 0, NULL, '2025-01-06', '2025-01-06', NULL, 0, datetime('now')),
+                            ('t4', 'd', 0, NULL, '2025-01-07', '2025-01-07', NULL, 0, datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p1'), ('t3', 'p1'), ('t4', 'p1');
+                     INSERT INTO fact_task_custom_fields (task_gid, field_gid, enum_value_gid, display_value)
+                     VALUES ('t1', 'f1', 'high', 'High'), ('t2', 'f1', 'high', 'High'),
+                            ('t3', 'f1', 'low', 'Low'), ('t1', 'f2', NULL, '3');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let period = Period::parse("2025-Q1").unwrap();
+        let m = compute_project_metrics_by_field(&db, "p1", "priority", &period)
+            .await
+            .unwrap();
+        assert_eq!(m.field_gid, "f1");
+        assert_eq!(m.project_name.as_deref(), Some("Roadmap"));
+
+        let values: Vec<&str> = m.groups.iter().map(|g| g.value.as_str()).collect();
+        assert_eq!(values, vec!["High", "Low", NO_VALUE]);
+        let high = &m.groups[0];
+        assert_eq!(high.throughput.tasks_created, 2);
+        assert_eq!(high.throughput.tasks_completed, 2);
+        assert_eq!(high.lead_time.median_days_to_complete, Some(11.5));
+        assert_eq!(m.groups[1].health.total_open, 1);
+        assert_eq!(m.groups[2].health.total_open, 1);
+
+        // Only enum fields can be grouped by
+        assert!(
+            compute_project_metrics_by_field(&db, "p1", "Estimate", &period)
+                .await
+                .is_err()
+        );
+        assert!(
+            compute_project_metrics_by_field(&db, "p1", "Missing", &period)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Period-over-period change in headline metrics.
+//!
+//! A period still in progress is compared with the same stretch of the
+//! prior period (see [`Period::prior_period_to_date`]), so half a quarter
+//! isn't measured against a whole one.
+
+use serde::Serialize;
+
+use super::types::{
+    CollaborationMetrics, HealthMetrics, LeadTimeMetrics, PortfolioMetrics, ProjectMetrics,
+    TeamMetrics, ThroughputMetrics, UserMetrics,
+};
+use crate::error::Result;
+use crate::query::period::Period;
+use crate::storage::Database;
+
+/// One metric in a period and the prior one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricDelta {
+    pub metric: String,
+    pub current: f64,
+    pub previous: f64,
+    /// Percentage change from `previous`, to one decimal place. `None` when
+    /// `previous` is zero.
+    pub change_pct: Option<f64>,
+}
+
+/// An entity's metrics for a period and the prior one, and how the headline
+/// figures moved between them.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodComparison<M> {
+    pub period_key: String,
+    /// First and last day (`YYYY-MM-DD`) of the prior period compared against.
+    pub prior_start: String,
+    pub prior_end: String,
+    pub current: M,
+    pub prior: M,
+    /// Headline metrics with a value in both periods.
+    pub deltas: Vec<MetricDelta>,
+}
+
+impl<M> PeriodComparison<M> {
+    /// The deltas as one line each, e.g.
+    /// `- tasks_completed: 13 (prior 10, +30.0%)`, for prompts.
+    pub fn describe_deltas(&self) -> String {
+        let mut lines = vec![format!(
+            "Compared with {} to {}:",
+            self.prior_start, self.prior_end
+        )];
+        for d in &self.deltas {
+            let change = match d.change_pct {
+                Some(pct) => format!("{pct:+.1}%"),
+                None => "no prior value".to_string(),
+            };
+            lines.push(format!(
+                "- {}: {} (prior {}, {change})",
+                d.metric,
+                format_value(d.current),
+                format_value(d.previous)
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.1}")
+    }
+}
+
+/// The period `period` is compared against: the same stretch of the prior
+/// period while `period` is in progress, otherwise the whole prior period.
+pub fn prior_period(period: &Period) -> Period {
+    if period.is_current() {
+        period.prior_period_to_date(chrono::Local::now().date_naive())
+    } else {
+        period.previous()
+    }
+}
+
+/// Metrics whose movement is worth calling out, by name.
+trait Headline {
+    fn headline(&self) -> Vec<(&'static str, Option<f64>)>;
+}
+
+fn shared_headline(
+    throughput: &ThroughputMetrics,
+    lead_time: &LeadTimeMetrics,
+    collaboration: &CollaborationMetrics,
+    health: Option<&HealthMetrics>,
+) -> Vec<(&'static str, Option<f64>)> {
+    let mut metrics = vec![
+        ("tasks_completed", Some(throughput.tasks_completed as f64)),
+        ("tasks_created", Some(throughput.tasks_created as f64)),
+        ("net_new", Some(throughput.net_new as f64)),
+        (
+            "milestones_completed",
+            Some(throughput.milestones_completed as f64),
+        ),
+        ("median_days_to_complete", lead_time.median_days_to_complete),
+        ("total_comments", Some(collaboration.total_comments as f64)),
+    ];
+    if let Some(health) = health {
+        metrics.push(("overdue_count", Some(health.overdue_count as f64)));
+        metrics.push(("total_open", Some(health.total_open as f64)));
+    }
+    metrics
+}
+
+impl Headline for UserMetrics {
+    fn headline(&self) -> Vec<(&'static str, Option<f64>)> {
+        shared_headline(&self.throughput, &self.lead_time, &self.collaboration, None)
+    }
+}
+
+impl Headline for ProjectMetrics {
+    fn headline(&self) -> Vec<(&'static str, Option<f64>)> {
+        shared_headline(
+            &self.throughput,
+            &self.lead_time,
+            &self.collaboration,
+            Some(&self.health),
+        )
+    }
+}
+
+impl Headline for PortfolioMetrics {
+    fn headline(&self) -> Vec<(&'static str, Option<f64>)> {
+        shared_headline(
+            &self.throughput,
+            &self.lead_time,
+            &self.collaboration,
+            Some(&self.health),
+        )
+    }
+}
+
+impl Headline for TeamMetrics {
+    fn headline(&self) -> Vec<(&'static str, Option<f64>)> {
+        shared_headline(
+            &self.throughput,
+            &self.lead_time,
+            &self.collaboration,
+            Some(&self.health),
+        )
+    }
+}
+
+fn compare<M: Headline>(
+    period: &Period,
+    prior: &Period,
+    current: M,
+    before: M,
+) -> PeriodComparison<M> {
+    let deltas = current
+        .headline()
+        .into_iter()
+        .zip(before.headline())
+        .filter_map(|((metric, current), (_, previous))| {
+            let (current, previous) = (current?, previous?);
+            let change_pct = (previous != 0.0)
+                .then(|| ((current - previous) / previous.abs() * 1000.0).round() / 10.0);
+            Some(MetricDelta {
+                metric: metric.to_string(),
+                current,
+                previous,
+                change_pct,
+            })
+        })
+        .collect();
+    let (prior_start, prior_end) = prior.date_range();
+    PeriodComparison {
+        period_key: period.to_key(),
+        prior_start: prior_start.format("%Y-%m-%d").to_string(),
+        prior_end: prior_end.format("%Y-%m-%d").to_string(),
+        current,
+        prior: before,
+        deltas,
+    }
+}
+
+/// Compare a user's metrics for a period with the prior period.
+pub async fn compare_user_metrics(
+    db: &Database,
+    user_gid: &str,
+    period: &Period,
+) -> Result<PeriodComparison<UserMetrics>> {
+    let prior = prior_period(period);
+    let current = super::compute_user_metrics(db, user_gid, period).await?;
+    let before = super::compute_user_metrics(db, user_gid, &prior).await?;
+    Ok(compare(period, &prior, current, before))
+}
+
+/// Compare a project's metrics for a period with the prior period.
+pub async fn compare_project_metrics(
+    db: &Database,
+    project_gid: &str,
+    period: &Period,
+) -> Result<PeriodComparison<ProjectMetrics>> {
+    let prior = prior_period(period);
+    let current = super::compute_project_metrics(db, project_gid, period).await?;
+    let before = super::compute_project_metrics(db, project_gid, &prior).await?;
+    Ok(compare(period, &prior, current, before))
+}
+
+/// Compare a portfolio's metrics for a period with the prior period.
+pub async fn compare_portfolio_metrics(
+    db: &Database,
+    portfolio_gid: &str,
+    period: &Period,
+) -> Result<PeriodComparison<PortfolioMetrics>> {
+    let prior = prior_period(period);
+    let current = super::compute_portfolio_metrics(db, portfolio_gid, period).await?;
+    let before = super::compute_portfolio_metrics(db, portfolio_gid, &prior).await?;
+    Ok(compare(period, &prior, current, before))
+}
+
+/// Compare a team's metrics for a period with the prior period.
+pub async fn compare_team_metrics(
+    db: &Database,
+    team_gid: &str,
+    period: &Period,
+) -> Result<PeriodComparison<TeamMetrics>> {
+    let prior = prior_period(period);
+    let current = super::compute_team_metrics(db, team_gid, period).await?;
+    let before = super::compute_team_metrics(db, team_gid, &prior).await?;
+    Ok(compare(period, &prior, current, before))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compare_project_metrics() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, completed_date_key, created_at, created_date_key, modified_at, is_subtask, days_to_complete, is_overdue, cached_at)
+                     VALUES ('t1', 'Q4 one', 1, '2024-11-10T00:00:00Z', '2024-11-10', '2024-11-01T00:00:00Z', '2024-11-01', '2024-11-10T00:00:00Z', 0, 9, 0, datetime('now')),
+                            ('t2', 'Q4 two', 1, '2024-12-10T00:00:00Z', '2024-12-10', '2024-12-01T00:00:00Z', '2024-12-01', '2024-12-10T00:00:00Z', 0, 9, 0, datetime('now')),
+                            ('t3', 'Q1 one', 1, '2025-01-10T00:00:00Z', '2025-01-10', '2025-01-05T00:00:00Z', '2025-01-05', '2025-01-10T00:00:00Z', 0, 5, 0, datetime('now')),
+                            ('t4', 'Q1 two', 1, '2025-02-10T00:00:00Z', '2025-02-10', '2025-02-05T00:00:00Z', '2025-02-05', '2025-02-10T00:00:00Z', 0, 5, 0, datetime('now')),
+                            ('t5', 'Q1 three', 1, '2025-03-10T00:00:00Z', '2025-03-10', '2025-03-05T00:00:00Z', '2025-03-05', '2025-03-10T00:00:00Z', 0, 5, 0, datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p1'), ('t3', 'p1'), ('t4', 'p1'), ('t5', 'p1');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let comparison = compare_project_metrics(&db, "p1", &Period::Quarter(2025, 1))
+            .await
+            .unwrap();
+        assert_eq!(
+            (
+                comparison.prior_start.as_str(),
+                comparison.prior_end.as_str()
+            ),
+            ("2024-10-01", "2024-12-31")
+        );
+        let delta = |metric: &str| {
+            comparison
+                .deltas
+                .iter()
+                .find(|d| d.metric == metric)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(
+            delta("tasks_completed"),
+            MetricDelta {
+                metric: "tasks_completed".to_string(),
+                current: 3.0,
+                previous: 2.0,
+                change_pct: Some(50.0),
+            }
+        );
+        assert_eq!(delta("median_days_to_complete").change_pct, Some(-44.4));
+        assert_eq!(delta("total_comments").change_pct, None);
+
+        let described = comparison.describe_deltas();
+        assert!(described.starts_with("Compared with 2024-10-01 to 2024-12-31:"));
+        assert!(described.contains("- tasks_completed: 3 (prior 2, +50.0%)"));
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use super::types::{CycleTimeMetrics, SectionDwell};
+use crate::storage::repository;
+
+/// Config key listing the section names that start the cycle clock (comma-separated).
+pub const CYCLE_START_SECTIONS_KEY: &str = "cycle_start_sections";
+
+/// Sections that start the cycle clock when `cycle_start_sections` is not set.
+pub const DEFAULT_CYCLE_START_SECTIONS: &str = "In Progress,Doing";
+
+/// Cycle time and per-section dwell for a single completed task.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct TaskCycle {
+    pub(super) cycle_days: Option<f64>,
+    /// Days spent in each section visited before completion, in order.
+    pub(super) dwell: Vec<(String, f64)>,
+}
+
+/// Compute cycle time for tasks in a project completed within `[start, end]`.
+pub(crate) fn compute_cycle_time_sql(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    start: &str,
+    end: &str,
+) -> std::result::Result<CycleTimeMetrics, rusqlite::Error> {
+    let start_sections = repository::get_config(conn, CYCLE_START_SECTIONS_KEY)?
+        .unwrap_or_else(|| DEFAULT_CYCLE_START_SECTIONS.to_string());
+    let start_sections: Vec<String> = start_sections
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let cycles = completed_task_cycles(conn, project_gid, start, end, &start_sections)?;
+    Ok(cycle_metrics_from(&cycles))
+}
+
+/// Cycle and dwell for each task in a project completed within `[start, end]`
+/// that has section history.
+pub(super) fn completed_task_cycles(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    start: &str,
+    end: &str,
+    start_sections: &[String],
+) -> std::result::Result<Vec<TaskCycle>, rusqlite::Error> {
+    // Transitions are only counted when they happened in this project
+    // (or the story text did not name a project).
+    let mut stmt = conn.prepare(
+        "SELECT t.task_gid, t.completed_at, st.to_section, st.transitioned_at
+         FROM fact_tasks t
+         JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+         JOIN dim_projects p ON p.project_gid = btp.project_gid
+         JOIN fact_section_transitions st ON st.task_gid = t.task_gid
+         WHERE btp.project_gid = ?1 AND t.is_deleted = 0
+           AND t.is_completed = 1 AND t.completed_at IS NOT NULL
+           AND t.completed_date_key >= ?2 AND t.completed_date_key <= ?3
+           AND (st.project_name IS NULL OR st.project_name = p.name)
+         ORDER BY t.task_gid, st.transitioned_at",
+    )?;
+    let rows: Vec<(String, String, String, String)> = stmt
+        .query_map(rusqlite::params![project_gid, start, end], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut cycles = Vec::new();
+    let mut i = 0;
+    while i < rows.len() {
+        let task_gid = &rows[i].0;
+        let completed_at = parse_timestamp(&rows[i].1);
+        let mut transitions = Vec::new();
+        while i < rows.len() && rows[i].0 == *task_gid {
+            if let Some(at) = parse_timestamp(&rows[i].3) {
+                transitions.push((rows[i].2.clone(), at));
+            }
+            i += 1;
+        }
+        if let Some(completed_at) = completed_at {
+            cycles.push(task_cycle(&transitions, completed_at, start_sections));
+        }
+    }
+
+    Ok(cycles)
+}
+
+/// Walk a task's chronological section transitions up to completion.
+fn task_cycle(
+    transitions: &[(String, NaiveDateTime)],
+    completed_at: NaiveDateTime,
+    start_sections: &[String],
+) -> TaskCycle {
+    let cycle_days = transitions
+        .iter()
+        .find(|(section, _)| start_sections.contains(&section.trim().to_lowercase()))
+        .map(|(_, at)| days_between(*at, completed_at))
+        .filter(|d| *d >= 0.0);
+
+    let dwell = transitions
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, entered))| *entered <= completed_at)
+        .map(|(idx, (section, entered))| {
+            let left = transitions
+                .get(idx + 1)
+                .map(|(_, at)| *at)
+                .unwrap_or(completed_at)
+                .min(completed_at);
+            (section.clone(), days_between(*entered, left).max(0.0))
+        })
+        .collect();
+
+    TaskCycle { cycle_days, dwell }
+}
+
+fn cycle_metrics_from(cycles: &[TaskCycle]) -> CycleTimeMetrics {
+    let mut days: Vec<f64> = cycles.iter().filter_map(|c| c.cycle_days).collect();
+    days.sort_by(|a, b| a.total_cmp(b));
+
+    let mut by_section: HashMap<&str, Vec<f64>> = HashMap::new();
+    for (section, d) in cycles.iter().flat_map(|c| c.dwell.iter()) {
+        by_section.entry(section.as_str()).or_default().push(*d);
+    }
+    let mut section_dwell: Vec<SectionDwell> = by_section
+        .into_iter()
+        .map(|(section, mut d)| {
+            d.sort_by(|a, b| a.total_cmp(b));
+            SectionDwell {
+                section_name: section.to_string(),
+                visits: d.len() as u64,
+                avg_days: d.iter().sum::<f64>() / d.len() as f64,
+                median_days: median(&d).unwrap_or(0.0),
+            }
+        })
+        .collect();
+    section_dwell.sort_by(|a, b| {
+        b.avg_days
+            .total_cmp(&a.avg_days)
+            .then_with(|| a.section_name.cmp(&b.section_name))
+    });
+
+    if days.is_empty() {
+        return CycleTimeMetrics {
+            section_dwell,
+            ..Default::default()
+        };
+    }
+
+    let p90_idx = ((days.len() as f64) * 0.9).ceil() as usize;
+    let p90_idx = p90_idx.min(days.len()).max(1) - 1;
+
+    CycleTimeMetrics {
+        tasks_measured: days.len() as u64,
+        avg_days: Some(days.iter().sum::<f64>() / days.len() as f64),
+        median_days: median(&days),
+        p90_days: Some(days[p90_idx]),
+        section_dwell,
+    }
+}
+
+/// Median of an already-sorted slice.
+fn median(sorted: &[f64]) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let mid = sorted.len() / 2;
+    #[allow(clippy::manual_is_multiple_of)]
+    if sorted.len() % 2 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+pub(super) fn days_between(from: NaiveDateTime, to: NaiveDateTime) -> f64 {
+    (to - from).num_seconds() as f64 / 86_400.0
+}
+
+/// Parse an Asana timestamp (RFC 3339) or bare date (midnight UTC).
+pub(super) fn parse_timestamp(s: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.naive_utc())
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::compute_project_metrics;
+    use crate::query::period::Period;
+    use crate::storage::{transitions, Database};
+
+    fn ts(s: &str) -> NaiveDateTime {
+        parse_timestamp(s).unwrap()
+    }
+
+    #[test]
+    fn test_task_cycle_and_dwell() {
+        let start = vec!["in progress".to_string()];
+        let transitions = vec![
+            ("Backlog".to_string(), ts("2025-01-01T00:00:00Z")),
+            ("In Progress".to_string(), ts("2025-01-03T00:00:00Z")),
+            ("Review".to_string(), ts("2025-01-06T12:00:00Z")),
+        ];
+        let cycle = task_cycle(&transitions, ts("2025-01-07T00:00:00Z"), &start);
+        assert_eq!(cycle.cycle_days, Some(4.0));
+        assert_eq!(
+            cycle.dwell,
+            vec![
+                ("Backlog".to_string(), 2.0),
+                ("In Progress".to_string(), 3.5),
+                ("Review".to_string(), 0.5),
+            ]
+        );
+
+        // Never entered an in-progress section: dwell only
+        let cycle = task_cycle(&transitions[..1], ts("2025-01-02T00:00:00Z"), &start);
+        assert_eq!(cycle.cycle_days, None);
+        assert_eq!(cycle.dwell.len(), 1);
+    }
+
+    #[test]
+    fn test_cycle_metrics_from() {
+        let cycles = vec![
+            TaskCycle {
+                cycle_days: Some(2.0),
+                dwell: vec![("Doing".to_string(), 2.0)],
+            },
+            TaskCycle {
+                cycle_days: Some(4.0),
+                dwell: vec![("Doing".to_string(), 3.0), ("Review".to_string(), 1.0)],
+            },
+            TaskCycle {
+                cycle_days: None,
+                dwell: vec![],
+            },
+        ];
+        let m = cycle_metrics_from(&cycles);
+        assert_eq!(m.tasks_measured, 2);
+        assert_eq!(m.avg_days, Some(3.0));
+        assert_eq!(m.median_days, Some(3.0));
+        assert_eq!(m.p90_days, Some(4.0));
+        assert_eq!(m.section_dwell[0].section_name, "Doing");
+        assert_eq!(m.section_dwell[0].visits, 2);
+        assert_eq!(m.section_dwell[0].avg_days, 2.5);
+
+        let empty = cycle_metrics_from(&[]);
+        assert!(empty.avg_days.is_none());
+        assert!(empty.section_dwell.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_project_cycle_time_from_stories() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Task', 1, '2025-01-10T00:00:00Z', '2025-01-10', '2025-01-01T00:00:00Z', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t1', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('s1', 't1', 'moved this task from \"Backlog\" to \"In Progress\" in Roadmap', 'section_changed', '2025-01-04T00:00:00Z', '2025-01-04', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('s2', 't1', 'moved this task from \"In Progress\" to \"Review\" in Other Project', 'section_changed', '2025-01-05T00:00:00Z', '2025-01-05', datetime('now'));",
+                )?;
+                transitions::rebuild_section_transitions(conn, "t1")?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let m = compute_project_metrics(&db, "p1", &Period::Month(2025, 1))
+            .await
+            .unwrap();
+        assert_eq!(m.lead_time.avg_days_to_complete, None);
+        assert_eq!(m.cycle_time.tasks_measured, 1);
+        assert_eq!(m.cycle_time.avg_days, Some(6.0));
+        // The move in another project is ignored
+        assert_eq!(m.cycle_time.section_dwell.len(), 1);
+        assert_eq!(m.cycle_time.section_dwell[0].avg_days, 6.0);
+    }
+}
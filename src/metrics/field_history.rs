@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::params;
+
+use super::cycle_time::{days_between, parse_timestamp};
+use super::types::FieldValueDwell;
+
+/// One task's recorded changes to a field, oldest first.
+struct TaskHistory {
+    completed_at: Option<NaiveDateTime>,
+    /// `(changed_at, to_value)`; `None` when the field was cleared.
+    changes: Vec<(NaiveDateTime, Option<String>)>,
+}
+
+/// Days each value of `field_name` was held by the project's tasks within
+/// `[start, end]`. A value is held from the change that set it until the
+/// next change, the task's completion, or now.
+pub(crate) fn compute_field_dwell_sql(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    field_name: &str,
+    start: &str,
+    end: &str,
+) -> std::result::Result<Vec<FieldValueDwell>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT t.task_gid, t.completed_at, c.to_value, c.changed_at
+         FROM fact_tasks t
+         JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+         JOIN fact_custom_field_changes c ON c.task_gid = t.task_gid
+         WHERE btp.project_gid = ?1 AND t.is_deleted = 0
+           AND c.field_name = ?2 COLLATE NOCASE
+         ORDER BY t.task_gid, c.changed_at",
+    )?;
+    let mut histories: Vec<TaskHistory> = Vec::new();
+    let mut last_task: Option<String> = None;
+    let mut rows = stmt.query(params![project_gid, field_name])?;
+    while let Some(row) = rows.next()? {
+        let task_gid: String = row.get(0)?;
+        let Some(changed_at) = parse_timestamp(&row.get::<_, String>(3)?) else {
+            continue;
+        };
+        if last_task.as_ref() != Some(&task_gid) {
+            histories.push(TaskHistory {
+                completed_at: row
+                    .get::<_, Option<String>>(1)?
+                    .and_then(|s| parse_timestamp(&s)),
+                changes: Vec::new(),
+            });
+            last_task = Some(task_gid);
+        }
+        if let Some(h) = histories.last_mut() {
+            h.changes.push((changed_at, row.get(2)?));
+        }
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+    let (Some(start), Some(end)) = (day(start), day(end)) else {
+        return Ok(Vec::new());
+    };
+    let period_start = start.and_time(chrono::NaiveTime::MIN);
+    let period_end = (end.and_time(chrono::NaiveTime::MIN) + chrono::Duration::days(1)).min(now);
+    Ok(dwell_from(&histories, period_start, period_end, now))
+}
+
+fn dwell_from(
+    histories: &[TaskHistory],
+    period_start: NaiveDateTime,
+    period_end: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Vec<FieldValueDwell> {
+    let mut by_value: HashMap<&str, FieldValueDwell> = HashMap::new();
+    for h in histories {
+        let held_until = h.completed_at.unwrap_or(now);
+        let mut task_days: HashMap<&str, f64> = HashMap::new();
+        for (i, (changed_at, value)) in h.changes.iter().enumerate() {
+            let Some(value) = value.as_deref() else {
+                continue;
+            };
+            let until = h
+                .changes
+                .get(i + 1)
+                .map_or(held_until, |(next, _)| *next)
+                .min(held_until);
+            let from = (*changed_at).max(period_start);
+            let to = until.min(period_end);
+            if to > from {
+                *task_days.entry(value).or_default() += days_between(from, to);
+            }
+        }
+        for (value, days) in task_days {
+            let d = by_value.entry(value).or_insert_with(|| empty_dwell(value));
+            d.tasks += 1;
+            d.total_days += days;
+            d.max_days = d.max_days.max(days);
+        }
+        if h.completed_at.is_none() {
+            if let Some((_, Some(value))) = h.changes.last() {
+                by_value
+                    .entry(value.as_str())
+                    .or_insert_with(|| empty_dwell(value))
+                    .open_now += 1;
+            }
+        }
+    }
+
+    let mut values: Vec<FieldValueDwell> = by_value
+        .into_values()
+        .map(|mut d| {
+            if d.tasks > 0 {
+                d.avg_days = d.total_days / d.tasks as f64;
+            }
+            d
+        })
+        .collect();
+    values.sort_by(|a, b| {
+        b.total_days
+            .total_cmp(&a.total_days)
+            .then_with(|| a.value.cmp(&b.value))
+    });
+    values
+}
+
+fn empty_dwell(value: &str) -> FieldValueDwell {
+    FieldValueDwell {
+        value: value.to_string(),
+        tasks: 0,
+        total_days: 0.0,
+        avg_days: 0.0,
+        max_days: 0.0,
+        open_now: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::compute_field_history;
+    use crate::query::period::Period;
+    use crate::storage::{field_changes, Database};
+
+    #[tokio::test]
+    async fn test_field_history_from_stories() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Board', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, completed_date_key,
+                         created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'shipped', 1, '2025-01-21T00:00:00Z', '2025-01-21', '2025-01-01T00:00:00Z', '2025-01-01', datetime('now')),
+                            ('t2', 'stuck', 0, NULL, NULL, '2025-03-01T00:00:00Z', '2025-03-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t1', 'p1'), ('t2', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('s1', 't1', 'changed Status to \"In Progress\"', 'enumcustomfieldchanged', '2025-01-01T00:00:00Z', '2025-01-01', datetime('now')),
+                            ('s2', 't1', 'changed Status from \"In Progress\" to \"Blocked\"', 'enumcustomfieldchanged', '2025-01-11T00:00:00Z', '2025-01-11', datetime('now')),
+                            ('s3', 't1', 'changed Status from \"Blocked\" to \"In Progress\"', 'enumcustomfieldchanged', '2025-01-16T00:00:00Z', '2025-01-16', datetime('now')),
+                            ('s4', 't2', 'changed Status to Blocked', 'enumcustomfieldchanged', '2025-03-22T00:00:00Z', '2025-03-22', datetime('now')),
+                            ('s5', 't2', 'changed Priority to High', 'enumcustomfieldchanged', '2025-03-22T00:00:00Z', '2025-03-22', datetime('now'));
+                     INSERT INTO app_config (key, value, updated_at)
+                     VALUES ('tracked_custom_fields', 'Status', datetime('now'));",
+                )?;
+                field_changes::rebuild_all_field_changes(conn)
+            })
+            .await
+            .unwrap();
+
+        let period = Period::parse("2025-Q1").unwrap();
+        let m = compute_field_history(&db, "p1", "status", &period)
+            .await
+            .unwrap();
+        assert_eq!(m.field_name, "Status");
+        let blocked = &m.values[0];
+        assert_eq!(blocked.value, "Blocked");
+        assert_eq!((blocked.tasks, blocked.open_now), (2, 1));
+        assert!((blocked.total_days - 15.0).abs() < 1e-9);
+        assert!((blocked.max_days - 10.0).abs() < 1e-9);
+        let in_progress = &m.values[1];
+        assert_eq!(in_progress.value, "In Progress");
+        assert!((in_progress.total_days - 15.0).abs() < 1e-9);
+
+        assert!(compute_field_history(&db, "p1", "Priority", &period)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Open-task health (overdue, unassigned, stale) as of a date.
+//!
+//! Health for today comes from the live task rows. For an earlier date it
+//! comes from the snapshot a sync took that day, or else is rebuilt from
+//! task creation and completion dates and story history, so metrics for a
+//! closed period stop changing as tasks move on. Rebuilt health uses each
+//! task's current due date and assignee, which Asana history doesn't give
+//! back reliably.
+
+use rusqlite::{params, Connection};
+
+use super::types::HealthMetrics;
+
+/// Days without activity before an open task counts as stale.
+const STALE_DAYS: i64 = 14;
+
+/// Health of `project_gid`'s open tasks (or all tasks) at the end of `end`
+/// (`YYYY-MM-DD`).
+pub(crate) fn compute_health_sql(
+    conn: &Connection,
+    project_gid: Option<&str>,
+    end: &str,
+) -> std::result::Result<HealthMetrics, rusqlite::Error> {
+    let today = chrono::Local::now()
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string();
+    health_as_of(conn, project_gid, end, &today)
+}
+
+fn health_as_of(
+    conn: &Connection,
+    project_gid: Option<&str>,
+    end: &str,
+    today: &str,
+) -> std::result::Result<HealthMetrics, rusqlite::Error> {
+    if end >= today {
+        return live_health(conn, project_gid, today);
+    }
+    if let Some(pgid) = project_gid {
+        if let Some(health) = snapshot_health(conn, pgid, end)? {
+            return Ok(health);
+        }
+    }
+    rebuilt_health(conn, project_gid, end)
+}
+
+fn project_filter(project_gid: Option<&str>) -> (&'static str, &'static str) {
+    if project_gid.is_some() {
+        (
+            "JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid",
+            " AND btp.project_gid = ?2",
+        )
+    } else {
+        ("", "")
+    }
+}
+
+/// Health from the task rows as they are now.
+fn live_health(
+    conn: &Connection,
+    project_gid: Option<&str>,
+    today: &str,
+) -> std::result::Result<HealthMetrics, rusqlite::Error> {
+    let (join, where_extra) = project_filter(project_gid);
+    let sql = format!(
+        "SELECT
+            SUM(CASE WHEN t.due_on < ?1 AND t.due_on IS NOT NULL THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.assignee_gid IS NULL THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.modified_at < date(?1, '-{STALE_DAYS} days') THEN 1 ELSE 0 END),
+            COUNT(*)
+         FROM fact_tasks t
+         {join}
+         WHERE t.is_completed = 0 AND t.is_deleted = 0{where_extra}"
+    );
+    query_health(conn, &sql, project_gid, today)
+}
+
+/// Health rebuilt from history: tasks created by `end` and not completed
+/// by then, stale if their last change or story before `end` is older than
+/// [`STALE_DAYS`].
+fn rebuilt_health(
+    conn: &Connection,
+    project_gid: Option<&str>,
+    end: &str,
+) -> std::result::Result<HealthMetrics, rusqlite::Error> {
+    let (join, where_extra) = project_filter(project_gid);
+    let sql = format!(
+        "SELECT
+            SUM(CASE WHEN t.due_on < ?1 AND t.due_on IS NOT NULL THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.assignee_gid IS NULL THEN 1 ELSE 0 END),
+            SUM(CASE WHEN MAX(
+                t.created_at,
+                COALESCE(CASE WHEN date(t.modified_at) <= ?1 THEN t.modified_at END, ''),
+                COALESCE((SELECT MAX(c.created_at) FROM fact_comments c
+                          WHERE c.task_gid = t.task_gid AND c.created_date_key <= ?1), '')
+            ) < date(?1, '-{STALE_DAYS} days') THEN 1 ELSE 0 END),
+            COUNT(*)
+         FROM fact_tasks t
+         {join}
+         WHERE t.created_date_key <= ?1 AND t.is_deleted = 0
+           AND (t.is_completed = 0 OR t.completed_date_key > ?1){where_extra}"
+    );
+    query_health(conn, &sql, project_gid, end)
+}
+
+fn query_health(
+    conn: &Connection,
+    sql: &str,
+    project_gid: Option<&str>,
+    date: &str,
+) -> std::result::Result<HealthMetrics, rusqlite::Error> {
+    let mut stmt = conn.prepare(sql)?;
+    stmt.raw_bind_parameter(1, date)?;
+    if let Some(pgid) = project_gid {
+        stmt.raw_bind_parameter(2, pgid)?;
+    }
+    let mut rows = stmt.raw_query();
+    let row = rows.next()?.unwrap();
+    Ok(health_from_counts(
+        row.get::<_, Option<i64>>(0)?.unwrap_or(0) as u64,
+        row.get::<_, Option<i64>>(1)?.unwrap_or(0) as u64,
+        row.get::<_, Option<i64>>(2)?.unwrap_or(0) as u64,
+        row.get::<_, i64>(3)? as u64,
+    ))
+}
+
+fn health_from_counts(overdue: u64, unassigned: u64, stale: u64, total_open: u64) -> HealthMetrics {
+    let pct = |n: u64| {
+        if total_open > 0 {
+            n as f64 / total_open as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+    HealthMetrics {
+        overdue_count: overdue,
+        unassigned_count: unassigned,
+        stale_count: stale,
+        total_open,
+        overdue_pct: pct(overdue),
+        unassigned_pct: pct(unassigned),
+    }
+}
+
+fn snapshot_health(
+    conn: &Connection,
+    project_gid: &str,
+    date_key: &str,
+) -> std::result::Result<Option<HealthMetrics>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT overdue_count, unassigned_count, stale_count, total_open
+         FROM fact_health_snapshots WHERE project_gid = ?1 AND date_key = ?2",
+    )?;
+    let mut rows = stmt.query(params![project_gid, date_key])?;
+    rows.next()?
+        .map(|row| {
+            Ok(health_from_counts(
+                row.get::<_, i64>(0)? as u64,
+                row.get::<_, i64>(1)? as u64,
+                row.get::<_, i64>(2)? as u64,
+                row.get::<_, i64>(3)? as u64,
+            ))
+        })
+        .transpose()
+}
+
+/// Record today's health for a project, replacing any earlier snapshot
+/// from today. Called after each project sync.
+pub fn record_health_snapshot(
+    conn: &Connection,
+    project_gid: &str,
+) -> std::result::Result<(), rusqlite::Error> {
+    let today = chrono::Local::now()
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string();
+    let health = live_health(conn, Some(project_gid), &today)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO fact_health_snapshots (
+            project_gid, date_key, overdue_count, unassigned_count, stale_count,
+            total_open, captured_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![
+            project_gid,
+            today,
+            health.overdue_count as i64,
+            health.unassigned_count as i64,
+            health.stale_count as i64,
+            health.total_open as i64,
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_health_as_of() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, due_on, created_at, created_date_key, modified_at, cached_at)
+                     VALUES ('t1', 'Done in March', 'u1', 1, '2025-03-05T00:00:00Z', '2025-03-05', '2025-01-20', '2025-01-02T00:00:00Z', '2025-01-02', '2025-03-05T00:00:00Z', datetime('now')),
+                            ('t2', 'Still open', NULL, 0, NULL, NULL, NULL, '2025-01-03T00:00:00Z', '2025-01-03', '2025-06-01T00:00:00Z', datetime('now')),
+                            ('t3', 'Created later', 'u1', 0, NULL, NULL, NULL, '2025-02-10T00:00:00Z', '2025-02-10', '2025-06-01T00:00:00Z', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p1'), ('t3', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'Working on it', 'comment', '2025-01-28T00:00:00Z', '2025-01-28', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let (january, live) = db
+            .reader()
+            .call(|conn| {
+                Ok::<_, rusqlite::Error>((
+                    health_as_of(conn, Some("p1"), "2025-01-31", "2025-07-01")?,
+                    health_as_of(conn, Some("p1"), "2025-07-01", "2025-07-01")?,
+                ))
+            })
+            .await
+            .unwrap();
+        // t1 was open, overdue, and recently commented on; t2 untouched since creation
+        assert_eq!(january.total_open, 2);
+        assert_eq!(january.overdue_count, 1);
+        assert_eq!(january.unassigned_count, 1);
+        assert_eq!(january.stale_count, 1);
+        assert_eq!(live.total_open, 2);
+        assert_eq!(live.overdue_count, 0);
+        assert_eq!(live.stale_count, 2);
+
+        // A snapshot from the day wins over the rebuilt figures
+        let january = db
+            .writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO fact_health_snapshots VALUES ('p1', '2025-01-31', 0, 0, 0, 5, '2025-01-31T18:00:00Z')",
+                    [],
+                )?;
+                health_as_of(conn, Some("p1"), "2025-01-31", "2025-07-01")
+            })
+            .await
+            .unwrap();
+        assert_eq!((january.total_open, january.overdue_count), (5, 0));
+    }
+}
@@ -0,0 +1,117 @@
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::storage::Database;
+
+/// Default look-back window for [`compute_hot_projects`].
+pub const DEFAULT_HOT_DAYS: u32 = 7;
+
+/// Recent activity on one project.
+#[derive(Debug, Clone, Serialize)]
+pub struct HotProject {
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    /// Tasks modified within the window.
+    pub tasks_modified: u64,
+    /// Comments posted within the window.
+    pub comments: u64,
+    pub last_activity_at: Option<String>,
+    /// Recency-weighted activity: each task change or comment counts 1.0
+    /// when it just happened, fading linearly to 0 at the edge of the window.
+    pub heat: f64,
+}
+
+/// Rank synced, unarchived, non-template projects by recent activity (task modifications
+/// plus comments in the last `days` days), hottest first.
+pub async fn compute_hot_projects(db: &Database, days: u32, limit: u32) -> Result<Vec<HotProject>> {
+    let days = days.max(1);
+    db.reader()
+        .call(move |conn| {
+            let sql = "
+                WITH activity AS (
+                    SELECT btp.project_gid, 1 AS is_task, t.modified_at AS at
+                    FROM fact_tasks t
+                    JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+                    WHERE t.is_deleted = 0 AND julianday(t.modified_at) >= julianday('now') - ?1
+                    UNION ALL
+                    SELECT btp.project_gid, 0, c.created_at
+                    FROM fact_comments c
+                    JOIN bridge_task_projects btp ON btp.task_gid = c.task_gid
+                    WHERE c.is_bot = 0 AND julianday(c.created_at) >= julianday('now') - ?1
+                )
+                SELECT a.project_gid, p.name, SUM(a.is_task), SUM(1 - a.is_task), MAX(a.at),
+                       SUM(MAX(0.0, 1.0 - (julianday('now') - julianday(a.at)) / ?1)) AS heat
+                FROM activity a
+                JOIN dim_projects p ON p.project_gid = a.project_gid
+                WHERE p.is_archived = 0 AND COALESCE(p.is_template, 0) = 0
+                GROUP BY a.project_gid
+                ORDER BY heat DESC, MAX(a.at) DESC
+                LIMIT ?2";
+            let mut stmt = conn.prepare(sql)?;
+            let projects = stmt
+                .query_map(rusqlite::params![days as f64, limit], |row| {
+                    Ok(HotProject {
+                        project_gid: row.get(0)?,
+                        project_name: row.get(1)?,
+                        tasks_modified: row.get::<_, i64>(2)? as u64,
+                        comments: row.get::<_, i64>(3)? as u64,
+                        last_activity_at: row.get(4)?,
+                        heat: row.get(5)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok::<Vec<HotProject>, rusqlite::Error>(projects)
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hot_projects_ranked_by_recent_activity() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, is_archived, cached_at)
+                     VALUES ('busy', 'Busy', 'w1', 0, datetime('now')),
+                            ('quiet', 'Quiet', 'w1', 0, datetime('now')),
+                            ('old', 'Old', 'w1', 0, datetime('now')),
+                            ('archived', 'Archived', 'w1', 1, datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, is_template, cached_at)
+                     VALUES ('tmpl', 'Template', 'w1', 1, datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, modified_at, cached_at)
+                     VALUES ('t1', 'a', '2025-01-01', '2025-01-01', strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '-1 hour'), datetime('now')),
+                            ('t2', 'b', '2025-01-01', '2025-01-01', strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '-2 hours'), datetime('now')),
+                            ('t3', 'c', '2025-01-01', '2025-01-01', strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '-6 days'), datetime('now')),
+                            ('t4', 'd', '2025-01-01', '2025-01-01', strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '-30 days'), datetime('now')),
+                            ('t5', 'e', '2025-01-01', '2025-01-01', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), datetime('now')),
+                            ('t6', 'f', '2025-01-01', '2025-01-01', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'busy'), ('t2', 'busy'), ('t3', 'quiet'), ('t4', 'old'), ('t5', 'archived'), ('t6', 'tmpl');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'hi', 'comment', strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '-3 hours'), '2025-01-01', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let hot = compute_hot_projects(&db, DEFAULT_HOT_DAYS, 10)
+            .await
+            .unwrap();
+        let gids: Vec<&str> = hot.iter().map(|p| p.project_gid.as_str()).collect();
+        assert_eq!(gids, vec!["busy", "quiet"]);
+        assert_eq!(hot[0].tasks_modified, 2);
+        assert_eq!(hot[0].comments, 1);
+        assert!(hot[0].heat > 2.9 && hot[0].heat <= 3.0);
+        assert!(hot[1].heat < 0.2);
+
+        assert_eq!(compute_hot_projects(&db, 7, 1).await.unwrap().len(), 1);
+        assert_eq!(compute_hot_projects(&db, 60, 10).await.unwrap().len(), 3);
+    }
+}
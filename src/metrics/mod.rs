@@ -1,10 +1,28 @@
+pub mod by_field;
+pub mod compare;
+pub mod cycle_time;
+pub mod field_history;
+pub mod health;
+pub mod hot;
+pub mod notability;
+pub mod onboarding;
+pub mod orphans;
+pub mod phases;
+pub mod recognition;
+pub mod sections;
+pub mod stats;
+pub mod topics;
 pub mod types;
+pub mod workload;
 
 pub use types::*;
 
+use self::health::compute_health_sql;
 use crate::error::Result;
+use crate::query::labels;
 use crate::query::period::Period;
-use crate::storage::Database;
+use crate::storage::{repository, Database};
+use crate::sync::DEFAULT_PORTFOLIO_DEPTH;
 
 /// Compute metrics for a user over a period.
 pub async fn compute_user_metrics(
@@ -12,9 +30,7 @@ pub async fn compute_user_metrics(
     user_gid: &str,
     period: &Period,
 ) -> Result<UserMetrics> {
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
+    let (from, until) = period.timestamp_bounds();
     let period_key = period.to_key();
     let user_gid = user_gid.to_string();
 
@@ -28,12 +44,10 @@ pub async fn compute_user_metrics(
                 )
                 .ok();
 
-            let throughput =
-                compute_throughput_sql(conn, Some(&user_gid), None, &start_str, &end_str)?;
-            let lead_time =
-                compute_lead_time_sql(conn, Some(&user_gid), None, &start_str, &end_str)?;
+            let throughput = compute_throughput_sql(conn, Some(&user_gid), None, &from, &until)?;
+            let lead_time = compute_lead_time_sql(conn, Some(&user_gid), None, &from, &until)?;
             let collaboration =
-                compute_collaboration_sql(conn, Some(&user_gid), None, &start_str, &end_str)?;
+                compute_collaboration_sql(conn, Some(&user_gid), None, &from, &until)?;
 
             Ok::<UserMetrics, rusqlite::Error>(UserMetrics {
                 user_gid,
@@ -57,6 +71,7 @@ pub async fn compute_project_metrics(
     let (start, end) = period.date_range();
     let start_str = start.format("%Y-%m-%d").to_string();
     let end_str = end.format("%Y-%m-%d").to_string();
+    let (from, until) = period.timestamp_bounds();
     let period_key = period.to_key();
     let project_gid = project_gid.to_string();
 
@@ -70,13 +85,15 @@ pub async fn compute_project_metrics(
                 )
                 .ok();
 
-            let throughput =
-                compute_throughput_sql(conn, None, Some(&project_gid), &start_str, &end_str)?;
+            let throughput = compute_throughput_sql(conn, None, Some(&project_gid), &from, &until)?;
             let health = compute_health_sql(conn, Some(&project_gid), &end_str)?;
-            let lead_time =
-                compute_lead_time_sql(conn, None, Some(&project_gid), &start_str, &end_str)?;
+            let lead_time = compute_lead_time_sql(conn, None, Some(&project_gid), &from, &until)?;
+            let cycle_time =
+                cycle_time::compute_cycle_time_sql(conn, &project_gid, &start_str, &end_str)?;
+            let phases =
+                phases::compute_phase_metrics_sql(conn, &project_gid, &start_str, &end_str)?;
             let collaboration =
-                compute_collaboration_sql(conn, None, Some(&project_gid), &start_str, &end_str)?;
+                compute_collaboration_sql(conn, None, Some(&project_gid), &from, &until)?;
 
             Ok::<ProjectMetrics, rusqlite::Error>(ProjectMetrics {
                 project_gid,
@@ -85,6 +102,8 @@ pub async fn compute_project_metrics(
                 throughput,
                 health,
                 lead_time,
+                cycle_time,
+                phases,
                 collaboration,
             })
         })
@@ -92,15 +111,26 @@ pub async fn compute_project_metrics(
         .map_err(|e| crate::error::Error::Database(e.to_string()))
 }
 
-/// Compute metrics for a portfolio over a period.
+/// Compute metrics for a portfolio over a period, including the projects of
+/// portfolios nested up to [`DEFAULT_PORTFOLIO_DEPTH`] levels below it.
 pub async fn compute_portfolio_metrics(
     db: &Database,
     portfolio_gid: &str,
     period: &Period,
 ) -> Result<PortfolioMetrics> {
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
+    compute_portfolio_metrics_to_depth(db, portfolio_gid, period, DEFAULT_PORTFOLIO_DEPTH).await
+}
+
+/// Compute metrics for a portfolio over a period, including the projects of
+/// portfolios nested up to `max_depth` levels below it.
+pub async fn compute_portfolio_metrics_to_depth(
+    db: &Database,
+    portfolio_gid: &str,
+    period: &Period,
+    max_depth: u32,
+) -> Result<PortfolioMetrics> {
+    let end_str = period.date_range().1.format("%Y-%m-%d").to_string();
+    let (from, until) = period.timestamp_bounds();
     let period_key = period.to_key();
     let portfolio_gid = portfolio_gid.to_string();
 
@@ -114,71 +144,15 @@ pub async fn compute_portfolio_metrics(
                 )
                 .ok();
 
-            // Get project GIDs in this portfolio
-            let project_gids = get_portfolio_project_gids(conn, &portfolio_gid)?;
+            // Projects in this portfolio and the ones nested under it
+            let nested_portfolio_count =
+                repository::portfolio_descendants(conn, &portfolio_gid, max_depth)?.len() as u64
+                    - 1;
+            let project_gids = repository::portfolio_project_gids(conn, &portfolio_gid, max_depth)?;
             let project_count = project_gids.len() as u64;
 
-            let mut throughput = ThroughputMetrics::default();
-            let mut health = HealthMetrics::default();
-            let mut lead_time_days: Vec<i32> = Vec::new();
-            let mut collaboration = CollaborationMetrics::default();
-
-            for pgid in &project_gids {
-                let t = compute_throughput_sql(conn, None, Some(pgid), &start_str, &end_str)?;
-                throughput.tasks_created += t.tasks_created;
-                throughput.tasks_completed += t.tasks_completed;
-                throughput.net_new += t.net_new;
-
-                let h = compute_health_sql(conn, Some(pgid), &end_str)?;
-                health.overdue_count += h.overdue_count;
-                health.unassigned_count += h.unassigned_count;
-                health.stale_count += h.stale_count;
-                health.total_open += h.total_open;
-
-                let lt = compute_lead_time_raw(conn, None, Some(pgid), &start_str, &end_str)?;
-                lead_time_days.extend(lt);
-
-                let c = compute_collaboration_sql(conn, None, Some(pgid), &start_str, &end_str)?;
-                collaboration.total_comments += c.total_comments;
-                collaboration.total_likes += c.total_likes;
-                // unique_commenters recalculated below
-            }
-
-            if health.total_open > 0 {
-                health.overdue_pct = health.overdue_count as f64 / health.total_open as f64 * 100.0;
-                health.unassigned_pct =
-                    health.unassigned_count as f64 / health.total_open as f64 * 100.0;
-            }
-
-            // Aggregate unique commenters across portfolio
-            let placeholders = project_gids
-                .iter()
-                .map(|_| "?")
-                .collect::<Vec<_>>()
-                .join(",");
-            if !project_gids.is_empty() {
-                let sql = format!(
-                    "SELECT COUNT(DISTINCT c.author_gid)
-                     FROM fact_comments c
-                     JOIN bridge_task_projects btp ON btp.task_gid = c.task_gid
-                     WHERE btp.project_gid IN ({placeholders})
-                       AND c.created_date_key >= ? AND c.created_date_key <= ?"
-                );
-                let mut stmt = conn.prepare(&sql)?;
-                let mut idx = 1;
-                for pgid in &project_gids {
-                    stmt.raw_bind_parameter(idx, pgid)?;
-                    idx += 1;
-                }
-                stmt.raw_bind_parameter(idx, &start_str)?;
-                stmt.raw_bind_parameter(idx + 1, &end_str)?;
-                let mut rows = stmt.raw_query();
-                if let Some(row) = rows.next()? {
-                    collaboration.unique_commenters = row.get::<_, i64>(0)? as u64;
-                }
-            }
-
-            let lead_time = percentiles_from_days(&lead_time_days);
+            let (throughput, health, lead_time, collaboration) =
+                aggregate_project_metrics(conn, &project_gids, &from, &until, &end_str)?;
 
             Ok::<PortfolioMetrics, rusqlite::Error>(PortfolioMetrics {
                 portfolio_gid,
@@ -189,6 +163,198 @@ pub async fn compute_portfolio_metrics(
                 lead_time,
                 collaboration,
                 project_count,
+                nested_portfolio_count,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+/// Compute metrics across the projects matching a `project_labels` entry.
+pub async fn compute_label_metrics(
+    db: &Database,
+    label: &str,
+    period: &Period,
+) -> Result<LabelMetrics> {
+    let label = labels::load_project_label(db, label).await?;
+    let end_str = period.date_range().1.format("%Y-%m-%d").to_string();
+    let (from, until) = period.timestamp_bounds();
+    let period_key = period.to_key();
+
+    db.reader()
+        .call(move |conn| {
+            let project_gids = labels::label_project_gids(conn, &label)?;
+            let project_count = project_gids.len() as u64;
+
+            let (throughput, health, lead_time, collaboration) =
+                aggregate_project_metrics(conn, &project_gids, &from, &until, &end_str)?;
+
+            Ok::<LabelMetrics, rusqlite::Error>(LabelMetrics {
+                label: label.name,
+                period_key,
+                throughput,
+                health,
+                lead_time,
+                collaboration,
+                project_count,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+/// Compute per-section WIP and throughput for a project over a period.
+pub async fn compute_section_metrics(
+    db: &Database,
+    project_gid: &str,
+    period: &Period,
+) -> Result<ProjectSectionMetrics> {
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+    let period_key = period.to_key();
+    let project_gid = project_gid.to_string();
+
+    db.reader()
+        .call(move |conn| {
+            let project_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_projects WHERE project_gid = ?1",
+                    [&project_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+            let sections =
+                sections::compute_section_metrics_sql(conn, &project_gid, &start_str, &end_str)?;
+
+            Ok::<ProjectSectionMetrics, rusqlite::Error>(ProjectSectionMetrics {
+                project_gid,
+                project_name,
+                period_key,
+                sections,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+/// How long a project's tasks held each value of a tracked custom field
+/// (see `tracked_custom_fields`) over a period, e.g. days spent `Blocked`.
+pub async fn compute_field_history(
+    db: &Database,
+    project_gid: &str,
+    field: &str,
+    period: &Period,
+) -> Result<FieldHistoryMetrics> {
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+    let period_key = period.to_key();
+    let project_gid = project_gid.to_string();
+    let field = field.to_string();
+
+    let tracked = db
+        .reader()
+        .call(|conn| crate::storage::field_changes::tracked_fields(conn))
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))?;
+    let field_name = tracked
+        .into_iter()
+        .find(|t| t.eq_ignore_ascii_case(&field))
+        .ok_or_else(|| {
+            crate::error::Error::Config(format!(
+                "custom field '{field}' is not listed in {}",
+                crate::storage::field_changes::TRACKED_FIELDS_KEY
+            ))
+        })?;
+
+    db.reader()
+        .call(move |conn| {
+            let project_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_projects WHERE project_gid = ?1",
+                    [&project_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+            let values = field_history::compute_field_dwell_sql(
+                conn,
+                &project_gid,
+                &field_name,
+                &start_str,
+                &end_str,
+            )?;
+
+            Ok::<FieldHistoryMetrics, rusqlite::Error>(FieldHistoryMetrics {
+                project_gid,
+                project_name,
+                field_name,
+                period_key,
+                values,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+/// Compute project metrics bucketed by the value of an enum custom field,
+/// given by GID or by name (e.g. `Priority`).
+pub async fn compute_project_metrics_by_field(
+    db: &Database,
+    project_gid: &str,
+    field: &str,
+    period: &Period,
+) -> Result<FieldMetrics> {
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+    let period_key = period.to_key();
+    let project_gid = project_gid.to_string();
+
+    let resolved = db
+        .reader()
+        .call({
+            let project_gid = project_gid.clone();
+            let field = field.to_string();
+            move |conn| by_field::resolve_field(conn, &project_gid, &field)
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))?;
+    let (field_gid, field_name, field_type) = resolved.ok_or_else(|| {
+        crate::error::Error::NotFound(format!(
+            "custom field '{field}' (not a field GID or the name of a field used in project {project_gid})"
+        ))
+    })?;
+    if field_type != "enum" {
+        return Err(crate::error::Error::Other(format!(
+            "custom field '{field_name}' is a {field_type} field; only enum fields can be grouped by"
+        )));
+    }
+
+    db.reader()
+        .call(move |conn| {
+            let project_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_projects WHERE project_gid = ?1",
+                    [&project_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+            let groups = by_field::compute_field_groups_sql(
+                conn,
+                &project_gid,
+                &field_gid,
+                &start_str,
+                &end_str,
+            )?;
+
+            Ok::<FieldMetrics, rusqlite::Error>(FieldMetrics {
+                project_gid,
+                project_name,
+                field_gid,
+                field_name,
+                period_key,
+                groups,
             })
         })
         .await
@@ -201,9 +367,7 @@ pub async fn compute_team_metrics(
     team_gid: &str,
     period: &Period,
 ) -> Result<TeamMetrics> {
-    let (start, end) = period.date_range();
-    let start_str = start.format("%Y-%m-%d").to_string();
-    let end_str = end.format("%Y-%m-%d").to_string();
+    let (from, until) = period.timestamp_bounds();
     let period_key = period.to_key();
     let team_gid = team_gid.to_string();
 
@@ -225,106 +389,183 @@ pub async fn compute_team_metrics(
                 .filter_map(|r| r.ok())
                 .collect();
             let member_count = member_gids.len() as u64;
-
-            let mut throughput = ThroughputMetrics::default();
-            let mut health = HealthMetrics::default();
-            let mut lead_time_days: Vec<i32> = Vec::new();
-            let mut collaboration = CollaborationMetrics::default();
-
-            for uid in &member_gids {
-                let t = compute_throughput_sql(conn, Some(uid), None, &start_str, &end_str)?;
-                throughput.tasks_created += t.tasks_created;
-                throughput.tasks_completed += t.tasks_completed;
-                throughput.net_new += t.net_new;
-
-                let lt = compute_lead_time_raw(conn, Some(uid), None, &start_str, &end_str)?;
-                lead_time_days.extend(lt);
-
-                let c = compute_collaboration_sql(conn, Some(uid), None, &start_str, &end_str)?;
-                collaboration.total_comments += c.total_comments;
-                collaboration.total_likes += c.total_likes;
-            }
-
-            // Health across team's tasks (all open assigned to team members)
-            if !member_gids.is_empty() {
-                let placeholders = member_gids
-                    .iter()
-                    .map(|_| "?")
-                    .collect::<Vec<_>>()
-                    .join(",");
-                let sql = format!(
-                    "SELECT
-                        SUM(CASE WHEN is_overdue = 1 THEN 1 ELSE 0 END),
-                        SUM(CASE WHEN assignee_gid IS NULL THEN 1 ELSE 0 END),
-                        SUM(CASE WHEN modified_at < date('now', '-14 days') THEN 1 ELSE 0 END),
-                        COUNT(*)
-                     FROM fact_tasks
-                     WHERE is_completed = 0
-                       AND assignee_gid IN ({placeholders})"
-                );
-                let mut stmt = conn.prepare(&sql)?;
-                for (i, uid) in member_gids.iter().enumerate() {
-                    stmt.raw_bind_parameter(i + 1, uid)?;
-                }
-                let mut rows = stmt.raw_query();
-                if let Some(row) = rows.next()? {
-                    health.overdue_count = row.get::<_, Option<i64>>(0)?.unwrap_or(0) as u64;
-                    health.unassigned_count = row.get::<_, Option<i64>>(1)?.unwrap_or(0) as u64;
-                    health.stale_count = row.get::<_, Option<i64>>(2)?.unwrap_or(0) as u64;
-                    health.total_open = row.get::<_, i64>(3)? as u64;
-                }
-                if health.total_open > 0 {
-                    health.overdue_pct =
-                        health.overdue_count as f64 / health.total_open as f64 * 100.0;
-                    health.unassigned_pct =
-                        health.unassigned_count as f64 / health.total_open as f64 * 100.0;
-                }
-
-                // Unique commenters across team
-                let sql = format!(
-                    "SELECT COUNT(DISTINCT c.author_gid)
-                     FROM fact_comments c
-                     JOIN fact_tasks t ON t.task_gid = c.task_gid
-                     WHERE t.assignee_gid IN ({placeholders})
-                       AND c.created_date_key >= ? AND c.created_date_key <= ?"
-                );
-                let mut stmt = conn.prepare(&sql)?;
-                for (i, uid) in member_gids.iter().enumerate() {
-                    stmt.raw_bind_parameter(i + 1, uid)?;
-                }
-                stmt.raw_bind_parameter(member_gids.len() + 1, &start_str)?;
-                stmt.raw_bind_parameter(member_gids.len() + 2, &end_str)?;
-                let mut rows = stmt.raw_query();
-                if let Some(row) = rows.next()? {
-                    collaboration.unique_commenters = row.get::<_, i64>(0)? as u64;
-                }
-            }
-
-            let lead_time = percentiles_from_days(&lead_time_days);
+            let rollup = compute_member_rollup(conn, &member_gids, &from, &until)?;
 
             Ok::<TeamMetrics, rusqlite::Error>(TeamMetrics {
                 team_gid,
                 team_name,
                 period_key,
-                throughput,
-                health,
-                lead_time,
-                collaboration,
+                throughput: rollup.throughput,
+                health: rollup.health,
+                lead_time: rollup.lead_time,
+                collaboration: rollup.collaboration,
+                member_count,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+/// Compute metrics for a manager's reporting line over a period: the
+/// manager plus everyone under them in `dim_users.manager_gid`, regardless
+/// of Asana team membership.
+pub async fn compute_org_metrics(
+    db: &Database,
+    manager_gid: &str,
+    period: &Period,
+) -> Result<OrgMetrics> {
+    let (from, until) = period.timestamp_bounds();
+    let period_key = period.to_key();
+    let manager_gid = manager_gid.to_string();
+
+    db.reader()
+        .call(move |conn| {
+            let manager_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_users WHERE user_gid = ?1",
+                    [&manager_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+            let direct_reports: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM dim_users WHERE manager_gid = ?1 AND user_gid != ?1",
+                [&manager_gid],
+                |row| row.get(0),
+            )?;
+
+            let member_gids = repository::get_reporting_line(conn, &manager_gid)?;
+            let member_count = member_gids.len() as u64;
+            let rollup = compute_member_rollup(conn, &member_gids, &from, &until)?;
+
+            Ok::<OrgMetrics, rusqlite::Error>(OrgMetrics {
+                manager_gid,
+                manager_name,
+                period_key,
+                throughput: rollup.throughput,
+                health: rollup.health,
+                lead_time: rollup.lead_time,
+                collaboration: rollup.collaboration,
                 member_count,
+                direct_reports: direct_reports as u64,
             })
         })
         .await
         .map_err(|e| crate::error::Error::Database(e.to_string()))
 }
 
+/// Metrics summed over a set of users.
+struct MemberRollup {
+    throughput: ThroughputMetrics,
+    health: HealthMetrics,
+    lead_time: LeadTimeMetrics,
+    collaboration: CollaborationMetrics,
+}
+
+fn compute_member_rollup(
+    conn: &rusqlite::Connection,
+    member_gids: &[String],
+    from: &str,
+    until: &str,
+) -> std::result::Result<MemberRollup, rusqlite::Error> {
+    let mut throughput = ThroughputMetrics::default();
+    let mut health = HealthMetrics::default();
+    let mut lead_time_days: Vec<i32> = Vec::new();
+    let mut collaboration = CollaborationMetrics::default();
+
+    for uid in member_gids {
+        let t = compute_throughput_sql(conn, Some(uid), None, from, until)?;
+        throughput.tasks_created += t.tasks_created;
+        throughput.tasks_completed += t.tasks_completed;
+        throughput.milestones_completed += t.milestones_completed;
+        throughput.net_new += t.net_new;
+
+        let lt = compute_lead_time_raw(conn, Some(uid), None, from, until)?;
+        lead_time_days.extend(lt);
+
+        let c = compute_collaboration_sql(conn, Some(uid), None, from, until)?;
+        collaboration.total_comments += c.total_comments;
+        collaboration.total_likes += c.total_likes;
+        collaboration.silent_completions += c.silent_completions;
+    }
+    collaboration.silent_completion_pct =
+        silent_completion_pct(collaboration.silent_completions, throughput.tasks_completed);
+
+    // Health across the members' tasks (all open assigned to them)
+    if !member_gids.is_empty() {
+        let placeholders = member_gids
+            .iter()
+            .map(|_| "?")
+            .collect::<Vec<_>>()
+            .join(",");
+        let sql = format!(
+            "SELECT
+                    SUM(CASE WHEN is_overdue = 1 THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN assignee_gid IS NULL THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN modified_at < date('now', '-14 days') THEN 1 ELSE 0 END),
+                    COUNT(*)
+                 FROM fact_tasks
+                 WHERE is_completed = 0 AND is_deleted = 0
+                   AND assignee_gid IN ({placeholders})"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        for (i, uid) in member_gids.iter().enumerate() {
+            stmt.raw_bind_parameter(i + 1, uid)?;
+        }
+        let mut rows = stmt.raw_query();
+        if let Some(row) = rows.next()? {
+            health.overdue_count = row.get::<_, Option<i64>>(0)?.unwrap_or(0) as u64;
+            health.unassigned_count = row.get::<_, Option<i64>>(1)?.unwrap_or(0) as u64;
+            health.stale_count = row.get::<_, Option<i64>>(2)?.unwrap_or(0) as u64;
+            health.total_open = row.get::<_, i64>(3)? as u64;
+        }
+        if health.total_open > 0 {
+            health.overdue_pct = health.overdue_count as f64 / health.total_open as f64 * 100.0;
+            health.unassigned_pct =
+                health.unassigned_count as f64 / health.total_open as f64 * 100.0;
+        }
+
+        // Unique commenters across the members' tasks
+        let sql = format!(
+            "SELECT COUNT(DISTINCT c.author_gid)
+                 FROM fact_comments c
+                 JOIN fact_tasks t ON t.task_gid = c.task_gid
+                 WHERE t.is_deleted = 0 AND t.assignee_gid IN ({placeholders})
+                   AND c.is_bot = 0 AND c.created_at >= ? AND c.created_at < ?"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        for (i, uid) in member_gids.iter().enumerate() {
+            stmt.raw_bind_parameter(i + 1, uid)?;
+        }
+        stmt.raw_bind_parameter(member_gids.len() + 1, from)?;
+        stmt.raw_bind_parameter(member_gids.len() + 2, until)?;
+        let mut rows = stmt.raw_query();
+        if let Some(row) = rows.next()? {
+            collaboration.unique_commenters = row.get::<_, i64>(0)? as u64;
+        }
+    }
+
+    Ok(MemberRollup {
+        throughput,
+        health,
+        lead_time: percentiles_from_days(&lead_time_days),
+        collaboration,
+    })
+}
+
 // ── Internal SQL helpers ───────────────────────────────────────────
+//
+// Throughput, lead time, and collaboration compare stored timestamps against
+// `from <= ts < until` bounds from `Period::timestamp_bounds`, so rolling-hour
+// periods count exactly their hours (migration 043 indexes the timestamps).
+// Health, cycle time, phases, and section and field breakdowns work in whole
+// days from `Period::date_range`.
 
 fn compute_throughput_sql(
     conn: &rusqlite::Connection,
     user_gid: Option<&str>,
     project_gid: Option<&str>,
-    start: &str,
-    end: &str,
+    from: &str,
+    until: &str,
 ) -> std::result::Result<ThroughputMetrics, rusqlite::Error> {
     #[allow(clippy::type_complexity)]
     let (where_clause, join_clause, bind_fn): (
@@ -335,82 +576,32 @@ fn compute_throughput_sql(
 
     // Tasks created in period
     let sql = format!(
-        "SELECT COUNT(*) FROM fact_tasks t {join_clause} WHERE t.created_date_key >= ?1 AND t.created_date_key <= ?2 {where_clause}"
+        "SELECT COUNT(*) FROM fact_tasks t {join_clause} WHERE t.created_at >= ?1 AND t.created_at < ?2 {where_clause}"
     );
     let mut stmt = conn.prepare(&sql)?;
-    stmt.raw_bind_parameter(1, start)?;
-    stmt.raw_bind_parameter(2, end)?;
+    stmt.raw_bind_parameter(1, from)?;
+    stmt.raw_bind_parameter(2, until)?;
     bind_fn(&mut stmt, 3)?;
     let created: i64 = stmt.raw_query().next()?.unwrap().get(0)?;
 
     // Tasks completed in period
     let sql = format!(
-        "SELECT COUNT(*) FROM fact_tasks t {join_clause} WHERE t.completed_date_key >= ?1 AND t.completed_date_key <= ?2 AND t.is_completed = 1 {where_clause}"
+        "SELECT COUNT(*), COALESCE(SUM(t.resource_subtype = 'milestone'), 0) FROM fact_tasks t {join_clause} WHERE t.completed_at >= ?1 AND t.completed_at < ?2 AND t.is_completed = 1 {where_clause}"
     );
     let mut stmt = conn.prepare(&sql)?;
-    stmt.raw_bind_parameter(1, start)?;
-    stmt.raw_bind_parameter(2, end)?;
+    stmt.raw_bind_parameter(1, from)?;
+    stmt.raw_bind_parameter(2, until)?;
     bind_fn(&mut stmt, 3)?;
-    let completed: i64 = stmt.raw_query().next()?.unwrap().get(0)?;
+    let mut rows = stmt.raw_query();
+    let row = rows.next()?.unwrap();
+    let completed: i64 = row.get(0)?;
+    let milestones: i64 = row.get(1)?;
 
     Ok(ThroughputMetrics {
         tasks_created: created as u64,
         tasks_completed: completed as u64,
         net_new: created - completed,
-    })
-}
-
-fn compute_health_sql(
-    conn: &rusqlite::Connection,
-    project_gid: Option<&str>,
-    _end: &str,
-) -> std::result::Result<HealthMetrics, rusqlite::Error> {
-    let (join, where_extra) = if project_gid.is_some() {
-        (
-            "JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid",
-            " AND btp.project_gid = ?1",
-        )
-    } else {
-        ("", "")
-    };
-
-    let sql = format!(
-        "SELECT
-            SUM(CASE WHEN t.due_on < date('now') AND t.due_on IS NOT NULL THEN 1 ELSE 0 END),
-            SUM(CASE WHEN t.assignee_gid IS NULL THEN 1 ELSE 0 END),
-            SUM(CASE WHEN t.modified_at < date('now', '-14 days') THEN 1 ELSE 0 END),
-            COUNT(*)
-         FROM fact_tasks t
-         {join}
-         WHERE t.is_completed = 0{where_extra}"
-    );
-    let mut stmt = conn.prepare(&sql)?;
-    if let Some(pgid) = project_gid {
-        stmt.raw_bind_parameter(1, pgid)?;
-    }
-    let mut rows = stmt.raw_query();
-    let row = rows.next()?.unwrap();
-
-    let overdue = row.get::<_, Option<i64>>(0)?.unwrap_or(0) as u64;
-    let unassigned = row.get::<_, Option<i64>>(1)?.unwrap_or(0) as u64;
-    let stale = row.get::<_, Option<i64>>(2)?.unwrap_or(0) as u64;
-    let total_open = row.get::<_, i64>(3)? as u64;
-
-    Ok(HealthMetrics {
-        overdue_count: overdue,
-        unassigned_count: unassigned,
-        stale_count: stale,
-        total_open,
-        overdue_pct: if total_open > 0 {
-            overdue as f64 / total_open as f64 * 100.0
-        } else {
-            0.0
-        },
-        unassigned_pct: if total_open > 0 {
-            unassigned as f64 / total_open as f64 * 100.0
-        } else {
-            0.0
-        },
+        milestones_completed: milestones as u64,
     })
 }
 
@@ -418,8 +609,8 @@ fn compute_lead_time_raw(
     conn: &rusqlite::Connection,
     user_gid: Option<&str>,
     project_gid: Option<&str>,
-    start: &str,
-    end: &str,
+    from: &str,
+    until: &str,
 ) -> std::result::Result<Vec<i32>, rusqlite::Error> {
     #[allow(clippy::type_complexity)]
     let (where_clause, join_clause, bind_fn): (
@@ -431,12 +622,12 @@ fn compute_lead_time_raw(
     let sql = format!(
         "SELECT t.days_to_complete FROM fact_tasks t {join_clause}
          WHERE t.is_completed = 1 AND t.days_to_complete IS NOT NULL
-           AND t.completed_date_key >= ?1 AND t.completed_date_key <= ?2 {where_clause}
+           AND t.completed_at >= ?1 AND t.completed_at < ?2 {where_clause}
          ORDER BY t.days_to_complete"
     );
     let mut stmt = conn.prepare(&sql)?;
-    stmt.raw_bind_parameter(1, start)?;
-    stmt.raw_bind_parameter(2, end)?;
+    stmt.raw_bind_parameter(1, from)?;
+    stmt.raw_bind_parameter(2, until)?;
     bind_fn(&mut stmt, 3)?;
 
     let mut days = Vec::new();
@@ -451,10 +642,10 @@ fn compute_lead_time_sql(
     conn: &rusqlite::Connection,
     user_gid: Option<&str>,
     project_gid: Option<&str>,
-    start: &str,
-    end: &str,
+    from: &str,
+    until: &str,
 ) -> std::result::Result<LeadTimeMetrics, rusqlite::Error> {
-    let days = compute_lead_time_raw(conn, user_gid, project_gid, start, end)?;
+    let days = compute_lead_time_raw(conn, user_gid, project_gid, from, until)?;
     Ok(percentiles_from_days(&days))
 }
 
@@ -462,21 +653,22 @@ fn compute_collaboration_sql(
     conn: &rusqlite::Connection,
     user_gid: Option<&str>,
     project_gid: Option<&str>,
-    start: &str,
-    end: &str,
+    from: &str,
+    until: &str,
 ) -> std::result::Result<CollaborationMetrics, rusqlite::Error> {
     // Comments — use parameterized query for entity filter
     let (task_join, task_where, entity_val): (&str, &str, Option<&str>) =
         if let Some(pgid) = project_gid {
             (
-                "JOIN bridge_task_projects btp ON btp.task_gid = c.task_gid",
-                " AND btp.project_gid = ?3",
+                "JOIN bridge_task_projects btp ON btp.task_gid = c.task_gid
+                 JOIN fact_tasks t ON t.task_gid = c.task_gid",
+                " AND t.is_deleted = 0 AND btp.project_gid = ?3",
                 Some(pgid),
             )
         } else if let Some(uid) = user_gid {
             (
                 "JOIN fact_tasks t ON t.task_gid = c.task_gid",
-                " AND t.assignee_gid = ?3",
+                " AND t.is_deleted = 0 AND t.assignee_gid = ?3",
                 Some(uid),
             )
         } else {
@@ -486,14 +678,14 @@ fn compute_collaboration_sql(
     let sql = format!(
         "SELECT COUNT(*), COUNT(DISTINCT c.author_gid)
          FROM fact_comments c {task_join}
-         WHERE c.created_date_key >= ?1 AND c.created_date_key <= ?2{task_where}"
+         WHERE c.is_bot = 0 AND c.created_at >= ?1 AND c.created_at < ?2{task_where}"
     );
     let total_comments: i64;
     let unique_commenters: i64;
     {
         let mut stmt = conn.prepare(&sql)?;
-        stmt.raw_bind_parameter(1, start)?;
-        stmt.raw_bind_parameter(2, end)?;
+        stmt.raw_bind_parameter(1, from)?;
+        stmt.raw_bind_parameter(2, until)?;
         if let Some(val) = entity_val {
             stmt.raw_bind_parameter(3, val)?;
         }
@@ -519,23 +711,49 @@ fn compute_collaboration_sql(
 
     let sql = format!(
         "SELECT COALESCE(SUM(t.num_likes), 0) FROM fact_tasks t {like_join}
-         WHERE t.created_date_key >= ?1 AND t.created_date_key <= ?2{like_where}"
+         WHERE t.is_deleted = 0 AND t.created_at >= ?1 AND t.created_at < ?2{like_where}"
     );
     let mut stmt = conn.prepare(&sql)?;
-    stmt.raw_bind_parameter(1, start)?;
-    stmt.raw_bind_parameter(2, end)?;
+    stmt.raw_bind_parameter(1, from)?;
+    stmt.raw_bind_parameter(2, until)?;
     if let Some(val) = like_val {
         stmt.raw_bind_parameter(3, val)?;
     }
     let total_likes: i64 = stmt.raw_query().next()?.unwrap().get(0)?;
 
+    // Silent completions: completed in the period with no comment ever
+    let (where_clause, join_clause, bind_fn) = build_entity_filter(user_gid, project_gid);
+    let sql = format!(
+        "SELECT COUNT(*), COALESCE(SUM(NOT EXISTS (
+                SELECT 1 FROM fact_comments c
+                WHERE c.task_gid = t.task_gid AND c.story_type = 'comment' AND c.is_bot = 0)), 0)
+         FROM fact_tasks t {join_clause}
+         WHERE t.is_completed = 1 AND t.completed_at >= ?1 AND t.completed_at < ?2 {where_clause}"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    stmt.raw_bind_parameter(1, from)?;
+    stmt.raw_bind_parameter(2, until)?;
+    bind_fn(&mut stmt, 3)?;
+    let mut rows = stmt.raw_query();
+    let row = rows.next()?.unwrap();
+    let completed = row.get::<_, i64>(0)? as u64;
+    let silent = row.get::<_, i64>(1)? as u64;
+
     Ok(CollaborationMetrics {
         total_comments: total_comments as u64,
         unique_commenters: unique_commenters as u64,
         total_likes: total_likes as u64,
+        silent_completions: silent,
+        silent_completion_pct: silent_completion_pct(silent, completed),
     })
 }
 
+/// `silent` as a percentage of `completed`, or `None` when nothing was
+/// completed.
+fn silent_completion_pct(silent: u64, completed: u64) -> Option<f64> {
+    (completed > 0).then(|| silent as f64 / completed as f64 * 100.0)
+}
+
 #[allow(clippy::type_complexity)]
 fn build_entity_filter<'a>(
     user_gid: Option<&'a str>,
@@ -547,7 +765,7 @@ fn build_entity_filter<'a>(
 ) {
     if let Some(pgid) = project_gid {
         (
-            " AND btp.project_gid = ?3".to_string(),
+            " AND t.is_deleted = 0 AND btp.project_gid = ?3".to_string(),
             "JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid".to_string(),
             Box::new(move |stmt: &mut rusqlite::Statement<'_>, idx: usize| {
                 stmt.raw_bind_parameter(idx, pgid)?;
@@ -556,7 +774,7 @@ fn build_entity_filter<'a>(
         )
     } else if let Some(uid) = user_gid {
         (
-            " AND t.assignee_gid = ?3".to_string(),
+            " AND t.is_deleted = 0 AND t.assignee_gid = ?3".to_string(),
             String::new(),
             Box::new(move |stmt: &mut rusqlite::Statement<'_>, idx: usize| {
                 stmt.raw_bind_parameter(idx, uid)?;
@@ -565,24 +783,125 @@ fn build_entity_filter<'a>(
         )
     } else {
         (
-            String::new(),
+            " AND t.is_deleted = 0".to_string(),
             String::new(),
             Box::new(|_stmt: &mut rusqlite::Statement<'_>, _idx: usize| Ok(())),
         )
     }
 }
 
-fn get_portfolio_project_gids(
+/// Sum throughput, health, lead time, and collaboration across a set of
+/// projects, with health as of `end`.
+#[allow(clippy::type_complexity)]
+fn aggregate_project_metrics(
     conn: &rusqlite::Connection,
-    portfolio_gid: &str,
-) -> std::result::Result<Vec<String>, rusqlite::Error> {
-    let mut stmt =
-        conn.prepare("SELECT project_gid FROM bridge_portfolio_projects WHERE portfolio_gid = ?1")?;
-    let gids: Vec<String> = stmt
-        .query_map([portfolio_gid], |row| row.get(0))?
-        .filter_map(|r| r.ok())
-        .collect();
-    Ok(gids)
+    project_gids: &[String],
+    from: &str,
+    until: &str,
+    end: &str,
+) -> std::result::Result<
+    (
+        ThroughputMetrics,
+        HealthMetrics,
+        LeadTimeMetrics,
+        CollaborationMetrics,
+    ),
+    rusqlite::Error,
+> {
+    let mut throughput = ThroughputMetrics::default();
+    let mut health = HealthMetrics::default();
+    let mut lead_time_days: Vec<i32> = Vec::new();
+    let mut collaboration = CollaborationMetrics::default();
+
+    for pgid in project_gids {
+        let t = compute_throughput_sql(conn, None, Some(pgid), from, until)?;
+        throughput.tasks_created += t.tasks_created;
+        throughput.tasks_completed += t.tasks_completed;
+        throughput.milestones_completed += t.milestones_completed;
+        throughput.net_new += t.net_new;
+
+        let h = compute_health_sql(conn, Some(pgid), end)?;
+        health.overdue_count += h.overdue_count;
+        health.unassigned_count += h.unassigned_count;
+        health.stale_count += h.stale_count;
+        health.total_open += h.total_open;
+
+        let lt = compute_lead_time_raw(conn, None, Some(pgid), from, until)?;
+        lead_time_days.extend(lt);
+
+        let c = compute_collaboration_sql(conn, None, Some(pgid), from, until)?;
+        collaboration.total_comments += c.total_comments;
+        collaboration.total_likes += c.total_likes;
+        collaboration.silent_completions += c.silent_completions;
+        // unique_commenters recalculated below
+    }
+    collaboration.silent_completion_pct =
+        silent_completion_pct(collaboration.silent_completions, throughput.tasks_completed);
+
+    if health.total_open > 0 {
+        health.overdue_pct = health.overdue_count as f64 / health.total_open as f64 * 100.0;
+        health.unassigned_pct = health.unassigned_count as f64 / health.total_open as f64 * 100.0;
+    }
+
+    // Aggregate unique commenters across all projects
+    let placeholders = project_gids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+    if !project_gids.is_empty() {
+        let sql = format!(
+            "SELECT COUNT(DISTINCT c.author_gid)
+             FROM fact_comments c
+             JOIN bridge_task_projects btp ON btp.task_gid = c.task_gid
+             JOIN fact_tasks t ON t.task_gid = c.task_gid
+             WHERE btp.project_gid IN ({placeholders}) AND t.is_deleted = 0
+               AND c.is_bot = 0 AND c.created_at >= ? AND c.created_at < ?"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut idx = 1;
+        for pgid in project_gids {
+            stmt.raw_bind_parameter(idx, pgid)?;
+            idx += 1;
+        }
+        stmt.raw_bind_parameter(idx, from)?;
+        stmt.raw_bind_parameter(idx + 1, until)?;
+        let mut rows = stmt.raw_query();
+        if let Some(row) = rows.next()? {
+            collaboration.unique_commenters = row.get::<_, i64>(0)? as u64;
+        }
+    }
+
+    let lead_time = percentiles_from_days(&lead_time_days);
+
+    Ok((throughput, health, lead_time, collaboration))
+}
+
+/// Inclusive day ranges for the lead-time histogram; the last is open-ended.
+const LEAD_TIME_BUCKETS: &[(i32, Option<i32>)] = &[
+    (0, Some(1)),
+    (2, Some(3)),
+    (4, Some(7)),
+    (8, Some(14)),
+    (15, None),
+];
+
+fn lead_time_histogram(days: &[i32]) -> Vec<LeadTimeBucket> {
+    LEAD_TIME_BUCKETS
+        .iter()
+        .map(|&(min, max)| LeadTimeBucket {
+            label: match max {
+                Some(max) => format!("{min}-{max}d"),
+                None => format!("{min}+d"),
+            },
+            min_days: min,
+            max_days: max,
+            count: days
+                .iter()
+                .filter(|&&d| d.max(0) >= min && max.is_none_or(|max| d <= max))
+                .count() as u64,
+        })
+        .collect()
 }
 
 fn percentiles_from_days(days: &[i32]) -> LeadTimeMetrics {
@@ -611,6 +930,7 @@ fn percentiles_from_days(days: &[i32]) -> LeadTimeMetrics {
         p90_days_to_complete: Some(p90),
         min_days_to_complete: days.first().copied(),
         max_days_to_complete: days.last().copied(),
+        histogram: lead_time_histogram(days),
     }
 }
 
@@ -627,6 +947,29 @@ mod tests {
         assert!(lt.p90_days_to_complete.is_none());
     }
 
+    #[test]
+    fn test_lead_time_histogram() {
+        assert!(percentiles_from_days(&[]).histogram.is_empty());
+        // Bimodal: quick fixes and long-running work
+        let lt = percentiles_from_days(&[0, 1, 1, 3, 9, 20, 30]);
+        let counts: Vec<(&str, u64)> = lt
+            .histogram
+            .iter()
+            .map(|b| (b.label.as_str(), b.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("0-1d", 3),
+                ("2-3d", 1),
+                ("4-7d", 0),
+                ("8-14d", 1),
+                ("15+d", 2)
+            ]
+        );
+        assert_eq!(lt.histogram[4].max_days, None);
+    }
+
     #[test]
     fn test_percentiles_single_element() {
         // This was a panic bug — single element would underflow
@@ -765,4 +1108,116 @@ mod tests {
         assert_eq!(metrics.throughput.tasks_completed, 1);
         assert_eq!(metrics.lead_time.avg_days_to_complete, Some(14.0));
     }
+
+    #[tokio::test]
+    async fn test_rolling_hours_use_timestamps() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'This morning', 'u1', 1, '2025-03-02T08:00:00.000Z', '2025-03-02', '2025-02-20T00:00:00.000Z', '2025-02-20', datetime('now')),
+                            ('t2', 'Yesterday evening', 'u1', 1, '2025-03-01T18:00:00.000Z', '2025-03-01', '2025-02-20T00:00:00.000Z', '2025-02-20', datetime('now')),
+                            ('t3', 'Yesterday morning', 'u1', 1, '2025-03-01T08:00:00.000Z', '2025-03-01', '2025-02-20T00:00:00.000Z', '2025-02-20', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let until = chrono::NaiveDate::from_ymd_opt(2025, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let metrics = compute_user_metrics(&db, "u1", &Period::RollingHours(24, until))
+            .await
+            .unwrap();
+        assert_eq!(metrics.throughput.tasks_completed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_period_bounds_use_timestamp_indexes() {
+        let db = Database::open_memory().await.unwrap();
+        let plans = db
+            .reader()
+            .call(|conn| {
+                let plan = |sql: &str| {
+                    conn.query_row(&format!("EXPLAIN QUERY PLAN {sql}"), ["", ""], |row| {
+                        row.get::<_, String>(3)
+                    })
+                };
+                Ok::<_, rusqlite::Error>([
+                    plan("SELECT COUNT(*) FROM fact_tasks t WHERE t.created_at >= ?1 AND t.created_at < ?2")?,
+                    plan("SELECT COUNT(*) FROM fact_tasks t WHERE t.completed_at >= ?1 AND t.completed_at < ?2 AND t.is_completed = 1")?,
+                    plan("SELECT COUNT(*) FROM fact_comments c WHERE c.created_at >= ?1 AND c.created_at < ?2")?,
+                ])
+            })
+            .await
+            .unwrap();
+        assert!(plans[0].contains("idx_tasks_created_at"), "{}", plans[0]);
+        assert!(plans[1].contains("idx_tasks_completed_at"), "{}", plans[1]);
+        assert!(plans[2].contains("idx_comments_created_at"), "{}", plans[2]);
+    }
+
+    #[tokio::test]
+    async fn test_throughput_counts_milestones() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, resource_subtype, cached_at)
+                     VALUES ('t1', 'Launch', 'u1', 1, '2025-01-15T10:00:00.000Z', '2025-01-15', '2025-01-01', '2025-01-01', 'milestone', datetime('now')),
+                            ('t2', 'Sign-off', 'u1', 1, '2025-01-16T10:00:00.000Z', '2025-01-16', '2025-01-01', '2025-01-01', 'approval', datetime('now')),
+                            ('t3', 'Chore', 'u1', 1, '2025-01-17T10:00:00.000Z', '2025-01-17', '2025-01-01', '2025-01-01', 'default_task', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let period = Period::Month(2025, 1);
+        let metrics = compute_user_metrics(&db, "u1", &period).await.unwrap();
+        assert_eq!(metrics.throughput.tasks_completed, 3);
+        assert_eq!(metrics.throughput.milestones_completed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_silent_completions() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Discussed', 'u1', 1, '2025-01-15T10:00:00.000Z', '2025-01-15', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t2', 'Quiet', 'u1', 1, '2025-01-16T10:00:00.000Z', '2025-01-16', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t3', 'Only a system story', 'u1', 1, '2025-01-17T10:00:00.000Z', '2025-01-17', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t4', 'Open', 'u1', 0, NULL, NULL, '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'Shipped', 'comment', '2024-12-20', '2024-12-20', datetime('now')),
+                            ('c2', 't3', 'moved to Done', 'system', '2025-01-17', '2025-01-17', datetime('now')),
+                            ('c3', 't4', 'Any news?', 'comment', '2025-01-10', '2025-01-10', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let metrics = compute_user_metrics(&db, "u1", &Period::Month(2025, 1))
+            .await
+            .unwrap();
+        assert_eq!(metrics.collaboration.silent_completions, 2);
+        let pct = metrics.collaboration.silent_completion_pct.unwrap();
+        assert!((pct - 200.0 / 3.0).abs() < 1e-9);
+
+        let empty = compute_user_metrics(&db, "u1", &Period::Month(2024, 6))
+            .await
+            .unwrap();
+        assert_eq!(empty.collaboration.silent_completion_pct, None);
+    }
 }
@@ -0,0 +1,333 @@
+//! Deterministic notability scores for tasks, stored in
+//! `fact_tasks.notability_score` so the most notable work in a period can be
+//! picked out without an LLM.
+//!
+//! Comment, like, and subtask counts and lead time are log-scaled, so one
+//! long thread doesn't outweigh everything else:
+//!
+//! ```text
+//! score = comments * ln(1 + comment count) + likes * ln(1 + like count)
+//!       + subtasks * ln(1 + subtask count) + lead_time * ln(1 + weeks to complete)
+//!       + milestone (milestones only) + matching custom field weights
+//! ```
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::query::period::Period;
+use crate::storage::repository;
+use crate::storage::Database;
+
+/// Config key overriding the default weights, e.g.
+/// `comments=2,milestone=5,Priority:High=3`.
+pub const NOTABILITY_WEIGHTS_KEY: &str = "notability_weights";
+
+/// Weights for each notability signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotabilityWeights {
+    pub comments: f64,
+    pub likes: f64,
+    pub subtasks: f64,
+    pub lead_time: f64,
+    pub milestone: f64,
+    /// `(field name, value, weight)`: added when the task's custom field has
+    /// that display value. Names and values match case-insensitively.
+    pub fields: Vec<(String, String, f64)>,
+}
+
+impl Default for NotabilityWeights {
+    fn default() -> Self {
+        Self {
+            comments: 2.0,
+            likes: 1.5,
+            subtasks: 1.5,
+            lead_time: 1.0,
+            milestone: 3.0,
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Parse a `notability_weights` config value: comma-separated `signal=weight`
+/// entries, where the signal is `comments`, `likes`, `subtasks`,
+/// `lead_time`, `milestone`, or `Field:Value` for a custom field value.
+/// Signals left out keep their default weight.
+pub fn parse_notability_weights(spec: &str) -> Result<NotabilityWeights> {
+    let mut weights = NotabilityWeights::default();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let invalid = |reason: &str| {
+            Error::Config(format!(
+                "invalid {NOTABILITY_WEIGHTS_KEY} entry '{entry}': {reason}"
+            ))
+        };
+        let (signal, weight) = entry
+            .rsplit_once('=')
+            .ok_or_else(|| invalid("expected signal=weight"))?;
+        let weight: f64 = weight
+            .trim()
+            .parse()
+            .map_err(|_| invalid("weight is not a number"))?;
+        match signal.trim() {
+            "comments" => weights.comments = weight,
+            "likes" => weights.likes = weight,
+            "subtasks" => weights.subtasks = weight,
+            "lead_time" => weights.lead_time = weight,
+            "milestone" => weights.milestone = weight,
+            other => {
+                let (field, value) = other.split_once(':').ok_or_else(|| {
+                    invalid("unknown signal; use comments, likes, subtasks, lead_time, milestone, or Field:Value")
+                })?;
+                weights
+                    .fields
+                    .push((field.trim().to_string(), value.trim().to_string(), weight));
+            }
+        }
+    }
+    Ok(weights)
+}
+
+/// The configured weights, or the defaults when `notability_weights` is
+/// unset or invalid.
+pub fn load_weights(conn: &Connection) -> std::result::Result<NotabilityWeights, rusqlite::Error> {
+    let Some(spec) = repository::get_config(conn, NOTABILITY_WEIGHTS_KEY)? else {
+        return Ok(NotabilityWeights::default());
+    };
+    Ok(parse_notability_weights(&spec).unwrap_or_else(|e| {
+        log::warn!("{e}; using default notability weights");
+        NotabilityWeights::default()
+    }))
+}
+
+/// The signals a task is scored on.
+#[derive(Debug, Clone, Default)]
+struct TaskSignals {
+    comments: u64,
+    likes: u64,
+    subtasks: u64,
+    days_to_complete: Option<i64>,
+    is_milestone: bool,
+    /// `(field name, display value)`
+    fields: Vec<(String, String)>,
+}
+
+fn score(signals: &TaskSignals, weights: &NotabilityWeights) -> f64 {
+    let log = |n: f64| (1.0 + n).ln();
+    let mut score = weights.comments * log(signals.comments as f64)
+        + weights.likes * log(signals.likes as f64)
+        + weights.subtasks * log(signals.subtasks as f64)
+        + weights.lead_time * log(signals.days_to_complete.unwrap_or(0).max(0) as f64 / 7.0);
+    if signals.is_milestone {
+        score += weights.milestone;
+    }
+    for (field, value, weight) in &weights.fields {
+        if signals
+            .fields
+            .iter()
+            .any(|(f, v)| f.eq_ignore_ascii_case(field) && v.eq_ignore_ascii_case(value))
+        {
+            score += weight;
+        }
+    }
+    (score * 100.0).round() / 100.0
+}
+
+/// Recompute and store the notability score of each task in `task_gids`.
+/// Returns the number of tasks scored.
+pub fn score_tasks(
+    conn: &Connection,
+    weights: &NotabilityWeights,
+    task_gids: &[String],
+) -> std::result::Result<usize, rusqlite::Error> {
+    let mut signals_stmt = conn.prepare_cached(
+        "SELECT t.num_likes, t.num_subtasks, t.days_to_complete,
+                t.resource_subtype = 'milestone',
+                (SELECT COUNT(*) FROM fact_comments c
+                 WHERE c.task_gid = t.task_gid AND c.story_type = 'comment' AND c.is_bot = 0)
+         FROM fact_tasks t WHERE t.task_gid = ?1",
+    )?;
+    let mut fields_stmt = conn.prepare_cached(
+        "SELECT f.name, tcf.display_value FROM fact_task_custom_fields tcf
+         JOIN dim_custom_fields f ON f.field_gid = tcf.field_gid
+         WHERE tcf.task_gid = ?1",
+    )?;
+    let mut update =
+        conn.prepare_cached("UPDATE fact_tasks SET notability_score = ?2 WHERE task_gid = ?1")?;
+
+    let mut scored = 0;
+    for gid in task_gids {
+        let signals = signals_stmt.query_row([gid], |row| {
+            Ok(TaskSignals {
+                likes: row.get::<_, Option<i64>>(0)?.unwrap_or(0).max(0) as u64,
+                subtasks: row.get::<_, Option<i64>>(1)?.unwrap_or(0).max(0) as u64,
+                days_to_complete: row.get(2)?,
+                is_milestone: row.get(3)?,
+                comments: row.get::<_, i64>(4)? as u64,
+                fields: Vec::new(),
+            })
+        });
+        let mut signals = match signals {
+            Ok(signals) => signals,
+            Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+            Err(e) => return Err(e),
+        };
+        if !weights.fields.is_empty() {
+            signals.fields = fields_stmt
+                .query_map([gid], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<std::result::Result<_, _>>()?;
+        }
+        update.execute(params![gid, score(&signals, weights)])?;
+        scored += 1;
+    }
+    Ok(scored)
+}
+
+/// Recompute every task's notability score, e.g. after changing
+/// `notability_weights`. Returns the number of tasks scored.
+pub async fn rescore_all(db: &Database) -> Result<usize> {
+    let scored = db
+        .writer()
+        .call(|conn| {
+            let weights = load_weights(conn)?;
+            let gids: Vec<String> = conn
+                .prepare("SELECT task_gid FROM fact_tasks")?
+                .query_map([], |row| row.get(0))?
+                .collect::<std::result::Result<_, _>>()?;
+            score_tasks(conn, &weights, &gids)
+        })
+        .await?;
+    Ok(scored)
+}
+
+/// Whose completed tasks [`top_notable_tasks`] ranks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotableScope {
+    User(String),
+    Project(String),
+    /// Every synced task.
+    All,
+}
+
+/// A completed task and its notability score.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotableTask {
+    pub task_gid: String,
+    pub name: String,
+    pub assignee_name: Option<String>,
+    pub completed_at: Option<String>,
+    pub resource_subtype: String,
+    pub notability_score: f64,
+    pub permalink_url: Option<String>,
+}
+
+/// The `limit` most notable tasks completed in `period`, highest score
+/// first. Tasks not yet scored are left out.
+pub async fn top_notable_tasks(
+    db: &Database,
+    scope: &NotableScope,
+    period: &Period,
+    limit: u32,
+) -> Result<Vec<NotableTask>> {
+    let (from, until) = period.timestamp_bounds();
+    let (filter, gid) = match scope {
+        NotableScope::User(gid) => ("t.assignee_gid = ?3", Some(gid.clone())),
+        NotableScope::Project(gid) => (
+            "t.task_gid IN (SELECT task_gid FROM bridge_task_projects WHERE project_gid = ?3)",
+            Some(gid.clone()),
+        ),
+        NotableScope::All => ("?3 IS NULL", None),
+    };
+    let sql = format!(
+        "SELECT t.task_gid, t.name, u.name, t.completed_at, t.resource_subtype,
+                t.notability_score, t.permalink_url
+         FROM fact_tasks t
+         LEFT JOIN dim_users u ON u.user_gid = t.assignee_gid
+         WHERE t.is_completed = 1 AND t.is_deleted = 0 AND t.notability_score IS NOT NULL
+           AND t.completed_at >= ?1 AND t.completed_at < ?2 AND {filter}
+         ORDER BY t.notability_score DESC, t.completed_at DESC
+         LIMIT ?4"
+    );
+    let tasks = db
+        .reader()
+        .call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params![from, until, gid, limit], |row| {
+                Ok(NotableTask {
+                    task_gid: row.get(0)?,
+                    name: row.get(1)?,
+                    assignee_name: row.get(2)?,
+                    completed_at: row.get(3)?,
+                    resource_subtype: row.get(4)?,
+                    notability_score: row.get(5)?,
+                    permalink_url: row.get(6)?,
+                })
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()
+        })
+        .await?;
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notability_weights() {
+        let weights =
+            parse_notability_weights("comments=3, milestone=0, Priority:High=2.5").unwrap();
+        assert_eq!(weights.comments, 3.0);
+        assert_eq!(weights.milestone, 0.0);
+        assert_eq!(weights.likes, NotabilityWeights::default().likes);
+        assert_eq!(
+            weights.fields,
+            [("Priority".to_string(), "High".to_string(), 2.5)]
+        );
+        assert!(parse_notability_weights("comments").is_err());
+        assert!(parse_notability_weights("comments=lots").is_err());
+        assert!(parse_notability_weights("reactions=1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_score_and_rank_tasks() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Ada', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, created_at, created_date_key, modified_at, is_subtask, num_subtasks, num_likes, days_to_complete, resource_subtype, cached_at)
+                     VALUES ('t1', 'Launch', 'u1', 1, '2025-02-10T00:00:00Z', '2025-01-01T00:00:00Z', '2025-01-01', '2025-02-10T00:00:00Z', 0, 4, 3, 40, 'milestone', datetime('now')),
+                            ('t2', 'Fix typo', 'u1', 1, '2025-02-11T00:00:00Z', '2025-02-11T00:00:00Z', '2025-02-11', '2025-02-11T00:00:00Z', 0, 0, 0, 0, 'default_task', datetime('now')),
+                            ('t3', 'Pricing', 'u1', 1, '2025-02-12T00:00:00Z', '2025-02-01T00:00:00Z', '2025-02-01', '2025-02-12T00:00:00Z', 0, 0, 0, 11, 'default_task', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'u1', 'Shipped!', 'comment', '2025-02-10T00:00:00Z', '2025-02-10', datetime('now')),
+                            ('c2', 't1', 'u1', 'moved', 'system', '2025-02-10T00:00:00Z', '2025-02-10', datetime('now'));
+                     INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('f1', 'Priority', 'enum', datetime('now'));
+                     INSERT INTO fact_task_custom_fields (task_gid, field_gid, display_value)
+                     VALUES ('t3', 'f1', 'high');
+                     INSERT INTO app_config (key, value, updated_at)
+                     VALUES ('notability_weights', 'Priority:High=10', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(rescore_all(&db).await.unwrap(), 3);
+        let top = top_notable_tasks(
+            &db,
+            &NotableScope::User("u1".to_string()),
+            &Period::Month(2025, 2),
+            2,
+        )
+        .await
+        .unwrap();
+        let ranked: Vec<(&str, f64)> = top
+            .iter()
+            .map(|t| (t.task_gid.as_str(), t.notability_score))
+            .collect();
+        // Pricing: 10 (High) + ln(1 + 11/7); Launch: 2 ln 2 + 1.5 ln 4 + 1.5 ln 5 + ln(1 + 40/7) + 3
+        assert_eq!(ranked, [("t3", 10.94), ("t1", 10.78)]);
+    }
+}
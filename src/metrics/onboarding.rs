@@ -0,0 +1,328 @@
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::storage::Database;
+
+/// A project the new hire started working in.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingProject {
+    pub project_gid: String,
+    pub name: Option<String>,
+    /// First day they were assigned, completed, or commented on a task in it.
+    pub first_activity: String,
+    pub tasks_completed: u64,
+}
+
+/// One week of the onboarding window.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingWeek {
+    pub week_start: String,
+    pub tasks_completed: u64,
+    pub comments: u64,
+}
+
+/// Someone the new hire worked with, and how.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingCollaborator {
+    pub user_gid: String,
+    pub user_name: Option<String>,
+    /// Tasks both of them were assigned to or commented on.
+    pub shared_tasks: u64,
+    /// Comment replies between the two, in either direction.
+    pub replies: u64,
+    /// Likes between the two on those tasks, in either direction.
+    pub likes: u64,
+}
+
+/// A new hire's first weeks: projects joined, completions, and who they
+/// interacted with.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingReport {
+    pub user_gid: String,
+    pub user_name: Option<String>,
+    pub start_date: String,
+    pub end_date: String,
+    pub tasks_completed: u64,
+    pub comments: u64,
+    /// In the order they were joined.
+    pub projects: Vec<OnboardingProject>,
+    pub weeks: Vec<OnboardingWeek>,
+    /// Most interactions first.
+    pub collaborators: Vec<OnboardingCollaborator>,
+}
+
+/// Tasks the user touched in `[?2, ?3]`, one row per touch: assigned tasks
+/// created or completed in the window, and tasks they commented on.
+const ACTIVITY_CTE: &str = "
+    activity AS (
+        SELECT task_gid, created_date_key AS day, 0 AS done FROM fact_tasks
+        WHERE assignee_gid = ?1 AND is_deleted = 0
+          AND created_date_key >= ?2 AND created_date_key <= ?3
+        UNION ALL
+        SELECT task_gid, completed_date_key, 1 FROM fact_tasks
+        WHERE assignee_gid = ?1 AND is_deleted = 0 AND is_completed = 1
+          AND completed_date_key >= ?2 AND completed_date_key <= ?3
+        UNION ALL
+        SELECT task_gid, created_date_key, 0 FROM fact_comments
+        WHERE author_gid = ?1 AND story_type = 'comment'
+          AND created_date_key >= ?2 AND created_date_key <= ?3
+    )";
+
+/// Summarize `user_gid`'s first `weeks` weeks, starting at `since` or, by
+/// default, their first assigned task or comment in the warehouse.
+pub async fn compute_onboarding_report(
+    db: &Database,
+    user_gid: &str,
+    weeks: u32,
+    since: Option<NaiveDate>,
+) -> Result<OnboardingReport> {
+    if weeks == 0 {
+        return Err(Error::Config("weeks must be at least 1".into()));
+    }
+    let user_gid = user_gid.to_string();
+    let not_found = Error::NotFound(format!("no activity for user {user_gid}"));
+    let report = db
+        .reader()
+        .call(move |conn| {
+            let start = match since {
+                Some(d) => d,
+                None => {
+                    let first: Option<String> = conn.query_row(
+                        "SELECT MIN(day) FROM (
+                             SELECT created_date_key AS day FROM fact_tasks
+                             WHERE assignee_gid = ?1 AND is_deleted = 0
+                             UNION ALL
+                             SELECT created_date_key FROM fact_comments
+                             WHERE author_gid = ?1 AND story_type = 'comment'
+                         )",
+                        [&user_gid],
+                        |row| row.get(0),
+                    )?;
+                    match first.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()) {
+                        Some(d) => d,
+                        None => return Ok(None),
+                    }
+                }
+            };
+            let end = start + Duration::days(7 * i64::from(weeks) - 1);
+            let start_str = start.format("%Y-%m-%d").to_string();
+            let end_str = end.format("%Y-%m-%d").to_string();
+            let args = rusqlite::params![user_gid, start_str, end_str];
+
+            let user_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_users WHERE user_gid = ?1",
+                    [&user_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+
+            let mut weekly: Vec<OnboardingWeek> = (0..weeks)
+                .map(|w| OnboardingWeek {
+                    week_start: (start + Duration::days(7 * i64::from(w)))
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                    tasks_completed: 0,
+                    comments: 0,
+                })
+                .collect();
+            let mut stmt = conn.prepare(
+                "SELECT completed_date_key, 1 FROM fact_tasks
+                 WHERE assignee_gid = ?1 AND is_deleted = 0 AND is_completed = 1
+                   AND completed_date_key >= ?2 AND completed_date_key <= ?3
+                 UNION ALL
+                 SELECT created_date_key, 0 FROM fact_comments
+                 WHERE author_gid = ?1 AND story_type = 'comment'
+                   AND created_date_key >= ?2 AND created_date_key <= ?3",
+            )?;
+            let events: Vec<(String, bool)> = stmt
+                .query_map(args, |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            for (day, completed) in events {
+                let Ok(day) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") else {
+                    continue;
+                };
+                let Some(week) = weekly.get_mut(((day - start).num_days() / 7) as usize) else {
+                    continue;
+                };
+                if completed {
+                    week.tasks_completed += 1;
+                } else {
+                    week.comments += 1;
+                }
+            }
+
+            let mut stmt = conn.prepare(&format!(
+                "WITH {ACTIVITY_CTE}
+                 SELECT btp.project_gid, p.name, MIN(a.day),
+                        COUNT(DISTINCT CASE WHEN a.done = 1 THEN a.task_gid END)
+                 FROM activity a
+                 JOIN bridge_task_projects btp ON btp.task_gid = a.task_gid
+                 LEFT JOIN dim_projects p ON p.project_gid = btp.project_gid
+                 GROUP BY btp.project_gid
+                 ORDER BY MIN(a.day), p.name"
+            ))?;
+            let projects: Vec<OnboardingProject> = stmt
+                .query_map(args, |row| {
+                    Ok(OnboardingProject {
+                        project_gid: row.get(0)?,
+                        name: row.get(1)?,
+                        first_activity: row.get(2)?,
+                        tasks_completed: row.get::<_, i64>(3)? as u64,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let mut stmt = conn.prepare(&format!(
+                "WITH {ACTIVITY_CTE},
+                 touched AS (SELECT DISTINCT task_gid FROM activity),
+                 shared AS (
+                     SELECT c.author_gid AS other, c.task_gid FROM fact_comments c
+                     JOIN touched tt ON tt.task_gid = c.task_gid
+                     WHERE c.story_type = 'comment' AND c.is_bot = 0 AND c.author_gid IS NOT NULL
+                       AND c.author_gid != ?1
+                       AND c.created_date_key >= ?2 AND c.created_date_key <= ?3
+                     UNION
+                     SELECT t.assignee_gid, t.task_gid FROM fact_tasks t
+                     JOIN touched tt ON tt.task_gid = t.task_gid
+                     WHERE t.assignee_gid IS NOT NULL AND t.assignee_gid != ?1
+                 ),
+                 replies AS (
+                     SELECT p.author_gid AS other FROM fact_comments c
+                     JOIN fact_comments p ON p.comment_gid = c.parent_comment_gid
+                     WHERE c.author_gid = ?1 AND p.author_gid != ?1 AND p.is_bot = 0
+                       AND c.created_date_key >= ?2 AND c.created_date_key <= ?3
+                     UNION ALL
+                     SELECT c.author_gid FROM fact_comments c
+                     JOIN fact_comments p ON p.comment_gid = c.parent_comment_gid
+                     WHERE p.author_gid = ?1 AND c.author_gid != ?1 AND c.is_bot = 0
+                       AND c.created_date_key >= ?2 AND c.created_date_key <= ?3
+                 ),
+                 likes AS (
+                     SELECT l.user_gid AS other FROM fact_task_likes l
+                     JOIN touched tt ON tt.task_gid = l.task_gid
+                     JOIN fact_tasks t ON t.task_gid = l.task_gid
+                     WHERE t.assignee_gid = ?1 AND l.user_gid != ?1
+                     UNION ALL
+                     SELECT t.assignee_gid FROM fact_task_likes l
+                     JOIN touched tt ON tt.task_gid = l.task_gid
+                     JOIN fact_tasks t ON t.task_gid = l.task_gid
+                     WHERE l.user_gid = ?1 AND t.assignee_gid IS NOT NULL
+                       AND t.assignee_gid != ?1
+                 ),
+                 edges AS (
+                     SELECT other, 1 AS shared, 0 AS replies, 0 AS likes FROM shared
+                     UNION ALL SELECT other, 0, 1, 0 FROM replies
+                     UNION ALL SELECT other, 0, 0, 1 FROM likes
+                 )
+                 SELECT e.other, u.name, SUM(e.shared), SUM(e.replies), SUM(e.likes)
+                 FROM edges e
+                 LEFT JOIN dim_users u ON u.user_gid = e.other
+                 GROUP BY e.other
+                 ORDER BY SUM(e.shared) + SUM(e.replies) + SUM(e.likes) DESC, u.name"
+            ))?;
+            let collaborators: Vec<OnboardingCollaborator> = stmt
+                .query_map(args, |row| {
+                    Ok(OnboardingCollaborator {
+                        user_gid: row.get(0)?,
+                        user_name: row.get(1)?,
+                        shared_tasks: row.get::<_, i64>(2)? as u64,
+                        replies: row.get::<_, i64>(3)? as u64,
+                        likes: row.get::<_, i64>(4)? as u64,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok::<_, rusqlite::Error>(Some(OnboardingReport {
+                user_gid,
+                user_name,
+                start_date: start_str,
+                end_date: end_str,
+                tasks_completed: weekly.iter().map(|w| w.tasks_completed).sum(),
+                comments: weekly.iter().map(|w| w.comments).sum(),
+                projects,
+                weeks: weekly,
+                collaborators,
+            }))
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+    report.ok_or(not_found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compute_onboarding_report() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('new', 'Nia', datetime('now')), ('m', 'Mo', datetime('now')), ('x', 'Xu', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Onboarding', 'w1', datetime('now')), ('p2', 'Billing', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Set up laptop', 'new', 1, '2025-02-04T10:00:00Z', '2025-02-04', '2025-02-03', '2025-02-03', datetime('now')),
+                            ('t2', 'First bug', 'new', 1, '2025-02-12T10:00:00Z', '2025-02-12', '2025-02-10', '2025-02-10', datetime('now')),
+                            ('t3', 'Invoice export', 'm', 0, NULL, NULL, '2025-01-15', '2025-01-15', datetime('now')),
+                            ('t4', 'Much later', 'new', 1, '2025-06-01T10:00:00Z', '2025-06-01', '2025-05-20', '2025-05-20', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p2'), ('t3', 'p2'), ('t4', 'p2');
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, parent_comment_gid, cached_at)
+                     VALUES ('c1', 't3', 'm', 'Can you take a look?', 'comment', '2025-02-11T09:00:00Z', '2025-02-11', NULL, datetime('now')),
+                            ('c2', 't3', 'new', 'Sure', 'comment', '2025-02-11T10:00:00Z', '2025-02-11', 'c1', datetime('now')),
+                            ('c3', 't2', 'x', 'Nice', 'comment', '2025-02-12T11:00:00Z', '2025-02-12', NULL, datetime('now'));
+                     INSERT INTO fact_task_likes (task_gid, user_gid, cached_at) VALUES ('t2', 'm', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let r = compute_onboarding_report(&db, "new", 2, None)
+            .await
+            .unwrap();
+        assert_eq!(r.user_name.as_deref(), Some("Nia"));
+        assert_eq!(
+            (r.start_date.as_str(), r.end_date.as_str()),
+            ("2025-02-03", "2025-02-16")
+        );
+        assert_eq!((r.tasks_completed, r.comments), (2, 1));
+        assert_eq!(r.weeks[0].tasks_completed, 1);
+        assert_eq!((r.weeks[1].tasks_completed, r.weeks[1].comments), (1, 1));
+
+        let projects: Vec<(&str, &str, u64)> = r
+            .projects
+            .iter()
+            .map(|p| {
+                (
+                    p.project_gid.as_str(),
+                    p.first_activity.as_str(),
+                    p.tasks_completed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            projects,
+            vec![("p1", "2025-02-03", 1), ("p2", "2025-02-10", 1)]
+        );
+
+        // Mo: shared t3 (assignee and commenter), one reply, one like on t2
+        let mo = &r.collaborators[0];
+        assert_eq!(mo.user_gid, "m");
+        assert_eq!((mo.shared_tasks, mo.replies, mo.likes), (1, 1, 1));
+        let xu = &r.collaborators[1];
+        assert_eq!((xu.user_gid.as_str(), xu.shared_tasks), ("x", 1));
+
+        assert!(compute_onboarding_report(&db, "nobody", 4, None)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::storage::Database;
+
+/// Why a task is considered orphaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanReason {
+    Unassigned,
+    DeactivatedAssignee,
+}
+
+/// A potential new owner for an orphaned task, ranked by local evidence.
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerCandidate {
+    pub user_gid: String,
+    pub user_name: Option<String>,
+    pub score: i64,
+    /// Comments this user left on the orphaned task.
+    pub comments_on_task: u64,
+    /// Completed tasks in the same project (or sharing a tag) assigned to this user.
+    pub related_completions: u64,
+}
+
+/// An open task with no active owner.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedTask {
+    pub task_gid: String,
+    pub name: String,
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    pub section_name: Option<String>,
+    pub assignee_gid: Option<String>,
+    pub assignee_name: Option<String>,
+    pub reason: OrphanReason,
+    pub due_on: Option<String>,
+    pub permalink_url: Option<String>,
+    pub candidates: Vec<OwnerCandidate>,
+}
+
+/// Orphaned work for a project or team.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanReport {
+    pub entity_key: String,
+    pub entity_name: Option<String>,
+    pub tasks: Vec<OrphanedTask>,
+}
+
+/// Suggested owner for an orphaned task (produced by the `llm` feature).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnerSuggestion {
+    pub task_gid: String,
+    pub user_gid: String,
+    pub rationale: String,
+}
+
+/// Find open tasks in a project that are unassigned or assigned to a deactivated user.
+pub async fn find_project_orphans(
+    db: &Database,
+    project_gid: &str,
+    candidate_limit: usize,
+) -> Result<OrphanReport> {
+    let project_gid = project_gid.to_string();
+
+    db.reader()
+        .call(move |conn| {
+            let entity_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_projects WHERE project_gid = ?1",
+                    [&project_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+            let tasks =
+                find_orphans_sql(conn, std::slice::from_ref(&project_gid), candidate_limit)?;
+            Ok::<OrphanReport, rusqlite::Error>(OrphanReport {
+                entity_key: format!("project:{project_gid}"),
+                entity_name,
+                tasks,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+/// Find orphaned tasks across all non-archived projects owned by a team.
+pub async fn find_team_orphans(
+    db: &Database,
+    team_gid: &str,
+    candidate_limit: usize,
+) -> Result<OrphanReport> {
+    let team_gid = team_gid.to_string();
+
+    db.reader()
+        .call(move |conn| {
+            let entity_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_teams WHERE team_gid = ?1",
+                    [&team_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+
+            let mut stmt = conn.prepare(
+                "SELECT project_gid FROM dim_projects
+                 WHERE team_gid = ?1 AND is_archived = 0 AND COALESCE(is_template, 0) = 0",
+            )?;
+            let project_gids: Vec<String> = stmt
+                .query_map([&team_gid], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let tasks = find_orphans_sql(conn, &project_gids, candidate_limit)?;
+            Ok::<OrphanReport, rusqlite::Error>(OrphanReport {
+                entity_key: format!("team:{team_gid}"),
+                entity_name,
+                tasks,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+fn find_orphans_sql(
+    conn: &rusqlite::Connection,
+    project_gids: &[String],
+    candidate_limit: usize,
+) -> std::result::Result<Vec<OrphanedTask>, rusqlite::Error> {
+    if project_gids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let placeholders = project_gids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+    let sql = format!(
+        "SELECT t.task_gid, t.name, btp.project_gid, p.name, s.name, btp.section_gid,
+                t.assignee_gid, u.name, t.due_on, t.permalink_url
+         FROM fact_tasks t
+         JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+         LEFT JOIN dim_projects p ON p.project_gid = btp.project_gid
+         LEFT JOIN dim_sections s ON s.section_gid = btp.section_gid
+         LEFT JOIN dim_users u ON u.user_gid = t.assignee_gid
+         WHERE t.is_completed = 0 AND t.is_deleted = 0
+           AND (t.assignee_gid IS NULL OR u.is_active = 0)
+           AND btp.project_gid IN ({placeholders})
+         GROUP BY t.task_gid
+         ORDER BY t.due_on IS NULL, t.due_on, t.created_at"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    for (i, pgid) in project_gids.iter().enumerate() {
+        stmt.raw_bind_parameter(i + 1, pgid)?;
+    }
+
+    let mut rows_out = Vec::new();
+    let mut rows = stmt.raw_query();
+    while let Some(row) = rows.next()? {
+        let assignee_gid: Option<String> = row.get(6)?;
+        let reason = if assignee_gid.is_some() {
+            OrphanReason::DeactivatedAssignee
+        } else {
+            OrphanReason::Unassigned
+        };
+        let section_gid: Option<String> = row.get(5)?;
+        rows_out.push((
+            OrphanedTask {
+                task_gid: row.get(0)?,
+                name: row.get(1)?,
+                project_gid: row.get(2)?,
+                project_name: row.get(3)?,
+                section_name: row.get(4)?,
+                assignee_gid,
+                assignee_name: row.get(7)?,
+                reason,
+                due_on: row.get(8)?,
+                permalink_url: row.get(9)?,
+                candidates: Vec::new(),
+            },
+            section_gid,
+        ));
+    }
+
+    let mut tasks = Vec::with_capacity(rows_out.len());
+    for (mut task, section_gid) in rows_out {
+        task.candidates = owner_candidates_sql(
+            conn,
+            &task.task_gid,
+            &task.project_gid,
+            section_gid.as_deref(),
+            candidate_limit,
+        )?;
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+/// Rank active users who could pick up a task.
+///
+/// Commenting on the task itself is the strongest signal (3 points per
+/// comment). Completing a task in the same section or sharing a tag scores
+/// 2 points, and completing any other task in the project scores 1.
+fn owner_candidates_sql(
+    conn: &rusqlite::Connection,
+    task_gid: &str,
+    project_gid: &str,
+    section_gid: Option<&str>,
+    limit: usize,
+) -> std::result::Result<Vec<OwnerCandidate>, rusqlite::Error> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "WITH cand AS (
+             SELECT c.author_gid AS user_gid, 3 AS weight, 1 AS is_comment, 0 AS is_completion
+             FROM fact_comments c
+             WHERE c.task_gid = ?1 AND c.story_type = 'comment' AND c.is_bot = 0
+               AND c.author_gid IS NOT NULL
+             UNION ALL
+             SELECT t.assignee_gid,
+                    CASE WHEN ?3 IS NOT NULL AND btp.section_gid = ?3 THEN 2 ELSE 1 END,
+                    0, 1
+             FROM fact_tasks t
+             JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+             WHERE btp.project_gid = ?2 AND t.is_completed = 1 AND t.is_deleted = 0
+               AND t.assignee_gid IS NOT NULL AND t.task_gid != ?1
+             UNION ALL
+             SELECT t.assignee_gid, 2, 0, 1
+             FROM bridge_task_tags mine
+             JOIN bridge_task_tags other
+               ON other.tag_gid = mine.tag_gid AND other.task_gid != mine.task_gid
+             JOIN fact_tasks t ON t.task_gid = other.task_gid
+             WHERE mine.task_gid = ?1 AND t.is_completed = 1 AND t.is_deleted = 0
+               AND t.assignee_gid IS NOT NULL
+         )
+         SELECT cand.user_gid, u.name, SUM(cand.weight), SUM(cand.is_comment), SUM(cand.is_completion)
+         FROM cand
+         JOIN dim_users u ON u.user_gid = cand.user_gid
+         WHERE u.is_active = 1
+         GROUP BY cand.user_gid
+         ORDER BY 3 DESC, u.name
+         LIMIT ?4",
+    )?;
+    let candidates = stmt
+        .query_map(
+            params![task_gid, project_gid, section_gid, limit as i64],
+            |row| {
+                Ok(OwnerCandidate {
+                    user_gid: row.get(0)?,
+                    user_name: row.get(1)?,
+                    score: row.get(2)?,
+                    comments_on_task: row.get::<_, i64>(3)? as u64,
+                    related_completions: row.get::<_, i64>(4)? as u64,
+                })
+            },
+        )?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_find_project_orphans_with_candidates() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, is_active, cached_at) VALUES ('u2', 'Gone', 0, datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u3', 'Carol', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Widget', 'w1', datetime('now'));
+
+                     -- Orphan: unassigned
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Unowned', 0, '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t1', 'p1');
+
+                     -- Orphan: deactivated assignee
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('t2', 'Left behind', 'u2', 0, '2025-01-02', '2025-01-02', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t2', 'p1');
+
+                     -- Not orphaned: active assignee
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('t3', 'Owned', 'u1', 0, '2025-01-03', '2025-01-03', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t3', 'p1');
+
+                     -- Completed work by Alice in the project
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('t4', 'Done', 'u1', 1, '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t4', 'p1');
+
+                     -- Carol commented on the unassigned task
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'u3', 'I can look at this', 'comment', '2025-01-05', '2025-01-05', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let report = find_project_orphans(&db, "p1", 3).await.unwrap();
+        assert_eq!(report.entity_name.as_deref(), Some("Widget"));
+        assert_eq!(report.tasks.len(), 2);
+
+        let t1 = report.tasks.iter().find(|t| t.task_gid == "t1").unwrap();
+        assert_eq!(t1.reason, OrphanReason::Unassigned);
+        assert_eq!(t1.candidates[0].user_gid, "u3");
+        assert_eq!(t1.candidates[0].comments_on_task, 1);
+        assert!(t1.candidates.iter().any(|c| c.user_gid == "u1"));
+
+        let t2 = report.tasks.iter().find(|t| t.task_gid == "t2").unwrap();
+        assert_eq!(t2.reason, OrphanReason::DeactivatedAssignee);
+        // Deactivated users are never suggested
+        assert!(t2.candidates.iter().all(|c| c.user_gid != "u2"));
+    }
+}
@@ -0,0 +1,264 @@
+//! Lifecycle phases (Backlog, In Progress, Review, Done) mapped from section
+//! names, so boards with different section names can be compared.
+
+use serde::Serialize;
+
+use super::cycle_time;
+use super::types::PhaseMetrics;
+use crate::error::{Error, Result};
+use crate::storage::repository;
+
+/// Config key holding the section-to-phase mapping.
+pub const SECTION_PHASES_KEY: &str = "section_phases";
+
+/// Section names for each phase when `section_phases` doesn't list it.
+const DEFAULT_SECTIONS: &[(Phase, &str)] = &[
+    (
+        Phase::Backlog,
+        "Backlog,To Do,To-Do,Todo,Up Next,Icebox,Ideas",
+    ),
+    (Phase::InProgress, "In Progress,Doing,WIP,Active,Started"),
+    (Phase::Review, "Review,In Review,Code Review,QA,Testing"),
+    (Phase::Done, "Done,Complete,Completed,Shipped,Closed"),
+];
+
+/// A lifecycle phase a section can belong to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Backlog,
+    InProgress,
+    Review,
+    Done,
+}
+
+impl Phase {
+    /// Parse a phase name, ignoring case, spaces, `-`, and `_`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name: String = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_str() {
+            "backlog" => Some(Phase::Backlog),
+            "inprogress" => Some(Phase::InProgress),
+            "review" => Some(Phase::Review),
+            "done" => Some(Phase::Done),
+            _ => None,
+        }
+    }
+}
+
+/// Which phase each section name belongs to.
+///
+/// Configured as `phase=section,section;phase=section`:
+///
+/// ```text
+/// backlog=Ideas,Next Up;in progress=Building;review=Needs QA;done=Released
+/// ```
+///
+/// Phases missing from the config keep their default section names.
+/// Section names match case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionPhases {
+    entries: Vec<(Phase, Vec<String>)>,
+}
+
+impl Default for SectionPhases {
+    fn default() -> Self {
+        let entries = DEFAULT_SECTIONS
+            .iter()
+            .map(|(phase, names)| (*phase, split_names(names)))
+            .collect();
+        Self { entries }
+    }
+}
+
+impl SectionPhases {
+    /// The phase a section belongs to, if any.
+    pub fn phase_of(&self, section_name: &str) -> Option<Phase> {
+        let name = section_name.trim().to_lowercase();
+        self.entries
+            .iter()
+            .find(|(_, names)| names.contains(&name))
+            .map(|(phase, _)| *phase)
+    }
+}
+
+fn split_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !n.is_empty())
+        .collect()
+}
+
+/// Parse a `section_phases` config value.
+pub fn parse_section_phases(spec: &str) -> Result<SectionPhases> {
+    let mut phases = SectionPhases::default();
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, sections) = entry.split_once('=').ok_or_else(|| {
+            Error::Config(format!(
+                "invalid section_phases entry '{entry}': expected phase=section,section,..."
+            ))
+        })?;
+        let phase = Phase::parse(name).ok_or_else(|| {
+            Error::Config(format!(
+                "unknown phase '{}' in section_phases. Use: backlog, in progress, review, done",
+                name.trim()
+            ))
+        })?;
+        let sections = split_names(sections);
+        phases.entries.retain(|(p, _)| *p != phase);
+        // Configured phases are matched before the defaults
+        phases.entries.insert(0, (phase, sections));
+    }
+    Ok(phases)
+}
+
+/// Load the configured mapping, falling back to the defaults (with a
+/// warning) if it doesn't parse.
+pub(crate) fn load_section_phases(
+    conn: &rusqlite::Connection,
+) -> std::result::Result<SectionPhases, rusqlite::Error> {
+    let Some(spec) = repository::get_config(conn, SECTION_PHASES_KEY)? else {
+        return Ok(SectionPhases::default());
+    };
+    Ok(parse_section_phases(&spec).unwrap_or_else(|e| {
+        log::warn!("Ignoring {SECTION_PHASES_KEY}: {e}");
+        SectionPhases::default()
+    }))
+}
+
+/// Open tasks per phase and flow efficiency for a project, over tasks
+/// completed within `[start, end]`.
+pub(crate) fn compute_phase_metrics_sql(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    start: &str,
+    end: &str,
+) -> std::result::Result<PhaseMetrics, rusqlite::Error> {
+    let phases = load_section_phases(conn)?;
+    let mut metrics = PhaseMetrics::default();
+
+    let mut stmt = conn.prepare(
+        "SELECT s.name, COUNT(*)
+         FROM bridge_task_projects btp
+         JOIN fact_tasks t ON t.task_gid = btp.task_gid
+         LEFT JOIN dim_sections s ON s.section_gid = btp.section_gid
+         WHERE btp.project_gid = ?1 AND t.is_completed = 0 AND t.is_deleted = 0
+         GROUP BY s.name
+         ORDER BY s.name",
+    )?;
+    let mut rows = stmt.query([project_gid])?;
+    while let Some(row) = rows.next()? {
+        let section: Option<String> = row.get(0)?;
+        let count = row.get::<_, i64>(1)? as u64;
+        match section.as_deref().and_then(|s| phases.phase_of(s)) {
+            Some(Phase::Backlog) => metrics.backlog_open += count,
+            Some(Phase::InProgress) => metrics.in_progress_open += count,
+            Some(Phase::Review) => metrics.review_open += count,
+            Some(Phase::Done) => metrics.done_open += count,
+            None => {
+                metrics.unmapped_open += count;
+                if let Some(section) = section {
+                    metrics.unmapped_sections.push(section);
+                }
+            }
+        }
+    }
+
+    let cycles = cycle_time::completed_task_cycles(conn, project_gid, start, end, &[])?;
+    let (mut active, mut in_flight) = (0.0, 0.0);
+    for (section, days) in cycles.iter().flat_map(|c| c.dwell.iter()) {
+        match phases.phase_of(section) {
+            Some(Phase::InProgress) => {
+                active += days;
+                in_flight += days;
+            }
+            Some(Phase::Review) => in_flight += days,
+            _ => {}
+        }
+    }
+    if in_flight > 0.0 {
+        metrics.flow_efficiency_pct = Some(active / in_flight * 100.0);
+    }
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::compute_project_metrics;
+    use crate::query::period::Period;
+    use crate::storage::{transitions, Database};
+
+    #[test]
+    fn test_parse_section_phases() {
+        let defaults = SectionPhases::default();
+        assert_eq!(defaults.phase_of(" doing "), Some(Phase::InProgress));
+        assert_eq!(defaults.phase_of("QA"), Some(Phase::Review));
+        assert_eq!(defaults.phase_of("Launch"), None);
+
+        let phases = parse_section_phases("In-Progress=Building, Doing ; done=QA").unwrap();
+        assert_eq!(phases.phase_of("building"), Some(Phase::InProgress));
+        // Configured names win over another phase's defaults
+        assert_eq!(phases.phase_of("QA"), Some(Phase::Done));
+        // Phases left out keep their defaults, listed ones are replaced
+        assert_eq!(phases.phase_of("Backlog"), Some(Phase::Backlog));
+        assert_eq!(phases.phase_of("Complete"), None);
+
+        assert!(parse_section_phases("shipping=Done").is_err());
+        assert!(parse_section_phases("done").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_project_phase_metrics() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO dim_sections (section_gid, project_gid, name, sort_order, cached_at) VALUES
+                        ('s1', 'p1', 'Ideas', 0, datetime('now')),
+                        ('s2', 'p1', 'Building', 1, datetime('now')),
+                        ('s3', 'p1', 'Review', 2, datetime('now')),
+                        ('s4', 'p1', 'Parking lot', 3, datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at) VALUES
+                        ('t1', 'Done', 1, '2025-01-10T00:00:00Z', '2025-01-10', '2025-01-01T00:00:00Z', '2025-01-01', datetime('now')),
+                        ('t2', 'Open 1', 0, NULL, NULL, '2025-01-01T00:00:00Z', '2025-01-01', datetime('now')),
+                        ('t3', 'Open 2', 0, NULL, NULL, '2025-01-01T00:00:00Z', '2025-01-01', datetime('now')),
+                        ('t4', 'Open 3', 0, NULL, NULL, '2025-01-01T00:00:00Z', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid, section_gid) VALUES
+                        ('t1', 'p1', 's3'), ('t2', 'p1', 's1'), ('t3', 'p1', 's2'), ('t4', 'p1', 's4');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at) VALUES
+                        ('c1', 't1', 'moved this task from \"Ideas\" to \"Building\" in Roadmap', 'section_changed', '2025-01-02T00:00:00Z', '2025-01-02', datetime('now')),
+                        ('c2', 't1', 'moved this task from \"Building\" to \"Review\" in Roadmap', 'section_changed', '2025-01-08T00:00:00Z', '2025-01-08', datetime('now'));
+                     INSERT INTO app_config (key, value, updated_at) VALUES ('section_phases', 'backlog=Ideas;in progress=Building', datetime('now'));",
+                )?;
+                transitions::rebuild_section_transitions(conn, "t1")?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let m = compute_project_metrics(&db, "p1", &Period::Month(2025, 1))
+            .await
+            .unwrap();
+        let p = m.phases;
+        assert_eq!(
+            (
+                p.backlog_open,
+                p.in_progress_open,
+                p.review_open,
+                p.unmapped_open
+            ),
+            (1, 1, 0, 1)
+        );
+        assert_eq!(p.unmapped_sections, ["Parking lot"]);
+        // 6 days Building, then 2 days in Review before completion
+        assert_eq!(p.flow_efficiency_pct, Some(75.0));
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::query::period::Period;
+use crate::storage::Database;
+
+/// Likes given and received by one user.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserRecognition {
+    pub user_gid: String,
+    pub user_name: Option<String>,
+    /// Likes this user left on other people's tasks.
+    pub given: u64,
+    /// Likes on tasks assigned to this user.
+    pub received: u64,
+}
+
+/// Likes exchanged on tasks completed in a period, per user.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecognitionReport {
+    pub period_key: String,
+    pub total_likes: u64,
+    /// Most recognized first.
+    pub users: Vec<UserRecognition>,
+}
+
+/// Count likes given and received on tasks completed within `period`.
+///
+/// The Asana API does not timestamp likes, so a task's completion date
+/// stands in for when it was recognized. Self-likes are ignored.
+pub async fn compute_recognition(db: &Database, period: &Period) -> Result<RecognitionReport> {
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+    let period_key = period.to_key();
+
+    db.reader()
+        .call(move |conn| {
+            let sql = "
+                WITH period_likes AS (
+                    SELECT l.user_gid AS giver, t.assignee_gid AS receiver
+                    FROM fact_task_likes l
+                    JOIN fact_tasks t ON t.task_gid = l.task_gid
+                    WHERE t.is_completed = 1 AND t.is_deleted = 0
+                      AND t.completed_date_key >= ?1 AND t.completed_date_key <= ?2
+                      AND (t.assignee_gid IS NULL OR t.assignee_gid != l.user_gid)
+                ),
+                counts AS (
+                    SELECT giver AS user_gid, 1 AS given, 0 AS received FROM period_likes
+                    UNION ALL
+                    SELECT receiver, 0, 1 FROM period_likes WHERE receiver IS NOT NULL
+                )
+                SELECT c.user_gid, u.name, SUM(c.given), SUM(c.received)
+                FROM counts c
+                LEFT JOIN dim_users u ON u.user_gid = c.user_gid
+                GROUP BY c.user_gid
+                ORDER BY SUM(c.received) DESC, SUM(c.given) DESC, u.name";
+            let mut stmt = conn.prepare(sql)?;
+            let users: Vec<UserRecognition> = stmt
+                .query_map(rusqlite::params![start_str, end_str], |row| {
+                    Ok(UserRecognition {
+                        user_gid: row.get(0)?,
+                        user_name: row.get(1)?,
+                        given: row.get::<_, i64>(2)? as u64,
+                        received: row.get::<_, i64>(3)? as u64,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            let total_likes = users.iter().map(|u| u.given).sum();
+
+            Ok::<RecognitionReport, rusqlite::Error>(RecognitionReport {
+                period_key,
+                total_likes,
+                users,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compute_recognition() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u2', 'Bob', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u3', 'Cara', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Shipped', 'u1', 1, '2025-01-10', '2025-01-10', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t2', 'Old work', 'u2', 1, '2024-11-10', '2024-11-10', '2024-11-01', '2024-11-01', datetime('now'));
+                     INSERT INTO fact_task_likes (task_gid, user_gid, cached_at) VALUES ('t1', 'u2', datetime('now'));
+                     INSERT INTO fact_task_likes (task_gid, user_gid, cached_at) VALUES ('t1', 'u3', datetime('now'));
+                     INSERT INTO fact_task_likes (task_gid, user_gid, cached_at) VALUES ('t1', 'u1', datetime('now'));
+                     INSERT INTO fact_task_likes (task_gid, user_gid, cached_at) VALUES ('t2', 'u1', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let report = compute_recognition(&db, &Period::Month(2025, 1))
+            .await
+            .unwrap();
+        // Self-like on t1 and the like on t2 (outside the period) are excluded
+        assert_eq!(report.total_likes, 2);
+        assert_eq!(report.users[0].user_gid, "u1");
+        assert_eq!(report.users[0].received, 2);
+        assert_eq!(report.users[0].given, 0);
+        assert_eq!(report.users.len(), 3);
+        assert!(report.users[1..]
+            .iter()
+            .all(|u| u.given == 1 && u.received == 0));
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use rusqlite::params;
+
+use super::types::SectionMetrics;
+
+/// Label for tasks that are not in any section.
+pub const NO_SECTION: &str = "(no section)";
+
+/// Open counts, oldest open task, and period throughput for each section of
+/// a project, in board order. Empty sections are included; tasks without a
+/// section come last.
+pub(crate) fn compute_section_metrics_sql(
+    conn: &rusqlite::Connection,
+    project_gid: &str,
+    start: &str,
+    end: &str,
+) -> std::result::Result<Vec<SectionMetrics>, rusqlite::Error> {
+    let mut sections: Vec<SectionMetrics> =
+        crate::storage::repository::get_project_sections(conn, project_gid)?
+            .into_iter()
+            .map(|s| empty_section(Some(s.section_gid), s.name))
+            .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT btp.section_gid,
+            SUM(CASE WHEN t.is_completed = 0 THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.is_completed = 0 AND t.due_on IS NOT NULL AND t.due_on < date('now')
+                THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.created_date_key >= ?2 AND t.created_date_key <= ?3 THEN 1 ELSE 0 END),
+            SUM(CASE WHEN t.is_completed = 1
+                 AND t.completed_date_key >= ?2 AND t.completed_date_key <= ?3 THEN 1 ELSE 0 END)
+         FROM bridge_task_projects btp
+         JOIN fact_tasks t ON t.task_gid = btp.task_gid
+         WHERE btp.project_gid = ?1 AND t.is_deleted = 0
+         GROUP BY btp.section_gid",
+    )?;
+    let mut counts: HashMap<Option<String>, (u64, u64, u64, u64)> = HashMap::new();
+    let mut rows = stmt.query(params![project_gid, start, end])?;
+    while let Some(row) = rows.next()? {
+        let get = |i: usize| row.get::<_, i64>(i).map(|v| v as u64);
+        counts.insert(row.get(0)?, (get(1)?, get(2)?, get(3)?, get(4)?));
+    }
+
+    // SQLite returns the other columns from the row holding the MIN()
+    let mut stmt = conn.prepare(
+        "SELECT btp.section_gid, t.task_gid, t.name, MIN(t.created_at),
+            CAST(julianday('now') - julianday(MIN(t.created_at)) AS INTEGER)
+         FROM bridge_task_projects btp
+         JOIN fact_tasks t ON t.task_gid = btp.task_gid
+         WHERE btp.project_gid = ?1 AND t.is_completed = 0 AND t.is_deleted = 0
+           AND t.created_at != ''
+         GROUP BY btp.section_gid",
+    )?;
+    let mut oldest: HashMap<Option<String>, (String, String, Option<i64>)> = HashMap::new();
+    let mut rows = stmt.query([project_gid])?;
+    while let Some(row) = rows.next()? {
+        oldest.insert(row.get(0)?, (row.get(1)?, row.get(2)?, row.get(4)?));
+    }
+
+    // Tasks in sections missing from dim_sections are reported like unsectioned ones
+    if counts
+        .keys()
+        .any(|k| k.is_none() || !sections.iter().any(|s| &s.section_gid == k))
+    {
+        sections.push(empty_section(None, NO_SECTION.to_string()));
+    }
+    let known: Vec<Option<String>> = sections.iter().map(|s| s.section_gid.clone()).collect();
+    let bucket = |key: &Option<String>| {
+        if known.contains(key) {
+            key.clone()
+        } else {
+            None
+        }
+    };
+
+    for (key, (open, overdue, created, completed)) in &counts {
+        let key = bucket(key);
+        if let Some(s) = sections.iter_mut().find(|s| s.section_gid == key) {
+            s.open_count += open;
+            s.overdue_count += overdue;
+            s.tasks_created += created;
+            s.tasks_completed += completed;
+        }
+    }
+    for (key, (task_gid, name, days)) in oldest {
+        let key = bucket(&key);
+        if let Some(s) = sections.iter_mut().find(|s| s.section_gid == key) {
+            if s.oldest_open_days
+                .is_none_or(|d| days.is_some_and(|n| n > d))
+            {
+                s.oldest_open_days = days;
+                s.oldest_open_task_gid = Some(task_gid);
+                s.oldest_open_task_name = Some(name);
+            }
+        }
+    }
+    Ok(sections)
+}
+
+fn empty_section(section_gid: Option<String>, section_name: String) -> SectionMetrics {
+    SectionMetrics {
+        section_gid,
+        section_name,
+        open_count: 0,
+        overdue_count: 0,
+        oldest_open_days: None,
+        oldest_open_task_gid: None,
+        oldest_open_task_name: None,
+        tasks_created: 0,
+        tasks_completed: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::compute_section_metrics;
+    use crate::query::period::Period;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_section_metrics() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Board', 'w1', datetime('now'));
+                     INSERT INTO dim_sections (section_gid, project_gid, name, sort_order, cached_at)
+                     VALUES ('s1', 'p1', 'To Do', 0, datetime('now')),
+                            ('s2', 'p1', 'Doing', 1, datetime('now')),
+                            ('s3', 'p1', 'Done', 2, datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_date_key,
+                         created_at, created_date_key, due_on, is_subtask, cached_at)
+                     VALUES ('t1', 'old', 0, NULL, '2024-01-01T00:00:00Z', '2024-01-01', '2024-02-01', 0, datetime('now')),
+                            ('t2', 'new', 0, NULL, '2025-01-05T00:00:00Z', '2025-01-05', NULL, 0, datetime('now')),
+                            ('t3', 'shipped', 1, '2025-01-20', '2025-01-06T00:00:00Z', '2025-01-06', NULL, 0, datetime('now')),
+                            ('t4', 'loose', 0, NULL, '2025-01-07T00:00:00Z', '2025-01-07', NULL, 0, datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid, section_gid)
+                     VALUES ('t1', 'p1', 's1'), ('t2', 'p1', 's1'), ('t3', 'p1', 's3'), ('t4', 'p1', NULL);",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let m = compute_section_metrics(&db, "p1", &Period::parse("2025-Q1").unwrap())
+            .await
+            .unwrap();
+        let names: Vec<&str> = m.sections.iter().map(|s| s.section_name.as_str()).collect();
+        assert_eq!(names, vec!["To Do", "Doing", "Done", NO_SECTION]);
+
+        let todo = &m.sections[0];
+        assert_eq!(todo.open_count, 2);
+        assert_eq!(todo.overdue_count, 1);
+        assert_eq!(todo.tasks_created, 1);
+        assert_eq!(todo.oldest_open_task_name.as_deref(), Some("old"));
+        assert!(todo.oldest_open_days.unwrap() > 365);
+
+        assert_eq!(m.sections[1].open_count, 0);
+        assert!(m.sections[1].oldest_open_days.is_none());
+        assert_eq!(m.sections[2].tasks_completed, 1);
+        assert_eq!(m.sections[2].open_count, 0);
+        assert_eq!(m.sections[3].open_count, 1);
+    }
+}
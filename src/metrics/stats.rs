@@ -0,0 +1,258 @@
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::storage::Database;
+
+/// The day a user completed the most tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct BusiestDay {
+    pub date: String,
+    pub completed: u64,
+}
+
+/// The completed task that stayed open the longest.
+#[derive(Debug, Clone, Serialize)]
+pub struct LongestOpenTask {
+    pub task_gid: String,
+    pub name: String,
+    pub days_open: i32,
+    pub completed_at: Option<String>,
+    pub permalink_url: Option<String>,
+}
+
+/// All-time personal stats for one user.
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonalStats {
+    pub user_gid: String,
+    pub user_name: Option<String>,
+    pub tasks_completed: u64,
+    /// Consecutive days with a completion, ending today (or yesterday, so a
+    /// streak isn't broken before the day is over).
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    /// Last day of the longest streak (most recent one on a tie).
+    pub longest_streak_end: Option<String>,
+    pub busiest_day: Option<BusiestDay>,
+    pub longest_open_closed: Option<LongestOpenTask>,
+    /// Average hours between someone else's comment on a task and this
+    /// user's next comment there.
+    pub avg_comment_response_hours: Option<f64>,
+    /// Number of replies the average is taken over.
+    pub comment_responses: u64,
+}
+
+/// Compute completion streaks, busiest day, longest-open task closed, and
+/// comment response time for tasks assigned to `user_gid`.
+pub async fn compute_personal_stats(db: &Database, user_gid: &str) -> Result<PersonalStats> {
+    let user_gid = user_gid.to_string();
+    let today = chrono::Local::now().date_naive();
+    db.reader()
+        .call(move |conn| {
+            let user_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_users WHERE user_gid = ?1",
+                    [&user_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+
+            // Completions per day, oldest first
+            let mut stmt = conn.prepare(
+                "SELECT completed_date_key, COUNT(*) FROM fact_tasks
+                 WHERE assignee_gid = ?1 AND is_completed = 1 AND is_deleted = 0
+                   AND completed_date_key IS NOT NULL
+                 GROUP BY completed_date_key
+                 ORDER BY completed_date_key",
+            )?;
+            let days: Vec<(NaiveDate, u64)> = stmt
+                .query_map([&user_gid], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+                })?
+                .filter_map(|r| r.ok())
+                .filter_map(|(d, n)| {
+                    NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+                        .ok()
+                        .map(|d| (d, n))
+                })
+                .collect();
+
+            let tasks_completed = days.iter().map(|(_, n)| n).sum();
+            let dates: Vec<NaiveDate> = days.iter().map(|(d, _)| *d).collect();
+            let streaks = completion_streaks(&dates, today);
+            // max_by_key keeps the last maximum, so the latest day wins a tie
+            let busiest_day = days
+                .iter()
+                .max_by_key(|(_, n)| *n)
+                .map(|(d, n)| BusiestDay {
+                    date: d.format("%Y-%m-%d").to_string(),
+                    completed: *n,
+                });
+
+            let longest_open_closed = conn
+                .query_row(
+                    "SELECT task_gid, name, days_to_complete, completed_at, permalink_url
+                     FROM fact_tasks
+                     WHERE assignee_gid = ?1 AND is_completed = 1 AND is_deleted = 0
+                       AND days_to_complete IS NOT NULL
+                     ORDER BY days_to_complete DESC, completed_at DESC
+                     LIMIT 1",
+                    [&user_gid],
+                    |row| {
+                        Ok(LongestOpenTask {
+                            task_gid: row.get(0)?,
+                            name: row.get(1)?,
+                            days_open: row.get(2)?,
+                            completed_at: row.get(3)?,
+                            permalink_url: row.get(4)?,
+                        })
+                    },
+                )
+                .ok();
+
+            let (avg_comment_response_hours, comment_responses) = conn.query_row(
+                "WITH ordered AS (
+                     SELECT author_gid, created_at,
+                            LAG(author_gid) OVER w AS prev_author,
+                            LAG(created_at) OVER w AS prev_at
+                     FROM fact_comments
+                     WHERE story_type = 'comment' AND is_bot = 0 AND author_gid IS NOT NULL
+                     WINDOW w AS (PARTITION BY task_gid ORDER BY created_at)
+                 )
+                 SELECT AVG((julianday(created_at) - julianday(prev_at)) * 24), COUNT(*)
+                 FROM ordered
+                 WHERE author_gid = ?1 AND prev_author IS NOT NULL AND prev_author != ?1",
+                [&user_gid],
+                |row| Ok((row.get::<_, Option<f64>>(0)?, row.get::<_, i64>(1)? as u64)),
+            )?;
+
+            Ok::<PersonalStats, rusqlite::Error>(PersonalStats {
+                user_gid,
+                user_name,
+                tasks_completed,
+                current_streak_days: streaks.current,
+                longest_streak_days: streaks.longest,
+                longest_streak_end: streaks
+                    .longest_end
+                    .map(|d| d.format("%Y-%m-%d").to_string()),
+                busiest_day,
+                longest_open_closed,
+                avg_comment_response_hours,
+                comment_responses,
+            })
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Streaks {
+    current: u32,
+    longest: u32,
+    longest_end: Option<NaiveDate>,
+}
+
+/// Streaks of consecutive days in `dates` (sorted ascending, distinct).
+fn completion_streaks(dates: &[NaiveDate], today: NaiveDate) -> Streaks {
+    let mut longest = 0;
+    let mut longest_end = None;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for &date in dates {
+        run = match prev {
+            Some(p) if date - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        if run >= longest {
+            longest = run;
+            longest_end = Some(date);
+        }
+        prev = Some(date);
+    }
+    // The final run is still going if it reached today or yesterday
+    let current = match prev {
+        Some(last) if today - last <= Duration::days(1) => run,
+        _ => 0,
+    };
+    Streaks {
+        current,
+        longest,
+        longest_end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_completion_streaks() {
+        let dates = [
+            d("2025-01-01"),
+            d("2025-01-02"),
+            d("2025-01-03"),
+            d("2025-01-07"),
+            d("2025-01-08"),
+        ];
+        let s = completion_streaks(&dates, d("2025-01-09"));
+        assert_eq!(s.current, 2);
+        assert_eq!(s.longest, 3);
+        assert_eq!(s.longest_end, Some(d("2025-01-03")));
+
+        // A gap of a full day ends the current streak
+        assert_eq!(completion_streaks(&dates, d("2025-01-10")).current, 0);
+        assert_eq!(
+            completion_streaks(&[], d("2025-01-10")),
+            Streaks {
+                current: 0,
+                longest: 0,
+                longest_end: None
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compute_personal_stats() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u2', 'Bob', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, days_to_complete, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Quick fix', 'u1', 1, '2025-01-02T10:00:00Z', '2025-01-02', 1, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t2', 'Old chore', 'u1', 1, '2025-01-02T15:00:00Z', '2025-01-02', 90, '2024-10-04', '2024-10-04', datetime('now')),
+                            ('t3', 'Follow-up', 'u1', 1, '2025-01-03T09:00:00Z', '2025-01-03', 2, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t4', 'Not mine', 'u2', 1, '2025-01-03T09:00:00Z', '2025-01-03', 300, '2024-03-09', '2024-03-09', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'u2', 'Can you look?', 'comment', '2025-01-01T08:00:00Z', '2025-01-01', datetime('now')),
+                            ('c2', 't1', 'u1', 'On it', 'comment', '2025-01-01T10:00:00Z', '2025-01-01', datetime('now')),
+                            ('c3', 't1', 'u1', 'Done', 'comment', '2025-01-01T11:00:00Z', '2025-01-01', datetime('now')),
+                            ('c4', 't3', 'u2', 'Ping', 'comment', '2025-01-02T08:00:00Z', '2025-01-02', datetime('now')),
+                            ('c5', 't3', 'u1', 'Pong', 'comment', '2025-01-02T12:00:00Z', '2025-01-02', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let stats = compute_personal_stats(&db, "u1").await.unwrap();
+        assert_eq!(stats.user_name.as_deref(), Some("Alice"));
+        assert_eq!(stats.tasks_completed, 3);
+        assert_eq!(stats.longest_streak_days, 2);
+        assert_eq!(stats.longest_streak_end.as_deref(), Some("2025-01-03"));
+        let busiest = stats.busiest_day.unwrap();
+        assert_eq!(
+            (busiest.date.as_str(), busiest.completed),
+            ("2025-01-02", 2)
+        );
+        assert_eq!(stats.longest_open_closed.unwrap().task_gid, "t2");
+        // Replies after 2h and 4h; the follow-up to its own comment doesn't count
+        assert_eq!(stats.comment_responses, 2);
+        assert!((stats.avg_comment_response_hours.unwrap() - 3.0).abs() < 1e-6);
+    }
+}
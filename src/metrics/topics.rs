@@ -0,0 +1,460 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::query::period::Period;
+use crate::storage::Database;
+
+/// Most topics a breakdown is split into unless asked for more.
+pub const MAX_DEFAULT_TOPICS: usize = 10;
+
+/// Terms too common in task titles to say anything about a topic.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "all", "also", "and", "any", "are", "been", "before", "but", "can", "could",
+    "does", "for", "from", "get", "has", "have", "how", "into", "its", "more", "need", "needs",
+    "new", "not", "now", "our", "out", "per", "should", "some", "task", "tasks", "than", "that",
+    "the", "their", "them", "then", "there", "these", "this", "todo", "use", "using", "via", "was",
+    "were", "what", "when", "which", "will", "with", "would", "you", "your",
+];
+
+/// How tasks were turned into vectors for clustering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicMethod {
+    /// TF-IDF over task names and notes.
+    Tfidf,
+    /// Embeddings from the configured `llm_embedding_model`.
+    Embeddings,
+}
+
+/// A group of completed tasks about the same thing.
+#[derive(Debug, Clone, Serialize)]
+pub struct Topic {
+    /// The top terms, e.g. `billing / invoice / export`.
+    pub label: String,
+    pub terms: Vec<String>,
+    pub tasks: u64,
+    /// Up to five task names, most typical first.
+    pub sample_tasks: Vec<String>,
+    pub task_gids: Vec<String>,
+}
+
+/// A project's completed tasks in a period, grouped into topics.
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicBreakdown {
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    pub period_key: String,
+    pub method: TopicMethod,
+    pub total_tasks: u64,
+    /// Largest first. Tasks with no usable words are grouped under "other".
+    pub topics: Vec<Topic>,
+}
+
+impl TopicBreakdown {
+    /// A few lines for an LLM prompt; empty unless there are several topics.
+    pub fn describe(&self) -> String {
+        if self.topics.len() < 2 {
+            return String::new();
+        }
+        let mut out = format!(
+            "\n\nThemes ({} completed tasks grouped by topic):",
+            self.total_tasks
+        );
+        for t in &self.topics {
+            out.push_str(&format!("\n  - {}: {} tasks", t.label, t.tasks));
+        }
+        out
+    }
+}
+
+struct TaskText {
+    task_gid: String,
+    name: String,
+    text: String,
+}
+
+/// Group the tasks completed in `project_gid` during `period` into topics:
+/// `clusters` of them, or a number picked from the task count. Tasks are
+/// compared by embeddings when `llm_embedding_model` is configured, and by
+/// TF-IDF otherwise; either way topics are labeled with their top TF-IDF
+/// terms.
+pub async fn compute_project_topics(
+    db: &Database,
+    project_gid: &str,
+    period: &Period,
+    clusters: Option<usize>,
+) -> Result<TopicBreakdown> {
+    let (start, end) = period.date_range();
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.format("%Y-%m-%d").to_string();
+    let gid = project_gid.to_string();
+    let (project_name, tasks) = db
+        .reader()
+        .call(move |conn| {
+            let name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_projects WHERE project_gid = ?1",
+                    [&gid],
+                    |row| row.get(0),
+                )
+                .ok();
+            let mut stmt = conn.prepare(
+                "SELECT t.task_gid, t.name, SUBSTR(COALESCE(t.notes, ''), 1, 500)
+                 FROM fact_tasks t
+                 JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+                 WHERE btp.project_gid = ?1 AND t.is_completed = 1 AND t.is_deleted = 0
+                   AND t.completed_date_key >= ?2 AND t.completed_date_key <= ?3
+                 ORDER BY t.completed_at, t.task_gid",
+            )?;
+            let tasks: Vec<TaskText> = stmt
+                .query_map(rusqlite::params![gid, start_str, end_str], |row| {
+                    let name: String = row.get(1)?;
+                    let notes: String = row.get(2)?;
+                    // Names say the most, so they count twice
+                    Ok(TaskText {
+                        task_gid: row.get(0)?,
+                        text: format!("{name} {name} {notes}"),
+                        name,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok::<_, rusqlite::Error>((name, tasks))
+        })
+        .await
+        .map_err(|e| crate::error::Error::Database(e.to_string()))?;
+
+    let docs: Vec<Vec<String>> = tasks.iter().map(|t| tokenize(&t.text)).collect();
+    let tfidf = TfIdf::fit(&docs);
+    let tfidf_vectors: Vec<Vec<f64>> = docs.iter().map(|d| tfidf.vector(d)).collect();
+
+    let (method, vectors) = match embed_tasks(db, &tasks).await {
+        Some(v) => (TopicMethod::Embeddings, v),
+        None => (TopicMethod::Tfidf, tfidf_vectors.clone()),
+    };
+    let k = clusters.unwrap_or_else(|| default_topic_count(tasks.len()));
+    let assignments = spherical_kmeans(&vectors, k);
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    let mut other = Vec::new();
+    for (i, cluster) in assignments.iter().enumerate() {
+        match cluster {
+            Some(c) => groups.entry(*c).or_default().push(i),
+            None => other.push(i),
+        }
+    }
+
+    let mut topics: Vec<Topic> = groups
+        .into_values()
+        .map(|members| {
+            let terms = tfidf.top_terms(&tfidf_vectors, &members, 3);
+            let label = if terms.is_empty() {
+                "other".to_string()
+            } else {
+                terms.join(" / ")
+            };
+            build_topic(label, terms, &members, &vectors, &tasks)
+        })
+        .collect();
+    if !other.is_empty() {
+        topics.push(build_topic(
+            "other".to_string(),
+            Vec::new(),
+            &other,
+            &vectors,
+            &tasks,
+        ));
+    }
+    topics.sort_by(|a, b| b.tasks.cmp(&a.tasks).then_with(|| a.label.cmp(&b.label)));
+
+    Ok(TopicBreakdown {
+        project_gid: project_gid.to_string(),
+        project_name,
+        period_key: period.to_key(),
+        method,
+        total_tasks: tasks.len() as u64,
+        topics,
+    })
+}
+
+/// Embedding vectors for the tasks, or `None` to fall back to TF-IDF.
+#[cfg(feature = "llm")]
+async fn embed_tasks(db: &Database, tasks: &[TaskText]) -> Option<Vec<Vec<f64>>> {
+    if tasks.is_empty() {
+        return None;
+    }
+    let texts: Vec<String> = tasks.iter().map(|t| t.text.clone()).collect();
+    match crate::llm::embed_texts(db, &texts).await {
+        Ok(Some(vectors)) => Some(vectors.into_iter().map(normalize).collect()),
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!("Embedding tasks failed, clustering with TF-IDF instead: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "llm"))]
+async fn embed_tasks(_db: &Database, _tasks: &[TaskText]) -> Option<Vec<Vec<f64>>> {
+    None
+}
+
+fn build_topic(
+    label: String,
+    terms: Vec<String>,
+    members: &[usize],
+    vectors: &[Vec<f64>],
+    tasks: &[TaskText],
+) -> Topic {
+    // Most typical first: closest to the group's mean
+    let centroid = normalize(mean(vectors, members));
+    let mut ranked: Vec<(usize, f64)> = members
+        .iter()
+        .map(|&i| (i, dot(&vectors[i], &centroid)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Topic {
+        label,
+        terms,
+        tasks: members.len() as u64,
+        sample_tasks: ranked
+            .iter()
+            .take(5)
+            .map(|&(i, _)| tasks[i].name.clone())
+            .collect(),
+        task_gids: members.iter().map(|&i| tasks[i].task_gid.clone()).collect(),
+    }
+}
+
+/// The square root of half the task count, at least one and at most
+/// [`MAX_DEFAULT_TOPICS`].
+fn default_topic_count(tasks: usize) -> usize {
+    ((tasks as f64 / 2.0).sqrt().round() as usize).clamp(1, MAX_DEFAULT_TOPICS)
+}
+
+/// Lowercased words of three or more letters, minus stopwords and numbers.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+struct TfIdf {
+    vocabulary: Vec<String>,
+    index: HashMap<String, usize>,
+    idf: Vec<f64>,
+}
+
+impl TfIdf {
+    /// Terms in at least two documents (or any, for tiny sets), weighted by
+    /// smoothed inverse document frequency.
+    fn fit(docs: &[Vec<String>]) -> Self {
+        let mut df: HashMap<&str, usize> = HashMap::new();
+        for doc in docs {
+            for term in doc.iter().map(String::as_str).collect::<HashSet<_>>() {
+                *df.entry(term).or_default() += 1;
+            }
+        }
+        let min_df = if docs.len() >= 10 { 2 } else { 1 };
+        let mut vocabulary: Vec<String> = df
+            .iter()
+            .filter(|(_, &n)| n >= min_df)
+            .map(|(t, _)| t.to_string())
+            .collect();
+        vocabulary.sort();
+        let n = docs.len() as f64;
+        let idf = vocabulary
+            .iter()
+            .map(|t| ((1.0 + n) / (1.0 + df[t.as_str()] as f64)).ln() + 1.0)
+            .collect();
+        let index = vocabulary
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.clone(), i))
+            .collect();
+        Self {
+            vocabulary,
+            index,
+            idf,
+        }
+    }
+
+    /// The document's L2-normalized TF-IDF vector.
+    fn vector(&self, doc: &[String]) -> Vec<f64> {
+        let mut v = vec![0.0; self.vocabulary.len()];
+        for term in doc {
+            if let Some(&i) = self.index.get(term) {
+                v[i] += self.idf[i];
+            }
+        }
+        normalize(v)
+    }
+
+    /// The `n` heaviest terms across `members`.
+    fn top_terms(&self, vectors: &[Vec<f64>], members: &[usize], n: usize) -> Vec<String> {
+        let total = mean(vectors, members);
+        let mut ranked: Vec<(usize, f64)> = total
+            .into_iter()
+            .enumerate()
+            .filter(|(_, w)| *w > 0.0)
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+            .into_iter()
+            .take(n)
+            .map(|(i, _)| self.vocabulary[i].clone())
+            .collect()
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(mut v: Vec<f64>) -> Vec<f64> {
+    let norm = dot(&v, &v).sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+fn mean(vectors: &[Vec<f64>], members: &[usize]) -> Vec<f64> {
+    let dims = members.first().map_or(0, |&i| vectors[i].len());
+    let mut sum = vec![0.0; dims];
+    for &i in members {
+        for (s, x) in sum.iter_mut().zip(&vectors[i]) {
+            *s += x;
+        }
+    }
+    let n = members.len().max(1) as f64;
+    sum.iter_mut().for_each(|s| *s /= n);
+    sum
+}
+
+/// Cluster unit vectors into at most `k` groups by cosine similarity.
+/// Seeds are picked farthest-first from the first vector, so results are
+/// deterministic. Zero vectors get `None`.
+fn spherical_kmeans(vectors: &[Vec<f64>], k: usize) -> Vec<Option<usize>> {
+    let usable: Vec<usize> = (0..vectors.len())
+        .filter(|&i| vectors[i].iter().any(|x| *x != 0.0))
+        .collect();
+    let mut assignments = vec![None; vectors.len()];
+    let Some(&first) = usable.first() else {
+        return assignments;
+    };
+
+    let mut centroids = vec![vectors[first].clone()];
+    while centroids.len() < k.min(usable.len()) {
+        let farthest = usable
+            .iter()
+            .map(|&i| {
+                let closest = centroids
+                    .iter()
+                    .map(|c| dot(&vectors[i], c))
+                    .fold(f64::MIN, f64::max);
+                (i, closest)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        match farthest {
+            // Every remaining vector duplicates a seed
+            Some((_, sim)) if sim >= 1.0 - 1e-9 => break,
+            Some((i, _)) => centroids.push(vectors[i].clone()),
+            None => break,
+        }
+    }
+
+    for _ in 0..50 {
+        let mut changed = false;
+        for &i in &usable {
+            let best = centroids
+                .iter()
+                .enumerate()
+                .map(|(c, centroid)| (c, dot(&vectors[i], centroid)))
+                .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(c, _)| c);
+            if assignments[i] != best {
+                assignments[i] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<usize> = usable
+                .iter()
+                .copied()
+                .filter(|&i| assignments[i] == Some(c))
+                .collect();
+            if !members.is_empty() {
+                *centroid = normalize(mean(vectors, &members));
+            }
+        }
+    }
+    assignments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Fix the Billing-export for Q3 2025 (v2)"),
+            vec!["fix", "billing", "export"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compute_project_topics() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Platform', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Invoice export to CSV', 1, '2025-01-10T00:00:00Z', '2025-01-10', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t2', 'Fix invoice export totals', 1, '2025-01-11T00:00:00Z', '2025-01-11', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t3', 'Invoice export timezone bug', 1, '2025-01-12T00:00:00Z', '2025-01-12', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t4', 'Onboarding email copy', 1, '2025-02-01T00:00:00Z', '2025-02-01', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t5', 'Onboarding email images', 1, '2025-02-02T00:00:00Z', '2025-02-02', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t6', '2025', 1, '2025-02-03T00:00:00Z', '2025-02-03', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t7', 'Invoice export, next year', 1, '2026-01-10T00:00:00Z', '2026-01-10', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p1'), ('t3', 'p1'), ('t4', 'p1'), ('t5', 'p1'), ('t6', 'p1'), ('t7', 'p1');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let period = Period::parse("2025-Q1").unwrap();
+        let b = compute_project_topics(&db, "p1", &period, Some(2))
+            .await
+            .unwrap();
+        assert_eq!(b.method, TopicMethod::Tfidf);
+        assert_eq!(b.total_tasks, 6);
+        let summary: Vec<(&str, u64)> = b
+            .topics
+            .iter()
+            .map(|t| (t.label.as_str(), t.tasks))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("export / invoice / csv", 3),
+                ("email / onboarding / copy", 2),
+                ("other", 1)
+            ]
+        );
+        assert_eq!(b.topics[0].task_gids, ["t1", "t2", "t3"]);
+        assert!(b.describe().contains("email / onboarding / copy: 2 tasks"));
+    }
+}
@@ -6,6 +6,8 @@ pub struct ThroughputMetrics {
     pub tasks_created: u64,
     pub tasks_completed: u64,
     pub net_new: i64,
+    /// Milestone tasks among `tasks_completed`.
+    pub milestones_completed: u64,
 }
 
 /// Health metrics: overdue tasks, unassigned tasks, stale tasks.
@@ -30,6 +32,61 @@ pub struct LeadTimeMetrics {
     pub p90_days_to_complete: Option<f64>,
     pub min_days_to_complete: Option<i32>,
     pub max_days_to_complete: Option<i32>,
+    /// Completed tasks per lead-time bucket, shortest first. Empty when no
+    /// tasks were completed.
+    pub histogram: Vec<LeadTimeBucket>,
+}
+
+/// Number of completed tasks whose lead time falls within a range of days.
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadTimeBucket {
+    /// Display label, e.g. `2-3d` or `15+d`.
+    pub label: String,
+    pub min_days: i32,
+    /// Inclusive upper bound; `None` for the open-ended last bucket.
+    pub max_days: Option<i32>,
+    pub count: u64,
+}
+
+/// Cycle time metrics: how long tasks take from entering an in-progress
+/// section to completion, derived from section transitions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CycleTimeMetrics {
+    /// Completed tasks that passed through an in-progress section.
+    pub tasks_measured: u64,
+    pub avg_days: Option<f64>,
+    pub median_days: Option<f64>,
+    pub p90_days: Option<f64>,
+    /// Time spent in each section by completed tasks, longest average first.
+    pub section_dwell: Vec<SectionDwell>,
+}
+
+/// Dwell time for one section across completed tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionDwell {
+    pub section_name: String,
+    /// Number of times a task entered this section.
+    pub visits: u64,
+    pub avg_days: f64,
+    pub median_days: f64,
+}
+
+/// Lifecycle-phase metrics for a project, from its sections' phases (see
+/// `metrics::phases`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhaseMetrics {
+    /// Open tasks per phase (work in progress).
+    pub backlog_open: u64,
+    pub in_progress_open: u64,
+    pub review_open: u64,
+    pub done_open: u64,
+    /// Open tasks in no section or in a section no phase claims.
+    pub unmapped_open: u64,
+    /// Names of sections holding open tasks that map to no phase.
+    pub unmapped_sections: Vec<String>,
+    /// Share of completed tasks' time in In Progress or Review that was
+    /// spent In Progress, as a percentage. `None` without section history.
+    pub flow_efficiency_pct: Option<f64>,
 }
 
 /// Collaboration metrics: comments, likes, followers.
@@ -38,6 +95,12 @@ pub struct CollaborationMetrics {
     pub total_comments: u64,
     pub unique_commenters: u64,
     pub total_likes: u64,
+    /// Tasks completed in the period without a single comment, a rough
+    /// sign of work that went undocumented.
+    pub silent_completions: u64,
+    /// Silent completions as a percentage of tasks completed in the
+    /// period; `None` when none were.
+    pub silent_completion_pct: Option<f64>,
 }
 
 /// Aggregated metrics for a user over a period.
@@ -60,6 +123,8 @@ pub struct ProjectMetrics {
     pub throughput: ThroughputMetrics,
     pub health: HealthMetrics,
     pub lead_time: LeadTimeMetrics,
+    pub cycle_time: CycleTimeMetrics,
+    pub phases: PhaseMetrics,
     pub collaboration: CollaborationMetrics,
 }
 
@@ -73,9 +138,101 @@ pub struct PortfolioMetrics {
     pub health: HealthMetrics,
     pub lead_time: LeadTimeMetrics,
     pub collaboration: CollaborationMetrics,
+    /// Projects in the portfolio and its nested portfolios.
+    pub project_count: u64,
+    /// Portfolios nested under this one whose projects are included.
+    pub nested_portfolio_count: u64,
+}
+
+/// Aggregated metrics for the projects matching a configured project label.
+#[derive(Debug, Clone, Serialize)]
+pub struct LabelMetrics {
+    pub label: String,
+    pub period_key: String,
+    pub throughput: ThroughputMetrics,
+    pub health: HealthMetrics,
+    pub lead_time: LeadTimeMetrics,
+    pub collaboration: CollaborationMetrics,
     pub project_count: u64,
 }
 
+/// Work-in-progress and throughput for one section of a project. Tasks are
+/// counted in the section they currently sit in.
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionMetrics {
+    /// `None` for tasks not in any section.
+    pub section_gid: Option<String>,
+    pub section_name: String,
+    pub open_count: u64,
+    pub overdue_count: u64,
+    /// Days since the oldest open task in the section was created.
+    pub oldest_open_days: Option<i64>,
+    pub oldest_open_task_gid: Option<String>,
+    pub oldest_open_task_name: Option<String>,
+    pub tasks_created: u64,
+    pub tasks_completed: u64,
+}
+
+/// Per-section metrics for a project, in board order.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectSectionMetrics {
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    pub period_key: String,
+    pub sections: Vec<SectionMetrics>,
+}
+
+/// Project metrics for the tasks holding one value of an enum custom field.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldValueMetrics {
+    /// `None` for tasks with no value set.
+    pub option_gid: Option<String>,
+    pub value: String,
+    pub throughput: ThroughputMetrics,
+    pub health: HealthMetrics,
+    pub lead_time: LeadTimeMetrics,
+}
+
+/// Project metrics bucketed by an enum custom field (e.g. Priority).
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldMetrics {
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    pub field_gid: String,
+    pub field_name: String,
+    pub period_key: String,
+    /// Busiest values first; tasks without a value come last.
+    pub groups: Vec<FieldValueMetrics>,
+}
+
+/// Time a project's tasks spent holding one value of a tracked custom field.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldValueDwell {
+    pub value: String,
+    /// Tasks that held the value at some point in the period.
+    pub tasks: u64,
+    /// Days held within the period, summed across tasks.
+    pub total_days: f64,
+    /// Days per task.
+    pub avg_days: f64,
+    /// Longest time one task held the value within the period.
+    pub max_days: f64,
+    /// Open tasks whose latest recorded change set this value.
+    pub open_now: u64,
+}
+
+/// How long a project's tasks held each value of a tracked custom field
+/// (e.g. days in `Status: Blocked`), from `fact_custom_field_changes`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldHistoryMetrics {
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    pub field_name: String,
+    pub period_key: String,
+    /// Most total days first.
+    pub values: Vec<FieldValueDwell>,
+}
+
 /// Aggregated metrics for a team over a period.
 #[derive(Debug, Clone, Serialize)]
 pub struct TeamMetrics {
@@ -88,3 +245,18 @@ pub struct TeamMetrics {
     pub collaboration: CollaborationMetrics,
     pub member_count: u64,
 }
+
+/// Aggregated metrics for a manager's reporting line over a period.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrgMetrics {
+    pub manager_gid: String,
+    pub manager_name: Option<String>,
+    pub period_key: String,
+    pub throughput: ThroughputMetrics,
+    pub health: HealthMetrics,
+    pub lead_time: LeadTimeMetrics,
+    pub collaboration: CollaborationMetrics,
+    /// The manager plus everyone reporting to them, directly or not.
+    pub member_count: u64,
+    pub direct_reports: u64,
+}
@@ -0,0 +1,210 @@
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::Database;
+
+/// Config key naming a number custom field (GID or name) that holds task
+/// estimates. Without it, every open task counts as one unit of load.
+pub const WORKLOAD_ESTIMATE_FIELD_KEY: &str = "workload_estimate_field";
+
+/// Open work assigned to one team member, across all of their projects.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberWorkload {
+    pub user_gid: String,
+    pub user_name: Option<String>,
+    pub open_tasks: u64,
+    pub overdue_tasks: u64,
+    pub due_next_7_days: u64,
+    /// Sum of open task estimates; tasks without an estimate count as 1.
+    pub estimated_load: f64,
+    /// `estimated_load` relative to the team average (1.0 = average).
+    pub load_vs_average: Option<f64>,
+}
+
+/// Per-member workload for a team, heaviest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamWorkload {
+    pub team_gid: String,
+    pub team_name: Option<String>,
+    /// Name of the estimate field used, if `workload_estimate_field` is set.
+    pub estimate_field: Option<String>,
+    pub average_load: f64,
+    pub members: Vec<MemberWorkload>,
+}
+
+/// Compute the current workload of each member of a team.
+pub async fn compute_team_workload(db: &Database, team_gid: &str) -> Result<TeamWorkload> {
+    let configured: Option<String> = db
+        .reader()
+        .call(|conn| repository::get_config(conn, WORKLOAD_ESTIMATE_FIELD_KEY))
+        .await?;
+    let estimate_field = match configured {
+        Some(field) => {
+            let lookup = field.clone();
+            let found = db
+                .reader()
+                .call(move |conn| resolve_number_field(conn, &lookup))
+                .await
+                .map_err(|e| Error::Database(e.to_string()))?;
+            Some(found.ok_or_else(|| {
+                Error::Config(format!(
+                    "{WORKLOAD_ESTIMATE_FIELD_KEY} '{field}' is not a synced number custom field"
+                ))
+            })?)
+        }
+        None => None,
+    };
+
+    let team_gid = team_gid.to_string();
+    db.reader()
+        .call(move |conn| {
+            let team_name: Option<String> = conn
+                .query_row(
+                    "SELECT name FROM dim_teams WHERE team_gid = ?1",
+                    [&team_gid],
+                    |row| row.get(0),
+                )
+                .ok();
+
+            let field_gid = estimate_field.as_ref().map(|(gid, _)| gid.clone());
+            let mut stmt = conn.prepare(
+                "SELECT m.user_gid, u.name,
+                    COUNT(t.task_gid),
+                    COALESCE(SUM(CASE WHEN t.due_on < date('now') THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN t.due_on >= date('now')
+                                       AND t.due_on <= date('now', '+7 days') THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN t.task_gid IS NOT NULL
+                                      THEN COALESCE(est.number_value, 1.0) END), 0.0)
+                 FROM bridge_team_members m
+                 LEFT JOIN dim_users u ON u.user_gid = m.user_gid
+                 LEFT JOIN fact_tasks t
+                   ON t.assignee_gid = m.user_gid AND t.is_completed = 0 AND t.is_deleted = 0
+                 LEFT JOIN fact_task_custom_fields est
+                   ON est.task_gid = t.task_gid AND est.field_gid = ?2
+                 WHERE m.team_gid = ?1
+                 GROUP BY m.user_gid",
+            )?;
+            let mut members: Vec<MemberWorkload> = stmt
+                .query_map(rusqlite::params![team_gid, field_gid], |row| {
+                    Ok(MemberWorkload {
+                        user_gid: row.get(0)?,
+                        user_name: row.get(1)?,
+                        open_tasks: row.get::<_, i64>(2)? as u64,
+                        overdue_tasks: row.get::<_, i64>(3)? as u64,
+                        due_next_7_days: row.get::<_, i64>(4)? as u64,
+                        estimated_load: row.get(5)?,
+                        load_vs_average: None,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let average_load = if members.is_empty() {
+                0.0
+            } else {
+                members.iter().map(|m| m.estimated_load).sum::<f64>() / members.len() as f64
+            };
+            for m in &mut members {
+                if average_load > 0.0 {
+                    m.load_vs_average = Some(m.estimated_load / average_load);
+                }
+            }
+            members.sort_by(|a, b| {
+                b.estimated_load
+                    .total_cmp(&a.estimated_load)
+                    .then(b.overdue_tasks.cmp(&a.overdue_tasks))
+            });
+
+            Ok::<TeamWorkload, rusqlite::Error>(TeamWorkload {
+                team_gid,
+                team_name,
+                estimate_field: estimate_field.map(|(_, name)| name),
+                average_load,
+                members,
+            })
+        })
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Find a number custom field by GID or name; returns `(field_gid, name)`.
+fn resolve_number_field(
+    conn: &rusqlite::Connection,
+    field: &str,
+) -> std::result::Result<Option<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT field_gid, name FROM dim_custom_fields
+         WHERE field_type = 'number' AND (field_gid = ?1 OR LOWER(name) = LOWER(?1))
+         ORDER BY field_gid = ?1 DESC
+         LIMIT 1",
+    )?;
+    let mut rows = stmt.query([field])?;
+    match rows.next()? {
+        Some(row) => Ok(Some((row.get(0)?, row.get(1)?))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_team_workload() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_teams (team_gid, name, workspace_gid, cached_at)
+                     VALUES ('team1', 'Platform', 'w1', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('u1', 'Alice', datetime('now')), ('u2', 'Bob', datetime('now')),
+                            ('u3', 'Idle', datetime('now'));
+                     INSERT INTO bridge_team_members (team_gid, user_gid)
+                     VALUES ('team1', 'u1'), ('team1', 'u2'), ('team1', 'u3');
+                     INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('est', 'Estimate', 'number', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, due_on,
+                         created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'a', 'u1', 0, date('now', '-2 days'), '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t2', 'b', 'u1', 0, date('now', '+3 days'), '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t3', 'c', 'u1', 1, NULL, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t4', 'd', 'u2', 0, date('now', '+30 days'), '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_task_custom_fields (task_gid, field_gid, number_value, display_value)
+                     VALUES ('t4', 'est', 8, '8');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let w = compute_team_workload(&db, "team1").await.unwrap();
+        assert_eq!(w.team_name.as_deref(), Some("Platform"));
+        assert!(w.estimate_field.is_none());
+        let alice = &w.members[0];
+        assert_eq!(alice.user_gid, "u1");
+        assert_eq!(alice.open_tasks, 2);
+        assert_eq!(alice.overdue_tasks, 1);
+        assert_eq!(alice.due_next_7_days, 1);
+        assert_eq!(alice.estimated_load, 2.0);
+        assert_eq!(w.members[2].open_tasks, 0);
+        assert_eq!(w.average_load, 1.0);
+
+        db.writer()
+            .call(|conn| repository::set_config(conn, WORKLOAD_ESTIMATE_FIELD_KEY, "estimate"))
+            .await
+            .unwrap();
+        let w = compute_team_workload(&db, "team1").await.unwrap();
+        assert_eq!(w.estimate_field.as_deref(), Some("Estimate"));
+        assert_eq!(w.members[0].user_gid, "u2");
+        assert_eq!(w.members[0].estimated_load, 8.0);
+        assert_eq!(w.members[0].load_vs_average, Some(2.4));
+
+        db.writer()
+            .call(|conn| repository::set_config(conn, WORKLOAD_ESTIMATE_FIELD_KEY, "Points"))
+            .await
+            .unwrap();
+        assert!(compute_team_workload(&db, "team1").await.is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! One-call view of a user, project, portfolio, or team for rendering an
+//! entity page: stored metadata, sync state, headline metrics, and the
+//! latest cached period summary.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::metrics::{self, PortfolioMetrics, ProjectMetrics, TeamMetrics, UserMetrics};
+use crate::storage::Database;
+
+/// Period the overview's metrics cover unless `default_period` is set.
+pub const DEFAULT_OVERVIEW_PERIOD: &str = "qtd";
+
+/// Everything an entity page shows, from local data only.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityOverview {
+    pub entity_key: String,
+    pub entity_type: String,
+    pub entity_gid: String,
+    pub metadata: EntityMetadata,
+    pub sync: SyncInfo,
+    pub metrics: HeadlineMetrics,
+    /// The latest summary for the metrics' period, or else the latest for
+    /// any period.
+    pub summary: Option<CachedSummary>,
+}
+
+/// Stored dimension fields. Fields an entity type doesn't have are `None`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EntityMetadata {
+    pub name: Option<String>,
+    /// Users only.
+    pub email: Option<String>,
+    /// Projects and portfolios.
+    pub owner_name: Option<String>,
+    /// Projects only.
+    pub team_name: Option<String>,
+    /// Teams only.
+    pub description: Option<String>,
+    /// Projects only.
+    pub is_archived: Option<bool>,
+    pub permalink_url: Option<String>,
+}
+
+/// Whether the entity is monitored, and how its last sync went.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncInfo {
+    pub monitored: bool,
+    pub sync_enabled: bool,
+    pub last_sync_at: Option<String>,
+    pub last_job: Option<SyncJobInfo>,
+}
+
+/// The entity's most recent sync job.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncJobInfo {
+    pub status: String,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+    pub synced_items: u64,
+    pub error: Option<String>,
+}
+
+/// The entity type's metrics for the configured `default_period`, or
+/// [`DEFAULT_OVERVIEW_PERIOD`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HeadlineMetrics {
+    User(UserMetrics),
+    Project(ProjectMetrics),
+    Portfolio(PortfolioMetrics),
+    Team(TeamMetrics),
+}
+
+/// The newest generation of a stored period summary.
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedSummary {
+    pub period_key: String,
+    pub generation: u32,
+    pub headline: String,
+    pub what_changed: String,
+    pub why_it_matters: String,
+    pub prompt_version: String,
+    pub generated_at: String,
+}
+
+/// Overview of the entity `entity_key` (`user:GID`, `project:GID`,
+/// `portfolio:GID`, or `team:GID`). Fails with [`Error::NotFound`] when the
+/// entity is neither stored nor monitored.
+pub async fn entity_overview(db: &Database, entity_key: &str) -> Result<EntityOverview> {
+    let (entity_type, entity_gid) = match entity_key.split_once(':') {
+        Some((t @ ("user" | "project" | "portfolio" | "team"), gid)) if !gid.is_empty() => {
+            (t.to_string(), gid.to_string())
+        }
+        _ => {
+            return Err(Error::InvalidIdentifier(format!(
+                "{entity_key} (expected user:, project:, portfolio:, or team: followed by a GID)"
+            )))
+        }
+    };
+    let period = crate::config::default_period(db, DEFAULT_OVERVIEW_PERIOD).await?;
+    let period_key = period.to_key();
+
+    let (metadata, sync, summary) = db
+        .reader()
+        .call({
+            let entity_key = entity_key.to_string();
+            let entity_type = entity_type.clone();
+            let entity_gid = entity_gid.clone();
+            move |conn| {
+                let metadata = load_metadata(conn, &entity_type, &entity_gid)?;
+                let sync = load_sync_info(conn, &entity_key)?;
+                let summary = load_summary(conn, &entity_type, &entity_gid, &period_key)?;
+                Ok::<_, rusqlite::Error>((metadata, sync, summary))
+            }
+        })
+        .await?;
+    // A monitored entity that hasn't synced yet has no dimension row
+    let metadata = match metadata {
+        Some(metadata) => metadata,
+        None if sync.monitored => EntityMetadata::default(),
+        None => return Err(Error::NotFound(entity_key.to_string())),
+    };
+
+    let metrics = match entity_type.as_str() {
+        "user" => {
+            HeadlineMetrics::User(metrics::compute_user_metrics(db, &entity_gid, &period).await?)
+        }
+        "project" => HeadlineMetrics::Project(
+            metrics::compute_project_metrics(db, &entity_gid, &period).await?,
+        ),
+        "portfolio" => HeadlineMetrics::Portfolio(
+            metrics::compute_portfolio_metrics(db, &entity_gid, &period).await?,
+        ),
+        _ => HeadlineMetrics::Team(metrics::compute_team_metrics(db, &entity_gid, &period).await?),
+    };
+
+    Ok(EntityOverview {
+        entity_key: entity_key.to_string(),
+        entity_type,
+        entity_gid,
+        metadata,
+        sync,
+        metrics,
+        summary,
+    })
+}
+
+pub(crate) fn load_metadata(
+    conn: &Connection,
+    entity_type: &str,
+    gid: &str,
+) -> std::result::Result<Option<EntityMetadata>, rusqlite::Error> {
+    let sql = match entity_type {
+        "user" => {
+            "SELECT name, email, NULL, NULL, NULL, NULL, NULL
+             FROM dim_users WHERE user_gid = ?1"
+        }
+        "project" => {
+            "SELECT p.name, NULL, o.name, t.name, NULL, p.is_archived, p.permalink_url
+             FROM dim_projects p
+             LEFT JOIN dim_users o ON o.user_gid = p.owner_gid
+             LEFT JOIN dim_teams t ON t.team_gid = p.team_gid
+             WHERE p.project_gid = ?1"
+        }
+        "portfolio" => {
+            "SELECT p.name, NULL, o.name, NULL, NULL, NULL, p.permalink_url
+             FROM dim_portfolios p
+             LEFT JOIN dim_users o ON o.user_gid = p.owner_gid
+             WHERE p.portfolio_gid = ?1"
+        }
+        _ => {
+            "SELECT name, NULL, NULL, NULL, description, NULL, NULL
+             FROM dim_teams WHERE team_gid = ?1"
+        }
+    };
+    conn.query_row(sql, params![gid], |row| {
+        Ok(EntityMetadata {
+            name: row.get(0)?,
+            email: row.get(1)?,
+            owner_name: row.get(2)?,
+            team_name: row.get(3)?,
+            description: row.get(4)?,
+            is_archived: row.get(5)?,
+            permalink_url: row.get(6)?,
+        })
+    })
+    .optional()
+}
+
+fn load_sync_info(
+    conn: &Connection,
+    entity_key: &str,
+) -> std::result::Result<SyncInfo, rusqlite::Error> {
+    let monitored: Option<(bool, Option<String>)> = conn
+        .query_row(
+            "SELECT sync_enabled, last_sync_at FROM monitored_entities WHERE entity_key = ?1",
+            params![entity_key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let last_job = conn
+        .query_row(
+            "SELECT status, started_at, completed_at, COALESCE(synced_items, 0), error_message
+             FROM sync_jobs WHERE entity_key = ?1
+             ORDER BY started_at DESC, id DESC LIMIT 1",
+            params![entity_key],
+            |row| {
+                Ok(SyncJobInfo {
+                    status: row.get(0)?,
+                    started_at: row.get(1)?,
+                    completed_at: row.get(2)?,
+                    synced_items: row.get::<_, i64>(3)?.max(0) as u64,
+                    error: row.get(4)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(SyncInfo {
+        monitored: monitored.is_some(),
+        sync_enabled: monitored.as_ref().is_some_and(|(enabled, _)| *enabled),
+        last_sync_at: monitored.and_then(|(_, at)| at),
+        last_job,
+    })
+}
+
+pub(crate) fn load_summary(
+    conn: &Connection,
+    entity_type: &str,
+    gid: &str,
+    period_key: &str,
+) -> std::result::Result<Option<CachedSummary>, rusqlite::Error> {
+    let sql = format!(
+        "SELECT period_key, generation, headline, what_changed, why_it_matters,
+                prompt_version, generated_at
+         FROM fact_{entity_type}_period_summaries
+         WHERE {entity_type}_gid = ?1
+         ORDER BY period_key = ?2 DESC, generated_at DESC, generation DESC
+         LIMIT 1"
+    );
+    conn.query_row(&sql, params![gid, period_key], |row| {
+        Ok(CachedSummary {
+            period_key: row.get(0)?,
+            generation: row.get(1)?,
+            headline: row.get(2)?,
+            what_changed: row.get(3)?,
+            why_it_matters: row.get(4)?,
+            prompt_version: row.get(5)?,
+            generated_at: row.get(6)?,
+        })
+    })
+    .optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::period::Period;
+
+    #[tokio::test]
+    async fn test_entity_overview() {
+        let db = Database::open_memory().await.unwrap();
+        let period_key = Period::parse(DEFAULT_OVERVIEW_PERIOD).unwrap().to_key();
+        db.writer()
+            .call(move |conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Ada', datetime('now'));
+                     INSERT INTO dim_teams (team_gid, name, workspace_gid, cached_at) VALUES ('tm1', 'Platform', 'w1', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, owner_gid, team_gid, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'u1', 'tm1', 'w1', datetime('now'));
+                     INSERT INTO monitored_entities (entity_key, entity_type, entity_gid, added_at, last_sync_at)
+                     VALUES ('project:p1', 'project', 'p1', datetime('now'), '2025-01-02T00:00:00Z');
+                     INSERT INTO sync_jobs (entity_key, status, started_at, synced_items)
+                     VALUES ('project:p1', 'completed', '2025-01-02T00:00:00Z', 7);",
+                )?;
+                conn.execute(
+                    "INSERT INTO fact_project_period_summaries (project_gid, period_key, generation, headline, what_changed, why_it_matters, key_milestones, prompt_version, generated_at)
+                     VALUES ('p1', '2020-Q1', 1, 'Old news', '', '', '[]', 'v1', '2020-04-01'),
+                            ('p1', ?1, 1, 'First take', '', '', '[]', 'v1', '2020-01-01'),
+                            ('p1', ?1, 2, 'Second take', '', '', '[]', 'v1', '2020-01-01')",
+                    params![period_key],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let overview = entity_overview(&db, "project:p1").await.unwrap();
+        assert_eq!(overview.metadata.name.as_deref(), Some("Roadmap"));
+        assert_eq!(overview.metadata.owner_name.as_deref(), Some("Ada"));
+        assert_eq!(overview.metadata.team_name.as_deref(), Some("Platform"));
+        assert!(overview.sync.monitored);
+        assert_eq!(overview.sync.last_job.as_ref().unwrap().synced_items, 7);
+        assert!(matches!(overview.metrics, HeadlineMetrics::Project(_)));
+        assert_eq!(overview.summary.unwrap().headline, "Second take");
+
+        let user = entity_overview(&db, "user:u1").await.unwrap();
+        assert!(!user.sync.monitored);
+        assert!(user.summary.is_none());
+
+        assert!(matches!(
+            entity_overview(&db, "project:nope").await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            entity_overview(&db, "p1").await,
+            Err(Error::InvalidIdentifier(_))
+        ));
+    }
+}
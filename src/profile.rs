@@ -0,0 +1,159 @@
+//! Named profiles, each bundling an Asana token, workspace, and database
+//! path, so separate accounts sync into separate warehouses. Stored as
+//! JSON in `~/.asanadw/profiles.json`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// One account's settings. Anything unset falls back to the usual default
+/// (`ASANA_TOKEN`, the configured workspace, `~/.asanadw/asanadw.db`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Workspace GID or name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db: Option<PathBuf>,
+}
+
+/// Every profile, and which one commands use without `--profile`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profiles {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// `~/.asanadw/profiles.json`.
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::home_dir()
+            .ok_or_else(|| Error::Config("cannot determine home directory".into()))?;
+        Ok(dir.join(".asanadw").join("profiles.json"))
+    }
+
+    /// Read the profiles file; a missing file means no profiles.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| Error::Config(format!("{}: {e}", path.display()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Config(format!("{}: {e}", path.display()))),
+        }
+    }
+
+    /// Write the profiles file, readable only by the owner since it holds
+    /// tokens.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let io_err = |e: std::io::Error| Error::Config(format!("{}: {e}", path.display()));
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| Error::Other(e.to_string()))?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path).map_err(io_err)?;
+        // `mode` only applies to a new file; narrow an existing one before
+        // writing tokens to it
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))
+            .map_err(io_err)?;
+        std::io::Write::write_all(&mut file, json.as_bytes()).map_err(io_err)
+    }
+
+    /// Add or replace a profile. The first profile added becomes current.
+    pub fn add(&mut self, name: &str, profile: Profile) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(Error::Config("profile name is empty".into()));
+        }
+        self.profiles.insert(name.to_string(), profile);
+        if self.current.is_none() {
+            self.current = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Make `name` the profile used without `--profile`.
+    pub fn switch(&mut self, name: &str) -> Result<()> {
+        if !self.profiles.contains_key(name) {
+            return Err(self.not_found(name));
+        }
+        self.current = Some(name.to_string());
+        Ok(())
+    }
+
+    /// The profile a command runs under: `requested` (from `--profile`),
+    /// else the current one, else none.
+    pub fn active(&self, requested: Option<&str>) -> Result<Option<(&str, &Profile)>> {
+        let Some(name) = requested.or(self.current.as_deref()) else {
+            return Ok(None);
+        };
+        self.profiles
+            .get_key_value(name)
+            .map(|(name, profile)| Some((name.as_str(), profile)))
+            .ok_or_else(|| self.not_found(name))
+    }
+
+    fn not_found(&self, name: &str) -> Error {
+        let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+        Error::NotFound(if known.is_empty() {
+            format!("profile '{name}' (no profiles defined; add one with `asanadw profile add`)")
+        } else {
+            format!("profile '{name}' (known: {})", known.join(", "))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.json");
+        let mut profiles = Profiles::load(&path).unwrap();
+        assert_eq!(profiles.active(None).unwrap(), None);
+
+        let work = Profile {
+            token: Some("work-token".into()),
+            workspace: Some("Acme".into()),
+            db: Some(dir.path().join("work.db")),
+        };
+        profiles.add("work", work.clone()).unwrap();
+        profiles.add("personal", Profile::default()).unwrap();
+        profiles.save(&path).unwrap();
+
+        let mut profiles = Profiles::load(&path).unwrap();
+        assert_eq!(profiles.active(None).unwrap(), Some(("work", &work)));
+        profiles.switch("personal").unwrap();
+        assert_eq!(profiles.active(None).unwrap().unwrap().0, "personal");
+        assert_eq!(profiles.active(Some("work")).unwrap().unwrap().0, "work");
+        assert!(profiles.switch("nope").is_err());
+        assert!(profiles.active(Some("nope")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_narrows_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.json");
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        Profiles::load(&path).unwrap().save(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
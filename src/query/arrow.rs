@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use ::arrow::array::{ArrayRef, BooleanArray, Int32Array, StringArray};
+use ::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use ::arrow::record_batch::RecordBatch;
+
+use super::builder::{QueryBuilder, TaskRow};
+use crate::error::{Error, Result};
+use crate::storage::Database;
+
+/// Arrow schema for [`TaskRow`], one column per field in declaration order.
+pub fn task_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("task_gid", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("assignee_gid", DataType::Utf8, true),
+        Field::new("assignee_name", DataType::Utf8, true),
+        Field::new("is_completed", DataType::Boolean, false),
+        Field::new("completed_at", DataType::Utf8, true),
+        Field::new("due_on", DataType::Utf8, true),
+        Field::new("created_at", DataType::Utf8, false),
+        Field::new("modified_at", DataType::Utf8, true),
+        Field::new("project_name", DataType::Utf8, true),
+        Field::new("section_name", DataType::Utf8, true),
+        Field::new("is_overdue", DataType::Boolean, false),
+        Field::new("days_to_complete", DataType::Int32, true),
+        Field::new("num_subtasks", DataType::Int32, false),
+        Field::new("num_likes", DataType::Int32, false),
+        Field::new("permalink_url", DataType::Utf8, true),
+    ]))
+}
+
+/// Convert task rows into a single record batch with [`task_schema`].
+pub fn tasks_to_record_batch(rows: &[TaskRow]) -> Result<RecordBatch> {
+    fn text<'a>(rows: &'a [TaskRow], f: impl Fn(&'a TaskRow) -> Option<&'a str>) -> ArrayRef {
+        Arc::new(rows.iter().map(f).collect::<StringArray>())
+    }
+    fn flag(rows: &[TaskRow], f: impl Fn(&TaskRow) -> bool) -> ArrayRef {
+        Arc::new(rows.iter().map(|r| Some(f(r))).collect::<BooleanArray>())
+    }
+    fn int(rows: &[TaskRow], f: impl Fn(&TaskRow) -> Option<i32>) -> ArrayRef {
+        Arc::new(rows.iter().map(f).collect::<Int32Array>())
+    }
+
+    let columns = vec![
+        text(rows, |r| Some(r.task_gid.as_str())),
+        text(rows, |r| Some(r.name.as_str())),
+        text(rows, |r| r.assignee_gid.as_deref()),
+        text(rows, |r| r.assignee_name.as_deref()),
+        flag(rows, |r| r.is_completed),
+        text(rows, |r| r.completed_at.as_deref()),
+        text(rows, |r| r.due_on.as_deref()),
+        text(rows, |r| Some(r.created_at.as_str())),
+        text(rows, |r| r.modified_at.as_deref()),
+        text(rows, |r| r.project_name.as_deref()),
+        text(rows, |r| r.section_name.as_deref()),
+        flag(rows, |r| r.is_overdue),
+        int(rows, |r| r.days_to_complete),
+        int(rows, |r| Some(r.num_subtasks)),
+        int(rows, |r| Some(r.num_likes)),
+        text(rows, |r| r.permalink_url.as_deref()),
+    ];
+    RecordBatch::try_new(task_schema(), columns).map_err(|e| Error::Other(e.to_string()))
+}
+
+impl QueryBuilder {
+    /// Execute the query and return the matching tasks as an Arrow record batch,
+    /// for handing off to Polars, DataFusion, or other Arrow consumers.
+    pub async fn to_arrow(self, db: &Database) -> Result<RecordBatch> {
+        let rows = self.tasks(db).await?;
+        tasks_to_record_batch(&rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::arrow::array::Array;
+
+    #[tokio::test]
+    async fn test_to_arrow() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, completed_date_key, created_at, created_date_key, days_to_complete, num_likes, cached_at)
+                     VALUES ('t1', 'Done', 1, '2025-01-10', '2025-01-10', '2025-01-01', '2025-01-01', 9, 2, datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('t2', 'Open', 0, '2025-01-02', '2025-01-02', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let batch = QueryBuilder::new()
+            .order_by("t.task_gid")
+            .to_arrow(&db)
+            .await
+            .unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), task_schema());
+
+        let names = batch
+            .column_by_name("name")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "Done");
+        let days = batch
+            .column_by_name("days_to_complete")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(days.value(0), 9);
+        assert!(days.is_null(1));
+    }
+}
@@ -1,6 +1,11 @@
+use std::io::Write;
+
+use rusqlite::types::Value;
 use serde::Serialize;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::query::labels::{self, ProjectLabel};
+use crate::search::query::compile_query;
 use crate::storage::Database;
 
 /// A row from a task query.
@@ -24,6 +29,312 @@ pub struct TaskRow {
     pub permalink_url: Option<String>,
 }
 
+/// CSV header for task rows, in [`TaskRow::csv_record`] order.
+const TASK_CSV_HEADER: [&str; 16] = [
+    "task_gid",
+    "name",
+    "assignee_gid",
+    "assignee_name",
+    "is_completed",
+    "completed_at",
+    "due_on",
+    "created_at",
+    "modified_at",
+    "project_name",
+    "section_name",
+    "is_overdue",
+    "days_to_complete",
+    "num_subtasks",
+    "num_likes",
+    "permalink_url",
+];
+
+impl TaskRow {
+    /// Read a row of [`QueryBuilder::build_sql`]'s select list.
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(TaskRow {
+            task_gid: row.get(0)?,
+            name: row.get(1)?,
+            assignee_gid: row.get(2)?,
+            assignee_name: row.get(3)?,
+            is_completed: row.get::<_, i32>(4)? != 0,
+            completed_at: row.get(5)?,
+            due_on: row.get(6)?,
+            created_at: row.get(7)?,
+            modified_at: row.get(8)?,
+            project_name: row.get(9)?,
+            section_name: row.get(10)?,
+            is_overdue: row.get::<_, i32>(11)? != 0,
+            days_to_complete: row.get(12)?,
+            num_subtasks: row.get(13)?,
+            num_likes: row.get(14)?,
+            permalink_url: row.get(15)?,
+        })
+    }
+
+    fn csv_record(&self) -> [String; 16] {
+        let text = |s: &Option<String>| s.clone().unwrap_or_default();
+        [
+            self.task_gid.clone(),
+            self.name.clone(),
+            text(&self.assignee_gid),
+            text(&self.assignee_name),
+            self.is_completed.to_string(),
+            text(&self.completed_at),
+            text(&self.due_on),
+            self.created_at.clone(),
+            text(&self.modified_at),
+            text(&self.project_name),
+            text(&self.section_name),
+            self.is_overdue.to_string(),
+            self.days_to_complete
+                .map_or(String::new(), |d| d.to_string()),
+            self.num_subtasks.to_string(),
+            self.num_likes.to_string(),
+            text(&self.permalink_url),
+        ]
+    }
+}
+
+/// How [`QueryBuilder::custom_field`] compares a field's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOp {
+    /// Display value matches, case-insensitively (or the number, for numbers)
+    Equals,
+    /// Display value contains the text, case-insensitively
+    Contains,
+    /// Number or date is greater than the value
+    Gt,
+    /// Number or date is at least the value
+    Gte,
+    /// Number or date is less than the value
+    Lt,
+    /// Number or date is at most the value
+    Lte,
+}
+
+impl FieldOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            FieldOp::Equals => "=",
+            FieldOp::Contains => "~",
+            FieldOp::Gt => ">",
+            FieldOp::Gte => ">=",
+            FieldOp::Lt => "<",
+            FieldOp::Lte => "<=",
+        }
+    }
+}
+
+/// Split a filter like `Priority=High`, `Notes~invoice`, or `Points>=3`
+/// into field, operator, and value.
+pub fn parse_field_filter(expr: &str) -> Result<(String, FieldOp, String)> {
+    let invalid = || {
+        Error::Other(format!(
+            "invalid field filter '{expr}': expected FIELD=VALUE, FIELD~TEXT, or FIELD>=VALUE (also >, <, <=)"
+        ))
+    };
+    let at = expr.find(['=', '~', '<', '>']).ok_or_else(invalid)?;
+    let rest = &expr[at..];
+    let (op, len) = if rest.starts_with(">=") {
+        (FieldOp::Gte, 2)
+    } else if rest.starts_with("<=") {
+        (FieldOp::Lte, 2)
+    } else {
+        match rest.as_bytes()[0] {
+            b'=' => (FieldOp::Equals, 1),
+            b'~' => (FieldOp::Contains, 1),
+            b'>' => (FieldOp::Gt, 1),
+            _ => (FieldOp::Lt, 1),
+        }
+    };
+    let field = expr[..at].trim();
+    let value = rest[len..].trim();
+    if field.is_empty() || value.is_empty() {
+        return Err(invalid());
+    }
+    Ok((field.to_string(), op, value.to_string()))
+}
+
+/// A dimension to aggregate query results over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Assignee,
+    /// A task in several projects counts toward each.
+    Project,
+    /// A task in several projects counts toward its section in each.
+    Section,
+    /// A task with several tags counts toward each.
+    Tag,
+    /// Month of completion (`YYYY-MM`).
+    CompletedMonth,
+}
+
+impl GroupBy {
+    /// Parse a dimension name, ignoring case, `-`, and `_`. `month` is
+    /// accepted for `completed_month`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name: String = name
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_str() {
+            "assignee" => Some(GroupBy::Assignee),
+            "project" => Some(GroupBy::Project),
+            "section" => Some(GroupBy::Section),
+            "tag" => Some(GroupBy::Tag),
+            "month" | "completedmonth" => Some(GroupBy::CompletedMonth),
+            _ => None,
+        }
+    }
+
+    /// Joins onto the matched tasks `m`, then the group key and label
+    /// expressions. `project` is the parameter holding a project filter,
+    /// which limits project and section groups to that project.
+    fn sql(self, project: Option<usize>) -> (String, &'static str, &'static str) {
+        let bridge = match project {
+            Some(n) => format!(
+                "LEFT JOIN bridge_task_projects gb ON gb.task_gid = m.task_gid AND gb.project_gid = ?{n}"
+            ),
+            None => "LEFT JOIN bridge_task_projects gb ON gb.task_gid = m.task_gid".to_string(),
+        };
+        match self {
+            GroupBy::Assignee => (String::new(), "m.assignee_gid", "m.assignee_name"),
+            GroupBy::Project => (
+                format!("{bridge} LEFT JOIN dim_projects gp ON gp.project_gid = gb.project_gid"),
+                "gp.project_gid",
+                "gp.name",
+            ),
+            GroupBy::Section => (
+                format!(
+                    "{bridge} LEFT JOIN dim_projects gp ON gp.project_gid = gb.project_gid
+                     LEFT JOIN dim_sections gs ON gs.section_gid = gb.section_gid"
+                ),
+                "gs.section_gid",
+                "gp.name || ' / ' || gs.name",
+            ),
+            GroupBy::Tag => (
+                "LEFT JOIN bridge_task_tags gt ON gt.task_gid = m.task_gid".to_string(),
+                "gt.tag_gid",
+                "gt.tag_name",
+            ),
+            GroupBy::CompletedMonth => (
+                String::new(),
+                "substr(m.completed_at, 1, 7)",
+                "substr(m.completed_at, 1, 7)",
+            ),
+        }
+    }
+
+    /// Label for the group of tasks with no value.
+    fn missing_label(self) -> &'static str {
+        match self {
+            GroupBy::Assignee => "(unassigned)",
+            GroupBy::Project => "(no project)",
+            GroupBy::Section => "(no section)",
+            GroupBy::Tag => "(untagged)",
+            GroupBy::CompletedMonth => "(not completed)",
+        }
+    }
+}
+
+/// CSV header for group rows, in [`GroupRow::csv_record`] order.
+const GROUP_CSV_HEADER: [&str; 8] = [
+    "key",
+    "label",
+    "tasks",
+    "completed",
+    "overdue",
+    "subtasks",
+    "avg_days_to_complete",
+    "field_sum",
+];
+
+/// Aggregates for one group of a grouped query.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupRow {
+    /// Assignee, project, section, or tag GID, or `YYYY-MM`; `None` for
+    /// tasks with no value.
+    pub key: Option<String>,
+    pub label: String,
+    pub tasks: u64,
+    pub completed: u64,
+    pub overdue: u64,
+    pub subtasks: u64,
+    pub avg_days_to_complete: Option<f64>,
+    /// Sum of the custom field set with [`QueryBuilder::sum_field`].
+    pub field_sum: Option<f64>,
+}
+
+impl GroupRow {
+    fn csv_record(&self) -> [String; 8] {
+        [
+            self.key.clone().unwrap_or_default(),
+            self.label.clone(),
+            self.tasks.to_string(),
+            self.completed.to_string(),
+            self.overdue.to_string(),
+            self.subtasks.to_string(),
+            self.avg_days_to_complete
+                .map_or(String::new(), |d| format!("{d:.1}")),
+            self.field_sum.map_or(String::new(), |s| s.to_string()),
+        ]
+    }
+}
+
+/// Column of the sort key [`QueryBuilder`] selects after the task columns.
+const SORT_KEY_COLUMN: usize = 16;
+
+/// One page of a query, from [`QueryBuilder::page`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskPage {
+    pub tasks: Vec<TaskRow>,
+    /// Pass to [`QueryBuilder::after`] for the next page; `None` on the
+    /// last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Where a page starts: after a task, looked up in the results, or after
+/// a sort key and task GID from a cursor.
+#[derive(Debug, Clone)]
+enum After {
+    Task(String),
+    Key(Value, String),
+}
+
+impl After {
+    fn parse(cursor: &str) -> Self {
+        Self::parse_cursor(cursor).unwrap_or_else(|| After::Task(cursor.to_string()))
+    }
+
+    /// A cursor is a JSON array of the sort key and task GID.
+    fn parse_cursor(cursor: &str) -> Option<Self> {
+        let (key, gid): (serde_json::Value, String) = serde_json::from_str(cursor).ok()?;
+        let key = match key {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::String(s) => Value::Text(s),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Integer(i),
+                None => Value::Real(n.as_f64()?),
+            },
+            _ => return None,
+        };
+        Some(After::Key(key, gid))
+    }
+}
+
+fn encode_cursor(key: &Value, task_gid: &str) -> String {
+    let key = match key {
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Real(f) => serde_json::Value::from(*f),
+        Value::Text(s) => serde_json::Value::from(s.as_str()),
+        Value::Null | Value::Blob(_) => serde_json::Value::Null,
+    };
+    serde_json::json!([key, task_gid]).to_string()
+}
+
 /// Builder for constructing task queries with optional filters.
 #[derive(Debug, Clone, Default)]
 pub struct QueryBuilder {
@@ -31,6 +342,7 @@ pub struct QueryBuilder {
     portfolio_gid: Option<String>,
     team_gid: Option<String>,
     assignee_gid: Option<String>,
+    task_gids: Option<Vec<String>>,
     completed: Option<bool>,
     overdue: Option<bool>,
     created_after: Option<String>,
@@ -41,8 +353,20 @@ pub struct QueryBuilder {
     due_before: Option<String>,
     has_assignee: Option<bool>,
     is_subtask: Option<bool>,
+    subtype: Option<String>,
+    include_templates: bool,
+    include_deleted: bool,
     tag_name: Option<String>,
+    project_color: Option<String>,
+    project_label: Option<String>,
+    label_filter: Option<ProjectLabel>,
+    field_was: Option<(String, String)>,
+    custom_fields: Vec<(String, FieldOp, String)>,
+    matching: Option<String>,
+    group_by: Option<GroupBy>,
+    sum_field: Option<String>,
     limit: Option<u32>,
+    after: Option<After>,
     order_by: Option<String>,
     order_desc: bool,
 }
@@ -72,6 +396,13 @@ impl QueryBuilder {
         self
     }
 
+    /// Restrict to these task GIDs. Listed tasks are returned even if their
+    /// only project is a template.
+    pub fn gids(mut self, gids: Vec<String>) -> Self {
+        self.task_gids = Some(gids);
+        self
+    }
+
     pub fn completed(mut self, val: bool) -> Self {
         self.completed = Some(val);
         self
@@ -122,16 +453,102 @@ impl QueryBuilder {
         self
     }
 
+    /// Filter by Asana task subtype: `default_task`, `milestone`, or `approval`.
+    pub fn subtype(mut self, subtype: &str) -> Self {
+        self.subtype = Some(subtype.to_lowercase());
+        self
+    }
+
+    /// Include tasks whose only project is a template. Templates are
+    /// excluded by default so their placeholder tasks don't skew results.
+    pub fn include_templates(mut self, val: bool) -> Self {
+        self.include_templates = val;
+        self
+    }
+
+    /// Include tasks deleted in Asana, which sync keeps flagged
+    /// `is_deleted` rather than removing.
+    pub fn include_deleted(mut self, val: bool) -> Self {
+        self.include_deleted = val;
+        self
+    }
+
     pub fn tag(mut self, name: &str) -> Self {
         self.tag_name = Some(name.to_string());
         self
     }
 
+    /// Filter to tasks in projects with this Asana color (e.g. `dark-green`).
+    pub fn project_color(mut self, color: &str) -> Self {
+        self.project_color = Some(color.to_lowercase());
+        self
+    }
+
+    /// Filter to tasks in projects matching a label from the
+    /// `project_labels` config. Resolved when the query runs.
+    pub fn project_label(mut self, name: &str) -> Self {
+        self.project_label = Some(name.to_string());
+        self
+    }
+
+    /// Filter to tasks whose custom field `field` (by name) holds or once
+    /// held `value`, both matched case-insensitively. Past values come from
+    /// `fact_custom_field_changes`, so the field must be listed in
+    /// `tracked_custom_fields`.
+    pub fn field_was(mut self, field: &str, value: &str) -> Self {
+        self.field_was = Some((field.to_string(), value.to_string()));
+        self
+    }
+
+    /// Filter to tasks whose custom field `field` (by name, case-insensitive,
+    /// or GID) currently compares to `value`. Range operators compare
+    /// numbers when `value` is numeric and `YYYY-MM-DD` dates otherwise.
+    /// Repeat to require several fields.
+    pub fn custom_field(mut self, field: &str, op: FieldOp, value: &str) -> Self {
+        self.custom_fields
+            .push((field.to_string(), op, value.to_string()));
+        self
+    }
+
+    /// Filter to tasks whose name or notes match `text`, in the syntax of
+    /// [`crate::search::query`] (its `field:` filters are ignored; use the
+    /// builder's). Unless `order_by` is set, results are ordered by
+    /// relevance. Text with no positive terms matches nothing.
+    pub fn matching(mut self, text: &str) -> Self {
+        self.matching = Some(text.to_string());
+        self
+    }
+
+    /// Aggregate matching tasks by `dimension`. [`Self::groups`] returns
+    /// the groups, as do the JSON and CSV output methods; the limit
+    /// then applies to groups rather than tasks, and `after` is ignored.
+    pub fn group_by(mut self, dimension: GroupBy) -> Self {
+        self.group_by = Some(dimension);
+        self
+    }
+
+    /// Also sum the number custom field `field` (by name, case-insensitive,
+    /// or GID) in each group.
+    pub fn sum_field(mut self, field: &str) -> Self {
+        self.sum_field = Some(field.to_string());
+        self
+    }
+
     pub fn limit(mut self, n: u32) -> Self {
         self.limit = Some(n);
         self
     }
 
+    /// Start after `cursor`: a [`TaskPage::next_cursor`], or the GID of
+    /// the last task of the previous page. Tasks are ordered by the order
+    /// field, then task GID, so pages neither skip nor repeat tasks. A
+    /// task GID has to still match the query; a cursor doesn't. Keep the
+    /// filters and order the same from page to page.
+    pub fn after(mut self, cursor: &str) -> Self {
+        self.after = Some(After::parse(cursor));
+        self
+    }
+
     pub fn order_by(mut self, field: &str) -> Self {
         self.order_by = Some(field.to_string());
         self
@@ -142,35 +559,24 @@ impl QueryBuilder {
         self
     }
 
+    /// Look up a named project label in config, if one was requested.
+    async fn resolve_label(mut self, db: &Database) -> Result<Self> {
+        if let Some(ref name) = self.project_label {
+            self.label_filter = Some(labels::load_project_label(db, name).await?);
+        }
+        Ok(self)
+    }
+
     /// Build and execute the query, returning task rows.
     pub async fn tasks(self, db: &Database) -> Result<Vec<TaskRow>> {
-        let builder = self;
+        let builder = self.resolve_label(db).await?;
         db.reader()
             .call(move |conn| {
                 let (sql, params) = builder.build_sql();
                 let param_refs: Vec<&dyn rusqlite::types::ToSql> =
                     params.iter().map(|p| p.as_ref()).collect();
                 let mut stmt = conn.prepare(&sql)?;
-                let rows = stmt.query_map(param_refs.as_slice(), |row| {
-                    Ok(TaskRow {
-                        task_gid: row.get(0)?,
-                        name: row.get(1)?,
-                        assignee_gid: row.get(2)?,
-                        assignee_name: row.get(3)?,
-                        is_completed: row.get::<_, i32>(4)? != 0,
-                        completed_at: row.get(5)?,
-                        due_on: row.get(6)?,
-                        created_at: row.get(7)?,
-                        modified_at: row.get(8)?,
-                        project_name: row.get(9)?,
-                        section_name: row.get(10)?,
-                        is_overdue: row.get::<_, i32>(11)? != 0,
-                        days_to_complete: row.get(12)?,
-                        num_subtasks: row.get(13)?,
-                        num_likes: row.get(14)?,
-                        permalink_url: row.get(15)?,
-                    })
-                })?;
+                let rows = stmt.query_map(param_refs.as_slice(), TaskRow::from_row)?;
                 let result: std::result::Result<Vec<TaskRow>, _> = rows.collect();
                 result
             })
@@ -178,9 +584,48 @@ impl QueryBuilder {
             .map_err(|e| crate::error::Error::Database(e.to_string()))
     }
 
+    /// Build and execute the query, returning up to `limit` tasks and a
+    /// cursor for [`Self::after`] to fetch the ones that follow.
+    pub async fn page(mut self, db: &Database) -> Result<TaskPage> {
+        let size = self.limit.map(|n| n as usize);
+        // One more row than the page says whether another page follows
+        self.limit = self.limit.map(|n| n.saturating_add(1));
+        let builder = self.resolve_label(db).await?;
+        let mut rows = db
+            .reader()
+            .call(move |conn| {
+                let (sql, params) = builder.build_sql();
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(param_refs.as_slice(), |row| {
+                    Ok((
+                        TaskRow::from_row(row)?,
+                        row.get::<_, Value>(SORT_KEY_COLUMN)?,
+                    ))
+                })?;
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+            })
+            .await
+            .map_err(|e| crate::error::Error::Database(e.to_string()))?;
+
+        let next_cursor = match size {
+            Some(size) if rows.len() > size => {
+                rows.truncate(size);
+                rows.last()
+                    .map(|(task, key)| encode_cursor(key, &task.task_gid))
+            }
+            _ => None,
+        };
+        Ok(TaskPage {
+            tasks: rows.into_iter().map(|(task, _)| task).collect(),
+            next_cursor,
+        })
+    }
+
     /// Build and execute the query, returning a count of matching tasks.
     pub async fn count(self, db: &Database) -> Result<u64> {
-        let builder = self;
+        let builder = self.resolve_label(db).await?;
         db.reader()
             .call(move |conn| {
                 let (inner_sql, params) = builder.build_sql();
@@ -194,40 +639,190 @@ impl QueryBuilder {
             .map_err(|e| crate::error::Error::Database(e.to_string()))
     }
 
+    /// Build and execute a grouped query, returning one row per group,
+    /// largest first (chronological for [`GroupBy::CompletedMonth`]).
+    pub async fn groups(self, db: &Database) -> Result<Vec<GroupRow>> {
+        let mut builder = self.resolve_label(db).await?;
+        let Some(group_by) = builder.group_by else {
+            return Err(Error::Other("groups() requires group_by".to_string()));
+        };
+        let limit = builder.limit.take();
+        builder.after = None;
+        db.reader()
+            .call(move |conn| {
+                let (inner_sql, mut params) = builder.build_sql();
+                let field_sum = match builder.sum_field {
+                    Some(ref field) => {
+                        params.push(Box::new(field.clone()));
+                        format!(
+                            "SUM((SELECT tcf.number_value FROM fact_task_custom_fields tcf
+                                JOIN dim_custom_fields cf ON cf.field_gid = tcf.field_gid
+                                WHERE tcf.task_gid = m.task_gid
+                                  AND (tcf.field_gid = ?{n} OR cf.name = ?{n} COLLATE NOCASE)
+                                LIMIT 1))",
+                            n = params.len()
+                        )
+                    }
+                    None => "NULL".to_string(),
+                };
+                // A task filtered to one project only counts toward it
+                let project = match builder.project_gid {
+                    Some(ref gid) if matches!(group_by, GroupBy::Project | GroupBy::Section) => {
+                        params.push(Box::new(gid.clone()));
+                        Some(params.len())
+                    }
+                    _ => None,
+                };
+                let (joins, key, label) = group_by.sql(project);
+                let order = match group_by {
+                    GroupBy::CompletedMonth => "group_key IS NULL, group_key",
+                    _ => "tasks DESC, group_key IS NULL, group_label",
+                };
+                let mut sql = format!(
+                    "WITH m AS ({inner_sql})
+                     SELECT {key} AS group_key, MAX({label}) AS group_label, COUNT(*) AS tasks,
+                            SUM(m.is_completed), SUM(m.is_overdue), SUM(m.num_subtasks),
+                            AVG(m.days_to_complete), {field_sum}
+                     FROM m {joins}
+                     GROUP BY {key}
+                     ORDER BY {order}"
+                );
+                if let Some(limit) = limit {
+                    params.push(Box::new(limit));
+                    sql.push_str(&format!(" LIMIT ?{}", params.len()));
+                }
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(param_refs.as_slice(), |row| {
+                    let key: Option<String> = row.get(0)?;
+                    let label: Option<String> = row.get(1)?;
+                    Ok(GroupRow {
+                        label: match (label, &key) {
+                            (Some(label), _) => label,
+                            (None, Some(key)) => key.clone(),
+                            (None, None) => group_by.missing_label().to_string(),
+                        },
+                        key,
+                        tasks: row.get::<_, i64>(2)? as u64,
+                        completed: row.get::<_, Option<i64>>(3)?.unwrap_or(0) as u64,
+                        overdue: row.get::<_, Option<i64>>(4)?.unwrap_or(0) as u64,
+                        subtasks: row.get::<_, Option<i64>>(5)?.unwrap_or(0) as u64,
+                        avg_days_to_complete: row.get(6)?,
+                        field_sum: row.get(7)?,
+                    })
+                })?;
+                rows.collect::<std::result::Result<Vec<_>, _>>()
+            })
+            .await
+            .map_err(|e| crate::error::Error::Database(e.to_string()))
+    }
+
     /// Build and execute the query, returning results as JSON.
     pub async fn to_json(self, db: &Database) -> Result<String> {
-        let rows = self.tasks(db).await?;
-        serde_json::to_string_pretty(&rows).map_err(|e| crate::error::Error::Other(e.to_string()))
+        let json = if self.group_by.is_some() {
+            serde_json::to_string_pretty(&self.groups(db).await?)
+        } else {
+            serde_json::to_string_pretty(&self.tasks(db).await?)
+        };
+        json.map_err(|e| crate::error::Error::Other(e.to_string()))
     }
 
     /// Build and execute the query, returning results as CSV.
     pub async fn to_csv(self, db: &Database) -> Result<String> {
-        let rows = self.tasks(db).await?;
-        let mut out = String::new();
-        out.push_str("task_gid,name,assignee_gid,assignee_name,is_completed,completed_at,due_on,created_at,modified_at,project_name,section_name,is_overdue,days_to_complete,num_subtasks,num_likes,permalink_url\n");
-        for row in &rows {
-            out.push_str(&format!(
-                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
-                csv_escape(&row.task_gid),
-                csv_escape(&row.name),
-                csv_escape(row.assignee_gid.as_deref().unwrap_or("")),
-                csv_escape(row.assignee_name.as_deref().unwrap_or("")),
-                row.is_completed,
-                csv_escape(row.completed_at.as_deref().unwrap_or("")),
-                csv_escape(row.due_on.as_deref().unwrap_or("")),
-                csv_escape(&row.created_at),
-                csv_escape(row.modified_at.as_deref().unwrap_or("")),
-                csv_escape(row.project_name.as_deref().unwrap_or("")),
-                csv_escape(row.section_name.as_deref().unwrap_or("")),
-                row.is_overdue,
-                row.days_to_complete
-                    .map_or(String::new(), |d| d.to_string()),
-                row.num_subtasks,
-                row.num_likes,
-                csv_escape(row.permalink_url.as_deref().unwrap_or("")),
-            ));
+        let (_, out) = self.write_csv_into(db, Vec::new()).await?;
+        String::from_utf8(out).map_err(|e| Error::Other(e.to_string()))
+    }
+
+    /// Build and execute the query, writing results to `out` as CSV one
+    /// row at a time, so exports of any size run in constant memory.
+    /// Returns the number of rows written.
+    pub async fn write_csv<W: Write + Send + 'static>(self, db: &Database, out: W) -> Result<u64> {
+        Ok(self.write_csv_into(db, out).await?.0)
+    }
+
+    /// Build and execute the query, writing results to `out` as JSON
+    /// lines, one object per task (or group), one row at a time. Returns
+    /// the number of rows written.
+    pub async fn write_jsonl<W: Write + Send + 'static>(
+        self,
+        db: &Database,
+        mut out: W,
+    ) -> Result<u64> {
+        fn line<W: Write>(out: &mut W, row: &impl Serialize) -> Result<()> {
+            serde_json::to_writer(&mut *out, row).map_err(|e| Error::Other(e.to_string()))?;
+            writeln!(out).map_err(io_error)
         }
-        Ok(out)
+        let written = if self.group_by.is_some() {
+            let groups = self.groups(db).await?;
+            for group in &groups {
+                line(&mut out, group)?;
+            }
+            groups.len() as u64
+        } else {
+            let (written, returned) = self.stream(db, out, |out, row| line(out, &row)).await?;
+            out = returned;
+            written
+        };
+        out.flush().map_err(io_error)?;
+        Ok(written)
+    }
+
+    async fn write_csv_into<W: Write + Send + 'static>(
+        self,
+        db: &Database,
+        out: W,
+    ) -> Result<(u64, W)> {
+        let mut writer = csv::Writer::from_writer(out);
+        let written = if self.group_by.is_some() {
+            let groups = self.groups(db).await?;
+            writer.write_record(GROUP_CSV_HEADER).map_err(csv_error)?;
+            for group in &groups {
+                writer.write_record(group.csv_record()).map_err(csv_error)?;
+            }
+            groups.len() as u64
+        } else {
+            writer.write_record(TASK_CSV_HEADER).map_err(csv_error)?;
+            let (written, returned) = self
+                .stream(db, writer, |writer, row| {
+                    writer.write_record(row.csv_record()).map_err(csv_error)
+                })
+                .await?;
+            writer = returned;
+            written
+        };
+        let out = writer.into_inner().map_err(|e| io_error(e.into_error()))?;
+        Ok((written, out))
+    }
+
+    /// Run the query on the database thread, handing each task to `write`
+    /// as it is read rather than collecting them. Returns the number of
+    /// tasks and `state` back.
+    async fn stream<S, F>(self, db: &Database, mut state: S, mut write: F) -> Result<(u64, S)>
+    where
+        S: Send + 'static,
+        F: FnMut(&mut S, TaskRow) -> Result<()> + Send + 'static,
+    {
+        let builder = self.resolve_label(db).await?;
+        db.reader()
+            .call(move |conn| {
+                let (sql, params) = builder.build_sql();
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let mut rows = stmt.query(param_refs.as_slice())?;
+                let mut written = 0;
+                while let Some(row) = rows.next()? {
+                    write(&mut state, TaskRow::from_row(row)?)?;
+                    written += 1;
+                }
+                Ok((written, state))
+            })
+            .await
+            .map_err(|e| match e {
+                tokio_rusqlite::Error::Error(e) => e,
+                other => Error::Database(other.to_string()),
+            })
     }
 
     fn build_sql(&self) -> (String, Vec<Box<dyn rusqlite::types::ToSql>>) {
@@ -237,12 +832,12 @@ impl QueryBuilder {
         let mut param_idx = 1;
 
         // Base query
-        let select = "SELECT t.task_gid, t.name, t.assignee_gid, u.name as assignee_name,
+        let columns = "SELECT t.task_gid, t.name, t.assignee_gid, u.name as assignee_name,
                 t.is_completed, t.completed_at, t.due_on, t.created_at, t.modified_at,
                 p.name as project_name, s.name as section_name,
                 t.is_overdue, t.days_to_complete, t.num_subtasks, t.num_likes,
-                t.permalink_url
-            FROM fact_tasks t
+                t.permalink_url";
+        let from = "FROM fact_tasks t
             LEFT JOIN dim_users u ON u.user_gid = t.assignee_gid
             LEFT JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
             LEFT JOIN dim_projects p ON p.project_gid = btp.project_gid
@@ -280,6 +875,15 @@ impl QueryBuilder {
             param_idx += 1;
         }
 
+        // Task GID list, bound as one JSON array so its length is unbounded
+        if let Some(ref gids) = self.task_gids {
+            wheres.push(format!(
+                "t.task_gid IN (SELECT value FROM json_each(?{param_idx}))"
+            ));
+            params.push(Box::new(serde_json::json!(gids).to_string()));
+            param_idx += 1;
+        }
+
         // Completed filter
         if let Some(completed) = self.completed {
             wheres.push(format!("t.is_completed = ?{param_idx}"));
@@ -342,6 +946,23 @@ impl QueryBuilder {
             param_idx += 1;
         }
 
+        // Subtype filter
+        if let Some(ref subtype) = self.subtype {
+            wheres.push(format!("t.resource_subtype = ?{param_idx}"));
+            params.push(Box::new(subtype.clone()));
+            param_idx += 1;
+        }
+
+        // Template projects
+        if !self.include_templates && self.task_gids.is_none() {
+            wheres.push("(p.is_template IS NULL OR p.is_template = 0)".to_string());
+        }
+
+        // Deleted tasks
+        if !self.include_deleted {
+            wheres.push("t.is_deleted = 0".to_string());
+        }
+
         // Tag filter
         if let Some(ref tag) = self.tag_name {
             joins.push(format!(
@@ -351,8 +972,101 @@ impl QueryBuilder {
             param_idx += 1;
         }
 
-        // Assemble SQL
-        let mut sql = select.to_string();
+        // Project color filter
+        if let Some(ref color) = self.project_color {
+            wheres.push(format!("LOWER(p.color) = ?{param_idx}"));
+            params.push(Box::new(color.clone()));
+            param_idx += 1;
+        }
+
+        // Project label filter (resolved from config)
+        if let Some(ref label) = self.label_filter {
+            let (clause, values) = labels::label_sql(label, "p.project_gid", "p.color", param_idx);
+            wheres.push(clause);
+            param_idx += values.len();
+            for v in values {
+                params.push(Box::new(v));
+            }
+        }
+
+        // Custom field value, now or in its change history
+        if let Some((ref field, ref value)) = self.field_was {
+            let (f, v) = (param_idx, param_idx + 1);
+            wheres.push(format!(
+                "(EXISTS (SELECT 1 FROM fact_custom_field_changes cfc
+                     WHERE cfc.task_gid = t.task_gid AND cfc.field_name = ?{f} COLLATE NOCASE
+                       AND (cfc.to_value = ?{v} COLLATE NOCASE OR cfc.from_value = ?{v} COLLATE NOCASE))
+                  OR EXISTS (SELECT 1 FROM fact_task_custom_fields tcf
+                     JOIN dim_custom_fields cf ON cf.field_gid = tcf.field_gid
+                     WHERE tcf.task_gid = t.task_gid AND cf.name = ?{f} COLLATE NOCASE
+                       AND tcf.display_value = ?{v} COLLATE NOCASE))"
+            ));
+            params.push(Box::new(field.clone()));
+            params.push(Box::new(value.clone()));
+            param_idx += 2;
+        }
+
+        // Current custom field values
+        for (field, op, value) in &self.custom_fields {
+            let (f, v) = (param_idx, param_idx + 1);
+            params.push(Box::new(field.clone()));
+            params.push(Box::new(value.clone()));
+            param_idx += 2;
+            // Contains always matches text
+            let number = match op {
+                FieldOp::Contains => None,
+                _ => value.parse::<f64>().ok().filter(|n| n.is_finite()),
+            };
+            let n = param_idx;
+            if let Some(number) = number {
+                params.push(Box::new(number));
+                param_idx += 1;
+            }
+            let condition = match (op, number) {
+                (FieldOp::Equals, Some(_)) => {
+                    format!("(tcf.display_value = ?{v} COLLATE NOCASE OR tcf.number_value = ?{n})")
+                }
+                (FieldOp::Equals, None) => format!("tcf.display_value = ?{v} COLLATE NOCASE"),
+                (FieldOp::Contains, _) => {
+                    format!("instr(LOWER(tcf.display_value), LOWER(?{v})) > 0")
+                }
+                (op, Some(_)) => format!("tcf.number_value {} ?{n}", op.symbol()),
+                (op, None) => format!("tcf.date_value {} ?{v}", op.symbol()),
+            };
+            wheres.push(format!(
+                "EXISTS (SELECT 1 FROM fact_task_custom_fields tcf
+                     JOIN dim_custom_fields cf ON cf.field_gid = tcf.field_gid
+                     WHERE tcf.task_gid = t.task_gid
+                       AND (tcf.field_gid = ?{f} OR cf.name = ?{f} COLLATE NOCASE)
+                       AND {condition})"
+            ));
+        }
+
+        // Full-text match on name and notes
+        let mut ranked = false;
+        if let Some(ref text) = self.matching {
+            match compile_query(text).match_expr {
+                Some(expr) => {
+                    joins.push("JOIN tasks_fts ON tasks_fts.rowid = t.id".to_string());
+                    wheres.push(format!("tasks_fts MATCH ?{param_idx}"));
+                    params.push(Box::new(expr));
+                    param_idx += 1;
+                    ranked = true;
+                }
+                None => wheres.push("0".to_string()),
+            }
+        }
+
+        let default_order = if ranked {
+            "tasks_fts.rank"
+        } else {
+            "t.modified_at"
+        };
+        let order_field = self.order_by.as_deref().unwrap_or(default_order);
+        let order_dir = if self.order_desc { "DESC" } else { "ASC" };
+
+        // Assemble SQL, selecting the order as the sort key for cursors
+        let mut sql = format!("{columns}, {order_field} AS sort_key {from}");
         for join in &joins {
             sql.push(' ');
             sql.push_str(join);
@@ -365,10 +1079,44 @@ impl QueryBuilder {
         // GROUP BY to deduplicate when task is in multiple projects
         sql.push_str(" GROUP BY t.task_gid");
 
-        // ORDER BY
-        let order_field = self.order_by.as_deref().unwrap_or("t.modified_at");
-        let order_dir = if self.order_desc { "DESC" } else { "ASC" };
-        sql.push_str(&format!(" ORDER BY {order_field} {order_dir}"));
+        // ORDER BY, with the task GID breaking ties so pages are stable
+        match self.after {
+            None => sql.push_str(&format!(
+                " ORDER BY {order_field} {order_dir}, t.task_gid {order_dir}"
+            )),
+            Some(ref after) => {
+                let cursor = match after {
+                    After::Task(gid) => {
+                        let cursor = format!(
+                            "SELECT sort_key AS k, task_gid AS g FROM q WHERE task_gid = ?{param_idx}"
+                        );
+                        params.push(Box::new(gid.clone()));
+                        param_idx += 1;
+                        cursor
+                    }
+                    After::Key(key, gid) => {
+                        let cursor = format!("SELECT ?{} AS k, ?{} AS g", param_idx, param_idx + 1);
+                        params.push(Box::new(key.clone()));
+                        params.push(Box::new(gid.clone()));
+                        param_idx += 2;
+                        cursor
+                    }
+                };
+                // NULL sort keys come first ascending and last descending
+                let (cmp, nulls) = if self.order_desc {
+                    ("<", "q.sort_key IS NULL AND c.k IS NOT NULL")
+                } else {
+                    (">", "c.k IS NULL AND q.sort_key IS NOT NULL")
+                };
+                sql = format!(
+                    "WITH q AS ({sql}), c AS ({cursor})
+                     SELECT q.* FROM q, c
+                     WHERE (q.sort_key IS c.k AND q.task_gid {cmp} c.g)
+                        OR ({nulls}) OR q.sort_key {cmp} c.k
+                     ORDER BY q.sort_key {order_dir}, q.task_gid {order_dir}"
+                );
+            }
+        }
 
         // LIMIT
         if let Some(limit) = self.limit {
@@ -381,12 +1129,12 @@ impl QueryBuilder {
     }
 }
 
-fn csv_escape(s: &str) -> String {
-    if s.contains(',') || s.contains('"') || s.contains('\n') {
-        format!("\"{}\"", s.replace('"', "\"\""))
-    } else {
-        s.to_string()
-    }
+pub(crate) fn csv_error(e: csv::Error) -> Error {
+    Error::Other(format!("CSV write failed: {e}"))
+}
+
+pub(crate) fn io_error(e: std::io::Error) -> Error {
+    Error::Other(format!("Write failed: {e}"))
 }
 
 #[cfg(test)]
@@ -399,8 +1147,18 @@ mod tests {
         let (sql, params) = builder.build_sql();
         assert!(sql.contains("FROM fact_tasks t"));
         assert!(sql.contains("GROUP BY t.task_gid"));
-        assert!(sql.contains("ORDER BY t.modified_at ASC"));
+        assert!(sql.contains("ORDER BY t.modified_at ASC, t.task_gid ASC"));
+        assert!(sql.contains("(p.is_template IS NULL OR p.is_template = 0)"));
         assert!(params.is_empty());
+
+        assert!(sql.contains("t.is_deleted = 0"));
+
+        let (sql, _) = QueryBuilder::new()
+            .include_templates(true)
+            .include_deleted(true)
+            .build_sql();
+        assert!(!sql.contains("is_template"));
+        assert!(!sql.contains("is_deleted"));
     }
 
     #[test]
@@ -420,9 +1178,507 @@ mod tests {
     }
 
     #[test]
-    fn test_csv_escape() {
-        assert_eq!(csv_escape("hello"), "hello");
-        assert_eq!(csv_escape("hello,world"), "\"hello,world\"");
-        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    fn test_build_sql_with_project_color_and_label() {
+        let mut builder = QueryBuilder::new().project_color("Dark-Green").limit(5);
+        builder.label_filter = Some(ProjectLabel {
+            name: "client".into(),
+            colors: vec!["light-green".into()],
+            project_gids: vec!["123".into()],
+        });
+        let (sql, params) = builder.build_sql();
+        assert!(sql.contains("LOWER(p.color) = ?1"));
+        assert!(sql.contains("(LOWER(p.color) IN (?2) OR p.project_gid IN (?3))"));
+        assert!(sql.contains("LIMIT ?4"));
+        assert_eq!(params.len(), 4);
+    }
+
+    #[test]
+    fn test_build_sql_with_subtype() {
+        let (sql, params) = QueryBuilder::new()
+            .completed(true)
+            .subtype("Milestone")
+            .build_sql();
+        assert!(sql.contains("t.resource_subtype = ?2"));
+        assert_eq!(params.len(), 2);
+    }
+
+    #[test]
+    fn test_build_sql_with_field_was() {
+        let (sql, params) = QueryBuilder::new()
+            .project("123")
+            .field_was("Status", "Blocked")
+            .limit(5)
+            .build_sql();
+        assert!(sql.contains("cfc.field_name = ?2 COLLATE NOCASE"));
+        assert!(sql.contains("tcf.display_value = ?3 COLLATE NOCASE"));
+        assert!(sql.contains("LIMIT ?4"));
+        assert_eq!(params.len(), 4);
+    }
+
+    #[test]
+    fn test_build_sql_with_matching() {
+        let (sql, params) = QueryBuilder::new()
+            .project("123")
+            .matching("launch -draft")
+            .limit(5)
+            .build_sql();
+        assert!(sql.contains("JOIN tasks_fts ON tasks_fts.rowid = t.id"));
+        assert!(sql.contains("tasks_fts MATCH ?2"));
+        assert!(sql.contains("ORDER BY tasks_fts.rank ASC"));
+        assert!(sql.contains("LIMIT ?3"));
+        assert_eq!(params.len(), 3);
+
+        let (sql, params) = QueryBuilder::new().matching("-draft").build_sql();
+        assert!(sql.contains(" AND 0 GROUP BY"));
+        assert!(!sql.contains("tasks_fts"));
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_parse_field_filter() {
+        let parse = |s| parse_field_filter(s).unwrap();
+        assert_eq!(
+            parse("Priority=High"),
+            ("Priority".into(), FieldOp::Equals, "High".into())
+        );
+        assert_eq!(
+            parse(" Notes ~ a=b "),
+            ("Notes".into(), FieldOp::Contains, "a=b".into())
+        );
+        assert_eq!(parse("Points>=3").1, FieldOp::Gte);
+        assert_eq!(parse("Points<3").1, FieldOp::Lt);
+        assert_eq!(parse("Due<=2025-03-31").2, "2025-03-31");
+        assert!(parse_field_filter("Priority").is_err());
+        assert!(parse_field_filter("=High").is_err());
+        assert!(parse_field_filter("Priority=").is_err());
+    }
+
+    #[test]
+    fn test_build_sql_with_custom_field() {
+        let (sql, params) = QueryBuilder::new()
+            .project("123")
+            .custom_field("Points", FieldOp::Gte, "3")
+            .custom_field("Priority", FieldOp::Equals, "High")
+            .limit(5)
+            .build_sql();
+        assert!(sql.contains("cf.name = ?2 COLLATE NOCASE"));
+        assert!(sql.contains("tcf.number_value >= ?4"));
+        assert!(sql.contains("cf.name = ?5 COLLATE NOCASE"));
+        assert!(sql.contains("tcf.display_value = ?6 COLLATE NOCASE"));
+        assert!(sql.contains("LIMIT ?7"));
+        assert_eq!(params.len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_query_by_custom_field() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('f1', 'Priority', 'enum', datetime('now')),
+                            ('f2', 'Points', 'number', datetime('now')),
+                            ('f3', 'Launch', 'date', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('1', 'One', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('2', 'Two', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('3', 'Three', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_task_custom_fields (task_gid, field_gid, text_value, number_value, date_value, display_value)
+                     VALUES ('1', 'f1', NULL, NULL, NULL, 'High'),
+                            ('2', 'f1', NULL, NULL, NULL, 'Highest'),
+                            ('3', 'f1', NULL, NULL, NULL, 'Low'),
+                            ('1', 'f2', NULL, 5, NULL, '5'),
+                            ('2', 'f2', NULL, 2, NULL, '2'),
+                            ('1', 'f3', NULL, NULL, '2025-03-01', '2025-03-01'),
+                            ('3', 'f3', NULL, NULL, '2025-06-01', '2025-06-01');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let db = &db;
+        let gids = |b: QueryBuilder| async move {
+            let mut gids: Vec<String> = b
+                .tasks(db)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|r| r.task_gid)
+                .collect();
+            gids.sort();
+            gids
+        };
+        let q = QueryBuilder::new;
+        assert_eq!(
+            gids(q().custom_field("priority", FieldOp::Equals, "high")).await,
+            ["1"]
+        );
+        assert_eq!(
+            gids(q().custom_field("f1", FieldOp::Contains, "HIGH")).await,
+            ["1", "2"]
+        );
+        assert_eq!(
+            gids(q().custom_field("Points", FieldOp::Gte, "3")).await,
+            ["1"]
+        );
+        assert_eq!(
+            gids(q().custom_field("Points", FieldOp::Equals, "2.0")).await,
+            ["2"]
+        );
+        assert_eq!(
+            gids(q().custom_field("Launch", FieldOp::Lt, "2025-04-01")).await,
+            ["1"]
+        );
+        assert_eq!(
+            gids(
+                q().custom_field("Priority", FieldOp::Contains, "high")
+                    .custom_field("Points", FieldOp::Lt, "3")
+            )
+            .await,
+            ["2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_matching() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Web', 'w1', datetime('now')),
+                            ('p2', 'Mobile', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, notes, is_completed, created_at, created_date_key, cached_at)
+                     VALUES ('1', 'Fix login redirect', NULL, 0, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('2', 'Login page copy', 'Login login login', 1, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('3', 'Signup flow', 'Reuse the login form', 0, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('4', 'Login on tablets', NULL, 0, '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('1', 'p1'), ('2', 'p1'), ('3', 'p1'), ('3', 'p2'), ('4', 'p2');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let rows = QueryBuilder::new()
+            .project("p1")
+            .completed(false)
+            .matching("login")
+            .tasks(&db)
+            .await
+            .unwrap();
+        let mut gids: Vec<&str> = rows.iter().map(|r| r.task_gid.as_str()).collect();
+        gids.sort();
+        assert_eq!(gids, ["1", "3"]);
+        assert_eq!(rows[0].project_name.as_deref(), Some("Web"));
+
+        // Relevance order: the task mentioning login most ranks first
+        let ranked = QueryBuilder::new()
+            .matching("login")
+            .tasks(&db)
+            .await
+            .unwrap();
+        assert_eq!(ranked.len(), 4);
+        assert_eq!(ranked[0].task_gid, "2");
+
+        let count = QueryBuilder::new()
+            .matching("login -redirect")
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+        let none = QueryBuilder::new()
+            .matching("-login")
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(none, 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_group_by() {
+        assert_eq!(
+            GroupBy::parse("Completed-Month"),
+            Some(GroupBy::CompletedMonth)
+        );
+        assert_eq!(GroupBy::parse("month"), Some(GroupBy::CompletedMonth));
+        assert_eq!(GroupBy::parse("owner"), None);
+
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('u1', 'Ada', datetime('now')), ('u2', 'Grace', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Web', 'w1', datetime('now')),
+                            ('p2', 'Mobile', 'w1', datetime('now'));
+                     INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('f1', 'Points', 'number', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, days_to_complete, created_at, created_date_key, cached_at)
+                     VALUES ('1', 'One', 'u1', 1, '2025-01-10T00:00:00Z', 4, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('2', 'Two', 'u1', 1, '2025-02-03T00:00:00Z', 8, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('3', 'Three', 'u2', 0, NULL, NULL, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('4', 'Four', NULL, 1, '2025-01-20T00:00:00Z', 2, '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('1', 'p1'), ('2', 'p1'), ('2', 'p2'), ('3', 'p2'), ('4', 'p1');
+                     INSERT INTO bridge_task_tags (task_gid, tag_gid, tag_name)
+                     VALUES ('1', 'g1', 'bug'), ('2', 'g1', 'bug'), ('2', 'g2', 'ux');
+                     INSERT INTO fact_task_custom_fields (task_gid, field_gid, text_value, number_value, date_value, display_value)
+                     VALUES ('1', 'f1', NULL, 3, NULL, '3'), ('2', 'f1', NULL, 5, NULL, '5');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let groups = QueryBuilder::new()
+            .group_by(GroupBy::Assignee)
+            .sum_field("points")
+            .groups(&db)
+            .await
+            .unwrap();
+        let summary: Vec<(&str, u64, u64)> = groups
+            .iter()
+            .map(|g| (g.label.as_str(), g.tasks, g.completed))
+            .collect();
+        assert_eq!(
+            summary,
+            [("Ada", 2, 2), ("Grace", 1, 0), ("(unassigned)", 1, 1)]
+        );
+        assert_eq!(groups[0].key.as_deref(), Some("u1"));
+        assert_eq!(groups[0].avg_days_to_complete, Some(6.0));
+        assert_eq!(groups[0].field_sum, Some(8.0));
+        assert_eq!(groups[2].field_sum, None);
+
+        let labels = |groups: Vec<GroupRow>| -> Vec<(String, u64)> {
+            groups.into_iter().map(|g| (g.label, g.tasks)).collect()
+        };
+        let by_project = QueryBuilder::new()
+            .group_by(GroupBy::Project)
+            .groups(&db)
+            .await
+            .unwrap();
+        assert_eq!(
+            labels(by_project),
+            [("Web".to_string(), 3), ("Mobile".to_string(), 2)]
+        );
+        let in_mobile = QueryBuilder::new()
+            .project("p2")
+            .group_by(GroupBy::Project)
+            .groups(&db)
+            .await
+            .unwrap();
+        assert_eq!(labels(in_mobile), [("Mobile".to_string(), 2)]);
+
+        let by_tag = QueryBuilder::new()
+            .group_by(GroupBy::Tag)
+            .limit(2)
+            .groups(&db)
+            .await
+            .unwrap();
+        assert_eq!(
+            labels(by_tag),
+            [("bug".to_string(), 2), ("(untagged)".to_string(), 2)]
+        );
+
+        let by_month = QueryBuilder::new()
+            .group_by(GroupBy::CompletedMonth)
+            .groups(&db)
+            .await
+            .unwrap();
+        assert_eq!(
+            labels(by_month),
+            [
+                ("2025-01".to_string(), 2),
+                ("2025-02".to_string(), 1),
+                ("(not completed)".to_string(), 1)
+            ]
+        );
+
+        let csv = QueryBuilder::new()
+            .completed(true)
+            .group_by(GroupBy::CompletedMonth)
+            .to_csv(&db)
+            .await
+            .unwrap();
+        assert_eq!(csv.lines().nth(1), Some("2025-01,2025-01,2,2,0,0,3.0,"));
+        assert!(QueryBuilder::new().groups(&db).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_by_gids() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, is_template, cached_at)
+                     VALUES ('p1', 'Template', 'w1', 1, datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('1', 'One', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('2', 'Two', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('3', 'Three', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('3', 'p1');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let gids = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut rows: Vec<String> = QueryBuilder::new()
+            .gids(gids(&["1", "3", "404"]))
+            .tasks(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.task_gid)
+            .collect();
+        rows.sort();
+        assert_eq!(rows, ["1", "3"]);
+
+        let none = QueryBuilder::new()
+            .gids(Vec::new())
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(none, 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_pages() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, modified_at, cached_at)
+                     VALUES ('1', 'One', '2025-01-01', '2025-01-01', '2025-01-02', datetime('now')),
+                            ('2', 'Two', '2025-01-01', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('3', 'Three', '2025-01-01', '2025-01-01', '2025-01-02', datetime('now')),
+                            ('4', 'Four', '2025-01-01', '2025-01-01', NULL, datetime('now')),
+                            ('5', 'Five', '2025-01-01', '2025-01-01', '2025-01-03', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        async fn pages(db: &Database, descending: bool, by_gid: bool) -> Vec<Vec<String>> {
+            let mut pages = Vec::new();
+            let mut after: Option<String> = None;
+            loop {
+                let mut builder = QueryBuilder::new().order_by("t.modified_at").limit(2);
+                if descending {
+                    builder = builder.descending();
+                }
+                if let Some(ref after) = after {
+                    builder = builder.after(after);
+                }
+                let page = builder.page(db).await.unwrap();
+                let gids: Vec<String> = page.tasks.iter().map(|t| t.task_gid.clone()).collect();
+                let last = gids.last().cloned();
+                pages.push(gids);
+                match page.next_cursor {
+                    Some(_) if by_gid => after = last,
+                    Some(cursor) => after = Some(cursor),
+                    None => return pages,
+                }
+            }
+        }
+        assert_eq!(
+            pages(&db, false, false).await,
+            [vec!["4", "2"], vec!["1", "3"], vec!["5"]]
+        );
+        assert_eq!(
+            pages(&db, true, false).await,
+            [vec!["5", "3"], vec!["1", "2"], vec!["4"]]
+        );
+        assert_eq!(
+            pages(&db, false, true).await,
+            [vec!["4", "2"], vec!["1", "3"], vec!["5"]]
+        );
+
+        let first = QueryBuilder::new().limit(2).page(&db).await.unwrap();
+        let cursor = first.next_cursor.unwrap();
+        assert_eq!(cursor, r#"["2025-01-01","2"]"#);
+        let whole = QueryBuilder::new().limit(5).page(&db).await.unwrap();
+        assert_eq!(whole.tasks.len(), 5);
+        assert_eq!(whole.next_cursor, None);
+
+        // A cursor still works once its task drops out of the results
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "UPDATE fact_tasks SET is_completed = 1 WHERE task_gid = '2'",
+                    [],
+                )
+            })
+            .await
+            .unwrap();
+        let rest = QueryBuilder::new()
+            .completed(false)
+            .after(&cursor)
+            .limit(2)
+            .tasks(&db)
+            .await
+            .unwrap();
+        let gids: Vec<&str> = rest.iter().map(|t| t.task_gid.as_str()).collect();
+        assert_eq!(gids, ["1", "3"]);
+        let rest = QueryBuilder::new()
+            .completed(false)
+            .after("2")
+            .tasks(&db)
+            .await
+            .unwrap();
+        assert!(rest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_csv_and_jsonl() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, modified_at, cached_at)
+                     VALUES ('1', 'hello', '2025-01-01', '2025-01-01', '2025-01-03', datetime('now')),
+                            ('2', 'hello,world', '2025-01-01', '2025-01-01', '2025-01-02', datetime('now')),
+                            ('3', 'say \"hi\"', '2025-01-01', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('4', 'two' || char(13) || 'lines', '2025-01-01', '2025-01-01', NULL, datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let builder = || QueryBuilder::new().order_by("t.task_gid");
+        let csv = builder().to_csv(&db).await.unwrap();
+        assert!(csv.starts_with("task_gid,name,assignee_gid,"));
+        assert!(csv.contains("\n1,hello,,"));
+        assert!(csv.contains("\n3,\"say \"\"hi\"\"\",,"));
+        let names: Vec<String> = csv::Reader::from_reader(csv.as_bytes())
+            .records()
+            .map(|r| r.unwrap()[1].to_string())
+            .collect();
+        assert_eq!(names, ["hello", "hello,world", "say \"hi\"", "two\rlines"]);
+
+        let path = std::env::temp_dir().join(format!("asanadw-export-{}.csv", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let written = builder().write_csv(&db, file).await.unwrap();
+        assert_eq!(written, 4);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+
+        let file = std::fs::File::create(&path).unwrap();
+        let written = builder().write_jsonl(&db, file).await.unwrap();
+        let jsonl = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written, 4);
+        let rows: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[2]["name"], "say \"hi\"");
     }
 }
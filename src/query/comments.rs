@@ -0,0 +1,373 @@
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::query::builder::{csv_error, io_error};
+use crate::storage::Database;
+
+/// Which kind of text a [`CommentQueryBuilder`] searches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommentSource {
+    /// Comments on tasks (system stories are skipped).
+    #[default]
+    Comments,
+    /// Project and portfolio status updates.
+    StatusUpdates,
+}
+
+/// A row from a comment or status update query.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentRow {
+    /// Comment or status update GID.
+    pub gid: String,
+    /// The task commented on, or the project or portfolio updated.
+    pub parent_gid: String,
+    pub parent_name: Option<String>,
+    /// A project the commented task is in; status updates leave this unset.
+    pub project_name: Option<String>,
+    pub author_gid: Option<String>,
+    pub author_name: Option<String>,
+    /// Status updates only.
+    pub title: Option<String>,
+    /// Status updates only, e.g. `on_track`.
+    pub status_type: Option<String>,
+    pub text: Option<String>,
+    /// Characters in `text`.
+    pub text_length: i64,
+    pub created_at: String,
+}
+
+/// Builder for comment and status update queries, the counterpart of
+/// [`QueryBuilder`](crate::QueryBuilder) for text rather than tasks.
+#[derive(Debug, Clone, Default)]
+pub struct CommentQueryBuilder {
+    source: CommentSource,
+    project_gid: Option<String>,
+    author_gid: Option<String>,
+    created_after: Option<String>,
+    created_before: Option<String>,
+    min_length: Option<u32>,
+    max_length: Option<u32>,
+    limit: Option<u32>,
+}
+
+impl CommentQueryBuilder {
+    /// Query comments on tasks.
+    pub fn comments() -> Self {
+        Self::default()
+    }
+
+    /// Query project and portfolio status updates.
+    pub fn status_updates() -> Self {
+        Self {
+            source: CommentSource::StatusUpdates,
+            ..Self::default()
+        }
+    }
+
+    /// Comments on the project's tasks, or the project's status updates.
+    pub fn project(mut self, gid: &str) -> Self {
+        self.project_gid = Some(gid.to_string());
+        self
+    }
+
+    pub fn author(mut self, gid: &str) -> Self {
+        self.author_gid = Some(gid.to_string());
+        self
+    }
+
+    pub fn created_after(mut self, date: &str) -> Self {
+        self.created_after = Some(date.to_string());
+        self
+    }
+
+    pub fn created_before(mut self, date: &str) -> Self {
+        self.created_before = Some(date.to_string());
+        self
+    }
+
+    /// Only text at least this many characters long.
+    pub fn min_length(mut self, chars: u32) -> Self {
+        self.min_length = Some(chars);
+        self
+    }
+
+    /// Only text at most this many characters long.
+    pub fn max_length(mut self, chars: u32) -> Self {
+        self.max_length = Some(chars);
+        self
+    }
+
+    pub fn limit(mut self, n: u32) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Build and execute the query, newest first.
+    pub async fn rows(self, db: &Database) -> Result<Vec<CommentRow>> {
+        db.reader()
+            .call(move |conn| {
+                let (sql, params) = self.build_sql();
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(param_refs.as_slice(), |row| {
+                    Ok(CommentRow {
+                        gid: row.get(0)?,
+                        parent_gid: row.get(1)?,
+                        parent_name: row.get(2)?,
+                        project_name: row.get(3)?,
+                        author_gid: row.get(4)?,
+                        author_name: row.get(5)?,
+                        title: row.get(6)?,
+                        status_type: row.get(7)?,
+                        text: row.get(8)?,
+                        text_length: row.get(9)?,
+                        created_at: row.get(10)?,
+                    })
+                })?;
+                let result: std::result::Result<Vec<CommentRow>, _> = rows.collect();
+                result
+            })
+            .await
+            .map_err(|e| crate::error::Error::Database(e.to_string()))
+    }
+
+    /// Build and execute the query, returning a count of matching rows.
+    pub async fn count(self, db: &Database) -> Result<u64> {
+        db.reader()
+            .call(move |conn| {
+                let (inner_sql, params) = self.build_sql();
+                let sql = format!("SELECT COUNT(*) FROM ({inner_sql})");
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let count: i64 = conn.query_row(&sql, param_refs.as_slice(), |row| row.get(0))?;
+                Ok::<u64, rusqlite::Error>(count as u64)
+            })
+            .await
+            .map_err(|e| crate::error::Error::Database(e.to_string()))
+    }
+
+    /// Build and execute the query, returning results as JSON.
+    pub async fn to_json(self, db: &Database) -> Result<String> {
+        let rows = self.rows(db).await?;
+        serde_json::to_string_pretty(&rows).map_err(|e| crate::error::Error::Other(e.to_string()))
+    }
+
+    /// Build and execute the query, returning results as CSV.
+    pub async fn to_csv(self, db: &Database) -> Result<String> {
+        let rows = self.rows(db).await?;
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .write_record([
+                "gid",
+                "parent_gid",
+                "parent_name",
+                "project_name",
+                "author_gid",
+                "author_name",
+                "title",
+                "status_type",
+                "text",
+                "text_length",
+                "created_at",
+            ])
+            .map_err(csv_error)?;
+        let text = |s: &Option<String>| s.clone().unwrap_or_default();
+        for row in &rows {
+            writer
+                .write_record([
+                    row.gid.clone(),
+                    row.parent_gid.clone(),
+                    text(&row.parent_name),
+                    text(&row.project_name),
+                    text(&row.author_gid),
+                    text(&row.author_name),
+                    text(&row.title),
+                    text(&row.status_type),
+                    text(&row.text),
+                    row.text_length.to_string(),
+                    row.created_at.clone(),
+                ])
+                .map_err(csv_error)?;
+        }
+        let out = writer.into_inner().map_err(|e| io_error(e.into_error()))?;
+        String::from_utf8(out).map_err(|e| crate::error::Error::Other(e.to_string()))
+    }
+
+    fn build_sql(&self) -> (String, Vec<Box<dyn rusqlite::types::ToSql>>) {
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+        let mut wheres = Vec::new();
+        let mut param_idx = 1;
+
+        let select = match self.source {
+            CommentSource::Comments => {
+                wheres.push("c.story_type = 'comment'".to_string());
+                "SELECT c.comment_gid, c.task_gid, t.name,
+                    (SELECT p.name FROM bridge_task_projects btp
+                     JOIN dim_projects p ON p.project_gid = btp.project_gid
+                     WHERE btp.task_gid = c.task_gid ORDER BY p.name LIMIT 1),
+                    c.author_gid, u.name, NULL, NULL, c.text,
+                    LENGTH(COALESCE(c.text, '')), c.created_at
+                FROM fact_comments c
+                LEFT JOIN fact_tasks t ON t.task_gid = c.task_gid
+                LEFT JOIN dim_users u ON u.user_gid = c.author_gid"
+            }
+            CommentSource::StatusUpdates => {
+                "SELECT c.status_gid, c.parent_gid, COALESCE(p.name, pf.name),
+                    NULL, c.author_gid, u.name, c.title, c.status_type, c.text,
+                    LENGTH(COALESCE(c.text, '')), c.created_at
+                FROM fact_status_updates c
+                LEFT JOIN dim_projects p ON p.project_gid = c.parent_gid
+                LEFT JOIN dim_portfolios pf ON pf.portfolio_gid = c.parent_gid
+                LEFT JOIN dim_users u ON u.user_gid = c.author_gid"
+            }
+        };
+
+        // Project filter
+        if let Some(ref gid) = self.project_gid {
+            wheres.push(match self.source {
+                CommentSource::Comments => format!(
+                    "EXISTS (SELECT 1 FROM bridge_task_projects btp
+                     WHERE btp.task_gid = c.task_gid AND btp.project_gid = ?{param_idx})"
+                ),
+                CommentSource::StatusUpdates => format!("c.parent_gid = ?{param_idx}"),
+            });
+            params.push(Box::new(gid.clone()));
+            param_idx += 1;
+        }
+
+        // Author filter
+        if let Some(ref gid) = self.author_gid {
+            wheres.push(format!("c.author_gid = ?{param_idx}"));
+            params.push(Box::new(gid.clone()));
+            param_idx += 1;
+        }
+
+        // Date range filters
+        if let Some(ref date) = self.created_after {
+            wheres.push(format!("c.created_date_key >= ?{param_idx}"));
+            params.push(Box::new(date.clone()));
+            param_idx += 1;
+        }
+        if let Some(ref date) = self.created_before {
+            wheres.push(format!("c.created_date_key <= ?{param_idx}"));
+            params.push(Box::new(date.clone()));
+            param_idx += 1;
+        }
+
+        // Text length filters
+        if let Some(n) = self.min_length {
+            wheres.push(format!("LENGTH(COALESCE(c.text, '')) >= ?{param_idx}"));
+            params.push(Box::new(n));
+            param_idx += 1;
+        }
+        if let Some(n) = self.max_length {
+            wheres.push(format!("LENGTH(COALESCE(c.text, '')) <= ?{param_idx}"));
+            params.push(Box::new(n));
+            param_idx += 1;
+        }
+
+        let mut sql = select.to_string();
+        if !wheres.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&wheres.join(" AND "));
+        }
+        sql.push_str(" ORDER BY c.created_at DESC");
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT ?{param_idx}"));
+            params.push(Box::new(limit));
+        }
+
+        (sql, params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_sql_with_filters() {
+        let (sql, params) = CommentQueryBuilder::comments()
+            .project("123")
+            .author("u1")
+            .min_length(20)
+            .limit(10)
+            .build_sql();
+        assert!(sql.contains("FROM fact_comments c"));
+        assert!(sql.contains("c.story_type = 'comment'"));
+        assert!(sql.contains("btp.project_gid = ?1"));
+        assert!(sql.contains("c.author_gid = ?2"));
+        assert!(sql.contains("LENGTH(COALESCE(c.text, '')) >= ?3"));
+        assert!(sql.contains("LIMIT ?4"));
+        assert_eq!(params.len(), 4);
+
+        let (sql, params) = CommentQueryBuilder::status_updates()
+            .project("123")
+            .created_after("2025-01-01")
+            .build_sql();
+        assert!(sql.contains("FROM fact_status_updates c"));
+        assert!(sql.contains("c.parent_gid = ?1"));
+        assert!(sql.contains("c.created_date_key >= ?2"));
+        assert_eq!(params.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_query_comments_and_status_updates() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Write docs', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t1', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'u1', 'ok', 'comment', '2025-01-02T10:00:00Z', '2025-01-02', datetime('now')),
+                            ('c2', 't1', 'u1', 'Here is the full plan, with caveats', 'comment', '2025-01-03T10:00:00Z', '2025-01-03', datetime('now')),
+                            ('s1', 't1', NULL, 'marked this task complete', 'system', '2025-01-04T10:00:00Z', '2025-01-04', datetime('now'));
+                     INSERT INTO fact_status_updates (status_gid, parent_gid, parent_type, author_gid, title, text, status_type, created_at, created_date_key, cached_at)
+                     VALUES ('su1', 'p1', 'project', 'u1', 'Week 1', 'On track, docs underway', 'on_track', '2025-01-05T10:00:00Z', '2025-01-05', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let rows = CommentQueryBuilder::comments()
+            .project("p1")
+            .rows(&db)
+            .await
+            .unwrap();
+        let gids: Vec<&str> = rows.iter().map(|r| r.gid.as_str()).collect();
+        assert_eq!(gids, ["c2", "c1"]);
+        assert_eq!(rows[0].parent_name.as_deref(), Some("Write docs"));
+        assert_eq!(rows[0].project_name.as_deref(), Some("Launch"));
+        assert_eq!(rows[0].author_name.as_deref(), Some("Alice"));
+
+        let long = CommentQueryBuilder::comments()
+            .min_length(10)
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(long, 1);
+
+        let updates = CommentQueryBuilder::status_updates()
+            .author("u1")
+            .rows(&db)
+            .await
+            .unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].parent_name.as_deref(), Some("Launch"));
+        assert_eq!(updates[0].status_type.as_deref(), Some("on_track"));
+
+        let csv = CommentQueryBuilder::comments()
+            .max_length(5)
+            .to_csv(&db)
+            .await
+            .unwrap();
+        assert_eq!(csv.lines().count(), 2);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::Database;
+
+/// Config key holding the project label mapping.
+pub const PROJECT_LABELS_KEY: &str = "project_labels";
+
+/// A user-defined project grouping, matched by Asana project color or GID.
+///
+/// Configured as `label=item,item;label=item`, where each item is either a
+/// project color (e.g. `dark-green`) or a numeric project GID:
+///
+/// ```text
+/// client work=dark-green,light-green;internal=dark-blue,1204567890
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectLabel {
+    pub name: String,
+    pub colors: Vec<String>,
+    pub project_gids: Vec<String>,
+}
+
+/// Parse a `project_labels` config value.
+pub fn parse_project_labels(spec: &str) -> Result<Vec<ProjectLabel>> {
+    let mut labels = Vec::new();
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, items) = entry.split_once('=').ok_or_else(|| {
+            Error::Config(format!(
+                "invalid project_labels entry '{entry}': expected label=color,gid,..."
+            ))
+        })?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::Config(format!(
+                "invalid project_labels entry '{entry}': empty label"
+            )));
+        }
+        let mut label = ProjectLabel {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        for item in items.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            if item.chars().all(|c| c.is_ascii_digit()) {
+                label.project_gids.push(item.to_string());
+            } else {
+                label.colors.push(item.to_lowercase());
+            }
+        }
+        labels.push(label);
+    }
+    Ok(labels)
+}
+
+/// Look up a configured label by name (case-insensitive).
+pub async fn load_project_label(db: &Database, name: &str) -> Result<ProjectLabel> {
+    let spec = db
+        .reader()
+        .call(|conn| repository::get_config(conn, PROJECT_LABELS_KEY))
+        .await?
+        .unwrap_or_default();
+    let labels = parse_project_labels(&spec)?;
+    if let Some(label) = labels.iter().find(|l| l.name.eq_ignore_ascii_case(name)) {
+        return Ok(label.clone());
+    }
+    let known: Vec<&str> = labels.iter().map(|l| l.name.as_str()).collect();
+    Err(Error::Config(if known.is_empty() {
+        format!(
+            "unknown project label '{name}'. Define labels with: asanadw config set project_labels \"label=color,gid;...\""
+        )
+    } else {
+        format!(
+            "unknown project label '{name}'. Configured labels: {}",
+            known.join(", ")
+        )
+    }))
+}
+
+/// GIDs of synced projects matching a label's colors or explicit GIDs.
+/// Template projects are left out.
+pub fn label_project_gids(
+    conn: &rusqlite::Connection,
+    label: &ProjectLabel,
+) -> std::result::Result<Vec<String>, rusqlite::Error> {
+    let (clause, values) = label_sql(label, "project_gid", "color", 1);
+    let sql = format!(
+        "SELECT project_gid FROM dim_projects
+         WHERE {clause} AND COALESCE(is_template, 0) = 0
+         ORDER BY project_gid"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    for (i, v) in values.iter().enumerate() {
+        stmt.raw_bind_parameter(i + 1, v)?;
+    }
+    let mut rows = stmt.raw_query();
+    let mut gids = Vec::new();
+    while let Some(row) = rows.next()? {
+        gids.push(row.get(0)?);
+    }
+    Ok(gids)
+}
+
+/// SQL predicate matching projects in a label, with numbered placeholders
+/// starting at `?{first_param}`, and its bind values. A label with no items
+/// matches nothing.
+pub(crate) fn label_sql(
+    label: &ProjectLabel,
+    gid_column: &str,
+    color_column: &str,
+    first_param: usize,
+) -> (String, Vec<String>) {
+    let mut parts = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+    for (column, items) in [
+        (format!("LOWER({color_column})"), &label.colors),
+        (gid_column.to_string(), &label.project_gids),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        let mut placeholders = Vec::new();
+        for item in items {
+            placeholders.push(format!("?{}", first_param + values.len()));
+            values.push(item.clone());
+        }
+        parts.push(format!("{column} IN ({})", placeholders.join(",")));
+    }
+    if parts.is_empty() {
+        ("0".to_string(), values)
+    } else {
+        (format!("({})", parts.join(" OR ")), values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_project_labels() {
+        let labels =
+            parse_project_labels("client work=dark-green, Light-Green ;internal=dark-blue,123;")
+                .unwrap();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0].name, "client work");
+        assert_eq!(labels[0].colors, vec!["dark-green", "light-green"]);
+        assert!(labels[0].project_gids.is_empty());
+        assert_eq!(labels[1].colors, vec!["dark-blue"]);
+        assert_eq!(labels[1].project_gids, vec!["123"]);
+
+        assert!(parse_project_labels("").unwrap().is_empty());
+        assert!(parse_project_labels("no-equals-sign").is_err());
+        assert!(parse_project_labels("=dark-blue").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_label_project_gids() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, color, cached_at)
+                     VALUES ('p1', 'Client A', 'w1', 'dark-green', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, color, cached_at)
+                     VALUES ('p2', 'Ops', 'w1', 'dark-blue', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, color, cached_at)
+                     VALUES ('3', 'Special', 'w1', NULL, datetime('now'));
+                     INSERT INTO app_config (key, value, updated_at) VALUES ('project_labels', 'client=dark-green,3', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let label = load_project_label(&db, "CLIENT").await.unwrap();
+        let gids = db
+            .reader()
+            .call(move |conn| label_project_gids(conn, &label))
+            .await
+            .unwrap();
+        assert_eq!(gids, vec!["3", "p1"]);
+
+        assert!(load_project_label(&db, "internal").await.is_err());
+    }
+}
@@ -1,2 +1,7 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod builder;
+pub mod comments;
+pub mod labels;
 pub mod period;
+pub mod saved;
@@ -1,6 +1,6 @@
 use std::sync::LazyLock;
 
-use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
 use regex::Regex;
 
 use crate::date_util::{last_day_of_month, quarter_of};
@@ -11,6 +11,11 @@ static RE_QUARTER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{4})-Q([1
 static RE_WEEK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{4})-W(\d{1,2})$").unwrap());
 static RE_MONTH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{4})-(\d{2})$").unwrap());
 
+/// Examples of the formats [`Period::parse`] accepts, for `--period` help
+/// and tool schemas.
+pub const PERIOD_EXAMPLES: &str =
+    "qtd, ytd, mtd, wtd, 2024, 2024-H1, 2024-Q1, 2024-03, 2024-W10, 30d, or 24h";
+
 /// A time period for metrics and queries.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Period {
@@ -20,6 +25,8 @@ pub enum Period {
     Month(i32, u8),
     Week(i32, u8),
     Rolling(u32, NaiveDate),
+    /// The last N hours up to a UTC instant.
+    RollingHours(u32, NaiveDateTime),
     YearToDate(i32),
     HalfToDate(i32, u8),
     QuarterToDate(i32, u8),
@@ -37,6 +44,7 @@ impl Period {
     /// - `2025-01` — month
     /// - `2025-W05` — ISO week
     /// - `30d` — rolling last N days
+    /// - `24h` — rolling last N hours, up to now
     /// - `ytd` — year to date (current year)
     /// - `htd` — half to date (current half)
     /// - `qtd` — quarter to date (current quarter)
@@ -76,6 +84,13 @@ impl Period {
             }
         }
 
+        // Rolling hours: "24h", "72h", etc.
+        if s.ends_with('h') || s.ends_with('H') {
+            if let Ok(n) = s[..s.len() - 1].parse::<u32>() {
+                return Ok(Period::RollingHours(n, chrono::Utc::now().naive_utc()));
+            }
+        }
+
         // Year-qualified to-date: "2025-ytd"
         if let Some(rest) = s.strip_suffix("-ytd") {
             let year: i32 = rest
@@ -112,7 +127,8 @@ impl Period {
         if let Some(caps) = RE_WEEK.captures(s) {
             let year: i32 = caps[1].parse().unwrap();
             let week: u8 = caps[2].parse().unwrap();
-            if (1..=53).contains(&week) {
+            // Only some years have a week 53
+            if NaiveDate::from_isoywd_opt(year, week as u32, Weekday::Mon).is_some() {
                 return Ok(Period::Week(year, week));
             }
         }
@@ -138,6 +154,7 @@ impl Period {
             Period::Month(y, m) => format!("{y}-{m:02}"),
             Period::Week(y, w) => format!("{y}-W{w:02}"),
             Period::Rolling(n, _) => format!("{n}d"),
+            Period::RollingHours(n, _) => format!("{n}h"),
             Period::YearToDate(y) => format!("{y}-ytd"),
             Period::HalfToDate(y, h) => format!("{y}-H{h}-td"),
             Period::QuarterToDate(y, q) => format!("{y}-Q{q}-td"),
@@ -147,6 +164,7 @@ impl Period {
     }
 
     /// Get the date range (inclusive start, inclusive end) for this period.
+    /// For rolling hours, the UTC dates the hours fall on.
     pub fn date_range(&self) -> (NaiveDate, NaiveDate) {
         let today = chrono::Local::now().date_naive();
         match self {
@@ -184,6 +202,9 @@ impl Period {
                 (start, start + Duration::days(6))
             }
             Period::Rolling(n, as_of) => (*as_of - Duration::days(*n as i64 - 1), *as_of),
+            Period::RollingHours(n, until) => {
+                ((*until - Duration::hours(*n as i64)).date(), until.date())
+            }
             Period::YearToDate(y) => (NaiveDate::from_ymd_opt(*y, 1, 1).unwrap(), today),
             Period::HalfToDate(y, h) => {
                 let start = if *h == 1 {
@@ -233,14 +254,13 @@ impl Period {
                 }
             }
             Period::Week(y, w) => {
-                if *w == 1 {
-                    // Last week of previous year — approximate
-                    Period::Week(y - 1, 52)
-                } else {
-                    Period::Week(*y, w - 1)
-                }
+                let (y, w) = previous_iso_week(*y, *w);
+                Period::Week(y, w)
             }
             Period::Rolling(n, as_of) => Period::Rolling(*n, *as_of - Duration::days(*n as i64)),
+            Period::RollingHours(n, until) => {
+                Period::RollingHours(*n, *until - Duration::hours(*n as i64))
+            }
             Period::YearToDate(y) => Period::YearToDate(y - 1),
             Period::HalfToDate(y, h) => {
                 if *h == 1 {
@@ -264,11 +284,8 @@ impl Period {
                 }
             }
             Period::WeekToDate(y, w) => {
-                if *w == 1 {
-                    Period::WeekToDate(y - 1, 52)
-                } else {
-                    Period::WeekToDate(*y, w - 1)
-                }
+                let (y, w) = previous_iso_week(*y, *w);
+                Period::WeekToDate(y, w)
             }
         }
     }
@@ -277,6 +294,9 @@ impl Period {
     /// range in the prior period. E.g., if this is Q1 2026 and as_of is
     /// Feb 7, returns the prior Q1 clamped to the same day offset.
     pub fn prior_period_to_date(&self, as_of: NaiveDate) -> Self {
+        if let Period::RollingHours(..) = self {
+            return self.previous();
+        }
         let (start, _end) = self.date_range();
         let offset = (as_of - start).num_days();
 
@@ -290,6 +310,25 @@ impl Period {
         Period::Rolling(days as u32, clamped)
     }
 
+    /// Bounds for comparing stored UTC timestamps such as `completed_at`:
+    /// `start <= ts AND ts < end`. Calendar periods give whole days as
+    /// `YYYY-MM-DD`, `end` being the day after the last, so they match the
+    /// same rows as the date keys; rolling hours give exact instants.
+    pub fn timestamp_bounds(&self) -> (String, String) {
+        if let Period::RollingHours(n, until) = self {
+            let start = *until - Duration::hours(*n as i64);
+            return (
+                start.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                until.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            );
+        }
+        let (start, end) = self.date_range();
+        (
+            start.format("%Y-%m-%d").to_string(),
+            (end + Duration::days(1)).format("%Y-%m-%d").to_string(),
+        )
+    }
+
     /// Returns true if this period contains today.
     pub fn is_current(&self) -> bool {
         let today = chrono::Local::now().date_naive();
@@ -298,6 +337,19 @@ impl Period {
     }
 }
 
+/// The ISO week before week `week` of `year`. Week 1's predecessor is the
+/// last week of the previous ISO year, which is week 53 in long years.
+fn previous_iso_week(year: i32, week: u8) -> (i32, u8) {
+    if week > 1 {
+        return (year, week - 1);
+    }
+    // Dec 28 always falls in the last ISO week of its year
+    let last = NaiveDate::from_ymd_opt(year - 1, 12, 28)
+        .unwrap()
+        .iso_week();
+    (last.year(), last.week() as u8)
+}
+
 impl std::fmt::Display for Period {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.to_key())
@@ -307,6 +359,15 @@ impl std::fmt::Display for Period {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_period_examples_parse() {
+        for example in PERIOD_EXAMPLES.split(", ") {
+            let example = example.trim_start_matches("or ");
+            assert!(Period::parse(example).is_ok(), "{example} should parse");
+        }
+    }
 
     #[test]
     fn test_parse_year() {
@@ -434,6 +495,60 @@ mod tests {
         assert_eq!(Period::Month(2025, 6).previous(), Period::Month(2025, 5));
     }
 
+    #[test]
+    fn test_previous_week_across_years() {
+        // 2020 and 2026 have 53 ISO weeks, 2025 has 52
+        assert_eq!(Period::Week(2021, 1).previous(), Period::Week(2020, 53));
+        assert_eq!(Period::Week(2026, 1).previous(), Period::Week(2025, 52));
+        assert_eq!(Period::Week(2027, 1).previous(), Period::Week(2026, 53));
+        assert_eq!(
+            Period::WeekToDate(2021, 1).previous(),
+            Period::WeekToDate(2020, 53)
+        );
+        assert_eq!(Period::parse("2020-W53").unwrap(), Period::Week(2020, 53));
+        assert!(Period::parse("2025-W53").is_err());
+        assert!(Period::parse("2025-W00").is_err());
+    }
+
+    #[test]
+    fn test_rolling_hours() {
+        match Period::parse("72h").unwrap() {
+            Period::RollingHours(72, _) => {}
+            p => panic!("expected RollingHours(72, _), got {p:?}"),
+        }
+
+        let until = NaiveDate::from_ymd_opt(2025, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let period = Period::RollingHours(24, until);
+        assert_eq!(period.to_key(), "24h");
+        assert_eq!(
+            period.timestamp_bounds(),
+            (
+                "2025-03-01T09:30:00Z".to_string(),
+                "2025-03-02T09:30:00Z".to_string()
+            )
+        );
+        assert_eq!(
+            period.date_range(),
+            (
+                NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 3, 2).unwrap()
+            )
+        );
+        assert_eq!(
+            period.previous().timestamp_bounds().1,
+            "2025-03-01T09:30:00Z"
+        );
+
+        // Calendar periods cover whole days, the end bound being exclusive
+        assert_eq!(
+            Period::Month(2025, 2).timestamp_bounds(),
+            ("2025-02-01".to_string(), "2025-03-01".to_string())
+        );
+    }
+
     #[test]
     fn test_prior_period_to_date() {
         // Q1 2026, as of Feb 7 = day 37 of the quarter
@@ -448,6 +563,67 @@ mod tests {
         assert_eq!(pe, NaiveDate::from_ymd_opt(2025, 11, 7).unwrap());
     }
 
+    /// Any period with a fixed date range, from 1900 through 2200.
+    fn fixed_period() -> impl Strategy<Value = Period> {
+        let year = 1900..2200i32;
+        prop_oneof![
+            year.clone().prop_map(Period::Year),
+            (year.clone(), 1..=2u8).prop_map(|(y, h)| Period::Half(y, h)),
+            (year.clone(), 1..=4u8).prop_map(|(y, q)| Period::Quarter(y, q)),
+            (year.clone(), 1..=12u8).prop_map(|(y, m)| Period::Month(y, m)),
+            // Any date's ISO week, so week 53 shows up in the years that have one
+            (year.clone(), 0..366u32).prop_map(|(y, d)| {
+                let iw = (NaiveDate::from_ymd_opt(y, 1, 1).unwrap() + Duration::days(d as i64))
+                    .iso_week();
+                Period::Week(iw.year(), iw.week() as u8)
+            }),
+            (1..400u32, year, 0..366u32).prop_map(|(n, y, d)| {
+                Period::Rolling(
+                    n,
+                    NaiveDate::from_ymd_opt(y, 1, 1).unwrap() + Duration::days(d as i64),
+                )
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_previous_ends_the_day_before(period in fixed_period()) {
+            let (start, end) = period.date_range();
+            let (prev_start, prev_end) = period.previous().date_range();
+            prop_assert!(start <= end);
+            prop_assert!(prev_start <= prev_end);
+            prop_assert_eq!(prev_end + Duration::days(1), start);
+        }
+
+        #[test]
+        fn prop_previous_keys_round_trip(period in fixed_period()) {
+            let prev = period.previous();
+            if !matches!(prev, Period::Rolling(..)) {
+                prop_assert_eq!(Period::parse(&prev.to_key()).unwrap(), prev);
+            }
+        }
+
+        #[test]
+        fn prop_prior_period_to_date_within_previous(
+            period in fixed_period(),
+            offset in 0..400i64,
+        ) {
+            let (start, end) = period.date_range();
+            let as_of = (start + Duration::days(offset)).min(end);
+            let (prev_start, prev_end) = period.previous().date_range();
+            let (prior_start, prior_end) = period.prior_period_to_date(as_of).date_range();
+            prop_assert_eq!(prior_start, prev_start);
+            prop_assert!(prior_end <= prev_end);
+            // The same number of days in, unless the previous period is shorter
+            let days_in = (as_of - start).num_days();
+            prop_assert_eq!(
+                (prior_end - prior_start).num_days(),
+                days_in.min((prev_end - prev_start).num_days())
+            );
+        }
+    }
+
     #[test]
     fn test_is_current() {
         let today = chrono::Local::now().date_naive();
@@ -0,0 +1,417 @@
+//! Named task queries, e.g. `my-overdue-p1s`, stored in `saved_queries`
+//! and run by name from `asanadw query run` or the MCP `saved_query` tool.
+//!
+//! A saved query keeps its filters as given, not resolved: aliases, emails,
+//! and `mine` are looked up each time it runs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::query::builder::{parse_field_filter, GroupBy, QueryBuilder};
+use crate::storage::repository::{self, SavedQueryRow};
+use crate::storage::Database;
+
+/// Tasks returned when neither the query nor the caller sets a limit.
+pub const DEFAULT_LIMIT: u32 = 100;
+
+/// The task filters of `asanadw query`, as stored for a saved query.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryFilters {
+    /// Project GID, URL, or alias.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub portfolio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_label: Option<String>,
+    /// `FIELD=VALUE`: the custom field holds or once held the value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_was: Option<String>,
+    /// Custom field filters such as `Priority=P1` or `Points>=3`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Name or notes match, in search syntax.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>,
+    /// With `group_by`, a number custom field to sum per group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sum: Option<String>,
+    /// User GID, email, or alias.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// Tasks assigned to the `user_gid` config, whoever that is when run.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub mine: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<bool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub overdue: bool,
+    /// `default_task`, `milestone`, or `approval`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_before: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub include_templates: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub include_deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl QueryFilters {
+    /// Check the filters that are parsed rather than passed through, so a
+    /// bad one is rejected when a query is saved instead of when it runs.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(g) = &self.group_by {
+            parse_group_by(g)?;
+        }
+        if self.sum.is_some() && self.group_by.is_none() {
+            return Err(Error::Other("sum needs group_by".to_string()));
+        }
+        if let Some(fv) = &self.field_was {
+            split_field_was(fv)?;
+        }
+        for expr in &self.fields {
+            parse_field_filter(expr)?;
+        }
+        Ok(())
+    }
+
+    /// A query for these filters, with aliases, users, and `mine` resolved
+    /// against the database. A text match sorts by relevance, anything else
+    /// by most recently modified.
+    pub async fn to_builder(&self, db: &Database) -> Result<QueryBuilder> {
+        let mut builder = QueryBuilder::new()
+            .include_templates(self.include_templates)
+            .include_deleted(self.include_deleted)
+            .limit(self.limit.unwrap_or(DEFAULT_LIMIT));
+        builder = match &self.text {
+            Some(text) => builder.matching(text),
+            None => builder.order_by("t.modified_at").descending(),
+        };
+
+        if let Some(g) = &self.group_by {
+            builder = builder.group_by(parse_group_by(g)?);
+        }
+        if let Some(field) = &self.sum {
+            builder = builder.sum_field(field);
+        }
+        if let Some(p) = &self.project {
+            builder = builder.project(&crate::alias::resolve(db, p).await?);
+        }
+        if let Some(p) = &self.portfolio {
+            builder = builder.portfolio(&crate::alias::resolve(db, p).await?);
+        }
+        if let Some(t) = &self.team {
+            builder = builder.team(&crate::alias::resolve(db, t).await?);
+        }
+        if let Some(c) = &self.project_color {
+            builder = builder.project_color(c);
+        }
+        if let Some(l) = &self.project_label {
+            builder = builder.project_label(l);
+        }
+        if let Some(fv) = &self.field_was {
+            let (field, value) = split_field_was(fv)?;
+            builder = builder.field_was(field, value);
+        }
+        for expr in &self.fields {
+            let (field, op, value) = parse_field_filter(expr)?;
+            builder = builder.custom_field(&field, op, &value);
+        }
+        if self.mine {
+            let gid = db
+                .reader()
+                .call(|conn| repository::get_config(conn, "user_gid"))
+                .await?
+                .ok_or_else(|| {
+                    Error::Config("User identity not set. Run 'asanadw sync all' first.".into())
+                })?;
+            builder = builder.assignee(&gid);
+        } else if let Some(a) = &self.assignee {
+            builder = builder.assignee(&resolve_user(db, a).await?);
+        }
+        if let Some(s) = &self.subtype {
+            builder = builder.subtype(s);
+        }
+        if let Some(c) = self.completed {
+            builder = builder.completed(c);
+        }
+        if self.overdue {
+            builder = builder.overdue(true);
+        }
+        if let Some(d) = &self.created_after {
+            builder = builder.created_after(d);
+        }
+        if let Some(d) = &self.created_before {
+            builder = builder.created_before(d);
+        }
+        if let Some(d) = &self.due_after {
+            builder = builder.due_after(d);
+        }
+        if let Some(d) = &self.due_before {
+            builder = builder.due_before(d);
+        }
+        Ok(builder)
+    }
+}
+
+fn parse_group_by(name: &str) -> Result<GroupBy> {
+    GroupBy::parse(name).ok_or_else(|| {
+        Error::Other(format!(
+            "Unknown group: {name}. Use: assignee, project, section, tag, month"
+        ))
+    })
+}
+
+fn split_field_was(expr: &str) -> Result<(&str, &str)> {
+    let (field, value) = expr
+        .split_once('=')
+        .ok_or_else(|| Error::Other(format!("--field-was expects FIELD=VALUE, got '{expr}'")))?;
+    Ok((field.trim(), value.trim()))
+}
+
+/// Resolve a user alias, GID, name, or email; unknown users are used as
+/// given.
+async fn resolve_user(db: &Database, identifier: &str) -> Result<String> {
+    let id = identifier.to_string();
+    let resolved = db
+        .reader()
+        .call(move |conn| {
+            let alias = repository::get_alias(conn, &id)?;
+            match alias.as_deref().and_then(|k| k.strip_prefix("user:")) {
+                Some(gid) => Ok(Some(gid.to_string())),
+                None => repository::resolve_user_identifier(conn, &id),
+            }
+        })
+        .await?;
+    Ok(resolved.unwrap_or_else(|| {
+        log::warn!("Could not resolve user '{identifier}' in local database — using as-is");
+        identifier.to_string()
+    }))
+}
+
+/// A named query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub description: Option<String>,
+    pub filters: QueryFilters,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl TryFrom<SavedQueryRow> for SavedQuery {
+    type Error = Error;
+
+    fn try_from(row: SavedQueryRow) -> Result<Self> {
+        let filters = serde_json::from_str(&row.filters).map_err(|e| {
+            Error::Other(format!(
+                "saved query '{}' has invalid filters: {e}",
+                row.name
+            ))
+        })?;
+        Ok(Self {
+            name: row.name,
+            description: row.description,
+            filters,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+/// Check a saved query name: letters, digits, `-`, `_`, and `.`, so it
+/// reads as one word on the command line.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidIdentifier(format!(
+            "saved query name '{name}' may only contain letters, digits, '-', '_', and '.'"
+        )))
+    }
+}
+
+/// Save `filters` as `name`, replacing any query already saved under it.
+pub async fn save_query(
+    db: &Database,
+    name: &str,
+    description: Option<&str>,
+    filters: &QueryFilters,
+) -> Result<()> {
+    validate_name(name)?;
+    filters.validate()?;
+    let json = serde_json::to_string(filters).map_err(|e| Error::Other(e.to_string()))?;
+    let name = name.to_string();
+    let description = description.map(String::from);
+    db.writer()
+        .call(move |conn| repository::save_query(conn, &name, description.as_deref(), &json))
+        .await?;
+    Ok(())
+}
+
+/// The query saved as `name`, ignoring case.
+pub async fn get_query(db: &Database, name: &str) -> Result<Option<SavedQuery>> {
+    let name = name.to_string();
+    let row = db
+        .reader()
+        .call(move |conn| repository::get_saved_query(conn, &name))
+        .await?;
+    row.map(SavedQuery::try_from).transpose()
+}
+
+pub async fn list_queries(db: &Database) -> Result<Vec<SavedQuery>> {
+    let rows = db
+        .reader()
+        .call(|conn| repository::list_saved_queries(conn))
+        .await?;
+    rows.into_iter().map(SavedQuery::try_from).collect()
+}
+
+/// Returns false if no query is saved under `name`.
+pub async fn remove_query(db: &Database, name: &str) -> Result<bool> {
+    let name = name.to_string();
+    Ok(db
+        .writer()
+        .call(move |conn| repository::remove_saved_query(conn, &name))
+        .await?)
+}
+
+/// A query for the filters saved as `name`, ready to run.
+pub async fn query_builder(db: &Database, name: &str) -> Result<QueryBuilder> {
+    let saved = get_query(db, name)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("saved query '{name}'")))?;
+    saved.filters.to_builder(db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(validate_name("my-overdue_p1s.v2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("my p1s").is_err());
+        assert!(validate_name("a/b").is_err());
+
+        let filters = QueryFilters {
+            fields: vec!["Priority=P1".to_string()],
+            group_by: Some("assignee".to_string()),
+            ..Default::default()
+        };
+        assert!(filters.validate().is_ok());
+        let bad_group = QueryFilters {
+            group_by: Some("color".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_group.validate().is_err());
+        let bad_field = QueryFilters {
+            field_was: Some("Status".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_field.validate().is_err());
+
+        // Unset filters are left out of the stored JSON
+        let json = serde_json::to_string(&QueryFilters {
+            mine: true,
+            overdue: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"mine":true,"overdue":true}"#);
+        let parsed: QueryFilters = serde_json::from_str(&json).unwrap();
+        assert!(parsed.mine && parsed.completed.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_saved_queries() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, email, cached_at)
+                     VALUES ('u1', 'Ada', 'ada@example.com', datetime('now')),
+                            ('u2', 'Grace', 'grace@example.com', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, due_on, is_overdue, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Ship billing', 'u1', '2024-02-01', 1, '2024-01-01', '2024-01-01', datetime('now')),
+                            ('t2', 'Write docs', 'u1', '2099-01-01', 0, '2024-01-01', '2024-01-01', datetime('now')),
+                            ('t3', 'Review billing', 'u2', '2024-02-01', 1, '2024-01-01', '2024-01-01', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let overdue = QueryFilters {
+            mine: true,
+            overdue: true,
+            ..Default::default()
+        };
+        save_query(&db, "My-Overdue", Some("Overdue and mine"), &overdue)
+            .await
+            .unwrap();
+        // `mine` is resolved when the query runs
+        assert!(query_builder(&db, "my-overdue").await.is_err());
+        db.writer()
+            .call(|conn| repository::set_config(conn, "user_gid", "u1"))
+            .await
+            .unwrap();
+        let tasks = query_builder(&db, "my-overdue")
+            .await
+            .unwrap()
+            .tasks(&db)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].task_gid, "t1");
+
+        // Saving again replaces the filters under the original name
+        let grace = QueryFilters {
+            assignee: Some("grace@example.com".to_string()),
+            ..Default::default()
+        };
+        save_query(&db, "my-overdue", None, &grace).await.unwrap();
+        let saved = list_queries(&db).await.unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, "My-Overdue");
+        assert_eq!(saved[0].description, None);
+        assert_eq!(saved[0].filters, grace);
+        let tasks = query_builder(&db, "My-Overdue")
+            .await
+            .unwrap()
+            .tasks(&db)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].task_gid, "t3");
+
+        assert!(save_query(&db, "bad name", None, &grace).await.is_err());
+        assert!(matches!(
+            query_builder(&db, "missing").await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(remove_query(&db, "MY-OVERDUE").await.unwrap());
+        assert!(!remove_query(&db, "my-overdue").await.unwrap());
+    }
+}
@@ -0,0 +1,132 @@
+//! Line parsing for `asanadw repl`: slash-commands run the matching CLI
+//! subcommand, anything else is a question for the LLM.
+
+use crate::error::{Error, Result};
+
+/// Subcommands available as slash-commands.
+pub const COMMANDS: &[&str] = &["query", "metrics", "search"];
+
+/// One line entered at the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplLine {
+    Empty,
+    Quit,
+    Help,
+    /// `/history [n]`: the last `n` lines entered.
+    History(Option<usize>),
+    /// A subcommand from [`COMMANDS`] with its arguments, e.g.
+    /// `/query --overdue --limit 5`.
+    Command {
+        name: String,
+        args: Vec<String>,
+    },
+    /// Anything not starting with `/`.
+    Question(String),
+}
+
+/// Parse a line entered at the prompt.
+pub fn parse_line(line: &str) -> Result<ReplLine> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(ReplLine::Empty);
+    }
+    let Some(command) = line.strip_prefix('/') else {
+        return Ok(ReplLine::Question(line.to_string()));
+    };
+
+    let mut args = split_args(command)?;
+    if args.is_empty() {
+        return Err(Error::Other("Empty command".to_string()));
+    }
+    let name = args.remove(0);
+    match name.as_str() {
+        "quit" | "exit" => Ok(ReplLine::Quit),
+        "help" => Ok(ReplLine::Help),
+        "history" => match args.first() {
+            None => Ok(ReplLine::History(None)),
+            Some(n) => n
+                .parse()
+                .map(|n| ReplLine::History(Some(n)))
+                .map_err(|_| Error::Other(format!("/history takes a number of lines, not '{n}'"))),
+        },
+        name if COMMANDS.contains(&name) => Ok(ReplLine::Command {
+            name: name.to_string(),
+            args,
+        }),
+        other => Err(Error::Other(format!(
+            "Unknown command '/{other}'. Commands: {}, /history, /help, /quit",
+            COMMANDS
+                .iter()
+                .map(|c| format!("/{c}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Split a command line into arguments on whitespace, keeping single- or
+/// double-quoted runs together.
+fn split_args(line: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if let Some(q) = quote {
+        return Err(Error::Other(format!("Unclosed {q} in command")));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("   ").unwrap(), ReplLine::Empty);
+        assert_eq!(parse_line("/exit").unwrap(), ReplLine::Quit);
+        assert_eq!(
+            parse_line("/history 5").unwrap(),
+            ReplLine::History(Some(5))
+        );
+        assert_eq!(
+            parse_line("who closed the most tasks?").unwrap(),
+            ReplLine::Question("who closed the most tasks?".to_string())
+        );
+        assert_eq!(
+            parse_line(r#"/search "launch plan" --type task --project ''"#).unwrap(),
+            ReplLine::Command {
+                name: "search".to_string(),
+                args: ["launch plan", "--type", "task", "--project", ""]
+                    .map(String::from)
+                    .to_vec(),
+            }
+        );
+        assert!(parse_line("/sync all").is_err());
+        assert!(parse_line("/history lots").is_err());
+        assert!(parse_line("/query --name \"unclosed").is_err());
+    }
+}
@@ -0,0 +1,661 @@
+//! Period reports for a project, rendered as Markdown or a standalone HTML
+//! page with inline SVG charts, for pasting into a wiki.
+//!
+//! A report reads only local data: metrics, a daily burndown, top
+//! contributors, and the project's summary for the period if one has been
+//! generated (`asanadw summarize project`). It never calls the LLM.
+
+pub mod svg;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::metrics::{self, ProjectMetrics};
+use crate::overview::{self, CachedSummary, EntityMetadata};
+use crate::query::period::Period;
+use crate::storage::Database;
+
+/// Contributors listed in a report.
+pub const TOP_CONTRIBUTORS: usize = 5;
+
+/// Open and completed task counts at the end of one day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BurndownPoint {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    /// Tasks created by this day and not yet completed.
+    pub open: u64,
+    /// Tasks completed since the period started.
+    pub completed: u64,
+}
+
+/// Tasks created and completed during one week of the period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ThroughputPoint {
+    /// First day of the week, `YYYY-MM-DD`. Weeks start on the period's
+    /// first day.
+    pub week: String,
+    pub created: u64,
+    pub completed: u64,
+}
+
+/// One person's activity on the project during the period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Contributor {
+    pub user_gid: String,
+    pub name: Option<String>,
+    /// Tasks assigned to them that were completed.
+    pub tasks_completed: u64,
+    /// Comments, not counting bot stories.
+    pub comments: u64,
+}
+
+/// Everything in a project report.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectReport {
+    pub project_gid: String,
+    pub metadata: EntityMetadata,
+    pub period_key: String,
+    /// First day of the period, `YYYY-MM-DD`.
+    pub start: String,
+    /// Last day of the period, `YYYY-MM-DD`.
+    pub end: String,
+    pub metrics: ProjectMetrics,
+    /// One point per day, through today for a period still in progress.
+    pub burndown: Vec<BurndownPoint>,
+    /// One point per week, over the same days as the burndown; the last
+    /// week may be shorter.
+    pub throughput: Vec<ThroughputPoint>,
+    /// Most tasks completed first, then most comments; at most
+    /// [`TOP_CONTRIBUTORS`].
+    pub contributors: Vec<Contributor>,
+    /// The newest stored summary of this exact period.
+    pub summary: Option<CachedSummary>,
+}
+
+/// Build the report for project `project_gid` over `period`. Fails with
+/// [`Error::NotFound`] when the project isn't stored.
+pub async fn project_report(
+    db: &Database,
+    project_gid: &str,
+    period: &Period,
+) -> Result<ProjectReport> {
+    let (start, end) = period.date_range();
+    // A period in progress burns down through today, not its last day
+    let last_day = end.min(chrono::Local::now().date_naive());
+    let start = start.format("%Y-%m-%d").to_string();
+    let end = end.format("%Y-%m-%d").to_string();
+    let last_day = last_day.format("%Y-%m-%d").to_string();
+    let (from, until) = period.timestamp_bounds();
+    let period_key = period.to_key();
+
+    let (metadata, burndown, throughput, contributors, summary) = db
+        .reader()
+        .call({
+            let project_gid = project_gid.to_string();
+            let start = start.clone();
+            let period_key = period_key.clone();
+            move |conn| {
+                let metadata = overview::load_metadata(conn, "project", &project_gid)?;
+                let burndown = load_burndown(conn, &project_gid, &start, &last_day)?;
+                let throughput = load_throughput(conn, &project_gid, &start, &last_day)?;
+                let contributors = load_contributors(conn, &project_gid, &from, &until)?;
+                let summary = overview::load_summary(conn, "project", &project_gid, &period_key)?
+                    .filter(|s| s.period_key == period_key);
+                Ok::<_, rusqlite::Error>((metadata, burndown, throughput, contributors, summary))
+            }
+        })
+        .await?;
+    let metadata = metadata.ok_or_else(|| Error::NotFound(format!("project:{project_gid}")))?;
+    let metrics = metrics::compute_project_metrics(db, project_gid, period).await?;
+
+    Ok(ProjectReport {
+        project_gid: project_gid.to_string(),
+        metadata,
+        period_key,
+        start,
+        end,
+        metrics,
+        burndown,
+        throughput,
+        contributors,
+        summary,
+    })
+}
+
+fn load_burndown(
+    conn: &Connection,
+    project_gid: &str,
+    start: &str,
+    last_day: &str,
+) -> std::result::Result<Vec<BurndownPoint>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE days(day) AS (
+             SELECT ?2 WHERE ?2 <= ?3
+             UNION ALL
+             SELECT date(day, '+1 day') FROM days WHERE day < ?3
+         ),
+         tasks AS (
+             SELECT t.created_date_key AS created,
+                    CASE WHEN t.is_completed = 1 THEN t.completed_date_key END AS completed
+             FROM fact_tasks t
+             JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+             WHERE btp.project_gid = ?1 AND t.is_deleted = 0
+         )
+         SELECT day,
+                (SELECT COUNT(*) FROM tasks
+                 WHERE created <= day AND (completed IS NULL OR completed > day)),
+                (SELECT COUNT(*) FROM tasks WHERE completed >= ?2 AND completed <= day)
+         FROM days ORDER BY day",
+    )?;
+    let rows = stmt.query_map(params![project_gid, start, last_day], |row| {
+        Ok(BurndownPoint {
+            date: row.get(0)?,
+            open: row.get::<_, i64>(1)? as u64,
+            completed: row.get::<_, i64>(2)? as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+fn load_throughput(
+    conn: &Connection,
+    project_gid: &str,
+    start: &str,
+    last_day: &str,
+) -> std::result::Result<Vec<ThroughputPoint>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE weeks(week) AS (
+             SELECT ?2 WHERE ?2 <= ?3
+             UNION ALL
+             SELECT date(week, '+7 days') FROM weeks WHERE date(week, '+7 days') <= ?3
+         ),
+         tasks AS (
+             SELECT t.created_date_key AS created,
+                    CASE WHEN t.is_completed = 1 THEN t.completed_date_key END AS completed
+             FROM fact_tasks t
+             JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+             WHERE btp.project_gid = ?1 AND t.is_deleted = 0
+         )
+         SELECT week,
+                (SELECT COUNT(*) FROM tasks
+                 WHERE created >= week AND created < date(week, '+7 days') AND created <= ?3),
+                (SELECT COUNT(*) FROM tasks
+                 WHERE completed >= week AND completed < date(week, '+7 days') AND completed <= ?3)
+         FROM weeks ORDER BY week",
+    )?;
+    let rows = stmt.query_map(params![project_gid, start, last_day], |row| {
+        Ok(ThroughputPoint {
+            week: row.get(0)?,
+            created: row.get::<_, i64>(1)? as u64,
+            completed: row.get::<_, i64>(2)? as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+fn load_contributors(
+    conn: &Connection,
+    project_gid: &str,
+    from: &str,
+    until: &str,
+) -> std::result::Result<Vec<Contributor>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "WITH activity AS (
+             SELECT t.assignee_gid AS user_gid, 1 AS completed, 0 AS comments
+             FROM fact_tasks t
+             JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+             WHERE btp.project_gid = ?1 AND t.is_deleted = 0 AND t.is_completed = 1
+               AND t.completed_at >= ?2 AND t.completed_at < ?3
+               AND t.assignee_gid IS NOT NULL
+             UNION ALL
+             SELECT c.author_gid, 0, 1
+             FROM fact_comments c
+             JOIN bridge_task_projects btp ON btp.task_gid = c.task_gid
+             JOIN fact_tasks t ON t.task_gid = c.task_gid
+             WHERE btp.project_gid = ?1 AND t.is_deleted = 0 AND c.is_bot = 0
+               AND c.created_at >= ?2 AND c.created_at < ?3
+               AND c.author_gid IS NOT NULL
+         )
+         SELECT a.user_gid, u.name, SUM(a.completed), SUM(a.comments)
+         FROM activity a
+         LEFT JOIN dim_users u ON u.user_gid = a.user_gid
+         GROUP BY a.user_gid
+         ORDER BY SUM(a.completed) DESC, SUM(a.comments) DESC, u.name
+         LIMIT ?4",
+    )?;
+    let rows = stmt.query_map(
+        params![project_gid, from, until, TOP_CONTRIBUTORS as i64],
+        |row| {
+            Ok(Contributor {
+                user_gid: row.get(0)?,
+                name: row.get(1)?,
+                tasks_completed: row.get::<_, i64>(2)? as u64,
+                comments: row.get::<_, i64>(3)? as u64,
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+const OPEN_COLOR: &str = "#4573d2";
+const COMPLETED_COLOR: &str = "#5da283";
+
+/// Page colors for `to_html`, dark when the reader's system is. Charts keep
+/// their own light colors where this is stripped, e.g. pasted into a wiki.
+const THEME_CSS: &str = "body { background: #ffffff; color: #1e1f21; }
+a { color: inherit; }
+h2 { border-top: 1px solid #e0e0e0; }
+.muted { color: #6d6e6f; }
+@media (prefers-color-scheme: dark) {
+  body { background: #1e1f21; color: #f5f4f3; }
+  h2 { border-top-color: #424244; }
+  .muted, svg text { color: #a2a0a2; fill: #a2a0a2; }
+  svg line { stroke: #424244; }
+}
+";
+
+impl ProjectReport {
+    fn title(&self) -> String {
+        let name = self.metadata.name.as_deref().unwrap_or(&self.project_gid);
+        format!("{name}: {}", self.period_key)
+    }
+
+    /// Owner, team, and the period's dates, separated by `·`.
+    fn byline(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(owner) = &self.metadata.owner_name {
+            parts.push(format!("Owner: {owner}"));
+        }
+        if let Some(team) = &self.metadata.team_name {
+            parts.push(format!("Team: {team}"));
+        }
+        parts.push(format!("{} to {}", self.start, self.end));
+        parts.join(" · ")
+    }
+
+    /// Label and value rows of the key metrics table.
+    fn metric_rows(&self) -> Vec<(&'static str, String)> {
+        let m = &self.metrics;
+        let days = |d: Option<f64>| d.map_or("-".to_string(), |d| format!("{d:.1} days"));
+        vec![
+            ("Tasks created", m.throughput.tasks_created.to_string()),
+            ("Tasks completed", m.throughput.tasks_completed.to_string()),
+            ("Net new", format!("{:+}", m.throughput.net_new)),
+            (
+                "Milestones completed",
+                m.throughput.milestones_completed.to_string(),
+            ),
+            ("Open at period end", m.health.total_open.to_string()),
+            (
+                "Overdue",
+                format!("{} ({:.0}%)", m.health.overdue_count, m.health.overdue_pct),
+            ),
+            (
+                "Unassigned",
+                format!(
+                    "{} ({:.0}%)",
+                    m.health.unassigned_count, m.health.unassigned_pct
+                ),
+            ),
+            (
+                "Median lead time",
+                days(m.lead_time.median_days_to_complete),
+            ),
+            ("Median cycle time", days(m.cycle_time.median_days)),
+            (
+                "Comments",
+                format!(
+                    "{} from {} people",
+                    m.collaboration.total_comments, m.collaboration.unique_commenters
+                ),
+            ),
+        ]
+    }
+
+    /// Open and completed tasks per day; empty before the period starts.
+    pub fn burndown_svg(&self) -> String {
+        let labels: Vec<String> = self
+            .burndown
+            .iter()
+            .map(|p| p.date.get(5..).unwrap_or(&p.date).to_string())
+            .collect();
+        let open: Vec<f64> = self.burndown.iter().map(|p| p.open as f64).collect();
+        let completed: Vec<f64> = self.burndown.iter().map(|p| p.completed as f64).collect();
+        svg::line_chart(
+            &format!("Burndown, {}", self.period_key),
+            &labels,
+            &[
+                svg::Series {
+                    label: "Open",
+                    color: OPEN_COLOR,
+                    values: &open,
+                },
+                svg::Series {
+                    label: "Completed",
+                    color: COMPLETED_COLOR,
+                    values: &completed,
+                },
+            ],
+        )
+    }
+
+    /// Tasks created and completed per week; empty before the period
+    /// starts.
+    pub fn throughput_svg(&self) -> String {
+        let labels: Vec<String> = self
+            .throughput
+            .iter()
+            .map(|p| p.week.get(5..).unwrap_or(&p.week).to_string())
+            .collect();
+        let created: Vec<f64> = self.throughput.iter().map(|p| p.created as f64).collect();
+        let completed: Vec<f64> = self.throughput.iter().map(|p| p.completed as f64).collect();
+        svg::line_chart(
+            &format!("Weekly throughput, {}", self.period_key),
+            &labels,
+            &[
+                svg::Series {
+                    label: "Created",
+                    color: OPEN_COLOR,
+                    values: &created,
+                },
+                svg::Series {
+                    label: "Completed",
+                    color: COMPLETED_COLOR,
+                    values: &completed,
+                },
+            ],
+        )
+    }
+
+    /// Tasks completed in the period per lead-time bucket; empty when none
+    /// were.
+    pub fn lead_time_svg(&self) -> String {
+        let histogram = &self.metrics.lead_time.histogram;
+        if histogram.iter().all(|b| b.count == 0) {
+            return String::new();
+        }
+        let bars: Vec<(String, f64)> = histogram
+            .iter()
+            .map(|b| (b.label.clone(), b.count as f64))
+            .collect();
+        svg::bar_chart(
+            &format!("Lead time, {}", self.period_key),
+            &bars,
+            COMPLETED_COLOR,
+        )
+    }
+
+    /// Render as Markdown. Charts are inline SVG, which renders wherever
+    /// the Markdown allows raw HTML, so names and summary text are
+    /// HTML-escaped too.
+    pub fn to_markdown(&self) -> String {
+        let title = escape_html(&self.title());
+        let title = match &self.metadata.permalink_url {
+            Some(url) => format!(
+                "[{}]({})",
+                title.replace('[', "\\[").replace(']', "\\]"),
+                escape_html(url)
+            ),
+            None => title,
+        };
+        let mut md = format!("# {title}\n\n{}\n", escape_html(&self.byline()));
+
+        if let Some(s) = &self.summary {
+            md.push_str(&format!(
+                "\n## Summary\n\n**{}**\n\n{}\n",
+                escape_html(&s.headline),
+                escape_html(&s.what_changed)
+            ));
+            if !s.why_it_matters.is_empty() {
+                md.push_str(&format!("\n{}\n", escape_html(&s.why_it_matters)));
+            }
+        }
+
+        md.push_str("\n## Key metrics\n\n| Metric | Value |\n|---|---:|\n");
+        for (label, value) in self.metric_rows() {
+            md.push_str(&format!("| {label} | {value} |\n"));
+        }
+
+        md.push_str("\n## Burndown\n\n");
+        match self.burndown_svg() {
+            chart if chart.is_empty() => md.push_str("The period hasn't started.\n"),
+            chart => md.push_str(&format!("{chart}\n")),
+        }
+        md.push_str("\n## Throughput\n\n");
+        match self.throughput_svg() {
+            chart if chart.is_empty() => md.push_str("The period hasn't started.\n"),
+            chart => md.push_str(&format!("{chart}\n")),
+        }
+        md.push_str("\n## Lead time\n\n");
+        match self.lead_time_svg() {
+            chart if chart.is_empty() => md.push_str("No tasks completed in this period.\n"),
+            chart => md.push_str(&format!("{chart}\n")),
+        }
+
+        md.push_str("\n## Top contributors\n\n");
+        if self.contributors.is_empty() {
+            md.push_str("No completed tasks or comments in this period.\n");
+        } else {
+            md.push_str("| Contributor | Tasks completed | Comments |\n|---|---:|---:|\n");
+            for c in &self.contributors {
+                let name =
+                    escape_html(c.name.as_deref().unwrap_or(&c.user_gid)).replace('|', "\\|");
+                md.push_str(&format!(
+                    "| {name} | {} | {} |\n",
+                    c.tasks_completed, c.comments
+                ));
+            }
+        }
+        md
+    }
+
+    /// Render as a standalone HTML page with inline layout styles, in light
+    /// or dark colors to match the reader's system.
+    pub fn to_html(&self) -> String {
+        const H2: &str = "<h2 style=\"font-size: 18px; padding-top: 12px;\">";
+        const TD: &str = "<td style=\"padding: 4px 12px 4px 0;\">";
+        const TD_NUM: &str = "<td style=\"padding: 4px 0 4px 12px; text-align: right;\">";
+
+        let title = match &self.metadata.permalink_url {
+            Some(url) => format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(url),
+                escape_html(&self.title())
+            ),
+            None => escape_html(&self.title()),
+        };
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"color-scheme\" content=\"light dark\">\n<title>{}</title>\n\
+             <style>\n{THEME_CSS}</style>\n</head>\n\
+             <body style=\"font-family: Helvetica, Arial, sans-serif; max-width: 680px;\">\n\
+             <h1 style=\"font-size: 22px;\">{title}</h1>\n\
+             <p class=\"muted\" style=\"font-size: 13px;\">{}</p>\n",
+            escape_html(&self.title()),
+            escape_html(&self.byline())
+        );
+
+        if let Some(s) = &self.summary {
+            html.push_str(&format!(
+                "{H2}Summary</h2>\n<p><strong>{}</strong></p>\n<p>{}</p>\n",
+                escape_html(&s.headline),
+                escape_html(&s.what_changed)
+            ));
+            if !s.why_it_matters.is_empty() {
+                html.push_str(&format!("<p>{}</p>\n", escape_html(&s.why_it_matters)));
+            }
+        }
+
+        html.push_str(&format!(
+            "{H2}Key metrics</h2>\n<table style=\"border-collapse: collapse;\">\n"
+        ));
+        for (label, value) in self.metric_rows() {
+            html.push_str(&format!(
+                "<tr>{TD}{label}</td>{TD_NUM}{}</td></tr>\n",
+                escape_html(&value)
+            ));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str(&format!("{H2}Burndown</h2>\n"));
+        match self.burndown_svg() {
+            chart if chart.is_empty() => html.push_str("<p>The period hasn't started.</p>\n"),
+            chart => html.push_str(&format!("{chart}\n")),
+        }
+        html.push_str(&format!("{H2}Throughput</h2>\n"));
+        match self.throughput_svg() {
+            chart if chart.is_empty() => html.push_str("<p>The period hasn't started.</p>\n"),
+            chart => html.push_str(&format!("{chart}\n")),
+        }
+        html.push_str(&format!("{H2}Lead time</h2>\n"));
+        match self.lead_time_svg() {
+            chart if chart.is_empty() => {
+                html.push_str("<p>No tasks completed in this period.</p>\n")
+            }
+            chart => html.push_str(&format!("{chart}\n")),
+        }
+
+        html.push_str(&format!("{H2}Top contributors</h2>\n"));
+        if self.contributors.is_empty() {
+            html.push_str("<p>No completed tasks or comments in this period.</p>\n");
+        } else {
+            html.push_str(&format!(
+                "<table style=\"border-collapse: collapse;\">\n\
+                 <tr>{TD}<strong>Contributor</strong></td>{TD_NUM}<strong>Tasks completed</strong></td>\
+                 {TD_NUM}<strong>Comments</strong></td></tr>\n"
+            ));
+            for c in &self.contributors {
+                html.push_str(&format!(
+                    "<tr>{TD}{}</td>{TD_NUM}{}</td>{TD_NUM}{}</td></tr>\n",
+                    escape_html(c.name.as_deref().unwrap_or(&c.user_gid)),
+                    c.tasks_completed,
+                    c.comments
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Escape text for an HTML element or double-quoted attribute. Shared with
+/// the LLM digest's HTML rendering.
+pub(crate) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_project_report() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('u1', 'Ada', datetime('now')), ('u2', 'Grace <QA>', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, owner_gid, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'u1', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, days_to_complete, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Plan', 'u1', 1, '2024-03-02T10:00:00Z', '2024-03-02', 11, '2024-02-20T10:00:00Z', '2024-02-20', datetime('now')),
+                            ('t2', 'Build', 'u1', 1, '2024-03-04T10:00:00Z', '2024-03-04', 3, '2024-03-01T10:00:00Z', '2024-03-01', datetime('now')),
+                            ('t3', 'Test', 'u2', 0, NULL, NULL, NULL, '2024-03-03T10:00:00Z', '2024-03-03', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p1'), ('t3', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't3', 'u2', 'Found a bug', 'comment', '2024-03-03T12:00:00Z', '2024-03-03', datetime('now')),
+                            ('c2', 't3', 'u2', 'Fixed', 'comment', '2024-03-04T12:00:00Z', '2024-03-04', datetime('now'));
+                     INSERT INTO fact_project_period_summaries (project_gid, period_key, generation, headline, what_changed, why_it_matters, key_milestones, prompt_version, generated_at)
+                     VALUES ('p1', '2024-03', 1, 'Launch prep on track', 'Planning and build finished.', '', '[]', 'v1', '2024-04-01'),
+                            ('p1', '2024-02', 1, 'Kickoff', '', '', '[]', 'v1', '2024-03-01');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let period = Period::parse("2024-03").unwrap();
+        let report = project_report(&db, "p1", &period).await.unwrap();
+        assert_eq!(report.burndown.len(), 31);
+        let day = |date: &str| report.burndown.iter().find(|p| p.date == date).unwrap();
+        assert_eq!(
+            (day("2024-03-01").open, day("2024-03-01").completed),
+            (2, 0)
+        );
+        assert_eq!(
+            (day("2024-03-03").open, day("2024-03-03").completed),
+            (2, 1)
+        );
+        assert_eq!(
+            (day("2024-03-31").open, day("2024-03-31").completed),
+            (1, 2)
+        );
+
+        let weeks: Vec<_> = report
+            .throughput
+            .iter()
+            .map(|p| (p.week.as_str(), p.created, p.completed))
+            .collect();
+        assert_eq!(
+            weeks,
+            [
+                ("2024-03-01", 2, 2),
+                ("2024-03-08", 0, 0),
+                ("2024-03-15", 0, 0),
+                ("2024-03-22", 0, 0),
+                ("2024-03-29", 0, 0)
+            ]
+        );
+
+        assert_eq!(report.contributors.len(), 2);
+        assert_eq!(report.contributors[0].name.as_deref(), Some("Ada"));
+        assert_eq!(report.contributors[0].tasks_completed, 2);
+        assert_eq!(report.contributors[1].comments, 2);
+        assert_eq!(
+            report.summary.as_ref().unwrap().headline,
+            "Launch prep on track"
+        );
+
+        let md = report.to_markdown();
+        assert!(md.starts_with("# Launch: 2024-03\n"));
+        assert!(md.contains("**Launch prep on track**"));
+        assert!(md.contains("| Tasks completed | 2 |"));
+        assert!(md.contains("| Grace &lt;QA&gt; | 0 | 2 |"));
+        let mut linked = report.clone();
+        linked.metadata.name = Some("[Beta] <img src=x>".to_string());
+        linked.metadata.permalink_url = Some("https://app.asana.com/0/p1".to_string());
+        assert!(linked.to_markdown().starts_with(
+            "# [\\[Beta\\] &lt;img src=x&gt;: 2024-03](https://app.asana.com/0/p1)\n"
+        ));
+        assert_eq!(md.matches("<svg").count(), 3);
+        assert!(md.contains("<title>Weekly throughput, 2024-03</title>"));
+
+        let html = report.to_html();
+        assert!(html.contains("Grace &lt;QA&gt;"));
+        assert!(html.contains("<title>Launch: 2024-03</title>"));
+        assert!(html.contains("@media (prefers-color-scheme: dark)"));
+        assert!(html.trim_end().ends_with("</html>"));
+
+        // Only the report's own period has a summary
+        let april = project_report(&db, "p1", &Period::parse("2024-04").unwrap())
+            .await
+            .unwrap();
+        assert!(april.summary.is_none());
+        assert!(april
+            .to_markdown()
+            .contains("No tasks completed in this period."));
+
+        assert!(matches!(
+            project_report(&db, "nope", &period).await,
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! Charts as self-contained SVG: no scripts, stylesheets, or web fonts, so
+//! they survive being pasted into a wiki page or an email. The markup has
+//! no blank lines, which would end a raw HTML block in Markdown.
+
+use super::escape_html;
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 240.0;
+const MARGIN_LEFT: f64 = 44.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 24.0;
+const MARGIN_BOTTOM: f64 = 28.0;
+/// Horizontal grid lines, not counting the baseline.
+const GRID_LINES: u32 = 4;
+/// Most x-axis labels a line chart shows before thinning them out.
+const MAX_X_LABELS: usize = 6;
+const FONT: &str = "font-family=\"Helvetica, Arial, sans-serif\" font-size=\"11\" fill=\"#6d6e6f\"";
+
+/// One line of a [`line_chart`].
+pub struct Series<'a> {
+    pub label: &'a str,
+    /// Any CSS color, e.g. `#4573d2`.
+    pub color: &'a str,
+    /// One value per x-axis label.
+    pub values: &'a [f64],
+}
+
+/// A line chart of `series` over `x_labels`, with a legend. Empty without
+/// labels.
+pub fn line_chart(title: &str, x_labels: &[String], series: &[Series]) -> String {
+    if x_labels.is_empty() {
+        return String::new();
+    }
+    let max = axis_max(series.iter().flat_map(|s| s.values.iter().copied()));
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let x = |i: usize| {
+        if x_labels.len() == 1 {
+            MARGIN_LEFT + plot_width / 2.0
+        } else {
+            MARGIN_LEFT + plot_width * i as f64 / (x_labels.len() - 1) as f64
+        }
+    };
+
+    let mut svg = open(title);
+    svg.push_str(&grid(max));
+    let step = x_labels.len().div_ceil(MAX_X_LABELS);
+    for (i, label) in x_labels.iter().enumerate() {
+        if i % step == 0 || i == x_labels.len() - 1 {
+            svg.push_str(&x_label(x(i), label));
+        }
+    }
+    for s in series {
+        let points: Vec<String> = s
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| format!("{:.1},{:.1}", x(i), y(*v, max)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>\n",
+            escape_html(s.color),
+            points.join(" ")
+        ));
+        // A one-point line draws nothing, so mark the point
+        if let [value] = s.values {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"/>\n",
+                x(0),
+                y(*value, max),
+                escape_html(s.color)
+            ));
+        }
+    }
+    // Legend, right-aligned above the plot
+    let mut legend_x = WIDTH - MARGIN_RIGHT;
+    for s in series.iter().rev() {
+        legend_x -= 24.0 + 6.5 * s.label.chars().count() as f64;
+        svg.push_str(&format!(
+            "<rect x=\"{legend_x:.1}\" y=\"6\" width=\"10\" height=\"10\" fill=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"15\" {FONT}>{}</text>\n",
+            escape_html(s.color),
+            legend_x + 14.0,
+            escape_html(s.label)
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// A bar chart with one labeled bar per `(label, value)`, each topped with
+/// its value. Empty without bars.
+pub fn bar_chart(title: &str, bars: &[(String, f64)], color: &str) -> String {
+    if bars.is_empty() {
+        return String::new();
+    }
+    let max = axis_max(bars.iter().map(|(_, v)| *v));
+    let slot = (WIDTH - MARGIN_LEFT - MARGIN_RIGHT) / bars.len() as f64;
+    let bar_width = slot * 0.7;
+
+    let mut svg = open(title);
+    svg.push_str(&grid(max));
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let center = MARGIN_LEFT + slot * (i as f64 + 0.5);
+        let top = y(*value, max);
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{top:.1}\" width=\"{bar_width:.1}\" height=\"{:.1}\" fill=\"{}\"/>\
+             <text x=\"{center:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{}</text>\n",
+            center - bar_width / 2.0,
+            HEIGHT - MARGIN_BOTTOM - top,
+            escape_html(color),
+            top - 4.0,
+            tick_label(*value)
+        ));
+        svg.push_str(&x_label(center, label));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn open(title: &str) -> String {
+    let title = escape_html(title);
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" \
+         viewBox=\"0 0 {WIDTH} {HEIGHT}\" role=\"img\" aria-label=\"{title}\">\n\
+         <title>{title}</title>\n"
+    )
+}
+
+/// Grid lines with their values, and the baseline.
+fn grid(max: f64) -> String {
+    let mut svg = String::new();
+    for i in 0..=GRID_LINES {
+        let value = max * i as f64 / GRID_LINES as f64;
+        let top = y(value, max);
+        let stroke = if i == 0 { "#9ca0a4" } else { "#e8e8e8" };
+        svg.push_str(&format!(
+            "<line x1=\"{MARGIN_LEFT}\" y1=\"{top:.1}\" x2=\"{:.1}\" y2=\"{top:.1}\" stroke=\"{stroke}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" {FONT}>{}</text>\n",
+            WIDTH - MARGIN_RIGHT,
+            MARGIN_LEFT - 6.0,
+            top + 4.0,
+            tick_label(value)
+        ));
+    }
+    svg
+}
+
+fn x_label(x: f64, label: &str) -> String {
+    format!(
+        "<text x=\"{x:.1}\" y=\"{:.1}\" text-anchor=\"middle\" {FONT}>{}</text>\n",
+        HEIGHT - MARGIN_BOTTOM + 16.0,
+        escape_html(label)
+    )
+}
+
+fn y(value: f64, max: f64) -> f64 {
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    MARGIN_TOP + plot_height * (1.0 - value / max)
+}
+
+/// The top of the y axis: [`GRID_LINES`] steps of 1, 1.5, 2, 2.5, 3, 4, 5,
+/// 6, or 8 times a power of ten, reaching the largest value, so grid lines
+/// fall on readable numbers. Small counts get whole-number steps.
+fn axis_max(values: impl Iterator<Item = f64>) -> f64 {
+    let step = values.fold(0.0, f64::max) / GRID_LINES as f64;
+    if step <= 1.0 {
+        return GRID_LINES as f64;
+    }
+    let magnitude = 10f64.powf(step.log10().floor());
+    let nice = [1.0, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|m| *m >= step)
+        .unwrap_or(10.0 * magnitude);
+    nice * GRID_LINES as f64
+}
+
+fn tick_label(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.1}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_max() {
+        assert_eq!(axis_max([0.0].into_iter()), 4.0);
+        assert_eq!(axis_max([3.0].into_iter()), 4.0);
+        assert_eq!(axis_max([7.0, 2.0].into_iter()), 8.0);
+        assert_eq!(axis_max([10.0].into_iter()), 10.0);
+        assert_eq!(axis_max([21.0].into_iter()), 24.0);
+        assert_eq!(axis_max([480.0].into_iter()), 600.0);
+    }
+
+    #[test]
+    fn test_charts() {
+        let labels: Vec<String> = (1..=10).map(|d| format!("01-{d:02}")).collect();
+        let open: Vec<f64> = (0..10).map(|i| 10.0 - i as f64).collect();
+        let svg = line_chart(
+            "Open <tasks>",
+            &labels,
+            &[Series {
+                label: "Open",
+                color: "#4573d2",
+                values: &open,
+            }],
+        );
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("<title>Open &lt;tasks&gt;</title>"));
+        assert!(svg.contains("points=\"44.0,24.0 "));
+        // Ten days thin out to every other label, plus the last
+        assert!(svg.contains(">01-03<") && !svg.contains(">01-02<") && svg.contains(">01-10<"));
+        assert!(!svg.contains("\n\n"));
+        assert_eq!(line_chart("Empty", &[], &[]), "");
+        let one = line_chart(
+            "One day",
+            &labels[..1],
+            &[Series {
+                label: "Open",
+                color: "#4573d2",
+                values: &open[..1],
+            }],
+        );
+        assert!(one.contains("<circle cx=\"334.0\""));
+        assert!(!svg.contains("<circle"));
+
+        let bars = vec![("0-1d".to_string(), 3.0), ("2-3d".to_string(), 0.0)];
+        let svg = bar_chart("Lead time", &bars, "#5da283");
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert!(svg.contains(">0-1d<"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Consistency checks for the FTS5 indexes behind search.
+//!
+//! The indexes are kept current by triggers on their source tables. An
+//! `INSERT OR REPLACE` deletes the old row without firing `AFTER DELETE`
+//! triggers (unless `recursive_triggers` is on), which leaves stale index
+//! entries behind, so upserts use `ON CONFLICT ... DO UPDATE` instead.
+//! [`verify_index`] detects and repairs any drift that slipped through.
+
+use rusqlite::{Connection, ErrorCode};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::storage::Database;
+
+/// An FTS index and the table it indexes.
+struct FtsIndex {
+    index: &'static str,
+    source_table: &'static str,
+    /// External-content indexes (`content=...`) can be integrity-checked
+    /// against their source and rebuilt from it directly.
+    external_content: bool,
+}
+
+const FTS_INDEXES: &[FtsIndex] = &[
+    FtsIndex {
+        index: "tasks_fts",
+        source_table: "fact_tasks",
+        external_content: true,
+    },
+    FtsIndex {
+        index: "comments_fts",
+        source_table: "fact_comments",
+        external_content: true,
+    },
+    FtsIndex {
+        index: "projects_fts",
+        source_table: "dim_projects",
+        external_content: true,
+    },
+    FtsIndex {
+        index: "portfolios_fts",
+        source_table: "dim_portfolios",
+        external_content: true,
+    },
+    FtsIndex {
+        index: "project_briefs_fts",
+        source_table: "dim_project_briefs",
+        external_content: true,
+    },
+    FtsIndex {
+        index: "status_updates_fts",
+        source_table: "fact_status_updates",
+        external_content: true,
+    },
+    FtsIndex {
+        index: "custom_fields_fts",
+        source_table: "fact_task_custom_fields",
+        external_content: false,
+    },
+];
+
+/// Result of checking one FTS index against its source table.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexCheck {
+    pub index: String,
+    pub source_table: String,
+    pub source_rows: u64,
+    pub indexed_rows: u64,
+    /// Whether the index matched its source when checked.
+    pub in_sync: bool,
+    /// Whether the index was rebuilt because it had drifted.
+    pub repaired: bool,
+}
+
+/// Check every FTS index against its source table. With `repair`, indexes
+/// that have drifted are rebuilt from their source.
+pub async fn verify_index(db: &Database, repair: bool) -> Result<Vec<IndexCheck>> {
+    // FTS5 integrity checks and rebuilds are issued as INSERTs, so both run
+    // on the writer.
+    let checks = db
+        .writer()
+        .call(move |conn| {
+            let mut checks = Vec::with_capacity(FTS_INDEXES.len());
+            for fts in FTS_INDEXES {
+                let mut check = check_index(conn, fts)?;
+                if repair && !check.in_sync {
+                    rebuild_index(conn, fts)?;
+                    check.repaired = true;
+                }
+                checks.push(check);
+            }
+            Ok::<Vec<IndexCheck>, rusqlite::Error>(checks)
+        })
+        .await?;
+    Ok(checks)
+}
+
+fn check_index(
+    conn: &Connection,
+    fts: &FtsIndex,
+) -> std::result::Result<IndexCheck, rusqlite::Error> {
+    let FtsIndex {
+        index,
+        source_table,
+        ..
+    } = fts;
+    let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0));
+    let source_rows = count(&format!("SELECT COUNT(*) FROM {source_table}"))?;
+
+    let (indexed_rows, contents_match) = if fts.external_content {
+        // Selecting from an external-content index reads the source table,
+        // so the indexed document count comes from the docsize shadow table.
+        let indexed_rows = count(&format!("SELECT COUNT(*) FROM {index}_docsize"))?;
+        (indexed_rows, integrity_check(conn, index)?)
+    } else {
+        let indexed_rows = count(&format!("SELECT COUNT(*) FROM {index}"))?;
+        let stale = count(&format!(
+            "SELECT COUNT(*) FROM (
+                 SELECT task_gid, field_name, display_value FROM {index}
+                 EXCEPT
+                 {CUSTOM_FIELDS_SOURCE}
+             )"
+        ))?;
+        (indexed_rows, stale == 0)
+    };
+
+    Ok(IndexCheck {
+        index: index.to_string(),
+        source_table: source_table.to_string(),
+        source_rows: source_rows as u64,
+        indexed_rows: indexed_rows as u64,
+        in_sync: contents_match && indexed_rows == source_rows,
+        repaired: false,
+    })
+}
+
+/// Rows of `custom_fields_fts` as its triggers would write them.
+const CUSTOM_FIELDS_SOURCE: &str = "SELECT tcf.task_gid,
+        (SELECT name FROM dim_custom_fields WHERE field_gid = tcf.field_gid),
+        tcf.display_value
+    FROM fact_task_custom_fields tcf";
+
+/// FTS5's own check that an external-content index matches its source.
+/// Returns `false` when they differ.
+fn integrity_check(conn: &Connection, index: &str) -> std::result::Result<bool, rusqlite::Error> {
+    match conn.execute(
+        &format!("INSERT INTO {index}({index}, rank) VALUES ('integrity-check', 1)"),
+        [],
+    ) {
+        Ok(_) => Ok(true),
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::DatabaseCorrupt => {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn rebuild_index(conn: &Connection, fts: &FtsIndex) -> std::result::Result<(), rusqlite::Error> {
+    let index = fts.index;
+    if fts.external_content {
+        conn.execute(
+            &format!("INSERT INTO {index}({index}) VALUES ('rebuild')"),
+            [],
+        )?;
+    } else {
+        conn.execute_batch(&format!(
+            "DELETE FROM {index};
+             INSERT INTO {index}(task_gid, field_name, display_value) {CUSTOM_FIELDS_SOURCE};"
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<'a>(checks: &'a [IndexCheck], index: &str) -> &'a IndexCheck {
+        checks.iter().find(|c| c.index == index).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_upserts_keep_fts_in_sync() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                // Same statement shapes as the repository upserts
+                for name in ["Draft plan", "Final plan"] {
+                    conn.execute(
+                        "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                         VALUES ('t1', ?1, '2025-01-01', '2025-01-01', datetime('now'))
+                         ON CONFLICT(task_gid) DO UPDATE SET name = excluded.name",
+                        [name],
+                    )?;
+                }
+                for text in ["first", "edited"] {
+                    conn.execute(
+                        "INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                         VALUES ('c1', 't1', ?1, 'comment', '2025-01-02', '2025-01-02', datetime('now'))
+                         ON CONFLICT(comment_gid) DO UPDATE SET text = excluded.text",
+                        [text],
+                    )?;
+                }
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let checks = verify_index(&db, false).await.unwrap();
+        assert!(checks.iter().all(|c| c.in_sync), "{checks:?}");
+        assert_eq!(check(&checks, "tasks_fts").indexed_rows, 1);
+        assert_eq!(check(&checks, "comments_fts").indexed_rows, 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_index_detects_and_repairs_drift() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Old name', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO dim_custom_fields (field_gid, name, field_type, cached_at)
+                     VALUES ('f1', 'Priority', 'enum', datetime('now'));
+                     INSERT INTO fact_task_custom_fields (task_gid, field_gid, display_value)
+                     VALUES ('t1', 'f1', 'High');",
+                )?;
+                // REPLACE skips the delete trigger and strands the old entry
+                conn.execute(
+                    "INSERT OR REPLACE INTO fact_task_custom_fields (task_gid, field_gid, display_value)
+                     VALUES ('t1', 'f1', 'Low')",
+                    [],
+                )?;
+                // An index entry whose source row is gone
+                conn.execute(
+                    "INSERT INTO tasks_fts(rowid, task_gid, name, notes)
+                     VALUES (999, 'ghost', 'Old ghost', '')",
+                    [],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let checks = verify_index(&db, true).await.unwrap();
+        for index in ["tasks_fts", "custom_fields_fts"] {
+            let c = check(&checks, index);
+            assert!(!c.in_sync && c.repaired, "{c:?}");
+        }
+        assert!(check(&checks, "comments_fts").in_sync);
+
+        let checks = verify_index(&db, false).await.unwrap();
+        assert!(checks.iter().all(|c| c.in_sync), "{checks:?}");
+        let options = crate::search::SearchOptions {
+            hit_type: Some(crate::search::SearchHitType::CustomField),
+            ..Default::default()
+        };
+        let hits = crate::search::search(&db, "high", &options).await.unwrap();
+        assert_eq!(hits.total, 0);
+    }
+}
@@ -1,7 +1,11 @@
+pub mod index;
+pub mod query;
+
 use serde::Serialize;
 
 use crate::error::Result;
 use crate::storage::Database;
+use crate::url::generate_comment_url;
 
 /// What kind of entity matched the search.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -10,6 +14,8 @@ pub enum SearchHitType {
     Comment,
     Project,
     Portfolio,
+    ProjectBrief,
+    StatusUpdate,
     CustomField,
 }
 
@@ -18,9 +24,14 @@ pub enum SearchHitType {
 pub struct SearchHit {
     pub hit_type: SearchHitType,
     /// GID of the matched entity (task_gid, comment_gid, or project_gid).
+    /// Project brief hits carry the GID of the project they describe.
     pub gid: String,
     /// For comments and custom fields, the parent task GID.
     pub task_gid: Option<String>,
+    /// For status updates, the author's name.
+    pub author: Option<String>,
+    /// For status updates, when it was posted.
+    pub created_at: Option<String>,
     /// Display name / title.
     pub title: String,
     /// Highlighted snippet from the matching text.
@@ -36,12 +47,30 @@ pub struct SearchHit {
 pub struct SearchOptions {
     /// Maximum number of results to return.
     pub limit: Option<u32>,
+    /// Number of results to skip, for paging through matches.
+    pub offset: Option<u32>,
     /// Restrict search to a specific hit type.
     pub hit_type: Option<SearchHitType>,
     /// Filter to tasks assigned to this user GID.
     pub assignee_gid: Option<String>,
     /// Filter to tasks in this project GID.
     pub project_gid: Option<String>,
+    /// Filter to completed (`true`) or open (`false`) tasks. Comments
+    /// follow their task.
+    pub completed: Option<bool>,
+    /// Filter to tasks and comments created on or after this date (YYYY-MM-DD).
+    pub created_after: Option<String>,
+    /// Filter to tasks and comments created on or before this date (YYYY-MM-DD).
+    pub created_before: Option<String>,
+    /// Filter to tasks modified on or after this date (YYYY-MM-DD). Comments
+    /// can't be edited in the warehouse, so their creation date is used.
+    pub modified_after: Option<String>,
+    /// Filter to tasks modified on or before this date (YYYY-MM-DD).
+    pub modified_before: Option<String>,
+    /// Filter to tasks of this Asana subtype (`default_task`, `milestone`, or
+    /// `approval`). Comments and custom fields follow their task; hit types
+    /// that aren't about a task are skipped.
+    pub subtype: Option<String>,
 }
 
 /// Search results container.
@@ -49,28 +78,149 @@ pub struct SearchOptions {
 pub struct SearchResults {
     pub query: String,
     pub hits: Vec<SearchHit>,
+    /// Number of hits returned.
     pub total: usize,
+    /// Number of matches before `offset` and `limit` were applied.
+    pub total_unlimited: usize,
+    pub offset: u32,
+}
+
+/// Count the rows a search query matches, ignoring any limit.
+fn count_matches(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    params: &[&dyn rusqlite::types::ToSql],
+) -> std::result::Result<usize, rusqlite::Error> {
+    let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM ({sql})"), params, |row| {
+        row.get(0)
+    })?;
+    Ok(count as usize)
+}
+
+/// Task and comment filters beyond assignee and project.
+struct TaskFilters {
+    completed: Option<bool>,
+    subtype: Option<String>,
+    created_after: Option<String>,
+    created_before: Option<String>,
+    modified_after: Option<String>,
+    modified_before: Option<String>,
+}
+
+impl TaskFilters {
+    /// Append the filters to `sql`, given the completion flag, creation
+    /// date, and modification date expressions of the hit's table. The
+    /// hit's task must be joined as `t`.
+    fn push(
+        &self,
+        sql: &mut String,
+        params: &mut Vec<Box<dyn rusqlite::types::ToSql>>,
+        completed_col: &str,
+        created_col: &str,
+        modified_col: &str,
+    ) {
+        if let Some(completed) = self.completed {
+            sql.push_str(&format!(
+                " AND COALESCE({completed_col}, 0) = ?{}",
+                params.len() + 1
+            ));
+            params.push(Box::new(completed));
+        }
+        if let Some(ref subtype) = self.subtype {
+            sql.push_str(&format!(" AND t.resource_subtype = ?{}", params.len() + 1));
+            params.push(Box::new(subtype.clone()));
+        }
+        let bounds = [
+            (created_col, ">=", &self.created_after),
+            (created_col, "<=", &self.created_before),
+            (modified_col, ">=", &self.modified_after),
+            (modified_col, "<=", &self.modified_before),
+        ];
+        for (col, op, date) in bounds {
+            if let Some(date) = date {
+                sql.push_str(&format!(" AND {col} {op} ?{}", params.len() + 1));
+                params.push(Box::new(date.clone()));
+            }
+        }
+    }
+}
+
+fn empty_results(query: String, offset: u32) -> SearchResults {
+    SearchResults {
+        query,
+        hits: Vec::new(),
+        total: 0,
+        total_unlimited: 0,
+        offset,
+    }
 }
 
 /// Search across all FTS-indexed content.
+///
+/// `query` uses the syntax described in [`query`]: phrases, prefixes,
+/// exclusions, `OR`, and `assignee:`/`project:`/`subtype:` filters, which
+/// narrow the filters in `options`.
 pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Result<SearchResults> {
-    if query.trim().is_empty() {
-        return Ok(SearchResults {
-            query: query.to_string(),
-            hits: Vec::new(),
-            total: 0,
-        });
-    }
-    let query_owned = query.to_string();
-    let options_limit = options.limit.unwrap_or(50);
+    let compiled = query::compile_query(query);
+    let Some(query_owned) = compiled.match_expr else {
+        return Ok(empty_results(
+            query.to_string(),
+            options.offset.unwrap_or(0),
+        ));
+    };
+    let display_query = query.to_string();
+    let page_limit = options.limit.unwrap_or(50);
+    let offset = options.offset.unwrap_or(0);
+    // Each hit type is ranked separately, so every type must return enough
+    // hits to fill the page after the merged list is offset.
+    let options_limit = page_limit.saturating_add(offset);
     let hit_type_filter = options.hit_type.clone();
     let assignee_filter = options.assignee_gid.clone();
     let project_filter = options.project_gid.clone();
+    let mut task_filters = TaskFilters {
+        completed: options.completed,
+        subtype: options.subtype.as_deref().map(str::to_lowercase),
+        created_after: options.created_after.clone(),
+        created_before: options.created_before.clone(),
+        modified_after: options.modified_after.clone(),
+        modified_before: options.modified_before.clone(),
+    };
 
     let hits: SearchResults = db
         .reader()
         .call(move |conn| {
+            // A `field:` filter that matches nothing, or names a different
+            // entity than the options, leaves no results.
+            let assignee_filter = match compiled.assignee {
+                None => assignee_filter,
+                Some(value) => match (query::resolve_user(conn, &value)?, assignee_filter) {
+                    (Some(gid), None) => Some(gid),
+                    (Some(gid), Some(given)) if gid == given => Some(gid),
+                    _ => return Ok(empty_results(display_query, offset)),
+                },
+            };
+            let project_filter = match compiled.project {
+                None => project_filter,
+                Some(value) => match (query::resolve_project(conn, &value)?, project_filter) {
+                    (Some(gid), None) => Some(gid),
+                    (Some(gid), Some(given)) if gid == given => Some(gid),
+                    _ => return Ok(empty_results(display_query, offset)),
+                },
+            };
+            if let Some(value) = compiled.subtype {
+                let value = value.to_lowercase();
+                match &task_filters.subtype {
+                    Some(given) if *given != value => {
+                        return Ok(empty_results(display_query, offset))
+                    }
+                    _ => task_filters.subtype = Some(value),
+                }
+            }
+            // Projects, portfolios, briefs, and status updates have no subtype
+            let tasks_only = task_filters.subtype.is_some();
+
             let mut all_hits: Vec<SearchHit> = Vec::new();
+            let mut total_unlimited = 0;
 
             // Search tasks
             if hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::Task) {
@@ -78,7 +228,7 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                     "SELECT t.task_gid, t.name, snippet(tasks_fts, 2, '<b>', '</b>', '...', 32) as snip, tasks_fts.rank, t.permalink_url
                      FROM tasks_fts
                      JOIN fact_tasks t ON t.id = tasks_fts.rowid
-                     WHERE tasks_fts MATCH ?1",
+                     WHERE tasks_fts MATCH ?1 AND t.is_deleted = 0",
                 );
                 let mut params: Vec<Box<dyn rusqlite::types::ToSql>> =
                     vec![Box::new(query_owned.clone())];
@@ -94,6 +244,16 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                     ));
                     params.push(Box::new(project.clone()));
                 }
+                task_filters.push(
+                    &mut sql,
+                    &mut params,
+                    "t.is_completed",
+                    "t.created_date_key",
+                    "date(t.modified_at)",
+                );
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                total_unlimited += count_matches(conn, &sql, &param_refs)?;
                 sql.push_str(" ORDER BY rank LIMIT ?");
                 let limit_idx = params.len() + 1;
                 sql = sql.replace("LIMIT ?", &format!("LIMIT ?{limit_idx}"));
@@ -110,6 +270,8 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                         hit_type: SearchHitType::Task,
                         gid: gid.clone(),
                         task_gid: None,
+                        author: None,
+                        created_at: None,
                         title: row.get(1)?,
                         snippet: snippet.unwrap_or_default(),
                         rank: row.get(3)?,
@@ -124,11 +286,16 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
             // Search comments
             if hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::Comment) {
                 let mut sql = String::from(
-                    "SELECT c.comment_gid, c.task_gid, t.name, snippet(comments_fts, 2, '<b>', '</b>', '...', 32) as snip, comments_fts.rank, t.permalink_url
+                    "SELECT c.comment_gid, c.task_gid, t.name, snippet(comments_fts, 2, '<b>', '</b>', '...', 32) as snip, comments_fts.rank,
+                            COALESCE(
+                                (SELECT p.workspace_gid FROM bridge_task_projects btp
+                                 JOIN dim_projects p ON p.project_gid = btp.project_gid
+                                 WHERE btp.task_gid = c.task_gid LIMIT 1),
+                                (SELECT value FROM app_config WHERE key = 'workspace_gid'))
                      FROM comments_fts
                      JOIN fact_comments c ON c.id = comments_fts.rowid
                      LEFT JOIN fact_tasks t ON t.task_gid = c.task_gid
-                     WHERE comments_fts MATCH ?1",
+                     WHERE comments_fts MATCH ?1 AND COALESCE(t.is_deleted, 0) = 0",
                 );
                 let mut params: Vec<Box<dyn rusqlite::types::ToSql>> =
                     vec![Box::new(query_owned.clone())];
@@ -144,6 +311,16 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                     ));
                     params.push(Box::new(project.clone()));
                 }
+                task_filters.push(
+                    &mut sql,
+                    &mut params,
+                    "t.is_completed",
+                    "c.created_date_key",
+                    "c.created_date_key",
+                );
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                total_unlimited += count_matches(conn, &sql, &param_refs)?;
                 sql.push_str(&format!(" ORDER BY rank LIMIT ?{}", params.len() + 1));
                 params.push(Box::new(options_limit));
 
@@ -151,18 +328,25 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                     params.iter().map(|p| p.as_ref()).collect();
                 let mut stmt = conn.prepare(&sql)?;
                 let rows = stmt.query_map(param_refs.as_slice(), |row| {
+                    let comment_gid: String = row.get(0)?;
                     let task_gid: String = row.get(1)?;
                     let task_name: Option<String> = row.get(2)?;
                     let snippet: Option<String> = row.get(3)?;
-                    let stored_url: Option<String> = row.get(5)?;
+                    let workspace_gid: Option<String> = row.get(5)?;
                     Ok(SearchHit {
                         hit_type: SearchHitType::Comment,
-                        gid: row.get(0)?,
-                        task_gid: Some(task_gid.clone()),
+                        asana_url: Some(generate_comment_url(
+                            workspace_gid.as_deref(),
+                            &task_gid,
+                            &comment_gid,
+                        )),
+                        gid: comment_gid,
+                        task_gid: Some(task_gid),
+                        author: None,
+                        created_at: None,
                         title: format!("Comment on: {}", task_name.as_deref().unwrap_or("(unknown task)")),
                         snippet: snippet.unwrap_or_default(),
                         rank: row.get(4)?,
-                        asana_url: stored_url.or_else(|| Some(format!("https://app.asana.com/0/0/{task_gid}"))),
                     })
                 })?;
                 for row in rows {
@@ -171,23 +355,34 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
             }
 
             // Search projects
-            if hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::Project) {
+            if !tasks_only
+                && (hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::Project))
+            {
                 let sql =
-                    "SELECT p.project_gid, p.name, snippet(projects_fts, 1, '<b>', '</b>', '...', 32) as snip, projects_fts.rank, p.permalink_url
+                    "SELECT p.project_gid, p.name, snippet(projects_fts, 1, '<b>', '</b>', '...', 32) as snip, projects_fts.rank, p.permalink_url, p.is_template
                      FROM projects_fts
                      JOIN dim_projects p ON p.id = projects_fts.rowid
-                     WHERE projects_fts MATCH ?1
-                     ORDER BY rank LIMIT ?2";
-                let mut stmt = conn.prepare(sql)?;
+                     WHERE projects_fts MATCH ?1";
+                total_unlimited += count_matches(conn, sql, &[&query_owned])?;
+                let sql = format!("{sql} ORDER BY rank LIMIT ?2");
+                let mut stmt = conn.prepare(&sql)?;
                 let rows = stmt.query_map(rusqlite::params![query_owned, options_limit], |row| {
                     let gid: String = row.get(0)?;
                     let snippet: Option<String> = row.get(2)?;
                     let stored_url: Option<String> = row.get(4)?;
+                    let name: String = row.get(1)?;
+                    let is_template: Option<bool> = row.get(5)?;
                     Ok(SearchHit {
                         hit_type: SearchHitType::Project,
                         gid: gid.clone(),
                         task_gid: None,
-                        title: row.get(1)?,
+                        author: None,
+                        created_at: None,
+                        title: if is_template == Some(true) {
+                            format!("{name} [template]")
+                        } else {
+                            name
+                        },
                         snippet: snippet.unwrap_or_default(),
                         rank: row.get(3)?,
                         asana_url: stored_url.or_else(|| Some(format!("https://app.asana.com/0/{gid}"))),
@@ -199,14 +394,17 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
             }
 
             // Search portfolios
-            if hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::Portfolio) {
+            if !tasks_only
+                && (hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::Portfolio))
+            {
                 let sql =
                     "SELECT p.portfolio_gid, p.name, snippet(portfolios_fts, 1, '<b>', '</b>', '...', 32) as snip, portfolios_fts.rank, p.permalink_url
                      FROM portfolios_fts
                      JOIN dim_portfolios p ON p.rowid = portfolios_fts.rowid
-                     WHERE portfolios_fts MATCH ?1
-                     ORDER BY rank LIMIT ?2";
-                let mut stmt = conn.prepare(sql)?;
+                     WHERE portfolios_fts MATCH ?1";
+                total_unlimited += count_matches(conn, sql, &[&query_owned])?;
+                let sql = format!("{sql} ORDER BY rank LIMIT ?2");
+                let mut stmt = conn.prepare(&sql)?;
                 let rows = stmt.query_map(rusqlite::params![query_owned, options_limit], |row| {
                     let gid: String = row.get(0)?;
                     let snippet: Option<String> = row.get(2)?;
@@ -215,6 +413,8 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                         hit_type: SearchHitType::Portfolio,
                         gid: gid.clone(),
                         task_gid: None,
+                        author: None,
+                        created_at: None,
                         title: row.get(1)?,
                         snippet: snippet.unwrap_or_default(),
                         rank: row.get(3)?,
@@ -226,13 +426,115 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                 }
             }
 
+            // Search project briefs
+            if !tasks_only
+                && (hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::ProjectBrief))
+            {
+                let mut sql = String::from(
+                    "SELECT b.project_gid, p.name, snippet(project_briefs_fts, -1, '<b>', '</b>', '...', 32) as snip, project_briefs_fts.rank, b.permalink_url
+                     FROM project_briefs_fts
+                     JOIN dim_project_briefs b ON b.rowid = project_briefs_fts.rowid
+                     LEFT JOIN dim_projects p ON p.project_gid = b.project_gid
+                     WHERE project_briefs_fts MATCH ?1",
+                );
+                let mut params: Vec<Box<dyn rusqlite::types::ToSql>> =
+                    vec![Box::new(query_owned.clone())];
+                if let Some(ref project) = project_filter {
+                    let param_idx = params.len() + 1;
+                    sql.push_str(&format!(" AND b.project_gid = ?{param_idx}"));
+                    params.push(Box::new(project.clone()));
+                }
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                total_unlimited += count_matches(conn, &sql, &param_refs)?;
+                sql.push_str(&format!(" ORDER BY rank LIMIT ?{}", params.len() + 1));
+                params.push(Box::new(options_limit));
+
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(param_refs.as_slice(), |row| {
+                    let gid: String = row.get(0)?;
+                    let project_name: Option<String> = row.get(1)?;
+                    let snippet: Option<String> = row.get(2)?;
+                    let stored_url: Option<String> = row.get(4)?;
+                    Ok(SearchHit {
+                        hit_type: SearchHitType::ProjectBrief,
+                        gid: gid.clone(),
+                        task_gid: None,
+                        author: None,
+                        created_at: None,
+                        title: format!("Brief: {}", project_name.as_deref().unwrap_or("(unknown project)")),
+                        snippet: snippet.unwrap_or_default(),
+                        rank: row.get(3)?,
+                        asana_url: stored_url.or_else(|| Some(format!("https://app.asana.com/0/{gid}"))),
+                    })
+                })?;
+                for row in rows {
+                    all_hits.push(row?);
+                }
+            }
+
+            // Search status updates
+            if !tasks_only
+                && (hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::StatusUpdate))
+            {
+                let mut sql = String::from(
+                    "SELECT s.status_gid, s.title, snippet(status_updates_fts, -1, '<b>', '</b>', '...', 32) as snip, status_updates_fts.rank,
+                            u.name, s.created_at, s.parent_gid, COALESCE(p.permalink_url, pf.permalink_url)
+                     FROM status_updates_fts
+                     JOIN fact_status_updates s ON s.rowid = status_updates_fts.rowid
+                     LEFT JOIN dim_users u ON u.user_gid = s.author_gid
+                     LEFT JOIN dim_projects p ON s.parent_type = 'project' AND p.project_gid = s.parent_gid
+                     LEFT JOIN dim_portfolios pf ON s.parent_type = 'portfolio' AND pf.portfolio_gid = s.parent_gid
+                     WHERE status_updates_fts MATCH ?1",
+                );
+                let mut params: Vec<Box<dyn rusqlite::types::ToSql>> =
+                    vec![Box::new(query_owned.clone())];
+                if let Some(ref project) = project_filter {
+                    let param_idx = params.len() + 1;
+                    sql.push_str(&format!(
+                        " AND s.parent_type = 'project' AND s.parent_gid = ?{param_idx}"
+                    ));
+                    params.push(Box::new(project.clone()));
+                }
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                total_unlimited += count_matches(conn, &sql, &param_refs)?;
+                sql.push_str(&format!(" ORDER BY rank LIMIT ?{}", params.len() + 1));
+                params.push(Box::new(options_limit));
+
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(param_refs.as_slice(), |row| {
+                    let snippet: Option<String> = row.get(2)?;
+                    let parent_gid: String = row.get(6)?;
+                    let parent_url: Option<String> = row.get(7)?;
+                    Ok(SearchHit {
+                        hit_type: SearchHitType::StatusUpdate,
+                        gid: row.get(0)?,
+                        task_gid: None,
+                        author: row.get(4)?,
+                        created_at: row.get(5)?,
+                        title: row.get(1)?,
+                        snippet: snippet.unwrap_or_default(),
+                        rank: row.get(3)?,
+                        asana_url: parent_url.or_else(|| Some(format!("https://app.asana.com/0/{parent_gid}"))),
+                    })
+                })?;
+                for row in rows {
+                    all_hits.push(row?);
+                }
+            }
+
             // Search custom fields
             if hit_type_filter.is_none() || hit_type_filter == Some(SearchHitType::CustomField) {
                 let mut sql = String::from(
                     "SELECT cff.task_gid, t.name, cff.field_name, cff.display_value, cff.rank, t.permalink_url
                      FROM custom_fields_fts cff
                      LEFT JOIN fact_tasks t ON t.task_gid = cff.task_gid
-                     WHERE custom_fields_fts MATCH ?1",
+                     WHERE custom_fields_fts MATCH ?1 AND COALESCE(t.is_deleted, 0) = 0",
                 );
                 let mut params: Vec<Box<dyn rusqlite::types::ToSql>> =
                     vec![Box::new(query_owned.clone())];
@@ -249,6 +551,14 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                     ));
                     params.push(Box::new(project.clone()));
                 }
+                if let Some(ref subtype) = task_filters.subtype {
+                    let param_idx = params.len() + 1;
+                    sql.push_str(&format!(" AND t.resource_subtype = ?{param_idx}"));
+                    params.push(Box::new(subtype.clone()));
+                }
+                let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
+                total_unlimited += count_matches(conn, &sql, &param_refs)?;
                 sql.push_str(&format!(" ORDER BY rank LIMIT ?{}", params.len() + 1));
                 params.push(Box::new(options_limit));
 
@@ -265,6 +575,8 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
                         hit_type: SearchHitType::CustomField,
                         gid: task_gid.clone(),
                         task_gid: Some(task_gid.clone()),
+                        author: None,
+                        created_at: None,
                         title: format!(
                             "{}: {} = {}",
                             task_name.as_deref().unwrap_or("(unknown task)"),
@@ -284,14 +596,17 @@ pub async fn search(db: &Database, query: &str, options: &SearchOptions) -> Resu
             // Sort all hits by rank (lower = more relevant in FTS5)
             all_hits.sort_by(|a, b| a.rank.partial_cmp(&b.rank).unwrap_or(std::cmp::Ordering::Equal));
 
-            // Trim to overall limit
-            all_hits.truncate(options_limit as usize);
+            // Apply the page window
+            all_hits.drain(..(offset as usize).min(all_hits.len()));
+            all_hits.truncate(page_limit as usize);
 
             let total = all_hits.len();
             Ok::<SearchResults, rusqlite::Error>(SearchResults {
-                query: query_owned,
+                query: display_query,
                 hits: all_hits,
                 total,
+                total_unlimited,
+                offset,
             })
         })
         .await?;
@@ -337,9 +652,11 @@ mod tests {
 
         let options = SearchOptions {
             limit: Some(10),
+            offset: None,
             hit_type: None,
             assignee_gid: None,
             project_gid: None,
+            ..Default::default()
         };
 
         let results = search(&db, "login", &options).await.unwrap();
@@ -354,9 +671,11 @@ mod tests {
 
         let options = SearchOptions {
             limit: Some(10),
+            offset: None,
             hit_type: None,
             assignee_gid: None,
             project_gid: None,
+            ..Default::default()
         };
 
         let results = search(&db, "nonexistent", &options).await.unwrap();
@@ -388,9 +707,11 @@ mod tests {
         // Search for "widget" filtered to tasks only
         let options = SearchOptions {
             limit: Some(10),
+            offset: None,
             hit_type: Some(SearchHitType::Task),
             assignee_gid: None,
             project_gid: None,
+            ..Default::default()
         };
         let results = search(&db, "widget", &options).await.unwrap();
         assert!(results
@@ -401,9 +722,11 @@ mod tests {
         // Search for "widget" filtered to projects only
         let options = SearchOptions {
             limit: Some(10),
+            offset: None,
             hit_type: Some(SearchHitType::Project),
             assignee_gid: None,
             project_gid: None,
+            ..Default::default()
         };
         let results = search(&db, "widget", &options).await.unwrap();
         assert!(results
@@ -411,4 +734,348 @@ mod tests {
             .iter()
             .all(|h| h.hit_type == SearchHitType::Project));
     }
+
+    #[tokio::test]
+    async fn test_search_comment_deep_link() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', '111', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('222', 'Ship it', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('222', 'p1');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('333', '222', 'Rollback plan attached', 'comment', '2025-01-02', '2025-01-02', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let options = SearchOptions {
+            limit: Some(10),
+            offset: None,
+            hit_type: Some(SearchHitType::Comment),
+            assignee_gid: None,
+            project_gid: None,
+            ..Default::default()
+        };
+        let results = search(&db, "rollback", &options).await.unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(
+            results.hits[0].asana_url.as_deref(),
+            Some("https://app.asana.com/1/111/task/222/comment/333?focus=true")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_project_briefs() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'w1', datetime('now'))",
+                    [],
+                )?;
+                let brief = crate::sync::api_helpers::ProjectBriefInfo {
+                    gid: "b1".to_string(),
+                    title: Some("Launch brief".into()),
+                    text: Some("Goals: migrate billing before the holidays".into()),
+                    html_text: None,
+                    permalink_url: None,
+                };
+                crate::storage::repository::upsert_project_brief(conn, "p1", &brief)?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let options = SearchOptions {
+            limit: Some(10),
+            offset: None,
+            hit_type: Some(SearchHitType::ProjectBrief),
+            assignee_gid: None,
+            project_gid: None,
+            ..Default::default()
+        };
+        let results = search(&db, "billing", &options).await.unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].gid, "p1");
+        assert_eq!(results.hits[0].title, "Brief: Launch");
+
+        // Re-syncing the brief replaces its indexed text
+        db.writer()
+            .call(|conn| {
+                let brief = crate::sync::api_helpers::ProjectBriefInfo {
+                    gid: "b1".to_string(),
+                    title: Some("Launch brief".into()),
+                    text: Some("Goals: ship search".into()),
+                    html_text: None,
+                    permalink_url: None,
+                };
+                crate::storage::repository::upsert_project_brief(conn, "p1", &brief)
+            })
+            .await
+            .unwrap();
+        assert_eq!(search(&db, "billing", &options).await.unwrap().total, 0);
+        assert_eq!(search(&db, "search", &options).await.unwrap().total, 1);
+
+        db.writer()
+            .call(|conn| crate::storage::repository::delete_project_brief(conn, "p1"))
+            .await
+            .unwrap();
+        assert_eq!(search(&db, "search", &options).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_status_updates() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('u1', 'Alice', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'w1', datetime('now'));
+                     INSERT INTO fact_status_updates (status_gid, parent_gid, parent_type, author_gid,
+                         title, text, status_type, created_at, created_date_key, cached_at)
+                     VALUES ('s1', 'p1', 'project', 'u1', 'Week 3 update',
+                             'Vendor delay is a risk to the launch date', 'at_risk',
+                             '2025-01-08T10:00:00Z', '2025-01-08', datetime('now')),
+                            ('s2', 'p1', 'project', 'u1', 'Week 4 update',
+                             'Back on track', 'on_track',
+                             '2025-01-15T10:00:00Z', '2025-01-15', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let options = SearchOptions {
+            limit: Some(10),
+            offset: None,
+            hit_type: Some(SearchHitType::StatusUpdate),
+            assignee_gid: None,
+            project_gid: Some("p1".to_string()),
+            ..Default::default()
+        };
+        let results = search(&db, "risk", &options).await.unwrap();
+        assert_eq!(results.total, 1);
+        let hit = &results.hits[0];
+        assert_eq!(hit.gid, "s1");
+        assert_eq!(hit.title, "Week 3 update");
+        assert_eq!(hit.author.as_deref(), Some("Alice"));
+        assert_eq!(hit.created_at.as_deref(), Some("2025-01-08T10:00:00Z"));
+        assert_eq!(hit.asana_url.as_deref(), Some("https://app.asana.com/0/p1"));
+
+        // Re-synced updates are re-indexed
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "UPDATE fact_status_updates SET text = 'Vendor resolved' WHERE status_gid = 's1'",
+                    [],
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(search(&db, "risk", &options).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_across_types() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                for i in 0..5 {
+                    conn.execute(
+                        "INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                         VALUES (?1, ?2, '2025-01-01', '2025-01-01', datetime('now'))",
+                        rusqlite::params![format!("t{i}"), format!("Migration step {i}")],
+                    )?;
+                }
+                conn.execute(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Migration', 'w1', datetime('now'))",
+                    [],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let page = |offset| SearchOptions {
+            limit: Some(4),
+            offset: Some(offset),
+            hit_type: None,
+            assignee_gid: None,
+            project_gid: None,
+            ..Default::default()
+        };
+        let first = search(&db, "migration", &page(0)).await.unwrap();
+        assert_eq!(first.total, 4);
+        assert_eq!(first.total_unlimited, 6);
+        let second = search(&db, "migration", &page(4)).await.unwrap();
+        assert_eq!(second.total, 2);
+        assert_eq!(second.offset, 4);
+
+        // Pages don't overlap and together cover every match
+        let mut gids: Vec<String> = first
+            .hits
+            .iter()
+            .chain(&second.hits)
+            .map(|h| h.gid.clone())
+            .collect();
+        gids.sort();
+        gids.dedup();
+        assert_eq!(gids.len(), 6);
+
+        assert!(search(&db, "migration", &page(10))
+            .await
+            .unwrap()
+            .hits
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_query_syntax() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, email, cached_at)
+                     VALUES ('u1', 'Alice Smith', 'alice@example.com', datetime('now')),
+                            ('u2', 'Bob Jones', 'bob@example.com', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Widget Launch', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Rollout checklist', 'u1', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t2', 'Rollout draft', 'u1', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t3', 'Rollout review', 'u2', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('t1', 'p1');
+                     UPDATE fact_tasks SET resource_subtype = 'milestone' WHERE task_gid = 't3';",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let options = SearchOptions {
+            hit_type: Some(SearchHitType::Task),
+            ..Default::default()
+        };
+        let gids = |results: SearchResults| {
+            let mut gids: Vec<String> = results.hits.into_iter().map(|h| h.gid).collect();
+            gids.sort();
+            gids
+        };
+
+        let results = search(&db, "roll* assignee:alice -draft", &options)
+            .await
+            .unwrap();
+        assert_eq!(results.query, "roll* assignee:alice -draft");
+        assert_eq!(gids(results), ["t1"]);
+
+        let results = search(&db, "rollout project:widget", &options)
+            .await
+            .unwrap();
+        assert_eq!(gids(results), ["t1"]);
+
+        let results = search(&db, "checklist OR review", &options).await.unwrap();
+        assert_eq!(gids(results), ["t1", "t3"]);
+
+        let results = search(&db, "rollout subtype:Milestone", &options)
+            .await
+            .unwrap();
+        assert_eq!(gids(results), ["t3"]);
+        let milestones = SearchOptions {
+            subtype: Some("milestone".to_string()),
+            ..Default::default()
+        };
+        let results = search(&db, "widget OR rollout", &milestones).await.unwrap();
+        assert_eq!(gids(results), ["t3"]);
+        let results = search(&db, "rollout subtype:approval", &milestones)
+            .await
+            .unwrap();
+        assert_eq!(results.total, 0);
+
+        // FTS5 syntax in the query is searched literally rather than erroring
+        let results = search(&db, "rollout AND (", &options).await.unwrap();
+        assert_eq!(results.total, 0);
+
+        // A field filter that matches nothing, or contradicts the options
+        let results = search(&db, "rollout assignee:carol", &options)
+            .await
+            .unwrap();
+        assert_eq!(results.total, 0);
+        let options = SearchOptions {
+            assignee_gid: Some("u2".to_string()),
+            ..options
+        };
+        let results = search(&db, "rollout assignee:alice", &options)
+            .await
+            .unwrap();
+        assert_eq!(results.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_date_and_status_filters() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO fact_tasks (task_gid, name, is_completed, created_at, created_date_key, modified_at, cached_at)
+                     VALUES ('t1', 'Plan migration', 0, '2025-01-10T09:00:00.000Z', '2025-01-10', '2025-04-02T12:00:00.000Z', datetime('now')),
+                            ('t2', 'Run migration', 1, '2025-02-10T09:00:00.000Z', '2025-02-10', '2025-02-11T12:00:00.000Z', datetime('now')),
+                            ('t3', 'Old migration', 0, '2024-11-01T09:00:00.000Z', '2024-11-01', '2024-11-02T12:00:00.000Z', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't2', 'migration finished', 'comment', '2025-02-11T12:00:00.000Z', '2025-02-11', datetime('now')),
+                            ('c2', 't1', 'migration scoped', 'comment', '2025-01-12T12:00:00.000Z', '2025-01-12', datetime('now'));",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let gids = |results: SearchResults| {
+            let mut gids: Vec<String> = results.hits.into_iter().map(|h| h.gid).collect();
+            gids.sort();
+            gids
+        };
+
+        // Open tasks (and comments on them) created this quarter
+        let options = SearchOptions {
+            completed: Some(false),
+            created_after: Some("2025-01-01".to_string()),
+            created_before: Some("2025-03-31".to_string()),
+            ..Default::default()
+        };
+        let results = search(&db, "migration", &options).await.unwrap();
+        assert_eq!(gids(results), ["c2", "t1"]);
+
+        let options = SearchOptions {
+            completed: Some(true),
+            ..Default::default()
+        };
+        let results = search(&db, "migration", &options).await.unwrap();
+        assert_eq!(gids(results), ["c1", "t2"]);
+
+        // Modification bounds compare by day
+        let options = SearchOptions {
+            hit_type: Some(SearchHitType::Task),
+            modified_after: Some("2025-02-11".to_string()),
+            modified_before: Some("2025-04-02".to_string()),
+            ..Default::default()
+        };
+        let results = search(&db, "migration", &options).await.unwrap();
+        assert_eq!(gids(results), ["t1", "t2"]);
+    }
 }
@@ -0,0 +1,219 @@
+//! Translation of user search syntax into safe FTS5 `MATCH` expressions.
+//!
+//! Supported syntax:
+//! - bare words, matched as whole terms (all must match)
+//! - `"quoted phrases"`
+//! - `word*` and `"phrase"*` for prefix matching
+//! - `-word` and `-"phrase"` to exclude matches
+//! - `OR` between terms or phrases
+//! - `assignee:<name, email, or GID>`, `project:<name or GID>`, and
+//!   `subtype:<default_task, milestone, or approval>` filters
+//!
+//! Every term is emitted as a quoted FTS5 string, so punctuation and FTS5
+//! keywords in user input can never produce a syntax error.
+
+/// A compiled search query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompiledQuery {
+    /// FTS5 MATCH expression, or `None` if the query has no positive terms
+    /// (FTS5 cannot match on exclusions alone).
+    pub match_expr: Option<String>,
+    /// Value of an `assignee:` filter.
+    pub assignee: Option<String>,
+    /// Value of a `project:` filter.
+    pub project: Option<String>,
+    /// Value of a `subtype:` filter.
+    pub subtype: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Term { text: String, prefix: bool },
+    Or,
+}
+
+/// Compile user search syntax. Never fails: unbalanced quotes run to the
+/// end of the input, and unknown `field:` prefixes are searched as text.
+pub fn compile_query(input: &str) -> CompiledQuery {
+    let mut compiled = CompiledQuery::default();
+    let mut positives: Vec<Token> = Vec::new();
+    let mut negatives: Vec<String> = Vec::new();
+
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else { break };
+
+        let negated = first == '-';
+        if negated {
+            chars.next();
+        }
+
+        // Read one word or quoted phrase; a word may be `field:"quoted value"`
+        let mut text = String::new();
+        // Length of `text` when the first quote opened
+        let mut quote_at: Option<usize> = None;
+        while let Some(&c) = chars.peek() {
+            if c == '"' {
+                chars.next();
+                quote_at.get_or_insert(text.len());
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    text.push(c);
+                }
+            } else if c.is_whitespace() {
+                break;
+            } else {
+                text.push(c);
+                chars.next();
+            }
+        }
+
+        if !negated {
+            if text == "OR" && quote_at.is_none() {
+                positives.push(Token::Or);
+                continue;
+            }
+            let field_end = text.find(':').filter(|&i| quote_at.is_none_or(|q| i < q));
+            if let Some((field, value)) = field_end.map(|i| (&text[..i], &text[i + 1..])) {
+                let slot = match field.to_lowercase().as_str() {
+                    "assignee" => Some(&mut compiled.assignee),
+                    "project" => Some(&mut compiled.project),
+                    "subtype" => Some(&mut compiled.subtype),
+                    _ => None,
+                };
+                if let Some(slot) = slot {
+                    if !value.is_empty() {
+                        *slot = Some(value.to_string());
+                    }
+                    continue;
+                }
+            }
+        }
+
+        let prefix = text.ends_with('*');
+        let text = text.trim_end_matches('*').trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        if negated {
+            negatives.push(fts_string(&text, prefix));
+        } else {
+            positives.push(Token::Term { text, prefix });
+        }
+    }
+
+    compiled.match_expr = positive_expr(&positives).map(|expr| {
+        if negatives.is_empty() {
+            expr
+        } else {
+            // NOT binds tighter than OR in FTS5, so group the positives
+            format!("({expr}) NOT {}", negatives.join(" NOT "))
+        }
+    });
+    compiled
+}
+
+/// Join positive terms with implicit AND, honouring `OR` between terms.
+/// Stray `OR`s (leading, trailing, or doubled) are dropped.
+fn positive_expr(tokens: &[Token]) -> Option<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut pending_or = false;
+    for token in tokens {
+        match token {
+            Token::Or => pending_or = !parts.is_empty(),
+            Token::Term { text, prefix } => {
+                if pending_or {
+                    parts.push("OR".to_string());
+                    pending_or = false;
+                }
+                parts.push(fts_string(text, *prefix));
+            }
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// Resolve an `assignee:` value to a user GID: an exact GID, email, or name
+/// wins over a name prefix or email local part.
+pub(crate) fn resolve_user(
+    conn: &rusqlite::Connection,
+    value: &str,
+) -> std::result::Result<Option<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT user_gid FROM dim_users
+         WHERE user_gid = ?1 OR LOWER(email) = LOWER(?1) OR LOWER(name) = LOWER(?1)
+            OR LOWER(name) LIKE LOWER(?1) || '%' OR LOWER(email) LIKE LOWER(?1) || '@%'
+         ORDER BY user_gid = ?1 DESC, LOWER(email) = LOWER(?1) DESC,
+                  LOWER(name) = LOWER(?1) DESC, name
+         LIMIT 1",
+    )?;
+    let mut rows = stmt.query([value])?;
+    rows.next()?.map(|row| row.get(0)).transpose()
+}
+
+/// Resolve a `project:` value to a project GID: an exact GID or name wins
+/// over a partial name match.
+pub(crate) fn resolve_project(
+    conn: &rusqlite::Connection,
+    value: &str,
+) -> std::result::Result<Option<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT project_gid FROM dim_projects
+         WHERE project_gid = ?1 OR LOWER(name) LIKE '%' || LOWER(?1) || '%'
+         ORDER BY project_gid = ?1 DESC, LOWER(name) = LOWER(?1) DESC, is_archived, name
+         LIMIT 1",
+    )?;
+    let mut rows = stmt.query([value])?;
+    rows.next()?.map(|row| row.get(0)).transpose()
+}
+
+/// Quote text as an FTS5 string, optionally as a prefix query.
+fn fts_string(text: &str, prefix: bool) -> String {
+    let star = if prefix { "*" } else { "" };
+    format!("\"{}\"{star}", text.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expr(input: &str) -> Option<String> {
+        compile_query(input).match_expr
+    }
+
+    #[test]
+    fn test_compile_query() {
+        assert_eq!(expr("launch plan").as_deref(), Some(r#""launch" "plan""#));
+        assert_eq!(
+            expr(r#""launch plan" deploy*"#).as_deref(),
+            Some(r#""launch plan" "deploy"*"#)
+        );
+        assert_eq!(
+            expr(r#"bug OR defect -wontfix -"known issue""#).as_deref(),
+            Some(r#"("bug" OR "defect") NOT "wontfix" NOT "known issue""#)
+        );
+
+        // FTS5 syntax in user input is searched literally
+        assert_eq!(
+            expr("NEAR(a b) c:d AND").as_deref(),
+            Some(r#""NEAR(a" "b)" "c:d" "AND""#)
+        );
+        assert_eq!(expr(r#"say "hi"#).as_deref(), Some(r#""say" "hi""#));
+        assert_eq!(expr("OR * - \"\"").as_deref(), None);
+
+        let q =
+            compile_query(r#"rollout assignee:alice project:"Widget Launch" subtype:milestone"#);
+        assert_eq!(q.match_expr.as_deref(), Some(r#""rollout""#));
+        assert_eq!(q.assignee.as_deref(), Some("alice"));
+        assert_eq!(q.project.as_deref(), Some("Widget Launch"));
+        assert_eq!(q.subtype.as_deref(), Some("milestone"));
+
+        // Exclusions alone can't be matched
+        let q = compile_query("-draft assignee:bob@example.com");
+        assert_eq!(q.match_expr, None);
+        assert_eq!(q.assignee.as_deref(), Some("bob@example.com"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Stories posted by integrations and automation rules. In automated
+//! projects they far outnumber human comments, so they are flagged
+//! `is_bot` and left out of collaboration metrics and search.
+
+use std::collections::BTreeSet;
+
+use rusqlite::{params, Connection};
+
+use crate::storage::{repository, threads};
+
+/// Config key listing bot authors: user GIDs, or name or email patterns
+/// where `*` matches anything (comma-separated, e.g. `Jira,*bot*`).
+pub const BOT_AUTHORS_KEY: &str = "bot_authors";
+
+/// Config key listing `story_type` values that are always bot noise
+/// (comma-separated).
+pub const BOT_STORY_TYPES_KEY: &str = "bot_story_types";
+
+/// Which stories count as bot noise.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BotFilter {
+    /// Lowercased author GIDs or patterns.
+    pub authors: Vec<String>,
+    /// Lowercased story types.
+    pub story_types: Vec<String>,
+}
+
+impl BotFilter {
+    /// The configured filter; empty when neither key is set.
+    pub fn load(conn: &Connection) -> Result<Self, rusqlite::Error> {
+        let list = |key| -> Result<Vec<String>, rusqlite::Error> {
+            Ok(repository::get_config(conn, key)?
+                .map(|v| parse_bot_list(&v))
+                .unwrap_or_default())
+        };
+        Ok(Self {
+            authors: list(BOT_AUTHORS_KEY)?,
+            story_types: list(BOT_STORY_TYPES_KEY)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty() && self.story_types.is_empty()
+    }
+
+    /// Whether a story of `story_type` by this author is bot noise.
+    pub fn is_bot(
+        &self,
+        story_type: &str,
+        author_gid: Option<&str>,
+        author_name: Option<&str>,
+        author_email: Option<&str>,
+    ) -> bool {
+        if self
+            .story_types
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(story_type))
+        {
+            return true;
+        }
+        let name = author_name.map(str::to_lowercase);
+        let email = author_email.map(str::to_lowercase);
+        self.authors.iter().any(|pattern| {
+            author_gid == Some(pattern.as_str())
+                || name.as_deref().is_some_and(|n| glob_match(pattern, n))
+                || email.as_deref().is_some_and(|e| glob_match(pattern, e))
+        })
+    }
+}
+
+/// Split a `bot_authors` or `bot_story_types` value into lowercased entries.
+pub fn parse_bot_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let [first, middle @ .., last] = parts.as_slice() else {
+        return pattern == text;
+    };
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Recompute `is_bot` for every story on a task. Returns the number of
+/// stories flagged.
+pub fn flag_bot_comments(
+    conn: &Connection,
+    task_gid: &str,
+    filter: &BotFilter,
+) -> Result<usize, rusqlite::Error> {
+    Ok(flag(conn, Some(task_gid), filter)?.0)
+}
+
+/// Recompute `is_bot` for every stored story, after `bot_authors` or
+/// `bot_story_types` changes, and relink reply threads on tasks whose
+/// stories changed. Returns the number of stories flagged.
+pub fn reflag_all_bot_comments(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    let filter = BotFilter::load(conn)?;
+    let (flagged, changed_tasks) = flag(conn, None, &filter)?;
+    for task_gid in &changed_tasks {
+        threads::link_comment_threads(conn, task_gid)?;
+    }
+    Ok(flagged)
+}
+
+/// Returns the number of stories flagged and the tasks whose flags changed.
+fn flag(
+    conn: &Connection,
+    task_gid: Option<&str>,
+    filter: &BotFilter,
+) -> Result<(usize, BTreeSet<String>), rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.task_gid, c.story_type, c.author_gid, u.name, u.email, c.is_bot
+         FROM fact_comments c
+         LEFT JOIN dim_users u ON u.user_gid = c.author_gid
+         WHERE ?1 IS NULL OR c.task_gid = ?1",
+    )?;
+    let stories: Vec<(i64, String, bool, bool)> = stmt
+        .query_map([task_gid], |row| {
+            let story_type: String = row.get(2)?;
+            let author_gid: Option<String> = row.get(3)?;
+            let name: Option<String> = row.get(4)?;
+            let email: Option<String> = row.get(5)?;
+            let is_bot = filter.is_bot(
+                &story_type,
+                author_gid.as_deref(),
+                name.as_deref(),
+                email.as_deref(),
+            );
+            Ok((row.get(0)?, row.get(1)?, is_bot, row.get::<_, i64>(6)? != 0))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Only changed rows are written, since each update re-indexes the story
+    let mut update = conn.prepare_cached("UPDATE fact_comments SET is_bot = ?2 WHERE id = ?1")?;
+    let mut flagged = 0;
+    let mut changed_tasks = BTreeSet::new();
+    for (id, task_gid, is_bot, was_bot) in stories {
+        if is_bot != was_bot {
+            update.execute(params![id, is_bot as i32])?;
+            changed_tasks.insert(task_gid);
+        }
+        if is_bot {
+            flagged += 1;
+        }
+    }
+    Ok((flagged, changed_tasks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[test]
+    fn test_bot_filter() {
+        let filter = BotFilter {
+            authors: parse_bot_list(" Jira Cloud, *bot*,9001,"),
+            story_types: parse_bot_list("Marked_Duplicate"),
+        };
+        assert_eq!(filter.authors, vec!["jira cloud", "*bot*", "9001"]);
+        assert!(filter.is_bot("comment", Some("1"), Some("Jira Cloud"), None));
+        assert!(filter.is_bot("comment", Some("1"), Some("Ada"), Some("robot@x.io")));
+        assert!(filter.is_bot("comment", Some("9001"), None, None));
+        assert!(filter.is_bot("marked_duplicate", None, None, None));
+        assert!(!filter.is_bot("comment", Some("1"), Some("Jira"), Some("ada@x.io")));
+        assert!(!filter.is_bot("comment", None, None, None));
+
+        assert!(glob_match("*bot", "asana bot"));
+        assert!(glob_match("a*c*e", "abcde"));
+        assert!(!glob_match("a*c*e", "abcd"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
+    #[tokio::test]
+    async fn test_flag_bot_comments() {
+        let db = Database::open_memory().await.unwrap();
+        let (flagged, indexed, cleared) = db
+            .writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO app_config (key, value, updated_at)
+                     VALUES ('bot_authors', 'Zapier*', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('u1', 'Ada', datetime('now')),
+                            ('u2', 'Zapier Integration', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Launch', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'u1', 'Deploy looks good', 'comment', '2025-01-02T00:00:00Z', '2025-01-02', datetime('now')),
+                            ('c2', 't1', 'u2', 'Deploy ticket created', 'comment', '2025-01-02T01:00:00Z', '2025-01-02', datetime('now'));",
+                )?;
+                let filter = BotFilter::load(conn)?;
+                let flagged = flag_bot_comments(conn, "t1", &filter)?;
+                let search = |conn: &Connection| {
+                    conn.query_row(
+                        "SELECT COUNT(*) FROM comments_fts WHERE comments_fts MATCH 'deploy'",
+                        [],
+                        |row| row.get::<_, i64>(0),
+                    )
+                };
+                let indexed = search(conn)?;
+                conn.execute("DELETE FROM app_config WHERE key = 'bot_authors'", [])?;
+                reflag_all_bot_comments(conn)?;
+                let cleared = search(conn)?;
+                Ok::<_, rusqlite::Error>((flagged, indexed, cleared))
+            })
+            .await
+            .unwrap();
+        assert_eq!(flagged, 1);
+        assert_eq!(indexed, 1);
+        assert_eq!(cleared, 2);
+
+        let checks = crate::search::index::verify_index(&db, false)
+            .await
+            .unwrap();
+        assert!(checks.iter().all(|c| c.in_sync));
+    }
+}
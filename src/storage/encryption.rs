@@ -0,0 +1,95 @@
+//! SQLCipher support (the `sqlcipher` feature): finding the database key
+//! and writing encrypted copies of plaintext databases.
+//!
+//! The key comes from `ASANADW_DB_KEY` when set, otherwise from the OS
+//! keychain (macOS Keychain, Windows Credential Manager, or the Secret
+//! Service on Linux), where `asanadw db encrypt --save-key` stores it.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::error::{Error, Result};
+use crate::keychain;
+
+/// Environment variable holding the database key.
+pub const KEY_ENV: &str = "ASANADW_DB_KEY";
+
+/// Keychain name the key is saved under.
+const KEYCHAIN_NAME: &str = "database-key";
+
+/// The database key from `ASANADW_DB_KEY` or the OS keychain, or `None`
+/// when neither has one.
+pub fn database_key() -> Result<Option<String>> {
+    if let Some(key) = std::env::var(KEY_ENV).ok().filter(|k| !k.is_empty()) {
+        return Ok(Some(key));
+    }
+    keychain::get(KEYCHAIN_NAME).map_err(|e| Error::Config(format!("keychain: {e}")))
+}
+
+/// Store `key` in the OS keychain for later opens.
+pub fn save_key(key: &str) -> Result<()> {
+    keychain::set(KEYCHAIN_NAME, key).map_err(|e| Error::Config(format!("keychain: {e}")))
+}
+
+/// Copy the database `conn` is open on into a new file at `dest`,
+/// encrypted with `key`.
+pub fn export_encrypted(conn: &Connection, dest: &Path, key: &str) -> rusqlite::Result<()> {
+    // sqlcipher_export reads the main file, so fold the WAL into it first
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        params![dest.to_string_lossy(), key],
+    )?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+        // sqlcipher_export doesn't copy user_version, which tracks migrations
+        .and_then(|_| conn.pragma_update(Some("encrypted"), "user_version", user_version));
+    conn.execute("DETACH DATABASE encrypted", [])?;
+    exported
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_export_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = Database::open_at(dir.path().join("plain.db"))
+            .await
+            .unwrap();
+        plain
+            .writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('1', 'Ada', '2026-01-01T00:00:00Z')",
+                    [],
+                )
+            })
+            .await
+            .unwrap();
+
+        let dest = dir.path().join("encrypted.db");
+        plain
+            .export_encrypted(dest.clone(), "s3cret".into())
+            .await
+            .unwrap();
+        assert!(plain
+            .export_encrypted(dest.clone(), "s3cret".into())
+            .await
+            .is_err());
+
+        assert!(Database::open_encrypted(&dest, "wrong").await.is_err());
+        assert!(Database::open_at(&dest).await.is_err());
+        let encrypted = Database::open_encrypted(&dest, "s3cret").await.unwrap();
+        let users: i64 = encrypted
+            .reader()
+            .call(|conn| conn.query_row("SELECT COUNT(*) FROM dim_users", [], |r| r.get(0)))
+            .await
+            .unwrap();
+        assert_eq!(users, 1);
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use rusqlite::{params, Connection};
+
+use crate::storage::repository;
+
+/// Config key listing the custom fields whose changes are recorded
+/// (comma-separated names, e.g. `Status,Priority`).
+pub const TRACKED_FIELDS_KEY: &str = "tracked_custom_fields";
+
+/// System story text for a custom field change, e.g.
+/// `changed Status from "In Progress" to "Blocked"` or `set Priority to High`.
+/// Quotes and the `from` value are optional.
+static RE_FIELD_SET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)^(?:changed|set) (?:the )?(.+?)(?: field)? (?:from "?(.*?)"? )?to "?(.*?)"?$"#,
+    )
+    .unwrap()
+});
+
+/// System story text for a cleared custom field, e.g. `cleared Status`.
+static RE_FIELD_CLEARED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)^(?:cleared|removed) (?:the )?(.+?)(?: field)?(?: \(was "?(.*?)"?\))?$"#)
+        .unwrap()
+});
+
+/// A custom field change parsed from a system story.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The field's name as spelled in `tracked_custom_fields`.
+    pub field_name: String,
+    pub from_value: Option<String>,
+    /// `None` when the field was cleared.
+    pub to_value: Option<String>,
+}
+
+/// Split a `tracked_custom_fields` value into field names.
+pub fn parse_tracked_fields(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// The configured tracked fields; empty when none are set.
+pub fn tracked_fields(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    Ok(repository::get_config(conn, TRACKED_FIELDS_KEY)?
+        .map(|v| parse_tracked_fields(&v))
+        .unwrap_or_default())
+}
+
+/// Parse a story's text as a change to one of `tracked` (matched
+/// case-insensitively). Returns `None` for any other story.
+pub fn parse_field_change(text: &str, tracked: &[String]) -> Option<FieldChange> {
+    let text = text.trim();
+    let (field, from_value, to_value) = if let Some(caps) = RE_FIELD_SET.captures(text) {
+        (
+            caps.get(1)?.as_str(),
+            caps.get(2).map(|m| m.as_str().to_string()),
+            Some(caps.get(3)?.as_str().to_string()),
+        )
+    } else {
+        let caps = RE_FIELD_CLEARED.captures(text)?;
+        (
+            caps.get(1)?.as_str(),
+            caps.get(2).map(|m| m.as_str().to_string()),
+            None,
+        )
+    };
+    let field_name = tracked.iter().find(|t| t.eq_ignore_ascii_case(field))?;
+    Some(FieldChange {
+        field_name: field_name.clone(),
+        from_value: from_value.filter(|v| !v.is_empty()),
+        to_value: to_value.filter(|v| !v.is_empty()),
+    })
+}
+
+/// Rebuild `fact_custom_field_changes` for a task from its stored system
+/// stories. Returns the number of changes recorded.
+pub fn rebuild_field_changes(
+    conn: &Connection,
+    task_gid: &str,
+    tracked: &[String],
+) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM fact_custom_field_changes WHERE task_gid = ?1",
+        [task_gid],
+    )?;
+    if tracked.is_empty() {
+        return Ok(0);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT comment_gid, text, created_at, created_date_key FROM fact_comments
+         WHERE task_gid = ?1 AND story_type != 'comment' AND text IS NOT NULL",
+    )?;
+    let stories: Vec<(String, String, String, String)> = stmt
+        .query_map([task_gid], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut count = 0;
+    for (story_gid, text, created_at, date_key) in &stories {
+        let Some(change) = parse_field_change(text, tracked) else {
+            continue;
+        };
+        conn.execute(
+            "INSERT OR REPLACE INTO fact_custom_field_changes (
+                story_gid, task_gid, field_name, from_value, to_value,
+                changed_at, changed_date_key, cached_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))",
+            params![
+                story_gid,
+                task_gid,
+                change.field_name,
+                change.from_value,
+                change.to_value,
+                created_at,
+                date_key,
+            ],
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Rebuild the change history of every task from stored stories, after
+/// `tracked_custom_fields` changes. Returns the number of changes recorded.
+pub fn rebuild_all_field_changes(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    let tracked = tracked_fields(conn)?;
+    conn.execute("DELETE FROM fact_custom_field_changes", [])?;
+    if tracked.is_empty() {
+        return Ok(0);
+    }
+    let task_gids: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT task_gid FROM fact_comments WHERE story_type != 'comment'")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut count = 0;
+    for task_gid in &task_gids {
+        count += rebuild_field_changes(conn, task_gid, &tracked)?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_field_change() {
+        let tracked = parse_tracked_fields("Status, Priority ,");
+        assert_eq!(tracked, vec!["Status", "Priority"]);
+
+        let change = parse_field_change(
+            r#"changed Status from "In Progress" to "Blocked""#,
+            &tracked,
+        )
+        .unwrap();
+        assert_eq!(change.field_name, "Status");
+        assert_eq!(change.from_value.as_deref(), Some("In Progress"));
+        assert_eq!(change.to_value.as_deref(), Some("Blocked"));
+
+        let change = parse_field_change("Changed priority to High", &tracked).unwrap();
+        assert_eq!(change.field_name, "Priority");
+        assert_eq!(change.from_value, None);
+        assert_eq!(change.to_value.as_deref(), Some("High"));
+
+        let change = parse_field_change("cleared Status", &tracked).unwrap();
+        assert_eq!(change.to_value, None);
+
+        assert!(parse_field_change(r#"changed Effort to "3""#, &tracked).is_none());
+        assert!(parse_field_change("changed the due date to Jan 5", &tracked).is_none());
+        assert!(parse_field_change("completed this task", &tracked).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+//! The `llm_usage` log of agent invocations, the `llm_parse_failures` log of
+//! replies that didn't parse, and rollups of both.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::query::period::Period;
+use crate::storage::Database;
+
+/// One agent invocation, as written to `llm_usage`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageRecord {
+    pub agent: String,
+    pub prompt_version: String,
+    pub provider: String,
+    pub model: String,
+    /// Answered from a cached result without calling the model.
+    pub cache_hit: bool,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    /// Token counts were estimated because the provider didn't report them.
+    pub tokens_estimated: bool,
+    pub latency_ms: Option<u64>,
+    /// Set when the call failed.
+    pub error: Option<String>,
+}
+
+/// A reply that didn't parse into an agent's expected JSON, as written to
+/// `llm_parse_failures`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseFailure {
+    pub agent: String,
+    pub prompt_version: String,
+    pub provider: String,
+    pub model: String,
+    /// 0 for the first reply, then 1.. for repair attempts.
+    pub attempt: u32,
+    pub error: String,
+    /// The raw reply.
+    pub response: String,
+}
+
+/// Usage for one agent and model over a period.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageSummary {
+    pub agent: String,
+    pub provider: String,
+    pub model: String,
+    /// Invocations, including cache hits and failures.
+    pub calls: u64,
+    pub cache_hits: u64,
+    pub failures: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Model calls whose token counts were estimated.
+    pub estimated_calls: u64,
+    /// Mean latency of model calls (cache hits excluded).
+    pub avg_latency_ms: Option<f64>,
+    /// Replies that didn't parse, including ones a repair attempt fixed.
+    pub parse_failures: u64,
+}
+
+pub fn record_usage(
+    conn: &Connection,
+    record: &UsageRecord,
+) -> std::result::Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO llm_usage (agent, prompt_version, provider, model, cache_hit,
+            input_tokens, output_tokens, tokens_estimated, latency_ms, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![
+            record.agent,
+            record.prompt_version,
+            record.provider,
+            record.model,
+            record.cache_hit,
+            record.input_tokens.map(|n| n as i64),
+            record.output_tokens.map(|n| n as i64),
+            record.tokens_estimated,
+            record.latency_ms.map(|n| n as i64),
+            record.error,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn record_parse_failure(
+    conn: &Connection,
+    failure: &ParseFailure,
+) -> std::result::Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO llm_parse_failures (agent, prompt_version, provider, model, attempt,
+            error, response, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![
+            failure.agent,
+            failure.prompt_version,
+            failure.provider,
+            failure.model,
+            failure.attempt,
+            failure.error,
+            failure.response,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Usage per agent, provider, and model for calls made on dates within
+/// `[start, end]` (`YYYY-MM-DD`), busiest first.
+pub fn summarize_usage(
+    conn: &Connection,
+    start: &str,
+    end: &str,
+) -> std::result::Result<Vec<UsageSummary>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT agent, provider, model, COUNT(*),
+                SUM(cache_hit),
+                SUM(error IS NOT NULL),
+                COALESCE(SUM(input_tokens), 0),
+                COALESCE(SUM(output_tokens), 0),
+                SUM(tokens_estimated),
+                AVG(CASE WHEN cache_hit = 0 THEN latency_ms END),
+                (SELECT COUNT(*) FROM llm_parse_failures f
+                 WHERE f.agent = u.agent AND f.provider = u.provider AND f.model = u.model
+                   AND substr(f.created_at, 1, 10) BETWEEN ?1 AND ?2)
+         FROM llm_usage u
+         WHERE substr(created_at, 1, 10) BETWEEN ?1 AND ?2
+         GROUP BY agent, provider, model
+         ORDER BY COALESCE(SUM(input_tokens), 0) + COALESCE(SUM(output_tokens), 0) DESC,
+                  COUNT(*) DESC, agent",
+    )?;
+    let rows = stmt.query_map(params![start, end], |row| {
+        Ok(UsageSummary {
+            agent: row.get(0)?,
+            provider: row.get(1)?,
+            model: row.get(2)?,
+            calls: row.get::<_, i64>(3)? as u64,
+            cache_hits: row.get::<_, i64>(4)? as u64,
+            failures: row.get::<_, i64>(5)? as u64,
+            input_tokens: row.get::<_, i64>(6)? as u64,
+            output_tokens: row.get::<_, i64>(7)? as u64,
+            estimated_calls: row.get::<_, i64>(8)? as u64,
+            avg_latency_ms: row.get(9)?,
+            parse_failures: row.get::<_, i64>(10)? as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+/// LLM usage over a period, per agent and model.
+pub async fn usage_summary(db: &Database, period: &Period) -> Result<Vec<UsageSummary>> {
+    let (start, end) = period.date_range();
+    let start = start.format("%Y-%m-%d").to_string();
+    let end = end.format("%Y-%m-%d").to_string();
+    let summary = db
+        .reader()
+        .call(move |conn| summarize_usage(conn, &start, &end))
+        .await?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_usage_summary() {
+        let db = Database::open_memory().await.unwrap();
+        let call = |cache_hit: bool, tokens: Option<(u64, u64)>, error: Option<&str>| UsageRecord {
+            agent: "task".to_string(),
+            prompt_version: "task-v2".to_string(),
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            cache_hit,
+            input_tokens: tokens.map(|t| t.0),
+            output_tokens: tokens.map(|t| t.1),
+            latency_ms: (!cache_hit).then_some(1000),
+            error: error.map(String::from),
+            ..Default::default()
+        };
+        let records = [
+            call(false, Some((1200, 300)), None),
+            call(false, Some((800, 100)), None),
+            call(true, None, None),
+            call(false, None, Some("timeout")),
+        ];
+        let failure = ParseFailure {
+            agent: "task".to_string(),
+            prompt_version: "task-v2".to_string(),
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            attempt: 0,
+            error: "expected value at line 1 column 1".to_string(),
+            response: "Sure! Here's the summary".to_string(),
+        };
+        db.writer()
+            .call(move |conn| {
+                for r in &records {
+                    record_usage(conn, r)?;
+                }
+                record_parse_failure(conn, &failure)?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let summary = usage_summary(&db, &Period::parse("7d").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(summary.len(), 1);
+        let s = &summary[0];
+        assert_eq!((s.calls, s.cache_hits, s.failures), (4, 1, 1));
+        assert_eq!((s.input_tokens, s.output_tokens), (2000, 400));
+        assert_eq!(s.avg_latency_ms, Some(1000.0));
+        assert_eq!(s.parse_failures, 1);
+
+        let old = usage_summary(&db, &Period::parse("2025-Q1").unwrap())
+            .await
+            .unwrap();
+        assert!(old.is_empty());
+    }
+}
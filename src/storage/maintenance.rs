@@ -0,0 +1,217 @@
+//! Database upkeep: size and row-count stats, integrity checks, VACUUM,
+//! ANALYZE, and merging full-text index segments.
+
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Files FTS5 keeps for each full-text table, named `<table>_<suffix>`.
+const FTS_SHADOW_SUFFIXES: &[&str] = &["data", "idx", "content", "docsize", "config"];
+
+/// Row count and on-disk size of one table. A full-text table's size
+/// includes its index storage.
+#[derive(Debug, Clone, Serialize)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+    /// Bytes used by the table and its indexes, or `None` when this SQLite
+    /// build lacks the `dbstat` table.
+    pub bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseStats {
+    pub page_size: i64,
+    pub page_count: i64,
+    /// Pages freed by deletes but not yet returned to the OS by VACUUM.
+    pub freelist_count: i64,
+    pub size_bytes: i64,
+    pub free_bytes: i64,
+    /// Largest first.
+    pub tables: Vec<TableStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VacuumReport {
+    pub size_before: i64,
+    pub size_after: i64,
+    /// Full-text tables whose index segments were merged first.
+    pub fts_optimized: Vec<String>,
+}
+
+fn pragma_i64(conn: &Connection, pragma: &str) -> rusqlite::Result<i64> {
+    conn.query_row(&format!("PRAGMA {pragma}"), [], |row| row.get(0))
+}
+
+fn database_size(conn: &Connection) -> rusqlite::Result<i64> {
+    Ok(pragma_i64(conn, "page_count")? * pragma_i64(conn, "page_size")?)
+}
+
+/// Names of the FTS5 tables.
+pub fn fts_tables(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND sql LIKE 'CREATE VIRTUAL TABLE%USING fts5%'
+         ORDER BY name",
+    )?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// The full-text table a shadow table belongs to, if any.
+fn fts_owner<'a>(table: &str, fts: &'a [String]) -> Option<&'a String> {
+    fts.iter().find(|f| {
+        table
+            .strip_prefix(f.as_str())
+            .and_then(|rest| rest.strip_prefix('_'))
+            .is_some_and(|suffix| FTS_SHADOW_SUFFIXES.contains(&suffix))
+    })
+}
+
+/// Bytes per table (indexes included), from the `dbstat` virtual table.
+fn table_bytes(conn: &Connection) -> rusqlite::Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT m.tbl_name, SUM(s.pgsize)
+         FROM dbstat s JOIN sqlite_master m ON m.name = s.name
+         GROUP BY m.tbl_name",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+pub fn stats(conn: &Connection) -> rusqlite::Result<DatabaseStats> {
+    let page_size = pragma_i64(conn, "page_size")?;
+    let page_count = pragma_i64(conn, "page_count")?;
+    let freelist_count = pragma_i64(conn, "freelist_count")?;
+
+    let fts = fts_tables(conn)?;
+    let names: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    // dbstat is a compile-time option; report sizes only when it's there
+    let bytes = table_bytes(conn).ok();
+
+    let mut tables: Vec<TableStats> = Vec::new();
+    for name in names.iter().filter(|n| fts_owner(n, &fts).is_none()) {
+        let rows: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+            row.get(0)
+        })?;
+        tables.push(TableStats {
+            name: name.clone(),
+            rows,
+            bytes: bytes.as_ref().map(|_| 0),
+        });
+    }
+    if let Some(bytes) = &bytes {
+        for (table, size) in bytes {
+            let owner = fts_owner(table, &fts).unwrap_or(table);
+            if let Some(t) = tables.iter_mut().find(|t| &t.name == owner) {
+                *t.bytes.get_or_insert(0) += size;
+            }
+        }
+    }
+    tables.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.name.cmp(&b.name)));
+
+    Ok(DatabaseStats {
+        page_size,
+        page_count,
+        freelist_count,
+        size_bytes: page_size * page_count,
+        free_bytes: page_size * freelist_count,
+        tables,
+    })
+}
+
+/// Run `PRAGMA integrity_check`, returning the problems it finds (empty when
+/// the database is intact).
+pub fn integrity_check(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let problems: Vec<String> = rows.collect::<rusqlite::Result<_>>()?;
+    Ok(problems.into_iter().filter(|p| p != "ok").collect())
+}
+
+/// Merge each full-text index's segments into one, dropping the space
+/// left behind by updated and deleted rows. Returns the tables optimized.
+pub fn optimize_fts(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let fts = fts_tables(conn)?;
+    for table in &fts {
+        conn.execute(
+            &format!("INSERT INTO \"{table}\"(\"{table}\") VALUES ('optimize')"),
+            [],
+        )?;
+    }
+    Ok(fts)
+}
+
+/// Rebuild the database file to reclaim free pages, optionally optimizing
+/// the full-text indexes first. Must not run inside a transaction.
+pub fn vacuum(conn: &Connection, optimize_fts_first: bool) -> rusqlite::Result<VacuumReport> {
+    let size_before = database_size(conn)?;
+    let fts_optimized = if optimize_fts_first {
+        optimize_fts(conn)?
+    } else {
+        Vec::new()
+    };
+    conn.execute_batch("VACUUM")?;
+    // Shrink the WAL file too, or the space VACUUM freed just moves there
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(VacuumReport {
+        size_before,
+        size_after: database_size(conn)?,
+        fts_optimized,
+    })
+}
+
+/// Refresh the statistics SQLite's query planner uses to pick indexes.
+pub fn analyze(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ANALYZE")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_maintenance() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('1', 'Ada', '2026-01-01T00:00:00Z')",
+                    [],
+                )
+            })
+            .await
+            .unwrap();
+
+        let stats = db.stats().await.unwrap();
+        assert!(stats.size_bytes > 0);
+        let users = stats.tables.iter().find(|t| t.name == "dim_users").unwrap();
+        assert_eq!(users.rows, 1);
+        assert!(stats.tables.iter().any(|t| t.name == "tasks_fts"));
+        assert!(!stats.tables.iter().any(|t| t.name == "tasks_fts_data"));
+
+        assert!(db.integrity_check().await.unwrap().is_empty());
+        db.analyze().await.unwrap();
+        let report = db.vacuum(true).await.unwrap();
+        assert!(report.fts_optimized.contains(&"tasks_fts".to_string()));
+    }
+
+    #[test]
+    fn test_fts_owner() {
+        let fts = vec!["tasks_fts".to_string()];
+        assert_eq!(fts_owner("tasks_fts_data", &fts), Some(&fts[0]));
+        assert_eq!(fts_owner("tasks_fts", &fts), None);
+        assert_eq!(fts_owner("tasks_fts_other", &fts), None);
+    }
+}
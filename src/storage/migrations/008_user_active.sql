@@ -0,0 +1,3 @@
+-- Track whether a user is still an active member of the workspace so
+-- work assigned to deactivated accounts can be surfaced as orphaned.
+ALTER TABLE dim_users ADD COLUMN is_active INTEGER NOT NULL DEFAULT 1;
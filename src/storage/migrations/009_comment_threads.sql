@@ -0,0 +1,4 @@
+-- Comment threading: a reply points at the earlier comment it responds to.
+-- Populated by sync from story links, leading @mentions, and quoted text.
+ALTER TABLE fact_comments ADD COLUMN parent_comment_gid TEXT;
+CREATE INDEX idx_comments_parent ON fact_comments(parent_comment_gid);
@@ -0,0 +1,14 @@
+-- Section moves parsed from system stories, used for cycle time and
+-- per-section dwell time. Rebuilt per task from fact_comments on sync.
+CREATE TABLE fact_section_transitions (
+    story_gid TEXT PRIMARY KEY,
+    task_gid TEXT NOT NULL,
+    project_name TEXT,
+    from_section TEXT,
+    to_section TEXT NOT NULL,
+    transitioned_at TEXT NOT NULL,
+    transitioned_date_key TEXT NOT NULL,
+    cached_at TEXT NOT NULL,
+    FOREIGN KEY (task_gid) REFERENCES fact_tasks(task_gid) ON DELETE CASCADE
+);
+CREATE INDEX idx_transitions_task ON fact_section_transitions(task_gid, transitioned_at);
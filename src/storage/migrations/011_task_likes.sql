@@ -0,0 +1,11 @@
+-- Who liked each task. The Asana API exposes likes without timestamps,
+-- so the set is replaced wholesale each time a task is synced.
+CREATE TABLE fact_task_likes (
+    task_gid TEXT NOT NULL,
+    user_gid TEXT NOT NULL,
+    cached_at TEXT NOT NULL,
+    PRIMARY KEY (task_gid, user_gid),
+    FOREIGN KEY (task_gid) REFERENCES fact_tasks(task_gid) ON DELETE CASCADE,
+    FOREIGN KEY (user_gid) REFERENCES dim_users(user_gid)
+);
+CREATE INDEX idx_task_likes_user ON fact_task_likes(user_gid);
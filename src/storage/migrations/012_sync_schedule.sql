@@ -0,0 +1,15 @@
+-- Per-entity sync interval for the daemon (NULL = the sync_interval config default)
+ALTER TABLE monitored_entities ADD COLUMN sync_interval_minutes INTEGER;
+
+-- Daemon bookkeeping: when each monitored entity runs next and how its last run went.
+-- Timestamps are UTC in SQLite datetime() format so they compare as text.
+CREATE TABLE sync_schedule (
+    entity_key TEXT PRIMARY KEY,
+    next_run_at TEXT NOT NULL,
+    last_run_at TEXT,
+    last_status TEXT,
+    last_error TEXT,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (entity_key) REFERENCES monitored_entities(entity_key) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+-- Recurring exports run by the daemon. `tables` is a comma-separated list
+-- (NULL = every warehouse table); `out` is a local directory or s3:// URL.
+-- Timestamps are UTC in SQLite datetime() format so they compare as text.
+CREATE TABLE export_schedules (
+    schedule_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    format TEXT NOT NULL,
+    tables TEXT,
+    cron TEXT NOT NULL,
+    out TEXT NOT NULL,
+    next_run_at TEXT NOT NULL,
+    last_run_at TEXT,
+    last_status TEXT,
+    last_error TEXT,
+    created_at TEXT NOT NULL
+);
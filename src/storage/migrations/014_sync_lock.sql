@@ -0,0 +1,10 @@
+-- At most one process syncs a database at a time. The holder refreshes
+-- heartbeat_at while it runs; a lock with an old heartbeat is abandoned.
+-- Timestamps are UTC in SQLite datetime() format so they compare as text.
+CREATE TABLE sync_lock (
+    lock_id INTEGER PRIMARY KEY CHECK (lock_id = 1),
+    owner TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    acquired_at TEXT NOT NULL,
+    heartbeat_at TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Projects that synced tasks belong to but that are not monitored.
+-- status is 'pending' until `monitor review` promotes or dismisses it.
+CREATE TABLE discovered_projects (
+    project_gid TEXT PRIMARY KEY,
+    project_name TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL
+);
+CREATE INDEX idx_discovered_projects_status ON discovered_projects(status);
@@ -0,0 +1,3 @@
+-- Asana task subtype: default_task, milestone, or approval
+ALTER TABLE fact_tasks ADD COLUMN resource_subtype TEXT NOT NULL DEFAULT 'default_task';
+CREATE INDEX idx_tasks_resource_subtype ON fact_tasks(resource_subtype);
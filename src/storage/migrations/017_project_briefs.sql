@@ -0,0 +1,39 @@
+-- Project briefs (one per project) with FTS5 so search can find their content.
+
+CREATE TABLE dim_project_briefs (
+    project_gid TEXT PRIMARY KEY,
+    brief_gid TEXT NOT NULL,
+    title TEXT,
+    text TEXT,
+    html_text TEXT,
+    permalink_url TEXT,
+    cached_at TEXT NOT NULL
+);
+
+CREATE VIRTUAL TABLE project_briefs_fts USING fts5(
+    project_gid,
+    title,
+    text,
+    content='dim_project_briefs',
+    content_rowid='rowid',
+    tokenize='porter unicode61'
+);
+
+-- Triggers: dim_project_briefs <-> project_briefs_fts
+
+CREATE TRIGGER project_briefs_ai AFTER INSERT ON dim_project_briefs BEGIN
+    INSERT INTO project_briefs_fts(rowid, project_gid, title, text)
+    VALUES (NEW.rowid, NEW.project_gid, COALESCE(NEW.title, ''), COALESCE(NEW.text, ''));
+END;
+
+CREATE TRIGGER project_briefs_ad AFTER DELETE ON dim_project_briefs BEGIN
+    INSERT INTO project_briefs_fts(project_briefs_fts, rowid, project_gid, title, text)
+    VALUES ('delete', OLD.rowid, OLD.project_gid, COALESCE(OLD.title, ''), COALESCE(OLD.text, ''));
+END;
+
+CREATE TRIGGER project_briefs_au AFTER UPDATE ON dim_project_briefs BEGIN
+    INSERT INTO project_briefs_fts(project_briefs_fts, rowid, project_gid, title, text)
+    VALUES ('delete', OLD.rowid, OLD.project_gid, COALESCE(OLD.title, ''), COALESCE(OLD.text, ''));
+    INSERT INTO project_briefs_fts(rowid, project_gid, title, text)
+    VALUES (NEW.rowid, NEW.project_gid, COALESCE(NEW.title, ''), COALESCE(NEW.text, ''));
+END;
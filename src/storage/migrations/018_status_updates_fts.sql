@@ -0,0 +1,34 @@
+-- Add FTS5 for status updates so they appear in search results.
+
+CREATE VIRTUAL TABLE status_updates_fts USING fts5(
+    status_gid,
+    title,
+    text,
+    content='fact_status_updates',
+    content_rowid='rowid',
+    tokenize='porter unicode61'
+);
+
+-- Triggers: fact_status_updates <-> status_updates_fts
+
+CREATE TRIGGER status_updates_ai AFTER INSERT ON fact_status_updates BEGIN
+    INSERT INTO status_updates_fts(rowid, status_gid, title, text)
+    VALUES (NEW.rowid, NEW.status_gid, COALESCE(NEW.title, ''), COALESCE(NEW.text, ''));
+END;
+
+CREATE TRIGGER status_updates_ad AFTER DELETE ON fact_status_updates BEGIN
+    INSERT INTO status_updates_fts(status_updates_fts, rowid, status_gid, title, text)
+    VALUES ('delete', OLD.rowid, OLD.status_gid, COALESCE(OLD.title, ''), COALESCE(OLD.text, ''));
+END;
+
+CREATE TRIGGER status_updates_au AFTER UPDATE ON fact_status_updates BEGIN
+    INSERT INTO status_updates_fts(status_updates_fts, rowid, status_gid, title, text)
+    VALUES ('delete', OLD.rowid, OLD.status_gid, COALESCE(OLD.title, ''), COALESCE(OLD.text, ''));
+    INSERT INTO status_updates_fts(rowid, status_gid, title, text)
+    VALUES (NEW.rowid, NEW.status_gid, COALESCE(NEW.title, ''), COALESCE(NEW.text, ''));
+END;
+
+-- Backfill existing status updates into FTS
+INSERT INTO status_updates_fts(rowid, status_gid, title, text)
+SELECT rowid, status_gid, COALESCE(title, ''), COALESCE(text, '')
+FROM fact_status_updates;
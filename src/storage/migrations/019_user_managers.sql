@@ -0,0 +1,5 @@
+-- Optional reporting line for org-chart rollups. Asana has no manager
+-- field, so this is filled by `asanadw import org-chart` and left alone
+-- by sync.
+ALTER TABLE dim_users ADD COLUMN manager_gid TEXT;
+CREATE INDEX idx_users_manager ON dim_users(manager_gid);
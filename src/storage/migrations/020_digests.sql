@@ -0,0 +1,13 @@
+-- Cached weekly digests: one user's summary plus their monitored projects
+-- and portfolios for a period, stored as structured JSON and rendered.
+
+CREATE TABLE fact_digests (
+    user_gid TEXT NOT NULL,
+    period_key TEXT NOT NULL,
+    digest_json TEXT NOT NULL,
+    markdown TEXT NOT NULL,
+    html TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    PRIMARY KEY (user_gid, period_key)
+);
@@ -0,0 +1,30 @@
+-- Risks the LLM found in status updates and comment threads.
+
+CREATE TABLE fact_risk_flags (
+    id INTEGER PRIMARY KEY,
+    source_type TEXT NOT NULL,
+    source_gid TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_gid TEXT NOT NULL,
+    entity_name TEXT,
+    severity TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    evidence TEXT NOT NULL,
+    task_gids TEXT NOT NULL,
+    source_date_key TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    detected_at TEXT NOT NULL
+);
+CREATE INDEX idx_risk_flags_source ON fact_risk_flags(source_type, source_gid);
+CREATE INDEX idx_risk_flags_date ON fact_risk_flags(source_date_key);
+
+-- Sources already scanned, so only new status updates and threads with new
+-- comments are sent to the LLM again.
+CREATE TABLE risk_scans (
+    source_type TEXT NOT NULL,
+    source_gid TEXT NOT NULL,
+    activity_at TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    scanned_at TEXT NOT NULL,
+    PRIMARY KEY (source_type, source_gid)
+);
@@ -0,0 +1,19 @@
+-- One row per agent invocation, including cache hits and failed calls, so
+-- LLM spend can be tracked per agent and model.
+
+CREATE TABLE llm_usage (
+    id INTEGER PRIMARY KEY,
+    agent TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    cache_hit INTEGER NOT NULL DEFAULT 0,
+    input_tokens INTEGER,
+    output_tokens INTEGER,
+    -- 1 when the provider didn't report usage and counts were estimated
+    tokens_estimated INTEGER NOT NULL DEFAULT 0,
+    latency_ms INTEGER,
+    error TEXT,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX idx_llm_usage_created ON llm_usage(created_at);
@@ -0,0 +1,12 @@
+-- Resumable task listings. A full project sync stages each page of tasks
+-- here and records the offset of the next page on its job, so a listing cut
+-- short by rate limiting picks up where it stopped instead of at page one.
+ALTER TABLE sync_jobs ADD COLUMN page_offset TEXT;
+ALTER TABLE sync_jobs ADD COLUMN pages_fetched INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE sync_job_pages (
+    job_id INTEGER NOT NULL,
+    page_index INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    PRIMARY KEY (job_id, page_index)
+);
@@ -0,0 +1,7 @@
+-- Fields beyond the built-in ones that an overridden summary prompt asked
+-- for (e.g. "customer_impact"), as a JSON object.
+ALTER TABLE fact_task_summaries ADD COLUMN extra_json TEXT;
+ALTER TABLE fact_user_period_summaries ADD COLUMN extra_json TEXT;
+ALTER TABLE fact_project_period_summaries ADD COLUMN extra_json TEXT;
+ALTER TABLE fact_portfolio_period_summaries ADD COLUMN extra_json TEXT;
+ALTER TABLE fact_team_period_summaries ADD COLUMN extra_json TEXT;
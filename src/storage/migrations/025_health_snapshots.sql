@@ -0,0 +1,13 @@
+-- Open-task health per project as of each day a sync ran, so metrics for
+-- past periods read what was measured then instead of today's task rows.
+
+CREATE TABLE fact_health_snapshots (
+    project_gid TEXT NOT NULL,
+    date_key TEXT NOT NULL,
+    overdue_count INTEGER NOT NULL,
+    unassigned_count INTEGER NOT NULL,
+    stale_count INTEGER NOT NULL,
+    total_open INTEGER NOT NULL,
+    captured_at TEXT NOT NULL,
+    PRIMARY KEY (project_gid, date_key)
+);
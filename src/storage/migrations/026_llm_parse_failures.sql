@@ -0,0 +1,17 @@
+-- Replies that didn't parse into the agent's expected JSON, with the raw
+-- text, kept for diagnosing prompts and models. A reply fixed by a later
+-- repair attempt is still recorded.
+
+CREATE TABLE llm_parse_failures (
+    id INTEGER PRIMARY KEY,
+    agent TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    -- 0 for the first reply, then 1.. for repair attempts
+    attempt INTEGER NOT NULL,
+    error TEXT NOT NULL,
+    response TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX idx_llm_parse_failures_created ON llm_parse_failures(created_at);
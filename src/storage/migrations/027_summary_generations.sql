@@ -0,0 +1,83 @@
+-- Keep every generation of a period summary instead of replacing it, so
+-- regenerated summaries can be listed and compared. Existing summaries
+-- become generation 1.
+
+CREATE TABLE fact_user_period_summaries_new (
+    user_gid TEXT NOT NULL,
+    period_key TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    headline TEXT NOT NULL,
+    what_changed TEXT NOT NULL,
+    why_it_matters TEXT NOT NULL,
+    key_accomplishments TEXT NOT NULL,
+    collaboration_notes TEXT,
+    prompt_version TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    extra_json TEXT,
+    PRIMARY KEY (user_gid, period_key, generation),
+    FOREIGN KEY (user_gid) REFERENCES dim_users(user_gid)
+);
+INSERT INTO fact_user_period_summaries_new (user_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, prompt_version, generated_at, extra_json, generation)
+SELECT user_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, collaboration_notes, prompt_version, generated_at, extra_json, 1 FROM fact_user_period_summaries;
+DROP TABLE fact_user_period_summaries;
+ALTER TABLE fact_user_period_summaries_new RENAME TO fact_user_period_summaries;
+
+CREATE TABLE fact_project_period_summaries_new (
+    project_gid TEXT NOT NULL,
+    period_key TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    headline TEXT NOT NULL,
+    what_changed TEXT NOT NULL,
+    why_it_matters TEXT NOT NULL,
+    key_milestones TEXT NOT NULL,
+    health_assessment TEXT,
+    prompt_version TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    extra_json TEXT,
+    PRIMARY KEY (project_gid, period_key, generation),
+    FOREIGN KEY (project_gid) REFERENCES dim_projects(project_gid)
+);
+INSERT INTO fact_project_period_summaries_new (project_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at, extra_json, generation)
+SELECT project_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at, extra_json, 1 FROM fact_project_period_summaries;
+DROP TABLE fact_project_period_summaries;
+ALTER TABLE fact_project_period_summaries_new RENAME TO fact_project_period_summaries;
+
+CREATE TABLE fact_portfolio_period_summaries_new (
+    portfolio_gid TEXT NOT NULL,
+    period_key TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    headline TEXT NOT NULL,
+    what_changed TEXT NOT NULL,
+    why_it_matters TEXT NOT NULL,
+    key_milestones TEXT NOT NULL,
+    health_assessment TEXT,
+    prompt_version TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    extra_json TEXT,
+    PRIMARY KEY (portfolio_gid, period_key, generation),
+    FOREIGN KEY (portfolio_gid) REFERENCES dim_portfolios(portfolio_gid)
+);
+INSERT INTO fact_portfolio_period_summaries_new (portfolio_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at, extra_json, generation)
+SELECT portfolio_gid, period_key, headline, what_changed, why_it_matters, key_milestones, health_assessment, prompt_version, generated_at, extra_json, 1 FROM fact_portfolio_period_summaries;
+DROP TABLE fact_portfolio_period_summaries;
+ALTER TABLE fact_portfolio_period_summaries_new RENAME TO fact_portfolio_period_summaries;
+
+CREATE TABLE fact_team_period_summaries_new (
+    team_gid TEXT NOT NULL,
+    period_key TEXT NOT NULL,
+    generation INTEGER NOT NULL,
+    headline TEXT NOT NULL,
+    what_changed TEXT NOT NULL,
+    why_it_matters TEXT NOT NULL,
+    key_accomplishments TEXT NOT NULL,
+    health_assessment TEXT,
+    prompt_version TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    extra_json TEXT,
+    PRIMARY KEY (team_gid, period_key, generation),
+    FOREIGN KEY (team_gid) REFERENCES dim_teams(team_gid)
+);
+INSERT INTO fact_team_period_summaries_new (team_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, health_assessment, prompt_version, generated_at, extra_json, generation)
+SELECT team_gid, period_key, headline, what_changed, why_it_matters, key_accomplishments, health_assessment, prompt_version, generated_at, extra_json, 1 FROM fact_team_period_summaries;
+DROP TABLE fact_team_period_summaries;
+ALTER TABLE fact_team_period_summaries_new RENAME TO fact_team_period_summaries;
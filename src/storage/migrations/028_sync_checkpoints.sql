@@ -0,0 +1,6 @@
+-- Checkpoints for the rest of a full project sync. Once a job's task listing
+-- is complete its pages stay staged until the job finishes, and
+-- comments_cursor records the last task whose comments were stored, so an
+-- interrupted job carries on from the next task instead of starting over.
+ALTER TABLE sync_jobs ADD COLUMN listing_complete INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE sync_jobs ADD COLUMN comments_cursor TEXT;
@@ -0,0 +1,5 @@
+-- How a period compared with the prior one, as the summary put it.
+ALTER TABLE fact_user_period_summaries ADD COLUMN trend TEXT;
+ALTER TABLE fact_project_period_summaries ADD COLUMN trend TEXT;
+ALTER TABLE fact_portfolio_period_summaries ADD COLUMN trend TEXT;
+ALTER TABLE fact_team_period_summaries ADD COLUMN trend TEXT;
@@ -0,0 +1,4 @@
+-- Tasks deleted in Asana keep their row, flagged so metrics and queries can
+-- leave them out. A task that reappears is unflagged by the next upsert.
+ALTER TABLE fact_tasks ADD COLUMN is_deleted INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fact_tasks ADD COLUMN deleted_at TEXT;
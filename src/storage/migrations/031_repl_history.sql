@@ -0,0 +1,6 @@
+-- Lines entered in `asanadw repl`, kept across sessions.
+CREATE TABLE repl_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    input TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
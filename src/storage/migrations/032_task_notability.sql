@@ -0,0 +1,4 @@
+-- Deterministic notability score (see metrics::notability), recomputed as
+-- tasks and their comments are synced. NULL until a task is first scored.
+ALTER TABLE fact_tasks ADD COLUMN notability_score REAL;
+CREATE INDEX idx_tasks_notability ON fact_tasks(notability_score);
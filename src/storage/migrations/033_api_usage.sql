@@ -0,0 +1,12 @@
+-- Asana API usage per sync: requests made, 429 retries, and seconds spent
+-- waiting on the rate limit. Nested project syncs count toward the team or
+-- portfolio sync that ran them.
+CREATE TABLE api_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_key TEXT NOT NULL,
+    requests INTEGER NOT NULL,
+    retries INTEGER NOT NULL,
+    wait_secs REAL NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+CREATE INDEX idx_api_usage_recorded ON api_usage(recorded_at);
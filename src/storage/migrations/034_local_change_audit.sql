@@ -0,0 +1,13 @@
+-- Destructive local changes `asanadw undo` can revert: monitor removals
+-- (old_value is the removed monitored_entities row as JSON, sync token
+-- included) and config overwrites (old_value is the previous value, NULL
+-- if the key was unset).
+CREATE TABLE audit_local_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    change_type TEXT NOT NULL,
+    target_key TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    changed_at TEXT NOT NULL,
+    undone_at TEXT
+);
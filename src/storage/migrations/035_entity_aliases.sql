@@ -0,0 +1,7 @@
+-- Short names for entities, e.g. `roadmap` for `project:1209759542987106`,
+-- accepted wherever a GID or Asana URL is.
+CREATE TABLE entity_aliases (
+    alias TEXT PRIMARY KEY COLLATE NOCASE,
+    entity_key TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
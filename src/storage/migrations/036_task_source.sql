@@ -0,0 +1,3 @@
+-- Where a task row came from: 'api' for synced tasks, 'export' for tasks
+-- seeded from an Asana export file. A sync overwrites exported rows.
+ALTER TABLE fact_tasks ADD COLUMN source TEXT NOT NULL DEFAULT 'api';
@@ -0,0 +1,11 @@
+-- Each entity's latest `sync all` report and the one before it, so a run
+-- can be compared with the last (items synced, duration, new failures).
+CREATE TABLE sync_report_history (
+    entity_key TEXT PRIMARY KEY,
+    report TEXT NOT NULL,
+    duration_secs REAL NOT NULL,
+    recorded_at TEXT NOT NULL,
+    previous_report TEXT,
+    previous_duration_secs REAL,
+    previous_recorded_at TEXT
+);
@@ -0,0 +1,13 @@
+-- Workspaces the token can see, so one warehouse can hold several, and the
+-- workspace each monitored entity belongs to. Entities monitored before
+-- this take the configured workspace.
+CREATE TABLE dim_workspaces (
+    workspace_gid TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    cached_at TEXT NOT NULL
+);
+
+ALTER TABLE monitored_entities ADD COLUMN workspace_gid TEXT;
+
+UPDATE monitored_entities
+SET workspace_gid = (SELECT value FROM app_config WHERE key = 'workspace_gid');
@@ -0,0 +1,16 @@
+-- Value changes of the custom fields named in `tracked_custom_fields`,
+-- parsed from system stories. Rebuilt per task from fact_comments on sync.
+-- to_value is NULL when the field was cleared.
+CREATE TABLE fact_custom_field_changes (
+    story_gid TEXT PRIMARY KEY,
+    task_gid TEXT NOT NULL,
+    field_name TEXT NOT NULL,
+    from_value TEXT,
+    to_value TEXT,
+    changed_at TEXT NOT NULL,
+    changed_date_key TEXT NOT NULL,
+    cached_at TEXT NOT NULL,
+    FOREIGN KEY (task_gid) REFERENCES fact_tasks(task_gid) ON DELETE CASCADE
+);
+CREATE INDEX idx_field_changes_task ON fact_custom_field_changes(task_gid, field_name, changed_at);
+CREATE INDEX idx_field_changes_value ON fact_custom_field_changes(field_name COLLATE NOCASE, to_value);
@@ -0,0 +1,48 @@
+-- Stories posted by integrations and automation rules, flagged from the
+-- bot_authors and bot_story_types config. Re-flagged per task on sync.
+ALTER TABLE fact_comments ADD COLUMN is_bot INTEGER NOT NULL DEFAULT 0;
+
+-- Bot stories stay out of search: comments_fts now reads its content
+-- through a view that blanks their text, so the index, its triggers, and
+-- 'rebuild' all agree.
+DROP TRIGGER IF EXISTS comments_ai;
+DROP TRIGGER IF EXISTS comments_ad;
+DROP TRIGGER IF EXISTS comments_au;
+DROP TABLE IF EXISTS comments_fts;
+
+CREATE VIEW comments_fts_source AS
+SELECT id, comment_gid, task_gid,
+       CASE WHEN is_bot = 1 THEN '' ELSE COALESCE(text, '') END AS text
+FROM fact_comments;
+
+CREATE VIRTUAL TABLE comments_fts USING fts5(
+    comment_gid,
+    task_gid,
+    text,
+    content='comments_fts_source',
+    content_rowid='id',
+    tokenize='porter unicode61'
+);
+
+CREATE TRIGGER comments_ai AFTER INSERT ON fact_comments BEGIN
+    INSERT INTO comments_fts(rowid, comment_gid, task_gid, text)
+    VALUES (NEW.id, NEW.comment_gid, NEW.task_gid,
+            CASE WHEN NEW.is_bot = 1 THEN '' ELSE COALESCE(NEW.text, '') END);
+END;
+
+CREATE TRIGGER comments_ad AFTER DELETE ON fact_comments BEGIN
+    INSERT INTO comments_fts(comments_fts, rowid, comment_gid, task_gid, text)
+    VALUES ('delete', OLD.id, OLD.comment_gid, OLD.task_gid,
+            CASE WHEN OLD.is_bot = 1 THEN '' ELSE COALESCE(OLD.text, '') END);
+END;
+
+CREATE TRIGGER comments_au AFTER UPDATE ON fact_comments BEGIN
+    INSERT INTO comments_fts(comments_fts, rowid, comment_gid, task_gid, text)
+    VALUES ('delete', OLD.id, OLD.comment_gid, OLD.task_gid,
+            CASE WHEN OLD.is_bot = 1 THEN '' ELSE COALESCE(OLD.text, '') END);
+    INSERT INTO comments_fts(rowid, comment_gid, task_gid, text)
+    VALUES (NEW.id, NEW.comment_gid, NEW.task_gid,
+            CASE WHEN NEW.is_bot = 1 THEN '' ELSE COALESCE(NEW.text, '') END);
+END;
+
+INSERT INTO comments_fts(comments_fts) VALUES ('rebuild');
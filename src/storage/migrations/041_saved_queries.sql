@@ -0,0 +1,9 @@
+-- Named task queries, e.g. `my-overdue-p1s`, run with `asanadw query run`.
+-- `filters` holds the query's filters as JSON.
+CREATE TABLE saved_queries (
+    name TEXT PRIMARY KEY COLLATE NOCASE,
+    description TEXT,
+    filters TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
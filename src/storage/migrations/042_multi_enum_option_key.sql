@@ -0,0 +1,24 @@
+-- The option foreign key pointed at dim_enum_options(option_gid), which
+-- isn't a key of that table, so SQLite rejected every write that reached
+-- bridge_task_multi_enum_values, including cascading task deletes. Options
+-- are keyed by field and option GID.
+CREATE TABLE bridge_task_multi_enum_values_new (
+    task_gid TEXT NOT NULL,
+    field_gid TEXT NOT NULL,
+    option_gid TEXT NOT NULL,
+    PRIMARY KEY (task_gid, field_gid, option_gid),
+    FOREIGN KEY (task_gid) REFERENCES fact_tasks(task_gid) ON DELETE CASCADE,
+    FOREIGN KEY (field_gid) REFERENCES dim_custom_fields(field_gid),
+    FOREIGN KEY (field_gid, option_gid) REFERENCES dim_enum_options(field_gid, option_gid) ON DELETE CASCADE
+);
+
+INSERT INTO bridge_task_multi_enum_values_new (task_gid, field_gid, option_gid)
+SELECT b.task_gid, b.field_gid, b.option_gid
+FROM bridge_task_multi_enum_values b
+JOIN dim_enum_options o ON o.field_gid = b.field_gid AND o.option_gid = b.option_gid
+WHERE b.task_gid IN (SELECT task_gid FROM fact_tasks);
+
+DROP TABLE bridge_task_multi_enum_values;
+ALTER TABLE bridge_task_multi_enum_values_new RENAME TO bridge_task_multi_enum_values;
+CREATE INDEX idx_btmev_field ON bridge_task_multi_enum_values(field_gid);
+CREATE INDEX idx_btmev_option ON bridge_task_multi_enum_values(option_gid);
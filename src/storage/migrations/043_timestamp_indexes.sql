@@ -0,0 +1,6 @@
+-- Metrics, summaries, and reports bound tasks and comments by timestamp,
+-- so rolling-hour periods count exact hours; index the timestamps the way
+-- 001 indexed the date keys.
+CREATE INDEX idx_tasks_created_at ON fact_tasks(created_at);
+CREATE INDEX idx_tasks_completed_at ON fact_tasks(is_completed, completed_at);
+CREATE INDEX idx_comments_created_at ON fact_comments(created_at);
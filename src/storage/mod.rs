@@ -1,10 +1,68 @@
+pub mod bots;
+#[cfg(feature = "sqlcipher")]
+pub mod encryption;
+pub mod field_changes;
+pub mod llm_usage;
+pub mod maintenance;
 pub mod repository;
+pub mod retention;
 pub mod schema;
+pub mod sql;
+pub mod threads;
+pub mod transitions;
 
 use rusqlite_migration::{Migrations, M};
 
 use crate::error::{Error, Result};
 
+/// Schema migrations, applied in order; `PRAGMA user_version` counts how
+/// many have run.
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/001_initial.sql"),
+    include_str!("migrations/002_add_permalink_urls.sql"),
+    include_str!("migrations/003_add_enum_options.sql"),
+    include_str!("migrations/004_add_event_sync_tokens.sql"),
+    include_str!("migrations/005_add_portfolio_portfolios.sql"),
+    include_str!("migrations/006_fix_fts_triggers.sql"),
+    include_str!("migrations/007_portfolio_fts.sql"),
+    include_str!("migrations/008_user_active.sql"),
+    include_str!("migrations/009_comment_threads.sql"),
+    include_str!("migrations/010_section_transitions.sql"),
+    include_str!("migrations/011_task_likes.sql"),
+    include_str!("migrations/012_sync_schedule.sql"),
+    include_str!("migrations/013_export_schedules.sql"),
+    include_str!("migrations/014_sync_lock.sql"),
+    include_str!("migrations/015_discovered_projects.sql"),
+    include_str!("migrations/016_task_subtype.sql"),
+    include_str!("migrations/017_project_briefs.sql"),
+    include_str!("migrations/018_status_updates_fts.sql"),
+    include_str!("migrations/019_user_managers.sql"),
+    include_str!("migrations/020_digests.sql"),
+    include_str!("migrations/021_risk_flags.sql"),
+    include_str!("migrations/022_llm_usage.sql"),
+    include_str!("migrations/023_sync_job_pages.sql"),
+    include_str!("migrations/024_summary_extra_fields.sql"),
+    include_str!("migrations/025_health_snapshots.sql"),
+    include_str!("migrations/026_llm_parse_failures.sql"),
+    include_str!("migrations/027_summary_generations.sql"),
+    include_str!("migrations/028_sync_checkpoints.sql"),
+    include_str!("migrations/029_summary_trends.sql"),
+    include_str!("migrations/030_task_tombstones.sql"),
+    include_str!("migrations/031_repl_history.sql"),
+    include_str!("migrations/032_task_notability.sql"),
+    include_str!("migrations/033_api_usage.sql"),
+    include_str!("migrations/034_local_change_audit.sql"),
+    include_str!("migrations/035_entity_aliases.sql"),
+    include_str!("migrations/036_task_source.sql"),
+    include_str!("migrations/037_sync_report_history.sql"),
+    include_str!("migrations/038_workspaces.sql"),
+    include_str!("migrations/039_custom_field_changes.sql"),
+    include_str!("migrations/040_bot_comments.sql"),
+    include_str!("migrations/041_saved_queries.sql"),
+    include_str!("migrations/042_multi_enum_option_key.sql"),
+    include_str!("migrations/043_timestamp_indexes.sql"),
+];
+
 /// Database wraps two `tokio_rusqlite::Connection` instances (writer + reader)
 /// using WAL mode for concurrent access. The writer serializes writes via
 /// `tokio_rusqlite`'s internal channel; the reader can proceed without blocking.
@@ -12,29 +70,101 @@ use crate::error::{Error, Result};
 pub struct Database {
     writer: tokio_rusqlite::Connection,
     reader: tokio_rusqlite::Connection,
+    read_only: bool,
 }
 
 impl Database {
-    /// Open the database at the default path (`~/.asanadw/asanadw.db`).
-    pub async fn open() -> Result<Self> {
+    /// The default database path, `~/.asanadw/asanadw.db`. Opening it for
+    /// writing creates the directory.
+    pub fn default_path() -> Result<std::path::PathBuf> {
         let dir = dirs::home_dir()
             .ok_or_else(|| Error::Config("cannot determine home directory".into()))?
             .join(".asanadw");
-        std::fs::create_dir_all(&dir).map_err(|e| Error::Config(e.to_string()))?;
-        Self::open_at(dir.join("asanadw.db")).await
+        Ok(dir.join("asanadw.db"))
+    }
+
+    /// Open the database at the default path (`~/.asanadw/asanadw.db`).
+    pub async fn open() -> Result<Self> {
+        Self::open_at(Self::default_path()?).await
     }
 
     /// Open the database at the given path.
     pub async fn open_at(path: impl AsRef<std::path::Path>) -> Result<Self> {
-        let path = path.as_ref().to_path_buf();
+        Self::open_with(path.as_ref(), None, false).await
+    }
+
+    /// Open an existing database for reading only, as for a shared
+    /// warehouse on a network drive. Nothing is migrated, and every write,
+    /// raw SQL included, fails.
+    pub async fn open_read_only(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::open_with(path.as_ref(), None, true).await
+    }
+
+    /// Open a SQLCipher-encrypted database at the given path, creating it
+    /// encrypted with `key` if it doesn't exist.
+    #[cfg(feature = "sqlcipher")]
+    pub async fn open_encrypted(path: impl AsRef<std::path::Path>, key: &str) -> Result<Self> {
+        Self::open_with(path.as_ref(), Some(key.to_string()), false).await
+    }
+
+    /// Like [`Database::open_read_only`], for an encrypted database.
+    #[cfg(feature = "sqlcipher")]
+    pub async fn open_encrypted_read_only(
+        path: impl AsRef<std::path::Path>,
+        key: &str,
+    ) -> Result<Self> {
+        Self::open_with(path.as_ref(), Some(key.to_string()), true).await
+    }
+
+    async fn open_with(
+        path: &std::path::Path,
+        key: Option<String>,
+        read_only: bool,
+    ) -> Result<Self> {
+        if read_only {
+            if !path.exists() {
+                return Err(Error::NotFound(format!("database {}", path.display())));
+            }
+            // Read-write without create: WAL readers still update the -shm
+            // file, and query_only is what blocks writes
+            let conn = tokio_rusqlite::Connection::open_with_flags(
+                path,
+                rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE
+                    | rusqlite::OpenFlags::SQLITE_OPEN_URI
+                    | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .await?;
+            if let Some(key) = key {
+                Self::apply_key(&conn, key).await?;
+            }
+            Self::init_read_only(&conn).await?;
+            return Ok(Self {
+                reader: conn.clone(),
+                writer: conn,
+                read_only: true,
+            });
+        }
 
-        let writer = tokio_rusqlite::Connection::open(&path).await?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| Error::Config(e.to_string()))?;
+        }
+        let writer = tokio_rusqlite::Connection::open(path).await?;
+        if let Some(key) = &key {
+            Self::apply_key(&writer, key.clone()).await?;
+        }
         Self::init_writer(&writer).await?;
 
-        let reader = tokio_rusqlite::Connection::open(&path).await?;
+        let reader = tokio_rusqlite::Connection::open(path).await?;
+        if let Some(key) = key {
+            Self::apply_key(&reader, key).await?;
+        }
         Self::init_reader(&reader).await?;
 
-        let db = Self { writer, reader };
+        let db = Self {
+            writer,
+            reader,
+            read_only: false,
+        };
         db.ensure_dimensions().await?;
         Ok(db)
     }
@@ -49,11 +179,30 @@ impl Database {
         let db = Self {
             reader: writer.clone(),
             writer,
+            read_only: false,
         };
         db.ensure_dimensions().await?;
         Ok(db)
     }
 
+    /// Set the SQLCipher key, which must come before anything else touches
+    /// the file, and check it by reading the schema.
+    async fn apply_key(conn: &tokio_rusqlite::Connection, key: String) -> Result<()> {
+        conn.call(move |conn| {
+            conn.pragma_update(None, "key", &key)?;
+            conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+        })
+        .await
+        .map_err(|e| match e {
+            tokio_rusqlite::Error::Error(rusqlite::Error::SqliteFailure(f, _))
+                if f.code == rusqlite::ErrorCode::NotADatabase =>
+            {
+                Error::Config("wrong database key, or the database is not encrypted".into())
+            }
+            other => Error::Database(other.to_string()),
+        })
+    }
+
     async fn init_writer(conn: &tokio_rusqlite::Connection) -> Result<()> {
         conn.call(|conn| {
             conn.execute_batch(
@@ -62,15 +211,7 @@ impl Database {
                  PRAGMA busy_timeout=5000;",
             )
             .map_err(|e| e.to_string())?;
-            let migrations = Migrations::new(vec![
-                M::up(include_str!("migrations/001_initial.sql")),
-                M::up(include_str!("migrations/002_add_permalink_urls.sql")),
-                M::up(include_str!("migrations/003_add_enum_options.sql")),
-                M::up(include_str!("migrations/004_add_event_sync_tokens.sql")),
-                M::up(include_str!("migrations/005_add_portfolio_portfolios.sql")),
-                M::up(include_str!("migrations/006_fix_fts_triggers.sql")),
-                M::up(include_str!("migrations/007_portfolio_fts.sql")),
-            ]);
+            let migrations = Migrations::new(MIGRATIONS.iter().copied().map(M::up).collect());
             migrations.to_latest(conn).map_err(|e| e.to_string())?;
             Ok::<(), String>(())
         })
@@ -91,6 +232,36 @@ impl Database {
         Ok(())
     }
 
+    async fn init_read_only(conn: &tokio_rusqlite::Connection) -> Result<()> {
+        let version = conn
+            .call(|conn| {
+                conn.execute_batch(
+                    "PRAGMA busy_timeout=5000;\
+                     PRAGMA query_only=ON;",
+                )?;
+                conn.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+            })
+            .await? as usize;
+        let latest = MIGRATIONS.len();
+        if version < latest {
+            return Err(Error::Migration(format!(
+                "schema version {version} is older than this asanadw's {latest}; \
+                 open the database once without read-only mode to migrate it"
+            )));
+        }
+        if version > latest {
+            return Err(Error::Migration(format!(
+                "schema version {version} is newer than this asanadw's {latest}; upgrade asanadw"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether this database was opened with [`Database::open_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Get a reference to the writer connection.
     pub fn writer(&self) -> &tokio_rusqlite::Connection {
         &self.writer
@@ -101,6 +272,83 @@ impl Database {
         &self.reader
     }
 
+    /// Run an ad-hoc read-only SQL statement, returning each row as a JSON
+    /// object keyed by column name. Statements that would modify the
+    /// database are rejected.
+    pub async fn query_arbitrary(&self, sql: &str) -> Result<Vec<serde_json::Value>> {
+        Ok(self.query_arbitrary_table(sql).await?.to_objects())
+    }
+
+    /// Like [`Database::query_arbitrary`], but keeps column order for
+    /// tabular output.
+    pub async fn query_arbitrary_table(&self, sql: &str) -> Result<sql::SqlResult> {
+        let sql = sql.to_string();
+        self.reader
+            .call(move |conn| sql::query_read_only(conn, &sql))
+            .await
+            .map_err(|e| match e {
+                tokio_rusqlite::Error::Error(e) => e,
+                other => Error::Database(other.to_string()),
+            })
+    }
+
+    /// Fail with [`Error::NotSynced`] when nothing about `entity_type`
+    /// `gid` (a project, user, portfolio, or team) is stored, so reports on
+    /// it would silently come back empty.
+    pub async fn require_synced(&self, entity_type: &str, gid: &str) -> Result<()> {
+        let (entity_type, gid) = (entity_type.to_string(), gid.to_string());
+        let key = format!("{entity_type}:{gid}");
+        let synced = self
+            .reader
+            .call(move |conn| repository::has_local_data(conn, &entity_type, &gid))
+            .await?;
+        if synced {
+            Ok(())
+        } else {
+            Err(Error::NotSynced(key))
+        }
+    }
+
+    /// Row counts and sizes for every table, largest first.
+    pub async fn stats(&self) -> Result<maintenance::DatabaseStats> {
+        Ok(self.reader.call(|conn| maintenance::stats(conn)).await?)
+    }
+
+    /// Run `PRAGMA integrity_check`; an empty list means no problems.
+    pub async fn integrity_check(&self) -> Result<Vec<String>> {
+        Ok(self
+            .reader
+            .call(|conn| maintenance::integrity_check(conn))
+            .await?)
+    }
+
+    /// VACUUM the database file, merging full-text index segments first when
+    /// `optimize_fts` is set.
+    pub async fn vacuum(&self, optimize_fts: bool) -> Result<maintenance::VacuumReport> {
+        Ok(self
+            .writer
+            .call(move |conn| maintenance::vacuum(conn, optimize_fts))
+            .await?)
+    }
+
+    /// Refresh query planner statistics.
+    pub async fn analyze(&self) -> Result<()> {
+        Ok(self.writer.call(|conn| maintenance::analyze(conn)).await?)
+    }
+
+    /// Write an encrypted copy of this database to `dest`, which must not
+    /// exist yet.
+    #[cfg(feature = "sqlcipher")]
+    pub async fn export_encrypted(&self, dest: std::path::PathBuf, key: String) -> Result<()> {
+        if dest.exists() {
+            return Err(Error::Config(format!("{} already exists", dest.display())));
+        }
+        Ok(self
+            .writer
+            .call(move |conn| encryption::export_encrypted(conn, &dest, &key))
+            .await?)
+    }
+
     /// Ensure dim_date and dim_period tables are populated.
     async fn ensure_dimensions(&self) -> Result<()> {
         self.writer
@@ -169,4 +417,55 @@ mod tests {
 
         assert!(count > 50, "dim_period should have >50 rows, got {count}");
     }
+
+    #[tokio::test]
+    async fn test_require_synced() {
+        let db = Database::open_memory().await.unwrap();
+        assert!(matches!(
+            db.require_synced("project", "p1").await,
+            Err(Error::NotSynced(key)) if key == "project:p1"
+        ));
+        assert!(db.require_synced("user", "u1").await.is_err());
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Empty but synced', 'w1', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Ada', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Task', 'u1', '2025-01-01', '2025-01-01', datetime('now'));",
+                )
+            })
+            .await
+            .unwrap();
+        db.require_synced("project", "p1").await.unwrap();
+        db.require_synced("user", "u1").await.unwrap();
+        db.require_synced("task", "anything").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_open_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.db");
+        assert!(matches!(
+            Database::open_read_only(&path).await,
+            Err(Error::NotFound(_))
+        ));
+        assert!(!path.exists());
+        drop(Database::open_at(&path).await.unwrap());
+
+        let db = Database::open_read_only(&path).await.unwrap();
+        assert!(db.is_read_only());
+        let users: i64 = db
+            .reader()
+            .call(|conn| conn.query_row("SELECT COUNT(*) FROM dim_users", [], |row| row.get(0)))
+            .await
+            .unwrap();
+        assert_eq!(users, 0);
+        let write = db
+            .writer()
+            .call(|conn| conn.execute("DELETE FROM dim_users", []))
+            .await;
+        assert!(write.is_err());
+    }
 }
@@ -1,6 +1,8 @@
 use chrono::NaiveDate;
 use rusqlite::{params, Connection, OptionalExtension};
 
+use crate::sync::rate_limit::RateLimitUsage;
+
 // ── Users ──────────────────────────────────────────────────────────
 
 pub fn upsert_user(conn: &Connection, user: &asanaclient::User) -> Result<(), rusqlite::Error> {
@@ -9,8 +11,11 @@ pub fn upsert_user(conn: &Connection, user: &asanaclient::User) -> Result<(), ru
         .as_ref()
         .and_then(|p| p.image_128x128.as_deref().or(p.image_60x60.as_deref()));
     conn.execute(
-        "INSERT OR REPLACE INTO dim_users (user_gid, email, name, photo_url, cached_at)
-         VALUES (?1, ?2, ?3, ?4, datetime('now'))",
+        "INSERT INTO dim_users (user_gid, email, name, photo_url, cached_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now'))
+         ON CONFLICT(user_gid) DO UPDATE SET
+           email = excluded.email, name = excluded.name, photo_url = excluded.photo_url,
+           cached_at = excluded.cached_at, is_active = 1",
         params![user.gid, user.email, user.name, photo_url],
     )?;
     Ok(())
@@ -67,6 +72,54 @@ pub fn resolve_user_identifier(
     Ok(gid)
 }
 
+/// Mark a known user as active or deactivated in the workspace.
+/// Returns false if the user is not in dim_users (unknown users are not inserted).
+pub fn set_user_active(
+    conn: &Connection,
+    user_gid: &str,
+    is_active: bool,
+) -> Result<bool, rusqlite::Error> {
+    let changed = conn.execute(
+        "UPDATE dim_users SET is_active = ?2 WHERE user_gid = ?1",
+        params![user_gid, is_active as i32],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Set or clear a known user's manager.
+/// Returns false if the user is not in dim_users.
+pub fn set_user_manager(
+    conn: &Connection,
+    user_gid: &str,
+    manager_gid: Option<&str>,
+) -> Result<bool, rusqlite::Error> {
+    let changed = conn.execute(
+        "UPDATE dim_users SET manager_gid = ?2 WHERE user_gid = ?1",
+        params![user_gid, manager_gid],
+    )?;
+    Ok(changed > 0)
+}
+
+/// A manager and everyone who reports to them, directly or indirectly.
+/// Cycles in the mapping are tolerated.
+pub fn get_reporting_line(
+    conn: &Connection,
+    manager_gid: &str,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE line(user_gid) AS (
+             SELECT ?1
+             UNION
+             SELECT u.user_gid FROM dim_users u JOIN line l ON u.manager_gid = l.user_gid
+         )
+         SELECT user_gid FROM line ORDER BY user_gid",
+    )?;
+    let gids = stmt
+        .query_map([manager_gid], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(gids)
+}
+
 // ── Projects ───────────────────────────────────────────────────────
 
 pub fn upsert_project(
@@ -134,10 +187,10 @@ pub fn upsert_task(conn: &Connection, task: &asanaclient::Task) -> Result<(), ru
             due_on, due_at, start_on, start_at,
             created_at, created_date_key, modified_at,
             parent_gid, is_subtask, num_subtasks, num_likes,
-            days_to_complete, is_overdue, permalink_url, cached_at
+            days_to_complete, is_overdue, permalink_url, resource_subtype, cached_at
         ) VALUES (
             ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12,
-            ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, datetime('now')
+            ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, datetime('now')
         )
         ON CONFLICT(task_gid) DO UPDATE SET
             name=excluded.name, notes=excluded.notes, notes_html=excluded.notes_html,
@@ -149,7 +202,8 @@ pub fn upsert_task(conn: &Connection, task: &asanaclient::Task) -> Result<(), ru
             parent_gid=excluded.parent_gid, is_subtask=excluded.is_subtask,
             num_subtasks=excluded.num_subtasks, num_likes=excluded.num_likes,
             days_to_complete=excluded.days_to_complete, is_overdue=excluded.is_overdue,
-            permalink_url=excluded.permalink_url, cached_at=excluded.cached_at",
+            permalink_url=excluded.permalink_url, resource_subtype=excluded.resource_subtype,
+            is_deleted=0, deleted_at=NULL, source='api', cached_at=excluded.cached_at",
         params![
             task.gid,
             task.name,
@@ -173,6 +227,7 @@ pub fn upsert_task(conn: &Connection, task: &asanaclient::Task) -> Result<(), ru
             days_to_complete,
             is_overdue as i32,
             task.permalink_url,
+            task.resource_subtype.as_deref().unwrap_or("default_task"),
         ],
     )?;
 
@@ -198,6 +253,11 @@ pub fn upsert_task(conn: &Connection, task: &asanaclient::Task) -> Result<(), ru
              VALUES (?1, ?2, ?3)",
             params![task.gid, membership.project.gid, section_gid],
         )?;
+        record_discovered_project(
+            conn,
+            &membership.project.gid,
+            membership.project.name.as_deref(),
+        )?;
     }
 
     // Insert tags
@@ -216,6 +276,151 @@ pub fn upsert_task(conn: &Connection, task: &asanaclient::Task) -> Result<(), ru
     Ok(())
 }
 
+/// Add a placeholder `dim_projects` row for a project known only by GID and
+/// name, e.g. from an export file. Existing rows are left untouched.
+pub fn ensure_project_minimal(
+    conn: &Connection,
+    project_gid: &str,
+    name: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+         VALUES (?1, ?2, '', datetime('now'))",
+        params![project_gid, name],
+    )?;
+    Ok(())
+}
+
+/// A task read from an Asana export file rather than the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedTask {
+    pub gid: String,
+    pub name: String,
+    pub notes: Option<String>,
+    pub assignee_gid: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<String>,
+    pub due_on: Option<String>,
+    pub start_on: Option<String>,
+    pub created_at: String,
+    pub modified_at: Option<String>,
+    pub parent_gid: Option<String>,
+}
+
+/// Store an exported task in `project_gid` with `source = 'export'`. A task
+/// already synced from the API is left alone; returns whether it was
+/// written. Callers disable FK checks, as the parent may not be stored.
+pub fn upsert_exported_task(
+    conn: &Connection,
+    project_gid: &str,
+    task: &ExportedTask,
+) -> Result<bool, rusqlite::Error> {
+    let created_date_key = date_key_from_iso(&task.created_at);
+    let completed_date_key = task.completed_at.as_deref().map(date_key_from_iso);
+    let days_to_complete = compute_days_to_complete(&task.created_at, task.completed_at.as_deref());
+    let is_overdue = compute_is_overdue(task.completed, task.due_on.as_deref());
+
+    let written = conn.execute(
+        "INSERT INTO fact_tasks (
+            task_gid, name, notes, assignee_gid,
+            is_completed, completed_at, completed_date_key, due_on, start_on,
+            created_at, created_date_key, modified_at, parent_gid, is_subtask,
+            days_to_complete, is_overdue, source, cached_at
+        ) VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+            'export', datetime('now')
+        )
+        ON CONFLICT(task_gid) DO UPDATE SET
+            name=excluded.name, notes=excluded.notes, assignee_gid=excluded.assignee_gid,
+            is_completed=excluded.is_completed, completed_at=excluded.completed_at,
+            completed_date_key=excluded.completed_date_key, due_on=excluded.due_on,
+            start_on=excluded.start_on, created_at=excluded.created_at,
+            created_date_key=excluded.created_date_key, modified_at=excluded.modified_at,
+            parent_gid=excluded.parent_gid, is_subtask=excluded.is_subtask,
+            days_to_complete=excluded.days_to_complete, is_overdue=excluded.is_overdue,
+            cached_at=excluded.cached_at
+        WHERE fact_tasks.source = 'export'",
+        params![
+            task.gid,
+            task.name,
+            task.notes,
+            task.assignee_gid,
+            task.completed as i32,
+            task.completed_at,
+            completed_date_key,
+            task.due_on,
+            task.start_on,
+            task.created_at,
+            created_date_key,
+            task.modified_at,
+            task.parent_gid,
+            task.parent_gid.is_some() as i32,
+            days_to_complete,
+            is_overdue as i32,
+        ],
+    )?;
+    if written > 0 {
+        conn.execute(
+            "INSERT OR IGNORE INTO bridge_task_projects (task_gid, project_gid) VALUES (?1, ?2)",
+            params![task.gid, project_gid],
+        )?;
+    }
+    Ok(written > 0)
+}
+
+/// Live tasks in a project that a full sync listing from `completed_since`
+/// (`YYYY-MM-DD`) should return: open ones and ones completed since then.
+pub fn project_task_gids_since(
+    conn: &Connection,
+    project_gid: &str,
+    completed_since: &str,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT t.task_gid FROM fact_tasks t
+         JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+         WHERE btp.project_gid = ?1 AND t.is_deleted = 0
+           AND (t.is_completed = 0 OR t.completed_date_key >= ?2)",
+    )?;
+    let rows = stmt.query_map(params![project_gid, completed_since], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Flag tasks as deleted in Asana. Returns how many weren't already.
+pub fn mark_tasks_deleted(
+    conn: &Connection,
+    task_gids: &[String],
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "UPDATE fact_tasks SET is_deleted = 1, deleted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+         WHERE task_gid = ?1 AND is_deleted = 0",
+    )?;
+    let mut marked = 0;
+    for gid in task_gids {
+        marked += stmt.execute([gid])?;
+    }
+    Ok(marked)
+}
+
+/// Replace the set of users who liked a task.
+pub fn replace_task_likes(
+    conn: &Connection,
+    task_gid: &str,
+    user_gids: &[String],
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM fact_task_likes WHERE task_gid = ?1",
+        [task_gid],
+    )?;
+    for user_gid in user_gids {
+        conn.execute(
+            "INSERT OR IGNORE INTO fact_task_likes (task_gid, user_gid, cached_at)
+             VALUES (?1, ?2, datetime('now'))",
+            params![task_gid, user_gid],
+        )?;
+    }
+    Ok(())
+}
+
 // ── Custom Fields ──────────────────────────────────────────────────
 
 pub fn upsert_enum_option(
@@ -300,10 +505,14 @@ pub fn upsert_custom_fields(
         let date_val = cf.date_value.as_ref().and_then(|d| d.date.as_deref());
 
         conn.execute(
-            "INSERT OR REPLACE INTO fact_task_custom_fields (
+            "INSERT INTO fact_task_custom_fields (
                 task_gid, field_gid, text_value, number_value, date_value,
                 enum_value_gid, display_value
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(task_gid, field_gid) DO UPDATE SET
+                text_value=excluded.text_value, number_value=excluded.number_value,
+                date_value=excluded.date_value, enum_value_gid=excluded.enum_value_gid,
+                display_value=excluded.display_value",
             params![
                 task_gid,
                 cf.gid,
@@ -394,11 +603,17 @@ pub fn upsert_status_update(
     let created_date_key = date_key_from_iso(created_at);
 
     conn.execute(
-        "INSERT OR REPLACE INTO fact_status_updates (
+        "INSERT INTO fact_status_updates (
             status_gid, parent_gid, parent_type, author_gid,
             title, text, html_text, status_type,
             created_at, created_date_key, cached_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))
+        ON CONFLICT(status_gid) DO UPDATE SET
+            parent_gid=excluded.parent_gid, parent_type=excluded.parent_type,
+            author_gid=excluded.author_gid, title=excluded.title, text=excluded.text,
+            html_text=excluded.html_text, status_type=excluded.status_type,
+            created_at=excluded.created_at, created_date_key=excluded.created_date_key,
+            cached_at=excluded.cached_at",
         params![
             status.gid,
             parent_gid,
@@ -415,6 +630,126 @@ pub fn upsert_status_update(
     Ok(())
 }
 
+/// A stored status update on a project or portfolio.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct StatusUpdateRow {
+    pub status_gid: String,
+    pub parent_gid: String,
+    /// `project` or `portfolio`.
+    pub parent_type: String,
+    /// Project or portfolio name, if stored.
+    pub parent_name: Option<String>,
+    pub author_gid: Option<String>,
+    pub author_name: Option<String>,
+    pub title: String,
+    pub text: Option<String>,
+    /// `on_track`, `at_risk`, `off_track`, `on_hold`, `complete`, or `none`.
+    pub status_type: String,
+    pub created_at: String,
+}
+
+const STATUS_UPDATE_SELECT: &str = "SELECT s.status_gid, s.parent_gid, s.parent_type,
+        COALESCE(p.name, pf.name), s.author_gid, u.name, s.title, s.text,
+        s.status_type, s.created_at
+     FROM fact_status_updates s
+     LEFT JOIN dim_projects p ON s.parent_type = 'project' AND p.project_gid = s.parent_gid
+     LEFT JOIN dim_portfolios pf ON s.parent_type = 'portfolio' AND pf.portfolio_gid = s.parent_gid
+     LEFT JOIN dim_users u ON u.user_gid = s.author_gid";
+
+fn status_update_from_row(row: &rusqlite::Row) -> Result<StatusUpdateRow, rusqlite::Error> {
+    Ok(StatusUpdateRow {
+        status_gid: row.get(0)?,
+        parent_gid: row.get(1)?,
+        parent_type: row.get(2)?,
+        parent_name: row.get(3)?,
+        author_gid: row.get(4)?,
+        author_name: row.get(5)?,
+        title: row.get(6)?,
+        text: row.get(7)?,
+        status_type: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+/// A project's or portfolio's status updates, newest first, at most `limit`
+/// of them.
+pub fn get_status_updates(
+    conn: &Connection,
+    parent_gid: &str,
+    limit: usize,
+) -> Result<Vec<StatusUpdateRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "{STATUS_UPDATE_SELECT}
+         WHERE s.parent_gid = ?1
+         ORDER BY s.created_at DESC, s.status_gid DESC
+         LIMIT ?2"
+    ))?;
+    let rows = stmt.query_map(params![parent_gid, limit as i64], status_update_from_row)?;
+    rows.collect()
+}
+
+/// A project's or portfolio's most recent status update.
+pub fn get_latest_status_update(
+    conn: &Connection,
+    parent_gid: &str,
+) -> Result<Option<StatusUpdateRow>, rusqlite::Error> {
+    Ok(get_status_updates(conn, parent_gid, 1)?.into_iter().next())
+}
+
+/// The most recent status update of every project that has one, newest
+/// first.
+pub fn latest_project_status_updates(
+    conn: &Connection,
+) -> Result<Vec<StatusUpdateRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "{STATUS_UPDATE_SELECT}
+         WHERE s.parent_type = 'project'
+           AND s.status_gid = (
+               SELECT s2.status_gid FROM fact_status_updates s2
+               WHERE s2.parent_gid = s.parent_gid
+               ORDER BY s2.created_at DESC, s2.status_gid DESC LIMIT 1)
+         ORDER BY s.created_at DESC"
+    ))?;
+    let rows = stmt.query_map([], status_update_from_row)?;
+    rows.collect()
+}
+
+// ── Project Briefs ─────────────────────────────────────────────────
+
+pub fn upsert_project_brief(
+    conn: &Connection,
+    project_gid: &str,
+    brief: &crate::sync::api_helpers::ProjectBriefInfo,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO dim_project_briefs (
+            project_gid, brief_gid, title, text, html_text, permalink_url, cached_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))
+        ON CONFLICT(project_gid) DO UPDATE SET
+            brief_gid=excluded.brief_gid, title=excluded.title, text=excluded.text,
+            html_text=excluded.html_text, permalink_url=excluded.permalink_url,
+            cached_at=excluded.cached_at",
+        params![
+            project_gid,
+            brief.gid,
+            brief.title,
+            brief.text,
+            brief.html_text,
+            brief.permalink_url,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Remove a project's brief after it was deleted in Asana.
+pub fn delete_project_brief(conn: &Connection, project_gid: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM dim_project_briefs WHERE project_gid = ?1",
+        [project_gid],
+    )?;
+    Ok(())
+}
+
 // ── Sections ───────────────────────────────────────────────────────
 
 pub fn upsert_section(
@@ -433,6 +768,88 @@ pub fn upsert_section(
     Ok(())
 }
 
+/// A stored project section.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SectionRow {
+    pub section_gid: String,
+    pub project_gid: String,
+    pub name: String,
+    pub sort_order: i32,
+}
+
+/// A project's sections in board order.
+pub fn get_project_sections(
+    conn: &Connection,
+    project_gid: &str,
+) -> Result<Vec<SectionRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT section_gid, project_gid, name, sort_order FROM dim_sections
+         WHERE project_gid = ?1
+         ORDER BY sort_order, name",
+    )?;
+    let rows = stmt.query_map([project_gid], |row| {
+        Ok(SectionRow {
+            section_gid: row.get(0)?,
+            project_gid: row.get(1)?,
+            name: row.get(2)?,
+            sort_order: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// ── Workspaces ─────────────────────────────────────────────────────
+
+/// A workspace or organization the Asana token can see.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Workspace {
+    pub workspace_gid: String,
+    pub name: String,
+}
+
+pub fn upsert_workspace(
+    conn: &Connection,
+    workspace_gid: &str,
+    name: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO dim_workspaces (workspace_gid, name, cached_at)
+         VALUES (?1, ?2, datetime('now'))",
+        params![workspace_gid, name],
+    )?;
+    Ok(())
+}
+
+pub fn list_workspaces(conn: &Connection) -> Result<Vec<Workspace>, rusqlite::Error> {
+    let mut stmt = conn
+        .prepare("SELECT workspace_gid, name FROM dim_workspaces ORDER BY name COLLATE NOCASE")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Workspace {
+            workspace_gid: row.get(0)?,
+            name: row.get(1)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Find a known workspace by GID or case-insensitive name.
+pub fn find_workspace(
+    conn: &Connection,
+    identifier: &str,
+) -> Result<Vec<Workspace>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT workspace_gid, name FROM dim_workspaces
+         WHERE workspace_gid = ?1 OR name = ?1 COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map(params![identifier], |row| {
+        Ok(Workspace {
+            workspace_gid: row.get(0)?,
+            name: row.get(1)?,
+        })
+    })?;
+    rows.collect()
+}
+
 // ── Teams ──────────────────────────────────────────────────────────
 
 pub fn upsert_team(
@@ -465,6 +882,41 @@ pub fn upsert_team_member(
     Ok(())
 }
 
+/// Replace a team's memberships with `user_gids`, keeping the roles of
+/// members who stay.
+pub fn replace_team_members(
+    conn: &Connection,
+    team_gid: &str,
+    user_gids: &[String],
+) -> Result<(), rusqlite::Error> {
+    let placeholders = vec!["?"; user_gids.len()].join(", ");
+    let mut values: Vec<&dyn rusqlite::ToSql> = vec![&team_gid];
+    values.extend(user_gids.iter().map(|g| g as &dyn rusqlite::ToSql));
+    conn.execute(
+        &format!(
+            "DELETE FROM bridge_team_members
+             WHERE team_gid = ?1 AND user_gid NOT IN ({placeholders})"
+        ),
+        values.as_slice(),
+    )?;
+    for user_gid in user_gids {
+        conn.execute(
+            "INSERT OR IGNORE INTO bridge_team_members (team_gid, user_gid) VALUES (?1, ?2)",
+            params![team_gid, user_gid],
+        )?;
+    }
+    Ok(())
+}
+
+/// GIDs of teams named `name`, ignoring case.
+pub fn find_teams_by_name(conn: &Connection, name: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT team_gid FROM dim_teams WHERE name = ?1 COLLATE NOCASE ORDER BY team_gid",
+    )?;
+    let rows = stmt.query_map([name], |row| row.get(0))?;
+    rows.collect()
+}
+
 // ── Portfolios ─────────────────────────────────────────────────────
 
 pub fn upsert_portfolio(
@@ -479,9 +931,13 @@ pub fn upsert_portfolio(
         .unwrap_or("");
 
     conn.execute(
-        "INSERT OR REPLACE INTO dim_portfolios (
+        "INSERT INTO dim_portfolios (
             portfolio_gid, name, owner_gid, workspace_gid, is_public, color, permalink_url, cached_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))",
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))
+        ON CONFLICT(portfolio_gid) DO UPDATE SET
+            name=excluded.name, owner_gid=excluded.owner_gid, workspace_gid=excluded.workspace_gid,
+            is_public=excluded.is_public, color=excluded.color,
+            permalink_url=excluded.permalink_url, cached_at=excluded.cached_at",
         params![
             portfolio.gid,
             portfolio.name,
@@ -521,6 +977,61 @@ pub fn upsert_portfolio_portfolio(
     .map(|_| ())
 }
 
+/// `portfolio_gid` and the portfolios nested under it, at most `max_depth`
+/// levels down, in breadth-first order. Each portfolio appears once even if
+/// the hierarchy has cycles.
+pub fn portfolio_descendants(
+    conn: &Connection,
+    portfolio_gid: &str,
+    max_depth: u32,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT child_portfolio_gid FROM bridge_portfolio_portfolios
+         WHERE parent_portfolio_gid = ?1 ORDER BY child_portfolio_gid",
+    )?;
+    let mut seen = std::collections::HashSet::from([portfolio_gid.to_string()]);
+    let mut gids = vec![portfolio_gid.to_string()];
+    let mut frontier = gids.clone();
+    for _ in 0..max_depth {
+        let mut next = Vec::new();
+        for gid in &frontier {
+            for child in stmt.query_map([gid], |row| row.get::<_, String>(0))? {
+                let child = child?;
+                if seen.insert(child.clone()) {
+                    next.push(child);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        gids.extend(next.iter().cloned());
+        frontier = next;
+    }
+    Ok(gids)
+}
+
+/// Non-template projects in `portfolio_gid` or any portfolio nested under it,
+/// at most `max_depth` levels down.
+pub fn portfolio_project_gids(
+    conn: &Connection,
+    portfolio_gid: &str,
+    max_depth: u32,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let portfolios = portfolio_descendants(conn, portfolio_gid, max_depth)?;
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT project_gid FROM bridge_portfolio_projects
+         WHERE portfolio_gid IN (SELECT value FROM json_each(?1))
+           AND project_gid NOT IN (SELECT project_gid FROM dim_projects WHERE is_template = 1)
+         ORDER BY project_gid",
+    )?;
+    let json = serde_json::to_string(&portfolios).unwrap_or_default();
+    let gids = stmt
+        .query_map([json], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(gids)
+}
+
 // ── Monitored Entities ─────────────────────────────────────────────
 
 pub fn add_monitored_entity(
@@ -539,6 +1050,34 @@ pub fn add_monitored_entity(
     Ok(())
 }
 
+/// Record which workspace a monitored entity belongs to.
+pub fn set_entity_workspace(
+    conn: &Connection,
+    entity_key: &str,
+    workspace_gid: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE monitored_entities SET workspace_gid = ?2 WHERE entity_key = ?1",
+        params![entity_key, workspace_gid],
+    )?;
+    Ok(())
+}
+
+/// The workspace a monitored entity was added in, if recorded.
+pub fn get_entity_workspace(
+    conn: &Connection,
+    entity_key: &str,
+) -> Result<Option<String>, rusqlite::Error> {
+    Ok(conn
+        .query_row(
+            "SELECT workspace_gid FROM monitored_entities WHERE entity_key = ?1",
+            params![entity_key],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten())
+}
+
 /// Ensure a `monitored_entities` row exists for the given entity so that
 /// sync tokens and timestamps can be stored against it.  Portfolio-discovered
 /// projects are not explicitly added by the user, so they may lack a row.
@@ -559,20 +1098,46 @@ pub fn ensure_entity_for_sync(
     Ok(())
 }
 
+/// Stop monitoring an entity. The removed row, sync token included, is
+/// recorded in `audit_local_changes` so `asanadw undo` can restore it.
 pub fn remove_monitored_entity(
     conn: &Connection,
     entity_key: &str,
 ) -> Result<bool, rusqlite::Error> {
-    let count = conn.execute(
+    let row: Option<String> = conn
+        .query_row(
+            "SELECT json_object(
+                'entity_key', entity_key, 'entity_type', entity_type,
+                'entity_gid', entity_gid, 'display_name', display_name,
+                'added_at', added_at, 'last_sync_at', last_sync_at,
+                'sync_enabled', sync_enabled, 'event_sync_token', event_sync_token,
+                'sync_interval_minutes', sync_interval_minutes, 'workspace_gid', workspace_gid)
+             FROM monitored_entities WHERE entity_key = ?1",
+            params![entity_key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(row) = row else {
+        return Ok(false);
+    };
+    record_local_change(
+        conn,
+        LOCAL_CHANGE_MONITOR_REMOVE,
+        entity_key,
+        Some(&row),
+        None,
+    )?;
+    conn.execute(
         "DELETE FROM monitored_entities WHERE entity_key = ?1",
         params![entity_key],
     )?;
-    Ok(count > 0)
+    Ok(true)
 }
 
 pub fn list_monitored_entities(conn: &Connection) -> Result<Vec<MonitoredEntity>, rusqlite::Error> {
     let mut stmt = conn.prepare(
-        "SELECT entity_key, entity_type, entity_gid, display_name, added_at, last_sync_at, sync_enabled
+        "SELECT entity_key, entity_type, entity_gid, display_name, added_at, last_sync_at, sync_enabled,
+                workspace_gid
          FROM monitored_entities WHERE sync_enabled = 1 ORDER BY added_at",
     )?;
     let rows = stmt.query_map([], |row| {
@@ -584,6 +1149,7 @@ pub fn list_monitored_entities(conn: &Connection) -> Result<Vec<MonitoredEntity>
             added_at: row.get(4)?,
             last_sync_at: row.get(5)?,
             sync_enabled: row.get(6)?,
+            workspace_gid: row.get(7)?,
         })
     })?;
     rows.collect()
@@ -622,41 +1188,383 @@ pub struct MonitoredEntity {
     pub added_at: String,
     pub last_sync_at: Option<String>,
     pub sync_enabled: bool,
+    /// Workspace the entity was added in; `None` if unknown.
+    pub workspace_gid: Option<String>,
 }
 
-// ── Event Sync Tokens ──────────────────────────────────────────────
-
-pub fn get_event_sync_token(
-    conn: &Connection,
-    entity_key: &str,
-) -> Result<Option<String>, rusqlite::Error> {
-    conn.query_row(
-        "SELECT event_sync_token FROM monitored_entities WHERE entity_key = ?1",
-        params![entity_key],
-        |row| row.get(0),
-    )
-    .optional()
-    .map(|opt| opt.flatten())
-}
+// ── Discovered Projects ────────────────────────────────────────────
 
-pub fn set_event_sync_token(
+/// Note that a synced task belongs to `project_gid`. Projects that already
+/// have a `monitored_entities` row are ignored; dismissed ones stay dismissed.
+pub fn record_discovered_project(
     conn: &Connection,
-    entity_key: &str,
-    token: &str,
+    project_gid: &str,
+    project_name: Option<&str>,
 ) -> Result<(), rusqlite::Error> {
     conn.execute(
-        "UPDATE monitored_entities SET event_sync_token = ?2 WHERE entity_key = ?1",
-        params![entity_key, token],
+        "INSERT INTO discovered_projects (project_gid, project_name, first_seen_at, last_seen_at)
+         SELECT ?1, ?2, datetime('now'), datetime('now')
+         WHERE NOT EXISTS (
+             SELECT 1 FROM monitored_entities WHERE entity_key = 'project:' || ?1)
+         ON CONFLICT(project_gid) DO UPDATE SET
+             project_name = COALESCE(excluded.project_name, project_name),
+             last_seen_at = excluded.last_seen_at",
+        params![project_gid, project_name],
     )?;
     Ok(())
 }
 
-// ── Config ─────────────────────────────────────────────────────────
+/// Discovered projects awaiting review, most referenced first.
+pub fn list_discovered_projects(
+    conn: &Connection,
+) -> Result<Vec<DiscoveredProject>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT d.project_gid, d.project_name,
+                (SELECT COUNT(*) FROM bridge_task_projects btp WHERE btp.project_gid = d.project_gid),
+                d.first_seen_at, d.last_seen_at
+         FROM discovered_projects d
+         WHERE d.status = 'pending'
+           AND NOT EXISTS (
+               SELECT 1 FROM monitored_entities m WHERE m.entity_key = 'project:' || d.project_gid)
+         ORDER BY 3 DESC, d.first_seen_at",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(DiscoveredProject {
+            project_gid: row.get(0)?,
+            project_name: row.get(1)?,
+            task_count: row.get(2)?,
+            first_seen_at: row.get(3)?,
+            last_seen_at: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
 
-pub fn get_config(conn: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
-    conn.query_row(
-        "SELECT value FROM app_config WHERE key = ?1",
-        params![key],
+/// Mark a discovered project `promoted` or `dismissed`. Returns false if it
+/// was never discovered.
+pub fn set_discovered_project_status(
+    conn: &Connection,
+    project_gid: &str,
+    status: &str,
+) -> Result<bool, rusqlite::Error> {
+    let count = conn.execute(
+        "UPDATE discovered_projects SET status = ?2 WHERE project_gid = ?1",
+        params![project_gid, status],
+    )?;
+    Ok(count > 0)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DiscoveredProject {
+    pub project_gid: String,
+    pub project_name: Option<String>,
+    /// Synced tasks that belong to the project.
+    pub task_count: i64,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+}
+
+// ── Sync Schedule ──────────────────────────────────────────────────
+
+/// Set (or clear, with `None`) an entity's daemon sync interval.
+/// Returns false if the entity is not monitored.
+pub fn set_sync_interval(
+    conn: &Connection,
+    entity_key: &str,
+    interval_minutes: Option<u32>,
+) -> Result<bool, rusqlite::Error> {
+    let count = conn.execute(
+        "UPDATE monitored_entities SET sync_interval_minutes = ?2 WHERE entity_key = ?1",
+        params![entity_key, interval_minutes],
+    )?;
+    if count > 0 {
+        // Reschedule from scratch so the new interval applies immediately
+        conn.execute(
+            "DELETE FROM sync_schedule WHERE entity_key = ?1",
+            params![entity_key],
+        )?;
+    }
+    Ok(count > 0)
+}
+
+/// Schedule state for every enabled monitored entity. Entities the daemon
+/// has never run have no `sync_schedule` row and come back with
+/// `next_run_at = None` (due now).
+pub fn list_sync_schedule(conn: &Connection) -> Result<Vec<SyncScheduleRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT m.entity_key, m.entity_type, m.entity_gid, m.display_name, m.sync_interval_minutes,
+                s.next_run_at, s.last_run_at, s.last_status, s.last_error,
+                COALESCE(s.consecutive_failures, 0)
+         FROM monitored_entities m
+         LEFT JOIN sync_schedule s ON s.entity_key = m.entity_key
+         WHERE m.sync_enabled = 1
+         ORDER BY s.next_run_at IS NOT NULL, s.next_run_at, m.added_at",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(SyncScheduleRow {
+            entity_key: row.get(0)?,
+            entity_type: row.get(1)?,
+            entity_gid: row.get(2)?,
+            display_name: row.get(3)?,
+            interval_minutes: row.get(4)?,
+            next_run_at: row.get(5)?,
+            last_run_at: row.get(6)?,
+            last_status: row.get(7)?,
+            last_error: row.get(8)?,
+            consecutive_failures: row.get(9)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Record the outcome of a daemon run and when the entity is next due.
+pub fn record_scheduled_run(
+    conn: &Connection,
+    entity_key: &str,
+    status: &str,
+    error: Option<&str>,
+    consecutive_failures: u32,
+    next_run_at: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sync_schedule (
+            entity_key, next_run_at, last_run_at, last_status, last_error,
+            consecutive_failures, updated_at
+        ) VALUES (?1, ?2, datetime('now'), ?3, ?4, ?5, datetime('now'))",
+        params![entity_key, next_run_at, status, error, consecutive_failures],
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncScheduleRow {
+    pub entity_key: String,
+    pub entity_type: String,
+    pub entity_gid: String,
+    pub display_name: Option<String>,
+    /// Per-entity override; `None` uses the `sync_interval` config default.
+    pub interval_minutes: Option<u32>,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+}
+
+// ── Export Schedules ───────────────────────────────────────────────
+
+/// Add a recurring export and return its ID.
+pub fn add_export_schedule(
+    conn: &Connection,
+    format: &str,
+    tables: Option<&str>,
+    cron: &str,
+    out: &str,
+    next_run_at: &str,
+) -> Result<i64, rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO export_schedules (format, tables, cron, out, next_run_at, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
+        params![format, tables, cron, out, next_run_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Returns false if no schedule has this ID.
+pub fn remove_export_schedule(
+    conn: &Connection,
+    schedule_id: i64,
+) -> Result<bool, rusqlite::Error> {
+    let count = conn.execute(
+        "DELETE FROM export_schedules WHERE schedule_id = ?1",
+        params![schedule_id],
+    )?;
+    Ok(count > 0)
+}
+
+/// All export schedules, soonest first.
+pub fn list_export_schedules(conn: &Connection) -> Result<Vec<ExportScheduleRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT schedule_id, format, tables, cron, out, next_run_at,
+                last_run_at, last_status, last_error
+         FROM export_schedules
+         ORDER BY next_run_at, schedule_id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ExportScheduleRow {
+            schedule_id: row.get(0)?,
+            format: row.get(1)?,
+            tables: row.get(2)?,
+            cron: row.get(3)?,
+            out: row.get(4)?,
+            next_run_at: row.get(5)?,
+            last_run_at: row.get(6)?,
+            last_status: row.get(7)?,
+            last_error: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Record the outcome of a scheduled export and when it is next due.
+pub fn record_export_run(
+    conn: &Connection,
+    schedule_id: i64,
+    status: &str,
+    error: Option<&str>,
+    next_run_at: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE export_schedules
+         SET next_run_at = ?2, last_run_at = datetime('now'), last_status = ?3, last_error = ?4
+         WHERE schedule_id = ?1",
+        params![schedule_id, next_run_at, status, error],
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportScheduleRow {
+    pub schedule_id: i64,
+    pub format: String,
+    /// Comma-separated table names; `None` exports every warehouse table.
+    pub tables: Option<String>,
+    pub cron: String,
+    pub out: String,
+    pub next_run_at: String,
+    pub last_run_at: Option<String>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+}
+
+// ── Sync Lock ──────────────────────────────────────────────────────
+
+/// Take the sync lock for `owner` if it is free, already ours, or its
+/// heartbeat is older than `stale_before` (or `force` is set). Returns the
+/// current holder when the lock could not be taken.
+pub fn try_acquire_sync_lock(
+    conn: &Connection,
+    owner: &str,
+    pid: u32,
+    stale_before: &str,
+    force: bool,
+) -> Result<Option<SyncLockRow>, rusqlite::Error> {
+    // A single upsert, so two processes can't both see the lock as free
+    let taken = conn.execute(
+        "INSERT INTO sync_lock (lock_id, owner, pid, acquired_at, heartbeat_at)
+         VALUES (1, ?1, ?2, datetime('now'), datetime('now'))
+         ON CONFLICT(lock_id) DO UPDATE SET
+            owner = excluded.owner, pid = excluded.pid,
+            acquired_at = CASE WHEN sync_lock.owner = excluded.owner
+                               THEN sync_lock.acquired_at ELSE excluded.acquired_at END,
+            heartbeat_at = excluded.heartbeat_at
+         WHERE sync_lock.owner = ?1 OR sync_lock.heartbeat_at < ?3 OR ?4",
+        params![owner, pid, stale_before, force],
+    )?;
+    if taken > 0 {
+        return Ok(None);
+    }
+    get_sync_lock(conn)
+}
+
+/// Refresh the lock's heartbeat. Returns false if `owner` no longer holds it.
+pub fn heartbeat_sync_lock(conn: &Connection, owner: &str) -> Result<bool, rusqlite::Error> {
+    let count = conn.execute(
+        "UPDATE sync_lock SET heartbeat_at = datetime('now') WHERE owner = ?1",
+        params![owner],
+    )?;
+    Ok(count > 0)
+}
+
+pub fn release_sync_lock(conn: &Connection, owner: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM sync_lock WHERE owner = ?1", params![owner])?;
+    Ok(())
+}
+
+pub fn get_sync_lock(conn: &Connection) -> Result<Option<SyncLockRow>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT owner, pid, acquired_at, heartbeat_at FROM sync_lock WHERE lock_id = 1",
+        [],
+        |row| {
+            Ok(SyncLockRow {
+                owner: row.get(0)?,
+                pid: row.get(1)?,
+                acquired_at: row.get(2)?,
+                heartbeat_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncLockRow {
+    pub owner: String,
+    pub pid: u32,
+    pub acquired_at: String,
+    pub heartbeat_at: String,
+}
+
+// ── Event Sync Tokens ──────────────────────────────────────────────
+
+pub fn get_event_sync_token(
+    conn: &Connection,
+    entity_key: &str,
+) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT event_sync_token FROM monitored_entities WHERE entity_key = ?1",
+        params![entity_key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|opt| opt.flatten())
+}
+
+pub fn set_event_sync_token(
+    conn: &Connection,
+    entity_key: &str,
+    token: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE monitored_entities SET event_sync_token = ?2 WHERE entity_key = ?1",
+        params![entity_key, token],
+    )?;
+    Ok(())
+}
+
+/// Whether anything about a `project`, `user`, `portfolio`, or `team` has
+/// been synced: its dimension row, or for a user any assigned task or
+/// comment. Other entity types always count as synced.
+pub fn has_local_data(
+    conn: &Connection,
+    entity_type: &str,
+    gid: &str,
+) -> Result<bool, rusqlite::Error> {
+    let sql = match entity_type {
+        "project" => {
+            "SELECT EXISTS (SELECT 1 FROM dim_projects WHERE project_gid = ?1)
+                 OR EXISTS (SELECT 1 FROM bridge_task_projects WHERE project_gid = ?1)"
+        }
+        "user" => {
+            "SELECT EXISTS (SELECT 1 FROM fact_tasks WHERE assignee_gid = ?1)
+                 OR EXISTS (SELECT 1 FROM fact_comments WHERE author_gid = ?1)"
+        }
+        "portfolio" => "SELECT EXISTS (SELECT 1 FROM dim_portfolios WHERE portfolio_gid = ?1)",
+        "team" => {
+            "SELECT EXISTS (SELECT 1 FROM dim_teams WHERE team_gid = ?1)
+                 OR EXISTS (SELECT 1 FROM bridge_team_members WHERE team_gid = ?1)"
+        }
+        _ => return Ok(true),
+    };
+    conn.query_row(sql, params![gid], |row| row.get(0))
+}
+
+// ── Config ─────────────────────────────────────────────────────────
+
+pub fn get_config(conn: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT value FROM app_config WHERE key = ?1",
+        params![key],
         |row| row.get(0),
     )
     .optional()
@@ -677,6 +1585,324 @@ pub fn list_config(conn: &Connection) -> Result<Vec<(String, String)>, rusqlite:
     rows.collect()
 }
 
+/// Set a config value on the user's behalf, recording the previous value
+/// so `asanadw undo` can restore it. Values cached by asanadw itself (such
+/// as `workspace_gid`) go through [`set_config`] instead.
+pub fn overwrite_config(conn: &Connection, key: &str, value: &str) -> Result<(), rusqlite::Error> {
+    let old = get_config(conn, key)?;
+    if old.as_deref() != Some(value) {
+        record_local_change(
+            conn,
+            LOCAL_CHANGE_CONFIG_SET,
+            key,
+            old.as_deref(),
+            Some(value),
+        )?;
+    }
+    set_config(conn, key, value)
+}
+
+// ── Local Change Audit ─────────────────────────────────────────────
+
+/// `change_type` of a `monitor remove`.
+pub const LOCAL_CHANGE_MONITOR_REMOVE: &str = "monitor_remove";
+/// `change_type` of a `config set`.
+pub const LOCAL_CHANGE_CONFIG_SET: &str = "config_set";
+
+/// A destructive local change `asanadw undo` can revert.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalChange {
+    pub id: i64,
+    pub change_type: String,
+    /// Entity key for monitor removals, config key for config changes.
+    pub target_key: String,
+    /// The removed monitor row as JSON, or the previous config value.
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
+    pub undone_at: Option<String>,
+}
+
+fn record_local_change(
+    conn: &Connection,
+    change_type: &str,
+    target_key: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO audit_local_changes (change_type, target_key, old_value, new_value, changed_at)
+         VALUES (?1, ?2, ?3, ?4, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![change_type, target_key, old_value, new_value],
+    )?;
+    Ok(())
+}
+
+fn local_change_from_row(row: &rusqlite::Row) -> Result<LocalChange, rusqlite::Error> {
+    Ok(LocalChange {
+        id: row.get(0)?,
+        change_type: row.get(1)?,
+        target_key: row.get(2)?,
+        old_value: row.get(3)?,
+        new_value: row.get(4)?,
+        changed_at: row.get(5)?,
+        undone_at: row.get(6)?,
+    })
+}
+
+/// The last `limit` local changes, newest first, undone ones included.
+pub fn recent_local_changes(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<LocalChange>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, change_type, target_key, old_value, new_value, changed_at, undone_at
+         FROM audit_local_changes ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![limit as i64], local_change_from_row)?;
+    rows.collect()
+}
+
+/// Revert the most recent local change not yet undone: re-add the removed
+/// monitored entity or restore the previous config value. Returns the
+/// reverted change, or `None` when there is nothing to undo.
+pub fn undo_last_local_change(conn: &Connection) -> Result<Option<LocalChange>, rusqlite::Error> {
+    let change = conn
+        .query_row(
+            "SELECT id, change_type, target_key, old_value, new_value, changed_at, undone_at
+             FROM audit_local_changes WHERE undone_at IS NULL ORDER BY id DESC LIMIT 1",
+            [],
+            local_change_from_row,
+        )
+        .optional()?;
+    let Some(mut change) = change else {
+        return Ok(None);
+    };
+
+    match (change.change_type.as_str(), &change.old_value) {
+        (LOCAL_CHANGE_MONITOR_REMOVE, Some(row)) => {
+            conn.execute(
+                "INSERT OR REPLACE INTO monitored_entities (
+                    entity_key, entity_type, entity_gid, display_name, added_at,
+                    last_sync_at, sync_enabled, event_sync_token, sync_interval_minutes,
+                    workspace_gid
+                 ) SELECT
+                    json_extract(?1, '$.entity_key'), json_extract(?1, '$.entity_type'),
+                    json_extract(?1, '$.entity_gid'), json_extract(?1, '$.display_name'),
+                    json_extract(?1, '$.added_at'), json_extract(?1, '$.last_sync_at'),
+                    json_extract(?1, '$.sync_enabled'), json_extract(?1, '$.event_sync_token'),
+                    json_extract(?1, '$.sync_interval_minutes'),
+                    json_extract(?1, '$.workspace_gid')",
+                params![row],
+            )?;
+        }
+        (LOCAL_CHANGE_CONFIG_SET, Some(value)) => set_config(conn, &change.target_key, value)?,
+        (LOCAL_CHANGE_CONFIG_SET, None) => {
+            conn.execute(
+                "DELETE FROM app_config WHERE key = ?1",
+                params![change.target_key],
+            )?;
+        }
+        _ => log::warn!(
+            "Don't know how to undo {} of {}",
+            change.change_type,
+            change.target_key
+        ),
+    }
+
+    let undone_at: String = conn.query_row(
+        "UPDATE audit_local_changes SET undone_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+         WHERE id = ?1 RETURNING undone_at",
+        params![change.id],
+        |row| row.get(0),
+    )?;
+    change.undone_at = Some(undone_at);
+    Ok(Some(change))
+}
+
+// ── Entity Aliases ─────────────────────────────────────────────────
+
+pub fn set_alias(conn: &Connection, alias: &str, entity_key: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO entity_aliases (alias, entity_key, created_at)
+         VALUES (?1, ?2, datetime('now'))",
+        params![alias, entity_key],
+    )?;
+    Ok(())
+}
+
+pub fn remove_alias(conn: &Connection, alias: &str) -> Result<bool, rusqlite::Error> {
+    let count = conn.execute(
+        "DELETE FROM entity_aliases WHERE alias = ?1",
+        params![alias],
+    )?;
+    Ok(count > 0)
+}
+
+/// The entity key an alias names, ignoring case.
+pub fn get_alias(conn: &Connection, alias: &str) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT entity_key FROM entity_aliases WHERE alias = ?1",
+        params![alias],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Every alias and its entity key, by alias.
+pub fn list_aliases(conn: &Connection) -> Result<Vec<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT alias, entity_key FROM entity_aliases ORDER BY alias")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+// ── Saved Queries ──────────────────────────────────────────────────
+
+/// A named query, with its filters as stored JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedQueryRow {
+    pub name: String,
+    pub description: Option<String>,
+    pub filters: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Save a query, replacing the filters and description of one with the
+/// same name but keeping when it was first saved.
+pub fn save_query(
+    conn: &Connection,
+    name: &str,
+    description: Option<&str>,
+    filters: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO saved_queries (name, description, filters, created_at, updated_at)
+         VALUES (?1, ?2, ?3, datetime('now'), datetime('now'))
+         ON CONFLICT(name) DO UPDATE SET
+            description = excluded.description,
+            filters = excluded.filters,
+            updated_at = excluded.updated_at",
+        params![name, description, filters],
+    )?;
+    Ok(())
+}
+
+pub fn remove_saved_query(conn: &Connection, name: &str) -> Result<bool, rusqlite::Error> {
+    let count = conn.execute("DELETE FROM saved_queries WHERE name = ?1", params![name])?;
+    Ok(count > 0)
+}
+
+fn saved_query_from_row(row: &rusqlite::Row) -> Result<SavedQueryRow, rusqlite::Error> {
+    Ok(SavedQueryRow {
+        name: row.get(0)?,
+        description: row.get(1)?,
+        filters: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// The saved query with this name, ignoring case.
+pub fn get_saved_query(
+    conn: &Connection,
+    name: &str,
+) -> Result<Option<SavedQueryRow>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT name, description, filters, created_at, updated_at
+         FROM saved_queries WHERE name = ?1",
+        params![name],
+        saved_query_from_row,
+    )
+    .optional()
+}
+
+/// Every saved query, by name.
+pub fn list_saved_queries(conn: &Connection) -> Result<Vec<SavedQueryRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT name, description, filters, created_at, updated_at
+         FROM saved_queries ORDER BY name",
+    )?;
+    let rows = stmt.query_map([], saved_query_from_row)?;
+    rows.collect()
+}
+
+// ── REPL History ───────────────────────────────────────────────────
+
+/// A line entered in `asanadw repl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplHistoryEntry {
+    pub id: i64,
+    pub input: String,
+    pub created_at: String,
+}
+
+pub fn add_repl_history(conn: &Connection, input: &str) -> Result<i64, rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO repl_history (input, created_at)
+         VALUES (?1, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![input],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// The last `limit` lines entered, oldest first.
+pub fn recent_repl_history(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<ReplHistoryEntry>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, input, created_at FROM
+            (SELECT id, input, created_at FROM repl_history ORDER BY id DESC LIMIT ?1)
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![limit as i64], |row| {
+        Ok(ReplHistoryEntry {
+            id: row.get(0)?,
+            input: row.get(1)?,
+            created_at: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// ── API Usage ──────────────────────────────────────────────────────
+
+pub fn insert_api_usage(
+    conn: &Connection,
+    entity_key: &str,
+    usage: &RateLimitUsage,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO api_usage (entity_key, requests, retries, wait_secs, recorded_at)
+         VALUES (?1, ?2, ?3, ?4, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![
+            entity_key,
+            usage.requests as i64,
+            usage.retries as i64,
+            usage.wait_secs
+        ],
+    )?;
+    Ok(())
+}
+
+/// API usage summed over syncs in the last `hours` hours.
+pub fn api_usage_since(conn: &Connection, hours: i64) -> Result<RateLimitUsage, rusqlite::Error> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(requests), 0), COALESCE(SUM(retries), 0), COALESCE(SUM(wait_secs), 0)
+         FROM api_usage
+         WHERE recorded_at >= strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?1)",
+        params![format!("-{hours} hours")],
+        |row| {
+            Ok(RateLimitUsage {
+                requests: row.get::<_, i64>(0)? as u64,
+                retries: row.get::<_, i64>(1)? as u64,
+                wait_secs: row.get(2)?,
+            })
+        },
+    )
+}
+
 // ── Sync Jobs ──────────────────────────────────────────────────────
 
 pub fn insert_sync_job(
@@ -724,6 +1950,147 @@ pub fn update_sync_job(
     Ok(())
 }
 
+/// An unfinished full sync job and how far it got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumableSyncJob {
+    pub job_id: i64,
+    pub entity_key: String,
+    pub range_start: String,
+    /// Offset of the next page of the task listing, while it's incomplete.
+    pub page_offset: Option<String>,
+    pub pages_fetched: u32,
+    /// Every page is staged; only comments remain.
+    pub listing_complete: bool,
+    /// The last task whose comments were stored.
+    pub comments_cursor: Option<String>,
+}
+
+const RESUMABLE_JOB_COLUMNS: &str = "id, entity_key, sync_range_start, page_offset, pages_fetched,
+    listing_complete, comments_cursor";
+
+/// Jobs that stopped partway through within the last `window_hours`.
+const RESUMABLE_JOB_WHERE: &str = "status != 'completed'
+    AND (page_offset IS NOT NULL OR listing_complete = 1)
+    AND started_at >= datetime('now', ?1)";
+
+fn resumable_job_from_row(row: &rusqlite::Row) -> Result<ResumableSyncJob, rusqlite::Error> {
+    Ok(ResumableSyncJob {
+        job_id: row.get(0)?,
+        entity_key: row.get(1)?,
+        range_start: row.get(2)?,
+        page_offset: row.get(3)?,
+        pages_fetched: row.get(4)?,
+        listing_complete: row.get(5)?,
+        comments_cursor: row.get(6)?,
+    })
+}
+
+/// The latest job for `entity_key` over the same range that stopped
+/// partway through within the last `window_hours`.
+pub fn find_resumable_sync_job(
+    conn: &Connection,
+    entity_key: &str,
+    range_start: &str,
+    window_hours: i64,
+) -> Result<Option<ResumableSyncJob>, rusqlite::Error> {
+    conn.query_row(
+        &format!(
+            "SELECT {RESUMABLE_JOB_COLUMNS} FROM sync_jobs
+             WHERE {RESUMABLE_JOB_WHERE} AND entity_key = ?2 AND sync_range_start = ?3
+             ORDER BY id DESC LIMIT 1"
+        ),
+        params![format!("-{window_hours} hours"), entity_key, range_start],
+        resumable_job_from_row,
+    )
+    .optional()
+}
+
+/// Every job that stopped partway through within the last `window_hours`,
+/// oldest first.
+pub fn list_resumable_sync_jobs(
+    conn: &Connection,
+    window_hours: i64,
+) -> Result<Vec<ResumableSyncJob>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {RESUMABLE_JOB_COLUMNS} FROM sync_jobs
+         WHERE {RESUMABLE_JOB_WHERE}
+         ORDER BY id"
+    ))?;
+    let rows = stmt.query_map(
+        params![format!("-{window_hours} hours")],
+        resumable_job_from_row,
+    )?;
+    rows.collect()
+}
+
+/// Mark a resumed job as running again.
+pub fn resume_sync_job(conn: &Connection, job_id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE sync_jobs SET status = 'running', completed_at = NULL, error_message = NULL
+         WHERE id = ?1",
+        params![job_id],
+    )?;
+    Ok(())
+}
+
+/// Stage one page of a job's task listing along with the offset of the next
+/// page (`None` once the listing is complete).
+pub fn save_sync_job_page(
+    conn: &Connection,
+    job_id: i64,
+    page_index: u32,
+    body: &str,
+    next_offset: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sync_job_pages (job_id, page_index, body) VALUES (?1, ?2, ?3)",
+        params![job_id, page_index, body],
+    )?;
+    conn.execute(
+        "UPDATE sync_jobs SET page_offset = ?2, pages_fetched = ?3, listing_complete = (?2 IS NULL)
+         WHERE id = ?1",
+        params![job_id, next_offset, page_index + 1],
+    )?;
+    Ok(())
+}
+
+/// Record that comments are stored for every task up to `task_gid`.
+pub fn set_sync_job_comments_cursor(
+    conn: &Connection,
+    job_id: i64,
+    task_gid: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE sync_jobs SET comments_cursor = ?2 WHERE id = ?1",
+        params![job_id, task_gid],
+    )?;
+    Ok(())
+}
+
+/// A job's staged pages, in listing order.
+pub fn load_sync_job_pages(conn: &Connection, job_id: i64) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt =
+        conn.prepare("SELECT body FROM sync_job_pages WHERE job_id = ?1 ORDER BY page_index")?;
+    let rows = stmt.query_map(params![job_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Drop staged pages for every job of `entity_key` and forget their
+/// checkpoints, so nothing is resumed from them.
+pub fn clear_sync_job_pages(conn: &Connection, entity_key: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM sync_job_pages
+         WHERE job_id IN (SELECT id FROM sync_jobs WHERE entity_key = ?1)",
+        params![entity_key],
+    )?;
+    conn.execute(
+        "UPDATE sync_jobs SET page_offset = NULL, listing_complete = 0, comments_cursor = NULL
+         WHERE entity_key = ?1 AND (page_offset IS NOT NULL OR listing_complete = 1)",
+        params![entity_key],
+    )?;
+    Ok(())
+}
+
 // ── Synced Ranges ──────────────────────────────────────────────────
 
 pub fn insert_synced_range(
@@ -809,6 +2176,168 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_repl_history() {
+        let db = Database::open_memory().await.unwrap();
+
+        let recent = db
+            .writer()
+            .call(|conn| {
+                for input in ["/query --overdue", "who closed the most tasks?", "/history"] {
+                    add_repl_history(conn, input)?;
+                }
+                recent_repl_history(conn, 2)
+            })
+            .await
+            .unwrap();
+        let inputs: Vec<&str> = recent.iter().map(|e| e.input.as_str()).collect();
+        assert_eq!(inputs, ["who closed the most tasks?", "/history"]);
+        assert!(recent[0].id < recent[1].id);
+    }
+
+    #[tokio::test]
+    async fn test_team_directory() {
+        let db = Database::open_memory().await.unwrap();
+
+        let (members, by_name, missing) = db
+            .writer()
+            .call(|conn| {
+                for (gid, name) in [("u1", "Ada"), ("u2", "Grace"), ("u3", "Linus")] {
+                    upsert_user_minimal(conn, gid, Some(name))?;
+                }
+                upsert_team(conn, "tm1", "Platform", "w1", None)?;
+                upsert_team_member(conn, "tm1", "u1", Some("lead"))?;
+                upsert_team_member(conn, "tm1", "u2", None)?;
+                replace_team_members(conn, "tm1", &["u1".to_string(), "u3".to_string()])?;
+
+                let mut stmt = conn.prepare(
+                    "SELECT user_gid, role FROM bridge_team_members WHERE team_gid = 'tm1' ORDER BY user_gid",
+                )?;
+                let members = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<(String, Option<String>)>, _>>()?;
+                Ok::<_, rusqlite::Error>((
+                    members,
+                    find_teams_by_name(conn, "platform")?,
+                    find_teams_by_name(conn, "Mobile")?,
+                ))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            members,
+            [
+                ("u1".to_string(), Some("lead".to_string())),
+                ("u3".to_string(), None)
+            ]
+        );
+        assert_eq!(by_name, ["tm1"]);
+        assert!(missing.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_update_and_section_reads() {
+        let db = Database::open_memory().await.unwrap();
+
+        let (history, latest, all_latest, sections) = db
+            .writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Ada', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now')), ('p2', 'Infra', 'w1', datetime('now'));
+                     INSERT INTO fact_status_updates (status_gid, parent_gid, parent_type, author_gid, title, text, status_type, created_at, created_date_key, cached_at)
+                     VALUES ('s1', 'p1', 'project', 'u1', 'Week 1', 'Fine', 'on_track', '2025-01-06T10:00:00Z', '2025-01-06', datetime('now')),
+                            ('s2', 'p1', 'project', 'u1', 'Week 2', 'Slipping', 'at_risk', '2025-01-13T10:00:00Z', '2025-01-13', datetime('now')),
+                            ('s3', 'p2', 'project', NULL, 'Kickoff', NULL, 'on_track', '2025-01-08T10:00:00Z', '2025-01-08', datetime('now'));",
+                )?;
+                upsert_section(conn, "p1", "sec2", "Doing", 1)?;
+                upsert_section(conn, "p1", "sec1", "To do", 0)?;
+                Ok::<_, rusqlite::Error>((
+                    get_status_updates(conn, "p1", 10)?,
+                    get_latest_status_update(conn, "p1")?,
+                    latest_project_status_updates(conn)?,
+                    get_project_sections(conn, "p1")?,
+                ))
+            })
+            .await
+            .unwrap();
+
+        let titles: Vec<&str> = history.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Week 2", "Week 1"]);
+        let latest = latest.unwrap();
+        assert_eq!(latest.status_type, "at_risk");
+        assert_eq!(latest.author_name.as_deref(), Some("Ada"));
+        assert_eq!(latest.parent_name.as_deref(), Some("Roadmap"));
+        let latest_gids: Vec<&str> = all_latest.iter().map(|s| s.status_gid.as_str()).collect();
+        assert_eq!(latest_gids, ["s2", "s3"]);
+        let names: Vec<&str> = sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["To do", "Doing"]);
+    }
+
+    #[tokio::test]
+    async fn test_api_usage_since() {
+        let db = Database::open_memory().await.unwrap();
+
+        let usage = db
+            .writer()
+            .call(|conn| {
+                let usage = RateLimitUsage {
+                    requests: 40,
+                    retries: 1,
+                    wait_secs: 60.5,
+                };
+                insert_api_usage(conn, "project:p1", &usage)?;
+                insert_api_usage(conn, "team:t1", &usage)?;
+                conn.execute(
+                    "UPDATE api_usage SET recorded_at = '2020-01-01T00:00:00Z' WHERE entity_key = 'team:t1'",
+                    [],
+                )?;
+                api_usage_since(conn, 24)
+            })
+            .await
+            .unwrap();
+        assert_eq!(usage.requests, 40);
+        assert_eq!(usage.retries, 1);
+        assert_eq!(usage.wait_secs, 60.5);
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_descendants() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_portfolios (portfolio_gid, name, workspace_gid, cached_at)
+                     VALUES ('root', 'Root', 'w1', datetime('now')), ('a', 'A', 'w1', datetime('now')),
+                            ('b', 'B', 'w1', datetime('now')), ('c', 'C', 'w1', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'One', 'w1', datetime('now')), ('p2', 'Two', 'w1', datetime('now')),
+                            ('p3', 'Three', 'w1', datetime('now'));
+                     INSERT INTO bridge_portfolio_portfolios (parent_portfolio_gid, child_portfolio_gid)
+                     VALUES ('root', 'a'), ('a', 'b'), ('b', 'root'), ('b', 'c');
+                     INSERT INTO bridge_portfolio_projects (portfolio_gid, project_gid)
+                     VALUES ('root', 'p1'), ('a', 'p2'), ('b', 'p2'), ('c', 'p3');",
+                )?;
+
+                // The b -> root cycle ends the walk instead of looping
+                assert_eq!(
+                    portfolio_descendants(conn, "root", 10)?,
+                    ["root", "a", "b", "c"]
+                );
+                assert_eq!(portfolio_descendants(conn, "root", 1)?, ["root", "a"]);
+                assert_eq!(portfolio_descendants(conn, "root", 0)?, ["root"]);
+
+                assert_eq!(portfolio_project_gids(conn, "root", 2)?, ["p1", "p2"]);
+                assert_eq!(portfolio_project_gids(conn, "root", 5)?, ["p1", "p2", "p3"]);
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_user_round_trip() {
         let db = Database::open_memory().await.unwrap();
@@ -860,6 +2389,79 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_workspaces() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                upsert_workspace(conn, "1", "Acme")?;
+                upsert_workspace(conn, "2", "Client Co")?;
+                assert_eq!(list_workspaces(conn)?.len(), 2);
+                assert_eq!(find_workspace(conn, "client co")?[0].workspace_gid, "2");
+                assert_eq!(find_workspace(conn, "1")?[0].name, "Acme");
+                assert!(find_workspace(conn, "Other")?.is_empty());
+
+                add_monitored_entity(conn, "team:9", "team", "9", None)?;
+                assert_eq!(get_entity_workspace(conn, "team:9")?, None);
+                set_entity_workspace(conn, "team:9", "2")?;
+                assert_eq!(get_entity_workspace(conn, "team:9")?.as_deref(), Some("2"));
+                assert_eq!(
+                    list_monitored_entities(conn)?[0].workspace_gid.as_deref(),
+                    Some("2")
+                );
+
+                // Undoing a removal keeps the workspace
+                remove_monitored_entity(conn, "team:9")?;
+                undo_last_local_change(conn)?;
+                assert_eq!(get_entity_workspace(conn, "team:9")?.as_deref(), Some("2"));
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_undo_local_changes() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                add_monitored_entity(conn, "project:123", "project", "123", Some("Roadmap"))?;
+                conn.execute(
+                    "UPDATE monitored_entities SET event_sync_token = 'tok', sync_interval_minutes = 15",
+                    [],
+                )?;
+                overwrite_config(conn, "default_period", "mtd")?;
+                overwrite_config(conn, "default_period", "ytd")?;
+                assert!(remove_monitored_entity(conn, "project:123")?);
+                assert!(!remove_monitored_entity(conn, "project:123")?);
+
+                let undone = undo_last_local_change(conn)?.unwrap();
+                assert_eq!(undone.change_type, LOCAL_CHANGE_MONITOR_REMOVE);
+                let (token, interval): (String, u32) = conn.query_row(
+                    "SELECT event_sync_token, sync_interval_minutes FROM monitored_entities
+                     WHERE entity_key = 'project:123'",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                assert_eq!((token.as_str(), interval), ("tok", 15));
+
+                undo_last_local_change(conn)?;
+                assert_eq!(get_config(conn, "default_period")?.as_deref(), Some("mtd"));
+                undo_last_local_change(conn)?;
+                assert_eq!(get_config(conn, "default_period")?, None);
+                assert_eq!(undo_last_local_change(conn)?, None);
+
+                let history = recent_local_changes(conn, 10)?;
+                assert_eq!(history.len(), 3);
+                assert!(history.iter().all(|c| c.undone_at.is_some()));
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_sync_job_round_trip() {
         let db = Database::open_memory().await.unwrap();
@@ -885,6 +2487,84 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_resumable_sync_job_pages() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                let job_id =
+                    insert_sync_job(conn, "project:123", Some("2025-01-01"), Some("2025-03-31"))?;
+                save_sync_job_page(conn, job_id, 0, "[1]", Some("off-1"))?;
+                save_sync_job_page(conn, job_id, 1, "[2]", Some("off-2"))?;
+                update_sync_job(conn, job_id, "failed", 0, 0, 0, 1, Some("429"))?;
+
+                let job = find_resumable_sync_job(conn, "project:123", "2025-01-01", 24)?.unwrap();
+                assert_eq!(
+                    job,
+                    ResumableSyncJob {
+                        job_id,
+                        entity_key: "project:123".to_string(),
+                        range_start: "2025-01-01".to_string(),
+                        page_offset: Some("off-2".to_string()),
+                        pages_fetched: 2,
+                        listing_complete: false,
+                        comments_cursor: None,
+                    }
+                );
+                // A different range starts over
+                assert!(find_resumable_sync_job(conn, "project:123", "2024-10-01", 24)?.is_none());
+
+                // Interrupted after the listing, partway through comments
+                resume_sync_job(conn, job_id)?;
+                save_sync_job_page(conn, job_id, 2, "[3]", None)?;
+                set_sync_job_comments_cursor(conn, job_id, "t42")?;
+                assert_eq!(load_sync_job_pages(conn, job_id)?, ["[1]", "[2]", "[3]"]);
+                let job = find_resumable_sync_job(conn, "project:123", "2025-01-01", 24)?.unwrap();
+                assert!(job.listing_complete);
+                assert_eq!(job.page_offset, None);
+                assert_eq!(job.comments_cursor.as_deref(), Some("t42"));
+                assert_eq!(list_resumable_sync_jobs(conn, 24)?, [job]);
+
+                clear_sync_job_pages(conn, "project:123")?;
+                assert!(load_sync_job_pages(conn, job_id)?.is_empty());
+                assert!(list_resumable_sync_jobs(conn, 24)?.is_empty());
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_task_tombstones() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_date_key, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Open', 0, NULL, '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t2', 'Done recently', 1, '2025-03-01', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('t3', 'Done long ago', 1, '2024-06-01', '2024-01-01', '2024-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('t1', 'p1'), ('t2', 'p1'), ('t3', 'p1');",
+                )?;
+                let mut gids = project_task_gids_since(conn, "p1", "2025-01-01")?;
+                gids.sort();
+                assert_eq!(gids, ["t1", "t2"]);
+
+                let deleted = ["t1".to_string()];
+                assert_eq!(mark_tasks_deleted(conn, &deleted)?, 1);
+                assert_eq!(mark_tasks_deleted(conn, &deleted)?, 0);
+                assert_eq!(project_task_gids_since(conn, "p1", "2025-01-01")?, ["t2"]);
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_upsert_user_minimal_does_not_overwrite() {
         let db = Database::open_memory().await.unwrap();
@@ -1135,4 +2815,35 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_discovered_projects() {
+        let db = Database::open_memory().await.unwrap();
+
+        db.writer()
+            .call(|conn| {
+                add_monitored_entity(conn, "project:1", "project", "1", Some("Monitored"))?;
+                record_discovered_project(conn, "1", Some("Monitored"))?;
+                record_discovered_project(conn, "2", Some("Shared"))?;
+                record_discovered_project(conn, "2", None)?;
+                record_discovered_project(conn, "3", Some("Other"))?;
+
+                let found = list_discovered_projects(conn)?;
+                let gids: Vec<&str> = found.iter().map(|p| p.project_gid.as_str()).collect();
+                assert_eq!(gids, vec!["2", "3"]);
+                assert_eq!(found[0].project_name.as_deref(), Some("Shared"));
+
+                // Dismissed projects stay hidden even when seen again
+                assert!(set_discovered_project_status(conn, "3", "dismissed")?);
+                record_discovered_project(conn, "3", Some("Other"))?;
+                // Monitoring a project by other means also hides it
+                add_monitored_entity(conn, "project:2", "project", "2", None)?;
+                assert!(list_discovered_projects(conn)?.is_empty());
+                assert!(!set_discovered_project_status(conn, "9", "dismissed")?);
+
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+    }
 }
@@ -0,0 +1,320 @@
+//! Retention: pruning tasks, comments, and status updates that are old
+//! enough to drop, optionally archiving them to a JSON-lines file first.
+//!
+//! A task is prunable once it was completed (or deleted in Asana) before
+//! the cutoff. Its comments, custom field values, bridges, likes, section
+//! transitions, and cached summary go with it through `ON DELETE CASCADE`,
+//! and the FTS triggers drop it from search. Status updates are pruned by
+//! creation date. Set `retention` in config to prune after every
+//! `sync all`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{Months, NaiveDate};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::sql;
+use crate::storage::Database;
+
+/// Config key for the age past which `sync all` prunes, e.g. `2y`.
+pub const RETENTION_KEY: &str = "retention";
+
+/// Config key for a directory automatic pruning archives into. Without
+/// it, automatic pruning deletes without an archive.
+pub const RETENTION_ARCHIVE_DIR_KEY: &str = "retention_archive_dir";
+
+/// What a prune removed, or with `dry_run` would remove.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    /// Rows dated before this day (YYYY-MM-DD) were pruned.
+    pub cutoff: String,
+    pub dry_run: bool,
+    pub tasks: u64,
+    pub comments: u64,
+    pub status_updates: u64,
+    /// JSON-lines file the pruned rows were written to.
+    pub archived_to: Option<String>,
+}
+
+/// The day `age` (like `90d`, `6w`, `18mo`, or `2y`) before `today`.
+pub fn cutoff_date(age: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let age = age.trim().to_lowercase();
+    let invalid = || {
+        Error::Config(format!(
+            "invalid age '{age}': expected e.g. 90d, 6w, 18mo, or 2y"
+        ))
+    };
+    let idx = age
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (digits, unit) = age.split_at(idx);
+    let n: u32 = digits.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?;
+    let cutoff = match unit.trim() {
+        "d" | "day" | "days" => today.checked_sub_days(chrono::Days::new(n.into())),
+        "w" | "week" | "weeks" => today.checked_sub_days(chrono::Days::new(u64::from(n) * 7)),
+        "mo" | "month" | "months" => today.checked_sub_months(Months::new(n)),
+        "y" | "year" | "years" => today.checked_sub_months(Months::new(n.saturating_mul(12))),
+        _ => None,
+    };
+    cutoff.ok_or_else(invalid)
+}
+
+fn count(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> rusqlite::Result<u64> {
+    conn.query_row(sql, params, |row| row.get::<_, i64>(0))
+        .map(|n| n as u64)
+}
+
+/// Append every row about to be pruned to `path`, one
+/// `{"table": ..., "row": {...}}` object per line.
+fn archive(conn: &Connection, path: &Path, cutoff: &str) -> Result<()> {
+    let queries = [
+        (
+            "fact_tasks",
+            "SELECT * FROM fact_tasks WHERE task_gid IN (SELECT task_gid FROM temp.prune_tasks)"
+                .to_string(),
+        ),
+        (
+            "fact_comments",
+            "SELECT * FROM fact_comments WHERE task_gid IN (SELECT task_gid FROM temp.prune_tasks)"
+                .to_string(),
+        ),
+        (
+            "fact_status_updates",
+            format!(
+                "SELECT * FROM fact_status_updates WHERE created_at < '{}'",
+                cutoff.replace('\'', "''")
+            ),
+        ),
+    ];
+    let io_err = |e: std::io::Error| Error::Other(format!("archive {}: {e}", path.display()));
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_err)?;
+    for (table, query) in &queries {
+        for row in sql::query_read_only(conn, query)?.to_objects() {
+            let line = serde_json::json!({ "table": table, "row": row });
+            writeln!(file, "{line}").map_err(io_err)?;
+        }
+    }
+    file.sync_all().map_err(io_err)
+}
+
+/// Prune everything dated before `cutoff`. With `dry_run`, only counts.
+pub fn prune(
+    conn: &mut Connection,
+    cutoff: NaiveDate,
+    archive_to: Option<&Path>,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    let cutoff = cutoff.format("%Y-%m-%d").to_string();
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        "DROP TABLE IF EXISTS temp.prune_tasks;
+         CREATE TEMP TABLE prune_tasks (task_gid TEXT PRIMARY KEY);",
+    )?;
+    tx.execute(
+        "INSERT INTO temp.prune_tasks
+         SELECT task_gid FROM fact_tasks
+         WHERE (is_completed = 1 AND completed_at < ?1)
+            OR (is_deleted = 1 AND deleted_at < ?1)",
+        [&cutoff],
+    )?;
+    // Subtasks reference their parent, so keep any parent with a subtask
+    // that stays, repeating for deeper nesting
+    loop {
+        let kept = tx.execute(
+            "DELETE FROM temp.prune_tasks WHERE task_gid IN (
+                 SELECT parent_gid FROM fact_tasks
+                 WHERE parent_gid IN (SELECT task_gid FROM temp.prune_tasks)
+                   AND task_gid NOT IN (SELECT task_gid FROM temp.prune_tasks))",
+            [],
+        )?;
+        if kept == 0 {
+            break;
+        }
+    }
+
+    let mut report = PruneReport {
+        cutoff: cutoff.clone(),
+        dry_run,
+        tasks: count(&tx, "SELECT COUNT(*) FROM temp.prune_tasks", [])?,
+        comments: count(
+            &tx,
+            "SELECT COUNT(*) FROM fact_comments
+             WHERE task_gid IN (SELECT task_gid FROM temp.prune_tasks)",
+            [],
+        )?,
+        status_updates: count(
+            &tx,
+            "SELECT COUNT(*) FROM fact_status_updates WHERE created_at < ?1",
+            [&cutoff],
+        )?,
+        archived_to: None,
+    };
+    let empty = report.tasks == 0 && report.status_updates == 0;
+    if dry_run || empty {
+        // Dropping the transaction rolls back the temp table too
+        return Ok(report);
+    }
+
+    if let Some(path) = archive_to {
+        archive(&tx, path, &cutoff)?;
+        report.archived_to = Some(path.display().to_string());
+    }
+    tx.execute(
+        "DELETE FROM fact_tasks WHERE task_gid IN (SELECT task_gid FROM temp.prune_tasks)",
+        [],
+    )?;
+    tx.execute(
+        "DELETE FROM fact_status_updates WHERE created_at < ?1",
+        [&cutoff],
+    )?;
+    tx.execute_batch("DROP TABLE temp.prune_tasks")?;
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Prune rows older than `age` (like `2y`), archiving them to `archive_to`
+/// first when given.
+pub async fn prune_older_than(
+    db: &Database,
+    age: &str,
+    archive_to: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    let cutoff = cutoff_date(age, chrono::Local::now().date_naive())?;
+    db.writer()
+        .call(move |conn| prune(conn, cutoff, archive_to.as_deref(), dry_run))
+        .await
+        .map_err(|e| match e {
+            tokio_rusqlite::Error::Error(e) => e,
+            other => Error::Database(other.to_string()),
+        })
+}
+
+/// Apply the configured `retention`, if any. Archives go to a timestamped
+/// file in `retention_archive_dir` when that's set.
+pub async fn auto_prune(db: &Database) -> Result<Option<PruneReport>> {
+    let (age, archive_dir) = db
+        .reader()
+        .call(|conn| {
+            Ok::<_, rusqlite::Error>((
+                repository::get_config(conn, RETENTION_KEY)?,
+                repository::get_config(conn, RETENTION_ARCHIVE_DIR_KEY)?,
+            ))
+        })
+        .await?;
+    let Some(age) = age else {
+        return Ok(None);
+    };
+    let archive_to = match archive_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir).map_err(|e| Error::Config(format!("{dir}: {e}")))?;
+            let name = format!(
+                "prune-{}.jsonl",
+                chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+            );
+            Some(Path::new(&dir).join(name))
+        }
+        None => None,
+    };
+    prune_older_than(db, &age, archive_to, false)
+        .await
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_cutoff_date() {
+        let today = day("2026-03-31");
+        assert_eq!(cutoff_date("90d", today).unwrap(), day("2025-12-31"));
+        assert_eq!(cutoff_date("2w", today).unwrap(), day("2026-03-17"));
+        assert_eq!(cutoff_date("1mo", today).unwrap(), day("2026-02-28"));
+        assert_eq!(cutoff_date("2y", today).unwrap(), day("2024-03-31"));
+        assert!(cutoff_date("2", today).is_err());
+        assert!(cutoff_date("0y", today).is_err());
+        assert!(cutoff_date("2m", today).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prune() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                // Dates before dim_date's range, as for long-lived databases
+                conn.execute_batch(
+                    "PRAGMA foreign_keys = OFF;
+                     INSERT INTO fact_tasks (task_gid, name, is_completed, completed_at, parent_gid,
+                         created_at, created_date_key, cached_at) VALUES
+                     ('old', 'Old', 1, '2020-01-05T00:00:00Z', NULL, '2020-01-01', '2020-01-01', '2026-01-01'),
+                     ('parent', 'Parent', 1, '2020-01-05T00:00:00Z', NULL, '2020-01-01', '2020-01-01', '2026-01-01'),
+                     ('child', 'Open child', 0, NULL, 'parent', '2020-01-01', '2020-01-01', '2026-01-01'),
+                     ('new', 'New', 1, '2026-01-05T00:00:00Z', NULL, '2026-01-01', '2026-01-01', '2026-01-01');
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type,
+                         created_at, created_date_key, cached_at)
+                     VALUES ('c1', 'old', 'done', 'comment', '2020-01-02', '2020-01-02', '2026-01-01');
+                     INSERT INTO fact_status_updates (status_gid, parent_gid, parent_type, title,
+                         status_type, created_at, created_date_key, cached_at)
+                     VALUES ('s1', 'p1', 'project', 'Q4', 'on_track', '2020-01-01', '2020-01-01', '2026-01-01');
+                     PRAGMA foreign_keys = ON;",
+                )
+            })
+            .await
+            .unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+
+        let cutoff = day("2024-01-01");
+        let dry = db
+            .writer()
+            .call(move |conn| prune(conn, cutoff, None, true))
+            .await
+            .unwrap();
+        assert_eq!((dry.tasks, dry.comments, dry.status_updates), (1, 1, 1));
+
+        let path = archive.path().to_path_buf();
+        let report = db
+            .writer()
+            .call(move |conn| prune(conn, cutoff, Some(&path), false))
+            .await
+            .unwrap();
+        assert_eq!(report.tasks, 1);
+        assert_eq!(
+            std::fs::read_to_string(archive.path())
+                .unwrap()
+                .lines()
+                .count(),
+            3
+        );
+
+        let (tasks, comments, hits): (i64, i64, i64) = db
+            .reader()
+            .call(|conn| {
+                Ok::<_, rusqlite::Error>((
+                    conn.query_row("SELECT COUNT(*) FROM fact_tasks", [], |r| r.get(0))?,
+                    conn.query_row("SELECT COUNT(*) FROM fact_comments", [], |r| r.get(0))?,
+                    conn.query_row(
+                        "SELECT COUNT(*) FROM tasks_fts WHERE tasks_fts MATCH 'old'",
+                        [],
+                        |r| r.get(0),
+                    )?,
+                ))
+            })
+            .await
+            .unwrap();
+        // The parent of an open subtask stays
+        assert_eq!((tasks, comments, hits), (3, 0, 0));
+    }
+}
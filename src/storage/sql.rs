@@ -0,0 +1,231 @@
+use rusqlite::types::ValueRef;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// Leading keywords accepted for ad-hoc queries. Checked in addition to
+/// SQLite's own read-only flag, which also allows ATTACH and transaction
+/// control statements.
+const ALLOWED_KEYWORDS: &[&str] = &["SELECT", "WITH", "EXPLAIN", "PRAGMA", "VALUES"];
+
+/// Pragmas an ad-hoc query may run: schema introspection only. Flag pragmas
+/// such as `foreign_keys` or `query_only` take effect when the statement is
+/// prepared, before its read-only flag can be checked.
+const ALLOWED_PRAGMAS: &[&str] = &[
+    "collation_list",
+    "compile_options",
+    "foreign_key_list",
+    "function_list",
+    "index_info",
+    "index_list",
+    "index_xinfo",
+    "pragma_list",
+    "table_info",
+    "table_list",
+    "table_xinfo",
+];
+
+/// Result of an ad-hoc SQL query, with columns in select order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SqlResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl SqlResult {
+    /// Rows as JSON objects keyed by column name.
+    pub fn to_objects(&self) -> Vec<Value> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let map = self
+                    .columns
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().cloned())
+                    .collect::<serde_json::Map<_, _>>();
+                Value::Object(map)
+            })
+            .collect()
+    }
+
+    /// Rows as CSV with a header line.
+    pub fn to_csv(&self) -> String {
+        // Writes to memory can't fail
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let _ = writer.write_record(&self.columns);
+        for row in &self.rows {
+            let _ = writer.write_record(row.iter().map(value_to_text));
+        }
+        String::from_utf8(writer.into_inner().unwrap_or_default()).unwrap_or_default()
+    }
+}
+
+/// Plain-text rendering of a cell (empty for NULL).
+pub fn value_to_text(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Run a single read-only statement and collect every row.
+///
+/// The statement runs with `query_only` on, so it can't write even on a
+/// connection that otherwise can; the connection's setting is restored
+/// afterwards.
+pub fn query_read_only(conn: &rusqlite::Connection, sql: &str) -> Result<SqlResult> {
+    let first_keyword = sql
+        .trim_start()
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or("")
+        .to_uppercase();
+    if !ALLOWED_KEYWORDS.contains(&first_keyword.as_str()) {
+        return Err(Error::Other(format!(
+            "only read-only queries are allowed (statement must start with {})",
+            ALLOWED_KEYWORDS.join(", ")
+        )));
+    }
+    if first_keyword == "PRAGMA" {
+        check_pragma(sql)?;
+    }
+
+    let query_only: bool = conn.query_row("PRAGMA query_only", [], |row| row.get(0))?;
+    if !query_only {
+        conn.pragma_update(None, "query_only", true)?;
+    }
+    let result = run_query(conn, sql);
+    if !query_only {
+        conn.pragma_update(None, "query_only", false)?;
+    }
+    result
+}
+
+/// Accept `PRAGMA name` or `PRAGMA name(argument)` for an
+/// [`ALLOWED_PRAGMAS`] name, optionally schema-qualified. Assignments are
+/// rejected in either form.
+fn check_pragma(sql: &str) -> Result<()> {
+    let rest = sql.trim_start()["PRAGMA".len()..].trim_start();
+    let name_end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        .unwrap_or(rest.len());
+    let (name, args) = rest.split_at(name_end);
+    let name = name.rsplit('.').next().unwrap_or("").to_lowercase();
+    let args = args.trim();
+    let args_ok = args.is_empty() || args == ";" || args.starts_with('(');
+    if !ALLOWED_PRAGMAS.contains(&name.as_str()) || !args_ok {
+        return Err(Error::Other(format!(
+            "only schema pragmas are allowed ({})",
+            ALLOWED_PRAGMAS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn run_query(conn: &rusqlite::Connection, sql: &str) -> Result<SqlResult> {
+    let mut stmt = conn.prepare(sql)?;
+    if !stmt.readonly() {
+        return Err(Error::Other("only read-only queries are allowed".into()));
+    }
+
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = Vec::new();
+    let mut result = stmt.query([])?;
+    while let Some(row) = result.next()? {
+        let mut values = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            values.push(match row.get_ref(i)? {
+                ValueRef::Null => Value::Null,
+                ValueRef::Integer(n) => Value::from(n),
+                ValueRef::Real(f) => serde_json::Number::from_f64(f)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+                ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
+                ValueRef::Blob(b) => {
+                    Value::String(b.iter().map(|byte| format!("{byte:02x}")).collect())
+                }
+            });
+        }
+        rows.push(values);
+    }
+    Ok(SqlResult { columns, rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_query_arbitrary_read_only() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('u1', 'Alice, A.', datetime('now'))",
+                    [],
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let rows = db
+            .query_arbitrary("SELECT user_gid, name, 1.5 AS score, NULL AS email FROM dim_users")
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["name"], "Alice, A.");
+        assert_eq!(rows[0]["score"], 1.5);
+        assert!(rows[0]["email"].is_null());
+
+        let table = db
+            .query_arbitrary_table("  with x AS (SELECT 1 AS n) SELECT n FROM x")
+            .await
+            .unwrap();
+        assert_eq!(table.columns, vec!["n"]);
+        assert_eq!(table.to_csv(), "n\n1\n");
+
+        for sql in [
+            "DELETE FROM dim_users",
+            "UPDATE dim_users SET name = 'x'",
+            "ATTACH DATABASE ':memory:' AS other",
+            "BEGIN",
+            "SELECT 1; DELETE FROM dim_users",
+            "PRAGMA foreign_keys = OFF",
+            "PRAGMA main.query_only=OFF",
+            "pragma foreign_keys(0)",
+            "PRAGMA writable_schema",
+        ] {
+            assert!(db.query_arbitrary(sql).await.is_err(), "{sql} should fail");
+        }
+        let still_there = db
+            .query_arbitrary("SELECT COUNT(*) AS n FROM dim_users")
+            .await
+            .unwrap();
+        assert_eq!(still_there[0]["n"], 1);
+
+        let columns = db
+            .query_arbitrary("PRAGMA table_info(dim_users)")
+            .await
+            .unwrap();
+        assert!(columns.iter().any(|c| c["name"] == "user_gid"));
+
+        // The in-memory reader is the writer; it can still write afterwards
+        let (foreign_keys, query_only) = db
+            .writer()
+            .call(|conn| {
+                conn.execute("DELETE FROM dim_users", [])?;
+                let foreign_keys: bool =
+                    conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+                let query_only: bool = conn.query_row("PRAGMA query_only", [], |row| row.get(0))?;
+                Ok::<_, rusqlite::Error>((foreign_keys, query_only))
+            })
+            .await
+            .unwrap();
+        assert!(foreign_keys);
+        assert!(!query_only);
+    }
+}
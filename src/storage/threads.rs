@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::url::generate_comment_url;
+
+/// Story GID in a comment deep link, e.g. `/0/<project>/<task>/<story>` or
+/// `/1/<workspace>/task/<task>/comment/<story>`.
+static RE_STORY_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"asana\.com/(?:0/\d+/\d+/(\d+)|1/\d+/(?:project/\d+/)?task/\d+/(?:comment|story)/(\d+))",
+    )
+    .unwrap()
+});
+
+/// A user mention at the very start of a comment's HTML body.
+static RE_LEADING_MENTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?:<body>)?\s*<a [^>]*data-asana-gid="(\d+)"[^>]*data-asana-type="user""#)
+        .unwrap()
+});
+
+/// A comment as it participates in thread inference.
+#[derive(Debug, Clone)]
+pub struct ThreadCandidate {
+    pub comment_gid: String,
+    pub author_gid: Option<String>,
+    pub author_name: Option<String>,
+    pub text: Option<String>,
+    pub html_text: Option<String>,
+}
+
+/// A comment with its replies nested beneath it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentNode {
+    pub comment_gid: String,
+    pub author_gid: Option<String>,
+    pub author_name: Option<String>,
+    pub text: Option<String>,
+    pub created_at: String,
+    /// Deep link that opens the task with this comment focused.
+    pub asana_url: String,
+    pub replies: Vec<CommentNode>,
+}
+
+/// Infer which earlier comment `comment` replies to.
+///
+/// Asana does not expose reply structure on stories, so this looks for, in order:
+/// 1. a deep link to an earlier comment on the same task,
+/// 2. a leading `@mention` of someone who commented earlier (their latest comment),
+/// 3. a leading `> quote` of an earlier comment's text.
+///
+/// `earlier` must be in chronological order.
+pub fn infer_parent_comment(
+    comment: &ThreadCandidate,
+    earlier: &[ThreadCandidate],
+) -> Option<String> {
+    let text = comment.text.as_deref().unwrap_or("");
+    let html = comment.html_text.as_deref().unwrap_or("");
+
+    // 1. Explicit link to another story
+    for haystack in [html, text] {
+        for caps in RE_STORY_LINK.captures_iter(haystack) {
+            let gid = caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str());
+            if let Some(gid) = gid {
+                if earlier.iter().any(|c| c.comment_gid == gid) {
+                    return Some(gid.to_string());
+                }
+            }
+        }
+    }
+
+    // 2. Leading @mention, from the HTML body or by matching a prior author's name
+    let mentioned_gid = RE_LEADING_MENTION
+        .captures(html)
+        .map(|c| c[1].to_string())
+        .or_else(|| {
+            let rest = text.trim_start().strip_prefix('@')?;
+            earlier
+                .iter()
+                .rev()
+                .find(|c| {
+                    c.author_name
+                        .as_deref()
+                        .is_some_and(|n| !n.is_empty() && rest.starts_with(n))
+                })
+                .and_then(|c| c.author_gid.clone())
+        });
+    if let Some(user_gid) = mentioned_gid {
+        if Some(user_gid.as_str()) != comment.author_gid.as_deref() {
+            if let Some(parent) = earlier
+                .iter()
+                .rev()
+                .find(|c| c.author_gid.as_deref() == Some(user_gid.as_str()))
+            {
+                return Some(parent.comment_gid.clone());
+            }
+        }
+    }
+
+    // 3. Leading quote of an earlier comment
+    if let Some(quoted) = text.trim_start().strip_prefix('>') {
+        let quoted = quoted.lines().next().unwrap_or("").trim();
+        if !quoted.is_empty() {
+            if let Some(parent) = earlier
+                .iter()
+                .rev()
+                .find(|c| c.text.as_deref().is_some_and(|t| t.contains(quoted)))
+            {
+                return Some(parent.comment_gid.clone());
+            }
+        }
+    }
+
+    None
+}
+
+/// Recompute `parent_comment_gid` for every comment on a task. Bot
+/// comments are never replies or replied to. Returns the number of
+/// comments identified as replies.
+pub fn link_comment_threads(conn: &Connection, task_gid: &str) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "UPDATE fact_comments SET parent_comment_gid = NULL
+         WHERE task_gid = ?1 AND is_bot = 1 AND parent_comment_gid IS NOT NULL",
+        [task_gid],
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT c.comment_gid, c.author_gid, u.name, c.text, c.html_text
+         FROM fact_comments c
+         LEFT JOIN dim_users u ON u.user_gid = c.author_gid
+         WHERE c.task_gid = ?1 AND c.story_type = 'comment' AND c.is_bot = 0
+         ORDER BY c.created_at, c.id",
+    )?;
+    let comments: Vec<ThreadCandidate> = stmt
+        .query_map([task_gid], |row| {
+            Ok(ThreadCandidate {
+                comment_gid: row.get(0)?,
+                author_gid: row.get(1)?,
+                author_name: row.get(2)?,
+                text: row.get(3)?,
+                html_text: row.get(4)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut replies = 0;
+    for (i, comment) in comments.iter().enumerate() {
+        let parent = infer_parent_comment(comment, &comments[..i]);
+        if parent.is_some() {
+            replies += 1;
+        }
+        conn.execute(
+            "UPDATE fact_comments SET parent_comment_gid = ?2 WHERE comment_gid = ?1",
+            params![comment.comment_gid, parent],
+        )?;
+    }
+    Ok(replies)
+}
+
+/// Load a task's comments as nested threads, oldest first.
+pub fn load_comment_threads(
+    conn: &Connection,
+    task_gid: &str,
+) -> Result<Vec<CommentNode>, rusqlite::Error> {
+    let workspace_gid: Option<String> = conn.query_row(
+        "SELECT COALESCE(
+             (SELECT p.workspace_gid FROM bridge_task_projects btp
+              JOIN dim_projects p ON p.project_gid = btp.project_gid
+              WHERE btp.task_gid = ?1 LIMIT 1),
+             (SELECT value FROM app_config WHERE key = 'workspace_gid'))",
+        [task_gid],
+        |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT c.comment_gid, c.author_gid, u.name, c.text, c.created_at, c.parent_comment_gid
+         FROM fact_comments c
+         LEFT JOIN dim_users u ON u.user_gid = c.author_gid
+         WHERE c.task_gid = ?1 AND c.story_type = 'comment'
+         ORDER BY c.created_at, c.id",
+    )?;
+    let rows: Vec<(CommentNode, Option<String>)> = stmt
+        .query_map([task_gid], |row| {
+            let comment_gid: String = row.get(0)?;
+            Ok((
+                CommentNode {
+                    asana_url: generate_comment_url(
+                        workspace_gid.as_deref(),
+                        task_gid,
+                        &comment_gid,
+                    ),
+                    comment_gid,
+                    author_gid: row.get(1)?,
+                    author_name: row.get(2)?,
+                    text: row.get(3)?,
+                    created_at: row.get(4)?,
+                    replies: Vec::new(),
+                },
+                row.get(5)?,
+            ))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(build_threads(rows))
+}
+
+/// Assemble flat (comment, parent) pairs into a forest. Comments whose
+/// parent is missing are treated as top-level.
+fn build_threads(rows: Vec<(CommentNode, Option<String>)>) -> Vec<CommentNode> {
+    let known: std::collections::HashSet<String> =
+        rows.iter().map(|(c, _)| c.comment_gid.clone()).collect();
+    let mut children: HashMap<String, Vec<CommentNode>> = HashMap::new();
+    let mut roots = Vec::new();
+    let mut order = Vec::new();
+    for (node, parent) in rows {
+        order.push(node.comment_gid.clone());
+        match parent.filter(|p| known.contains(p) && *p != node.comment_gid) {
+            Some(p) => children.entry(p).or_default().push(node),
+            None => roots.push(node),
+        }
+    }
+
+    fn attach(node: &mut CommentNode, children: &mut HashMap<String, Vec<CommentNode>>) {
+        if let Some(mut kids) = children.remove(&node.comment_gid) {
+            for kid in &mut kids {
+                attach(kid, children);
+            }
+            node.replies = kids;
+        }
+    }
+    for root in &mut roots {
+        attach(root, &mut children);
+    }
+    roots
+}
+
+/// Render threads as indented plain text, one comment per line.
+pub fn render_threads(nodes: &[CommentNode], max_text: usize) -> Vec<String> {
+    fn walk(nodes: &[CommentNode], depth: usize, max_text: usize, out: &mut Vec<String>) {
+        for node in nodes {
+            let indent = "  ".repeat(depth + 1);
+            let marker = if depth > 0 { "↳ " } else { "" };
+            let author = node.author_name.as_deref().unwrap_or("unknown");
+            let text = node.text.as_deref().unwrap_or("");
+            let text: String = text.chars().take(max_text).collect();
+            out.push(format!(
+                "{indent}{marker}[{}] {author}: {text}",
+                node.created_at
+            ));
+            walk(&node.replies, depth + 1, max_text, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(nodes, 0, max_text, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    fn candidate(gid: &str, author: &str, name: &str, text: &str) -> ThreadCandidate {
+        ThreadCandidate {
+            comment_gid: gid.to_string(),
+            author_gid: Some(author.to_string()),
+            author_name: Some(name.to_string()),
+            text: Some(text.to_string()),
+            html_text: None,
+        }
+    }
+
+    #[test]
+    fn test_infer_parent_from_story_link() {
+        let earlier = vec![candidate("111", "u1", "Alice", "Kickoff")];
+        let reply = candidate(
+            "222",
+            "u2",
+            "Bob",
+            "Re https://app.asana.com/0/999/555/111 — agreed",
+        );
+        assert_eq!(
+            infer_parent_comment(&reply, &earlier),
+            Some("111".to_string())
+        );
+    }
+
+    #[test]
+    fn test_infer_parent_from_leading_mention() {
+        let earlier = vec![
+            candidate("1", "u1", "Alice", "First"),
+            candidate("2", "u2", "Bob", "Second"),
+            candidate("3", "u1", "Alice", "Third"),
+        ];
+        let reply = candidate("4", "u2", "Bob", "@Alice sounds good");
+        assert_eq!(
+            infer_parent_comment(&reply, &earlier),
+            Some("3".to_string())
+        );
+
+        let mut html_reply = candidate("5", "u1", "Alice", "Bob thanks");
+        html_reply.html_text = Some(
+            r#"<body><a href="https://app.asana.com/0/u2/list" data-asana-gid="u2" data-asana-type="user">@Bob</a> thanks</body>"#
+                .replace("u2", "42"),
+        );
+        let earlier = vec![candidate("6", "42", "Bob", "Question?")];
+        assert_eq!(
+            infer_parent_comment(&html_reply, &earlier),
+            Some("6".to_string())
+        );
+    }
+
+    #[test]
+    fn test_infer_parent_from_quote_and_none() {
+        let earlier = vec![candidate("1", "u1", "Alice", "Can we ship Friday?")];
+        let quoted = candidate("2", "u2", "Bob", "> ship Friday\nYes");
+        assert_eq!(
+            infer_parent_comment(&quoted, &earlier),
+            Some("1".to_string())
+        );
+
+        let plain = candidate("3", "u2", "Bob", "Unrelated update");
+        assert_eq!(infer_parent_comment(&plain, &earlier), None);
+    }
+
+    #[tokio::test]
+    async fn test_link_and_load_threads() {
+        let db = Database::open_memory().await.unwrap();
+        let threads = db
+            .writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('1', 'Alice', datetime('now'));
+                     INSERT INTO dim_users (user_gid, name, cached_at) VALUES ('2', 'Bob', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('t1', 'Task', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', '1', 'Can we ship?', 'comment', '2025-01-02T10:00:00Z', '2025-01-02', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c2', 't1', '2', '@Alice yes', 'comment', '2025-01-02T11:00:00Z', '2025-01-02', datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c3', 't1', '1', 'Separate note', 'comment', '2025-01-03T09:00:00Z', '2025-01-03', datetime('now'));",
+                )?;
+                let replies = link_comment_threads(conn, "t1")?;
+                assert_eq!(replies, 1);
+                load_comment_threads(conn, "t1")
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].comment_gid, "c1");
+        assert_eq!(threads[0].replies.len(), 1);
+        assert_eq!(threads[0].replies[0].comment_gid, "c2");
+        assert_eq!(threads[1].comment_gid, "c3");
+        assert_eq!(
+            threads[0].replies[0].asana_url,
+            "https://app.asana.com/0/0/t1/c2"
+        );
+
+        let lines = render_threads(&threads, 100);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("↳ "));
+    }
+}
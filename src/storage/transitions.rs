@@ -0,0 +1,100 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use rusqlite::{params, Connection};
+
+/// System story text for a section move, e.g.
+/// `moved this task from "Backlog" to "In Progress" in Roadmap`.
+/// Quotes and the trailing project are optional.
+static RE_SECTION_CHANGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)^moved this task (?:from "?(.+?)"? )?to "?(.+?)"?(?: in "?(.+?)"?)?$"#)
+        .unwrap()
+});
+
+/// A section move parsed from a system story.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionChange {
+    pub from_section: Option<String>,
+    pub to_section: String,
+    pub project_name: Option<String>,
+}
+
+/// Parse a section-change story's text. Returns `None` for any other story.
+pub fn parse_section_change(text: &str) -> Option<SectionChange> {
+    let caps = RE_SECTION_CHANGE.captures(text.trim())?;
+    Some(SectionChange {
+        from_section: caps.get(1).map(|m| m.as_str().to_string()),
+        to_section: caps.get(2)?.as_str().to_string(),
+        project_name: caps.get(3).map(|m| m.as_str().to_string()),
+    })
+}
+
+/// Rebuild `fact_section_transitions` for a task from its stored system stories.
+/// Returns the number of transitions recorded.
+pub fn rebuild_section_transitions(
+    conn: &Connection,
+    task_gid: &str,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT comment_gid, text, created_at, created_date_key FROM fact_comments
+         WHERE task_gid = ?1 AND story_type != 'comment' AND text IS NOT NULL",
+    )?;
+    let stories: Vec<(String, String, String, String)> = stmt
+        .query_map([task_gid], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    conn.execute(
+        "DELETE FROM fact_section_transitions WHERE task_gid = ?1",
+        [task_gid],
+    )?;
+
+    let mut count = 0;
+    for (story_gid, text, created_at, date_key) in &stories {
+        let Some(change) = parse_section_change(text) else {
+            continue;
+        };
+        conn.execute(
+            "INSERT OR REPLACE INTO fact_section_transitions (
+                story_gid, task_gid, project_name, from_section, to_section,
+                transitioned_at, transitioned_date_key, cached_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))",
+            params![
+                story_gid,
+                task_gid,
+                change.project_name,
+                change.from_section,
+                change.to_section,
+                created_at,
+                date_key,
+            ],
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_section_change() {
+        let change =
+            parse_section_change(r#"moved this task from "Backlog" to "In Progress" in Roadmap"#)
+                .unwrap();
+        assert_eq!(change.from_section.as_deref(), Some("Backlog"));
+        assert_eq!(change.to_section, "In Progress");
+        assert_eq!(change.project_name.as_deref(), Some("Roadmap"));
+
+        let change = parse_section_change("Moved this Task from To do to Doing").unwrap();
+        assert_eq!(change.from_section.as_deref(), Some("To do"));
+        assert_eq!(change.to_section, "Doing");
+        assert_eq!(change.project_name, None);
+
+        assert!(parse_section_change("added this task to Roadmap").is_none());
+        assert!(parse_section_change("completed this task").is_none());
+    }
+}
@@ -11,7 +11,7 @@ pub async fn search_workspace_tasks(
     assignee_gid: Option<&str>,
 ) -> Result<Vec<asanaclient::Task>> {
     let mut query = vec![
-        ("opt_fields", "gid,name,completed,completed_at,assignee,assignee.name,due_on,due_at,start_on,created_at,modified_at,notes,html_notes,parent,num_subtasks,num_likes,memberships,memberships.project,memberships.project.name,memberships.section,memberships.section.name,tags,tags.name,custom_fields,custom_fields.gid,custom_fields.name,custom_fields.display_value,custom_fields.resource_subtype,custom_fields.text_value,custom_fields.number_value,custom_fields.enum_value,custom_fields.enum_value.gid,custom_fields.enum_value.name,custom_fields.enum_value.color,custom_fields.enum_value.enabled,custom_fields.multi_enum_values,custom_fields.multi_enum_values.gid,custom_fields.multi_enum_values.name,custom_fields.multi_enum_values.color,custom_fields.multi_enum_values.enabled,custom_fields.date_value,custom_fields.date_value.date,custom_fields.date_value.date_time,permalink_url"),
+        ("opt_fields", "gid,name,resource_subtype,completed,completed_at,assignee,assignee.name,due_on,due_at,start_on,created_at,modified_at,notes,html_notes,parent,num_subtasks,num_likes,memberships,memberships.project,memberships.project.name,memberships.section,memberships.section.name,tags,tags.name,custom_fields,custom_fields.gid,custom_fields.name,custom_fields.display_value,custom_fields.resource_subtype,custom_fields.text_value,custom_fields.number_value,custom_fields.enum_value,custom_fields.enum_value.gid,custom_fields.enum_value.name,custom_fields.enum_value.color,custom_fields.enum_value.enabled,custom_fields.multi_enum_values,custom_fields.multi_enum_values.gid,custom_fields.multi_enum_values.name,custom_fields.multi_enum_values.color,custom_fields.multi_enum_values.enabled,custom_fields.date_value,custom_fields.date_value.date,custom_fields.date_value.date_time,permalink_url"),
     ];
 
     if let Some(since) = modified_since {
@@ -22,6 +22,7 @@ pub async fn search_workspace_tasks(
     }
 
     let path = format!("/workspaces/{workspace_gid}/tasks/search");
+    crate::sync::rate_limit::acquire().await;
     let tasks: Vec<asanaclient::Task> = client.get_all(&path, &query).await?;
     Ok(tasks)
 }
@@ -30,6 +31,7 @@ pub async fn search_workspace_tasks(
 pub async fn get_project_sections(client: &Client, project_gid: &str) -> Result<Vec<SectionInfo>> {
     let path = format!("/projects/{project_gid}/sections");
     let query = [("opt_fields", "gid,name")];
+    crate::sync::rate_limit::acquire().await;
     let sections: Vec<SectionInfo> = client.get_all(&path, &query).await?;
     Ok(sections)
 }
@@ -40,10 +42,52 @@ pub struct SectionInfo {
     pub name: String,
 }
 
+/// Get a project's brief, if it has one. Briefs are not included in the
+/// project response, so this takes two requests: one for the brief's GID and
+/// one for its content.
+pub async fn get_project_brief(
+    client: &Client,
+    project_gid: &str,
+) -> Result<Option<ProjectBriefInfo>> {
+    let path = format!("/projects/{project_gid}");
+    let query = [("opt_fields", "project_brief")];
+    crate::sync::rate_limit::acquire().await;
+    let project: ProjectBriefRef = client.get(&path, &query).await?;
+    let Some(brief) = project.project_brief else {
+        return Ok(None);
+    };
+    let path = format!("/project_briefs/{}", brief.gid);
+    let query = [("opt_fields", "gid,title,text,html_text,permalink_url")];
+    crate::sync::rate_limit::acquire().await;
+    let brief: ProjectBriefInfo = client.get(&path, &query).await?;
+    Ok(Some(brief))
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct ProjectBriefRef {
+    #[serde(default)]
+    project_brief: Option<GidRef>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct GidRef {
+    gid: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ProjectBriefInfo {
+    pub gid: String,
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub html_text: Option<String>,
+    pub permalink_url: Option<String>,
+}
+
 /// Get members of a team.
 pub async fn get_team_members(client: &Client, team_gid: &str) -> Result<Vec<TeamMemberInfo>> {
     let path = format!("/teams/{team_gid}/users");
     let query = [("opt_fields", "gid,name,email")];
+    crate::sync::rate_limit::acquire().await;
     let members: Vec<TeamMemberInfo> = client.get_all(&path, &query).await?;
     Ok(members)
 }
@@ -55,10 +99,27 @@ pub struct TeamMemberInfo {
     pub email: Option<String>,
 }
 
+/// Get every team in a workspace the user can see.
+pub async fn get_workspace_teams(client: &Client, workspace_gid: &str) -> Result<Vec<TeamInfo>> {
+    let path = format!("/workspaces/{workspace_gid}/teams");
+    let query = [("opt_fields", "gid,name,description")];
+    crate::sync::rate_limit::acquire().await;
+    let teams: Vec<TeamInfo> = client.get_all(&path, &query).await?;
+    Ok(teams)
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TeamInfo {
+    pub gid: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
 /// Get projects belonging to a team.
 pub async fn get_team_projects(client: &Client, team_gid: &str) -> Result<Vec<ProjectRef>> {
     let path = format!("/teams/{team_gid}/projects");
     let query = [("opt_fields", "gid,name,archived")];
+    crate::sync::rate_limit::acquire().await;
     let projects: Vec<ProjectRef> = client.get_all(&path, &query).await?;
     Ok(projects)
 }
@@ -70,3 +131,191 @@ pub struct ProjectRef {
     #[serde(default)]
     pub archived: bool,
 }
+
+/// Get workspace memberships, including whether each user is still active.
+pub async fn get_workspace_memberships(
+    client: &Client,
+    workspace_gid: &str,
+) -> Result<Vec<WorkspaceMembershipInfo>> {
+    let path = format!("/workspaces/{workspace_gid}/workspace_memberships");
+    let query = [("opt_fields", "user,user.name,is_active")];
+    crate::sync::rate_limit::acquire().await;
+    let memberships: Vec<WorkspaceMembershipInfo> = client.get_all(&path, &query).await?;
+    Ok(memberships)
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct WorkspaceMembershipInfo {
+    pub user: UserRef,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct UserRef {
+    pub gid: String,
+    pub name: Option<String>,
+}
+
+/// A task fetched with `likes.user` in its opt_fields. `asanaclient::Task`
+/// does not carry likers, so they are captured alongside it.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TaskWithLikes {
+    #[serde(flatten)]
+    pub task: asanaclient::Task,
+    #[serde(default)]
+    pub likes: Vec<LikeInfo>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct LikeInfo {
+    pub user: UserRef,
+}
+
+/// Maximum actions per request to the Asana batch API.
+pub const BATCH_SIZE: usize = 10;
+
+/// One response from the batch API, in the same order as the actions sent.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct BatchResponse {
+    pub status_code: u16,
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+}
+
+/// Outcome of fetching one task through the batch API.
+#[derive(Debug)]
+pub enum BatchTaskOutcome {
+    Found(Box<TaskWithLikes>),
+    /// The task no longer exists (or is no longer visible).
+    NotFound,
+    /// The action failed; the caller should retry it individually.
+    Failed(String),
+}
+
+/// Fetch up to [`BATCH_SIZE`] tasks in one `/batch` request.
+///
+/// Errors for the request as a whole are returned as-is so the caller can
+/// back off or fall back to individual GETs; per-task errors come back as
+/// [`BatchTaskOutcome`]s.
+pub async fn batch_get_tasks(
+    client: &Client,
+    task_gids: &[&str],
+    opt_fields: &str,
+) -> std::result::Result<Vec<(String, BatchTaskOutcome)>, asanaclient::Error> {
+    let fields: Vec<&str> = opt_fields.split(',').collect();
+    let actions: Vec<serde_json::Value> = task_gids
+        .iter()
+        .map(|gid| {
+            serde_json::json!({
+                "relative_path": format!("/tasks/{gid}"),
+                "method": "get",
+                "options": { "fields": fields },
+            })
+        })
+        .collect();
+    let body = serde_json::json!({ "data": { "actions": actions } });
+    let responses: Vec<BatchResponse> = client.post("/batch", &body).await?;
+    Ok(batch_task_outcomes(task_gids, responses))
+}
+
+/// Pair batch responses with the task GIDs that produced them.
+fn batch_task_outcomes(
+    task_gids: &[&str],
+    responses: Vec<BatchResponse>,
+) -> Vec<(String, BatchTaskOutcome)> {
+    let mut responses = responses.into_iter();
+    task_gids
+        .iter()
+        .map(|gid| {
+            let outcome = match responses.next() {
+                None => BatchTaskOutcome::Failed("missing from batch response".into()),
+                Some(r) if r.status_code == 404 => BatchTaskOutcome::NotFound,
+                Some(r) if (200..300).contains(&r.status_code) => {
+                    match r
+                        .body
+                        .and_then(|mut b| b.get_mut("data").map(serde_json::Value::take))
+                    {
+                        Some(data) => match serde_json::from_value::<TaskWithLikes>(data) {
+                            Ok(task) => BatchTaskOutcome::Found(Box::new(task)),
+                            Err(e) => BatchTaskOutcome::Failed(format!("invalid task body: {e}")),
+                        },
+                        None => BatchTaskOutcome::Failed("empty batch response body".into()),
+                    }
+                }
+                Some(r) => BatchTaskOutcome::Failed(format!("status {}", r.status_code)),
+            };
+            (gid.to_string(), outcome)
+        })
+        .collect()
+}
+
+/// Fetch the `permalink_url` of up to [`BATCH_SIZE`] tasks, projects, or
+/// portfolios (`resource` is the API collection, e.g. `tasks`) in one
+/// `/batch` request. GIDs whose action failed come back as `None`.
+pub async fn batch_get_permalinks(
+    client: &Client,
+    resource: &str,
+    gids: &[&str],
+) -> std::result::Result<Vec<(String, Option<String>)>, asanaclient::Error> {
+    let actions: Vec<serde_json::Value> = gids
+        .iter()
+        .map(|gid| {
+            serde_json::json!({
+                "relative_path": format!("/{resource}/{gid}"),
+                "method": "get",
+                "options": { "fields": ["permalink_url"] },
+            })
+        })
+        .collect();
+    let body = serde_json::json!({ "data": { "actions": actions } });
+    let responses: Vec<BatchResponse> = client.post("/batch", &body).await?;
+    let mut responses = responses.into_iter();
+    Ok(gids
+        .iter()
+        .map(|gid| {
+            let url = responses
+                .next()
+                .filter(|r| (200..300).contains(&r.status_code))
+                .and_then(|r| r.body)
+                .and_then(|b| b["data"]["permalink_url"].as_str().map(str::to_string));
+            (gid.to_string(), url)
+        })
+        .collect())
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_task_outcomes() {
+        let responses: Vec<BatchResponse> = serde_json::from_value(serde_json::json!([
+            {"status_code": 200, "body": {"data": {
+                "gid": "1", "name": "Ship it", "completed": false,
+                "likes": [{"user": {"gid": "u1", "name": "Alice"}}]
+            }}},
+            {"status_code": 404, "body": {"errors": [{"message": "Not Found"}]}},
+            {"status_code": 500, "body": null},
+        ]))
+        .unwrap();
+
+        let outcomes = batch_task_outcomes(&["1", "2", "3", "4"], responses);
+        let gids: Vec<&str> = outcomes.iter().map(|(g, _)| g.as_str()).collect();
+        assert_eq!(gids, vec!["1", "2", "3", "4"]);
+        match &outcomes[0].1 {
+            BatchTaskOutcome::Found(t) => {
+                assert_eq!(t.task.name, "Ship it");
+                assert_eq!(t.likes[0].user.gid, "u1");
+            }
+            other => panic!("expected task, got {other:?}"),
+        }
+        assert!(matches!(outcomes[1].1, BatchTaskOutcome::NotFound));
+        assert!(matches!(&outcomes[2].1, BatchTaskOutcome::Failed(m) if m == "status 500"));
+        assert!(matches!(outcomes[3].1, BatchTaskOutcome::Failed(_)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Human-readable task changes picked up by incremental syncs, worked out by
+//! comparing fetched tasks and comments with what's already stored.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// One change to a task in a synced project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum TaskChange {
+    /// Not stored before.
+    Added {
+        task_gid: String,
+        name: String,
+    },
+    Completed {
+        task_gid: String,
+        name: String,
+        assignee: Option<String>,
+    },
+    Reopened {
+        task_gid: String,
+        name: String,
+    },
+    /// Assigned to someone else, or to nobody.
+    Reassigned {
+        task_gid: String,
+        name: String,
+        assignee: Option<String>,
+    },
+    Renamed {
+        task_gid: String,
+        old_name: String,
+        name: String,
+    },
+    Commented {
+        task_gid: String,
+        name: String,
+        author: Option<String>,
+        text: String,
+    },
+    Deleted {
+        task_gid: String,
+        name: Option<String>,
+    },
+}
+
+/// Longest comment excerpt shown in a change line.
+const EXCERPT_CHARS: usize = 80;
+
+impl fmt::Display for TaskChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskChange::Added { name, .. } => write!(f, "Added \"{name}\""),
+            TaskChange::Completed { name, assignee, .. } => match assignee {
+                Some(who) => write!(f, "Completed \"{name}\" ({who})"),
+                None => write!(f, "Completed \"{name}\""),
+            },
+            TaskChange::Reopened { name, .. } => write!(f, "Reopened \"{name}\""),
+            TaskChange::Reassigned { name, assignee, .. } => match assignee {
+                Some(who) => write!(f, "Assigned \"{name}\" to {who}"),
+                None => write!(f, "Unassigned \"{name}\""),
+            },
+            TaskChange::Renamed { old_name, name, .. } => {
+                write!(f, "Renamed \"{old_name}\" to \"{name}\"")
+            }
+            TaskChange::Commented {
+                name, author, text, ..
+            } => {
+                let mut excerpt: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if excerpt.chars().count() > EXCERPT_CHARS {
+                    excerpt = excerpt.chars().take(EXCERPT_CHARS - 3).collect::<String>() + "...";
+                }
+                let who = author.as_deref().unwrap_or("Someone");
+                write!(f, "{who} commented on \"{name}\": {excerpt}")
+            }
+            TaskChange::Deleted { task_gid, name } => match name {
+                Some(name) => write!(f, "Deleted \"{name}\""),
+                None => write!(f, "Deleted task {task_gid}"),
+            },
+        }
+    }
+}
+
+/// Changes between the stored state and freshly fetched `tasks`, their
+/// comments, and the GIDs of tasks found deleted. Call before storing them.
+pub fn detect_task_changes(
+    conn: &Connection,
+    tasks: &[asanaclient::Task],
+    task_comments: &[(String, Vec<asanaclient::Story>)],
+    deleted: &[String],
+) -> Result<Vec<TaskChange>, rusqlite::Error> {
+    let mut stored_stmt = conn.prepare_cached(
+        "SELECT name, is_completed, assignee_gid FROM fact_tasks WHERE task_gid = ?1",
+    )?;
+    let mut changes = Vec::new();
+    for task in tasks {
+        let stored: Option<(String, bool, Option<String>)> = stored_stmt
+            .query_row([&task.gid], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .optional()?;
+        let task_gid = task.gid.clone();
+        let name = task.name.clone();
+        let assignee = task
+            .assignee
+            .as_ref()
+            .map(|a| a.name.clone().unwrap_or_else(|| a.gid.clone()));
+        let Some((old_name, was_completed, old_assignee)) = stored else {
+            changes.push(TaskChange::Added { task_gid, name });
+            continue;
+        };
+        if old_name != task.name {
+            changes.push(TaskChange::Renamed {
+                task_gid: task_gid.clone(),
+                old_name,
+                name: name.clone(),
+            });
+        }
+        if old_assignee.as_deref() != task.assignee.as_ref().map(|a| a.gid.as_str()) {
+            changes.push(TaskChange::Reassigned {
+                task_gid: task_gid.clone(),
+                name: name.clone(),
+                assignee: assignee.clone(),
+            });
+        }
+        match (was_completed, task.completed) {
+            (false, true) => changes.push(TaskChange::Completed {
+                task_gid,
+                name,
+                assignee,
+            }),
+            (true, false) => changes.push(TaskChange::Reopened { task_gid, name }),
+            _ => {}
+        }
+    }
+
+    let names: std::collections::HashMap<&str, &str> = tasks
+        .iter()
+        .map(|t| (t.gid.as_str(), t.name.as_str()))
+        .collect();
+    let mut known_stmt =
+        conn.prepare_cached("SELECT comment_gid FROM fact_comments WHERE task_gid = ?1")?;
+    for (task_gid, comments) in task_comments {
+        let known: HashSet<String> = known_stmt
+            .query_map([task_gid], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        for story in comments {
+            let is_comment = story
+                .resource_subtype
+                .as_ref()
+                .is_some_and(|t| format!("{t:?}").eq_ignore_ascii_case("comment"));
+            if !is_comment || known.contains(&story.gid) {
+                continue;
+            }
+            changes.push(TaskChange::Commented {
+                task_gid: task_gid.clone(),
+                name: names.get(task_gid.as_str()).unwrap_or(&"?").to_string(),
+                author: story
+                    .created_by
+                    .as_ref()
+                    .map(|u| u.name.clone().unwrap_or_else(|| u.gid.clone())),
+                text: story.text.clone().unwrap_or_default(),
+            });
+        }
+    }
+
+    let mut name_stmt =
+        conn.prepare_cached("SELECT name FROM fact_tasks WHERE task_gid = ?1 AND is_deleted = 0")?;
+    for gid in deleted {
+        let name: Option<String> = name_stmt
+            .query_row(params![gid], |row| row.get(0))
+            .optional()?;
+        changes.push(TaskChange::Deleted {
+            task_gid: gid.clone(),
+            name,
+        });
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_detect_task_changes() {
+        let db = Database::open_memory().await.unwrap();
+        let changes = db
+            .writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO fact_tasks (task_gid, name, is_completed, created_at, created_date_key, modified_at, is_subtask, cached_at)
+                     VALUES ('t1', 'Launch', 0, '2025-01-01T00:00:00Z', '2025-01-01', '2025-01-01T00:00:00Z', 0, datetime('now')),
+                            ('t2', 'Old name', 0, '2025-01-01T00:00:00Z', '2025-01-01', '2025-01-01T00:00:00Z', 0, datetime('now')),
+                            ('t3', 'Gone', 0, '2025-01-01T00:00:00Z', '2025-01-01', '2025-01-01T00:00:00Z', 0, datetime('now'));
+                     INSERT INTO fact_comments (comment_gid, task_gid, text, story_type, created_at, created_date_key, cached_at)
+                     VALUES ('c1', 't1', 'Old comment', 'comment', '2025-01-01T00:00:00Z', '2025-01-01', datetime('now'));",
+                )?;
+                let task = |gid: &str, name: &str, completed: bool| -> asanaclient::Task {
+                    serde_json::from_value(serde_json::json!({
+                        "gid": gid, "name": name, "completed": completed,
+                    }))
+                    .unwrap()
+                };
+                let story = |gid: &str, text: &str| -> asanaclient::Story {
+                    serde_json::from_value(serde_json::json!({
+                        "gid": gid,
+                        "resource_subtype": "comment_added",
+                        "text": text,
+                        "created_by": {"gid": "u1", "name": "Ada"},
+                    }))
+                    .unwrap()
+                };
+                detect_task_changes(
+                    conn,
+                    &[
+                        task("t1", "Launch", true),
+                        task("t2", "New name", false),
+                        task("t4", "Fresh", false),
+                    ],
+                    &[(
+                        "t1".to_string(),
+                        vec![story("c1", "Old comment"), story("c2", "Shipped it")],
+                    )],
+                    &["t3".to_string()],
+                )
+            })
+            .await
+            .unwrap();
+
+        let lines: Vec<String> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "Completed \"Launch\"",
+                "Renamed \"Old name\" to \"New name\"",
+                "Added \"Fresh\"",
+                "Ada commented on \"Launch\": Shipped it",
+                "Deleted \"Gone\"",
+            ]
+        );
+    }
+}
@@ -1,20 +1,48 @@
+//! Which dates a project's full syncs have already covered, recorded in
+//! `synced_ranges`, and the holes a backfill still has to fetch.
+
 use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::Serialize;
 
 use crate::date_util::last_day_of_month;
+use crate::error::Result;
+use crate::storage::repository;
+use crate::storage::Database;
 
 /// A date range [start, end] inclusive.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DateRange {
     pub start: NaiveDate,
     pub end: NaiveDate,
 }
 
+impl std::fmt::Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}
+
 /// Given a desired range and a list of already-synced ranges, find the gaps
 /// that still need syncing. Ranges are month-aligned for efficient batch queries.
 pub fn find_gaps(
     desired_start: NaiveDate,
     desired_end: NaiveDate,
     synced_ranges: &[(NaiveDate, NaiveDate)],
+) -> Vec<DateRange> {
+    // Align to month boundaries for efficient batching
+    find_holes(desired_start, desired_end, synced_ranges)
+        .into_iter()
+        .flat_map(|g| split_into_months(g.start, g.end))
+        .collect()
+}
+
+/// The parts of a desired range not covered by any synced range, each as
+/// one contiguous range.
+pub fn find_holes(
+    desired_start: NaiveDate,
+    desired_end: NaiveDate,
+    synced_ranges: &[(NaiveDate, NaiveDate)],
 ) -> Vec<DateRange> {
     if desired_start > desired_end {
         return vec![];
@@ -48,15 +76,11 @@ pub fn find_gaps(
             end: desired_end,
         });
     }
-
-    // Align to month boundaries for efficient batching
-    gaps.into_iter()
-        .flat_map(|g| split_into_months(g.start, g.end))
-        .collect()
+    gaps
 }
 
 /// Merge overlapping/adjacent date ranges.
-fn merge_ranges(ranges: &[(NaiveDate, NaiveDate)]) -> Vec<DateRange> {
+pub fn merge_ranges(ranges: &[(NaiveDate, NaiveDate)]) -> Vec<DateRange> {
     if ranges.is_empty() {
         return vec![];
     }
@@ -80,6 +104,116 @@ fn merge_ranges(ranges: &[(NaiveDate, NaiveDate)]) -> Vec<DateRange> {
     merged
 }
 
+/// An entity's synced ranges, merged, oldest first. Rows that don't parse
+/// as dates are ignored.
+pub fn entity_coverage(
+    conn: &Connection,
+    entity_key: &str,
+) -> std::result::Result<Vec<DateRange>, rusqlite::Error> {
+    let ranges: Vec<(NaiveDate, NaiveDate)> = repository::get_synced_ranges(conn, entity_key)?
+        .iter()
+        .filter_map(|(start, end)| {
+            Some((
+                NaiveDate::parse_from_str(start, "%Y-%m-%d").ok()?,
+                NaiveDate::parse_from_str(end, "%Y-%m-%d").ok()?,
+            ))
+        })
+        .collect();
+    Ok(merge_ranges(&ranges))
+}
+
+/// Record that `[start, end]` has been synced for an entity. The entity's
+/// rows are rewritten merged, so each contiguous run of coverage is one row.
+pub fn record_coverage(
+    conn: &mut Connection,
+    entity_key: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> std::result::Result<(), rusqlite::Error> {
+    if start > end {
+        return Ok(());
+    }
+    let mut ranges: Vec<(NaiveDate, NaiveDate)> = entity_coverage(conn, entity_key)?
+        .into_iter()
+        .map(|r| (r.start, r.end))
+        .collect();
+    ranges.push((start, end));
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        "DELETE FROM synced_ranges WHERE entity_key = ?1",
+        params![entity_key],
+    )?;
+    for range in merge_ranges(&ranges) {
+        repository::insert_synced_range(
+            &tx,
+            entity_key,
+            &range.start.format("%Y-%m-%d").to_string(),
+            &range.end.format("%Y-%m-%d").to_string(),
+        )?;
+    }
+    tx.commit()
+}
+
+/// Extend an entity's most recent coverage through `end`, e.g. after an
+/// incremental sync has caught it up. Does nothing for an entity with no
+/// coverage yet.
+pub fn extend_coverage(
+    conn: &mut Connection,
+    entity_key: &str,
+    end: NaiveDate,
+) -> std::result::Result<(), rusqlite::Error> {
+    match entity_coverage(conn, entity_key)?.last() {
+        Some(last) if last.end < end => record_coverage(conn, entity_key, last.end, end),
+        _ => Ok(()),
+    }
+}
+
+/// How much of a date range an entity's full syncs have covered.
+#[derive(Debug, Clone, Serialize)]
+pub struct Coverage {
+    pub entity_key: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Synced ranges overlapping `[start, end]`, clipped to it.
+    pub covered: Vec<DateRange>,
+    /// The rest of `[start, end]`: what a backfill from `start` would fetch.
+    pub holes: Vec<DateRange>,
+}
+
+/// Coverage of `[start, end]` for an entity.
+pub async fn coverage(
+    db: &Database,
+    entity_key: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Coverage> {
+    let entity_key = entity_key.to_string();
+    let ranges = db
+        .reader()
+        .call({
+            let entity_key = entity_key.clone();
+            move |conn| entity_coverage(conn, &entity_key)
+        })
+        .await?;
+    let covered = ranges
+        .iter()
+        .filter(|r| r.end >= start && r.start <= end)
+        .map(|r| DateRange {
+            start: r.start.max(start),
+            end: r.end.min(end),
+        })
+        .collect();
+    let synced: Vec<(NaiveDate, NaiveDate)> = ranges.iter().map(|r| (r.start, r.end)).collect();
+    Ok(Coverage {
+        entity_key,
+        start,
+        end,
+        covered,
+        holes: find_holes(start, end, &synced),
+    })
+}
+
 /// Split a range into month-aligned batches.
 fn split_into_months(start: NaiveDate, end: NaiveDate) -> Vec<DateRange> {
     let mut batches = Vec::new();
@@ -186,6 +320,54 @@ mod tests {
         assert_eq!(gaps[0].start, d(2025, 2, 16));
     }
 
+    #[tokio::test]
+    async fn test_record_coverage_merges() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                record_coverage(conn, "project:p1", d(2025, 3, 1), d(2025, 3, 31))?;
+                record_coverage(conn, "project:p1", d(2025, 1, 1), d(2025, 1, 31))?;
+                // Adjacent to March, so merges with it
+                record_coverage(conn, "project:p1", d(2025, 4, 1), d(2025, 4, 10))?;
+                extend_coverage(conn, "project:p1", d(2025, 4, 20))?;
+                // Nothing to extend yet
+                extend_coverage(conn, "project:p2", d(2025, 4, 20))
+            })
+            .await
+            .unwrap();
+
+        let cov = coverage(&db, "project:p1", d(2024, 12, 15), d(2025, 4, 30))
+            .await
+            .unwrap();
+        assert_eq!(
+            cov.covered,
+            [
+                DateRange {
+                    start: d(2025, 1, 1),
+                    end: d(2025, 1, 31)
+                },
+                DateRange {
+                    start: d(2025, 3, 1),
+                    end: d(2025, 4, 20)
+                },
+            ]
+        );
+        let holes: Vec<String> = cov.holes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            holes,
+            [
+                "2024-12-15 to 2024-12-31",
+                "2025-02-01 to 2025-02-28",
+                "2025-04-21 to 2025-04-30",
+            ]
+        );
+        let p2 = coverage(&db, "project:p2", d(2025, 1, 1), d(2025, 1, 31))
+            .await
+            .unwrap();
+        assert!(p2.covered.is_empty());
+        assert_eq!(p2.holes.len(), 1);
+    }
+
     #[test]
     fn test_split_into_months() {
         let batches = split_into_months(d(2025, 1, 15), d(2025, 3, 10));
@@ -0,0 +1,148 @@
+//! Each entity's last two `sync all` reports, kept in `sync_report_history`
+//! so a run can be compared with the one before: a jump in items, requests,
+//! or duration usually means an entity fell back to full syncs.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::SyncReport;
+use crate::error::Result;
+use crate::storage::Database;
+
+/// How an entity's latest sync compares with its previous one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncReportDiff {
+    pub entity_key: String,
+    /// When the previous sync ran.
+    pub previous_at: String,
+    pub items_synced: u64,
+    pub previous_items_synced: u64,
+    pub duration_secs: f64,
+    pub previous_duration_secs: f64,
+    /// API requests made.
+    pub requests: u64,
+    pub previous_requests: u64,
+    /// Failed or partly failed after the previous sync succeeded.
+    pub new_failure: bool,
+    /// Succeeded after the previous sync failed.
+    pub recovered: bool,
+}
+
+/// Store `report` as the entity's latest, keeping the one it replaces.
+pub fn record_report(
+    conn: &Connection,
+    report: &SyncReport,
+    duration_secs: f64,
+) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(report)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    // Right-hand sides of an upsert's SET see the row as it was
+    conn.execute(
+        "INSERT INTO sync_report_history (entity_key, report, duration_secs, recorded_at)
+         VALUES (?1, ?2, ?3, datetime('now'))
+         ON CONFLICT(entity_key) DO UPDATE SET
+             previous_report = report,
+             previous_duration_secs = duration_secs,
+             previous_recorded_at = recorded_at,
+             report = excluded.report,
+             duration_secs = excluded.duration_secs,
+             recorded_at = excluded.recorded_at",
+        params![report.entity_key, json, duration_secs],
+    )?;
+    Ok(())
+}
+
+/// Compare the entity's latest report with the previous one. `None` until
+/// it has synced twice.
+pub fn report_diff(
+    conn: &Connection,
+    entity_key: &str,
+) -> rusqlite::Result<Option<SyncReportDiff>> {
+    conn.query_row(
+        "SELECT previous_recorded_at,
+                json_extract(report, '$.items_synced'),
+                json_extract(previous_report, '$.items_synced'),
+                duration_secs, previous_duration_secs,
+                json_extract(report, '$.rate_limit.requests'),
+                json_extract(previous_report, '$.rate_limit.requests'),
+                json_extract(report, '$.status'),
+                json_extract(previous_report, '$.status')
+         FROM sync_report_history
+         WHERE entity_key = ?1 AND previous_report IS NOT NULL",
+        [entity_key],
+        |row| {
+            let count = |i: usize| row.get::<_, Option<i64>>(i).map(|n| n.unwrap_or(0) as u64);
+            let succeeded = |i: usize| row.get::<_, String>(i).map(|s| s == "Success");
+            let (now_ok, before_ok) = (succeeded(7)?, succeeded(8)?);
+            Ok(SyncReportDiff {
+                entity_key: entity_key.to_string(),
+                previous_at: row.get(0)?,
+                items_synced: count(1)?,
+                previous_items_synced: count(2)?,
+                duration_secs: row.get(3)?,
+                previous_duration_secs: row.get(4)?,
+                requests: count(5)?,
+                previous_requests: count(6)?,
+                new_failure: before_ok && !now_ok,
+                recovered: !before_ok && now_ok,
+            })
+        },
+    )
+    .optional()
+}
+
+pub async fn record(db: &Database, report: &SyncReport, duration_secs: f64) -> Result<()> {
+    let report = report.clone();
+    db.writer()
+        .call(move |conn| record_report(conn, &report, duration_secs))
+        .await?;
+    Ok(())
+}
+
+/// Diffs for each of `entity_keys` that has a previous report.
+pub async fn diffs(db: &Database, entity_keys: Vec<String>) -> Result<Vec<SyncReportDiff>> {
+    Ok(db
+        .reader()
+        .call(move |conn| {
+            let mut diffs = Vec::new();
+            for key in &entity_keys {
+                diffs.extend(report_diff(conn, key)?);
+            }
+            Ok::<_, rusqlite::Error>(diffs)
+        })
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::SyncStatus;
+
+    #[tokio::test]
+    async fn test_report_diff() {
+        let db = Database::open_memory().await.unwrap();
+        let mut report = SyncReport::from_counts("project:1".into(), 12, 0, 1, 1);
+        record(&db, &report, 4.0).await.unwrap();
+        assert!(diffs(&db, vec!["project:1".into()])
+            .await
+            .unwrap()
+            .is_empty());
+
+        report.items_synced = 900;
+        report.items_failed = 3;
+        report.status = SyncStatus::PartialFailure;
+        report.rate_limit.requests = 250;
+        record(&db, &report, 95.5).await.unwrap();
+
+        let diff = diffs(&db, vec!["project:1".into(), "project:2".into()])
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!((diff.items_synced, diff.previous_items_synced), (900, 12));
+        assert_eq!((diff.requests, diff.previous_requests), (250, 0));
+        assert_eq!(diff.duration_secs, 95.5);
+        assert_eq!(diff.previous_duration_secs, 4.0);
+        assert!(diff.new_failure);
+        assert!(!diff.recovered);
+    }
+}
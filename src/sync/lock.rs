@@ -0,0 +1,217 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+
+use super::scheduler::TIMESTAMP_FORMAT;
+use crate::error::{Error, Result};
+use crate::storage::repository;
+use crate::storage::Database;
+
+/// A lock whose heartbeat is older than this is treated as abandoned.
+pub const STALE_AFTER_SECONDS: i64 = 120;
+
+/// How often the holder refreshes its heartbeat.
+const HEARTBEAT_SECONDS: u64 = 30;
+
+/// Process-level lock that keeps two processes from syncing the same
+/// database at once.
+///
+/// Held in the `sync_lock` table with the holder's PID and a heartbeat that a
+/// background task refreshes while the lock is alive. Call [`SyncLock::release`]
+/// when done; a lock that is dropped without releasing (e.g. after a crash)
+/// expires after [`STALE_AFTER_SECONDS`].
+///
+/// Acquiring the lock again in the process that holds it succeeds, so the
+/// library's sync entry points can take it under a caller that already
+/// did. Only the outermost acquisition's release frees it.
+pub struct SyncLock {
+    db: Database,
+    owner: String,
+    /// `None` for an acquisition nested in one this process already holds.
+    heartbeat: Option<tokio::task::JoinHandle<()>>,
+}
+
+/// Identifies this process as the lock's owner.
+fn process_owner() -> &'static str {
+    static OWNER: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    OWNER.get_or_init(|| {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        format!("{}-{nanos}", std::process::id())
+    })
+}
+
+impl SyncLock {
+    /// Take the lock, failing with [`Error::SyncLocked`] if another live
+    /// process holds it. `force` takes it regardless, for recovering from a
+    /// holder that is known to be gone.
+    pub async fn acquire(db: &Database, force: bool) -> Result<Self> {
+        Self::acquire_as(db, process_owner().to_string(), std::process::id(), force).await
+    }
+
+    async fn acquire_as(db: &Database, owner: String, pid: u32, force: bool) -> Result<Self> {
+        let stale_before = (Utc::now().naive_utc() - Duration::seconds(STALE_AFTER_SECONDS))
+            .format(TIMESTAMP_FORMAT)
+            .to_string();
+        let (nested, holder) = db
+            .writer()
+            .call({
+                let owner = owner.clone();
+                move |conn| {
+                    let held = repository::get_sync_lock(conn)?
+                        .is_some_and(|h| h.owner == owner && h.heartbeat_at >= stale_before);
+                    if held {
+                        return Ok((true, None));
+                    }
+                    let holder =
+                        repository::try_acquire_sync_lock(conn, &owner, pid, &stale_before, force)?;
+                    Ok::<_, rusqlite::Error>((false, holder))
+                }
+            })
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?;
+        if nested {
+            return Ok(Self {
+                db: db.clone(),
+                owner,
+                heartbeat: None,
+            });
+        }
+        if let Some(holder) = holder {
+            return Err(Error::SyncLocked(format!(
+                "another asanadw process (PID {}) has been syncing this database since {} UTC \
+                 (last heartbeat {} UTC). If that process is no longer running, wait {} seconds \
+                 or rerun with --force-unlock",
+                holder.pid, holder.acquired_at, holder.heartbeat_at, STALE_AFTER_SECONDS
+            )));
+        }
+
+        let heartbeat = tokio::spawn({
+            let db = db.clone();
+            let owner = owner.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(StdDuration::from_secs(HEARTBEAT_SECONDS)).await;
+                    let owner = owner.clone();
+                    match db
+                        .writer()
+                        .call(move |conn| repository::heartbeat_sync_lock(conn, &owner))
+                        .await
+                    {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            log::warn!("Sync lock was taken over by another process");
+                            break;
+                        }
+                        Err(e) => log::warn!("Could not refresh sync lock heartbeat: {e}"),
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            db: db.clone(),
+            owner,
+            heartbeat: Some(heartbeat),
+        })
+    }
+
+    /// Stop the heartbeat and release the lock, unless this acquisition was
+    /// nested in another.
+    pub async fn release(mut self) -> Result<()> {
+        let Some(heartbeat) = self.heartbeat.take() else {
+            return Ok(());
+        };
+        heartbeat.abort();
+        let owner = self.owner.clone();
+        self.db
+            .writer()
+            .call(move |conn| repository::release_sync_lock(conn, &owner))
+            .await
+            .map_err(|e| Error::Database(e.to_string()))
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.abort();
+        }
+    }
+}
+
+/// Current holder of the sync lock, if any.
+pub async fn current_holder(db: &Database) -> Result<Option<repository::SyncLockRow>> {
+    db.reader()
+        .call(|conn| repository::get_sync_lock(conn))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync_lock_excludes_other_owners() {
+        let db = Database::open_memory().await.unwrap();
+
+        let first = SyncLock::acquire_as(&db, "a".into(), 1, false)
+            .await
+            .unwrap();
+        let err = SyncLock::acquire_as(&db, "b".into(), 2, false)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::SyncLocked(ref m) if m.contains("PID 1")));
+        assert_eq!(current_holder(&db).await.unwrap().unwrap().pid, 1);
+
+        // Forcing takes the lock from a live holder
+        let forced = SyncLock::acquire_as(&db, "b".into(), 2, true)
+            .await
+            .unwrap();
+        assert_eq!(current_holder(&db).await.unwrap().unwrap().owner, "b");
+        // The previous holder's release no longer affects the new holder
+        first.release().await.unwrap();
+        assert!(current_holder(&db).await.unwrap().is_some());
+        forced.release().await.unwrap();
+        assert!(current_holder(&db).await.unwrap().is_none());
+
+        // A lock with an old heartbeat is abandoned
+        let _stale = SyncLock::acquire_as(&db, "c".into(), 3, false)
+            .await
+            .unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "UPDATE sync_lock SET heartbeat_at = datetime('now', '-1 hour')",
+                    [],
+                )
+            })
+            .await
+            .unwrap();
+        let taken = SyncLock::acquire_as(&db, "d".into(), 4, false)
+            .await
+            .unwrap();
+        assert_eq!(current_holder(&db).await.unwrap().unwrap().pid, 4);
+        taken.release().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sync_lock_nests_within_its_owner() {
+        let db = Database::open_memory().await.unwrap();
+
+        let outer = SyncLock::acquire_as(&db, "a".into(), 1, false)
+            .await
+            .unwrap();
+        let inner = SyncLock::acquire_as(&db, "a".into(), 1, false)
+            .await
+            .unwrap();
+        inner.release().await.unwrap();
+        assert_eq!(current_holder(&db).await.unwrap().unwrap().owner, "a");
+        outer.release().await.unwrap();
+        assert!(current_holder(&db).await.unwrap().is_none());
+    }
+}
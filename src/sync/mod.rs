@@ -1,18 +1,37 @@
 pub mod api_helpers;
+pub mod changes;
 pub mod gap;
+pub mod history;
+pub mod lock;
+pub mod permalinks;
+pub mod plan;
+pub mod progress;
 pub mod rate_limit;
+#[cfg(test)]
+mod replay;
+pub mod scheduler;
+pub mod since;
 pub mod syncer;
+pub mod watch;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
 
 use chrono::NaiveDate;
 use serde::Serialize;
 
+use crate::error::{Error, Result};
+
 /// Summary of changes detected during incremental sync.
 ///
 /// Passed to [`SyncProgress::on_incremental_sync`] so consumers can see
 /// exactly which resource types changed, not just task counts.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct IncrementalSyncSummary {
     pub tasks_changed: usize,
+    /// Tasks reported deleted in Asana.
+    pub tasks_deleted: usize,
     pub project_changed: bool,
     pub sections_changed: bool,
     pub status_updates_changed: bool,
@@ -34,7 +53,13 @@ pub trait SyncProgress: Send + Sync {
     fn on_comments_skipped(&self, _entity_key: &str, _skipped: usize, _total: usize) {}
     fn on_comments_progress(&self, _entity_key: &str, _current: usize, _total: usize) {}
     fn on_status_updates_synced(&self, _entity_key: &str, _count: usize) {}
+    /// Tasks found deleted in Asana and flagged `is_deleted`.
+    fn on_tasks_deleted(&self, _entity_key: &str, _count: usize) {}
     fn on_incremental_sync(&self, _entity_key: &str, _summary: &IncrementalSyncSummary) {}
+    /// Task changes an incremental sync picked up and stored.
+    fn on_task_changes(&self, _entity_key: &str, _changes: &[changes::TaskChange]) {}
+    /// The plan worked out instead of syncing when [`SyncOptions::dry_run`] is set.
+    fn on_plan(&self, _plan: &[plan::PlannedSync]) {}
     fn on_entity_complete(&self, _report: &SyncReport) {}
 }
 
@@ -42,13 +67,48 @@ pub trait SyncProgress: Send + Sync {
 pub struct NoopProgress;
 impl SyncProgress for NoopProgress {}
 
-/// Options controlling a sync operation.
+/// Options controlling a sync operation. Start from `SyncOptions::default()`
+/// so fields added later keep their defaults.
 #[derive(Debug, Clone)]
 pub struct SyncOptions {
     pub since: Option<NaiveDate>,
     pub days: Option<u32>,
     /// Force a full sync even if an incremental sync token is available.
     pub full: bool,
+    /// Work out what `sync_all` would fetch (see [`plan`]) and report it
+    /// through [`SyncProgress::on_plan`] instead of syncing.
+    pub dry_run: bool,
+    /// Maximum number of monitored entities `sync_all` syncs at once.
+    /// Values below 1 are treated as 1 (serial).
+    pub concurrency: usize,
+    /// Maximum number of in-flight comment requests within one project sync.
+    /// Values below 1 are treated as 1 (serial).
+    pub comment_concurrency: usize,
+    /// How many levels of nested portfolios a portfolio sync descends into.
+    pub max_portfolio_depth: u32,
+}
+
+/// Comment requests in flight per project sync when not otherwise configured.
+pub const DEFAULT_COMMENT_CONCURRENCY: usize = 4;
+
+/// Levels of nested portfolios below a portfolio that syncs and metrics
+/// include when not otherwise configured. The portfolio itself is level 0.
+pub const DEFAULT_PORTFOLIO_DEPTH: u32 = 5;
+
+impl Default for SyncOptions {
+    /// The default sync window, incremental where possible, one entity at a
+    /// time.
+    fn default() -> Self {
+        Self {
+            since: None,
+            days: None,
+            full: false,
+            dry_run: false,
+            concurrency: 1,
+            comment_concurrency: DEFAULT_COMMENT_CONCURRENCY,
+            max_portfolio_depth: DEFAULT_PORTFOLIO_DEPTH,
+        }
+    }
 }
 
 impl SyncOptions {
@@ -62,13 +122,96 @@ impl SyncOptions {
     }
 }
 
-/// Report returned after a sync operation completes.
+/// A project sync's report, or its error message, once it finishes.
+type SharedResult = Option<std::result::Result<SyncReport, String>>;
+
+/// Projects synced so far in one run, with each sync's result.
+///
+/// Portfolios and teams frequently share projects. A run passes one set to
+/// every entity it syncs, so each project is fetched once per run and every
+/// entity that reaches it reports that one sync's outcome.
+#[derive(Debug, Default)]
+pub struct SyncedProjects {
+    projects: Mutex<HashMap<String, tokio::sync::watch::Receiver<SharedResult>>>,
+}
+
+/// What one entity saw of a project through [`SyncedProjects::sync_once`].
+#[derive(Debug)]
+pub struct ProjectOutcome {
+    /// The project sync's report or error, whether this entity ran it or
+    /// not.
+    pub result: Result<SyncReport>,
+    /// Whether an earlier entity in the run synced the project.
+    pub shared: bool,
+}
+
+impl SyncedProjects {
+    /// Run `sync` for `project_gid` unless an earlier entity in the run
+    /// already has; then wait for that sync instead and return its result
+    /// as [`ProjectOutcome::shared`].
+    pub async fn sync_once(
+        &self,
+        project_gid: &str,
+        sync: impl Future<Output = Result<SyncReport>>,
+    ) -> ProjectOutcome {
+        let claimed = {
+            let mut projects = self.projects.lock().unwrap_or_else(|e| e.into_inner());
+            match projects.get(project_gid) {
+                Some(rx) => Err(rx.clone()),
+                None => {
+                    let (tx, rx) = tokio::sync::watch::channel(None);
+                    projects.insert(project_gid.to_string(), rx);
+                    Ok(tx)
+                }
+            }
+        };
+        match claimed {
+            Ok(tx) => {
+                let result = sync.await;
+                let shared = result.as_ref().cloned().map_err(|e| e.to_string());
+                tx.send_replace(Some(shared));
+                ProjectOutcome {
+                    result,
+                    shared: false,
+                }
+            }
+            Err(mut rx) => {
+                let result = match rx.wait_for(Option::is_some).await {
+                    Ok(done) => done.clone().expect("waited for a result"),
+                    // The claiming sync was dropped before finishing
+                    Err(_) => Err("its sync was cancelled".to_string()),
+                };
+                ProjectOutcome {
+                    result: result.map_err(|message| Error::Sync {
+                        entity_key: format!("project:{project_gid}"),
+                        message,
+                    }),
+                    shared: true,
+                }
+            }
+        }
+    }
+}
+
+/// Report returned after a sync operation completes. Build one with
+/// [`SyncReport::from_counts`] or [`SyncReport::failed`]; fields may be added.
 #[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
 pub struct SyncReport {
     pub entity_key: String,
     pub status: SyncStatus,
     pub items_synced: u64,
     pub items_failed: u64,
+    /// Projects an earlier entity in the same run already synced. They
+    /// aren't fetched again, but that sync's counts and failures are
+    /// included in this report.
+    pub skipped_duplicates: u64,
+    /// Parts of the requested range a full project sync didn't re-fetch
+    /// because earlier syncs already covered them (see [`gap`]).
+    pub skipped_ranges: Vec<gap::DateRange>,
+    /// API requests the sync made, its 429 retries, and time spent waiting
+    /// on the rate limit, including any nested project syncs.
+    pub rate_limit: rate_limit::RateLimitUsage,
     pub batches_completed: u32,
     pub batches_total: u32,
     pub error: Option<String>,
@@ -100,11 +243,23 @@ impl SyncReport {
             status,
             items_synced,
             items_failed,
+            skipped_duplicates: 0,
+            skipped_ranges: Vec::new(),
+            rate_limit: Default::default(),
             batches_completed,
             batches_total,
             error,
         }
     }
+
+    /// A sync of `entity_key` that failed outright with `error`.
+    pub fn failed(entity_key: String, error: impl std::fmt::Display) -> Self {
+        Self {
+            status: SyncStatus::Failed,
+            error: Some(error.to_string()),
+            ..Self::from_counts(entity_key, 0, 1, 0, 0)
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -113,3 +268,78 @@ pub enum SyncStatus {
     PartialFailure,
     Failed,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_report_and_default_options() {
+        let report = SyncReport::failed("project:p1".to_string(), "boom");
+        assert_eq!(report.status, SyncStatus::Failed);
+        assert_eq!((report.items_synced, report.items_failed), (0, 1));
+        assert_eq!(report.error.as_deref(), Some("boom"));
+
+        let options = SyncOptions::default();
+        assert!(!options.full && !options.dry_run);
+        assert_eq!(options.concurrency, 1);
+        assert_eq!(options.comment_concurrency, DEFAULT_COMMENT_CONCURRENCY);
+    }
+
+    #[tokio::test]
+    async fn test_synced_projects_syncs_once() {
+        let synced = SyncedProjects::default();
+        let report = |gid: &str| SyncReport::from_counts(format!("project:{gid}"), 7, 0, 1, 1);
+        let first = synced.sync_once("p1", async { Ok(report("p1")) }).await;
+        assert!(!first.shared);
+        assert_eq!(first.result.unwrap().items_synced, 7);
+
+        // Repeats get the first sync's report without running their own
+        let repeat = synced
+            .sync_once("p1", async { panic!("synced twice") })
+            .await;
+        assert!(repeat.shared);
+        assert_eq!(repeat.result.unwrap().items_synced, 7);
+
+        let failed = synced
+            .sync_once("p2", async { Err(Error::Other("boom".to_string())) })
+            .await;
+        assert!(!failed.shared && failed.result.is_err());
+        let repeat = synced.sync_once("p2", async { Ok(report("p2")) }).await;
+        assert!(repeat.shared);
+        assert_eq!(
+            repeat.result.unwrap_err().to_string(),
+            "Sync error for project:p2: boom"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_synced_projects_waits_for_a_sync_in_flight() {
+        let synced = SyncedProjects::default();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let first = synced.sync_once("p1", async {
+            released.await.unwrap();
+            Ok(SyncReport::from_counts(
+                "project:p1".to_string(),
+                3,
+                0,
+                1,
+                1,
+            ))
+        });
+        let repeat = async {
+            let outcome = synced
+                .sync_once("p1", async { panic!("synced twice") })
+                .await;
+            assert!(outcome.shared);
+            outcome.result.unwrap().items_synced
+        };
+        // Polled in order, so the first sync claims the project
+        let (first, repeat, _) = futures::join!(first, repeat, async {
+            tokio::task::yield_now().await;
+            release.send(()).unwrap();
+        });
+        assert_eq!(first.result.unwrap().items_synced, 3);
+        assert_eq!(repeat, 3);
+    }
+}
@@ -0,0 +1,245 @@
+//! Backfill `permalink_url` on rows synced before migration 002 added it.
+//! Without one, search and query output fall back to `/0/0/<gid>` links.
+
+use asanaclient::Client;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::storage::{repository, Database};
+use crate::sync::api_helpers::{batch_get_permalinks, BATCH_SIZE};
+use crate::sync::rate_limit::retry_api;
+use crate::url::generate_workspace_url;
+
+/// A table with a `permalink_url` column.
+struct PermalinkTable {
+    entity_type: &'static str,
+    /// API collection for `/batch` lookups.
+    resource: &'static str,
+    table: &'static str,
+    gid_column: &'static str,
+    /// Rows missing a permalink, with the workspace each belongs to.
+    missing_sql: &'static str,
+}
+
+const PERMALINK_TABLES: &[PermalinkTable] = &[
+    PermalinkTable {
+        entity_type: "task",
+        resource: "tasks",
+        table: "fact_tasks",
+        gid_column: "task_gid",
+        missing_sql: "SELECT t.task_gid,
+                (SELECT p.workspace_gid FROM bridge_task_projects btp
+                 JOIN dim_projects p ON p.project_gid = btp.project_gid
+                 WHERE btp.task_gid = t.task_gid LIMIT 1)
+            FROM fact_tasks t WHERE t.permalink_url IS NULL ORDER BY t.task_gid",
+    },
+    PermalinkTable {
+        entity_type: "project",
+        resource: "projects",
+        table: "dim_projects",
+        gid_column: "project_gid",
+        missing_sql: "SELECT project_gid, workspace_gid FROM dim_projects
+            WHERE permalink_url IS NULL ORDER BY project_gid",
+    },
+    PermalinkTable {
+        entity_type: "portfolio",
+        resource: "portfolios",
+        table: "dim_portfolios",
+        gid_column: "portfolio_gid",
+        missing_sql: "SELECT portfolio_gid, workspace_gid FROM dim_portfolios
+            WHERE permalink_url IS NULL ORDER BY portfolio_gid",
+    },
+];
+
+/// Backfill results for one entity type.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PermalinkCounts {
+    pub entity_type: String,
+    /// Rows that had no permalink.
+    pub missing: u64,
+    /// Filled with the permalink Asana returned.
+    pub from_api: u64,
+    /// Filled with a `/1/<workspace>/...` URL built locally.
+    pub generated: u64,
+    /// Left empty: not found through the API and no workspace known.
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermalinkReport {
+    pub dry_run: bool,
+    pub counts: Vec<PermalinkCounts>,
+}
+
+/// Fill in missing permalinks on tasks, projects, and portfolios. With a
+/// client, each row's permalink is fetched through the batch API
+/// ([`BATCH_SIZE`] per request, under the shared rate limit); rows the API
+/// doesn't return, or every row without a client, get a URL built from
+/// their workspace (a task's comes from its project, falling back to the
+/// `workspace_gid` config). With `dry_run`, nothing is written.
+pub async fn backfill_permalinks(
+    db: &Database,
+    client: Option<&Client>,
+    dry_run: bool,
+) -> Result<PermalinkReport> {
+    let mut counts = Vec::new();
+    for table in PERMALINK_TABLES {
+        let missing = db
+            .reader()
+            .call(move |conn| load_missing(conn, table))
+            .await?;
+        let mut c = PermalinkCounts {
+            entity_type: table.entity_type.to_string(),
+            missing: missing.len() as u64,
+            ..Default::default()
+        };
+
+        for chunk in missing.chunks(BATCH_SIZE) {
+            let mut found = Vec::new();
+            if let Some(client) = client {
+                let gids: Vec<&str> = chunk.iter().map(|(gid, _)| gid.as_str()).collect();
+                match retry_api!(batch_get_permalinks(client, table.resource, &gids)) {
+                    Ok(urls) => found = urls,
+                    Err(e) => log::warn!(
+                        "Failed to fetch permalinks for {} {}s: {e}",
+                        gids.len(),
+                        table.entity_type
+                    ),
+                }
+            }
+
+            let mut updates = Vec::new();
+            for (gid, workspace_gid) in chunk {
+                let from_api = found
+                    .iter()
+                    .find(|(g, _)| g == gid)
+                    .and_then(|(_, url)| url.clone());
+                if let Some(url) = from_api {
+                    c.from_api += 1;
+                    updates.push((gid.clone(), url));
+                } else if let Some(url) = workspace_gid
+                    .as_deref()
+                    .and_then(|ws| generate_workspace_url(ws, table.entity_type, gid))
+                {
+                    c.generated += 1;
+                    updates.push((gid.clone(), url));
+                } else {
+                    c.skipped += 1;
+                }
+            }
+
+            // Written per chunk so an interrupted run keeps its progress
+            if !dry_run && !updates.is_empty() {
+                db.writer()
+                    .call(move |conn| {
+                        let tx = conn.transaction()?;
+                        for (gid, url) in &updates {
+                            tx.execute(
+                                &format!(
+                                    "UPDATE {} SET permalink_url = ?2
+                                     WHERE {} = ?1 AND permalink_url IS NULL",
+                                    table.table, table.gid_column
+                                ),
+                                params![gid, url],
+                            )?;
+                        }
+                        tx.commit()
+                    })
+                    .await?;
+            }
+        }
+        counts.push(c);
+    }
+    Ok(PermalinkReport { dry_run, counts })
+}
+
+fn load_missing(
+    conn: &Connection,
+    table: &PermalinkTable,
+) -> std::result::Result<Vec<(String, Option<String>)>, rusqlite::Error> {
+    let fallback = repository::get_config(conn, "workspace_gid")?;
+    let mut stmt = conn.prepare(table.missing_sql)?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?
+                .or_else(|| fallback.clone()),
+        ))
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backfill_permalinks_offline() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('10', 'Old', '111', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, permalink_url, cached_at)
+                     VALUES ('11', 'New', '111', 'https://app.asana.com/1/111/project/11', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, created_at, created_date_key, cached_at)
+                     VALUES ('20', 'In project', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('21', 'No project', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid) VALUES ('20', '10');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let url_of = |gid: &'static str| {
+            let db = db.clone();
+            async move {
+                db.reader()
+                    .call(move |conn| {
+                        conn.query_row(
+                            "SELECT permalink_url FROM fact_tasks WHERE task_gid = ?1",
+                            [gid],
+                            |row| row.get::<_, Option<String>>(0),
+                        )
+                    })
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let dry = backfill_permalinks(&db, None, true).await.unwrap();
+        assert_eq!(dry.counts[0].missing, 2);
+        assert_eq!(dry.counts[0].generated, 1);
+        assert_eq!(dry.counts[0].skipped, 1);
+        assert_eq!(url_of("20").await, None);
+
+        let report = backfill_permalinks(&db, None, false).await.unwrap();
+        assert_eq!(report.counts[1].missing, 1);
+        assert_eq!(report.counts[1].generated, 1);
+        assert_eq!(
+            url_of("20").await.as_deref(),
+            Some("https://app.asana.com/1/111/task/20")
+        );
+
+        // The task outside any project needs the configured workspace
+        db.writer()
+            .call(|conn| {
+                conn.execute(
+                    "INSERT INTO app_config (key, value, updated_at)
+                     VALUES ('workspace_gid', '999', datetime('now'))",
+                    [],
+                )
+            })
+            .await
+            .unwrap();
+        let report = backfill_permalinks(&db, None, false).await.unwrap();
+        assert_eq!(report.counts[0].missing, 1);
+        assert_eq!(
+            url_of("21").await.as_deref(),
+            Some("https://app.asana.com/1/999/task/21")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! What `sync_all` would fetch, worked out from local state alone: no Asana
+//! calls and no writes. Task counts are estimates.
+
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::storage::repository::{self, MonitoredEntity};
+use crate::storage::Database;
+use crate::sync::since::smart_since;
+use crate::sync::SyncOptions;
+
+/// How an entity would be synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanMode {
+    /// Only changes since the stored event sync token.
+    Incremental,
+    /// Every task modified since `since`.
+    Full,
+    /// Membership only; the entity's projects are planned separately.
+    Projects,
+}
+
+/// Where [`PlannedSync::estimated_tasks`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateSource {
+    /// Tasks already stored for the entity in the sync range.
+    Stored,
+    /// Items synced by the entity's last completed sync job.
+    LastJob,
+    /// Nothing to go on yet.
+    Unknown,
+}
+
+/// One entity in a sync plan.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedSync {
+    pub entity_key: String,
+    pub display_name: Option<String>,
+    pub mode: PlanMode,
+    /// First day of the range a full sync would fetch.
+    pub since: Option<String>,
+    pub has_sync_token: bool,
+    pub last_sync_at: Option<String>,
+    pub estimated_tasks: Option<u64>,
+    pub estimate_source: EstimateSource,
+    /// Projects a team or portfolio sync would sync, as known locally.
+    /// Monitored projects are planned on their own instead, and any other
+    /// project appears under the first entity reaching it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<PlannedSync>,
+}
+
+/// Plan a sync of every enabled monitored entity with `options`.
+pub async fn plan_sync_all(db: &Database, options: &SyncOptions) -> Result<Vec<PlannedSync>> {
+    let today = chrono::Local::now().date_naive();
+    let options = options.clone();
+    let plan = db
+        .reader()
+        .call(move |conn| {
+            let entities = repository::list_monitored_entities(conn)?;
+            // Monitored projects are planned on their own, not under a team
+            // or portfolio
+            let mut planned_projects: HashSet<String> = entities
+                .iter()
+                .filter(|e| e.entity_type == "project")
+                .map(|e| e.entity_gid.clone())
+                .collect();
+            let mut plan = Vec::new();
+            for entity in &entities {
+                let project_gids = match entity.entity_type.as_str() {
+                    "team" => team_project_gids(conn, &entity.entity_gid)?,
+                    "portfolio" => repository::portfolio_project_gids(
+                        conn,
+                        &entity.entity_gid,
+                        options.max_portfolio_depth,
+                    )?,
+                    _ => Vec::new(),
+                };
+                let mut planned = plan_entity(conn, entity, &options, today)?;
+                for gid in project_gids {
+                    if !planned_projects.insert(gid.clone()) {
+                        continue;
+                    }
+                    let project = MonitoredEntity {
+                        entity_key: format!("project:{gid}"),
+                        entity_type: "project".to_string(),
+                        entity_gid: gid,
+                        display_name: None,
+                        added_at: String::new(),
+                        last_sync_at: None,
+                        sync_enabled: true,
+                        workspace_gid: entity.workspace_gid.clone(),
+                    };
+                    planned
+                        .projects
+                        .push(plan_entity(conn, &project, &options, today)?);
+                }
+                plan.push(planned);
+            }
+            Ok::<Vec<PlannedSync>, rusqlite::Error>(plan)
+        })
+        .await?;
+    Ok(plan)
+}
+
+fn plan_entity(
+    conn: &Connection,
+    entity: &MonitoredEntity,
+    options: &SyncOptions,
+    today: NaiveDate,
+) -> std::result::Result<PlannedSync, rusqlite::Error> {
+    // The same start the syncer would pick
+    let since = match options.since_date() {
+        Some(since) => since,
+        None => smart_since(conn, &entity.entity_key, options.full, today)?,
+    }
+    .format("%Y-%m-%d")
+    .to_string();
+    let since = since.as_str();
+    let has_sync_token = repository::get_event_sync_token(conn, &entity.entity_key)?.is_some();
+    let last_sync_at = match &entity.last_sync_at {
+        Some(at) => Some(at.clone()),
+        None => repository::get_last_sync_at(conn, &entity.entity_key)?,
+    };
+    let mode = match entity.entity_type.as_str() {
+        "team" | "portfolio" => PlanMode::Projects,
+        "project" if has_sync_token && !options.full => PlanMode::Incremental,
+        _ => PlanMode::Full,
+    };
+    let display_name = match (&entity.display_name, entity.entity_type.as_str()) {
+        (Some(name), _) => Some(name.clone()),
+        (None, "project") => conn
+            .query_row(
+                "SELECT name FROM dim_projects WHERE project_gid = ?1",
+                [&entity.entity_gid],
+                |row| row.get(0),
+            )
+            .ok(),
+        _ => None,
+    };
+
+    let stored = match (mode, entity.entity_type.as_str()) {
+        (PlanMode::Full, "project") => {
+            Some(repository::project_task_gids_since(conn, &entity.entity_gid, since)?.len() as u64)
+        }
+        (PlanMode::Full, "user") => Some(conn.query_row(
+            "SELECT COUNT(*) FROM fact_tasks
+             WHERE assignee_gid = ?1 AND is_deleted = 0 AND substr(modified_at, 1, 10) >= ?2",
+            params![entity.entity_gid, since],
+            |row| row.get::<_, i64>(0),
+        )? as u64),
+        _ => None,
+    };
+    let last_job: Option<u64> = conn
+        .query_row(
+            "SELECT synced_items FROM sync_jobs
+             WHERE entity_key = ?1 AND status = 'completed'
+             ORDER BY started_at DESC, id DESC LIMIT 1",
+            [&entity.entity_key],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .ok()
+        .flatten()
+        .map(|n| n.max(0) as u64);
+    let (estimated_tasks, estimate_source) = match (stored, last_job) {
+        (Some(n), _) if n > 0 => (Some(n), EstimateSource::Stored),
+        (_, Some(n)) if mode != PlanMode::Projects => (Some(n), EstimateSource::LastJob),
+        _ => (None, EstimateSource::Unknown),
+    };
+
+    Ok(PlannedSync {
+        entity_key: entity.entity_key.clone(),
+        display_name,
+        mode,
+        since: (mode == PlanMode::Full).then(|| since.to_string()),
+        has_sync_token,
+        last_sync_at,
+        estimated_tasks,
+        estimate_source,
+        projects: Vec::new(),
+    })
+}
+
+/// Non-template projects stored for a team.
+fn team_project_gids(
+    conn: &Connection,
+    team_gid: &str,
+) -> std::result::Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT project_gid FROM dim_projects
+         WHERE team_gid = ?1 AND COALESCE(is_template, 0) = 0
+         ORDER BY project_gid",
+    )?;
+    let gids = stmt
+        .query_map([team_gid], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    Ok(gids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_plan_sync_all() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_teams (team_gid, name, workspace_gid, cached_at) VALUES ('tm1', 'Platform', 'w1', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, team_gid, cached_at)
+                     VALUES ('p1', 'Roadmap', 'w1', 'tm1', datetime('now')),
+                            ('p2', 'Infra', 'w1', 'tm1', datetime('now'));
+                     INSERT INTO monitored_entities (entity_key, entity_type, entity_gid, added_at, event_sync_token)
+                     VALUES ('project:p1', 'project', 'p1', datetime('now'), 'tok'),
+                            ('team:tm1', 'team', 'tm1', datetime('now'), NULL);
+                     INSERT INTO sync_jobs (entity_key, status, started_at, synced_items)
+                     VALUES ('project:p1', 'completed', datetime('now'), 12);",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let mut options = SyncOptions {
+            since: None,
+            days: Some(30),
+            full: false,
+            dry_run: true,
+            concurrency: 1,
+            comment_concurrency: 1,
+            max_portfolio_depth: 1,
+        };
+        let plan = plan_sync_all(&db, &options).await.unwrap();
+        assert_eq!(plan.len(), 2);
+        let project = plan.iter().find(|p| p.entity_key == "project:p1").unwrap();
+        assert_eq!(project.mode, PlanMode::Incremental);
+        assert_eq!(project.display_name.as_deref(), Some("Roadmap"));
+        assert_eq!(
+            (project.estimated_tasks, project.estimate_source),
+            (Some(12), EstimateSource::LastJob)
+        );
+
+        // p1 is planned on its own, so the team only adds p2
+        let team = plan.iter().find(|p| p.entity_key == "team:tm1").unwrap();
+        assert_eq!(team.mode, PlanMode::Projects);
+        let keys: Vec<&str> = team
+            .projects
+            .iter()
+            .map(|p| p.entity_key.as_str())
+            .collect();
+        assert_eq!(keys, ["project:p2"]);
+        assert_eq!(team.projects[0].mode, PlanMode::Full);
+        assert_eq!(team.projects[0].estimate_source, EstimateSource::Unknown);
+
+        options.full = true;
+        let plan = plan_sync_all(&db, &options).await.unwrap();
+        let project = plan.iter().find(|p| p.entity_key == "project:p1").unwrap();
+        assert_eq!(project.mode, PlanMode::Full);
+        assert!(project.since.is_some());
+    }
+}
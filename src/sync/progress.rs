@@ -0,0 +1,274 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+
+use super::changes::TaskChange;
+use super::plan::PlannedSync;
+use super::{IncrementalSyncSummary, SyncProgress, SyncReport};
+
+/// Latest status of one entity that is currently syncing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InFlightEntity {
+    pub entity_key: String,
+    pub status: String,
+}
+
+/// [`SyncProgress`] adapter for concurrent syncs.
+///
+/// Keeps one status line per in-flight entity and calls `render` with the
+/// full set after every callback, so a caller can redraw a line per entity
+/// instead of interleaving output. Completed entities are dropped from the
+/// set and passed to `on_complete`.
+pub struct InFlightProgress<R, C>
+where
+    R: Fn(&[InFlightEntity]) + Send + Sync,
+    C: Fn(&SyncReport) + Send + Sync,
+{
+    entities: Mutex<Vec<InFlightEntity>>,
+    render: R,
+    on_complete: C,
+}
+
+impl<R, C> InFlightProgress<R, C>
+where
+    R: Fn(&[InFlightEntity]) + Send + Sync,
+    C: Fn(&SyncReport) + Send + Sync,
+{
+    pub fn new(render: R, on_complete: C) -> Self {
+        Self {
+            entities: Mutex::new(Vec::new()),
+            render,
+            on_complete,
+        }
+    }
+
+    fn set_status(&self, entity_key: &str, status: String) {
+        let mut entities = self.entities.lock().unwrap_or_else(|e| e.into_inner());
+        match entities.iter_mut().find(|e| e.entity_key == entity_key) {
+            Some(entity) => entity.status = status,
+            None => entities.push(InFlightEntity {
+                entity_key: entity_key.to_string(),
+                status,
+            }),
+        }
+        (self.render)(&entities);
+    }
+}
+
+impl<R, C> SyncProgress for InFlightProgress<R, C>
+where
+    R: Fn(&[InFlightEntity]) + Send + Sync,
+    C: Fn(&SyncReport) + Send + Sync,
+{
+    fn on_entity_start(&self, entity_key: &str, index: usize, total: usize) {
+        self.set_status(entity_key, format!("starting ({}/{total})", index + 1));
+    }
+
+    fn on_tasks_fetched(&self, entity_key: &str, count: usize) {
+        self.set_status(entity_key, format!("fetched {count} tasks"));
+    }
+
+    fn on_comments_skipped(&self, entity_key: &str, skipped: usize, total: usize) {
+        self.set_status(
+            entity_key,
+            format!("skipping comments for {skipped}/{total} unchanged tasks"),
+        );
+    }
+
+    fn on_comments_progress(&self, entity_key: &str, current: usize, total: usize) {
+        self.set_status(entity_key, format!("fetching comments {current}/{total}"));
+    }
+
+    fn on_status_updates_synced(&self, entity_key: &str, count: usize) {
+        self.set_status(entity_key, format!("synced {count} status updates"));
+    }
+
+    fn on_tasks_deleted(&self, entity_key: &str, count: usize) {
+        self.set_status(entity_key, format!("marked {count} tasks deleted"));
+    }
+
+    fn on_incremental_sync(&self, entity_key: &str, summary: &IncrementalSyncSummary) {
+        self.set_status(
+            entity_key,
+            format!("incremental: {} tasks changed", summary.tasks_changed),
+        );
+    }
+
+    fn on_entity_complete(&self, report: &SyncReport) {
+        let mut entities = self.entities.lock().unwrap_or_else(|e| e.into_inner());
+        entities.retain(|e| e.entity_key != report.entity_key);
+        (self.on_complete)(report);
+        (self.render)(&entities);
+    }
+}
+
+/// [`SyncProgress`] that writes every callback as a JSON line, for
+/// wrappers and CI jobs that track progress programmatically.
+///
+/// Each line has an `event` named after the callback (`entity_start`,
+/// `tasks_fetched`, ..., `entity_complete`), the `entity_key` when there is
+/// one, the callback's counts, an RFC 3339 `at` timestamp, and the
+/// [`crate::JSON_SCHEMA_VERSION`] as `schema_version`.
+pub struct JsonLinesProgress<W: Write + Send> {
+    out: Mutex<W>,
+}
+
+impl<W: Write + Send> JsonLinesProgress<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Mutex::new(out),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn emit(&self, event: &str, entity_key: Option<&str>, fields: Value) {
+        let mut line = json!({ "event": event });
+        if let Some(entity_key) = entity_key {
+            line["entity_key"] = json!(entity_key);
+        }
+        if let (Value::Object(line), Value::Object(fields)) = (&mut line, fields) {
+            line.extend(fields);
+        }
+        line["at"] = json!(chrono::Utc::now().to_rfc3339());
+        line["schema_version"] = json!(crate::JSON_SCHEMA_VERSION);
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        // Progress is best-effort; a closed pipe shouldn't fail the sync
+        let _ = writeln!(out, "{line}");
+        let _ = out.flush();
+    }
+}
+
+impl<W: Write + Send> SyncProgress for JsonLinesProgress<W> {
+    fn on_entity_start(&self, entity_key: &str, index: usize, total: usize) {
+        self.emit(
+            "entity_start",
+            Some(entity_key),
+            json!({ "index": index, "total": total }),
+        );
+    }
+
+    fn on_tasks_fetched(&self, entity_key: &str, count: usize) {
+        self.emit("tasks_fetched", Some(entity_key), json!({ "count": count }));
+    }
+
+    fn on_comments_skipped(&self, entity_key: &str, skipped: usize, total: usize) {
+        self.emit(
+            "comments_skipped",
+            Some(entity_key),
+            json!({ "skipped": skipped, "total": total }),
+        );
+    }
+
+    fn on_comments_progress(&self, entity_key: &str, current: usize, total: usize) {
+        self.emit(
+            "comments_progress",
+            Some(entity_key),
+            json!({ "current": current, "total": total }),
+        );
+    }
+
+    fn on_status_updates_synced(&self, entity_key: &str, count: usize) {
+        self.emit(
+            "status_updates_synced",
+            Some(entity_key),
+            json!({ "count": count }),
+        );
+    }
+
+    fn on_tasks_deleted(&self, entity_key: &str, count: usize) {
+        self.emit("tasks_deleted", Some(entity_key), json!({ "count": count }));
+    }
+
+    fn on_incremental_sync(&self, entity_key: &str, summary: &IncrementalSyncSummary) {
+        self.emit(
+            "incremental_sync",
+            Some(entity_key),
+            json!({ "summary": summary }),
+        );
+    }
+
+    fn on_task_changes(&self, entity_key: &str, changes: &[TaskChange]) {
+        self.emit(
+            "task_changes",
+            Some(entity_key),
+            json!({ "changes": changes }),
+        );
+    }
+
+    fn on_plan(&self, plan: &[PlannedSync]) {
+        self.emit("plan", None, json!({ "plan": plan }));
+    }
+
+    fn on_entity_complete(&self, report: &SyncReport) {
+        self.emit(
+            "entity_complete",
+            Some(&report.entity_key),
+            json!({ "report": report }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight_progress_tracks_entities() {
+        let frames: Mutex<Vec<Vec<InFlightEntity>>> = Mutex::new(Vec::new());
+        let completed: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        let progress = InFlightProgress::new(
+            |entities: &[InFlightEntity]| frames.lock().unwrap().push(entities.to_vec()),
+            |report: &SyncReport| completed.lock().unwrap().push(report.entity_key.clone()),
+        );
+
+        progress.on_entity_start("project:1", 0, 2);
+        progress.on_entity_start("project:2", 1, 2);
+        progress.on_tasks_fetched("project:1", 12);
+        progress.on_entity_complete(&SyncReport::from_counts("project:1".into(), 12, 0, 1, 1));
+
+        let frames = frames.into_inner().unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[1].len(), 2);
+        assert_eq!(frames[2][0].status, "fetched 12 tasks");
+        assert_eq!(frames[2][1].status, "starting (2/2)");
+        assert_eq!(
+            frames[3],
+            vec![InFlightEntity {
+                entity_key: "project:2".into(),
+                status: "starting (2/2)".into(),
+            }]
+        );
+        assert_eq!(completed.into_inner().unwrap(), vec!["project:1"]);
+    }
+
+    #[test]
+    fn test_json_lines_progress() {
+        let progress = JsonLinesProgress::new(Vec::new());
+        progress.on_entity_start("project:1", 0, 1);
+        progress.on_comments_progress("project:1", 3, 10);
+        progress.on_entity_complete(&SyncReport::from_counts("project:1".into(), 12, 0, 1, 1));
+
+        let out = String::from_utf8(progress.into_inner()).unwrap();
+        let lines: Vec<Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["event"], "entity_start");
+        assert_eq!(lines[0]["total"], 1);
+        assert_eq!(lines[1]["entity_key"], "project:1");
+        assert_eq!(
+            (lines[1]["current"].as_u64(), lines[1]["total"].as_u64()),
+            (Some(3), Some(10))
+        );
+        assert_eq!(lines[2]["report"]["items_synced"], 12);
+        assert!(lines.iter().all(|l| l["at"].is_string()));
+        assert!(lines
+            .iter()
+            .all(|l| l["schema_version"] == crate::JSON_SCHEMA_VERSION));
+    }
+}
@@ -1,8 +1,29 @@
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
 
 const MAX_RETRIES: u32 = 3;
 const BACKOFF_SECONDS: &[u64] = &[60, 120, 240];
 
+/// Requests per minute Asana allows a paid workspace, and the budget until a
+/// 429 says otherwise.
+pub const DEFAULT_BUDGET_PER_MINUTE: u32 = 1500;
+
+/// Fraction of the budget used in the last minute beyond which requests are
+/// spaced out evenly instead of sent as fast as they come.
+const SLOWDOWN_FRACTION: f64 = 0.8;
+
+/// How long a budget lowered by a 429 holds before the default returns.
+const LEARNED_BUDGET_TTL: Duration = Duration::from_secs(600);
+
+/// Lowest budget a 429 can lower it to.
+const MIN_BUDGET_PER_MINUTE: u32 = 10;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
 /// Check if an asanaclient error is a 429 rate limit.
 /// asanaclient's handle_response() converts 429 to `Error::Api { message }`
 /// containing "429" in the message, since it discards headers.
@@ -11,6 +32,22 @@ pub fn is_429_error(e: &asanaclient::Error) -> bool {
     msg.contains("429") || msg.to_lowercase().contains("rate limit")
 }
 
+/// Seconds from a `Retry-After` header quoted in an error message, e.g.
+/// "429 Too Many Requests (Retry-After: 30)". asanaclient doesn't expose
+/// response headers, so this is the only place to read one from.
+pub fn retry_after(e: &asanaclient::Error) -> Option<Duration> {
+    let msg = e.to_string().to_lowercase();
+    let pos = msg
+        .find("retry-after")
+        .or_else(|| msg.find("retry after"))?;
+    let secs: String = msg[pos + "retry-after".len()..]
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    secs.parse().ok().map(Duration::from_secs)
+}
+
 /// Retry an API call expression with exponential backoff on 429 errors.
 ///
 /// Usage: `retry_api!(client.projects().get_full(gid))`
@@ -21,19 +58,27 @@ macro_rules! retry_api {
     ($expr:expr) => {{
         let mut _attempt: u32 = 0;
         loop {
+            $crate::sync::rate_limit::acquire().await;
             match $expr.await {
                 Ok(val) => break Ok::<_, crate::error::Error>(val),
                 Err(e) => {
                     if $crate::sync::rate_limit::is_429_error(&e) && _attempt < 3 {
-                        let wait = [60u64, 120, 240]
-                            .get(_attempt as usize)
-                            .copied()
-                            .unwrap_or(240);
+                        let wait = $crate::sync::rate_limit::retry_after(&e)
+                            .map(|d| d.as_secs())
+                            .unwrap_or_else(|| {
+                                [60u64, 120, 240]
+                                    .get(_attempt as usize)
+                                    .copied()
+                                    .unwrap_or(240)
+                            });
                         log::warn!(
                             "Rate limited (429). Waiting {wait}s before retry {}/3",
                             _attempt + 1
                         );
-                        tokio::time::sleep(std::time::Duration::from_secs(wait)).await;
+                        let wait = std::time::Duration::from_secs(wait);
+                        $crate::sync::rate_limit::rate_limited(wait);
+                        $crate::sync::rate_limit::pause_for(wait);
+                        tokio::time::sleep(wait).await;
                         _attempt += 1;
                     } else {
                         break Err(crate::error::Error::Api(e));
@@ -46,6 +91,239 @@ macro_rules! retry_api {
 
 pub(crate) use retry_api;
 
+/// Process-wide pause set when any request is rate limited, so concurrent
+/// requests back off together instead of each discovering the 429 on its own.
+static PAUSED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Hold all `retry_api!` calls for at least `wait` from now.
+pub fn pause_for(wait: Duration) {
+    let until = Instant::now() + wait;
+    let mut paused = PAUSED_UNTIL.lock().unwrap_or_else(|e| e.into_inner());
+    if paused.is_none_or(|current| current < until) {
+        *paused = Some(until);
+    }
+}
+
+/// Wait out any active rate-limit pause.
+pub async fn wait_if_paused() {
+    let until = *PAUSED_UNTIL.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(until) = until {
+        let now = Instant::now();
+        if until > now {
+            tokio::time::sleep(until - now).await;
+            add_wait(until - now);
+        }
+    }
+}
+
+/// Wait out any rate-limit pause, then until the budget has room for one
+/// more request, and count the request. Called before every API request.
+pub async fn acquire() {
+    wait_if_paused().await;
+    loop {
+        match STATS.try_reserve(Instant::now()) {
+            Ok(()) => break,
+            Err(delay) => {
+                tokio::time::sleep(delay).await;
+                add_wait(delay);
+            }
+        }
+    }
+    with_scope(|usage| usage.requests += 1);
+}
+
+/// Record a 429 answered by waiting `wait` before retrying.
+pub fn rate_limited(wait: Duration) {
+    STATS.rate_limited(Instant::now(), wait);
+    with_scope(|usage| {
+        usage.retries += 1;
+        usage.wait_secs += wait.as_secs_f64();
+    });
+}
+
+fn add_wait(wait: Duration) {
+    STATS.add_wait(wait);
+    with_scope(|usage| usage.wait_secs += wait.as_secs_f64());
+}
+
+/// API requests, 429 retries, and time spent waiting on the rate limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct RateLimitUsage {
+    pub requests: u64,
+    /// Requests retried after a 429.
+    pub retries: u64,
+    /// Seconds spent in 429 backoff, shared pauses, and throttling.
+    pub wait_secs: f64,
+}
+
+impl RateLimitUsage {
+    fn add(&mut self, other: &RateLimitUsage) {
+        self.requests += other.requests;
+        self.retries += other.retries;
+        self.wait_secs += other.wait_secs;
+    }
+}
+
+/// Process-wide request budget: how many requests went out in the last
+/// minute against how many Asana allows.
+///
+/// The budget starts at [`DEFAULT_BUDGET_PER_MINUTE`]. A 429 lowers it to
+/// just under the number of requests that triggered it, for ten minutes.
+/// Past 80% of the budget requests are spaced evenly, and at the budget they
+/// wait for the oldest request to leave the window, so concurrent fetches
+/// slow down before Asana starts refusing them.
+pub struct RateLimitStats {
+    inner: Mutex<Budget>,
+}
+
+struct Budget {
+    sent: VecDeque<Instant>,
+    limit: u32,
+    learned_at: Option<Instant>,
+    totals: RateLimitUsage,
+}
+
+/// A snapshot of [`RateLimitStats`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BudgetSnapshot {
+    /// Requests sent in the last minute.
+    pub used: u32,
+    pub limit: u32,
+    /// Whether `limit` was lowered by a recent 429.
+    pub learned: bool,
+    /// Totals since the process started.
+    pub totals: RateLimitUsage,
+}
+
+static STATS: RateLimitStats = RateLimitStats::new(DEFAULT_BUDGET_PER_MINUTE);
+
+/// The process-wide request budget.
+pub fn stats() -> &'static RateLimitStats {
+    &STATS
+}
+
+impl RateLimitStats {
+    pub const fn new(limit: u32) -> Self {
+        Self {
+            inner: Mutex::new(Budget {
+                sent: VecDeque::new(),
+                limit,
+                learned_at: None,
+                totals: RateLimitUsage {
+                    requests: 0,
+                    retries: 0,
+                    wait_secs: 0.0,
+                },
+            }),
+        }
+    }
+
+    /// Count a request sent at `now` if the budget allows one, or return how
+    /// long to wait before asking again.
+    pub fn try_reserve(&self, now: Instant) -> Result<(), Duration> {
+        let mut budget = self.lock(now);
+        let used = budget.sent.len() as u32;
+        let delay = if used >= budget.limit {
+            budget.sent.front().map_or(Duration::ZERO, |oldest| {
+                (*oldest + WINDOW).saturating_duration_since(now)
+            })
+        } else if f64::from(used) >= f64::from(budget.limit) * SLOWDOWN_FRACTION {
+            let spacing = WINDOW / budget.limit;
+            budget.sent.back().map_or(Duration::ZERO, |last| {
+                (*last + spacing).saturating_duration_since(now)
+            })
+        } else {
+            Duration::ZERO
+        };
+        if !delay.is_zero() {
+            return Err(delay);
+        }
+        budget.sent.push_back(now);
+        budget.totals.requests += 1;
+        Ok(())
+    }
+
+    /// Lower the budget after a 429 at `now`, and count the retry.
+    pub fn rate_limited(&self, now: Instant, wait: Duration) {
+        let mut budget = self.lock(now);
+        let used = budget.sent.len() as u32;
+        budget.limit = (used * 9 / 10).clamp(MIN_BUDGET_PER_MINUTE, budget.limit);
+        budget.learned_at = Some(now);
+        budget.totals.retries += 1;
+        budget.totals.wait_secs += wait.as_secs_f64();
+    }
+
+    fn add_wait(&self, wait: Duration) {
+        self.lock(Instant::now()).totals.wait_secs += wait.as_secs_f64();
+    }
+
+    pub fn snapshot(&self) -> BudgetSnapshot {
+        let budget = self.lock(Instant::now());
+        BudgetSnapshot {
+            used: budget.sent.len() as u32,
+            limit: budget.limit,
+            learned: budget.learned_at.is_some(),
+            totals: budget.totals,
+        }
+    }
+
+    /// Lock the budget with requests older than a minute dropped, and a
+    /// learned limit past its TTL reset.
+    fn lock(&self, now: Instant) -> std::sync::MutexGuard<'_, Budget> {
+        let mut budget = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        while budget
+            .sent
+            .front()
+            .is_some_and(|sent| now.saturating_duration_since(*sent) >= WINDOW)
+        {
+            budget.sent.pop_front();
+        }
+        if budget
+            .learned_at
+            .is_some_and(|at| now.saturating_duration_since(at) >= LEARNED_BUDGET_TTL)
+        {
+            budget.limit = DEFAULT_BUDGET_PER_MINUTE;
+            budget.learned_at = None;
+        }
+        budget
+    }
+}
+
+tokio::task_local! {
+    static SCOPE: Arc<Mutex<RateLimitUsage>>;
+}
+
+fn with_scope(f: impl FnOnce(&mut RateLimitUsage)) {
+    let _ = SCOPE.try_with(|usage| f(&mut usage.lock().unwrap_or_else(|e| e.into_inner())));
+}
+
+/// What [`track`] returns.
+pub struct Tracked<T> {
+    pub value: T,
+    /// Usage by requests made while `value` was computed.
+    pub usage: RateLimitUsage,
+    /// False when tracked inside another [`track`], which also counts this
+    /// usage.
+    pub outermost: bool,
+}
+
+/// Run `fut`, counting the API usage of requests it makes. Nested calls
+/// count toward every enclosing one.
+pub async fn track<T>(fut: impl Future<Output = T>) -> Tracked<T> {
+    let outer = SCOPE.try_with(Arc::clone).ok();
+    let scope = Arc::new(Mutex::new(RateLimitUsage::default()));
+    let value = SCOPE.scope(scope.clone(), fut).await;
+    let usage = *scope.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(outer) = &outer {
+        outer.lock().unwrap_or_else(|e| e.into_inner()).add(&usage);
+    }
+    Tracked {
+        value,
+        usage,
+        outermost: outer.is_none(),
+    }
+}
+
 /// Sleep for the backoff duration (for use in non-macro contexts).
 pub async fn backoff_sleep(attempt: u32) {
     let wait = BACKOFF_SECONDS
@@ -57,5 +335,58 @@ pub async fn backoff_sleep(attempt: u32) {
         attempt + 1,
         MAX_RETRIES
     );
+    rate_limited(Duration::from_secs(wait));
+    pause_for(Duration::from_secs(wait));
     tokio::time::sleep(Duration::from_secs(wait)).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_throttles_and_learns() {
+        let stats = RateLimitStats::new(100);
+        let start = Instant::now();
+        for _ in 0..80 {
+            stats.try_reserve(start).unwrap();
+        }
+        // Past 80% requests are spaced 600ms apart
+        assert_eq!(stats.try_reserve(start), Err(Duration::from_millis(600)));
+        let mut now = start;
+        for _ in 0..20 {
+            now += Duration::from_millis(600);
+            stats.try_reserve(now).unwrap();
+        }
+        // Full: wait for the first requests to leave the window
+        assert_eq!(stats.try_reserve(now), Err(start + WINDOW - now));
+        assert!(stats.try_reserve(start + WINDOW).is_ok());
+
+        // A 429 at 21 requests in the window lowers the budget to 18
+        stats.rate_limited(start + WINDOW, Duration::from_secs(30));
+        let snapshot = stats.snapshot();
+        assert_eq!((snapshot.limit, snapshot.learned), (18, true));
+        assert_eq!(snapshot.totals.requests, 101);
+        assert_eq!(snapshot.totals.retries, 1);
+        assert!(stats.try_reserve(start + WINDOW).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_track_nests() {
+        let outer = track(async {
+            acquire().await;
+            let inner = track(async {
+                acquire().await;
+                rate_limited(Duration::from_secs(2));
+            })
+            .await;
+            assert!(!inner.outermost);
+            assert_eq!(inner.usage.requests, 1);
+        })
+        .await;
+        assert!(outer.outermost);
+        assert_eq!(outer.usage.requests, 2);
+        assert_eq!(outer.usage.retries, 1);
+        assert_eq!(outer.usage.wait_secs, 2.0);
+    }
+}
@@ -0,0 +1,405 @@
+//! Record/replay harness for end-to-end sync tests.
+//!
+//! A recording is a directory under `tests/fixtures/sync/` holding the API
+//! responses one full project sync fetched:
+//!
+//! - `project.json`: the project
+//! - `sections.json`: its sections, in board order
+//! - `tasks.json`: the task listing, with likes
+//! - `stories.json`: stories by task GID, for the tasks whose comments were fetched
+//! - `statuses.json`: the project's status updates
+//! - `deleted.json`: GIDs of stored tasks the listing left out and Asana no
+//!   longer has
+//!
+//! Every file but `project.json` is optional. Replaying a recording runs
+//! the project's full sync, `sync_project_full`, with the recording standing
+//! in for the API, then dumps the warehouse tables and compares them to a
+//! golden file under `tests/golden/sync/`. A change to the syncer or
+//! repository that changes what a sync stores shows up as a golden diff.
+//!
+//! `BLESS=1 cargo test sync::replay` rewrites the golden files after an
+//! intended change; review the diff before committing. Without it, a missing
+//! or different golden file fails the test. The ignored `record_fixture`
+//! test captures a new recording from a real project.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+
+use crate::storage::{repository, Database};
+use crate::sync::api_helpers::{BatchTaskOutcome, ProjectBriefInfo, SectionInfo, TaskWithLikes};
+use crate::sync::syncer::{self, ProjectSource};
+use crate::sync::{NoopProgress, SyncOptions, DEFAULT_PORTFOLIO_DEPTH};
+
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sync");
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/sync");
+
+/// Tables a project sync writes synced data to.
+const DUMPED_TABLES: &[&str] = &[
+    "dim_users",
+    "dim_teams",
+    "dim_projects",
+    "dim_sections",
+    "dim_custom_fields",
+    "dim_enum_options",
+    "fact_tasks",
+    "bridge_task_projects",
+    "bridge_task_tags",
+    "fact_task_custom_fields",
+    "bridge_task_multi_enum_values",
+    "fact_task_likes",
+    "fact_comments",
+    "fact_section_transitions",
+    "fact_custom_field_changes",
+    "fact_status_updates",
+    "discovered_projects",
+];
+
+/// Columns holding row IDs or the time a row was written, which differ
+/// between runs.
+const VOLATILE_COLUMNS: &[&str] = &[
+    "id",
+    "cached_at",
+    "deleted_at",
+    "first_seen_at",
+    "last_seen_at",
+];
+
+/// One full project sync's API responses.
+struct Recording {
+    project: asanaclient::Project,
+    sections: Vec<SectionInfo>,
+    tasks: Vec<serde_json::Value>,
+    stories: BTreeMap<String, Vec<asanaclient::Story>>,
+    statuses: Vec<asanaclient::types::StatusUpdate>,
+    deleted: Vec<String>,
+}
+
+impl Recording {
+    fn load(name: &str) -> Self {
+        let dir = Path::new(FIXTURES_DIR).join(name);
+        Self {
+            project: read_json(&dir, "project.json")
+                .unwrap_or_else(|| panic!("{} has no project.json", dir.display())),
+            sections: read_json(&dir, "sections.json").unwrap_or_default(),
+            tasks: read_json(&dir, "tasks.json").unwrap_or_default(),
+            stories: read_json(&dir, "stories.json").unwrap_or_default(),
+            statuses: read_json(&dir, "statuses.json").unwrap_or_default(),
+            deleted: read_json(&dir, "deleted.json").unwrap_or_default(),
+        }
+    }
+}
+
+fn read_json<T: DeserializeOwned>(dir: &Path, file: &str) -> Option<T> {
+    let path = dir.join(file);
+    let text = std::fs::read_to_string(&path).ok()?;
+    let value = serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("invalid recording {}: {e}", path.display()));
+    Some(value)
+}
+
+impl ProjectSource for Recording {
+    async fn project(&self, _project_gid: &str) -> crate::error::Result<asanaclient::Project> {
+        Ok(self.project.clone())
+    }
+
+    async fn sections(&self, _project_gid: &str) -> crate::error::Result<Vec<SectionInfo>> {
+        Ok(self.sections.clone())
+    }
+
+    /// The whole listing, as one page.
+    async fn task_page(
+        &self,
+        _path: &str,
+        _query_params: &[(&str, &str)],
+    ) -> crate::error::Result<(Vec<serde_json::Value>, Option<String>)> {
+        Ok((self.tasks.clone(), None))
+    }
+
+    /// Listed tasks are found and `deleted.json` ones aren't; any other
+    /// lookup fails, which leaves the task alone.
+    async fn unlisted_tasks(
+        &self,
+        task_gids: &[&str],
+    ) -> crate::error::Result<Vec<(String, BatchTaskOutcome)>> {
+        let outcomes = task_gids
+            .iter()
+            .map(|&gid| {
+                let listed = self.tasks.iter().find(|task| task["gid"] == gid);
+                let outcome = if self.deleted.iter().any(|deleted| deleted == gid) {
+                    BatchTaskOutcome::NotFound
+                } else if let Some(task) = listed {
+                    let task: TaskWithLikes = serde_json::from_value(task.clone())
+                        .unwrap_or_else(|e| panic!("invalid recorded task {gid}: {e}"));
+                    BatchTaskOutcome::Found(Box::new(task))
+                } else {
+                    BatchTaskOutcome::Failed("not in the recording".to_string())
+                };
+                (gid.to_string(), outcome)
+            })
+            .collect();
+        Ok(outcomes)
+    }
+
+    async fn task_comments(&self, task_gid: &str) -> crate::error::Result<Vec<asanaclient::Story>> {
+        Ok(self.stories.get(task_gid).cloned().unwrap_or_default())
+    }
+
+    async fn status_updates(
+        &self,
+        _project_gid: &str,
+    ) -> crate::error::Result<Vec<asanaclient::types::StatusUpdate>> {
+        Ok(self.statuses.clone())
+    }
+
+    async fn project_brief(
+        &self,
+        _project_gid: &str,
+    ) -> crate::error::Result<Option<ProjectBriefInfo>> {
+        Ok(None)
+    }
+
+    async fn establish_events(&self, _project_gid: &str) -> crate::error::Result<String> {
+        Ok("replay".to_string())
+    }
+}
+
+/// Run a full sync of the recording's project against the recording.
+///
+/// Recordings are replayed long after they were made, so the previous sync
+/// is dated to the newest change it stored first; otherwise every task would
+/// look unchanged since and keep its stored comments.
+async fn replay(db: &Database, recording: &Recording) {
+    let project_gid = recording.project.gid.clone();
+    db.writer()
+        .call({
+            let project_gid = project_gid.clone();
+            move |conn| {
+                let entity_key = format!("project:{project_gid}");
+                repository::ensure_entity_for_sync(conn, &entity_key, "project", &project_gid)?;
+                conn.execute(
+                    "UPDATE monitored_entities
+                     SET last_sync_at = (SELECT MAX(modified_at) FROM fact_tasks)
+                     WHERE entity_key = ?1",
+                    [&entity_key],
+                )?;
+                Ok::<_, rusqlite::Error>(())
+            }
+        })
+        .await
+        .unwrap();
+
+    let options = SyncOptions {
+        since: None,
+        days: None,
+        full: true,
+        dry_run: false,
+        concurrency: 1,
+        comment_concurrency: 1,
+        max_portfolio_depth: DEFAULT_PORTFOLIO_DEPTH,
+    };
+    syncer::sync_project_full(db, recording, &project_gid, &options, &NoopProgress)
+        .await
+        .unwrap();
+}
+
+/// Every [`DUMPED_TABLES`] row as text, sorted, without [`VOLATILE_COLUMNS`].
+async fn dump_tables(db: &Database) -> String {
+    db.reader().call(|conn| dump(conn)).await.unwrap()
+}
+
+fn dump(conn: &Connection) -> Result<String, rusqlite::Error> {
+    let mut out = String::new();
+    for table in DUMPED_TABLES {
+        let columns: Vec<String> = conn
+            .prepare(&format!("PRAGMA table_info({table})"))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|c| !VOLATILE_COLUMNS.contains(&c.as_str()))
+            .collect();
+        let order: Vec<String> = (1..=columns.len()).map(|i| i.to_string()).collect();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM {table} ORDER BY {}",
+            columns.join(", "),
+            order.join(", ")
+        ))?;
+
+        out.push_str(&format!("== {table} ==\n{}\n", columns.join("|")));
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let values = (0..columns.len())
+                .map(|i| row.get_ref(i).map(format_value))
+                .collect::<Result<Vec<_>, _>>()?;
+            out.push_str(&values.join("|"));
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// One value per line and column: newlines and separators are escaped.
+fn format_value(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t)
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('|', "\\|"),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+/// Compare a dump to `tests/golden/sync/<name>.txt`, or write it there
+/// with `BLESS` set.
+fn assert_golden(name: &str, actual: &str) {
+    let path: PathBuf = Path::new(GOLDEN_DIR).join(format!("{name}.txt"));
+    if std::env::var_os("BLESS").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "can't read {}: {e}\nRun with BLESS=1 to write it, then commit it",
+            path.display()
+        )
+    });
+    if expected == actual {
+        return;
+    }
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let line = expected
+        .iter()
+        .zip(&actual)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected.len().min(actual.len()));
+    panic!(
+        "{} differs at line {}:\n  expected: {}\n  actual:   {}\n\
+         Rerun with BLESS=1 if the change is intended",
+        path.display(),
+        line + 1,
+        expected.get(line).unwrap_or(&"<end of file>"),
+        actual.get(line).unwrap_or(&"<end of file>"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replay_project_sync() {
+        let db = Database::open_memory().await.unwrap();
+
+        let launch = Recording::load("launch");
+        replay(&db, &launch).await;
+        let first = dump_tables(&db).await;
+        assert_golden("launch", &first);
+
+        // Syncing unchanged data again changes nothing
+        replay(&db, &launch).await;
+        assert_eq!(dump_tables(&db).await, first);
+
+        replay(&db, &Recording::load("launch-later")).await;
+        assert_golden("launch-later", &dump_tables(&db).await);
+
+        let (renamed, deleted, comments) = db
+            .reader()
+            .call(|conn| {
+                let renamed: String = conn.query_row(
+                    "SELECT name FROM fact_tasks WHERE task_gid = '5002'",
+                    [],
+                    |row| row.get(0),
+                )?;
+                let deleted: i64 = conn.query_row(
+                    "SELECT is_deleted FROM fact_tasks WHERE task_gid = '5003'",
+                    [],
+                    |row| row.get(0),
+                )?;
+                let comments: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM fact_comments", [], |row| row.get(0))?;
+                Ok::<_, rusqlite::Error>((renamed, deleted, comments))
+            })
+            .await
+            .unwrap();
+        assert_eq!(renamed, "Book venue (Hall A)");
+        assert_eq!(deleted, 1);
+        assert_eq!(comments, 4);
+    }
+
+    /// Capture a recording of a real project, named by
+    /// `ASANADW_RECORD_NAME`, from `ASANADW_RECORD_PROJECT` with the
+    /// `ASANA_TOKEN` account:
+    ///
+    /// `cargo test record_fixture -- --ignored`
+    ///
+    /// The requests mirror a full sync's. Recordings hold real names,
+    /// emails, and text, so scrub them before committing.
+    #[tokio::test]
+    #[ignore]
+    async fn record_fixture() {
+        let project_gid = std::env::var("ASANADW_RECORD_PROJECT").unwrap();
+        let name = std::env::var("ASANADW_RECORD_NAME").unwrap();
+        let client = asanaclient::Client::from_env().unwrap();
+        let dir = Path::new(FIXTURES_DIR).join(&name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |file: &str, value: &serde_json::Value| {
+            let text = serde_json::to_string_pretty(value).unwrap();
+            std::fs::write(dir.join(file), text + "\n").unwrap();
+        };
+
+        let path = format!("/projects/{project_gid}");
+        let query = [(
+            "opt_fields",
+            "gid,name,archived,color,notes,html_notes,created_at,modified_at,owner,owner.name,team,team.name,workspace,workspace.name,permalink_url",
+        )];
+        crate::sync::rate_limit::acquire().await;
+        let project: serde_json::Value = client.get(&path, &query).await.unwrap();
+        write("project.json", &project);
+
+        let path = format!("/projects/{project_gid}/sections");
+        let query = [("opt_fields", "gid,name")];
+        crate::sync::rate_limit::acquire().await;
+        let sections: Vec<serde_json::Value> = client.get_all(&path, &query).await.unwrap();
+        write("sections.json", &serde_json::Value::from(sections));
+
+        let path = format!("/projects/{project_gid}/tasks");
+        let query = [("opt_fields", syncer::PROJECT_TASK_FIELDS)];
+        crate::sync::rate_limit::acquire().await;
+        let tasks: Vec<serde_json::Value> = client.get_all(&path, &query).await.unwrap();
+
+        let mut stories = serde_json::Map::new();
+        for gid in tasks.iter().filter_map(|t| t["gid"].as_str()) {
+            let path = format!("/tasks/{gid}/stories");
+            let query = [(
+                "opt_fields",
+                "gid,created_at,created_by,created_by.name,resource_subtype,text,html_text,type",
+            )];
+            crate::sync::rate_limit::acquire().await;
+            let task_stories: Vec<serde_json::Value> = client.get_all(&path, &query).await.unwrap();
+            stories.insert(gid.to_string(), task_stories.into());
+        }
+        write("tasks.json", &serde_json::Value::from(tasks));
+        write("stories.json", &serde_json::Value::from(stories));
+
+        let query = [
+            ("parent", project_gid.as_str()),
+            (
+                "opt_fields",
+                "gid,title,text,html_text,status_type,created_at,created_by,created_by.name,resource_subtype",
+            ),
+        ];
+        crate::sync::rate_limit::acquire().await;
+        let statuses: Vec<serde_json::Value> =
+            client.get_all("/status_updates", &query).await.unwrap();
+        write("statuses.json", &serde_json::Value::from(statuses));
+    }
+}
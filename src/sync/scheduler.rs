@@ -0,0 +1,329 @@
+use std::future::Future;
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+
+use super::{SyncOptions, SyncProgress, SyncReport, SyncStatus, SyncedProjects};
+use crate::error::{Error, Result};
+use crate::storage::repository::{self, SyncScheduleRow};
+use crate::storage::Database;
+use crate::AsanaDW;
+
+/// Config key for the default daemon sync interval (e.g. `30m`, `2h`).
+pub const SYNC_INTERVAL_KEY: &str = "sync_interval";
+
+/// Interval used when neither the entity nor `sync_interval` sets one.
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 30;
+
+/// Failure backoff never delays an entity by more than this.
+const MAX_BACKOFF_MINUTES: i64 = 24 * 60;
+
+/// Longest the daemon sleeps before re-reading the schedule, so newly
+/// monitored entities and interval changes are picked up promptly.
+const POLL_SECONDS: i64 = 60;
+
+/// SQLite `datetime()` format used for schedule timestamps.
+pub(crate) const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parse an interval like `45`, `45m`, `2h`, or `1d` into minutes.
+pub fn parse_interval(s: &str) -> Result<u32> {
+    let s = s.trim().to_lowercase();
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => s.split_at(idx),
+        None => (s.as_str(), "m"),
+    };
+    let multiplier = match unit.trim() {
+        "m" | "min" | "mins" => 1,
+        "h" | "hr" | "hrs" => 60,
+        "d" | "day" | "days" => 24 * 60,
+        _ => 0,
+    };
+    match digits.parse::<u32>() {
+        Ok(n) if n > 0 && multiplier > 0 => Ok(n.saturating_mul(multiplier)),
+        _ => Err(Error::Config(format!(
+            "invalid sync interval '{s}': expected e.g. 30m, 2h, or 1d"
+        ))),
+    }
+}
+
+/// Minutes to wait before the next run: the interval after a success,
+/// doubling per consecutive failure (capped at a day).
+pub fn backoff_minutes(interval_minutes: u32, consecutive_failures: u32) -> i64 {
+    let interval = interval_minutes as i64;
+    if consecutive_failures == 0 {
+        return interval;
+    }
+    let factor = 1i64 << consecutive_failures.min(10);
+    (interval * factor).min(MAX_BACKOFF_MINUTES.max(interval))
+}
+
+/// Spread runs by up to 10% of the interval so entities added together
+/// don't all hit the API at once. Derived from the clock rather than an
+/// RNG; it only needs to differ between runs.
+fn jitter_seconds(interval_minutes: u32) -> i64 {
+    let max = (interval_minutes as i64 * 6).max(1);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as i64)
+        .unwrap_or(0);
+    nanos % max
+}
+
+/// When an entity should next run, given the outcome of the run that just finished.
+pub fn next_run_at(
+    now: NaiveDateTime,
+    interval_minutes: u32,
+    consecutive_failures: u32,
+    jitter_seconds: i64,
+) -> NaiveDateTime {
+    now + Duration::minutes(backoff_minutes(interval_minutes, consecutive_failures))
+        + Duration::seconds(jitter_seconds)
+}
+
+/// Entities whose next run is at or before `now` (never-run entities are always due).
+pub fn due_entities(schedule: &[SyncScheduleRow], now: NaiveDateTime) -> Vec<&SyncScheduleRow> {
+    let now = now.format(TIMESTAMP_FORMAT).to_string();
+    schedule
+        .iter()
+        .filter(|row| {
+            row.next_run_at
+                .as_deref()
+                .is_none_or(|at| at <= now.as_str())
+        })
+        .collect()
+}
+
+/// The daemon's default interval: `sync_interval` config, else [`DEFAULT_INTERVAL_MINUTES`].
+pub async fn default_interval(db: &Database) -> Result<u32> {
+    let configured: Option<String> = db
+        .reader()
+        .call(|conn| repository::get_config(conn, SYNC_INTERVAL_KEY))
+        .await?;
+    match configured {
+        Some(s) => parse_interval(&s),
+        None => Ok(DEFAULT_INTERVAL_MINUTES),
+    }
+}
+
+/// Load the schedule for all enabled monitored entities.
+pub async fn load_schedule(db: &Database) -> Result<Vec<SyncScheduleRow>> {
+    db.reader()
+        .call(|conn| repository::list_sync_schedule(conn))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))
+}
+
+/// Sync every entity that is currently due and record the outcomes.
+/// `default_interval` applies to entities without their own interval.
+pub async fn run_due(
+    dw: &AsanaDW,
+    default_interval: u32,
+    options: &SyncOptions,
+    progress: &dyn SyncProgress,
+) -> Result<Vec<SyncReport>> {
+    let schedule = load_schedule(dw.db()).await?;
+    let due = due_entities(&schedule, Utc::now().naive_utc());
+    let total = due.len();
+
+    let synced = SyncedProjects::default();
+    let mut reports = Vec::new();
+    for (i, row) in due.into_iter().enumerate() {
+        progress.on_entity_start(&row.entity_key, i, total);
+        let report = match dw
+            .sync_entity_with(
+                &row.entity_type,
+                &row.entity_gid,
+                options,
+                progress,
+                &synced,
+            )
+            .await
+        {
+            Ok(report) => report,
+            Err(e) => {
+                log::error!("Scheduled sync of {} failed: {e}", row.entity_key);
+                SyncReport::failed(row.entity_key.clone(), e)
+            }
+        };
+        progress.on_entity_complete(&report);
+
+        let failures = match report.status {
+            SyncStatus::Failed => row.consecutive_failures + 1,
+            _ => 0,
+        };
+        let interval = row.interval_minutes.unwrap_or(default_interval);
+        let next = next_run_at(
+            Utc::now().naive_utc(),
+            interval,
+            failures,
+            jitter_seconds(interval),
+        )
+        .format(TIMESTAMP_FORMAT)
+        .to_string();
+        let status = match report.status {
+            SyncStatus::Success => "success",
+            SyncStatus::PartialFailure => "partial_failure",
+            SyncStatus::Failed => "failed",
+        };
+
+        dw.db()
+            .writer()
+            .call({
+                let entity_key = row.entity_key.clone();
+                let error = report.error.clone();
+                move |conn| {
+                    repository::record_scheduled_run(
+                        conn,
+                        &entity_key,
+                        status,
+                        error.as_deref(),
+                        failures,
+                        &next,
+                    )
+                }
+            })
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?;
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+/// How long to sleep before the next entity or scheduled export is due,
+/// between one second and the poll interval.
+async fn time_until_next_due(db: &Database) -> Result<StdDuration> {
+    let schedule = load_schedule(db).await?;
+    let next_export = crate::export::schedule::next_due(db).await?;
+    let now = Utc::now().naive_utc();
+    let wait = schedule
+        .iter()
+        .map(|row| match &row.next_run_at {
+            Some(at) => NaiveDateTime::parse_from_str(at, TIMESTAMP_FORMAT)
+                .map(|at| (at - now).num_seconds())
+                .unwrap_or(0),
+            None => 0,
+        })
+        .chain(next_export.map(|at| (at - now).num_seconds()))
+        .min()
+        .unwrap_or(POLL_SECONDS)
+        .clamp(1, POLL_SECONDS);
+    Ok(StdDuration::from_secs(wait as u64))
+}
+
+/// Run scheduled syncs and exports until `shutdown` resolves. Shutdown is
+/// checked between runs, so an in-flight sync or export always finishes.
+pub async fn run_daemon<F>(
+    dw: &AsanaDW,
+    options: &SyncOptions,
+    progress: &dyn SyncProgress,
+    shutdown: F,
+) -> Result<()>
+where
+    F: Future<Output = ()>,
+{
+    if let Err(e) = dw.ensure_user_identity().await {
+        log::warn!("Could not auto-detect user identity: {e}");
+    }
+    tokio::pin!(shutdown);
+
+    loop {
+        let interval = default_interval(dw.db()).await?;
+        run_due(dw, interval, options, progress).await?;
+        for run in crate::export::schedule::run_due(dw.db()).await? {
+            if run.error.is_none() {
+                log::info!(
+                    "Scheduled export #{} wrote {} tables to {}",
+                    run.schedule_id,
+                    run.tables_exported,
+                    run.out
+                );
+            }
+        }
+
+        let wait = time_until_next_due(dw.db()).await?;
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, TIMESTAMP_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("45").unwrap(), 45);
+        assert_eq!(parse_interval("15m").unwrap(), 15);
+        assert_eq!(parse_interval("2h").unwrap(), 120);
+        assert_eq!(parse_interval(" 1D ").unwrap(), 1440);
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("soon").is_err());
+        assert!(parse_interval("5w").is_err());
+    }
+
+    #[test]
+    fn test_backoff_and_next_run() {
+        assert_eq!(backoff_minutes(30, 0), 30);
+        assert_eq!(backoff_minutes(30, 1), 60);
+        assert_eq!(backoff_minutes(30, 3), 240);
+        assert_eq!(backoff_minutes(30, 20), MAX_BACKOFF_MINUTES);
+        // Intervals longer than the cap are never shortened by a failure
+        assert_eq!(backoff_minutes(2 * 1440, 1), 2 * 1440);
+
+        let now = ts("2025-01-01 00:00:00");
+        assert_eq!(next_run_at(now, 30, 0, 90), ts("2025-01-01 00:31:30"));
+        assert_eq!(next_run_at(now, 30, 2, 0), ts("2025-01-01 02:00:00"));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_tracks_runs() {
+        let db = Database::open_memory().await.unwrap();
+        db.writer()
+            .call(|conn| {
+                repository::add_monitored_entity(conn, "project:1", "project", "1", None)?;
+                repository::add_monitored_entity(conn, "team:2", "team", "2", None)?;
+                repository::set_sync_interval(conn, "team:2", Some(120))?;
+                repository::record_scheduled_run(
+                    conn,
+                    "project:1",
+                    "failed",
+                    Some("boom"),
+                    2,
+                    "2025-01-01 01:00:00",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        let schedule = load_schedule(&db).await.unwrap();
+        assert_eq!(schedule.len(), 2);
+        // Never-run entities sort first
+        assert_eq!(schedule[0].entity_key, "team:2");
+        assert_eq!(schedule[0].interval_minutes, Some(120));
+        assert!(schedule[0].next_run_at.is_none());
+        assert_eq!(schedule[1].consecutive_failures, 2);
+        assert_eq!(schedule[1].last_error.as_deref(), Some("boom"));
+
+        let due: Vec<&str> = due_entities(&schedule, ts("2025-01-01 00:30:00"))
+            .iter()
+            .map(|r| r.entity_key.as_str())
+            .collect();
+        assert_eq!(due, vec!["team:2"]);
+        assert_eq!(due_entities(&schedule, ts("2025-01-01 01:00:00")).len(), 2);
+
+        assert_eq!(default_interval(&db).await.unwrap(), 30);
+        db.writer()
+            .call(|conn| repository::set_config(conn, SYNC_INTERVAL_KEY, "1h"))
+            .await
+            .unwrap();
+        assert_eq!(default_interval(&db).await.unwrap(), 60);
+    }
+}
@@ -0,0 +1,143 @@
+//! Where a sync starts when neither `--since` nor `--days` is given.
+//!
+//! A fixed 90-day lookback refetches months of unchanged tasks on every
+//! routine sync of a long-lived project. Once a monitored project or user
+//! has synced, its default starts shortly before the newest task change
+//! already stored for it instead.
+
+use chrono::{Duration, NaiveDate};
+use rusqlite::Connection;
+
+use crate::error::Result;
+use crate::storage::{repository, Database};
+use crate::sync::SyncOptions;
+
+/// Days a sync goes back without a `--since`, `--days`, or stored activity.
+pub const DEFAULT_LOOKBACK_DAYS: i64 = 90;
+
+/// Days before the newest stored change a smart default starts, for tasks
+/// modified while the last sync ran or reported late by Asana.
+pub const SMART_SINCE_OVERLAP_DAYS: i64 = 7;
+
+/// The first day a sync of `entity_key` fetches: `--since` or `--days` when
+/// given, otherwise [`smart_since`].
+pub async fn sync_since(
+    db: &Database,
+    entity_key: &str,
+    options: &SyncOptions,
+    today: NaiveDate,
+) -> Result<NaiveDate> {
+    if let Some(since) = options.since_date() {
+        return Ok(since);
+    }
+    let entity_key = entity_key.to_string();
+    let full = options.full;
+    let since = db
+        .reader()
+        .call(move |conn| smart_since(conn, &entity_key, full, today))
+        .await?;
+    Ok(since)
+}
+
+/// The default start for `entity_key`: [`SMART_SINCE_OVERLAP_DAYS`] before
+/// the earlier of its last sync as a monitored entity and the newest
+/// `modified_at` stored for its tasks, but never before the
+/// [`DEFAULT_LOOKBACK_DAYS`] window. Projects and users that haven't
+/// completed a monitored sync, other entity types, and `full` syncs get
+/// the whole window.
+pub fn smart_since(
+    conn: &Connection,
+    entity_key: &str,
+    full: bool,
+    today: NaiveDate,
+) -> std::result::Result<NaiveDate, rusqlite::Error> {
+    let window = today - Duration::days(DEFAULT_LOOKBACK_DAYS);
+    if full {
+        return Ok(window);
+    }
+    let newest_sql = match entity_key.split_once(':') {
+        Some(("project", _)) => {
+            "SELECT MAX(t.modified_at) FROM fact_tasks t
+             JOIN bridge_task_projects btp ON btp.task_gid = t.task_gid
+             WHERE btp.project_gid = ?1"
+        }
+        Some(("user", _)) => "SELECT MAX(modified_at) FROM fact_tasks WHERE assignee_gid = ?1",
+        _ => return Ok(window),
+    };
+    // Tasks stored by another entity's sync may be newer than this one's
+    // last sync, so both bound the start
+    let Some(last_sync_at) = repository::get_last_sync_at(conn, entity_key)? else {
+        return Ok(window);
+    };
+    let gid = entity_key.split_once(':').map_or("", |(_, gid)| gid);
+    let newest: Option<String> = conn.query_row(newest_sql, [gid], |row| row.get(0))?;
+    let Some(newest) = newest else {
+        return Ok(window);
+    };
+    let activity = newest.as_str().min(last_sync_at.as_str());
+    let Some(day) = activity
+        .get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    else {
+        return Ok(window);
+    };
+    Ok((day - Duration::days(SMART_SINCE_OVERLAP_DAYS)).clamp(window, today))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_smart_since() {
+        let db = Database::open_memory().await.unwrap();
+        let today = date("2025-06-30");
+        let since = |key: &'static str, full: bool| {
+            let db = db.clone();
+            async move {
+                db.reader()
+                    .call(move |conn| smart_since(conn, key, full, today))
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Never synced: the whole window
+        assert_eq!(since("project:p1", false).await, date("2025-04-01"));
+
+        db.writer()
+            .call(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO dim_users (user_gid, name, cached_at)
+                     VALUES ('u1', 'Ada', datetime('now')), ('u2', 'Grace', datetime('now'));
+                     INSERT INTO dim_projects (project_gid, name, workspace_gid, cached_at)
+                     VALUES ('p1', 'Launch', 'w1', datetime('now')), ('p2', 'Archive', 'w1', datetime('now'));
+                     INSERT INTO fact_tasks (task_gid, name, assignee_gid, modified_at, created_at, created_date_key, cached_at)
+                     VALUES ('1', 'One', 'u1', '2025-06-20T10:00:00Z', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('2', 'Two', 'u1', '2025-06-25T10:00:00Z', '2025-01-01', '2025-01-01', datetime('now')),
+                            ('3', 'Old', 'u2', '2025-01-05T10:00:00Z', '2025-01-01', '2025-01-01', datetime('now'));
+                     INSERT INTO bridge_task_projects (task_gid, project_gid)
+                     VALUES ('1', 'p1'), ('3', 'p2');
+                     INSERT INTO monitored_entities (entity_key, entity_type, entity_gid, added_at, last_sync_at)
+                     VALUES ('project:p1', 'project', 'p1', '2025-01-01', '2025-06-28 00:05:00'),
+                            ('project:p2', 'project', 'p2', '2025-01-01', '2025-06-28 00:05:00'),
+                            ('user:u1', 'user', 'u1', '2025-01-01', '2025-06-22 00:05:00');",
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(since("project:p1", false).await, date("2025-06-13"));
+        assert_eq!(since("project:p1", true).await, date("2025-04-01"));
+        // A stale project keeps the window
+        assert_eq!(since("project:p2", false).await, date("2025-04-01"));
+        // Task 2 is newer than the user's last sync, which bounds the start
+        assert_eq!(since("user:u1", false).await, date("2025-06-15"));
+        assert_eq!(since("team:t1", false).await, date("2025-04-01"));
+    }
+}
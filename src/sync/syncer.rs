@@ -1,12 +1,31 @@
 use std::collections::HashSet;
 
 use chrono::Duration;
+use futures::StreamExt;
 
 use crate::error::Result;
+use crate::metrics::notability;
 use crate::storage::repository;
 use crate::storage::Database;
-use crate::sync::rate_limit::retry_api;
-use crate::sync::{IncrementalSyncSummary, SyncOptions, SyncProgress, SyncReport, SyncStatus};
+use crate::storage::{bots, field_changes, threads, transitions};
+use crate::sync::api_helpers::{
+    batch_get_tasks, BatchTaskOutcome, ProjectBriefInfo, SectionInfo, TaskWithLikes, UserRef,
+    BATCH_SIZE,
+};
+use crate::sync::changes;
+use crate::sync::gap;
+use crate::sync::rate_limit::{self, is_429_error, retry_api};
+use crate::sync::since::sync_since;
+use crate::sync::{
+    IncrementalSyncSummary, SyncOptions, SyncProgress, SyncReport, SyncStatus, SyncedProjects,
+};
+
+/// How long an interrupted full sync job stays resumable. Older jobs list
+/// the project from the first page again.
+const RESUME_WINDOW_HOURS: i64 = 24;
+
+/// Tasks whose comments a full sync fetches and stores between checkpoints.
+const COMMENT_CHECKPOINT_TASKS: usize = 100;
 
 /// Maximum number of changed tasks before falling back to full sync.
 /// If events report more changes than this, individual GETs would be slower
@@ -14,12 +33,151 @@ use crate::sync::{IncrementalSyncSummary, SyncOptions, SyncProgress, SyncReport,
 const INCREMENTAL_THRESHOLD: usize = 50;
 
 /// Task fields requested during project sync (both incremental and full).
-const PROJECT_TASK_FIELDS: &str = "gid,name,completed,completed_at,assignee,assignee.name,assignee.email,due_on,due_at,start_on,start_at,created_at,modified_at,notes,html_notes,parent,parent.name,num_subtasks,num_likes,memberships,memberships.project,memberships.project.name,memberships.section,memberships.section.name,tags,tags.name,custom_fields,custom_fields.gid,custom_fields.name,custom_fields.display_value,custom_fields.resource_subtype,custom_fields.text_value,custom_fields.number_value,custom_fields.enum_value,custom_fields.enum_value.gid,custom_fields.enum_value.name,custom_fields.enum_value.color,custom_fields.enum_value.enabled,custom_fields.multi_enum_values,custom_fields.multi_enum_values.gid,custom_fields.multi_enum_values.name,custom_fields.multi_enum_values.color,custom_fields.multi_enum_values.enabled,custom_fields.date_value,custom_fields.date_value.date,custom_fields.date_value.date_time,permalink_url";
+pub(super) const PROJECT_TASK_FIELDS: &str = "gid,name,resource_subtype,completed,completed_at,assignee,assignee.name,assignee.email,due_on,due_at,start_on,start_at,created_at,modified_at,notes,html_notes,parent,parent.name,num_subtasks,num_likes,likes,likes.user,likes.user.name,memberships,memberships.project,memberships.project.name,memberships.section,memberships.section.name,tags,tags.name,custom_fields,custom_fields.gid,custom_fields.name,custom_fields.display_value,custom_fields.resource_subtype,custom_fields.text_value,custom_fields.number_value,custom_fields.enum_value,custom_fields.enum_value.gid,custom_fields.enum_value.name,custom_fields.enum_value.color,custom_fields.enum_value.enabled,custom_fields.multi_enum_values,custom_fields.multi_enum_values.gid,custom_fields.multi_enum_values.name,custom_fields.multi_enum_values.color,custom_fields.multi_enum_values.enabled,custom_fields.date_value,custom_fields.date_value.date,custom_fields.date_value.date_time,permalink_url";
+
+/// The Asana reads a full project sync makes. [`asanaclient::Client`] makes
+/// them against the API, retrying rate-limited requests; the replay tests
+/// answer them from a recording.
+pub(super) trait ProjectSource {
+    /// The project, with the fields its metadata is stored from.
+    async fn project(&self, project_gid: &str) -> Result<asanaclient::Project>;
+
+    /// The project's sections, in board order.
+    async fn sections(&self, project_gid: &str) -> Result<Vec<SectionInfo>>;
+
+    /// One page of a task listing, and the offset of the next one.
+    async fn task_page(
+        &self,
+        path: &str,
+        query_params: &[(&str, &str)],
+    ) -> Result<(Vec<serde_json::Value>, Option<String>)>;
+
+    /// Look up tasks the listing left out, one outcome per GID.
+    async fn unlisted_tasks(&self, task_gids: &[&str]) -> Result<Vec<(String, BatchTaskOutcome)>>;
+
+    /// A task's comments.
+    async fn task_comments(&self, task_gid: &str) -> Result<Vec<asanaclient::Story>>;
+
+    /// The project's status updates.
+    async fn status_updates(
+        &self,
+        project_gid: &str,
+    ) -> Result<Vec<asanaclient::types::StatusUpdate>>;
+
+    /// The project's brief, if it has one.
+    async fn project_brief(&self, project_gid: &str) -> Result<Option<ProjectBriefInfo>>;
+
+    /// A fresh event sync token for the project.
+    async fn establish_events(&self, project_gid: &str) -> Result<String>;
+}
+
+impl ProjectSource for asanaclient::Client {
+    async fn project(&self, project_gid: &str) -> Result<asanaclient::Project> {
+        retry_api!(self.projects().get_full(project_gid))
+    }
+
+    async fn sections(&self, project_gid: &str) -> Result<Vec<SectionInfo>> {
+        super::api_helpers::get_project_sections(self, project_gid).await
+    }
+
+    async fn task_page(
+        &self,
+        path: &str,
+        query_params: &[(&str, &str)],
+    ) -> Result<(Vec<serde_json::Value>, Option<String>)> {
+        let page: asanaclient::Page<serde_json::Value> =
+            retry_api!(self.get_page(path, query_params))?;
+        Ok((page.data, page.next_page.map(|p| p.offset)))
+    }
+
+    async fn unlisted_tasks(&self, task_gids: &[&str]) -> Result<Vec<(String, BatchTaskOutcome)>> {
+        retry_api!(batch_get_tasks(self, task_gids, PROJECT_TASK_FIELDS))
+    }
+
+    async fn task_comments(&self, task_gid: &str) -> Result<Vec<asanaclient::Story>> {
+        retry_api!(self.tasks().comments(task_gid))
+    }
+
+    async fn status_updates(
+        &self,
+        project_gid: &str,
+    ) -> Result<Vec<asanaclient::types::StatusUpdate>> {
+        retry_api!(self.projects().status_updates(project_gid))
+    }
+
+    async fn project_brief(&self, project_gid: &str) -> Result<Option<ProjectBriefInfo>> {
+        super::api_helpers::get_project_brief(self, project_gid).await
+    }
+
+    async fn establish_events(&self, project_gid: &str) -> Result<String> {
+        rate_limit::acquire().await;
+        Ok(self.events().establish(project_gid).await?)
+    }
+}
+
+/// Fetch comments for each task with up to `concurrency` requests in flight.
+///
+/// A task whose comments can't be fetched gets an empty list rather than
+/// failing the sync. Results come back in completion order, and progress
+/// counts completed fetches on top of `done` out of `total`, for callers
+/// fetching a long list in chunks.
+async fn fetch_task_comments(
+    source: &impl ProjectSource,
+    entity_key: &str,
+    task_gids: &[&str],
+    concurrency: usize,
+    progress: &dyn SyncProgress,
+    (done, total): (usize, usize),
+) -> Vec<(String, Vec<asanaclient::Story>)> {
+    // Equivalent to `buffer_unordered`, but without a closure: closures
+    // returning borrowed futures aren't general enough for the boxed,
+    // `Send` portfolio sync future that (transitively) awaits this.
+    let mut pending = task_gids.iter();
+    let mut in_flight = futures::stream::FuturesUnordered::new();
+    for &task_gid in pending.by_ref().take(concurrency.max(1)) {
+        in_flight.push(fetch_comments_for_task(source, task_gid));
+    }
+
+    let mut task_comments = Vec::with_capacity(task_gids.len());
+    while let Some(result) = in_flight.next().await {
+        task_comments.push(result);
+        progress.on_comments_progress(entity_key, done + task_comments.len(), total);
+        if let Some(&task_gid) = pending.next() {
+            in_flight.push(fetch_comments_for_task(source, task_gid));
+        }
+    }
+    task_comments
+}
+
+/// Fetch one task with the project task fields. `Ok(None)` means it was deleted.
+async fn fetch_task(client: &asanaclient::Client, task_gid: &str) -> Result<Option<TaskWithLikes>> {
+    let path = format!("/tasks/{task_gid}");
+    let query_params = [("opt_fields", PROJECT_TASK_FIELDS)];
+    match retry_api!(client.get::<TaskWithLikes>(&path, &query_params)) {
+        Ok(task) => Ok(Some(task)),
+        Err(crate::error::Error::Api(asanaclient::Error::NotFound(_))) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+async fn fetch_comments_for_task(
+    source: &impl ProjectSource,
+    task_gid: &str,
+) -> (String, Vec<asanaclient::Story>) {
+    let comments = match source.task_comments(task_gid).await {
+        Ok(comments) => comments,
+        Err(e) => {
+            log::warn!("Failed to fetch comments for task {task_gid}: {e}");
+            Vec::new()
+        }
+    };
+    (task_gid.to_string(), comments)
+}
 
 /// Store status updates and their authors in the database.
 ///
 /// Shared by full sync, incremental sync, and portfolio sync paths.
-async fn upsert_status_updates(
+pub(super) async fn upsert_status_updates(
     db: &Database,
     parent_gid: &str,
     parent_type: &str,
@@ -47,9 +205,37 @@ async fn upsert_status_updates(
     Ok(())
 }
 
+/// Fetch and store a project's brief, or remove the stored one if the
+/// project no longer has a brief. Failures are logged, not fatal.
+async fn sync_project_brief(
+    source: &impl ProjectSource,
+    db: &Database,
+    entity_key: &str,
+    project_gid: &str,
+) -> Result<()> {
+    let brief = match source.project_brief(project_gid).await {
+        Ok(brief) => brief,
+        Err(e) => {
+            log::warn!("Failed to fetch project brief for {entity_key}: {e}");
+            return Ok(());
+        }
+    };
+    db.writer()
+        .call({
+            let project_gid = project_gid.to_string();
+            move |conn| match brief {
+                Some(brief) => repository::upsert_project_brief(conn, &project_gid, &brief),
+                None => repository::delete_project_brief(conn, &project_gid),
+            }
+        })
+        .await?;
+    Ok(())
+}
+
 /// Classified summary of Asana events by resource type.
 struct EventSummary {
     changed_task_gids: HashSet<String>,
+    deleted_task_gids: HashSet<String>,
     project_changed: bool,
     sections_changed: bool,
     status_updates_changed: bool,
@@ -58,6 +244,7 @@ struct EventSummary {
 impl EventSummary {
     fn has_changes(&self) -> bool {
         !self.changed_task_gids.is_empty()
+            || !self.deleted_task_gids.is_empty()
             || self.project_changed
             || self.sections_changed
             || self.status_updates_changed
@@ -66,6 +253,7 @@ impl EventSummary {
     fn to_progress_summary(&self) -> IncrementalSyncSummary {
         IncrementalSyncSummary {
             tasks_changed: self.changed_task_gids.len(),
+            tasks_deleted: self.deleted_task_gids.len(),
             project_changed: self.project_changed,
             sections_changed: self.sections_changed,
             status_updates_changed: self.status_updates_changed,
@@ -77,6 +265,7 @@ impl EventSummary {
 fn classify_events(events: &[asanaclient::Event]) -> EventSummary {
     let mut summary = EventSummary {
         changed_task_gids: HashSet::new(),
+        deleted_task_gids: HashSet::new(),
         project_changed: false,
         sections_changed: false,
         status_updates_changed: false,
@@ -90,9 +279,16 @@ fn classify_events(events: &[asanaclient::Event]) -> EventSummary {
             .unwrap_or("");
         match resource_type {
             "task" => match event.action.as_str() {
-                "changed" | "added" | "undeleted" => {
+                // "removed" means removed from the project; refetching the
+                // task refreshes its memberships
+                "changed" | "added" | "undeleted" | "removed" => {
+                    summary.deleted_task_gids.remove(&event.resource.gid);
                     summary.changed_task_gids.insert(event.resource.gid.clone());
                 }
+                "deleted" => {
+                    summary.changed_task_gids.remove(&event.resource.gid);
+                    summary.deleted_task_gids.insert(event.resource.gid.clone());
+                }
                 _ => {}
             },
             "story" => match event.action.as_str() {
@@ -127,7 +323,7 @@ fn classify_events(events: &[asanaclient::Event]) -> EventSummary {
 /// Store project metadata (owner, team, project, sections) in the database.
 ///
 /// Shared by full sync and incremental sync (project/section refresh) paths.
-async fn upsert_project_metadata(
+pub(super) async fn upsert_project_metadata(
     db: &Database,
     project: &asanaclient::Project,
     sections: &[super::api_helpers::SectionInfo],
@@ -171,10 +367,11 @@ async fn upsert_project_metadata(
 ///
 /// Handles the FK constraint dance: upsert referenced users first, temporarily
 /// disable FK checks for tasks (parent_gid may reference tasks not yet synced),
-/// then re-enable FK checks before inserting comments.
+/// then re-enable FK checks before inserting comments. Every task touched is
+/// rescored for notability afterwards.
 ///
 /// Shared by full sync and incremental sync paths.
-async fn upsert_tasks_and_comments(
+pub(super) async fn upsert_tasks_and_comments(
     db: &Database,
     tasks: &[asanaclient::Task],
     task_comments: &[(String, Vec<asanaclient::Story>)],
@@ -221,12 +418,144 @@ async fn upsert_tasks_and_comments(
                 conn.execute_batch("PRAGMA foreign_keys = ON;")?;
 
                 // Upsert comments
+                let tracked = field_changes::tracked_fields(conn)?;
+                let bot_filter = bots::BotFilter::load(conn)?;
                 for (task_gid, comments) in &task_comments {
                     for comment in comments {
                         repository::upsert_comment(conn, task_gid, comment)?;
                     }
+                    if !comments.is_empty() {
+                        bots::flag_bot_comments(conn, task_gid, &bot_filter)?;
+                        threads::link_comment_threads(conn, task_gid)?;
+                        transitions::rebuild_section_transitions(conn, task_gid)?;
+                        field_changes::rebuild_field_changes(conn, task_gid, &tracked)?;
+                    }
+                }
+
+                let touched: Vec<String> = tasks
+                    .iter()
+                    .map(|t| t.gid.clone())
+                    .chain(task_comments.iter().map(|(gid, _)| gid.clone()))
+                    .collect::<HashSet<_>>()
+                    .into_iter()
+                    .collect();
+                let weights = notability::load_weights(conn)?;
+                notability::score_tasks(conn, &weights, &touched)?;
+
+                Ok::<(), rusqlite::Error>(())
+            }
+        })
+        .await?;
+    Ok(())
+}
+
+/// Tasks stored for the project that a full listing from `since` should
+/// have returned but didn't, and that Asana no longer has. Each is looked up
+/// first: one that still exists (moved to another project, say) is stored
+/// afresh instead, which also drops its membership here.
+async fn tasks_missing_from_listing(
+    db: &Database,
+    source: &impl ProjectSource,
+    project_gid: &str,
+    since: chrono::NaiveDate,
+    listed: &HashSet<&str>,
+) -> Result<Vec<String>> {
+    let stored = db
+        .reader()
+        .call({
+            let project_gid = project_gid.to_string();
+            let since = since.format("%Y-%m-%d").to_string();
+            move |conn| repository::project_task_gids_since(conn, &project_gid, &since)
+        })
+        .await?;
+    let unlisted: Vec<&str> = stored
+        .iter()
+        .map(String::as_str)
+        .filter(|gid| !listed.contains(gid))
+        .collect();
+
+    let mut missing = Vec::new();
+    let mut refreshed = Vec::new();
+    for chunk in unlisted.chunks(BATCH_SIZE) {
+        let outcomes = match source.unlisted_tasks(chunk).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                // Leave them be rather than tombstone tasks we couldn't check
+                log::warn!("Failed to check {} unlisted tasks: {e}", chunk.len());
+                continue;
+            }
+        };
+        for (gid, outcome) in outcomes {
+            match outcome {
+                BatchTaskOutcome::Found(task) => refreshed.push(*task),
+                BatchTaskOutcome::NotFound => missing.push(gid),
+                BatchTaskOutcome::Failed(reason) => {
+                    log::debug!("Couldn't check unlisted task {gid}: {reason}");
                 }
+            }
+        }
+    }
+
+    let (tasks, task_likes) = split_task_likes(refreshed);
+    upsert_tasks_and_comments(db, &tasks, &[]).await?;
+    upsert_task_likes(db, &task_likes).await?;
+    Ok(missing)
+}
 
+/// Flag tasks Asana no longer has as deleted.
+pub(super) async fn mark_tasks_deleted(
+    db: &Database,
+    entity_key: &str,
+    task_gids: Vec<String>,
+    progress: &dyn SyncProgress,
+) -> Result<()> {
+    if task_gids.is_empty() {
+        return Ok(());
+    }
+    let marked = db
+        .writer()
+        .call(move |conn| repository::mark_tasks_deleted(conn, &task_gids))
+        .await?;
+    if marked > 0 {
+        progress.on_tasks_deleted(entity_key, marked);
+    }
+    Ok(())
+}
+
+/// Separate fetched tasks from the users who liked them.
+#[allow(clippy::type_complexity)]
+pub(super) fn split_task_likes(
+    fetched: Vec<TaskWithLikes>,
+) -> (Vec<asanaclient::Task>, Vec<(String, Vec<UserRef>)>) {
+    fetched
+        .into_iter()
+        .map(|t| {
+            let likers = t.likes.into_iter().map(|l| l.user).collect();
+            let gid = t.task.gid.clone();
+            (t.task, (gid, likers))
+        })
+        .unzip()
+}
+
+/// Store the users who liked each task, replacing any previous set.
+pub(super) async fn upsert_task_likes(
+    db: &Database,
+    task_likes: &[(String, Vec<UserRef>)],
+) -> Result<()> {
+    if task_likes.is_empty() {
+        return Ok(());
+    }
+    db.writer()
+        .call({
+            let task_likes = task_likes.to_vec();
+            move |conn| {
+                for (task_gid, likers) in &task_likes {
+                    for user in likers {
+                        repository::upsert_user_minimal(conn, &user.gid, user.name.as_deref())?;
+                    }
+                    let gids: Vec<String> = likers.iter().map(|u| u.gid.clone()).collect();
+                    repository::replace_task_likes(conn, task_gid, &gids)?;
+                }
                 Ok::<(), rusqlite::Error>(())
             }
         })
@@ -234,6 +563,50 @@ async fn upsert_tasks_and_comments(
     Ok(())
 }
 
+/// Resume every full project sync that stopped partway through in the last
+/// [`RESUME_WINDOW_HOURS`], picking each up from its staged task pages and
+/// comment checkpoint.
+pub async fn resume_interrupted(
+    db: &Database,
+    client: &asanaclient::Client,
+    options: &SyncOptions,
+    progress: &dyn SyncProgress,
+) -> Result<Vec<SyncReport>> {
+    let jobs = db
+        .reader()
+        .call(|conn| repository::list_resumable_sync_jobs(conn, RESUME_WINDOW_HOURS))
+        .await?;
+
+    let total = jobs.len();
+    let mut reports = Vec::new();
+    for (i, job) in jobs.into_iter().enumerate() {
+        let Some(project_gid) = job.entity_key.strip_prefix("project:") else {
+            log::warn!("Can't resume sync of {}", job.entity_key);
+            continue;
+        };
+        progress.on_entity_start(&job.entity_key, i, total);
+        // The job is found again by its range start, so sync from exactly there
+        let since = chrono::NaiveDate::parse_from_str(&job.range_start, "%Y-%m-%d").ok();
+        let options = SyncOptions {
+            since,
+            days: None,
+            full: true,
+            dry_run: false,
+            ..options.clone()
+        };
+        let report = match sync_project(db, client, project_gid, &options, progress).await {
+            Ok(report) => report,
+            Err(e) => {
+                log::error!("Failed to resume sync of {}: {e}", job.entity_key);
+                SyncReport::failed(job.entity_key.clone(), e)
+            }
+        };
+        progress.on_entity_complete(&report);
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
 /// Sync a single project's tasks and metadata to the database.
 ///
 /// Attempts incremental sync via the Asana Events API first. Falls back to
@@ -245,6 +618,17 @@ pub async fn sync_project(
     project_gid: &str,
     options: &SyncOptions,
     progress: &dyn SyncProgress,
+) -> Result<SyncReport> {
+    let sync = sync_project_inner(db, client, project_gid, options, progress);
+    track_usage(db, format!("project:{project_gid}"), sync).await
+}
+
+async fn sync_project_inner(
+    db: &Database,
+    client: &asanaclient::Client,
+    project_gid: &str,
+    options: &SyncOptions,
+    progress: &dyn SyncProgress,
 ) -> Result<SyncReport> {
     // Ensure a monitored_entities row exists so sync tokens and timestamps
     // can be stored.  Portfolio-discovered projects won't have one otherwise,
@@ -260,17 +644,68 @@ pub async fn sync_project(
         })
         .await?;
 
+    // A range reaching back past what earlier syncs covered is backfilled by
+    // a full sync; incremental syncs only pick up changes since the last one
+    let backfill = match options.since_date() {
+        Some(since) if !options.full => {
+            let yesterday = chrono::Local::now().date_naive() - Duration::days(1);
+            !gap::coverage(db, &entity_key, since, yesterday)
+                .await?
+                .holes
+                .is_empty()
+        }
+        _ => false,
+    };
+
     // Try incremental sync via events
-    if !options.full {
+    let mut report = None;
+    if !options.full && !backfill {
         match sync_project_incremental(db, client, project_gid, options, progress).await {
-            Ok(Some(report)) => return Ok(report), // Incremental succeeded
-            Ok(None) => {}                         // No token or expired, fall through
+            Ok(Some(r)) => report = Some(r), // Incremental succeeded
+            Ok(None) => {}                   // No token or expired, fall through
             Err(e) => log::warn!("Incremental sync failed, falling back to full: {e}"),
         }
     }
 
     // Full sync (existing logic)
-    sync_project_full(db, client, project_gid, options, progress).await
+    let report = match report {
+        Some(report) => report,
+        None => sync_project_full(db, client, project_gid, options, progress).await?,
+    };
+
+    // Keep today's health so later metrics for this period don't drift
+    let pgid = project_gid.to_string();
+    if let Err(e) = db
+        .writer()
+        .call(move |conn| crate::metrics::health::record_health_snapshot(conn, &pgid))
+        .await
+    {
+        log::warn!("Failed to record health snapshot for project {project_gid}: {e}");
+    }
+    Ok(report)
+}
+
+/// Run one entity's sync, attaching the API usage it caused to its report.
+/// The outermost sync of a run also logs its usage for `asanadw status`.
+async fn track_usage(
+    db: &Database,
+    entity_key: String,
+    sync: impl std::future::Future<Output = Result<SyncReport>>,
+) -> Result<SyncReport> {
+    let tracked = rate_limit::track(sync).await;
+    let usage = tracked.usage;
+    if tracked.outermost && usage.requests > 0 {
+        let logged = db
+            .writer()
+            .call(move |conn| repository::insert_api_usage(conn, &entity_key, &usage))
+            .await;
+        if let Err(e) = logged {
+            log::warn!("Failed to log API usage: {e}");
+        }
+    }
+    let mut report = tracked.value?;
+    report.rate_limit = usage;
+    Ok(report)
 }
 
 /// Attempt incremental sync for a project using the Asana Events API.
@@ -283,7 +718,7 @@ async fn sync_project_incremental(
     db: &Database,
     client: &asanaclient::Client,
     project_gid: &str,
-    _options: &SyncOptions,
+    options: &SyncOptions,
     progress: &dyn SyncProgress,
 ) -> Result<Option<SyncReport>> {
     let entity_key = format!("project:{project_gid}");
@@ -302,6 +737,7 @@ async fn sync_project_incremental(
         None => {
             // No token — establish one, then signal full sync needed
             log::info!("No event sync token for {entity_key}, establishing...");
+            rate_limit::acquire().await;
             match client.events().establish(project_gid).await {
                 Ok(new_token) => {
                     db.writer()
@@ -322,6 +758,7 @@ async fn sync_project_incremental(
     };
 
     // Fetch events since the token
+    rate_limit::acquire().await;
     let events_response = match client.events().get_events(project_gid, &token).await {
         Ok(resp) => resp,
         Err(asanaclient::Error::SyncTokenExpired { sync }) => {
@@ -362,6 +799,9 @@ async fn sync_project_incremental(
             status: SyncStatus::Success,
             items_synced: 0,
             items_failed: 0,
+            skipped_duplicates: 0,
+            skipped_ranges: Vec::new(),
+            rate_limit: Default::default(),
             batches_completed: 1,
             batches_total: 1,
             error: None,
@@ -386,57 +826,101 @@ async fn sync_project_incremental(
 
     progress.on_incremental_sync(&entity_key, &summary.to_progress_summary());
 
-    // Fetch full task data for each changed task
-    let mut tasks: Vec<asanaclient::Task> = Vec::new();
+    // Fetch full task data for the changed tasks, BATCH_SIZE per /batch request.
+    // Tasks the batch couldn't return are retried with individual GETs.
+    let changed: Vec<&str> = summary
+        .changed_task_gids
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let mut fetched: Vec<TaskWithLikes> = Vec::new();
+    let mut deleted: Vec<String> = summary.deleted_task_gids.iter().cloned().collect();
     let mut fetch_failures: u64 = 0;
-    for gid in &summary.changed_task_gids {
-        let path = format!("/tasks/{gid}");
-        let query_params = [("opt_fields", PROJECT_TASK_FIELDS)];
-        match retry_api!(client.get::<asanaclient::Task>(&path, &query_params)) {
-            Ok(task) => tasks.push(task),
-            Err(crate::error::Error::Api(asanaclient::Error::NotFound(_))) => {
-                // Task was deleted — skip it for now; full sync handles cleanup
-                log::debug!("Task {gid} not found (likely deleted), skipping");
-            }
+    for chunk in changed.chunks(BATCH_SIZE) {
+        let outcomes = match retry_api!(batch_get_tasks(client, chunk, PROJECT_TASK_FIELDS)) {
+            Ok(outcomes) => outcomes,
             Err(e) => {
-                log::warn!("Failed to fetch task {gid}: {e}");
-                fetch_failures += 1;
+                log::warn!("Batch task fetch failed, falling back to individual requests: {e}");
+                chunk
+                    .iter()
+                    .map(|gid| (gid.to_string(), BatchTaskOutcome::Failed(e.to_string())))
+                    .collect()
+            }
+        };
+        for (gid, outcome) in outcomes {
+            match outcome {
+                BatchTaskOutcome::Found(task) => fetched.push(*task),
+                BatchTaskOutcome::NotFound => {
+                    log::debug!("Task {gid} not found, marking it deleted");
+                    deleted.push(gid);
+                }
+                BatchTaskOutcome::Failed(reason) => {
+                    log::debug!("Batch fetch of task {gid} failed ({reason}), retrying alone");
+                    match fetch_task(client, &gid).await {
+                        Ok(Some(task)) => fetched.push(task),
+                        Ok(None) => {
+                            log::debug!("Task {gid} not found, marking it deleted");
+                            deleted.push(gid);
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to fetch task {gid}: {e}");
+                            fetch_failures += 1;
+                        }
+                    }
+                }
             }
         }
     }
 
+    let (tasks, task_likes) = split_task_likes(fetched);
     progress.on_tasks_fetched(&entity_key, tasks.len());
 
     // Fetch comments for each changed task
-    let mut task_comments: Vec<(String, Vec<asanaclient::Story>)> = Vec::new();
-    let comments_total = tasks.len();
-    for (i, task) in tasks.iter().enumerate() {
-        progress.on_comments_progress(&entity_key, i + 1, comments_total);
-        let task_gid = task.gid.clone();
-        match retry_api!(client.tasks().comments(&task_gid)) {
-            Ok(comments) => {
-                task_comments.push((task.gid.clone(), comments));
-            }
-            Err(e) => {
-                log::warn!("Failed to fetch comments for task {}: {e}", task.gid);
-                task_comments.push((task.gid.clone(), Vec::new()));
-            }
-        }
-    }
+    let task_gids: Vec<&str> = tasks.iter().map(|t| t.gid.as_str()).collect();
+    let task_comments = fetch_task_comments(
+        client,
+        &entity_key,
+        &task_gids,
+        options.comment_concurrency,
+        progress,
+        (0, task_gids.len()),
+    )
+    .await;
 
     let total_synced = tasks.len() as u64;
 
+    // Compare with what's stored before overwriting it
+    let changes = db
+        .reader()
+        .call({
+            let tasks = tasks.clone();
+            let task_comments = task_comments.clone();
+            let deleted = deleted.clone();
+            move |conn| changes::detect_task_changes(conn, &tasks, &task_comments, &deleted)
+        })
+        .await?;
+
     // Store tasks and comments
     upsert_tasks_and_comments(db, &tasks, &task_comments).await?;
+    upsert_task_likes(db, &task_likes).await?;
+    mark_tasks_deleted(db, &entity_key, deleted, progress).await?;
+    if !changes.is_empty() {
+        progress.on_task_changes(&entity_key, &changes);
+    }
 
-    // Update sync token and timestamp
+    // Update sync token and timestamp. Caught up with every change, the
+    // project's coverage now runs through yesterday
     let new_token = events_response.sync.clone();
+    let yesterday = chrono::Local::now().date_naive() - Duration::days(1);
     db.writer()
         .call({
             let entity_key = entity_key.clone();
             move |conn| {
                 repository::set_event_sync_token(conn, &entity_key, &new_token)?;
                 repository::update_monitored_entity_sync_time(conn, &entity_key)?;
+                if fetch_failures == 0 {
+                    gap::extend_coverage(conn, &entity_key, yesterday)?;
+                }
                 Ok::<(), rusqlite::Error>(())
             }
         })
@@ -448,6 +932,9 @@ async fn sync_project_incremental(
         let sections = super::api_helpers::get_project_sections(client, project_gid).await?;
         upsert_project_metadata(db, &project, &sections).await?;
     }
+    if summary.project_changed {
+        sync_project_brief(client, db, &entity_key, project_gid).await?;
+    }
 
     // Refresh status updates if changed (non-fatal if unavailable)
     if summary.status_updates_changed {
@@ -475,6 +962,9 @@ async fn sync_project_incremental(
         status,
         items_synced: total_synced,
         items_failed: fetch_failures,
+        skipped_duplicates: 0,
+        skipped_ranges: Vec::new(),
+        rate_limit: Default::default(),
         batches_completed: 1,
         batches_total: 1,
         error: if fetch_failures > 0 {
@@ -485,13 +975,116 @@ async fn sync_project_incremental(
     }))
 }
 
+/// List a project's tasks page by page, staging each page and the next
+/// page's offset on the sync job. Rate-limit retries repeat only the current
+/// page, a job that failed mid-listing resumes from its saved offset, and one
+/// whose listing was complete reads its staged pages without listing again.
+/// The pages stay staged until the job finishes.
+async fn fetch_task_pages(
+    db: &Database,
+    source: &impl ProjectSource,
+    entity_key: &str,
+    job_id: i64,
+    resume: Option<repository::ResumableSyncJob>,
+    path: &str,
+    query_params: &[(&str, &str)],
+) -> Result<Vec<TaskWithLikes>> {
+    let (mut offset, mut page_index, listed) = match resume {
+        Some(job) if job.listing_complete => {
+            log::info!(
+                "Resuming sync job {job_id} with its {} staged pages",
+                job.pages_fetched
+            );
+            (None, job.pages_fetched, true)
+        }
+        Some(job) => {
+            log::info!(
+                "Resuming task listing for sync job {job_id} after {} pages",
+                job.pages_fetched
+            );
+            (job.page_offset, job.pages_fetched, false)
+        }
+        None => (None, 0, false),
+    };
+
+    if !listed {
+        let mut resuming = offset.is_some();
+        loop {
+            let mut query = query_params.to_vec();
+            if let Some(offset) = &offset {
+                query.push(("offset", offset.as_str()));
+            }
+            let (data, next_offset) = match source.task_page(path, &query).await {
+                Ok(page) => page,
+                Err(crate::error::Error::Api(e)) if resuming && !is_429_error(&e) => {
+                    // Asana rejects offsets it no longer recognizes
+                    log::warn!(
+                        "Saved offset for sync job {job_id} was rejected ({e}); starting over"
+                    );
+                    db.writer()
+                        .call({
+                            let entity_key = entity_key.to_string();
+                            move |conn| repository::clear_sync_job_pages(conn, &entity_key)
+                        })
+                        .await?;
+                    resuming = false;
+                    offset = None;
+                    page_index = 0;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            resuming = false;
+            let body = serde_json::to_string(&data).map_err(|e| sync_error(entity_key, e))?;
+            db.writer()
+                .call({
+                    let next_offset = next_offset.clone();
+                    move |conn| {
+                        repository::save_sync_job_page(
+                            conn,
+                            job_id,
+                            page_index,
+                            &body,
+                            next_offset.as_deref(),
+                        )
+                    }
+                })
+                .await?;
+            page_index += 1;
+            offset = next_offset;
+            if offset.is_none() {
+                break;
+            }
+        }
+    }
+
+    let pages = db
+        .writer()
+        .call(move |conn| repository::load_sync_job_pages(conn, job_id))
+        .await?;
+    let mut tasks = Vec::new();
+    for body in pages {
+        let page: Vec<TaskWithLikes> =
+            serde_json::from_str(&body).map_err(|e| sync_error(entity_key, e))?;
+        tasks.extend(page);
+    }
+    Ok(tasks)
+}
+
+fn sync_error(entity_key: &str, e: serde_json::Error) -> crate::error::Error {
+    crate::error::Error::Sync {
+        entity_key: entity_key.to_string(),
+        message: format!("invalid staged task page: {e}"),
+    }
+}
+
 /// Full sync for a project: re-fetch all tasks and comments.
 ///
 /// This is the original sync logic, used as fallback when incremental sync
 /// is not possible (first run, expired token, --full flag).
-async fn sync_project_full(
+pub(super) async fn sync_project_full(
     db: &Database,
-    client: &asanaclient::Client,
+    source: &impl ProjectSource,
     project_gid: &str,
     options: &SyncOptions,
     progress: &dyn SyncProgress,
@@ -508,21 +1101,55 @@ async fn sync_project_full(
         .await?;
 
     // Fetch and store project metadata + sections
-    let project = retry_api!(client.projects().get_full(project_gid))?;
-    let sections = super::api_helpers::get_project_sections(client, project_gid).await?;
+    let project = source.project(project_gid).await?;
+    let sections = source.sections(project_gid).await?;
     upsert_project_metadata(db, &project, &sections).await?;
 
-    // Create sync job record
+    // Asana lists a project's tasks completed since a date, with no upper
+    // bound, so the listing starts at the first day earlier syncs haven't
+    // covered. Covered days before it are skipped; --full fetches them all.
     let today = chrono::Local::now().date_naive();
-    let since = options.since_date().unwrap_or(today - Duration::days(90));
-    let job_id = db
+    let requested_since = sync_since(db, &entity_key, options, today).await?;
+    let (since, skipped_ranges) = if options.full {
+        (requested_since, Vec::new())
+    } else {
+        let coverage = gap::coverage(db, &entity_key, requested_since, today).await?;
+        let since = coverage.holes.first().map_or(today, |hole| hole.start);
+        let skipped: Vec<gap::DateRange> = coverage
+            .covered
+            .into_iter()
+            .filter(|range| range.end < since)
+            .collect();
+        (since, skipped)
+    };
+
+    // Create a sync job record, or pick up one whose task listing stopped
+    // partway through
+    let (job_id, resume) = db
         .writer()
         .call({
             let entity_key = entity_key.clone();
             let range_start = since.format("%Y-%m-%d").to_string();
             let range_end = today.format("%Y-%m-%d").to_string();
             move |conn| {
-                repository::insert_sync_job(conn, &entity_key, Some(&range_start), Some(&range_end))
+                let resumable = repository::find_resumable_sync_job(
+                    conn,
+                    &entity_key,
+                    &range_start,
+                    RESUME_WINDOW_HOURS,
+                )?;
+                if let Some(job) = resumable {
+                    repository::resume_sync_job(conn, job.job_id)?;
+                    return Ok((job.job_id, Some(job)));
+                }
+                repository::clear_sync_job_pages(conn, &entity_key)?;
+                let job_id = repository::insert_sync_job(
+                    conn,
+                    &entity_key,
+                    Some(&range_start),
+                    Some(&range_end),
+                )?;
+                Ok::<_, rusqlite::Error>((job_id, None))
             }
         })
         .await?;
@@ -535,7 +1162,31 @@ async fn sync_project_full(
         ("opt_fields", PROJECT_TASK_FIELDS),
         ("completed_since", completed_since.as_str()),
     ];
-    let tasks: Vec<asanaclient::Task> = retry_api!(client.get_all(&path, &query_params))?;
+    let comments_cursor = resume.as_ref().and_then(|job| job.comments_cursor.clone());
+    let fetched = fetch_task_pages(
+        db,
+        source,
+        &entity_key,
+        job_id,
+        resume,
+        &path,
+        &query_params,
+    )
+    .await;
+    let fetched = match fetched {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            // Leave the staged pages and offset for the next run to resume from
+            let message = e.to_string();
+            db.writer()
+                .call(move |conn| {
+                    repository::update_sync_job(conn, job_id, "failed", 0, 0, 0, 1, Some(&message))
+                })
+                .await?;
+            return Err(e);
+        }
+    };
+    let (tasks, task_likes) = split_task_likes(fetched);
 
     progress.on_tasks_fetched(&entity_key, tasks.len());
 
@@ -557,29 +1208,46 @@ async fn sync_project_full(
         progress.on_comments_skipped(&entity_key, skipped, total_tasks);
     }
 
-    let mut task_comments: Vec<(String, Vec<asanaclient::Story>)> = Vec::new();
-    let comments_total = tasks_needing_comments.len();
-    for (i, task) in tasks_needing_comments.iter().enumerate() {
-        progress.on_comments_progress(&entity_key, i + 1, comments_total);
-        let task_gid = task.gid.clone();
-        match retry_api!(client.tasks().comments(&task_gid)) {
-            Ok(comments) => {
-                task_comments.push((task.gid.clone(), comments));
-            }
-            Err(e) => {
-                log::warn!("Failed to fetch comments for task {}: {e}", task.gid);
-                task_comments.push((task.gid.clone(), Vec::new()));
-            }
+    let mut task_gids: Vec<&str> = tasks_needing_comments
+        .iter()
+        .map(|t| t.gid.as_str())
+        .collect();
+    // An interrupted run already stored comments up to its cursor
+    if let Some(cursor) = &comments_cursor {
+        if let Some(pos) = task_gids.iter().position(|gid| gid == cursor) {
+            task_gids.drain(..=pos);
         }
     }
 
     let total_synced = tasks.len() as u64;
 
-    // Store tasks and comments
-    upsert_tasks_and_comments(db, &tasks, &task_comments).await?;
+    // Store tasks first, then comments a chunk at a time, checkpointing the
+    // last task whose comments are stored so a restart can pick up from there
+    upsert_tasks_and_comments(db, &tasks, &[]).await?;
+    upsert_task_likes(db, &task_likes).await?;
+    let listed: HashSet<&str> = tasks.iter().map(|t| t.gid.as_str()).collect();
+    let missing = tasks_missing_from_listing(db, source, project_gid, since, &listed).await?;
+    mark_tasks_deleted(db, &entity_key, missing, progress).await?;
+    let total_comment_tasks = task_gids.len();
+    for (i, chunk) in task_gids.chunks(COMMENT_CHECKPOINT_TASKS).enumerate() {
+        let task_comments = fetch_task_comments(
+            source,
+            &entity_key,
+            chunk,
+            options.comment_concurrency,
+            progress,
+            (i * COMMENT_CHECKPOINT_TASKS, total_comment_tasks),
+        )
+        .await;
+        upsert_tasks_and_comments(db, &[], &task_comments).await?;
+        let cursor = chunk[chunk.len() - 1].to_string();
+        db.writer()
+            .call(move |conn| repository::set_sync_job_comments_cursor(conn, job_id, &cursor))
+            .await?;
+    }
 
     // Fetch and store status updates for the project (non-fatal if unavailable)
-    match retry_api!(client.projects().status_updates(project_gid)) {
+    match source.status_updates(project_gid).await {
         Ok(statuses) => {
             progress.on_status_updates_synced(&entity_key, statuses.len());
             upsert_status_updates(db, project_gid, "project", &statuses).await?;
@@ -588,6 +1256,7 @@ async fn sync_project_full(
             log::warn!("Failed to fetch status updates for {entity_key}: {e}");
         }
     }
+    sync_project_brief(source, db, &entity_key, project_gid).await?;
 
     let status = if total_synced > 0 || tasks.is_empty() {
         SyncStatus::Success
@@ -600,6 +1269,7 @@ async fn sync_project_full(
         SyncStatus::Failed => "failed",
     }
     .to_string();
+    let covered = status == SyncStatus::Success;
 
     // Update sync job
     db.writer()
@@ -616,14 +1286,19 @@ async fn sync_project_full(
                     1,
                     None,
                 )?;
+                repository::clear_sync_job_pages(conn, &entity_key)?;
                 repository::update_monitored_entity_sync_time(conn, &entity_key)?;
+                if covered {
+                    // Today isn't over, so coverage stops at yesterday
+                    gap::record_coverage(conn, &entity_key, since, today - Duration::days(1))?;
+                }
                 Ok::<(), rusqlite::Error>(())
             }
         })
         .await?;
 
     // Establish a fresh event sync token so the next sync can be incremental
-    match client.events().establish(project_gid).await {
+    match source.establish_events(project_gid).await {
         Ok(new_token) => {
             db.writer()
                 .call({
@@ -642,6 +1317,9 @@ async fn sync_project_full(
         status,
         items_synced: total_synced,
         items_failed: 0,
+        skipped_duplicates: 0,
+        skipped_ranges,
+        rate_limit: Default::default(),
         batches_completed: 1,
         batches_total: 1,
         error: None,
@@ -656,10 +1334,22 @@ pub async fn sync_user(
     user_gid: &str,
     options: &SyncOptions,
     progress: &dyn SyncProgress,
+) -> Result<SyncReport> {
+    let sync = sync_user_inner(db, client, workspace_gid, user_gid, options, progress);
+    track_usage(db, format!("user:{user_gid}"), sync).await
+}
+
+async fn sync_user_inner(
+    db: &Database,
+    client: &asanaclient::Client,
+    workspace_gid: &str,
+    user_gid: &str,
+    options: &SyncOptions,
+    progress: &dyn SyncProgress,
 ) -> Result<SyncReport> {
     let entity_key = format!("user:{user_gid}");
     let today = chrono::Local::now().date_naive();
-    let since = options.since_date().unwrap_or(today - Duration::days(90));
+    let since = sync_since(db, &entity_key, options, today).await?;
 
     let modified_since = format!("{}T00:00:00Z", since);
 
@@ -713,20 +1403,46 @@ pub async fn sync_user(
         status: SyncStatus::Success,
         items_synced: task_count,
         items_failed: 0,
+        skipped_duplicates: 0,
+        skipped_ranges: Vec::new(),
+        rate_limit: Default::default(),
         batches_completed: 1,
         batches_total: 1,
         error: None,
     })
 }
 
-/// Sync a team: fetch members, projects, and sync each project.
+/// Sync a team: fetch members, projects, and sync each project not
+/// already in `synced`.
 pub async fn sync_team(
+    db: &Database,
+    client: &asanaclient::Client,
+    workspace_gid: &str,
+    team_gid: &str,
+    options: &SyncOptions,
+    progress: &dyn SyncProgress,
+    synced: &SyncedProjects,
+) -> Result<SyncReport> {
+    let sync = sync_team_inner(
+        db,
+        client,
+        workspace_gid,
+        team_gid,
+        options,
+        progress,
+        synced,
+    );
+    track_usage(db, format!("team:{team_gid}"), sync).await
+}
+
+async fn sync_team_inner(
     db: &Database,
     client: &asanaclient::Client,
     _workspace_gid: &str,
     team_gid: &str,
     options: &SyncOptions,
     progress: &dyn SyncProgress,
+    synced: &SyncedProjects,
 ) -> Result<SyncReport> {
     let entity_key = format!("team:{team_gid}");
 
@@ -751,13 +1467,23 @@ pub async fn sync_team(
     let projects = super::api_helpers::get_team_projects(client, team_gid).await?;
     let mut total_synced: u64 = 0;
     let mut total_failed: u64 = 0;
+    let mut skipped_duplicates: u64 = 0;
     let total = projects.len() as u32;
 
     for project_ref in &projects {
         if project_ref.archived {
             continue;
         }
-        match sync_project(db, client, &project_ref.gid, options, progress).await {
+        let outcome = synced
+            .sync_once(
+                &project_ref.gid,
+                sync_project(db, client, &project_ref.gid, options, progress),
+            )
+            .await;
+        if outcome.shared {
+            skipped_duplicates += 1;
+        }
+        match outcome.result {
             Ok(report) => {
                 total_synced += report.items_synced;
             }
@@ -772,8 +1498,87 @@ pub async fn sync_team(
         }
     }
 
+    Ok(SyncReport {
+        skipped_duplicates,
+        ..SyncReport::from_counts(
+            entity_key,
+            total_synced,
+            total_failed,
+            total.saturating_sub(total_failed as u32),
+            total,
+        )
+    })
+}
+
+/// Sync the workspace's team directory: every team into `dim_teams` and its
+/// members into `bridge_team_members`, without syncing any team's projects.
+/// Each team is one item; a team whose members can't be fetched counts as
+/// failed and keeps its previous memberships.
+pub async fn sync_team_directory(
+    db: &Database,
+    client: &asanaclient::Client,
+    workspace_gid: &str,
+) -> Result<SyncReport> {
+    let sync = sync_team_directory_inner(db, client, workspace_gid);
+    track_usage(db, "teams".to_string(), sync).await
+}
+
+async fn sync_team_directory_inner(
+    db: &Database,
+    client: &asanaclient::Client,
+    workspace_gid: &str,
+) -> Result<SyncReport> {
+    let teams = super::api_helpers::get_workspace_teams(client, workspace_gid).await?;
+    let total = teams.len() as u32;
+    let mut total_synced: u64 = 0;
+    let mut total_failed: u64 = 0;
+
+    for team in teams {
+        let members = match super::api_helpers::get_team_members(client, &team.gid).await {
+            Ok(members) => Some(members),
+            Err(e) => {
+                log::error!(
+                    "Failed to fetch members of team {} ({}): {e}",
+                    team.name,
+                    team.gid
+                );
+                total_failed += 1;
+                None
+            }
+        };
+        let workspace_gid = workspace_gid.to_string();
+        let synced_members = members.is_some();
+        db.writer()
+            .call(move |conn| {
+                repository::upsert_team(
+                    conn,
+                    &team.gid,
+                    &team.name,
+                    &workspace_gid,
+                    team.description.as_deref(),
+                )?;
+                if let Some(members) = members {
+                    for member in &members {
+                        repository::upsert_user_minimal_with_email(
+                            conn,
+                            &member.gid,
+                            member.name.as_deref(),
+                            member.email.as_deref(),
+                        )?;
+                    }
+                    let user_gids: Vec<String> = members.into_iter().map(|m| m.gid).collect();
+                    repository::replace_team_members(conn, &team.gid, &user_gids)?;
+                }
+                Ok::<(), rusqlite::Error>(())
+            })
+            .await?;
+        if synced_members {
+            total_synced += 1;
+        }
+    }
+
     Ok(SyncReport::from_counts(
-        entity_key,
+        "teams".to_string(),
         total_synced,
         total_failed,
         total.saturating_sub(total_failed as u32),
@@ -781,27 +1586,28 @@ pub async fn sync_team(
     ))
 }
 
-/// Maximum nesting depth for recursive portfolio sync. The root portfolio
-/// is at depth 0, so a limit of 5 allows up to 6 total hierarchy levels.
-const MAX_PORTFOLIO_DEPTH: u32 = 5;
-
 /// Sync a portfolio and all its contents (projects and nested sub-portfolios).
 ///
-/// Recursively descends into child portfolios up to 5 nesting levels
-/// (6 total hierarchy levels). Each child portfolio's metadata, status
-/// updates, and contained projects are synced. Sub-portfolios that exceed
-/// the depth limit are skipped with a warning.
+/// Recursively descends into child portfolios up to
+/// `options.max_portfolio_depth` nesting levels. Each child portfolio's
+/// metadata, status updates, and contained projects are synced. Deeper
+/// sub-portfolios are skipped, as are portfolios that contain one of their
+/// own ancestors, which would otherwise loop. Projects already in `synced`
+/// are linked to the portfolio without being synced again.
 pub async fn sync_portfolio(
     db: &Database,
     client: &asanaclient::Client,
     portfolio_gid: &str,
     options: &SyncOptions,
     progress: &dyn SyncProgress,
+    synced: &SyncedProjects,
 ) -> Result<SyncReport> {
-    sync_portfolio_recursive(db, client, portfolio_gid, options, progress, 0).await
+    let sync = sync_portfolio_recursive(db, client, portfolio_gid, options, progress, synced, &[]);
+    track_usage(db, format!("portfolio:{portfolio_gid}"), sync).await
 }
 
-/// Recursive implementation of portfolio sync with depth tracking.
+/// Recursive implementation of portfolio sync. `ancestors` holds the
+/// portfolios above this one, root first, so its length is the depth.
 /// Uses a boxed future to allow async recursion.
 fn sync_portfolio_recursive<'a>(
     db: &'a Database,
@@ -809,27 +1615,13 @@ fn sync_portfolio_recursive<'a>(
     portfolio_gid: &'a str,
     options: &'a SyncOptions,
     progress: &'a dyn SyncProgress,
-    depth: u32,
+    synced: &'a SyncedProjects,
+    ancestors: &'a [String],
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<SyncReport>> + Send + 'a>> {
     Box::pin(async move {
         let entity_key = format!("portfolio:{portfolio_gid}");
-
-        if depth > MAX_PORTFOLIO_DEPTH {
-            log::warn!(
-            "Portfolio {portfolio_gid} exceeds max nesting depth ({MAX_PORTFOLIO_DEPTH}), skipping"
-        );
-            return Ok(SyncReport {
-                entity_key,
-                status: SyncStatus::Failed,
-                items_synced: 0,
-                items_failed: 0,
-                batches_completed: 0,
-                batches_total: 0,
-                error: Some(format!(
-                    "exceeded max portfolio nesting depth ({MAX_PORTFOLIO_DEPTH})"
-                )),
-            });
-        }
+        let mut path = ancestors.to_vec();
+        path.push(portfolio_gid.to_string());
 
         let portfolio = retry_api!(client.portfolios().get(portfolio_gid))?;
         db.writer()
@@ -862,6 +1654,7 @@ fn sync_portfolio_recursive<'a>(
 
         let mut total_synced: u64 = 0;
         let mut total_failed: u64 = 0;
+        let mut skipped_duplicates: u64 = 0;
         let mut child_count: u32 = 0;
 
         for item in &items {
@@ -871,7 +1664,13 @@ fn sync_portfolio_recursive<'a>(
             match resource_type {
                 "project" => {
                     child_count += 1;
-                    match sync_project(db, client, gid, options, progress).await {
+                    let outcome = synced
+                        .sync_once(gid, sync_project(db, client, gid, options, progress))
+                        .await;
+                    if outcome.shared {
+                        skipped_duplicates += 1;
+                    }
+                    match outcome.result {
                         Ok(report) => {
                             total_synced += report.items_synced;
                             db.writer()
@@ -895,13 +1694,29 @@ fn sync_portfolio_recursive<'a>(
                     }
                 }
                 "portfolio" => {
+                    if path.contains(gid) {
+                        log::warn!(
+                            "Portfolio {portfolio_gid} contains its ancestor {gid}, not descending"
+                        );
+                        continue;
+                    }
+                    if path.len() as u32 > options.max_portfolio_depth {
+                        log::warn!(
+                            "Sub-portfolio {gid} is deeper than {} levels, skipping",
+                            options.max_portfolio_depth
+                        );
+                        continue;
+                    }
                     child_count += 1;
-                    match sync_portfolio_recursive(db, client, gid, options, progress, depth + 1)
-                        .await
+                    match sync_portfolio_recursive(
+                        db, client, gid, options, progress, synced, &path,
+                    )
+                    .await
                     {
                         Ok(report) => {
                             total_synced += report.items_synced;
                             total_failed += report.items_failed;
+                            skipped_duplicates += report.skipped_duplicates;
                             db.writer()
                                 .call({
                                     let parent_gid = portfolio_gid.to_string();
@@ -926,12 +1741,41 @@ fn sync_portfolio_recursive<'a>(
             }
         }
 
-        Ok(SyncReport::from_counts(
-            entity_key,
-            total_synced,
-            total_failed,
-            child_count.saturating_sub(total_failed as u32),
-            child_count,
-        ))
+        Ok(SyncReport {
+            skipped_duplicates,
+            ..SyncReport::from_counts(
+                entity_key,
+                total_synced,
+                total_failed,
+                child_count.saturating_sub(total_failed as u32),
+                child_count,
+            )
+        })
     })
 }
+
+/// Refresh `dim_users.is_active` from the workspace membership list.
+///
+/// Only users already present in the warehouse are updated. Returns the
+/// number of known users that are deactivated.
+pub async fn refresh_user_activity(
+    db: &Database,
+    client: &asanaclient::Client,
+    workspace_gid: &str,
+) -> Result<usize> {
+    let memberships = super::api_helpers::get_workspace_memberships(client, workspace_gid).await?;
+
+    let inactive = db
+        .writer()
+        .call(move |conn| {
+            let mut inactive = 0;
+            for m in &memberships {
+                if repository::set_user_active(conn, &m.user.gid, m.is_active)? && !m.is_active {
+                    inactive += 1;
+                }
+            }
+            Ok::<usize, rusqlite::Error>(inactive)
+        })
+        .await?;
+    Ok(inactive)
+}
@@ -0,0 +1,52 @@
+//! `asanadw watch`: poll one project's events and sync each batch of
+//! changes as it arrives.
+
+use std::future::Future;
+use std::time::Duration;
+
+use super::{SyncOptions, SyncProgress};
+use crate::error::Result;
+use crate::AsanaDW;
+
+/// Seconds between polls when not otherwise configured.
+pub const DEFAULT_WATCH_INTERVAL_SECS: u64 = 30;
+
+/// Sync `project` every `interval` until `shutdown` resolves. Each poll is
+/// an incremental sync from the stored event token, so changes reach
+/// `progress` through [`SyncProgress::on_task_changes`]; the first poll runs
+/// a full sync if the project has no token yet. If the first poll fails the
+/// error is returned; later failures are logged and retried on the next poll.
+pub async fn watch_project<F>(
+    dw: &AsanaDW,
+    project: &str,
+    options: &SyncOptions,
+    interval: Duration,
+    progress: &dyn SyncProgress,
+    shutdown: F,
+) -> Result<()>
+where
+    F: Future<Output = ()>,
+{
+    let options = SyncOptions {
+        full: false,
+        dry_run: false,
+        ..options.clone()
+    };
+    tokio::pin!(shutdown);
+
+    let mut first = true;
+    loop {
+        match dw.sync_project(project, &options, progress).await {
+            Ok(_) => {}
+            // A project that can't be synced at all is a mistake, not a blip
+            Err(e) if first => return Err(e),
+            Err(e) => log::warn!("Watch poll of {project} failed: {e}"),
+        }
+        first = false;
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+    Ok(())
+}
@@ -145,11 +145,74 @@ pub fn generate_asana_url(entity_type: &str, gid: &str) -> String {
     }
 }
 
+/// Generate a `/1/` format URL for a task, project, or portfolio in a
+/// workspace, the format Asana's own permalinks use. Returns `None` for
+/// other entity types or a non-numeric workspace.
+pub fn generate_workspace_url(workspace_gid: &str, entity_type: &str, gid: &str) -> Option<String> {
+    if !is_gid(workspace_gid) || !matches!(entity_type, "task" | "project" | "portfolio") {
+        return None;
+    }
+    Some(format!(
+        "https://app.asana.com/1/{workspace_gid}/{entity_type}/{gid}"
+    ))
+}
+
+/// Generate a deep link to one comment on a task.
+///
+/// With a workspace GID this uses the `/1/` format, which opens the task with
+/// the comment focused; without one it falls back to the legacy
+/// `/0/0/<task>/<story>` link.
+pub fn generate_comment_url(
+    workspace_gid: Option<&str>,
+    task_gid: &str,
+    comment_gid: &str,
+) -> String {
+    match workspace_gid.filter(|ws| is_gid(ws)) {
+        Some(ws) => {
+            format!("https://app.asana.com/1/{ws}/task/{task_gid}/comment/{comment_gid}?focus=true")
+        }
+        None => format!("https://app.asana.com/0/0/{task_gid}/{comment_gid}"),
+    }
+}
+
 /// Check if a string looks like an Asana GID (all digits).
 pub fn is_gid(s: &str) -> bool {
     !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
 }
 
+/// Extract task GIDs from a list of task URLs or GIDs, one per line, as
+/// pasted from chat. Blank lines and `#` comments are skipped, Slack's
+/// `<url|label>` link markup is unwrapped, and repeats are dropped.
+pub fn task_gids_from_list(text: &str) -> Result<Vec<String>> {
+    let mut gids: Vec<String> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let link = line.trim_start_matches('<');
+        let link = link.split(['|', '>']).next().unwrap_or(link).trim();
+        let gid = if is_gid(link) {
+            link.to_string()
+        } else {
+            match parse_asana_url(link) {
+                Ok(AsanaUrlInfo::Task { task_gid, .. }) => task_gid,
+                Ok(_) => {
+                    return Err(Error::UrlParse(format!(
+                        "line {}: not a task URL: {line}",
+                        i + 1
+                    )))
+                }
+                Err(e) => return Err(Error::UrlParse(format!("line {}: {e}", i + 1))),
+            }
+        };
+        if !gids.contains(&gid) {
+            gids.push(gid);
+        }
+    }
+    Ok(gids)
+}
+
 /// Extract a GID from either a raw GID or an Asana URL.
 /// Returns the GID string.
 pub fn resolve_gid(input: &str) -> Result<String> {
@@ -272,6 +335,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_generate_comment_url() {
+        let url = generate_comment_url(Some("111"), "222", "333");
+        assert_eq!(
+            url,
+            "https://app.asana.com/1/111/task/222/comment/333?focus=true"
+        );
+        assert_eq!(
+            resolve_gid(&url).unwrap(),
+            "222",
+            "comment links still resolve to their task"
+        );
+        assert_eq!(
+            generate_comment_url(None, "222", "333"),
+            "https://app.asana.com/0/0/222/333"
+        );
+    }
+
+    #[test]
+    fn test_generate_workspace_url() {
+        let url = generate_workspace_url("111", "task", "222").unwrap();
+        assert_eq!(url, "https://app.asana.com/1/111/task/222");
+        assert_eq!(resolve_gid(&url).unwrap(), "222");
+        assert_eq!(
+            parse_asana_url(&generate_workspace_url("111", "portfolio", "333").unwrap()).unwrap(),
+            AsanaUrlInfo::Portfolio {
+                portfolio_gid: "333".to_string()
+            }
+        );
+        assert_eq!(generate_workspace_url("", "task", "222"), None);
+        assert_eq!(generate_workspace_url("111", "team", "222"), None);
+    }
+
     #[test]
     fn test_not_asana_url() {
         assert!(parse_asana_url("https://google.com/foo").is_err());
@@ -296,6 +392,21 @@ mod tests {
         assert_eq!(resolve_gid("user@example.com").unwrap(), "user@example.com");
     }
 
+    #[test]
+    fn test_task_gids_from_list() {
+        let list = "# from #launch\n\
+            https://app.asana.com/0/111/222\n\
+            \n\
+            <https://app.asana.com/1/999/task/333?focus=true|Ship it>\n\
+            444\n\
+            222\n";
+        assert_eq!(task_gids_from_list(list).unwrap(), ["222", "333", "444"]);
+
+        let err = task_gids_from_list("444\nhttps://app.asana.com/0/111/list").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+        assert!(task_gids_from_list("not a link").is_err());
+    }
+
     #[test]
     fn test_is_gid() {
         assert!(is_gid("1234567890"));
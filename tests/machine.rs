@@ -0,0 +1,89 @@
+//! `--machine` output of the JSON-producing commands, run against a seeded
+//! database: every JSON document, and every JSON line, carries the schema
+//! version.
+
+use std::path::Path;
+use std::process::Command;
+
+async fn seed(path: &Path) {
+    let db = asanadw::Database::open_at(path).await.unwrap();
+    db.writer()
+        .call(|conn| {
+            conn.execute_batch(
+                "INSERT INTO dim_users (user_gid, name, cached_at)
+                 VALUES ('u1', 'Ada', datetime('now')), ('u2', 'Grace', datetime('now'));
+                 INSERT INTO dim_projects (project_gid, name, owner_gid, workspace_gid, cached_at)
+                 VALUES ('1001', 'Launch', 'u1', 'w1', datetime('now'));
+                 INSERT INTO fact_tasks (task_gid, name, assignee_gid, is_completed, completed_at, completed_date_key, days_to_complete, created_at, created_date_key, modified_at, cached_at)
+                 VALUES ('t1', 'Plan', 'u1', 1, '2024-03-02T10:00:00Z', '2024-03-02', 11, '2024-02-20T10:00:00Z', '2024-02-20', '2024-03-02T10:00:00Z', datetime('now')),
+                        ('t2', 'Build', 'u2', 0, NULL, NULL, NULL, '2024-03-01T10:00:00Z', '2024-03-01', '2024-03-01T10:00:00Z', datetime('now'));
+                 INSERT INTO bridge_task_projects (task_gid, project_gid)
+                 VALUES ('t1', '1001'), ('t2', '1001');
+                 INSERT INTO fact_comments (comment_gid, task_gid, author_gid, text, story_type, created_at, created_date_key, cached_at)
+                 VALUES ('c1', 't1', 'u2', 'Looks good', 'comment', '2024-03-02T12:00:00Z', '2024-03-02', datetime('now'));",
+            )?;
+            Ok::<(), rusqlite::Error>(())
+        })
+        .await
+        .unwrap();
+}
+
+/// Run the CLI under `--machine` and return its stdout.
+fn machine(db: &Path, home: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_asanadw"))
+        .arg("--machine")
+        .arg("--db")
+        .arg(db)
+        .args(args)
+        .env("HOME", home)
+        .env_remove("ASANA_TOKEN")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn assert_envelope(args: &[&str], json: &str) -> serde_json::Value {
+    let value: serde_json::Value =
+        serde_json::from_str(json).unwrap_or_else(|e| panic!("{args:?}: {e}\n{json}"));
+    assert_eq!(
+        value["schema_version"],
+        asanadw::JSON_SCHEMA_VERSION,
+        "{args:?}: {json}"
+    );
+    value["data"].clone()
+}
+
+#[tokio::test]
+async fn test_machine_json_outputs_carry_schema_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("asanadw.db");
+    seed(&db).await;
+
+    let documents: &[&[&str]] = &[
+        &["query", "--project", "1001", "--json"],
+        &["sql", "SELECT task_gid FROM fact_tasks", "--json"],
+        &[
+            "metrics", "project", "1001", "--period", "2024-03", "--json",
+        ],
+        &[
+            "report", "project", "1001", "--period", "2024-03", "--format", "json",
+        ],
+        &["export", "graph", "--project", "1001", "--format", "json"],
+    ];
+    for args in documents {
+        let data = assert_envelope(args, &machine(&db, dir.path(), args));
+        assert!(!data.is_null(), "{args:?}");
+    }
+
+    let args: &[&str] = &["query", "--project", "1001", "--jsonl"];
+    let lines = machine(&db, dir.path(), args);
+    assert_eq!(lines.lines().count(), 2);
+    for line in lines.lines() {
+        assert!(assert_envelope(args, line)["task_gid"].is_string());
+    }
+}